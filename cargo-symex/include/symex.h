@@ -0,0 +1,51 @@
+/*
+ * Functions for C programs analyzed with `cargo symex c`, the counterparts of
+ * `symbolic`, `assume` and `suppress_path` in `symex_lib`.
+ *
+ * When the program is run as LLVM bitcode the calls are replaced by the
+ * executor. When it is run as an ELF file `symex_symbolic` and
+ * `symex_suppress_path` are hooked at their addresses, which are found from
+ * the debug information, and `symex_assume` runs its body.
+ *
+ * The definitions are weak so the header can be included in every source file
+ * of a program.
+ */
+#ifndef SYMEX_H
+#define SYMEX_H
+
+#include <stddef.h>
+
+#define SYMEX_FUNCTION __attribute__((weak, noinline, used))
+
+/*
+ * Suppresses this path from the analysis result.
+ */
+SYMEX_FUNCTION void symex_suppress_path(void) {
+    __asm__ volatile("" ::: "memory");
+}
+
+/*
+ * Creates a new unconstrained value for the `size` bytes at `value`.
+ */
+SYMEX_FUNCTION void symex_symbolic(void *value, size_t size) {
+    __asm__ volatile("" : : "r"(value), "r"(size) : "memory");
+}
+
+/*
+ * Adds a constraint that `condition` is non zero, the path is suppressed if
+ * it can never be.
+ */
+SYMEX_FUNCTION void symex_assume(int condition) {
+    if (!condition) {
+        symex_suppress_path();
+    }
+}
+
+#ifndef SYMEX_NO_SHORT_NAMES
+/* Makes the variable `x` symbolic. */
+#define symbolic(x) symex_symbolic(&(x), sizeof(x))
+#define assume(condition) symex_assume(condition)
+#define suppress_path() symex_suppress_path()
+#endif
+
+#endif
@@ -49,6 +49,69 @@ pub struct Args {
     #[clap(short, long)]
     pub function: Option<String>,
 
+    /// Path to a target description (toml) used for cycle counting when running elf files.
+    #[clap(long)]
+    pub target_config: Option<PathBuf>,
+
+    /// Merge paths where they rejoin when running elf files, the reported cycle count is the
+    /// worst case of the merged paths.
+    #[clap(long)]
+    pub merge_paths: bool,
+
+    /// Run a response time analysis of an RTIC application, the file (toml) gives the period or
+    /// minimum inter-arrival time in cycles of each task.
+    #[clap(long, conflicts_with = "function")]
+    pub rtic: Option<PathBuf>,
+
+    /// Resume the analysis of an elf file from a snapshot (toml) of the registers and memory
+    /// instead of starting at a function.
+    #[clap(
+        long,
+        conflicts_with = "function",
+        conflicts_with = "rtic",
+        conflicts_with = "format"
+    )]
+    pub snapshot: Option<PathBuf>,
+
+    /// Format of the file given by path when it is not an elf file, one of bin, ihex or srec.
+    #[clap(long, requires = "path", requires = "arch")]
+    pub format: Option<String>,
+
+    /// Address a raw binary is loaded at.
+    #[clap(long, requires = "format")]
+    pub base: Option<String>,
+
+    /// Architecture of a binary image, one of armv6-m, armv7e-m, armv8-m.base, armv8-m.main,
+    /// rv32imac or aarch64.
+    #[clap(long, requires = "format")]
+    pub arch: Option<String>,
+
+    /// Symbols of a binary image, a linker map file or a list of `name = address` lines.
+    #[clap(long, requires = "format")]
+    pub symbols: Option<PathBuf>,
+
+    /// The binary image is big-endian.
+    #[clap(long, requires = "format")]
+    pub big_endian: bool,
+
+    /// Replay each path of an elf file with concrete inputs and write the executed instructions
+    /// to the file, `-` writes to stdout.
+    #[clap(
+        long,
+        conflicts_with = "merge_paths",
+        conflicts_with = "rtic",
+        conflicts_with = "format"
+    )]
+    pub trace: Option<PathBuf>,
+
+    /// Format of the trace, one of text or jsonl.
+    #[clap(long, requires = "trace")]
+    pub trace_format: Option<String>,
+
+    /// Only trace paths that ended in a failure.
+    #[clap(long, requires = "trace")]
+    pub trace_failed: bool,
+
     #[clap(subcommand)]
     pub subcommand: Option<Subcommands>,
 
@@ -64,11 +127,31 @@ pub enum Subcommands {
 
 #[derive(Parser, Debug)]
 pub struct ClangArgs {
-    /// Path to c-file to build.
-    #[clap(name = "File")]
-    pub path: PathBuf,
+    /// Paths to the c-files to build.
+    #[clap(name = "File", required = true)]
+    pub paths: Vec<PathBuf>,
 
     /// Name of function to run.
     #[clap(long, short)]
     pub function: Option<String>,
+
+    /// Directory to search for headers, `symex.h` is always available.
+    #[clap(short = 'I', long = "include")]
+    pub include_dirs: Vec<PathBuf>,
+
+    /// Macro to define, as `NAME` or `NAME=VALUE`.
+    #[clap(short = 'D', long = "define")]
+    pub defines: Vec<String>,
+
+    /// Cross compile to an elf file and analyze the machine code instead of the bitcode.
+    #[clap(long)]
+    pub elf: bool,
+
+    /// Target triple used with --elf.
+    #[clap(long, requires = "elf", default_value = "thumbv7em-none-eabi")]
+    pub target: String,
+
+    /// Path to a target description (toml) used for cycle counting with --elf.
+    #[clap(long, requires = "elf")]
+    pub target_config: Option<PathBuf>,
 }
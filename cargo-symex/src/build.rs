@@ -4,9 +4,12 @@ use cargo_project::{Artifact, Profile, Project};
 use log::debug;
 #[cfg(feature = "llvm")]
 use regex::Regex;
+#[cfg(feature = "llvm")]
+use serde::Deserialize;
 use std::env;
 #[cfg(feature = "llvm")]
 use std::{
+    collections::{BTreeSet, HashMap, HashSet},
     fs,
     path::{Path, PathBuf},
     process::Command,
@@ -24,6 +27,10 @@ pub struct Settings {
 
     /// Build in release.
     pub release: bool,
+
+    /// Embed bitcode in the output and link using LTO.
+    #[cfg(feature = "llvm")]
+    pub embed_bitcode: bool,
 }
 
 impl Settings {
@@ -73,6 +80,14 @@ impl Settings {
         Ok(name)
     }
 
+    /// Returns the name of the package in the current directory.
+    #[cfg(feature = "llvm")]
+    pub fn get_package_name(&self) -> Result<String> {
+        let cwd = env::current_dir()?;
+        let project = Project::query(cwd).map_err(|err| anyhow!(err.to_string()))?;
+        Ok(project.name().to_string())
+    }
+
     /// Returns the name of the module.
     #[cfg(feature = "llvm")]
     pub fn get_module_name(&self) -> Result<String> {
@@ -137,9 +152,17 @@ pub fn generate_build_command(opts: &Settings) -> Command {
     let mut cargo = Command::new("cargo");
     cargo.args(&["rustc", "--verbose", "--color=always"]);
 
+    // The artifacts of the build are used to find the .bc files of the
+    // dependencies, see `get_dependency_bcs`.
+    cargo.arg("--message-format=json-diagnostic-rendered-ansi");
+
+    // Dependencies are only built with the flags in `RUSTFLAGS`, so ask for
+    // bitcode there as well to be able to link them into the analysis.
+    let mut rustflags = vec!["--emit=llvm-bc"];
     if opts.embed_bitcode {
-        cargo.env("RUSTFLAGS", "-C embed-bitcode=yes");
+        rustflags.extend(["-C", "embed-bitcode=yes"]);
     }
+    cargo.env("RUSTFLAGS", rustflags.join(" "));
 
     match &opts.features {
         Features::None => {}
@@ -236,3 +259,210 @@ pub fn get_latest_bc(dir: impl AsRef<Path>, file_prefix: &str) -> Result<Option<
 
     Ok(result)
 }
+
+/// Prints the diagnostics in the json messages from cargo.
+#[cfg(feature = "llvm")]
+pub fn print_diagnostics(messages: &str) {
+    for message in messages.lines() {
+        if let Ok(Message::Diagnostic { message }) = serde_json::from_str(message) {
+            eprint!("{}", message.rendered.unwrap_or_default());
+        }
+    }
+}
+
+/// Runs `cargo metadata` with the same features as the build.
+#[cfg(feature = "llvm")]
+pub fn generate_metadata_command(opts: &Settings) -> Command {
+    let mut cargo = Command::new("cargo");
+    cargo.args(["metadata", "--format-version=1"]);
+    match &opts.features {
+        Features::None => {}
+        Features::Some(features) => {
+            cargo.args(["--features", &features.join(",")]);
+        }
+        Features::All => {
+            cargo.arg("--all-features");
+        }
+    };
+    cargo
+}
+
+/// A json message from cargo, only the fields that are used are parsed.
+#[cfg(feature = "llvm")]
+#[derive(Deserialize)]
+#[serde(tag = "reason")]
+enum Message {
+    #[serde(rename = "compiler-artifact")]
+    Artifact {
+        package_id: String,
+        target: MessageTarget,
+        filenames: Vec<PathBuf>,
+    },
+    #[serde(rename = "compiler-message")]
+    Diagnostic { message: Diagnostic },
+    #[serde(other)]
+    Other,
+}
+
+#[cfg(feature = "llvm")]
+#[derive(Deserialize)]
+struct MessageTarget {
+    kind: Vec<String>,
+}
+
+#[cfg(feature = "llvm")]
+#[derive(Deserialize)]
+struct Diagnostic {
+    rendered: Option<String>,
+}
+
+/// The output of `cargo metadata`, only the fields that are used are parsed.
+#[cfg(feature = "llvm")]
+#[derive(Deserialize)]
+struct Metadata {
+    packages: Vec<Package>,
+    workspace_members: Vec<String>,
+    resolve: Resolve,
+}
+
+#[cfg(feature = "llvm")]
+#[derive(Deserialize)]
+struct Package {
+    id: String,
+    name: String,
+    targets: Vec<MessageTarget>,
+}
+
+#[cfg(feature = "llvm")]
+#[derive(Deserialize)]
+struct Resolve {
+    nodes: Vec<Node>,
+}
+
+#[cfg(feature = "llvm")]
+#[derive(Deserialize)]
+struct Node {
+    id: String,
+    deps: Vec<NodeDep>,
+}
+
+#[cfg(feature = "llvm")]
+#[derive(Deserialize)]
+struct NodeDep {
+    pkg: String,
+    dep_kinds: Vec<DepKind>,
+}
+
+#[cfg(feature = "llvm")]
+#[derive(Deserialize)]
+struct DepKind {
+    /// `None` for normal dependencies, `dev` or `build` otherwise.
+    kind: Option<String>,
+}
+
+/// Returns the ids of the packages that the package `name` depends on when it
+/// runs on the target, i.e. not through build or dev dependencies and not
+/// through proc macros as those run on the host.
+#[cfg(feature = "llvm")]
+fn target_dependencies(metadata: &Metadata, name: &str) -> Result<HashSet<String>> {
+    let root = metadata
+        .packages
+        .iter()
+        .find(|package| package.name == name && metadata.workspace_members.contains(&package.id))
+        .ok_or_else(|| anyhow!("Could not find the package {name} in the workspace"))?;
+    let proc_macros: HashSet<_> = metadata
+        .packages
+        .iter()
+        .filter(|package| {
+            package
+                .targets
+                .iter()
+                .any(|target| target.kind.iter().any(|kind| kind == "proc-macro"))
+        })
+        .map(|package| package.id.as_str())
+        .collect();
+    let nodes: HashMap<_, _> = metadata
+        .resolve
+        .nodes
+        .iter()
+        .map(|node| (node.id.as_str(), node))
+        .collect();
+
+    let mut dependencies = HashSet::new();
+    let mut stack = vec![root.id.as_str()];
+    while let Some(id) = stack.pop() {
+        let Some(node) = nodes.get(id) else {
+            continue;
+        };
+        for dep in node.deps.iter() {
+            let normal = dep.dep_kinds.iter().any(|kind| kind.kind.is_none());
+            if normal
+                && !proc_macros.contains(dep.pkg.as_str())
+                && dependencies.insert(dep.pkg.clone())
+            {
+                stack.push(&dep.pkg);
+            }
+        }
+    }
+    Ok(dependencies)
+}
+
+/// Collects the .bc files emitted for the dependencies of the target.
+///
+/// `messages` are the json messages from the build and `metadata` the output
+/// of `cargo metadata`. Only the libraries built for the target in this build
+/// are included, so old builds left in the `deps` directory, build scripts and
+/// proc macros are not. `exclude` is never included in the result.
+#[cfg(feature = "llvm")]
+pub fn get_dependency_bcs(
+    messages: &str,
+    metadata: &str,
+    package: &str,
+    exclude: &Path,
+) -> Result<Vec<PathBuf>> {
+    let metadata: Metadata = serde_json::from_str(metadata)?;
+    let dependencies = target_dependencies(&metadata, package)?;
+
+    let mut result = BTreeSet::new();
+    for message in messages.lines() {
+        let Ok(Message::Artifact {
+            package_id,
+            target,
+            filenames,
+        }) = serde_json::from_str(message)
+        else {
+            continue;
+        };
+        let is_lib = target
+            .kind
+            .iter()
+            .any(|kind| matches!(kind.as_str(), "lib" | "rlib"));
+        if !is_lib || !dependencies.contains(&package_id) {
+            continue;
+        }
+
+        // Libraries are named `lib<crate name>-<hash>.rlib` and the bitcode
+        // `<crate name>-<hash>.bc`.
+        for filename in filenames {
+            let Some(stem) = filename
+                .file_stem()
+                .and_then(|stem| stem.to_str())
+                .and_then(|stem| stem.strip_prefix("lib"))
+            else {
+                continue;
+            };
+            let path = filename.with_file_name(format!("{stem}.bc"));
+            if path == exclude {
+                continue;
+            }
+            match path.exists() {
+                true => {
+                    result.insert(path);
+                }
+                false => debug!("No .bc file for {filename:?}"),
+            }
+        }
+    }
+
+    Ok(result.into_iter().collect())
+}
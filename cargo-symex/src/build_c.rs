@@ -1,19 +1,151 @@
-#![cfg(feature = "llvm")]
-use std::{path::PathBuf, process::Command};
+#[cfg(feature = "llvm")]
+use std::{collections::HashSet, path::Path};
+use std::{fs, path::PathBuf, process::Command};
+
+/// Header with `symbolic`, `assume` and `suppress_path` for C.
+const PRELUDE: &str = include_str!("../include/symex.h");
+
+/// Start of the stack in the elf files, there is no linker script that
+/// places it.
+const STACK_START: u64 = 0x2001_0000;
 
 pub struct Settings {
-    pub path: PathBuf,
+    /// The C files to build.
+    pub paths: Vec<PathBuf>,
+
+    /// Directories searched for headers.
+    pub include_dirs: Vec<PathBuf>,
+
+    /// Macros to define, as `NAME` or `NAME=VALUE`.
+    pub defines: Vec<String>,
 
-    pub out_path: PathBuf,
+    /// Directory the output and the prelude are written to.
+    pub out_dir: PathBuf,
+
+    /// Target triple of the elf file, when building an elf file instead of
+    /// bitcode.
+    pub elf_target: Option<String>,
 }
 
-pub fn generate_build_command(opts: &Settings) -> Command {
+impl Settings {
+    /// Path of the bitcode file built from each C file.
+    #[cfg(feature = "llvm")]
+    pub fn bitcode_paths(&self) -> Vec<PathBuf> {
+        let mut stems = HashSet::new();
+        self.paths
+            .iter()
+            .map(|path| {
+                let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+                // Files with the same name in different directories get a
+                // number appended.
+                let mut name = stem.to_string();
+                let mut n = 1;
+                while !stems.insert(name.clone()) {
+                    name = format!("{stem}-{n}");
+                    n += 1;
+                }
+                self.out_dir.join(name).with_extension("bc")
+            })
+            .collect()
+    }
+
+    /// Path of the elf file, named after the first C file.
+    pub fn elf_path(&self) -> PathBuf {
+        let stem = self.paths[0].file_stem().unwrap_or_default();
+        self.out_dir.join(stem).with_extension("elf")
+    }
+
+    fn include_dir(&self) -> PathBuf {
+        self.out_dir.join("include")
+    }
+}
+
+/// Writes `symex.h` to the include directory of the output.
+pub fn write_prelude(opts: &Settings) -> std::io::Result<()> {
+    let dir = opts.include_dir();
+    fs::create_dir_all(&dir)?;
+    fs::write(dir.join("symex.h"), PRELUDE)
+}
+
+fn clang(opts: &Settings) -> Command {
     let mut clang = Command::new("clang");
-    clang.args(&["-c", "-emit-llvm"]);
-    clang.arg(opts.path.as_os_str());
+    clang.arg("-g");
+    clang.arg("-I").arg(opts.include_dir());
+    for dir in opts.include_dirs.iter() {
+        clang.arg("-I").arg(dir);
+    }
+    for define in opts.defines.iter() {
+        clang.arg(format!("-D{define}"));
+    }
+    clang
+}
+
+/// Builds `path` into the bitcode file `out_path`.
+#[cfg(feature = "llvm")]
+pub fn generate_build_command(opts: &Settings, path: &Path, out_path: &Path) -> Command {
+    let mut clang = clang(opts);
+    clang.args(["-c", "-emit-llvm"]);
+    clang.arg(path.as_os_str());
+
+    clang.arg("-o");
+    clang.arg(out_path.as_os_str());
+
+    clang
+}
+
+/// Builds and links all files into an elf file for `target`, the analysis
+/// starts at `function`.
+pub fn generate_elf_build_command(opts: &Settings, target: &str, function: &str) -> Command {
+    let mut clang = clang(opts);
+    clang.arg(format!("--target={target}"));
+    clang.args(["-ffreestanding", "-nostdlib", "-fuse-ld=lld"]);
+    clang.arg(format!("-Wl,--entry={function}"));
+    clang.arg(format!("-Wl,--defsym=_stack_start={STACK_START:#x}"));
+    clang.args(opts.paths.iter());
 
     clang.arg("-o");
-    clang.arg(opts.out_path.as_os_str());
+    clang.arg(opts.elf_path().as_os_str());
 
     clang
 }
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::Settings;
+
+    fn settings(paths: &[&str]) -> Settings {
+        Settings {
+            paths: paths.iter().map(PathBuf::from).collect(),
+            include_dirs: vec![],
+            defines: vec![],
+            out_dir: PathBuf::from("out"),
+            elf_target: None,
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "llvm")]
+    fn test_bitcode_paths() {
+        // Files with the same name get a number, also when the name with the
+        // number is taken by another file.
+        let settings = settings(&["src/main.c", "lib/util.c", "test/main.c", "main-1.c"]);
+        let expected = [
+            "out/main.bc",
+            "out/util.bc",
+            "out/main-1.bc",
+            "out/main-1-1.bc",
+        ];
+        assert_eq!(
+            settings.bitcode_paths(),
+            expected.map(PathBuf::from).to_vec()
+        );
+    }
+
+    #[test]
+    fn test_elf_path() {
+        let settings = settings(&["src/main.c", "lib/util.c"]);
+        assert_eq!(settings.elf_path(), PathBuf::from("out/main.elf"));
+    }
+}
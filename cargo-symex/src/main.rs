@@ -1,10 +1,13 @@
 use anyhow::{anyhow, Result};
 use clap::Parser;
 use log::debug;
-#[cfg(feature = "llvm")]
 use std::{fs, path::PathBuf};
 #[cfg(feature = "llvm")]
-use symex::run::{self, RunConfig, SolveFor};
+use symex::vm::MemoryModel;
+use symex::{
+    analysis::{Analysis, Artifact},
+    general_assembly::project::target_config::TargetConfiguration,
+};
 
 const BINARY_NAME: &str = "symex";
 
@@ -12,20 +15,14 @@ mod args;
 mod build;
 mod build_c;
 
-#[cfg(not(feature = "llvm"))]
-use args::Args;
-#[cfg(feature = "llvm")]
-use args::{Args, ClangArgs};
+use args::{Args, ClangArgs, Subcommands};
 #[cfg(feature = "llvm")]
 use build::{
-    generate_build_command, get_extra_filename, get_latest_bc, Features, Settings, Target,
+    generate_build_command, generate_metadata_command, get_dependency_bcs, get_extra_filename,
+    get_latest_bc, print_diagnostics,
 };
-#[cfg(not(feature = "llvm"))]
 use build::{Features, Settings, Target};
 
-#[cfg(feature = "llvm")]
-use crate::args::Subcommands;
-
 fn main() -> Result<()> {
     tracing_subscriber::fmt::init();
 
@@ -55,28 +52,28 @@ fn run() -> Result<()> {
 
     let args = Args::parse_from(args);
 
-    // maybe  hacky look into later
-    #[cfg(not(feature = "llvm"))]
-    if args.elf {
-        run_elf(args)?;
-    }
-
-    #[cfg(feature = "llvm")]
     match args.subcommand {
-        Some(subcommand) => match subcommand {
-            Subcommands::C(clang_args) => run_c(clang_args),
-        },
+        Some(Subcommands::C(clang_args)) => run_c(clang_args),
+        None if args.elf => run_elf(args),
+        #[cfg(feature = "llvm")]
         None => run_rs(args),
+        #[cfg(not(feature = "llvm"))]
+        None => Ok(()),
     }
-
-    Ok(())
 }
 
-#[cfg(not(feature = "llvm"))]
 fn run_elf(args: Args) -> Result<()> {
     use crate::build::generate_binary_build_command;
 
     debug!("Run elf file.");
+    let image = match args.format {
+        Some(_) => Some(image_options(&args)?),
+        None => None,
+    };
+    let trace = match args.trace {
+        Some(_) => Some(trace_config(&args)?),
+        None => None,
+    };
     let path = match args.path {
         Some(path) => path,
         None => {
@@ -101,12 +98,112 @@ fn run_elf(args: Args) -> Result<()> {
         Some(function) => function,
         None => "main".to_owned(),
     };
-    debug!("Starting analasys on target: {path}, function: {function_name}");
+    let target = match &args.target_config {
+        Some(path) => Some(TargetConfiguration::from_file(path)?),
+        None => None,
+    };
 
-    symex::run_elf::run_elf(&path, &function_name, true)?;
+    if let Some(periods) = &args.rtic {
+        debug!("Starting RTIC analasys on target: {path}");
+        let periods = symex::rtic::read_periods(periods)?;
+        let analysis = symex::rtic::analyze(&path, &periods, target)?;
+        println!("{analysis}");
+        return Ok(());
+    }
+
+    let artifact = match image {
+        Some(options) => Artifact::Image(path.clone().into(), options),
+        None => Artifact::Elf(path.clone().into()),
+    };
+    let mut analysis = Analysis::new(artifact)
+        .merge_paths(args.merge_paths)
+        .output(std::io::stdout());
+    if let Some(target) = target {
+        analysis = analysis.target(target);
+    }
+    if let Some(trace) = trace {
+        analysis = analysis.trace(trace);
+    }
+    analysis = match &args.snapshot {
+        Some(snapshot) => {
+            debug!("Starting analasys on target: {path}, snapshot: {snapshot:?}");
+            let snapshot =
+                symex::general_assembly::project::snapshot::Snapshot::from_file(snapshot)?;
+            analysis.snapshot(snapshot)
+        }
+        None => {
+            debug!("Starting analasys on target: {path}, function: {function_name}");
+            analysis.function(function_name)
+        }
+    };
+    analysis.run()?;
     Ok(())
 }
 
+/// Reads where and how to write the traces from the arguments.
+fn trace_config(args: &Args) -> Result<symex::general_assembly::replay::TraceConfig> {
+    use std::io::Write;
+
+    use symex::general_assembly::replay::{TraceConfig, TraceFormat};
+
+    let format = match args.trace_format.as_deref() {
+        None | Some("text") => TraceFormat::Text,
+        Some("jsonl") => TraceFormat::JsonLines,
+        Some(format) => return Err(anyhow!("Unknown trace format {format}")),
+    };
+    let writer: Box<dyn Write> = match args.trace.as_deref() {
+        Some(path) if path.as_os_str() != "-" => {
+            Box::new(std::io::BufWriter::new(std::fs::File::create(path)?))
+        }
+        _ => Box::new(std::io::stdout()),
+    };
+    Ok(TraceConfig {
+        writer,
+        format,
+        only_failures: args.trace_failed,
+    })
+}
+
+/// Reads how to load a binary image that is not an elf file from the arguments.
+fn image_options(args: &Args) -> Result<symex::run_elf::ImageOptions> {
+    use symex::general_assembly::{
+        project::image::{parse_address, parse_symbol_map, ImageFormat},
+        Endianness,
+    };
+
+    let format = match args.format.as_deref() {
+        Some("bin") => {
+            let base = match &args.base {
+                Some(base) => {
+                    parse_address(base).ok_or_else(|| anyhow!("Invalid base address {base}"))?
+                }
+                None => 0,
+            };
+            ImageFormat::Binary { base }
+        }
+        Some("ihex") => ImageFormat::IntelHex,
+        Some("srec") => ImageFormat::SRecord,
+        format => return Err(anyhow!("Unknown image format {format:?}")),
+    };
+    let architecture = match &args.arch {
+        Some(arch) => arch.parse()?,
+        None => return Err(anyhow!("The architecture of the image is required")),
+    };
+    let symbols = match &args.symbols {
+        Some(path) => parse_symbol_map(&std::fs::read_to_string(path)?)?,
+        None => Default::default(),
+    };
+    Ok(symex::run_elf::ImageOptions {
+        format,
+        architecture,
+        endianness: match args.big_endian {
+            true => Endianness::Big,
+            false => Endianness::Little,
+        },
+        symbols,
+    })
+}
+
 #[cfg(feature = "llvm")]
 fn run_rs(args: Args) -> Result<()> {
     let opts = settings_from_args(&args);
@@ -114,6 +211,8 @@ fn run_rs(args: Args) -> Result<()> {
     // Build LLVM BC file.
     let cargo_out = generate_build_command(&opts).output()?;
     debug!("cargo output: {cargo_out:?}");
+    let messages = String::from_utf8(cargo_out.stdout)?;
+    print_diagnostics(&messages);
     if !cargo_out.status.success() {
         let cargo_output = String::from_utf8(cargo_out.stderr)?;
         return Err(anyhow!(cargo_output));
@@ -138,6 +237,20 @@ fn run_rs(args: Args) -> Result<()> {
     };
     debug!("Target .bc path: {target_path:?}");
 
+    // Link in the dependencies so calls into them can be followed.
+    let metadata_out = generate_metadata_command(&opts).output()?;
+    if !metadata_out.status.success() {
+        return Err(anyhow!(String::from_utf8(metadata_out.stderr)?));
+    }
+    let metadata = String::from_utf8(metadata_out.stdout)?;
+    let dependencies = get_dependency_bcs(
+        &messages,
+        &metadata,
+        &opts.get_package_name()?,
+        &target_path,
+    )?;
+    debug!("Dependency .bc paths: {dependencies:?}");
+
     // Get function name and analyze code.
     let fn_name = match args.function {
         None => "main".to_owned(),
@@ -146,14 +259,12 @@ fn run_rs(args: Args) -> Result<()> {
     let fn_name = format!("{}::{fn_name}", opts.get_module_name()?);
     debug!("Starting analysis on target: {target_path:?}, function: {fn_name}");
 
-    let cfg = RunConfig {
-        solve_inputs: true,
-        solve_symbolics: true,
-        solve_output: true,
-        solve_for: SolveFor::All,
-    };
-
-    run::run(&target_path, &fn_name, &cfg)?;
+    let paths = std::iter::once(target_path).chain(dependencies).collect();
+    Analysis::new(Artifact::Bitcode(paths))
+        .function(fn_name)
+        .memory_model(MemoryModel::Object)
+        .output(std::io::stdout())
+        .run()?;
     Ok(())
 }
 
@@ -178,47 +289,81 @@ fn settings_from_args(opts: &Args) -> Settings {
         target,
         features,
         release: opts.release,
+        #[cfg(feature = "llvm")]
+        embed_bitcode: opts.embed_bitcode.unwrap_or(false),
     }
 }
 
-#[cfg(feature = "llvm")]
 fn run_c(args: ClangArgs) -> Result<()> {
     let opts = clang_settings_from_args(&args);
 
-    // Create output directory
-    let mut dir = opts.out_path.clone();
-    dir.pop();
-    fs::create_dir_all(dir)?;
-
-    // Build .bc
-    let clang_out = build_c::generate_build_command(&opts).output()?;
-    debug!("clang output: {clang_out:?}");
-    if !clang_out.status.success() {
-        let clang_output = String::from_utf8(clang_out.stderr)?;
-        return Err(anyhow!(clang_output));
-    }
+    // Create output directory with the prelude.
+    fs::create_dir_all(&opts.out_dir)?;
+    build_c::write_prelude(&opts)?;
 
-    // Get function name and analyze code.
     let fn_name = match args.function {
         None => "main".to_owned(),
         Some(name) => name,
     };
-    debug!(
-        "Starting analysis on target: {:?}, function: {fn_name}",
-        opts.out_path
-    );
-    todo!()
-    // runner::run(&opts.out_path, &fn_name)
+
+    let analysis = match &opts.elf_target {
+        Some(target) => {
+            // Build .elf
+            let clang_out =
+                build_c::generate_elf_build_command(&opts, target, &fn_name).output()?;
+            debug!("clang output: {clang_out:?}");
+            if !clang_out.status.success() {
+                let clang_output = String::from_utf8(clang_out.stderr)?;
+                return Err(anyhow!(clang_output));
+            }
+
+            let elf_path = opts.elf_path();
+            debug!("Starting analysis on target: {elf_path:?}, function: {fn_name}");
+            let analysis = Analysis::new(Artifact::Elf(elf_path));
+            match &args.target_config {
+                Some(path) => analysis.target(TargetConfiguration::from_file(path)?),
+                None => analysis,
+            }
+        }
+        None => bitcode_analysis(&opts, &fn_name)?,
+    };
+    analysis.function(fn_name).output(std::io::stdout()).run()?;
+    Ok(())
 }
 
+/// Builds each C file to bitcode, which are linked together by the analysis.
 #[cfg(feature = "llvm")]
-fn clang_settings_from_args(opts: &ClangArgs) -> build_c::Settings {
-    let mut out_path = PathBuf::from("target/c");
-    out_path.push(opts.path.file_stem().unwrap());
-    out_path.set_extension("bc");
+fn bitcode_analysis(opts: &build_c::Settings, fn_name: &str) -> Result<Analysis> {
+    let out_paths = opts.bitcode_paths();
+    for (path, out_path) in opts.paths.iter().zip(out_paths.iter()) {
+        let clang_out = build_c::generate_build_command(opts, path, out_path).output()?;
+        debug!("clang output: {clang_out:?}");
+        if !clang_out.status.success() {
+            let clang_output = String::from_utf8(clang_out.stderr)?;
+            return Err(anyhow!(clang_output));
+        }
+    }
+
+    debug!("Starting analysis on target: {out_paths:?}, function: {fn_name}");
+    Ok(Analysis::new(Artifact::Bitcode(out_paths)).memory_model(MemoryModel::Object))
+}
+
+#[cfg(not(feature = "llvm"))]
+fn bitcode_analysis(_opts: &build_c::Settings, _fn_name: &str) -> Result<Analysis> {
+    Err(anyhow!(
+        "Analyzing C as LLVM bitcode requires the llvm feature, use --elf to analyze an elf file"
+    ))
+}
 
+fn clang_settings_from_args(opts: &ClangArgs) -> build_c::Settings {
     build_c::Settings {
-        path: opts.path.clone(),
-        out_path,
+        paths: opts.paths.clone(),
+        include_dirs: opts.include_dirs.clone(),
+        defines: opts.defines.clone(),
+        out_dir: PathBuf::from("target/c"),
+        elf_target: match opts.elf {
+            true => Some(opts.target.clone()),
+            false => None,
+        },
     }
 }
@@ -1,18 +1,22 @@
 use std::ffi::CStr;
 
+pub use llvm_sys::LLVMLinkage;
+
 use llvm_sys::{
     core::{
-        LLVMGetAlignment, LLVMGetDLLStorageClass, LLVMGetFirstBasicBlock, LLVMGetFirstParam,
-        LLVMGetFunctionCallConv, LLVMGetGC, LLVMGetInitializer, LLVMGetIntrinsicID, LLVMGetLinkage,
-        LLVMGetNextParam, LLVMGetPersonalityFn, LLVMGetSection, LLVMGetThreadLocalMode,
+        LLVMGetAlignment, LLVMGetDLLStorageClass, LLVMGetEnumAttributeAtIndex,
+        LLVMGetEnumAttributeKindForName, LLVMGetEnumAttributeValue, LLVMGetFirstBasicBlock,
+        LLVMGetFirstParam, LLVMGetFunctionCallConv, LLVMGetGC, LLVMGetInitializer,
+        LLVMGetIntrinsicID, LLVMGetLinkage, LLVMGetNextBasicBlock, LLVMGetNextParam,
+        LLVMGetParamParent, LLVMGetPersonalityFn, LLVMGetSection, LLVMGetThreadLocalMode,
         LLVMGetUnnamedAddress, LLVMGetValueKind, LLVMGetValueName2, LLVMGetVisibility,
         LLVMGlobalGetValueType, LLVMHasPersonalityFn, LLVMIntrinsicGetName,
         LLVMIntrinsicIsOverloaded, LLVMIsDeclaration, LLVMIsExternallyInitialized,
-        LLVMIsGlobalConstant, LLVMIsThreadLocal, LLVMPrintValueToString, LLVMTypeOf,
+        LLVMIsGlobalConstant, LLVMIsThreadLocal, LLVMPrintValueToString, LLVMSetLinkage,
+        LLVMTypeOf,
     },
     prelude::*,
-    LLVMDLLStorageClass, LLVMLinkage, LLVMThreadLocalMode, LLVMUnnamedAddr, LLVMValueKind,
-    LLVMVisibility,
+    LLVMDLLStorageClass, LLVMThreadLocalMode, LLVMUnnamedAddr, LLVMValueKind, LLVMVisibility,
 };
 
 use crate::{
@@ -173,6 +177,43 @@ impl Argument {
     pub fn ty(&self) -> Type {
         Type::new(unsafe { LLVMTypeOf(self.0) })
     }
+
+    pub fn name(&self) -> Option<&CStr> {
+        let name = unsafe {
+            let mut len = 0;
+            let ptr = LLVMGetValueName2(self.0, &mut len);
+            CStr::from_ptr(ptr)
+        };
+
+        match name.is_empty() {
+            false => Some(name),
+            true => None,
+        }
+    }
+
+    /// Position of the argument in the parameter list.
+    pub fn index(&self) -> u32 {
+        let function = unsafe { LLVMGetParamParent(self.0) };
+        unsafe { ParameterIter::new(function) }
+            .position(|parameter| parameter == Value::Argument(self.clone()))
+            .expect("argument is a parameter of its function") as u32
+    }
+
+    /// Number of bytes known to be dereferenceable through the pointer, from
+    /// the `dereferenceable` attribute.
+    pub fn dereferenceable_bytes(&self) -> Option<u64> {
+        let function = unsafe { LLVMGetParamParent(self.0) };
+        let name = "dereferenceable";
+        let attribute = unsafe {
+            let kind = LLVMGetEnumAttributeKindForName(name.as_ptr().cast(), name.len());
+            LLVMGetEnumAttributeAtIndex(function, self.index() + 1, kind)
+        };
+
+        match attribute.is_null() {
+            true => None,
+            false => Some(unsafe { LLVMGetEnumAttributeValue(attribute) }),
+        }
+    }
 }
 
 impl std::fmt::Display for Argument {
@@ -261,11 +302,41 @@ impl Function {
         }
     }
 
+    pub fn set_linkage(&self, linkage: LLVMLinkage) {
+        unsafe { LLVMSetLinkage(self.0, linkage) }
+    }
+
+    pub fn basic_blocks(&self) -> BasicBlockIter {
+        unsafe { BasicBlockIter::new(self.0) }
+    }
+
     pub fn parameters(&self) -> ParameterIter {
         unsafe { ParameterIter::new(self.0) }
     }
 }
 
+pub struct BasicBlockIter(LLVMBasicBlockRef);
+
+impl BasicBlockIter {
+    pub(crate) unsafe fn new(function_ref: LLVMValueRef) -> Self {
+        Self(unsafe { LLVMGetFirstBasicBlock(function_ref) })
+    }
+}
+
+impl Iterator for BasicBlockIter {
+    type Item = BasicBlock;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.0.is_null() {
+            None
+        } else {
+            let current = self.0;
+            self.0 = unsafe { LLVMGetNextBasicBlock(self.0) };
+            Some(BasicBlock::new(current))
+        }
+    }
+}
+
 pub struct ParameterIter(LLVMValueRef);
 
 impl ParameterIter {
@@ -381,6 +452,10 @@ impl GlobalVariable {
         unsafe { LLVMIsGlobalConstant(self.0) != 0 }
     }
 
+    pub fn set_linkage(&self, linkage: LLVMLinkage) {
+        unsafe { LLVMSetLinkage(self.0, linkage) }
+    }
+
     pub fn is_thread_local(&self) -> bool {
         unsafe { LLVMIsThreadLocal(self.0) != 0 }
     }
@@ -3,14 +3,15 @@ use llvm_sys::{
         LLVMCountIncoming, LLVMGetAlignment, LLVMGetAllocatedType, LLVMGetAtomicRMWBinOp,
         LLVMGetCalledFunctionType, LLVMGetCalledValue, LLVMGetCmpXchgFailureOrdering,
         LLVMGetCmpXchgSuccessOrdering, LLVMGetFCmpPredicate, LLVMGetFirstInstruction,
-        LLVMGetFunctionCallConv, LLVMGetGEPSourceElementType, LLVMGetICmpPredicate,
-        LLVMGetIncomingBlock, LLVMGetIncomingValue, LLVMGetIndices, LLVMGetInstructionOpcode,
-        LLVMGetLastInstruction, LLVMGetMaskValue, LLVMGetNSW, LLVMGetNUW, LLVMGetNextInstruction,
-        LLVMGetNormalDest, LLVMGetNumArgOperands, LLVMGetNumIndices, LLVMGetNumMaskElements,
-        LLVMGetNumOperands, LLVMGetNumSuccessors, LLVMGetOperand, LLVMGetOrdering,
-        LLVMGetSuccessor, LLVMGetSwitchDefaultDest, LLVMGetUndefMaskElem, LLVMGetUnwindDest,
-        LLVMGetVolatile, LLVMGetWeak, LLVMIsAtomicSingleThread, LLVMIsInBounds, LLVMTypeOf,
-        LLVMValueAsBasicBlock, LLVMValueIsBasicBlock,
+        LLVMGetFunctionCallConv, LLVMGetGEPSourceElementType, LLVMGetHandlers,
+        LLVMGetICmpPredicate, LLVMGetIncomingBlock, LLVMGetIncomingValue, LLVMGetIndices,
+        LLVMGetInstructionOpcode, LLVMGetLastInstruction, LLVMGetMDString, LLVMGetMaskValue,
+        LLVMGetNSW, LLVMGetNUW, LLVMGetNextInstruction, LLVMGetNormalDest, LLVMGetNumArgOperands,
+        LLVMGetNumHandlers, LLVMGetNumIndices, LLVMGetNumMaskElements, LLVMGetNumOperands,
+        LLVMGetNumSuccessors, LLVMGetOperand, LLVMGetOrdering, LLVMGetSuccessor,
+        LLVMGetSwitchDefaultDest, LLVMGetUndefMaskElem, LLVMGetUnwindDest, LLVMGetVolatile,
+        LLVMGetWeak, LLVMIsAMDNode, LLVMIsAValueAsMetadata, LLVMIsAtomicSingleThread,
+        LLVMIsInBounds, LLVMTypeOf, LLVMValueAsBasicBlock, LLVMValueIsBasicBlock,
     },
     prelude::*,
     LLVMOpcode,
@@ -873,24 +874,19 @@ impl_binop!(FRem);
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub struct FNeg(LLVMValueRef);
 impl_instruction!(FNeg);
-
-impl FNeg {
-    pub fn value(&self) -> LLVMValueRef {
-        unsafe { LLVMGetOperand(self.0, 0) }
-    }
-}
+impl_unop!(FNeg);
 
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub struct ExtractElement(LLVMValueRef);
 impl_instruction!(ExtractElement);
 
 impl ExtractElement {
-    pub fn aggregate(&self) -> LLVMValueRef {
-        unsafe { LLVMGetOperand(self.0, 0) }
+    pub fn aggregate(&self) -> Value {
+        Value::new(unsafe { LLVMGetOperand(self.0, 0) })
     }
 
-    pub fn index(&self) -> LLVMValueRef {
-        unsafe { LLVMGetOperand(self.0, 1) }
+    pub fn index(&self) -> Value {
+        Value::new(unsafe { LLVMGetOperand(self.0, 1) })
     }
 }
 
@@ -944,7 +940,6 @@ impl ShuffleVector {
             .map(|i| match is_scalable {
                 false => match i {
                     v if v == undefined_element_value => None,
-                    0 => panic!("fixed vector mask element is not 0 or undef"),
                     _ => Some(i),
                 },
                 true => match i {
@@ -1281,6 +1276,55 @@ impl Call {
     pub fn calling_convention(&self) -> u32 {
         unsafe { LLVMGetFunctionCallConv(self.0) }
     }
+
+    /// Source variable described by a call to `llvm.dbg.declare` or
+    /// `llvm.dbg.value`, `None` for all other calls.
+    pub fn debug_variable(&self) -> Option<DebugVariable> {
+        let Value::Function(function) = self.called_value() else {
+            return None;
+        };
+        let function_name = function.name().to_bytes();
+        if function_name != b"llvm.dbg.declare" && function_name != b"llvm.dbg.value" {
+            return None;
+        }
+
+        // Arguments: metadata <location>, metadata <DILocalVariable>, metadata <DIExpression>
+        let location = unsafe { LLVMGetOperand(self.0, 0) };
+        let variable = unsafe { LLVMGetOperand(self.0, 1) };
+        if unsafe {
+            LLVMIsAValueAsMetadata(location).is_null() || LLVMIsAMDNode(variable).is_null()
+        } {
+            return None;
+        }
+
+        // The name is the second operand of a DILocalVariable.
+        let name = unsafe {
+            let name = LLVMGetOperand(variable, 1);
+            let mut len = 0;
+            let ptr = LLVMGetMDString(name, &mut len);
+            match ptr.is_null() {
+                true => return None,
+                false => std::slice::from_raw_parts(ptr.cast::<u8>(), len as usize),
+            }
+        };
+        let value = unsafe { LLVMGetOperand(location, 0) };
+        if value.is_null() {
+            return None;
+        }
+
+        Some(DebugVariable {
+            name: String::from_utf8_lossy(name).into_owned(),
+            value: Value::new(value),
+        })
+    }
+}
+
+/// A source level variable and the value holding it, either the value itself
+/// or its address.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DebugVariable {
+    pub name: String,
+    pub value: Value,
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
@@ -1429,7 +1473,7 @@ impl Invoke {
     pub fn arguments(&self) -> Vec<Value> {
         let num_arguments = unsafe { LLVMGetNumArgOperands(self.0) };
         (0..num_arguments)
-            .map(|i| unsafe { LLVMGetOperand(self.0, i + 1) })
+            .map(|i| unsafe { LLVMGetOperand(self.0, i) })
             .map(Value::new)
             .collect()
     }
@@ -1469,19 +1513,49 @@ impl_instruction!(Unreachable);
 pub struct CleanupRet(LLVMValueRef);
 impl_instruction!(CleanupRet);
 
-impl CleanupRet {}
+impl CleanupRet {
+    /// Block to continue unwinding in, `None` if it unwinds to the caller.
+    pub fn unwind_destination(&self) -> Option<BasicBlock> {
+        let block = unsafe { LLVMGetUnwindDest(self.0) };
+        match block.is_null() {
+            true => None,
+            false => Some(BasicBlock(block)),
+        }
+    }
+}
 
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub struct CatchRet(LLVMValueRef);
 impl_instruction!(CatchRet);
 
-impl CleanupRet {}
+impl CatchRet {
+    pub fn successor(&self) -> BasicBlock {
+        BasicBlock(unsafe { LLVMGetSuccessor(self.0, 0) })
+    }
+}
 
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub struct CatchSwitch(LLVMValueRef);
 impl_instruction!(CatchSwitch);
 
-impl CleanupRet {}
+impl CatchSwitch {
+    pub fn handlers(&self) -> Vec<BasicBlock> {
+        let num_handlers = unsafe { LLVMGetNumHandlers(self.0) };
+        let mut handlers = Vec::with_capacity(num_handlers as usize);
+        unsafe { LLVMGetHandlers(self.0, handlers.as_mut_ptr()) };
+        unsafe { handlers.set_len(num_handlers as usize) };
+        handlers.into_iter().map(BasicBlock).collect()
+    }
+
+    /// Block to continue unwinding in, `None` if it unwinds to the caller.
+    pub fn unwind_destination(&self) -> Option<BasicBlock> {
+        let block = unsafe { LLVMGetUnwindDest(self.0) };
+        match block.is_null() {
+            true => None,
+            false => Some(BasicBlock(block)),
+        }
+    }
+}
 
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub struct CallBr(LLVMValueRef);
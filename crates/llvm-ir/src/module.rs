@@ -10,9 +10,11 @@ use llvm_sys::{
     core::{
         LLVMContextCreate, LLVMCreateMemoryBufferWithContentsOfFile, LLVMDisposeMemoryBuffer,
         LLVMGetDataLayoutStr, LLVMGetFirstFunction, LLVMGetFirstGlobal, LLVMGetFirstGlobalAlias,
-        LLVMGetFirstGlobalIFunc, LLVMGetModuleIdentifier, LLVMGetNextFunction, LLVMGetNextGlobal,
+        LLVMGetFirstGlobalIFunc, LLVMGetModuleContext, LLVMGetModuleIdentifier,
+        LLVMGetNamedFunction, LLVMGetNamedGlobal, LLVMGetNextFunction, LLVMGetNextGlobal,
         LLVMGetNextGlobalAlias, LLVMGetNextGlobalIFunc, LLVMGetSourceFileName, LLVMGetTarget,
     },
+    linker::LLVMLinkModules2,
     prelude::*,
 };
 
@@ -23,6 +25,10 @@ pub enum ModuleError {
     /// Failed to load LLVM bitcode or IR file.
     #[error("Failed to load LLVM ir: {0:?}")]
     FailedToLoad(CString),
+
+    /// Failed to link two modules.
+    #[error("Failed to link module: {0:?}")]
+    FailedToLink(CString),
 }
 
 pub struct Module(LLVMModuleRef);
@@ -68,6 +74,18 @@ impl Module {
     }
 
     pub fn load(path: impl AsRef<Path>) -> Result<Self, ModuleError> {
+        let ctx = unsafe { LLVMContextCreate() };
+        Self::load_in_context(path, ctx)
+    }
+
+    /// Load another module into the same context as this one, which is
+    /// required to [link](Module::link) them.
+    pub fn load_into_context(&self, path: impl AsRef<Path>) -> Result<Self, ModuleError> {
+        let ctx = unsafe { LLVMGetModuleContext(self.0) };
+        Self::load_in_context(path, ctx)
+    }
+
+    fn load_in_context(path: impl AsRef<Path>, ctx: LLVMContextRef) -> Result<Self, ModuleError> {
         let path = CString::new(path.as_ref().as_os_str().as_bytes()).unwrap();
 
         let mut memory_buffer = core::ptr::null_mut();
@@ -91,8 +109,6 @@ impl Module {
 
         let mut module: MaybeUninit<LLVMModuleRef> = MaybeUninit::uninit();
         let success = unsafe {
-            let ctx = LLVMContextCreate();
-            
             // LLVMDisposeMemoryBuffer(buffer);
             LLVMParseBitcodeInContext2(ctx, memory_buffer, module.as_mut_ptr())
         };
@@ -105,6 +121,19 @@ impl Module {
         Ok(Self(module))
     }
 
+    /// Link `other` into this module, `other` must have been loaded with
+    /// [`Module::load_into_context`].
+    ///
+    /// Declarations are resolved against the definitions in the other module,
+    /// following the usual LLVM linkage rules.
+    pub fn link(&mut self, other: Module) -> Result<(), ModuleError> {
+        let identifier = other.identifier().to_owned();
+        match unsafe { LLVMLinkModules2(self.0, other.0) } {
+            0 => Ok(()),
+            _ => Err(ModuleError::FailedToLink(identifier)),
+        }
+    }
+
     // fn load_module(path: &Path, is_bc: bool) -> LLVMModuleRef {}
 
     pub fn identifier(&self) -> &CStr {
@@ -144,6 +173,22 @@ impl Module {
     pub fn globals(&self) -> GlobalIter {
         unsafe { GlobalIter::new(self.0) }
     }
+
+    pub fn function(&self, name: &CStr) -> Option<Function> {
+        let function = unsafe { LLVMGetNamedFunction(self.0, name.as_ptr()) };
+        match function.is_null() {
+            true => None,
+            false => Some(function.into()),
+        }
+    }
+
+    pub fn global(&self, name: &CStr) -> Option<GlobalVariable> {
+        let global = unsafe { LLVMGetNamedGlobal(self.0, name.as_ptr()) };
+        match global.is_null() {
+            true => None,
+            false => Some(global.into()),
+        }
+    }
 }
 
 macro_rules! impl_iter {
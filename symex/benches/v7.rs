@@ -0,0 +1,98 @@
+//! Runs small Armv7-M programs from the v7 tests through the executor.
+//!
+//! Run with `cargo bench -p symex --bench v7`.
+
+use std::collections::HashMap;
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use general_assembly::register::Register;
+use symex::{
+    general_assembly::{
+        arch::arm::v7::ArmV7EM,
+        executor::PathResult,
+        project::{PCHook, Project},
+        state::GAState,
+        vm::VM,
+        Endianness,
+        WordSize,
+    },
+    smt::{DContext, DSolver},
+};
+
+/// Two independent if statements on the symbolic `R0` and `R1`, four paths.
+const TWO_IFS: [u16; 9] = [
+    0x2800, // CMP R0, #0
+    0xd001, // BEQ 0x8
+    0x3201, // ADDS R2, #1
+    0x3201, // ADDS R2, #1
+    0x2900, // CMP R1, #0
+    0xd001, // BEQ 0x10
+    0x3201, // ADDS R2, #1
+    0x3201, // ADDS R2, #1
+    0x4770, // BX LR
+];
+
+/// Counts `R0` down from 100, a single path of concrete instructions.
+const COUNTDOWN: [u16; 4] = [
+    0x2064, // MOVS R0, #100
+    0x3801, // SUBS R0, #1
+    0xd1fd, // BNE 0x2
+    0x4770, // BX LR
+];
+
+/// Creates a project that runs `code` from address 0 and returns to `0x1000`.
+fn project(code: &[u16]) -> &'static Project<ArmV7EM> {
+    // Instructions are read a word at a time so the last half word is padded.
+    let program: Vec<u8> = code
+        .iter()
+        .chain(&[0])
+        .flat_map(|half_word| half_word.to_le_bytes())
+        .collect();
+    let end = program.len() as u64;
+    let mut project = Box::new(Project::manual_project(
+        program,
+        0,
+        end,
+        WordSize::Bit32,
+        Endianness::Little,
+        HashMap::new(),
+        HashMap::new(),
+        Vec::new(),
+        Vec::new(),
+        HashMap::new(),
+        vec![],
+        HashMap::new(),
+        vec![],
+    ));
+    project.add_hooks(&ArmV7EM::default());
+    project.add_pc_hook(0x1000, PCHook::EndSuccess);
+    Box::leak(project)
+}
+
+/// Runs all paths of the program in `project` and returns the number of paths.
+fn run(project: &'static Project<ArmV7EM>, context: &'static DContext) -> usize {
+    let solver = DSolver::new(context);
+    let mut state =
+        GAState::create_test_state(project, context, solver, 0, 0x2000_0000, ArmV7EM::default());
+    state
+        .set_register(Register::LR, context.from_u64(0x1000, 32))
+        .unwrap();
+    let mut vm = VM::new_with_state(project, state);
+    let mut paths = 0;
+    while let Some((result, _)) = vm.run().unwrap() {
+        assert!(matches!(result, PathResult::Success(_)));
+        paths += 1;
+    }
+    paths
+}
+
+fn v7(c: &mut Criterion) {
+    let context = Box::leak(Box::new(DContext::new()));
+    for (name, code) in [("two_ifs", &TWO_IFS[..]), ("countdown", &COUNTDOWN[..])] {
+        let project = project(code);
+        c.bench_function(name, |b| b.iter(|| run(project, context)));
+    }
+}
+
+criterion_group!(benches, v7);
+criterion_main!(benches);
@@ -5,7 +5,9 @@
 //! [`Operand`](operand::Operand)s, [`Condition`](condition::Condition)s and
 //! [`Shift`](shift::Shift)s composed in to
 //! [`Operation`](operation::Operation)s. Which in turn can be composed in to
-//! meta instructions that describe more complex instructions.
+//! meta instructions that describe more complex instructions. Registers are
+//! referred to by [`Register`](register::Register)s from the
+//! [`RegisterSet`](register::RegisterSet) of the architecture.
 
 #![deny(warnings)]
 #![deny(clippy::all)]
@@ -15,6 +17,7 @@
 pub mod condition;
 pub mod operand;
 pub mod operation;
+pub mod register;
 pub mod shift;
 
 /// Re-exports the main exports of this crate.
@@ -23,6 +26,7 @@ pub mod prelude {
         condition::Condition,
         operand::{DataHalfWord, DataWord, Operand},
         operation::Operation,
+        register::Register,
         shift::Shift,
     };
 }
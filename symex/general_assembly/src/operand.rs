@@ -1,6 +1,8 @@
 //! Defines all types of operands that are valid in [Symex](../../../) General
 //! Assembly.
 
+use crate::register::Register;
+
 #[derive(Debug, Clone, Copy)]
 /// [Symex](../../../) representation for immediate fields.
 #[allow(missing_docs)]
@@ -37,7 +39,7 @@ impl From<DataHalfWord> for DataWord {
 #[derive(Debug, Clone)]
 pub enum Operand {
     /// Representing a value in a register.
-    Register(String),
+    Register(Register),
 
     /// Representing an immediate value.
     Immediate(DataWord),
@@ -58,7 +60,7 @@ pub enum Operand {
     #[allow(missing_docs)]
     AddressWithOffset {
         address: DataWord,
-        offset_reg: String,
+        offset_reg: Register,
         width: u32,
     },
 
@@ -66,7 +68,7 @@ pub enum Operand {
     Local(String),
 
     /// Represents a flag in the core.
-    Flag(String),
+    Flag(Register),
 }
 
 impl From<u64> for DataWord {
@@ -0,0 +1,237 @@
+//! Defines the registers used in [Symex](../../../) General Assembly.
+//!
+//! Each architecture declares its registers, including the pseudo registers
+//! that its translations use to hook reads and writes, in a [`RegisterSet`].
+//! The registers that the executor itself uses, the program counter, the
+//! stack pointer, the link register and the flags, are shared by all sets and
+//! come first. A [`Register`] is an index in to the set of its architecture
+//! so it can be copied, compared and used as an index without looking at the
+//! name. Registers named in a translation are resolved when the code is
+//! compiled with [`RegisterSet::named`].
+
+use std::{
+    fmt,
+    hash::{Hash, Hasher},
+};
+
+/// Names of the registers that are shared by all architectures, in the order
+/// of their indices.
+const SHARED: [&str; 7] = ["PC", "SP", "LR", "N", "Z", "C", "V"];
+
+/// A register in the [`RegisterSet`] of an architecture.
+///
+/// Registers are compared by their index, registers from the sets of two
+/// different architectures should not be mixed.
+#[derive(Clone, Copy)]
+pub struct Register {
+    index: u8,
+    name: &'static str,
+}
+
+impl Register {
+    /// The carry flag.
+    pub const C: Self = Self::shared(5);
+    /// The flags, in the order of their indices.
+    pub const FLAGS: [Self; 4] = [Self::N, Self::Z, Self::C, Self::V];
+    /// The link register.
+    pub const LR: Self = Self::shared(2);
+    /// The negative flag.
+    pub const N: Self = Self::shared(3);
+    /// The program counter.
+    pub const PC: Self = Self::shared(0);
+    /// The stack pointer.
+    pub const SP: Self = Self::shared(1);
+    /// The overflow flag.
+    pub const V: Self = Self::shared(6);
+    /// The zero flag.
+    pub const Z: Self = Self::shared(4);
+
+    const fn shared(index: usize) -> Self {
+        Self {
+            index: index as u8,
+            name: SHARED[index],
+        }
+    }
+
+    /// The index of the register in its set, all indices are smaller than
+    /// the length of the set.
+    pub const fn index(&self) -> usize {
+        self.index as usize
+    }
+
+    /// The name of the register.
+    pub const fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// Checks if the register is one of the flags.
+    pub const fn is_flag(&self) -> bool {
+        self.index >= Self::N.index && self.index <= Self::V.index
+    }
+}
+
+impl PartialEq for Register {
+    fn eq(&self, other: &Self) -> bool {
+        self.index == other.index
+    }
+}
+
+impl Eq for Register {}
+
+impl Hash for Register {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.index.hash(state);
+    }
+}
+
+impl PartialOrd for Register {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Register {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.index.cmp(&other.index)
+    }
+}
+
+impl fmt::Debug for Register {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}", self.name)
+    }
+}
+
+impl fmt::Display for Register {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name)
+    }
+}
+
+/// The registers of an architecture.
+///
+/// The set holds the registers shared by all architectures followed by the
+/// registers declared by the architecture.
+#[derive(Debug)]
+pub struct RegisterSet {
+    names: &'static [&'static str],
+}
+
+impl RegisterSet {
+    /// A set of the shared registers followed by the registers in `names`.
+    ///
+    /// # Panics
+    ///
+    /// If a name is declared twice or if there are more than 256 registers.
+    pub const fn new(names: &'static [&'static str]) -> Self {
+        assert!(SHARED.len() + names.len() <= 256, "too many registers");
+        let set = Self { names };
+        let mut index = 0;
+        while index < names.len() {
+            let mut other = 0;
+            while other < SHARED.len() + index {
+                assert!(
+                    !equal(set.name(other), names[index]),
+                    "register declared twice"
+                );
+                other += 1;
+            }
+            index += 1;
+        }
+        set
+    }
+
+    /// The number of registers in the set.
+    pub const fn len(&self) -> usize {
+        SHARED.len() + self.names.len()
+    }
+
+    /// Checks if the set is empty, it never is as the shared registers are in
+    /// all sets.
+    pub const fn is_empty(&self) -> bool {
+        false
+    }
+
+    /// The register called `name`.
+    ///
+    /// This is meant to be evaluated at compile time, in a constant or a
+    /// `const` block, where an unknown name is a compile error.
+    ///
+    /// # Panics
+    ///
+    /// If there is no register called `name`, use [`RegisterSet::get`] for
+    /// names that might not be registers.
+    pub const fn named(&self, name: &str) -> Register {
+        match self.get(name) {
+            Some(register) => register,
+            None => panic!("unknown register"),
+        }
+    }
+
+    /// The registers called `names`, in the same order.
+    ///
+    /// # Panics
+    ///
+    /// If one of the names is not a register.
+    pub const fn all_named<const N: usize>(&self, names: [&str; N]) -> [Register; N] {
+        let mut registers = [Register::PC; N];
+        let mut index = 0;
+        while index < N {
+            registers[index] = self.named(names[index]);
+            index += 1;
+        }
+        registers
+    }
+
+    /// The register called `name` if there is one.
+    pub const fn get(&self, name: &str) -> Option<Register> {
+        let mut index = 0;
+        while index < self.len() {
+            if equal(self.name(index), name) {
+                return Some(self.register(index));
+            }
+            index += 1;
+        }
+        None
+    }
+
+    /// The register at `index`.
+    ///
+    /// # Panics
+    ///
+    /// If `index` is not smaller than the length of the set.
+    pub const fn register(&self, index: usize) -> Register {
+        Register {
+            index: index as u8,
+            name: self.name(index),
+        }
+    }
+
+    /// All registers, in the order of their indices.
+    pub fn iter(&self) -> impl Iterator<Item = Register> + '_ {
+        (0..self.len()).map(|index| self.register(index))
+    }
+
+    const fn name(&self, index: usize) -> &'static str {
+        match index < SHARED.len() {
+            true => SHARED[index],
+            false => self.names[index - SHARED.len()],
+        }
+    }
+}
+
+/// Compares two strings in a constant context.
+const fn equal(lhs: &str, rhs: &str) -> bool {
+    let (lhs, rhs) = (lhs.as_bytes(), rhs.as_bytes());
+    if lhs.len() != rhs.len() {
+        return false;
+    }
+    let mut index = 0;
+    while index < lhs.len() {
+        if lhs[index] != rhs[index] {
+            return false;
+        }
+        index += 1;
+    }
+    true
+}
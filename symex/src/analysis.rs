@@ -0,0 +1,610 @@
+//! Front-end that runs an analysis on either LLVM bitcode or a binary.
+//!
+//! [`Analysis`] selects the executor from the [`Artifact`] it is given and
+//! reports each path as a [`PathReport`], so a tool does not need a separate
+//! code path for each executor.
+//!
+//! The artifact can be given explicitly or found from the contents of a file
+//! with [`Artifact::detect`]. Options that only one executor supports, such as
+//! snapshots for ELF files, are rejected with [`RunError::Unsupported`] for
+//! the other.
+use core::fmt::{self, Write as _};
+use std::{
+    fs::File,
+    io::{self, Read, Write},
+    path::{Path, PathBuf},
+    time::Instant,
+};
+
+use colored::*;
+use indenter::indented;
+
+use crate::{
+    elf_util::{self, PathStatus, Variable},
+    general_assembly::{
+        project::{snapshot::Snapshot, target_config::TargetConfiguration},
+        replay::TraceConfig,
+        state::ConcretizedBranch,
+        GAError,
+        DEFAULT_MAX_BRANCH_TARGETS,
+    },
+    run_elf::{self, ElfConfig, Entry, ImageOptions},
+};
+#[cfg(feature = "llvm")]
+use crate::{
+    run_llvm::{self, RunConfig},
+    util,
+    vm::{self, LLVMExecutorError, MemoryModel},
+};
+
+/// The program to analyze.
+#[derive(Debug, Clone)]
+pub enum Artifact {
+    /// An ELF file, the architecture is read from the file.
+    Elf(PathBuf),
+
+    /// A raw binary, Intel HEX or S-record image, see
+    /// [`ElfConfig::image`].
+    Image(PathBuf, ImageOptions),
+
+    /// LLVM bitcode files which are linked together before the analysis, see
+    /// [`run_modules`](run_llvm::run_modules).
+    #[cfg(feature = "llvm")]
+    Bitcode(Vec<PathBuf>),
+}
+
+impl Artifact {
+    /// Determines from the contents of the file at `path` if it is an ELF file
+    /// or LLVM bitcode.
+    pub fn detect(path: impl AsRef<Path>) -> Result<Self, RunError> {
+        let path = path.as_ref();
+        let unknown =
+            |reason: String| RunError::UnknownArtifact(path.display().to_string(), reason);
+
+        let mut magic = [0; 4];
+        File::open(path)
+            .and_then(|mut file| file.read_exact(&mut magic))
+            .map_err(|e| unknown(e.to_string()))?;
+
+        match magic {
+            [0x7f, b'E', b'L', b'F'] => Ok(Artifact::Elf(path.to_owned())),
+            // Raw bitcode, or bitcode in a wrapper.
+            #[cfg(feature = "llvm")]
+            [b'B', b'C', 0xc0, 0xde] | [0xde, 0xc0, 0x17, 0x0b] => {
+                Ok(Artifact::Bitcode(vec![path.to_owned()]))
+            }
+            #[cfg(not(feature = "llvm"))]
+            [b'B', b'C', 0xc0, 0xde] | [0xde, 0xc0, 0x17, 0x0b] => Err(RunError::Unsupported(
+                "LLVM bitcode requires the llvm feature",
+            )),
+            _ => Err(unknown("not an ELF file or LLVM bitcode".to_owned())),
+        }
+    }
+}
+
+/// Limits on how far the executors fork a path.
+#[derive(Debug, Clone)]
+pub struct Limits {
+    /// Maximum number of targets a branch to a symbolic address is forked to,
+    /// only used for binaries. Default is [`DEFAULT_MAX_BRANCH_TARGETS`].
+    pub max_branch_targets: usize,
+
+    /// Maximum amount of concretizations for memmove, memcpy, memset and other
+    /// intrisic functions, only used for bitcode. Default is `20`.
+    pub max_intrinsic_concretizations: usize,
+
+    /// Maximum amount of concretizations for the number of elements in an
+    /// `alloca`, only used for bitcode. Default is `20`.
+    pub max_alloca_concretizations: usize,
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Self {
+            max_branch_targets: DEFAULT_MAX_BRANCH_TARGETS,
+            max_intrinsic_concretizations: 20,
+            max_alloca_concretizations: 20,
+        }
+    }
+}
+
+/// Determine for which types of paths the solver should be invoked on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SolveFor {
+    /// All paths.
+    All,
+
+    /// Paths which return errors. Currently this is both internal executor
+    /// errors and program errors.
+    Error,
+
+    /// Paths which are sucessful.
+    Success,
+}
+
+/// Which paths are reported and which of their values are solved.
+///
+/// Paths of a binary always have their symbolic values and end state solved,
+/// only `paths` is used for them.
+#[derive(Debug, Clone)]
+pub struct SolvePolicy {
+    /// Which paths are solved and reported. Default is [`SolveFor::All`].
+    pub paths: SolveFor,
+
+    /// If the inputs should be solved. Default is `true`.
+    pub inputs: bool,
+
+    /// If the values passed to `symbolic` should be solved. Default is `true`.
+    pub symbolics: bool,
+
+    /// If the return value should be solved. Default is `true`.
+    pub output: bool,
+}
+
+impl Default for SolvePolicy {
+    fn default() -> Self {
+        Self {
+            paths: SolveFor::All,
+            inputs: true,
+            symbolics: true,
+            output: true,
+        }
+    }
+}
+
+impl SolvePolicy {
+    fn should_report(&self, status: &PathStatus) -> bool {
+        match self.paths {
+            SolveFor::All => true,
+            SolveFor::Error => matches!(status, PathStatus::Failed(_)),
+            SolveFor::Success => matches!(status, PathStatus::Ok(_)),
+        }
+    }
+}
+
+/// Where the execution starts.
+#[derive(Debug, Clone)]
+enum EntryPoint {
+    Function(String),
+    Snapshot(Snapshot),
+}
+
+/// An analysis of an [`Artifact`], configured with the builder methods and
+/// started by [`Analysis::run`].
+pub struct Analysis {
+    artifact: Artifact,
+    entry: EntryPoint,
+    limits: Limits,
+    solve: SolvePolicy,
+    target: Option<TargetConfiguration>,
+    merge_paths: bool,
+    trace: Option<TraceConfig>,
+    #[cfg(feature = "llvm")]
+    memory_model: MemoryModel,
+    output: Option<Box<dyn Write>>,
+}
+
+impl Analysis {
+    /// Creates an analysis of `artifact` that starts at `main`, with the
+    /// default limits and solving policy and without any output.
+    pub fn new(artifact: Artifact) -> Self {
+        Self {
+            artifact,
+            entry: EntryPoint::Function("main".to_owned()),
+            limits: Limits::default(),
+            solve: SolvePolicy::default(),
+            target: None,
+            merge_paths: false,
+            trace: None,
+            #[cfg(feature = "llvm")]
+            memory_model: MemoryModel::Object,
+            output: None,
+        }
+    }
+
+    /// Starts the execution at `function`, for binaries this can also be an
+    /// address.
+    pub fn function(mut self, function: impl Into<String>) -> Self {
+        self.entry = EntryPoint::Function(function.into());
+        self
+    }
+
+    /// Starts the execution from the registers and memory in `snapshot`, only
+    /// supported for ELF files.
+    pub fn snapshot(mut self, snapshot: Snapshot) -> Self {
+        self.entry = EntryPoint::Snapshot(snapshot);
+        self
+    }
+
+    /// Sets the limits on forking.
+    pub fn limits(mut self, limits: Limits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// Sets which paths are reported and which values are solved.
+    pub fn solve(mut self, solve: SolvePolicy) -> Self {
+        self.solve = solve;
+        self
+    }
+
+    /// Sets the target used when counting cycles of a binary.
+    pub fn target(mut self, target: TargetConfiguration) -> Self {
+        self.target = Some(target);
+        self
+    }
+
+    /// Merges paths of a binary where they rejoin, see
+    /// [`ElfConfig::merge_paths`].
+    pub fn merge_paths(mut self, merge_paths: bool) -> Self {
+        self.merge_paths = merge_paths;
+        self
+    }
+
+    /// Replays each path of a binary and writes its trace, see
+    /// [`ElfConfig::trace`].
+    pub fn trace(mut self, trace: TraceConfig) -> Self {
+        self.trace = Some(trace);
+        self
+    }
+
+    /// Sets the memory model used for bitcode.
+    #[cfg(feature = "llvm")]
+    pub fn memory_model(mut self, memory_model: MemoryModel) -> Self {
+        self.memory_model = memory_model;
+        self
+    }
+
+    /// Writes each reported path and a summary to `output`.
+    pub fn output(mut self, output: impl Write + 'static) -> Self {
+        self.output = Some(Box::new(output));
+        self
+    }
+
+    /// Runs the analysis and returns the reported paths.
+    pub fn run(mut self) -> Result<Vec<PathReport>, RunError> {
+        let start = Instant::now();
+        let output = self.output.take();
+
+        let reports = match self.artifact.clone() {
+            Artifact::Elf(path) => self.run_binary(path, None)?,
+            Artifact::Image(path, options) => self.run_binary(path, Some(options))?,
+            #[cfg(feature = "llvm")]
+            Artifact::Bitcode(paths) => self.run_bitcode(paths)?,
+        };
+
+        if let Some(mut output) = output {
+            for report in reports.iter() {
+                writeln!(output, "{report}")?;
+            }
+            writeln!(
+                output,
+                "Paths: {}, took: {:?}",
+                reports.len(),
+                start.elapsed()
+            )?;
+            output.flush()?;
+        }
+        Ok(reports)
+    }
+
+    fn run_binary(
+        &mut self,
+        path: PathBuf,
+        image: Option<ImageOptions>,
+    ) -> Result<Vec<PathReport>, RunError> {
+        let entry = match &self.entry {
+            EntryPoint::Function(function) => Entry::Function(function),
+            EntryPoint::Snapshot(_) if image.is_some() => {
+                return Err(RunError::Unsupported(
+                    "snapshots are only supported for ELF files",
+                ))
+            }
+            EntryPoint::Snapshot(snapshot) => Entry::Snapshot(snapshot),
+        };
+        let cfg = ElfConfig {
+            image,
+            target: self.target.take(),
+            show_path_results: false,
+            merge_paths: self.merge_paths,
+            trace: self.trace.take(),
+            max_branch_targets: self.limits.max_branch_targets,
+        };
+        let results = run_elf::run_elf(path, entry, cfg)?;
+        Ok(self.reports(results))
+    }
+
+    #[cfg(feature = "llvm")]
+    fn run_bitcode(&mut self, paths: Vec<PathBuf>) -> Result<Vec<PathReport>, RunError> {
+        let function = match &self.entry {
+            EntryPoint::Function(function) => function,
+            EntryPoint::Snapshot(_) => {
+                return Err(RunError::Unsupported(
+                    "snapshots are only supported for ELF files",
+                ))
+            }
+        };
+        if self.merge_paths || self.trace.is_some() || self.target.is_some() {
+            return Err(RunError::Unsupported(
+                "merging paths, tracing and targets are only supported for binaries",
+            ));
+        }
+
+        let cfg = RunConfig {
+            solve_for: self.solve.paths,
+            solve_inputs: self.solve.inputs,
+            solve_symbolics: self.solve.symbolics,
+            solve_output: self.solve.output,
+            show_path_results: false,
+        };
+        let vm_cfg = vm::Config {
+            max_intrinsic_concretizations: self.limits.max_intrinsic_concretizations,
+            max_alloca_concretizations: self.limits.max_alloca_concretizations,
+            memory_model: self.memory_model,
+            ..vm::Config::default()
+        };
+        let results = run_llvm::run_modules_with_config(paths, function, &cfg, vm_cfg)?;
+        Ok(results.into_iter().map(PathReport::from).collect())
+    }
+
+    /// Keeps the results of a binary that should be reported.
+    fn reports(&self, results: Vec<elf_util::VisualPathResult>) -> Vec<PathReport> {
+        results
+            .into_iter()
+            .filter(|result| self.solve.should_report(&result.result))
+            .map(PathReport::from)
+            .collect()
+    }
+}
+
+/// Result of a single path, for both bitcode and binaries.
+///
+/// The values that are specific to an executor are empty or `None` for the
+/// other one. The results of the executors are displayed through this, so
+/// all paths are formatted the same way.
+#[derive(Debug)]
+pub struct PathReport {
+    /// Which path this is.
+    pub path: usize,
+
+    /// The return value of the path or why it failed.
+    pub result: PathStatus,
+
+    /// Input variables passed to the analyzed function, only for bitcode.
+    pub inputs: Vec<Variable>,
+
+    /// Variables explicitly marked as symbolic.
+    pub symbolics: Vec<Variable>,
+
+    /// All register values at the end of execution, only for binaries.
+    pub end_state: Vec<Variable>,
+
+    /// The number of instructions executed in the path, only for binaries.
+    pub instruction_count: Option<usize>,
+
+    /// The maximum number of clock cycles the path can take, only for
+    /// binaries.
+    pub max_cycles: Option<usize>,
+
+    /// Cycle counts at marked events.
+    pub cycle_laps: Vec<(usize, String)>,
+
+    /// The number of bytes of stack used by the path, only for binaries.
+    pub stack_usage: Option<u64>,
+
+    /// Branches to symbolic addresses that were resolved to a concrete target
+    /// on this path.
+    pub concretized_branches: Vec<ConcretizedBranch>,
+}
+
+impl PathReport {
+    /// Returns true if the path ended in an error.
+    pub fn failed(&self) -> bool {
+        matches!(self.result, PathStatus::Failed(_))
+    }
+}
+
+impl From<elf_util::VisualPathResult> for PathReport {
+    fn from(result: elf_util::VisualPathResult) -> Self {
+        let initial_sp = result.initial_sp;
+        let stack_usage = result
+            .stack_usage
+            .map(|stack| initial_sp - stack.into_iter().min().unwrap_or(initial_sp));
+
+        PathReport {
+            path: result.path,
+            result: result.result,
+            inputs: Vec::new(),
+            symbolics: result.symbolics,
+            end_state: result.end_state,
+            instruction_count: Some(result.instruction_count),
+            max_cycles: Some(result.max_cycles),
+            cycle_laps: result.cycle_laps,
+            stack_usage,
+            concretized_branches: result.concretized_branches,
+        }
+    }
+}
+
+#[cfg(feature = "llvm")]
+impl From<util::VisualPathResult> for PathReport {
+    fn from(result: util::VisualPathResult) -> Self {
+        PathReport {
+            path: result.path,
+            result: result.result,
+            inputs: result.inputs,
+            symbolics: result.symbolics,
+            end_state: Vec::new(),
+            instruction_count: None,
+            max_cycles: None,
+            cycle_laps: Vec::new(),
+            stack_usage: None,
+            concretized_branches: Vec::new(),
+        }
+    }
+}
+
+impl fmt::Display for PathReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━ PATH {} ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━",
+            self.path
+        )?;
+
+        match &self.result {
+            PathStatus::Ok(None) => {
+                writeln!(f, "{}: returned void", "Success".green())?;
+            }
+            PathStatus::Ok(Some(value)) => {
+                writeln!(f, "{}: returned {}", "Success".green(), value)?;
+            }
+            PathStatus::Failed(err) => write!(f, "{err}")?,
+        }
+
+        let sections = [
+            ("Symbolic", &self.symbolics),
+            ("Inputs", &self.inputs),
+            ("End state", &self.end_state),
+        ];
+        for (title, values) in sections {
+            if values.is_empty() {
+                continue;
+            }
+            writeln!(f, "\n{title}:")?;
+            for (n, value) in values.iter().enumerate() {
+                match value.name.as_ref() {
+                    Some(name) => writeln!(indented(f), "{name}: {}", value)?,
+                    None => writeln!(indented(f), "{n}: {}", value)?,
+                }
+            }
+        }
+
+        if !self.concretized_branches.is_empty() {
+            writeln!(f, "\nConcretized branches:")?;
+            for branch in self.concretized_branches.iter() {
+                let restriction = match branch.function_entries {
+                    true => " (function entries)",
+                    false => "",
+                };
+                writeln!(
+                    indented(f),
+                    "{:#X} -> {:#X}, one of {} targets{}",
+                    branch.address,
+                    branch.target,
+                    branch.targets,
+                    restriction
+                )?;
+            }
+        }
+
+        if let Some(instruction_count) = self.instruction_count {
+            writeln!(f, "Instructions executed: {instruction_count}")?;
+        }
+        if let Some(max_cycles) = self.max_cycles {
+            writeln!(f, "Max number of cycles: {max_cycles}")?;
+        }
+        if !self.cycle_laps.is_empty() {
+            writeln!(f, "Cycle laps:")?;
+            for (cycles, name) in self.cycle_laps.iter() {
+                writeln!(indented(f), "{name}: {cycles}")?;
+            }
+        }
+        if let Some(stack_usage) = self.stack_usage {
+            writeln!(f, "Stack usage: {stack_usage} bytes")?;
+        }
+        Ok(())
+    }
+}
+
+/// Errors from either executor, or from setting up the analysis.
+#[derive(Debug, thiserror::Error)]
+pub enum RunError {
+    /// The analysis of a binary failed.
+    #[error(transparent)]
+    Elf(#[from] GAError),
+
+    /// The analysis of bitcode failed.
+    #[cfg(feature = "llvm")]
+    #[error(transparent)]
+    Llvm(#[from] LLVMExecutorError),
+
+    /// The format of the artifact could not be determined.
+    #[error("Unable to determine the format of {0}: {1}")]
+    UnknownArtifact(String, String),
+
+    /// The options are not supported for the artifact.
+    #[error("Unsupported analysis: {0}")]
+    Unsupported(&'static str),
+
+    /// The reports could not be written to the output.
+    #[error("Unable to write the output: {0}")]
+    Output(#[from] io::Error),
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Analysis, Artifact, PathReport, RunError, SolveFor, SolvePolicy};
+    use crate::{elf_util::PathStatus, general_assembly::project::image::test::TestImage};
+
+    #[test]
+    fn test_analysis_image() {
+        let image = TestImage::new("analysis");
+        let artifact = Artifact::Image(image.path.clone(), image.options.clone());
+
+        let reports = Analysis::new(artifact.clone()).run().unwrap();
+        assert_eq!(reports.len(), 2);
+        assert!(reports.iter().all(|report| !report.failed()));
+        assert!(reports.iter().all(|report| report.max_cycles.is_some()));
+
+        // No path fails, so none are reported.
+        let solve = SolvePolicy {
+            paths: SolveFor::Error,
+            ..SolvePolicy::default()
+        };
+        let reports = Analysis::new(artifact).solve(solve).run().unwrap();
+        assert!(reports.is_empty());
+    }
+
+    #[test]
+    fn test_display_cycle_laps() {
+        let report = PathReport {
+            path: 1,
+            result: PathStatus::Ok(None),
+            inputs: Vec::new(),
+            symbolics: Vec::new(),
+            end_state: Vec::new(),
+            instruction_count: Some(3),
+            max_cycles: Some(7),
+            cycle_laps: vec![(2, "start".to_owned()), (5, "end".to_owned())],
+            stack_usage: None,
+            concretized_branches: Vec::new(),
+        };
+        let report = report.to_string();
+        assert!(report.contains("Max number of cycles: 7\nCycle laps:\n    start: 2\n    end: 5\n"));
+    }
+
+    #[test]
+    fn test_detect_artifact() {
+        let path = std::env::temp_dir().join(format!("symex-detect-{}", std::process::id()));
+
+        std::fs::write(&path, b"\x7fELF\x01\x01\x01").unwrap();
+        assert!(matches!(Artifact::detect(&path), Ok(Artifact::Elf(_))));
+
+        std::fs::write(&path, b"BC\xc0\xde\x35\x14").unwrap();
+        #[cfg(feature = "llvm")]
+        assert!(matches!(Artifact::detect(&path), Ok(Artifact::Bitcode(_))));
+        #[cfg(not(feature = "llvm"))]
+        assert!(matches!(
+            Artifact::detect(&path),
+            Err(RunError::Unsupported(_))
+        ));
+        std::fs::remove_file(path).unwrap();
+
+        let image = TestImage::new("detect");
+        assert!(matches!(
+            Artifact::detect(&image.path),
+            Err(RunError::UnknownArtifact(..))
+        ));
+    }
+}
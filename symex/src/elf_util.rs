@@ -7,8 +7,16 @@ use colored::*;
 use indenter::indented;
 
 use crate::{
-    general_assembly::{arch::Arch, state::GAState, GAError},
-    smt::DExpr,
+    analysis::PathReport,
+    general_assembly::{
+        arch::Arch,
+        state::{ConcretizedBranch, Event, GAState},
+        GAError,
+    },
+    smt::{
+        fp::{self, FpFormat},
+        DExpr,
+    },
 };
 
 /// Result for a single path of execution.
@@ -18,7 +26,9 @@ use crate::{
 ///
 /// All input variables and variables used in `symbolic` calls will also have
 /// solutions available.
-#[derive(Debug)]
+///
+/// It is displayed as a [`PathReport`].
+#[derive(Debug, Clone)]
 pub struct VisualPathResult {
     /// Which path this is.
     pub path: usize,
@@ -44,11 +54,19 @@ pub struct VisualPathResult {
     /// cycle counts at marked events
     pub cycle_laps: Vec<(usize, String)>,
 
+    /// Events recorded by hooks with their cycle counts, see
+    /// [`GAState::record_event`].
+    pub events: Vec<(usize, Event)>,
+
     /// All of the stack pointer writes.
     pub stack_usage: Option<HashSet<u64>>,
 
     /// The initial stack pointer for this path.
     pub initial_sp: u64,
+
+    /// Branches to symbolic addresses that were resolved to a concrete target
+    /// on this path.
+    pub concretized_branches: Vec<ConcretizedBranch>,
 }
 
 fn elf_get_values<'a, I>(vars: I, state: &GAState<impl Arch>) -> Result<Vec<Variable>, GAError>
@@ -81,7 +99,7 @@ impl VisualPathResult {
             .registers
             .iter()
             .map(|(reg_name, value)| Variable {
-                name: Some(reg_name.to_owned()),
+                name: Some(reg_name.to_string()),
                 value: value.to_owned(),
                 ty: ExpressionType::Integer(state.project.get_word_size() as usize),
             })
@@ -98,64 +116,15 @@ impl VisualPathResult {
             initial_sp: state.inital_sp,
             max_cycles: state.cycle_count,
             cycle_laps: state.cycle_laps.clone(),
+            events: state.events.clone(),
+            concretized_branches: state.concretized_branches.clone(),
         })
     }
 }
 
 impl fmt::Display for VisualPathResult {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        writeln!(
-            f,
-            "━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━ PATH {} ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━",
-            self.path
-        )?;
-
-        match &self.result {
-            PathStatus::Ok(None) => {
-                writeln!(f, "{}: returned void", "Success".green())?;
-            }
-            PathStatus::Ok(Some(value)) => {
-                writeln!(f, "{}: returned {}", "Success".green(), value)?;
-            }
-            PathStatus::Failed(err) => {
-                writeln!(f, "{}: {}", "Error".red(), err.error_message)?;
-            }
-        }
-
-        if !self.symbolics.is_empty() {
-            writeln!(f, "\nSymbolic:")?;
-            for value in self.symbolics.iter() {
-                let name = if let Some(name) = value.name.as_ref() {
-                    name
-                } else {
-                    "_"
-                };
-                writeln!(indented(f), "{name}: {}", value)?;
-            }
-        }
-
-        if !self.end_state.is_empty() {
-            writeln!(f, "\nEnd state:")?;
-            for value in self.end_state.iter() {
-                let name = if let Some(name) = value.name.as_ref() {
-                    name
-                } else {
-                    "_"
-                };
-                writeln!(indented(f), "{name}: {}", value)?;
-            }
-        }
-
-        writeln!(f, "Instructions executed: {}", self.instruction_count)?;
-
-        writeln!(f, "Max number of cycles: {}", self.max_cycles)?;
-
-        if let Some(stack) = &self.stack_usage {
-            let min = stack.iter().min().unwrap_or(&self.initial_sp);
-            writeln!(f, "Stack usage: {} bytes", self.initial_sp - min)?;
-        }
-
-        Ok(())
+        write!(f, "{}", PathReport::from(self.clone()))
     }
 }
 
@@ -181,6 +150,35 @@ pub enum PathStatus {
 pub struct ErrorReason {
     /// Error message from the received error.
     pub error_message: String,
+
+    /// For which line in the execution the error was encountered.
+    pub error_location: Option<String>,
+
+    /// The stack trace to where the error was encountered.
+    ///
+    /// The stack trace is in the order of the innermost call to the outermost,
+    /// it is empty when the executor does not track the call stack.
+    pub stack_trace: Vec<LineTrace>,
+}
+
+impl fmt::Display for ErrorReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{}: {}", "Error".red(), self.error_message)?;
+        if let Some(error_location) = &self.error_location {
+            writeln!(indented(f), "at {error_location}\n")?;
+        }
+
+        if !self.stack_trace.is_empty() {
+            writeln!(f, "Stacktrace:")?;
+        }
+        for (n, line) in self.stack_trace.iter().enumerate() {
+            writeln!(f, "{n:4}: {}", line.function_name)?;
+            if let Some(line) = &line.line {
+                writeln!(indented(f), "at {line}")?;
+            }
+        }
+        Ok(())
+    }
 }
 
 /// One line in the stack trace. Contains the name of the function and the line
@@ -232,8 +230,8 @@ pub enum ExpressionType {
     /// Integer value of a certain size in bits.
     Integer(usize),
 
-    /// Floating point of a certain size in bits.
-    Float(usize),
+    /// Floating point of a certain format.
+    Float(FpFormat),
 
     /// Array or vector of a certain type with a specific number of values.
     Array(Box<ExpressionType>, usize),
@@ -249,7 +247,7 @@ impl ExpressionType {
     fn size_in_bits(&self) -> Option<usize> {
         match self {
             ExpressionType::Integer(bits) => Some(*bits),
-            ExpressionType::Float(bits) => Some(*bits),
+            ExpressionType::Float(format) => Some(format.bits() as usize),
             ExpressionType::Array(e, n) => {
                 let element_size = e.size_in_bits()?;
                 Some(*n * element_size)
@@ -271,7 +269,7 @@ impl ExpressionType {
                 assert!(raw.len() == *bits);
                 Some(TypedVariable::Integer(raw, *bits))
             }
-            ExpressionType::Float(bits) => Some(TypedVariable::Float(raw, *bits)),
+            ExpressionType::Float(format) => Some(TypedVariable::Float(raw, *format)),
             ExpressionType::Array(ty, num_elements) => {
                 let mut vars = Vec::with_capacity(*num_elements);
                 let size = ty.size_in_bits()?;
@@ -315,8 +313,8 @@ enum TypedVariable<'a> {
     /// Integer value of a certain size in bits.
     Integer(&'a str, usize),
 
-    /// Floating point of a certain size in bits.
-    Float(&'a str, usize),
+    /// Floating point of a certain format.
+    Float(&'a str, FpFormat),
 
     /// Array or vector of a certain type with a specific number of values.
     Array(Vec<TypedVariable<'a>>),
@@ -354,17 +352,26 @@ impl<'a> fmt::Display for TypedVariable<'a> {
                     }
                 }
             }
-            Float(value, bits) => match bits {
-                32 => {
+            Float(value, format) => match *format {
+                FpFormat::SINGLE => {
                     let value = u32::from_str_radix(value, 2).unwrap();
                     let value = f32::from_bits(value);
                     write!(f, "{value} (f32)")
                 }
-                64 => {
+                FpFormat::DOUBLE => {
                     let value = u64::from_str_radix(value, 2).unwrap();
                     let value = f64::from_bits(value);
                     write!(f, "{value} (f64)")
                 }
+                FpFormat::HALF | FpFormat::BFLOAT => {
+                    let name = match *format == FpFormat::HALF {
+                        true => "f16",
+                        false => "bf16",
+                    };
+                    let value = u64::from_str_radix(value, 2).unwrap();
+                    let value = fp::to_f64(*format, value);
+                    write!(f, "{value} ({name})")
+                }
                 _ => {
                     write!(f, "{value} (float)")
                 }
@@ -399,6 +406,7 @@ impl<'a> fmt::Display for TypedVariable<'a> {
 #[cfg(test)]
 mod tests {
     use super::TypedVariable;
+    use crate::smt::fp::FpFormat;
 
     #[test]
     fn i64_works() {
@@ -414,4 +422,23 @@ mod tests {
         let s = format!("{typed_variable}");
         assert_eq!(s, "0b1 (1-bit)");
     }
+
+    #[test]
+    fn floats_work() {
+        // -1.5 = 0xbfc00000
+        let typed_variable =
+            TypedVariable::Float("10111111110000000000000000000000", FpFormat::SINGLE);
+        let s = format!("{typed_variable}");
+        assert_eq!(s, "-1.5 (f32)");
+
+        // 0.333 = 0x3555
+        let typed_variable = TypedVariable::Float("0011010101010101", FpFormat::HALF);
+        let s = format!("{typed_variable}");
+        assert_eq!(s, "0.333251953125 (f16)");
+
+        // 1.5 = 0x3fc0 as a bfloat, but 1.937... as a half.
+        let typed_variable = TypedVariable::Float("0011111111000000", FpFormat::BFLOAT);
+        let s = format!("{typed_variable}");
+        assert_eq!(s, "1.5 (bf16)");
+    }
 }
@@ -6,17 +6,34 @@
 //! Moreover the architecture may define a few
 //! architecture specific hooks.
 
+pub mod aarch64;
 pub mod arm;
+pub mod riscv;
 use std::{
     collections::HashSet,
     fmt::{Debug, Display},
 };
 
-use arm::{v6::ArmV6M, v7::ArmV7EM};
+use aarch64::AArch64;
+use arm::{v6::ArmV6M, v7::ArmV7EM, v8::ArmV8M};
+use general_assembly::register::{Register, RegisterSet};
 use object::File;
+use riscv::RiscV32IMAC;
 use thiserror::Error;
+use tracing::trace;
 
-use crate::general_assembly::{instruction::Instruction, state::GAState, RunConfig};
+use crate::{
+    elf_util::{ExpressionType, Variable},
+    general_assembly::{
+        instruction::Instruction,
+        project::target_config::TargetConfiguration,
+        state::GAState,
+        Endianness,
+        GAError,
+        Result as GAResult,
+        RunConfig,
+    },
+};
 
 #[derive(Debug, Eq, PartialEq, PartialOrd, Clone, Error)]
 /// General architecture related errors.
@@ -33,6 +50,17 @@ pub enum ArchError {
     #[error("Tried to read a malformed section.")]
     MalformedSection,
 
+    /// Thrown when the target configuration describes a core of another
+    /// architecture.
+    #[error("The target core can not execute code for this architecture.")]
+    IncompatibleTarget,
+
+    /// Thrown when an instruction is not in the instruction set of the
+    /// architecture profile, such as a Mainline instruction in Armv8-M
+    /// Baseline.
+    #[error("The instruction is not available in this architecture profile.")]
+    UnsupportedInstruction,
+
     /// Thrown when a specific required section does not exist in the binary
     #[error("Elf file missing critical section {0}.")]
     MissingSection(&'static str),
@@ -78,9 +106,45 @@ pub enum ParseError {
     Generic(&'static str),
 }
 
+/// Writes a new symbolic value to the memory at the address in `pointer`, with
+/// the size in bytes in `size`.
+///
+/// This is the body of the hooks for `symbolic_size` and `symex_symbolic`, the
+/// architectures pass the registers of the first two arguments and return to
+/// the caller themselves.
+pub fn make_symbolic<A: Arch>(
+    state: &mut GAState<A>,
+    pointer: Register,
+    size: Register,
+) -> GAResult<()> {
+    let value_ptr = state.get_register(pointer)?;
+    let size = state
+        .get_register(size)?
+        .get_constant()
+        .ok_or(GAError::SymbolicSize)?
+        * 8;
+    trace!(
+        "trying to create symbolic: addr: {:?}, size: {}",
+        value_ptr,
+        size
+    );
+    let name = "any".to_owned() + &state.marked_symbolic.len().to_string();
+    let symb_value = state.new_input(size as u32, &name);
+    state.marked_symbolic.push(Variable {
+        name: Some(name),
+        value: symb_value.clone(),
+        ty: ExpressionType::Integer(size as usize),
+    });
+    state.memory.write(&value_ptr, symb_value)?;
+    Ok(())
+}
+
 pub enum Architecture {
     Armv7EM(ArmV7EM),
     Armv6EM(ArmV6M),
+    Armv8M(ArmV8M),
+    RiscV32IMAC(RiscV32IMAC),
+    AArch64(AArch64),
 }
 
 /// A generic architecture
@@ -88,6 +152,10 @@ pub enum Architecture {
 /// Denotes that the implementer can be treated as an architecture in this
 /// crate.
 pub trait Arch: Debug + Display + Clone + Sized + 'static {
+    /// The registers of the architecture, including the pseudo registers its
+    /// translations use.
+    const REGISTERS: &'static RegisterSet;
+
     /// Converts a slice of bytes to an [`Instruction`]
     fn translate(&self, buff: &[u8], state: &GAState<Self>)
         -> Result<Instruction<Self>, ArchError>;
@@ -99,7 +167,99 @@ pub trait Arch: Debug + Display + Clone + Sized + 'static {
     /// specific architecture.
     fn discover(file: &File) -> Result<Option<Self>, ArchError>;
 
+    /// Returns the target used when no target configuration is supplied.
+    fn default_target() -> TargetConfiguration;
+
     fn get_stack_pointers(&self) -> Option<HashSet<u64>> {
         None
     }
+
+    /// Combines the architecture specific state of two paths that are merged.
+    fn merge(&mut self, _other: &Self) {}
+
+    /// The initial stack pointer used when the binary does not define
+    /// `_stack_start`, such as binaries for hosted targets.
+    fn default_stack_start(&self) -> Option<u64> {
+        None
+    }
+
+    /// Describes the instruction at the start of `buff`, used in the traces
+    /// of replayed paths.
+    fn disassemble(&self, _buff: &[u8]) -> Option<String> {
+        None
+    }
+}
+
+/// Finds the file attributes of `vendor` in a build attributes section, the
+/// format shared by `.ARM.attributes` and `.riscv.attributes`.
+///
+/// The layout is described in the
+/// [ARM ELF ABI](https://github.com/ARM-software/abi-aa/blob/main/addenda32/addenda32.rst#build-attributes).
+/// The lengths are stored in the byte order of the file.
+fn file_attributes<'a>(
+    data: &'a [u8],
+    vendor: &[u8],
+    endianness: Endianness,
+) -> Result<Option<&'a [u8]>, ArchError> {
+    let mut data = match data.split_first() {
+        Some((b'A', data)) => data,
+        _ => return Err(ArchError::MalformedSection),
+    };
+    while !data.is_empty() {
+        let (subsection, rest) = split_length(data, endianness)?;
+        data = rest;
+        let (name, mut sub_subsections) = ntbs(subsection)?;
+        if name != vendor {
+            continue;
+        }
+        while let Some((tag, rest)) = sub_subsections.split_first() {
+            // The length of a sub-subsection includes the tag in front of it.
+            let length = endianness
+                .read_u32(rest)
+                .ok_or(ArchError::MalformedSection)? as usize;
+            if length < 5 || length > sub_subsections.len() {
+                return Err(ArchError::MalformedSection);
+            }
+            let attributes = &sub_subsections[5..length];
+            sub_subsections = &sub_subsections[length..];
+            // Only the attributes that apply to the whole file are used.
+            if *tag == 1 {
+                return Ok(Some(attributes));
+            }
+        }
+    }
+    Ok(None)
+}
+
+/// Splits a section that starts with its length, including the length itself,
+/// from `data`.
+fn split_length(data: &[u8], endianness: Endianness) -> Result<(&[u8], &[u8]), ArchError> {
+    let length = endianness
+        .read_u32(data)
+        .ok_or(ArchError::MalformedSection)? as usize;
+    if length < 4 || length > data.len() {
+        return Err(ArchError::MalformedSection);
+    }
+    Ok((&data[4..length], &data[length..]))
+}
+
+/// Splits a null terminated string from `data`, the string does not include
+/// the terminator.
+fn ntbs(data: &[u8]) -> Result<(&[u8], &[u8]), ArchError> {
+    match data.iter().position(|byte| *byte == 0) {
+        Some(end) => Ok((&data[..end], &data[end + 1..])),
+        None => Err(ArchError::MalformedSection),
+    }
+}
+
+/// Splits an unsigned LEB128 value from `data`.
+fn uleb128(data: &[u8]) -> Result<(u64, &[u8]), ArchError> {
+    let mut value = 0;
+    for (i, byte) in data.iter().enumerate().take(10) {
+        value |= ((byte & 0x7f) as u64) << (7 * i);
+        if byte & 0x80 == 0 {
+            return Ok((value, &data[i + 1..]));
+        }
+    }
+    Err(ArchError::MalformedSection)
 }
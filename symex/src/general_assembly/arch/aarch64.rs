@@ -0,0 +1,126 @@
+//! Defines the AArch64 architecture, the integer subset of the A64
+//! instruction set.
+//!
+//! Data processing, loads and stores, branches and the NZCV flags are
+//! supported. Floating point, SIMD, system registers and exception levels are
+//! not.
+pub mod decoder;
+pub mod parser;
+#[cfg(test)]
+pub mod test;
+pub mod timing;
+
+use std::fmt::Display;
+
+use general_assembly::register::{Register, RegisterSet};
+use object::{elf::EM_AARCH64, read::elf::FileHeader, File};
+use regex::Regex;
+use tracing::trace;
+
+use crate::general_assembly::{
+    arch::{make_symbolic, Arch, ArchError},
+    instruction::Instruction,
+    project::{
+        target_config::{AArch64Core, CoreFamily, TargetConfiguration},
+        PCHook,
+        RegisterReadHook,
+        RegisterWriteHook,
+    },
+    run_config::RunConfig,
+    state::GAState,
+};
+
+/// The stack pointer used when the binary does not define `_stack_start`.
+pub const DEFAULT_STACK_START: u64 = 0x0000_7fff_ffff_0000;
+
+/// Type level denotation for the AArch64 ISA.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct AArch64;
+
+/// The registers of AArch64, `x30` is `LR` and `PC-` is the address of the
+/// instruction being executed.
+pub const REGISTERS: RegisterSet = RegisterSet::new(&[
+    "X0", "X1", "X2", "X3", "X4", "X5", "X6", "X7", "X8", "X9", "X10", "X11", "X12", "X13", "X14",
+    "X15", "X16", "X17", "X18", "X19", "X20", "X21", "X22", "X23", "X24", "X25", "X26", "X27",
+    "X28", "X29", "PC-",
+]);
+
+impl Arch for AArch64 {
+    const REGISTERS: &'static RegisterSet = &REGISTERS;
+
+    fn add_hooks(&self, cfg: &mut RunConfig<Self>) {
+        let symbolic_sized = |state: &mut GAState<Self>| {
+            let pointer = const { REGISTERS.named("X0") };
+            let size = const { REGISTERS.named("X1") };
+            make_symbolic(state, pointer, size)?;
+
+            let lr = state.get_register(Register::LR)?;
+            state.set_register(Register::PC, lr)?;
+            Ok(())
+        };
+
+        cfg.pc_hooks.push((
+            Regex::new(r"^(symbolic_size<.+>|symex_symbolic)$").unwrap(),
+            PCHook::Intrinsic(symbolic_sized),
+        ));
+
+        // Offsets are relative to the address of the current instruction while
+        // PC points to the next instruction.
+        let read_pc: RegisterReadHook<Self> = |state| {
+            Ok(state
+                .ctx
+                .from_u64(state.last_pc, state.project.get_word_size()))
+        };
+        let write_pc: RegisterWriteHook<Self> =
+            |state, value| state.set_register(Register::PC, value);
+
+        cfg.register_read_hooks
+            .push((REGISTERS.named("PC-"), read_pc));
+        cfg.register_write_hooks
+            .push((REGISTERS.named("PC-"), write_pc));
+    }
+
+    fn translate(
+        &self,
+        buff: &[u8],
+        state: &GAState<Self>,
+    ) -> Result<Instruction<Self>, ArchError> {
+        if !matches!(state.project.get_target().core, CoreFamily::AArch64(_)) {
+            return Err(ArchError::IncompatibleTarget);
+        }
+        let operation = parser::parse(buff)?;
+        trace!("Running {:?}", operation);
+        Ok(Self::expand(operation))
+    }
+
+    fn disassemble(&self, buff: &[u8]) -> Option<String> {
+        parser::parse(buff)
+            .ok()
+            .map(|operation| format!("{operation:?}"))
+    }
+
+    fn discover(file: &File) -> Result<Option<Self>, ArchError> {
+        let f = match file {
+            File::Elf64(f) => Ok(f),
+            _ => Err(ArchError::IncorrectFileType),
+        }?;
+        if f.raw_header().e_machine(f.endian()) != EM_AARCH64 {
+            return Ok(None);
+        }
+        Ok(Some(AArch64))
+    }
+
+    fn default_target() -> TargetConfiguration {
+        TargetConfiguration::zero_wait_states(CoreFamily::AArch64(AArch64Core::CortexA53))
+    }
+
+    fn default_stack_start(&self) -> Option<u64> {
+        Some(DEFAULT_STACK_START)
+    }
+}
+
+impl Display for AArch64 {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "AArch64")
+    }
+}
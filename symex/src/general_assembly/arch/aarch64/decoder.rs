@@ -0,0 +1,1563 @@
+//! Defines translation rules from parsed A64 [`Operation`]s to general
+//! assembly [`Operation`](GAOperation)s.
+//!
+//! The registers are 64 bits wide. Instructions that operate on the 32 bit
+//! `w` registers compute their result on the low half of the register in
+//! locals and zero extend it when it is written back. The flags are always
+//! computed on 64 bit values, for 32 bit instructions the operands are moved
+//! to the top half of the word which gives the same N, Z, C and V flags as a
+//! 32 bit computation.
+
+use general_assembly::{
+    condition::Condition,
+    operand::{DataWord, Operand},
+    operation::Operation as GAOperation,
+    register::Register as GARegister,
+};
+
+use super::{
+    parser::{
+        Address,
+        BitfieldOp,
+        CompareOperand,
+        DataOp1,
+        DataOp2,
+        Extend,
+        LogicOp,
+        MoveWideOp,
+        Operation,
+        Register,
+        SelectOp,
+        ShiftType,
+        LR,
+        SP_OR_ZR,
+    },
+    timing,
+    AArch64,
+    REGISTERS,
+};
+use crate::general_assembly::instruction::{Instruction as GAInstruction, MemoryAccess};
+
+/// The registers `x0` to `x30`, `x30` is the link register.
+pub const GENERAL_REGISTERS: [GARegister; 31] = REGISTERS.all_named([
+    "X0", "X1", "X2", "X3", "X4", "X5", "X6", "X7", "X8", "X9", "X10", "X11", "X12", "X13", "X14",
+    "X15", "X16", "X17", "X18", "X19", "X20", "X21", "X22", "X23", "X24", "X25", "X26", "X27",
+    "X28", "X29", "LR",
+]);
+
+/// Reads the full register, register 31 is the stack pointer if `sp` is set
+/// and the zero register otherwise.
+fn read(register: Register, sp: bool) -> Operand {
+    match (register, sp) {
+        (SP_OR_ZR, true) => Operand::Register(GARegister::SP),
+        (SP_OR_ZR, false) => immediate(0, true),
+        (register, _) => Operand::Register(GENERAL_REGISTERS[register as usize]),
+    }
+}
+
+/// The destination for writes to the full register, writes to the zero
+/// register are discarded.
+fn write(register: Register, sp: bool) -> Operand {
+    match (register, sp) {
+        (SP_OR_ZR, true) => Operand::Register(GARegister::SP),
+        (SP_OR_ZR, false) => local("discarded"),
+        (register, _) => Operand::Register(GENERAL_REGISTERS[register as usize]),
+    }
+}
+
+fn local(name: &str) -> Operand {
+    Operand::Local(name.to_owned())
+}
+
+/// An immediate of the operation size, 64 bits if `sf` is set.
+fn immediate(value: u64, sf: bool) -> Operand {
+    match sf {
+        true => Operand::Immediate(DataWord::Word64(value)),
+        false => Operand::Immediate(DataWord::Word32(value as u32)),
+    }
+}
+
+/// A mask of the `bits` lowest bits.
+fn mask(bits: u32) -> u64 {
+    match bits {
+        64 => u64::MAX,
+        bits => (1 << bits) - 1,
+    }
+}
+
+fn size(sf: bool) -> u32 {
+    match sf {
+        true => 64,
+        false => 32,
+    }
+}
+
+/// The address of the instruction being executed, `PC` holds the address of
+/// the next instruction.
+fn pc() -> Operand {
+    Operand::Register(const { REGISTERS.named("PC-") })
+}
+
+fn flag(flag: GARegister) -> Operand {
+    Operand::Flag(flag)
+}
+
+/// Reads `register` at the operation size, 32 bit values are stored in the
+/// local `name`.
+fn value(
+    operations: &mut Vec<GAOperation>,
+    register: Register,
+    sp: bool,
+    sf: bool,
+    name: &str,
+) -> Operand {
+    match (sf, register == SP_OR_ZR && !sp) {
+        (true, _) => read(register, sp),
+        (false, true) => immediate(0, false),
+        (false, false) => {
+            operations.push(GAOperation::Resize {
+                destination: local(name),
+                operand: read(register, sp),
+                bits: 32,
+            });
+            local(name)
+        }
+    }
+}
+
+/// Writes an operation sized `value` to `register`, 32 bit values are zero
+/// extended.
+fn set(operations: &mut Vec<GAOperation>, register: Register, sp: bool, sf: bool, value: Operand) {
+    operations.push(match sf {
+        true => GAOperation::Move {
+            destination: write(register, sp),
+            source: value,
+        },
+        false => GAOperation::ZeroExtend {
+            destination: write(register, sp),
+            operand: value,
+            bits: 32,
+            target_bits: 64,
+        },
+    });
+}
+
+/// Moves an operation sized value to the top of a 64 bit local.
+fn to_top(operations: &mut Vec<GAOperation>, name: &str, value: Operand) -> Operand {
+    operations.extend([
+        GAOperation::ZeroExtend {
+            destination: local(name),
+            operand: value,
+            bits: 32,
+            target_bits: 64,
+        },
+        GAOperation::Sl {
+            destination: local(name),
+            operand: local(name),
+            shift: immediate(32, true),
+        },
+    ]);
+    local(name)
+}
+
+/// Sets the flags for `result`, which is `operand1 + operand2` or `operand1 -
+/// operand2` if `sub` is set. If `carry` is set the carry flag is added to
+/// the sum.
+fn arithmetic_flags(
+    operations: &mut Vec<GAOperation>,
+    sf: bool,
+    result: Operand,
+    operand1: Operand,
+    operand2: Operand,
+    sub: bool,
+    carry: bool,
+) {
+    let (result, operand1, operand2) = match sf {
+        true => (result, operand1, operand2),
+        false => {
+            let result = to_top(operations, "flag_result", result);
+            let operand1 = to_top(operations, "flag_operand1", operand1);
+            let operand2 = to_top(operations, "flag_operand2", operand2);
+            if carry {
+                // The carry in has to propagate in to the top half.
+                operations.push(GAOperation::Or {
+                    destination: operand1.clone(),
+                    operand1: operand1.clone(),
+                    operand2: immediate(0xffff_ffff, true),
+                });
+            }
+            (result, operand1, operand2)
+        }
+    };
+    // V is set before C as both read the carry in from the C flag.
+    operations.extend([
+        GAOperation::SetNFlag(result.clone()),
+        GAOperation::SetZFlag(result),
+        GAOperation::SetVFlag {
+            operand1: operand1.clone(),
+            operand2: operand2.clone(),
+            sub,
+            carry,
+        },
+        GAOperation::SetCFlag {
+            operand1,
+            operand2,
+            sub,
+            carry,
+        },
+    ]);
+}
+
+/// Sets N and Z for `result` and clears C and V.
+fn logical_flags(operations: &mut Vec<GAOperation>, sf: bool, result: Operand) {
+    let result = match sf {
+        true => result,
+        false => to_top(operations, "flag_result", result),
+    };
+    operations.extend([
+        GAOperation::SetNFlag(result.clone()),
+        GAOperation::SetZFlag(result),
+        GAOperation::Move {
+            destination: flag(GARegister::C),
+            source: immediate(0, true),
+        },
+        GAOperation::Move {
+            destination: flag(GARegister::V),
+            source: immediate(0, true),
+        },
+    ]);
+}
+
+/// Stores 1 in the 64 bit local `condition` if `condition` holds and 0
+/// otherwise.
+fn condition_holds(operations: &mut Vec<GAOperation>, condition: Condition) {
+    let destination = local("condition");
+    let one = immediate(1, true);
+    let not = |operations: &mut Vec<GAOperation>, operand: Operand, destination: Operand| {
+        operations.push(GAOperation::Xor {
+            destination,
+            operand1: operand,
+            operand2: immediate(1, true),
+        });
+    };
+    let (base, negated) = match condition {
+        Condition::EQ => (Condition::EQ, false),
+        Condition::NE => (Condition::EQ, true),
+        Condition::CS => (Condition::CS, false),
+        Condition::CC => (Condition::CS, true),
+        Condition::MI => (Condition::MI, false),
+        Condition::PL => (Condition::MI, true),
+        Condition::VS => (Condition::VS, false),
+        Condition::VC => (Condition::VS, true),
+        Condition::HI => (Condition::HI, false),
+        Condition::LS => (Condition::HI, true),
+        Condition::GE => (Condition::GE, false),
+        Condition::LT => (Condition::GE, true),
+        Condition::GT => (Condition::GT, false),
+        Condition::LE => (Condition::GT, true),
+        Condition::None => (Condition::None, false),
+    };
+    match base {
+        Condition::EQ => operations.push(GAOperation::Move {
+            destination: destination.clone(),
+            source: flag(GARegister::Z),
+        }),
+        Condition::CS => operations.push(GAOperation::Move {
+            destination: destination.clone(),
+            source: flag(GARegister::C),
+        }),
+        Condition::MI => operations.push(GAOperation::Move {
+            destination: destination.clone(),
+            source: flag(GARegister::N),
+        }),
+        Condition::VS => operations.push(GAOperation::Move {
+            destination: destination.clone(),
+            source: flag(GARegister::V),
+        }),
+        // C set and Z clear.
+        Condition::HI => {
+            not(operations, flag(GARegister::Z), local("not_z"));
+            operations.push(GAOperation::And {
+                destination: destination.clone(),
+                operand1: flag(GARegister::C),
+                operand2: local("not_z"),
+            });
+        }
+        // N equals V, and Z clear for GT.
+        Condition::GE | Condition::GT => {
+            operations.push(GAOperation::Xor {
+                destination: local("n_xor_v"),
+                operand1: flag(GARegister::N),
+                operand2: flag(GARegister::V),
+            });
+            not(operations, local("n_xor_v"), destination.clone());
+            if base == Condition::GT {
+                not(operations, flag(GARegister::Z), local("not_z"));
+                operations.push(GAOperation::And {
+                    destination: destination.clone(),
+                    operand1: destination.clone(),
+                    operand2: local("not_z"),
+                });
+            }
+        }
+        _ => operations.push(GAOperation::Move {
+            destination: destination.clone(),
+            source: one,
+        }),
+    }
+    if negated {
+        not(operations, destination.clone(), destination);
+    }
+}
+
+/// Writes `then` to `destination` if the local `condition` is 1 and
+/// `otherwise` if it is 0.
+fn select(
+    operations: &mut Vec<GAOperation>,
+    sf: bool,
+    destination: Operand,
+    then: Operand,
+    otherwise: Operand,
+) {
+    operations.push(GAOperation::Sub {
+        destination: local("select_mask"),
+        operand1: immediate(0, true),
+        operand2: local("condition"),
+    });
+    if !sf {
+        operations.push(GAOperation::Resize {
+            destination: local("select_mask"),
+            operand: local("select_mask"),
+            bits: 32,
+        });
+    }
+    operations.extend([
+        GAOperation::And {
+            destination: local("then"),
+            operand1: then,
+            operand2: local("select_mask"),
+        },
+        GAOperation::Not {
+            destination: local("select_mask"),
+            operand: local("select_mask"),
+        },
+        GAOperation::And {
+            destination: local("otherwise"),
+            operand1: otherwise,
+            operand2: local("select_mask"),
+        },
+        GAOperation::Or {
+            destination,
+            operand1: local("then"),
+            operand2: local("otherwise"),
+        },
+    ]);
+}
+
+/// Rotates the operation sized `operand` right by `amount`, an amount of 0
+/// leaves the value unchanged.
+fn rotate_right(
+    operations: &mut Vec<GAOperation>,
+    sf: bool,
+    destination: Operand,
+    operand: Operand,
+    amount: Operand,
+) {
+    operations.extend([
+        GAOperation::Sub {
+            destination: local("rotate_left"),
+            operand1: immediate(size(sf) as u64, sf),
+            operand2: amount.clone(),
+        },
+        GAOperation::Srl {
+            destination: local("rotate_low"),
+            operand: operand.clone(),
+            shift: amount,
+        },
+        GAOperation::Sl {
+            destination: local("rotate_high"),
+            operand,
+            shift: local("rotate_left"),
+        },
+        GAOperation::Or {
+            destination,
+            operand1: local("rotate_low"),
+            operand2: local("rotate_high"),
+        },
+    ]);
+}
+
+/// The second operand of the shifted register forms, stored in the local
+/// `operand2`.
+fn shifted(
+    operations: &mut Vec<GAOperation>,
+    sf: bool,
+    rm: Register,
+    shift: ShiftType,
+    amount: u32,
+) -> Operand {
+    let value = value(operations, rm, false, sf, "operand2");
+    if amount == 0 {
+        return value;
+    }
+    let destination = local("operand2");
+    let shift_amount = immediate(amount as u64, sf);
+    operations.push(match shift {
+        ShiftType::Lsl => GAOperation::Sl {
+            destination: destination.clone(),
+            operand: value,
+            shift: shift_amount,
+        },
+        ShiftType::Lsr => GAOperation::Srl {
+            destination: destination.clone(),
+            operand: value,
+            shift: shift_amount,
+        },
+        ShiftType::Asr => GAOperation::Sra {
+            destination: destination.clone(),
+            operand: value,
+            shift: shift_amount,
+        },
+        ShiftType::Ror => {
+            rotate_right(operations, sf, destination.clone(), value, shift_amount);
+            return destination;
+        }
+    });
+    destination
+}
+
+/// The low bits of `rm` extended to the operation size and shifted left by
+/// `amount`, stored in the local `name`.
+fn extended(
+    operations: &mut Vec<GAOperation>,
+    sf: bool,
+    rm: Register,
+    extend: Extend,
+    amount: u32,
+    name: &str,
+) -> Operand {
+    let destination = local(name);
+    operations.push(GAOperation::Move {
+        destination: destination.clone(),
+        source: read(rm, false),
+    });
+    if extend.bits < 64 {
+        operations.push(match extend.signed {
+            true => GAOperation::SignExtend {
+                destination: destination.clone(),
+                operand: destination.clone(),
+                bits: extend.bits,
+            },
+            false => GAOperation::ZeroExtend {
+                destination: destination.clone(),
+                operand: destination.clone(),
+                bits: extend.bits,
+                target_bits: 64,
+            },
+        });
+    }
+    if !sf {
+        operations.push(GAOperation::Resize {
+            destination: destination.clone(),
+            operand: destination.clone(),
+            bits: 32,
+        });
+    }
+    if amount != 0 {
+        operations.push(GAOperation::Sl {
+            destination: destination.clone(),
+            operand: destination.clone(),
+            shift: immediate(amount as u64, sf),
+        });
+    }
+    destination
+}
+
+/// Adds or subtracts `operand2` from `rn` and writes the result to `rd`.
+#[allow(clippy::too_many_arguments)]
+fn add_sub(
+    mut operations: Vec<GAOperation>,
+    sf: bool,
+    sub: bool,
+    set_flags: bool,
+    rd: Register,
+    rn: Register,
+    operand2: Operand,
+    sp: bool,
+) -> Vec<GAOperation> {
+    let operand1 = value(&mut operations, rn, sp, sf, "operand1");
+    let result = local("result");
+    operations.push(match sub {
+        true => GAOperation::Sub {
+            destination: result.clone(),
+            operand1: operand1.clone(),
+            operand2: operand2.clone(),
+        },
+        false => GAOperation::Add {
+            destination: result.clone(),
+            operand1: operand1.clone(),
+            operand2: operand2.clone(),
+        },
+    });
+    if set_flags {
+        arithmetic_flags(
+            &mut operations,
+            sf,
+            result.clone(),
+            operand1,
+            operand2,
+            sub,
+            false,
+        );
+    }
+    set(&mut operations, rd, sp && !set_flags, sf, result);
+    operations
+}
+
+/// Applies a logical operation to `rn` and `operand2` and writes the result
+/// to `rd`.
+fn logical(
+    mut operations: Vec<GAOperation>,
+    sf: bool,
+    op: LogicOp,
+    rd: Register,
+    rn: Register,
+    operand2: Operand,
+    sp: bool,
+) -> Vec<GAOperation> {
+    let operand1 = value(&mut operations, rn, false, sf, "operand1");
+    let result = local("result");
+    operations.push(match op {
+        LogicOp::And | LogicOp::Ands => GAOperation::And {
+            destination: result.clone(),
+            operand1,
+            operand2,
+        },
+        LogicOp::Orr => GAOperation::Or {
+            destination: result.clone(),
+            operand1,
+            operand2,
+        },
+        LogicOp::Eor => GAOperation::Xor {
+            destination: result.clone(),
+            operand1,
+            operand2,
+        },
+    });
+    if op == LogicOp::Ands {
+        logical_flags(&mut operations, sf, result.clone());
+    }
+    set(&mut operations, rd, sp && op != LogicOp::Ands, sf, result);
+    operations
+}
+
+/// Swaps the groups of `bits` bits in every group of `2 * bits` bits of the
+/// local `name`.
+fn swap(operations: &mut Vec<GAOperation>, sf: bool, name: &str, bits: u32) {
+    let mut pattern = mask(bits);
+    let mut width = 2 * bits;
+    while width < 64 {
+        pattern |= pattern << width;
+        width *= 2;
+    }
+    operations.extend([
+        GAOperation::Srl {
+            destination: local("swap_high"),
+            operand: local(name),
+            shift: immediate(bits as u64, sf),
+        },
+        GAOperation::And {
+            destination: local("swap_high"),
+            operand1: local("swap_high"),
+            operand2: immediate(pattern, sf),
+        },
+        GAOperation::And {
+            destination: local("swap_low"),
+            operand1: local(name),
+            operand2: immediate(pattern, sf),
+        },
+        GAOperation::Sl {
+            destination: local("swap_low"),
+            operand: local("swap_low"),
+            shift: immediate(bits as u64, sf),
+        },
+        GAOperation::Or {
+            destination: local(name),
+            operand1: local("swap_high"),
+            operand2: local("swap_low"),
+        },
+    ]);
+}
+
+/// Counts the leading zeroes of the operation sized `operand`.
+fn count_leading_zeroes(
+    operations: &mut Vec<GAOperation>,
+    sf: bool,
+    destination: Operand,
+    operand: Operand,
+) {
+    match sf {
+        true => operations.push(GAOperation::CountLeadingZeroes {
+            destination,
+            operand,
+        }),
+        false => operations.extend([
+            GAOperation::ZeroExtend {
+                destination: local("wide"),
+                operand,
+                bits: 32,
+                target_bits: 64,
+            },
+            GAOperation::CountLeadingZeroes {
+                destination: destination.clone(),
+                operand: local("wide"),
+            },
+            GAOperation::Sub {
+                destination: destination.clone(),
+                operand1: destination,
+                operand2: immediate(32, true),
+            },
+        ]),
+    }
+}
+
+/// Writes 0 to `destination` if the operation sized `divisor` is zero,
+/// otherwise the local `quotient`.
+fn zero_on_division_by_zero(
+    operations: &mut Vec<GAOperation>,
+    sf: bool,
+    destination: Operand,
+    divisor: Operand,
+) {
+    // The sign bit of `divisor | -divisor` is set for all divisors but zero.
+    operations.extend([
+        GAOperation::Sub {
+            destination: local("non_zero"),
+            operand1: immediate(0, sf),
+            operand2: divisor.clone(),
+        },
+        GAOperation::Or {
+            destination: local("non_zero"),
+            operand1: local("non_zero"),
+            operand2: divisor,
+        },
+        GAOperation::Sra {
+            destination: local("non_zero"),
+            operand: local("non_zero"),
+            shift: immediate(size(sf) as u64 - 1, sf),
+        },
+        GAOperation::And {
+            destination,
+            operand1: local("quotient"),
+            operand2: local("non_zero"),
+        },
+    ]);
+}
+
+/// Computes the address of a load or store in the local `address`. Returns
+/// the operations that write back the base register.
+fn address(operations: &mut Vec<GAOperation>, rn: Register, address: Address) -> Vec<GAOperation> {
+    let base = read(rn, true);
+    let (offset, writeback) = match address {
+        Address::Offset(offset) => (immediate(offset as u64, true), false),
+        Address::PreIndex(offset) => (immediate(offset as u64, true), true),
+        Address::PostIndex(offset) => {
+            operations.push(GAOperation::Move {
+                destination: local("address"),
+                source: base.clone(),
+            });
+            return vec![GAOperation::Add {
+                destination: write(rn, true),
+                operand1: base,
+                operand2: immediate(offset as u64, true),
+            }];
+        }
+        Address::Register { rm, extend, shift } => (
+            extended(operations, true, rm, extend, shift, "offset"),
+            false,
+        ),
+    };
+    operations.push(GAOperation::Add {
+        destination: local("address"),
+        operand1: base,
+        operand2: offset,
+    });
+    match writeback {
+        true => vec![GAOperation::Move {
+            destination: write(rn, true),
+            source: local("address"),
+        }],
+        false => vec![],
+    }
+}
+
+/// Loads `bits` bits from the address in the local `address` to `rt`.
+fn load(
+    operations: &mut Vec<GAOperation>,
+    rt: Register,
+    address: &str,
+    bits: u32,
+    signed: bool,
+    target_bits: u32,
+) {
+    operations.push(GAOperation::Move {
+        destination: local("value"),
+        source: Operand::AddressInLocal(address.to_owned(), bits),
+    });
+    if signed {
+        operations.push(GAOperation::SignExtend {
+            destination: local("value"),
+            operand: local("value"),
+            bits,
+        });
+    }
+    let bits = match signed {
+        true => target_bits,
+        false => bits,
+    };
+    operations.push(match bits {
+        64 => GAOperation::Move {
+            destination: write(rt, false),
+            source: local("value"),
+        },
+        bits => GAOperation::ZeroExtend {
+            destination: write(rt, false),
+            operand: local("value"),
+            bits,
+            target_bits: 64,
+        },
+    });
+}
+
+/// Stores the low `bits` bits of `rt` to the address in the local `address`.
+fn store(operations: &mut Vec<GAOperation>, rt: Register, address: &str, bits: u32) {
+    operations.push(GAOperation::Move {
+        destination: Operand::AddressInLocal(address.to_owned(), bits),
+        source: read(rt, false),
+    });
+}
+
+/// Jumps to the local `target` if `condition` holds.
+fn jump(operations: &mut Vec<GAOperation>, condition: Condition) {
+    operations.push(GAOperation::ConditionalJump {
+        destination: local("target"),
+        condition,
+    });
+}
+
+/// Computes the target of a pc relative branch in the local `target`.
+fn target(offset: i64) -> GAOperation {
+    GAOperation::Add {
+        destination: local("target"),
+        operand1: pc(),
+        operand2: immediate(offset as u64, true),
+    }
+}
+
+/// Jumps to `offset` if the flag Z set to `zero` holds for `condition`. The
+/// previous value of Z is restored after the jump so the flags are not
+/// changed by the instruction.
+fn branch_on_zero(
+    mut operations: Vec<GAOperation>,
+    zero: Operand,
+    condition: Condition,
+    offset: i64,
+) -> Vec<GAOperation> {
+    operations.extend([
+        GAOperation::Move {
+            destination: local("saved_z"),
+            source: flag(GARegister::Z),
+        },
+        GAOperation::SetZFlag(zero),
+        target(offset),
+        GAOperation::ConditionalJump {
+            destination: local("target"),
+            condition,
+        },
+        GAOperation::Move {
+            destination: flag(GARegister::Z),
+            source: local("saved_z"),
+        },
+    ]);
+    operations
+}
+
+impl AArch64 {
+    pub(super) fn expand(operation: Operation) -> GAInstruction<AArch64> {
+        let mut operations = vec![];
+        match operation {
+            Operation::AddSubImmediate {
+                sf,
+                sub,
+                set_flags,
+                rd,
+                rn,
+                imm,
+            } => {
+                operations = add_sub(
+                    operations,
+                    sf,
+                    sub,
+                    set_flags,
+                    rd,
+                    rn,
+                    immediate(imm, sf),
+                    true,
+                );
+            }
+            Operation::AddSubShifted {
+                sf,
+                sub,
+                set_flags,
+                rd,
+                rn,
+                rm,
+                shift,
+                amount,
+            } => {
+                let operand2 = shifted(&mut operations, sf, rm, shift, amount);
+                operations = add_sub(operations, sf, sub, set_flags, rd, rn, operand2, false);
+            }
+            Operation::AddSubExtended {
+                sf,
+                sub,
+                set_flags,
+                rd,
+                rn,
+                rm,
+                extend,
+                amount,
+            } => {
+                let operand2 = extended(&mut operations, sf, rm, extend, amount, "operand2");
+                operations = add_sub(operations, sf, sub, set_flags, rd, rn, operand2, true);
+            }
+            Operation::AddSubCarry {
+                sf,
+                sub,
+                set_flags,
+                rd,
+                rn,
+                rm,
+            } => {
+                let operand1 = value(&mut operations, rn, false, sf, "operand1");
+                let mut operand2 = value(&mut operations, rm, false, sf, "operand2");
+                // Subtraction with carry is addition of the inverted operand.
+                if sub {
+                    operations.push(GAOperation::Not {
+                        destination: local("operand2"),
+                        operand: operand2,
+                    });
+                    operand2 = local("operand2");
+                }
+                let carry = match sf {
+                    true => flag(GARegister::C),
+                    false => {
+                        operations.push(GAOperation::Resize {
+                            destination: local("carry"),
+                            operand: flag(GARegister::C),
+                            bits: 32,
+                        });
+                        local("carry")
+                    }
+                };
+                operations.extend([
+                    GAOperation::Add {
+                        destination: local("result"),
+                        operand1: operand1.clone(),
+                        operand2: operand2.clone(),
+                    },
+                    GAOperation::Add {
+                        destination: local("result"),
+                        operand1: local("result"),
+                        operand2: carry,
+                    },
+                ]);
+                if set_flags {
+                    arithmetic_flags(
+                        &mut operations,
+                        sf,
+                        local("result"),
+                        operand1,
+                        operand2,
+                        false,
+                        true,
+                    );
+                }
+                set(&mut operations, rd, false, sf, local("result"));
+            }
+            Operation::LogicalImmediate {
+                sf,
+                op,
+                rd,
+                rn,
+                imm,
+            } => {
+                operations = logical(operations, sf, op, rd, rn, immediate(imm, sf), true);
+            }
+            Operation::LogicalShifted {
+                sf,
+                op,
+                invert,
+                rd,
+                rn,
+                rm,
+                shift,
+                amount,
+            } => {
+                let mut operand2 = shifted(&mut operations, sf, rm, shift, amount);
+                if invert {
+                    operations.push(GAOperation::Not {
+                        destination: local("operand2"),
+                        operand: operand2,
+                    });
+                    operand2 = local("operand2");
+                }
+                operations = logical(operations, sf, op, rd, rn, operand2, false);
+            }
+            Operation::MoveWide {
+                sf,
+                op,
+                rd,
+                imm,
+                shift,
+            } => {
+                let imm = (imm as u64) << shift;
+                let result = match op {
+                    MoveWideOp::Movz => immediate(imm, sf),
+                    MoveWideOp::Movn => immediate(!imm & mask(size(sf)), sf),
+                    MoveWideOp::Movk => {
+                        let current = value(&mut operations, rd, false, sf, "current");
+                        operations.extend([
+                            GAOperation::And {
+                                destination: local("result"),
+                                operand1: current,
+                                operand2: immediate(!(0xffff << shift), sf),
+                            },
+                            GAOperation::Or {
+                                destination: local("result"),
+                                operand1: local("result"),
+                                operand2: immediate(imm, sf),
+                            },
+                        ]);
+                        local("result")
+                    }
+                };
+                set(&mut operations, rd, false, sf, result);
+            }
+            Operation::Adr { rd, offset, page } => {
+                let base = match page {
+                    true => {
+                        operations.push(GAOperation::And {
+                            destination: local("base"),
+                            operand1: pc(),
+                            operand2: immediate(!0xfff, true),
+                        });
+                        local("base")
+                    }
+                    false => pc(),
+                };
+                operations.push(GAOperation::Add {
+                    destination: write(rd, false),
+                    operand1: base,
+                    operand2: immediate(offset as u64, true),
+                });
+            }
+            Operation::Bitfield {
+                sf,
+                op,
+                rd,
+                rn,
+                immr,
+                imms,
+            } => {
+                let datasize = size(sf);
+                let source = value(&mut operations, rn, false, sf, "source");
+                let shift = |amount: u32| immediate(amount as u64, sf);
+                // The field is either extracted from `immr` to the bottom of
+                // the register or inserted from the bottom at `datasize - immr`.
+                let (width, position) = match imms >= immr {
+                    true => (imms - immr + 1, 0),
+                    false => (imms + 1, datasize - immr),
+                };
+                let lsb = match imms >= immr {
+                    true => immr,
+                    false => 0,
+                };
+                match op {
+                    BitfieldOp::Sbfm => operations.extend([
+                        GAOperation::Sl {
+                            destination: local("result"),
+                            operand: source,
+                            shift: shift(datasize - width - lsb),
+                        },
+                        GAOperation::Sra {
+                            destination: local("result"),
+                            operand: local("result"),
+                            shift: shift(datasize - width - position),
+                        },
+                    ]),
+                    BitfieldOp::Ubfm | BitfieldOp::Bfm => {
+                        operations.extend([
+                            GAOperation::Srl {
+                                destination: local("result"),
+                                operand: source,
+                                shift: shift(lsb),
+                            },
+                            GAOperation::And {
+                                destination: local("result"),
+                                operand1: local("result"),
+                                operand2: immediate(mask(width), sf),
+                            },
+                            GAOperation::Sl {
+                                destination: local("result"),
+                                operand: local("result"),
+                                shift: shift(position),
+                            },
+                        ]);
+                        if op == BitfieldOp::Bfm {
+                            let current = value(&mut operations, rd, false, sf, "current");
+                            operations.extend([
+                                GAOperation::And {
+                                    destination: local("current"),
+                                    operand1: current,
+                                    operand2: immediate(!(mask(width) << position), sf),
+                                },
+                                GAOperation::Or {
+                                    destination: local("result"),
+                                    operand1: local("result"),
+                                    operand2: local("current"),
+                                },
+                            ]);
+                        }
+                    }
+                }
+                set(&mut operations, rd, false, sf, local("result"));
+            }
+            Operation::Extract {
+                sf,
+                rd,
+                rn,
+                rm,
+                lsb,
+            } => {
+                let high = value(&mut operations, rn, false, sf, "high");
+                let low = value(&mut operations, rm, false, sf, "low");
+                let result = match lsb {
+                    0 => low,
+                    lsb => {
+                        operations.extend([
+                            GAOperation::Srl {
+                                destination: local("low"),
+                                operand: low,
+                                shift: immediate(lsb as u64, sf),
+                            },
+                            GAOperation::Sl {
+                                destination: local("high"),
+                                operand: high,
+                                shift: immediate((size(sf) - lsb) as u64, sf),
+                            },
+                            GAOperation::Or {
+                                destination: local("result"),
+                                operand1: local("low"),
+                                operand2: local("high"),
+                            },
+                        ]);
+                        local("result")
+                    }
+                };
+                set(&mut operations, rd, false, sf, result);
+            }
+            Operation::ConditionalSelect {
+                sf,
+                op,
+                rd,
+                rn,
+                rm,
+                condition,
+            } => {
+                condition_holds(&mut operations, condition);
+                let then = value(&mut operations, rn, false, sf, "operand1");
+                let otherwise = value(&mut operations, rm, false, sf, "operand2");
+                let otherwise = match op {
+                    SelectOp::Csel => otherwise,
+                    SelectOp::Csinc => {
+                        operations.push(GAOperation::Add {
+                            destination: local("operand2"),
+                            operand1: otherwise,
+                            operand2: immediate(1, sf),
+                        });
+                        local("operand2")
+                    }
+                    SelectOp::Csinv => {
+                        operations.push(GAOperation::Not {
+                            destination: local("operand2"),
+                            operand: otherwise,
+                        });
+                        local("operand2")
+                    }
+                    SelectOp::Csneg => {
+                        operations.push(GAOperation::Sub {
+                            destination: local("operand2"),
+                            operand1: immediate(0, sf),
+                            operand2: otherwise,
+                        });
+                        local("operand2")
+                    }
+                };
+                select(&mut operations, sf, local("result"), then, otherwise);
+                set(&mut operations, rd, false, sf, local("result"));
+            }
+            Operation::ConditionalCompare {
+                sf,
+                negative,
+                rn,
+                operand,
+                nzcv,
+                condition,
+            } => {
+                condition_holds(&mut operations, condition);
+                let operand1 = value(&mut operations, rn, false, sf, "operand1");
+                let operand2 = match operand {
+                    CompareOperand::Register(rm) => {
+                        value(&mut operations, rm, false, sf, "operand2")
+                    }
+                    CompareOperand::Immediate(imm) => immediate(imm as u64, sf),
+                };
+                operations.push(match negative {
+                    true => GAOperation::Add {
+                        destination: local("result"),
+                        operand1: operand1.clone(),
+                        operand2: operand2.clone(),
+                    },
+                    false => GAOperation::Sub {
+                        destination: local("result"),
+                        operand1: operand1.clone(),
+                        operand2: operand2.clone(),
+                    },
+                });
+                arithmetic_flags(
+                    &mut operations,
+                    sf,
+                    local("result"),
+                    operand1,
+                    operand2,
+                    !negative,
+                    false,
+                );
+                // Flags that are set in `nzcv` are set if the condition does not
+                // hold, the others are cleared.
+                operations.push(GAOperation::Xor {
+                    destination: local("not_condition"),
+                    operand1: local("condition"),
+                    operand2: immediate(1, true),
+                });
+                for (bit, name) in [
+                    (3, GARegister::N),
+                    (2, GARegister::Z),
+                    (1, GARegister::C),
+                    (0, GARegister::V),
+                ] {
+                    operations.push(match (nzcv >> bit) & 1 {
+                        1 => GAOperation::Or {
+                            destination: flag(name),
+                            operand1: flag(name),
+                            operand2: local("not_condition"),
+                        },
+                        _ => GAOperation::And {
+                            destination: flag(name),
+                            operand1: flag(name),
+                            operand2: local("condition"),
+                        },
+                    });
+                }
+            }
+            Operation::DataProcessing2 { sf, op, rd, rn, rm } => {
+                let operand1 = value(&mut operations, rn, false, sf, "operand1");
+                let operand2 = value(&mut operations, rm, false, sf, "operand2");
+                let result = local("result");
+                match op {
+                    DataOp2::Udiv | DataOp2::Sdiv => {
+                        operations.push(match op {
+                            DataOp2::Udiv => GAOperation::UDiv {
+                                destination: local("quotient"),
+                                operand1,
+                                operand2: operand2.clone(),
+                            },
+                            _ => GAOperation::SDiv {
+                                destination: local("quotient"),
+                                operand1,
+                                operand2: operand2.clone(),
+                            },
+                        });
+                        zero_on_division_by_zero(&mut operations, sf, result.clone(), operand2);
+                    }
+                    _ => {
+                        // The shift amount is taken modulo the register size.
+                        operations.push(GAOperation::And {
+                            destination: local("amount"),
+                            operand1: operand2,
+                            operand2: immediate(size(sf) as u64 - 1, sf),
+                        });
+                        let amount = local("amount");
+                        let destination = result.clone();
+                        match op {
+                            DataOp2::Lslv => operations.push(GAOperation::Sl {
+                                destination,
+                                operand: operand1,
+                                shift: amount,
+                            }),
+                            DataOp2::Lsrv => operations.push(GAOperation::Srl {
+                                destination,
+                                operand: operand1,
+                                shift: amount,
+                            }),
+                            DataOp2::Asrv => operations.push(GAOperation::Sra {
+                                destination,
+                                operand: operand1,
+                                shift: amount,
+                            }),
+                            _ => rotate_right(&mut operations, sf, destination, operand1, amount),
+                        }
+                    }
+                }
+                set(&mut operations, rd, false, sf, result);
+            }
+            Operation::DataProcessing1 { sf, op, rd, rn } => {
+                let source = value(&mut operations, rn, false, sf, "source");
+                operations.push(GAOperation::Move {
+                    destination: local("result"),
+                    source: source.clone(),
+                });
+                let byte_swaps: &[u32] = match (op, sf) {
+                    (DataOp1::Rev16, _) => &[8],
+                    (DataOp1::Rev32, _) | (DataOp1::Rev | DataOp1::Rbit, false) => &[8, 16],
+                    (DataOp1::Rev | DataOp1::Rbit, true) => &[8, 16, 32],
+                    _ => &[],
+                };
+                if op == DataOp1::Rbit {
+                    for bits in [1, 2, 4] {
+                        swap(&mut operations, sf, "result", bits);
+                    }
+                }
+                for bits in byte_swaps {
+                    swap(&mut operations, sf, "result", *bits);
+                }
+                match op {
+                    DataOp1::Clz => {
+                        count_leading_zeroes(&mut operations, sf, local("result"), source)
+                    }
+                    // The number of bits after the sign bit that are equal to
+                    // it.
+                    DataOp1::Cls => {
+                        operations.extend([
+                            GAOperation::Sra {
+                                destination: local("shifted"),
+                                operand: source.clone(),
+                                shift: immediate(1, sf),
+                            },
+                            GAOperation::Xor {
+                                destination: local("differing"),
+                                operand1: source,
+                                operand2: local("shifted"),
+                            },
+                        ]);
+                        count_leading_zeroes(
+                            &mut operations,
+                            sf,
+                            local("result"),
+                            local("differing"),
+                        );
+                        operations.push(GAOperation::Sub {
+                            destination: local("result"),
+                            operand1: local("result"),
+                            operand2: immediate(1, true),
+                        });
+                    }
+                    _ => (),
+                }
+                set(&mut operations, rd, false, sf, local("result"));
+            }
+            Operation::MultiplyAdd {
+                sf,
+                sub,
+                rd,
+                rn,
+                rm,
+                ra,
+            } => {
+                let operand1 = value(&mut operations, rn, false, sf, "operand1");
+                let operand2 = value(&mut operations, rm, false, sf, "operand2");
+                let accumulator = value(&mut operations, ra, false, sf, "accumulator");
+                operations.push(GAOperation::Mul {
+                    destination: local("product"),
+                    operand1,
+                    operand2,
+                });
+                operations.push(match sub {
+                    true => GAOperation::Sub {
+                        destination: local("result"),
+                        operand1: accumulator,
+                        operand2: local("product"),
+                    },
+                    false => GAOperation::Add {
+                        destination: local("result"),
+                        operand1: accumulator,
+                        operand2: local("product"),
+                    },
+                });
+                set(&mut operations, rd, false, sf, local("result"));
+            }
+            Operation::MultiplyLong {
+                signed,
+                sub,
+                rd,
+                rn,
+                rm,
+                ra,
+            } => {
+                let extend = Extend { signed, bits: 32 };
+                let operand1 = extended(&mut operations, true, rn, extend, 0, "operand1");
+                let operand2 = extended(&mut operations, true, rm, extend, 0, "operand2");
+                operations.push(GAOperation::Mul {
+                    destination: local("product"),
+                    operand1,
+                    operand2,
+                });
+                operations.push(match sub {
+                    true => GAOperation::Sub {
+                        destination: write(rd, false),
+                        operand1: read(ra, false),
+                        operand2: local("product"),
+                    },
+                    false => GAOperation::Add {
+                        destination: write(rd, false),
+                        operand1: read(ra, false),
+                        operand2: local("product"),
+                    },
+                });
+            }
+            Operation::MultiplyHigh { signed, rd, rn, rm } => {
+                // The unsigned product is computed on 128 bits.
+                operations.extend([
+                    GAOperation::ZeroExtend {
+                        destination: local("operand1"),
+                        operand: read(rn, false),
+                        bits: 64,
+                        target_bits: 128,
+                    },
+                    GAOperation::ZeroExtend {
+                        destination: local("operand2"),
+                        operand: read(rm, false),
+                        bits: 64,
+                        target_bits: 128,
+                    },
+                    GAOperation::ZeroExtend {
+                        destination: local("shift"),
+                        operand: immediate(64, true),
+                        bits: 64,
+                        target_bits: 128,
+                    },
+                    GAOperation::Mul {
+                        destination: local("product"),
+                        operand1: local("operand1"),
+                        operand2: local("operand2"),
+                    },
+                    GAOperation::Srl {
+                        destination: local("product"),
+                        operand: local("product"),
+                        shift: local("shift"),
+                    },
+                    GAOperation::Resize {
+                        destination: local("high"),
+                        operand: local("product"),
+                        bits: 64,
+                    },
+                ]);
+                // A negative operand adds `2^64` times the other operand to the
+                // unsigned product.
+                if signed {
+                    for (negative, other) in [(rn, rm), (rm, rn)] {
+                        operations.extend([
+                            GAOperation::Sra {
+                                destination: local("sign"),
+                                operand: read(negative, false),
+                                shift: immediate(63, true),
+                            },
+                            GAOperation::And {
+                                destination: local("sign"),
+                                operand1: local("sign"),
+                                operand2: read(other, false),
+                            },
+                            GAOperation::Sub {
+                                destination: local("high"),
+                                operand1: local("high"),
+                                operand2: local("sign"),
+                            },
+                        ]);
+                    }
+                }
+                operations.push(GAOperation::Move {
+                    destination: write(rd, false),
+                    source: local("high"),
+                });
+            }
+            Operation::LoadStore {
+                load: is_load,
+                bits,
+                signed,
+                target_bits,
+                rt,
+                rn,
+                address: mode,
+            } => {
+                let writeback = address(&mut operations, rn, mode);
+                match is_load {
+                    true => load(&mut operations, rt, "address", bits, signed, target_bits),
+                    false => store(&mut operations, rt, "address", bits),
+                }
+                operations.extend(writeback);
+            }
+            Operation::StoreExclusive { bits, rs, rt, rn } => {
+                address(&mut operations, rn, Address::Offset(0));
+                store(&mut operations, rt, "address", bits);
+                // There is a single core so the store always succeeds.
+                operations.push(GAOperation::Move {
+                    destination: write(rs, false),
+                    source: immediate(0, true),
+                });
+            }
+            Operation::LoadLiteral {
+                bits,
+                signed,
+                rt,
+                offset,
+            } => {
+                operations.push(GAOperation::Add {
+                    destination: local("address"),
+                    operand1: pc(),
+                    operand2: immediate(offset as u64, true),
+                });
+                load(&mut operations, rt, "address", bits, signed, 64);
+            }
+            Operation::LoadStorePair {
+                load: is_load,
+                bits,
+                signed,
+                rt,
+                rt2,
+                rn,
+                address: mode,
+            } => {
+                let writeback = address(&mut operations, rn, mode);
+                operations.push(GAOperation::Add {
+                    destination: local("address2"),
+                    operand1: local("address"),
+                    operand2: immediate(bits as u64 / 8, true),
+                });
+                for (register, address) in [(rt, "address"), (rt2, "address2")] {
+                    match is_load {
+                        true => load(&mut operations, register, address, bits, signed, 64),
+                        false => store(&mut operations, register, address, bits),
+                    }
+                }
+                operations.extend(writeback);
+            }
+            Operation::Branch { link, offset } => {
+                operations.push(target(offset));
+                if link {
+                    operations.push(GAOperation::Move {
+                        destination: write(LR, false),
+                        source: Operand::Register(GARegister::PC),
+                    });
+                }
+                jump(&mut operations, Condition::None);
+            }
+            Operation::BranchConditional { condition, offset } => {
+                operations.push(target(offset));
+                jump(&mut operations, condition);
+            }
+            Operation::CompareBranch {
+                sf,
+                nonzero,
+                rt,
+                offset,
+            } => {
+                let value = value(&mut operations, rt, false, sf, "value");
+                let zero = match sf {
+                    true => value,
+                    false => {
+                        operations.push(GAOperation::ZeroExtend {
+                            destination: local("value"),
+                            operand: value,
+                            bits: 32,
+                            target_bits: 64,
+                        });
+                        local("value")
+                    }
+                };
+                let condition = match nonzero {
+                    true => Condition::NE,
+                    false => Condition::EQ,
+                };
+                operations = branch_on_zero(operations, zero, condition, offset);
+            }
+            Operation::TestBranch {
+                nonzero,
+                rt,
+                bit,
+                offset,
+            } => {
+                operations.extend([
+                    GAOperation::Srl {
+                        destination: local("bit"),
+                        operand: read(rt, false),
+                        shift: immediate(bit as u64, true),
+                    },
+                    GAOperation::And {
+                        destination: local("bit"),
+                        operand1: local("bit"),
+                        operand2: immediate(1, true),
+                    },
+                ]);
+                let condition = match nonzero {
+                    true => Condition::NE,
+                    false => Condition::EQ,
+                };
+                operations = branch_on_zero(operations, local("bit"), condition, offset);
+            }
+            Operation::BranchRegister { link, rn } => {
+                operations.push(GAOperation::Move {
+                    destination: local("target"),
+                    source: read(rn, false),
+                });
+                if link {
+                    operations.push(GAOperation::Move {
+                        destination: write(LR, false),
+                        source: Operand::Register(GARegister::PC),
+                    });
+                }
+                jump(&mut operations, Condition::None);
+            }
+            Operation::Nop => (),
+        }
+
+        GAInstruction {
+            instruction_size: 32,
+            operations,
+            max_cycle: timing::cycle_count(&operation),
+            memory_access: Self::memory_access(&operation),
+        }
+    }
+
+    pub fn memory_access(operation: &Operation) -> MemoryAccess {
+        match operation {
+            Operation::LoadStore { load: true, .. }
+            | Operation::LoadLiteral { .. }
+            | Operation::LoadStorePair { load: true, .. } => MemoryAccess::Load,
+            Operation::LoadStore { load: false, .. }
+            | Operation::StoreExclusive { .. }
+            | Operation::LoadStorePair { load: false, .. } => MemoryAccess::Store,
+            _ => MemoryAccess::None,
+        }
+    }
+}
@@ -0,0 +1,863 @@
+//! Parses the integer subset of the A64 instruction set.
+//!
+//! Aliases such as `mov`, `cmp`, `lsl` or `mul` are not parsed separately,
+//! they are the instructions they are defined as with the zero register or
+//! constant operands.
+
+use general_assembly::condition::Condition;
+
+use crate::general_assembly::arch::ParseError;
+
+/// Index of a general purpose register, `x0` to `x30`. Index 31 is either the
+/// stack pointer or the zero register depending on the instruction.
+pub type Register = u8;
+
+/// The link register `x30`.
+pub const LR: Register = 30;
+
+/// The stack pointer or the zero register.
+pub const SP_OR_ZR: Register = 31;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShiftType {
+    Lsl,
+    Lsr,
+    Asr,
+    Ror,
+}
+
+/// Extension of the low `bits` of a register, used by the extended register
+/// forms.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Extend {
+    pub signed: bool,
+    pub bits: u32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogicOp {
+    And,
+    Orr,
+    Eor,
+    /// `and` that sets the flags.
+    Ands,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MoveWideOp {
+    /// Moves the inverted immediate.
+    Movn,
+    Movz,
+    /// Keeps the other bits of the register.
+    Movk,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BitfieldOp {
+    Sbfm,
+    Bfm,
+    Ubfm,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SelectOp {
+    Csel,
+    Csinc,
+    Csinv,
+    Csneg,
+}
+
+/// Operations with two source registers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DataOp2 {
+    Udiv,
+    Sdiv,
+    Lslv,
+    Lsrv,
+    Asrv,
+    Rorv,
+}
+
+/// Operations with one source register.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DataOp1 {
+    Rbit,
+    Rev16,
+    Rev32,
+    Rev,
+    Clz,
+    Cls,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompareOperand {
+    Register(Register),
+    Immediate(u8),
+}
+
+/// How the address of a load or store is computed from the base register.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Address {
+    /// Base plus an offset.
+    Offset(i64),
+    /// Base plus an offset, the address is written back to the base.
+    PreIndex(i64),
+    /// The base, the base plus the offset is written back to the base.
+    PostIndex(i64),
+    /// Base plus an extended register shifted left by `shift`.
+    Register {
+        rm: Register,
+        extend: Extend,
+        shift: u32,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Operation {
+    AddSubImmediate {
+        sf: bool,
+        sub: bool,
+        set_flags: bool,
+        rd: Register,
+        rn: Register,
+        imm: u64,
+    },
+    AddSubShifted {
+        sf: bool,
+        sub: bool,
+        set_flags: bool,
+        rd: Register,
+        rn: Register,
+        rm: Register,
+        shift: ShiftType,
+        amount: u32,
+    },
+    AddSubExtended {
+        sf: bool,
+        sub: bool,
+        set_flags: bool,
+        rd: Register,
+        rn: Register,
+        rm: Register,
+        extend: Extend,
+        amount: u32,
+    },
+    AddSubCarry {
+        sf: bool,
+        sub: bool,
+        set_flags: bool,
+        rd: Register,
+        rn: Register,
+        rm: Register,
+    },
+    LogicalImmediate {
+        sf: bool,
+        op: LogicOp,
+        rd: Register,
+        rn: Register,
+        imm: u64,
+    },
+    LogicalShifted {
+        sf: bool,
+        op: LogicOp,
+        /// The second operand is inverted, `bic`, `orn`, `eon` and `bics`.
+        invert: bool,
+        rd: Register,
+        rn: Register,
+        rm: Register,
+        shift: ShiftType,
+        amount: u32,
+    },
+    MoveWide {
+        sf: bool,
+        op: MoveWideOp,
+        rd: Register,
+        imm: u16,
+        shift: u32,
+    },
+    /// `adr` and `adrp`, the offset of `adrp` is already scaled to pages.
+    Adr {
+        rd: Register,
+        offset: i64,
+        page: bool,
+    },
+    Bitfield {
+        sf: bool,
+        op: BitfieldOp,
+        rd: Register,
+        rn: Register,
+        immr: u32,
+        imms: u32,
+    },
+    Extract {
+        sf: bool,
+        rd: Register,
+        rn: Register,
+        rm: Register,
+        lsb: u32,
+    },
+    ConditionalSelect {
+        sf: bool,
+        op: SelectOp,
+        rd: Register,
+        rn: Register,
+        rm: Register,
+        condition: Condition,
+    },
+    /// `ccmp` and `ccmn`.
+    ConditionalCompare {
+        sf: bool,
+        negative: bool,
+        rn: Register,
+        operand: CompareOperand,
+        nzcv: u8,
+        condition: Condition,
+    },
+    DataProcessing2 {
+        sf: bool,
+        op: DataOp2,
+        rd: Register,
+        rn: Register,
+        rm: Register,
+    },
+    DataProcessing1 {
+        sf: bool,
+        op: DataOp1,
+        rd: Register,
+        rn: Register,
+    },
+    /// `madd` and `msub`.
+    MultiplyAdd {
+        sf: bool,
+        sub: bool,
+        rd: Register,
+        rn: Register,
+        rm: Register,
+        ra: Register,
+    },
+    /// `smaddl`, `smsubl`, `umaddl` and `umsubl`.
+    MultiplyLong {
+        signed: bool,
+        sub: bool,
+        rd: Register,
+        rn: Register,
+        rm: Register,
+        ra: Register,
+    },
+    /// `smulh` and `umulh`.
+    MultiplyHigh {
+        signed: bool,
+        rd: Register,
+        rn: Register,
+        rm: Register,
+    },
+    /// Loads and stores of a single register, including the acquire, release
+    /// and exclusive loads.
+    LoadStore {
+        load: bool,
+        /// Size of the memory access.
+        bits: u32,
+        signed: bool,
+        /// Size of the register that is loaded in to.
+        target_bits: u32,
+        rt: Register,
+        rn: Register,
+        address: Address,
+    },
+    /// `stxr` and `stlxr`, the status is written to `rs`.
+    StoreExclusive {
+        bits: u32,
+        rs: Register,
+        rt: Register,
+        rn: Register,
+    },
+    LoadLiteral {
+        bits: u32,
+        signed: bool,
+        rt: Register,
+        offset: i64,
+    },
+    LoadStorePair {
+        load: bool,
+        bits: u32,
+        signed: bool,
+        rt: Register,
+        rt2: Register,
+        rn: Register,
+        address: Address,
+    },
+    Branch {
+        link: bool,
+        offset: i64,
+    },
+    BranchConditional {
+        condition: Condition,
+        offset: i64,
+    },
+    /// `cbz` and `cbnz`.
+    CompareBranch {
+        sf: bool,
+        nonzero: bool,
+        rt: Register,
+        offset: i64,
+    },
+    /// `tbz` and `tbnz`.
+    TestBranch {
+        nonzero: bool,
+        rt: Register,
+        bit: u32,
+        offset: i64,
+    },
+    /// `br`, `blr` and `ret`.
+    BranchRegister {
+        link: bool,
+        rn: Register,
+    },
+    /// Hints, barriers and prefetches.
+    Nop,
+}
+
+/// Parses the instruction at the start of `buff`.
+pub fn parse(buff: &[u8]) -> Result<Operation, ParseError> {
+    let word = match buff {
+        [b0, b1, b2, b3, ..] => u32::from_le_bytes([*b0, *b1, *b2, *b3]),
+        _ => return Err(ParseError::InsufficientInput),
+    };
+    match bits(word, 28, 25) {
+        0b1000 | 0b1001 => parse_immediate(word),
+        0b1010 | 0b1011 => parse_branch(word),
+        0b0101 | 0b1101 => parse_register(word),
+        0b0100 | 0b0110 | 0b1100 | 0b1110 => parse_load_store(word),
+        _ => Err(ParseError::InvalidInstruction),
+    }
+}
+
+/// Extracts the bits `high..=low` of `word`.
+fn bits(word: u32, high: u32, low: u32) -> u32 {
+    (word >> low) & ((1 << (high - low + 1)) - 1)
+}
+
+fn bit(word: u32, bit: u32) -> bool {
+    (word >> bit) & 1 == 1
+}
+
+/// Sign extends the bits `high..=low` of `word`.
+fn signed_bits(word: u32, high: u32, low: u32) -> i64 {
+    let width = high - low + 1;
+    ((bits(word, high, low) as i64) << (64 - width)) >> (64 - width)
+}
+
+fn rd(word: u32) -> Register {
+    bits(word, 4, 0) as Register
+}
+
+fn rn(word: u32) -> Register {
+    bits(word, 9, 5) as Register
+}
+
+fn rm(word: u32) -> Register {
+    bits(word, 20, 16) as Register
+}
+
+fn condition(code: u32) -> Condition {
+    match code {
+        0b0000 => Condition::EQ,
+        0b0001 => Condition::NE,
+        0b0010 => Condition::CS,
+        0b0011 => Condition::CC,
+        0b0100 => Condition::MI,
+        0b0101 => Condition::PL,
+        0b0110 => Condition::VS,
+        0b0111 => Condition::VC,
+        0b1000 => Condition::HI,
+        0b1001 => Condition::LS,
+        0b1010 => Condition::GE,
+        0b1011 => Condition::LT,
+        0b1100 => Condition::GT,
+        0b1101 => Condition::LE,
+        // `al` and `nv` both mean always.
+        _ => Condition::None,
+    }
+}
+
+fn shift_type(code: u32) -> ShiftType {
+    match code {
+        0b00 => ShiftType::Lsl,
+        0b01 => ShiftType::Lsr,
+        0b10 => ShiftType::Asr,
+        _ => ShiftType::Ror,
+    }
+}
+
+/// The extension selected by the `option` field.
+fn extend(option: u32) -> Extend {
+    Extend {
+        signed: option & 0b100 != 0,
+        bits: 8 << (option & 0b11),
+    }
+}
+
+/// Expands the `N:immr:imms` encoding of a logical immediate, `None` if the
+/// encoding is reserved.
+///
+/// The immediate is an element of 2, 4, 8, 16, 32 or 64 bits with a run of
+/// ones rotated right, repeated to fill the register.
+pub fn decode_bit_masks(n: bool, immr: u32, imms: u32, sf: bool) -> Option<u64> {
+    let combined = ((n as u32) << 6) | (!imms & 0x3f);
+    if combined == 0 || (n && !sf) {
+        return None;
+    }
+    let size = 1 << (31 - combined.leading_zeros());
+    if size < 2 {
+        return None;
+    }
+    let levels = size - 1;
+    let (r, s) = (immr & levels, imms & levels);
+    if s == levels {
+        return None;
+    }
+    let element_mask = match size {
+        64 => u64::MAX,
+        _ => (1 << size) - 1,
+    };
+    let ones = (1u64 << (s + 1)) - 1;
+    let element = match r {
+        0 => ones,
+        r => ((ones >> r) | (ones << (size - r))) & element_mask,
+    };
+    let mut imm = element;
+    let mut filled = size;
+    while filled < 64 {
+        imm |= imm << filled;
+        filled *= 2;
+    }
+    Some(match sf {
+        true => imm,
+        false => imm & 0xffff_ffff,
+    })
+}
+
+fn parse_immediate(word: u32) -> Result<Operation, ParseError> {
+    let sf = bit(word, 31);
+    Ok(match bits(word, 25, 23) {
+        0b000 | 0b001 => {
+            let imm = (signed_bits(word, 23, 5) << 2) | bits(word, 30, 29) as i64;
+            let page = sf;
+            Operation::Adr {
+                rd: rd(word),
+                offset: if page { imm << 12 } else { imm },
+                page,
+            }
+        }
+        0b010 => {
+            let imm = bits(word, 21, 10) as u64;
+            Operation::AddSubImmediate {
+                sf,
+                sub: bit(word, 30),
+                set_flags: bit(word, 29),
+                rd: rd(word),
+                rn: rn(word),
+                imm: if bit(word, 22) { imm << 12 } else { imm },
+            }
+        }
+        0b100 => Operation::LogicalImmediate {
+            sf,
+            op: match bits(word, 30, 29) {
+                0b00 => LogicOp::And,
+                0b01 => LogicOp::Orr,
+                0b10 => LogicOp::Eor,
+                _ => LogicOp::Ands,
+            },
+            rd: rd(word),
+            rn: rn(word),
+            imm: decode_bit_masks(bit(word, 22), bits(word, 21, 16), bits(word, 15, 10), sf)
+                .ok_or(ParseError::InvalidInstruction)?,
+        },
+        0b101 => {
+            let hw = bits(word, 22, 21);
+            if !sf && hw > 1 {
+                return Err(ParseError::InvalidInstruction);
+            }
+            Operation::MoveWide {
+                sf,
+                op: match bits(word, 30, 29) {
+                    0b00 => MoveWideOp::Movn,
+                    0b10 => MoveWideOp::Movz,
+                    0b11 => MoveWideOp::Movk,
+                    _ => return Err(ParseError::InvalidInstruction),
+                },
+                rd: rd(word),
+                imm: bits(word, 20, 5) as u16,
+                shift: hw * 16,
+            }
+        }
+        0b110 => {
+            if bit(word, 22) != sf {
+                return Err(ParseError::InvalidInstruction);
+            }
+            Operation::Bitfield {
+                sf,
+                op: match bits(word, 30, 29) {
+                    0b00 => BitfieldOp::Sbfm,
+                    0b01 => BitfieldOp::Bfm,
+                    0b10 => BitfieldOp::Ubfm,
+                    _ => return Err(ParseError::InvalidInstruction),
+                },
+                rd: rd(word),
+                rn: rn(word),
+                immr: bits(word, 21, 16),
+                imms: bits(word, 15, 10),
+            }
+        }
+        0b111 if bits(word, 30, 29) == 0 && bit(word, 22) == sf && !bit(word, 21) => {
+            Operation::Extract {
+                sf,
+                rd: rd(word),
+                rn: rn(word),
+                rm: rm(word),
+                lsb: bits(word, 15, 10),
+            }
+        }
+        _ => return Err(ParseError::InvalidInstruction),
+    })
+}
+
+fn parse_branch(word: u32) -> Result<Operation, ParseError> {
+    Ok(match bits(word, 31, 25) {
+        0b0001010 | 0b0001011 | 0b1001010 | 0b1001011 => Operation::Branch {
+            link: bit(word, 31),
+            offset: signed_bits(word, 25, 0) << 2,
+        },
+        0b0101010 if !bit(word, 24) && !bit(word, 4) => Operation::BranchConditional {
+            condition: condition(bits(word, 3, 0)),
+            offset: signed_bits(word, 23, 5) << 2,
+        },
+        0b0011010 | 0b1011010 => Operation::CompareBranch {
+            sf: bit(word, 31),
+            nonzero: bit(word, 24),
+            rt: rd(word),
+            offset: signed_bits(word, 23, 5) << 2,
+        },
+        0b0011011 | 0b1011011 => Operation::TestBranch {
+            nonzero: bit(word, 24),
+            rt: rd(word),
+            bit: (bits(word, 31, 31) << 5) | bits(word, 23, 19),
+            offset: signed_bits(word, 18, 5) << 2,
+        },
+        0b1101011 if bits(word, 20, 10) == 0b11111_000000 && rd(word) == 0 => {
+            match bits(word, 24, 21) {
+                0b0000 | 0b0010 => Operation::BranchRegister {
+                    link: false,
+                    rn: rn(word),
+                },
+                0b0001 => Operation::BranchRegister {
+                    link: true,
+                    rn: rn(word),
+                },
+                _ => return Err(ParseError::InvalidInstruction),
+            }
+        }
+        // Hints such as `nop` and the pointer authentication hints, and the
+        // barriers. The pointer authentication hints only sign and check the
+        // return address so they do not change the result of a correct
+        // program.
+        0b1101010 if word & 0xffff_f01f == 0xd503_201f || word & 0xffff_f01f == 0xd503_301f => {
+            Operation::Nop
+        }
+        _ => return Err(ParseError::InvalidInstruction),
+    })
+}
+
+fn parse_register(word: u32) -> Result<Operation, ParseError> {
+    let sf = bit(word, 31);
+    if !bit(word, 28) {
+        let amount = bits(word, 15, 10);
+        if !sf && amount > 31 {
+            return Err(ParseError::InvalidInstruction);
+        }
+        return Ok(match (bit(word, 24), bit(word, 21)) {
+            (false, invert) => Operation::LogicalShifted {
+                sf,
+                op: match bits(word, 30, 29) {
+                    0b00 => LogicOp::And,
+                    0b01 => LogicOp::Orr,
+                    0b10 => LogicOp::Eor,
+                    _ => LogicOp::Ands,
+                },
+                invert,
+                rd: rd(word),
+                rn: rn(word),
+                rm: rm(word),
+                shift: shift_type(bits(word, 23, 22)),
+                amount,
+            },
+            (true, false) => {
+                let shift = shift_type(bits(word, 23, 22));
+                if shift == ShiftType::Ror {
+                    return Err(ParseError::InvalidInstruction);
+                }
+                Operation::AddSubShifted {
+                    sf,
+                    sub: bit(word, 30),
+                    set_flags: bit(word, 29),
+                    rd: rd(word),
+                    rn: rn(word),
+                    rm: rm(word),
+                    shift,
+                    amount,
+                }
+            }
+            (true, true) => {
+                let amount = bits(word, 12, 10);
+                if bits(word, 23, 22) != 0 || amount > 4 {
+                    return Err(ParseError::InvalidInstruction);
+                }
+                Operation::AddSubExtended {
+                    sf,
+                    sub: bit(word, 30),
+                    set_flags: bit(word, 29),
+                    rd: rd(word),
+                    rn: rn(word),
+                    rm: rm(word),
+                    extend: extend(bits(word, 15, 13)),
+                    amount,
+                }
+            }
+        });
+    }
+
+    if bit(word, 24) {
+        if bits(word, 30, 29) != 0 {
+            return Err(ParseError::InvalidInstruction);
+        }
+        let sub = bit(word, 15);
+        let ra = bits(word, 14, 10) as Register;
+        return Ok(match (bits(word, 23, 21), sub) {
+            (0b000, sub) => Operation::MultiplyAdd {
+                sf,
+                sub,
+                rd: rd(word),
+                rn: rn(word),
+                rm: rm(word),
+                ra,
+            },
+            (0b001 | 0b101, sub) if sf => Operation::MultiplyLong {
+                signed: !bit(word, 23),
+                sub,
+                rd: rd(word),
+                rn: rn(word),
+                rm: rm(word),
+                ra,
+            },
+            (0b010 | 0b110, false) if sf => Operation::MultiplyHigh {
+                signed: !bit(word, 23),
+                rd: rd(word),
+                rn: rn(word),
+                rm: rm(word),
+            },
+            _ => return Err(ParseError::InvalidInstruction),
+        });
+    }
+
+    Ok(match bits(word, 23, 21) {
+        0b000 if bits(word, 15, 10) == 0 => Operation::AddSubCarry {
+            sf,
+            sub: bit(word, 30),
+            set_flags: bit(word, 29),
+            rd: rd(word),
+            rn: rn(word),
+            rm: rm(word),
+        },
+        0b010 if bit(word, 29) && !bit(word, 10) && !bit(word, 4) => {
+            Operation::ConditionalCompare {
+                sf,
+                negative: !bit(word, 30),
+                rn: rn(word),
+                operand: match bit(word, 11) {
+                    true => CompareOperand::Immediate(rm(word)),
+                    false => CompareOperand::Register(rm(word)),
+                },
+                nzcv: bits(word, 3, 0) as u8,
+                condition: condition(bits(word, 15, 12)),
+            }
+        }
+        0b100 if !bit(word, 29) && !bit(word, 11) => Operation::ConditionalSelect {
+            sf,
+            op: match (bit(word, 30), bit(word, 10)) {
+                (false, false) => SelectOp::Csel,
+                (false, true) => SelectOp::Csinc,
+                (true, false) => SelectOp::Csinv,
+                (true, true) => SelectOp::Csneg,
+            },
+            rd: rd(word),
+            rn: rn(word),
+            rm: rm(word),
+            condition: condition(bits(word, 15, 12)),
+        },
+        0b110 if !bit(word, 29) && !bit(word, 30) => Operation::DataProcessing2 {
+            sf,
+            op: match bits(word, 15, 10) {
+                0b000010 => DataOp2::Udiv,
+                0b000011 => DataOp2::Sdiv,
+                0b001000 => DataOp2::Lslv,
+                0b001001 => DataOp2::Lsrv,
+                0b001010 => DataOp2::Asrv,
+                0b001011 => DataOp2::Rorv,
+                _ => return Err(ParseError::InvalidInstruction),
+            },
+            rd: rd(word),
+            rn: rn(word),
+            rm: rm(word),
+        },
+        0b110 if !bit(word, 29) && rm(word) == 0 => Operation::DataProcessing1 {
+            sf,
+            op: match (bits(word, 15, 10), sf) {
+                (0b000000, _) => DataOp1::Rbit,
+                (0b000001, _) => DataOp1::Rev16,
+                (0b000010, false) | (0b000011, true) => DataOp1::Rev,
+                (0b000010, true) => DataOp1::Rev32,
+                (0b000100, _) => DataOp1::Clz,
+                (0b000101, _) => DataOp1::Cls,
+                _ => return Err(ParseError::InvalidInstruction),
+            },
+            rd: rd(word),
+            rn: rn(word),
+        },
+        _ => return Err(ParseError::InvalidInstruction),
+    })
+}
+
+fn parse_load_store(word: u32) -> Result<Operation, ParseError> {
+    // Loads and stores of the SIMD and floating point registers.
+    if bit(word, 26) {
+        return Err(ParseError::InvalidInstruction);
+    }
+    match bits(word, 29, 27) {
+        0b001 if bits(word, 24, 24) == 0 => parse_exclusive(word),
+        0b011 if bits(word, 25, 24) == 0 => {
+            let offset = signed_bits(word, 23, 5) << 2;
+            Ok(match bits(word, 31, 30) {
+                0b00 => Operation::LoadLiteral {
+                    bits: 32,
+                    signed: false,
+                    rt: rd(word),
+                    offset,
+                },
+                0b01 => Operation::LoadLiteral {
+                    bits: 64,
+                    signed: false,
+                    rt: rd(word),
+                    offset,
+                },
+                0b10 => Operation::LoadLiteral {
+                    bits: 32,
+                    signed: true,
+                    rt: rd(word),
+                    offset,
+                },
+                _ => Operation::Nop,
+            })
+        }
+        0b101 => {
+            let load = bit(word, 22);
+            let (bits_, signed) = match (bits(word, 31, 30), load) {
+                (0b00, _) => (32, false),
+                (0b01, true) => (32, true),
+                (0b10, _) => (64, false),
+                _ => return Err(ParseError::InvalidInstruction),
+            };
+            let offset = signed_bits(word, 21, 15) * (bits_ as i64 / 8);
+            Ok(Operation::LoadStorePair {
+                load,
+                bits: bits_,
+                signed,
+                rt: rd(word),
+                rt2: bits(word, 14, 10) as Register,
+                rn: rn(word),
+                address: match bits(word, 24, 23) {
+                    0b01 => Address::PostIndex(offset),
+                    0b11 => Address::PreIndex(offset),
+                    _ => Address::Offset(offset),
+                },
+            })
+        }
+        0b111 => parse_load_store_register(word),
+        _ => Err(ParseError::InvalidInstruction),
+    }
+}
+
+/// The exclusive and the acquire/release loads and stores. There is only a
+/// single core so exclusive stores always succeed.
+fn parse_exclusive(word: u32) -> Result<Operation, ParseError> {
+    let bits_ = 8 << bits(word, 31, 30);
+    let load = bit(word, 22);
+    // The pair variants.
+    if bit(word, 21) {
+        return Err(ParseError::InvalidInstruction);
+    }
+    let exclusive = !bit(word, 23);
+    Ok(match (load, exclusive) {
+        (false, true) => Operation::StoreExclusive {
+            bits: bits_,
+            rs: rm(word),
+            rt: rd(word),
+            rn: rn(word),
+        },
+        (load, _) => Operation::LoadStore {
+            load,
+            bits: bits_,
+            signed: false,
+            target_bits: 64,
+            rt: rd(word),
+            rn: rn(word),
+            address: Address::Offset(0),
+        },
+    })
+}
+
+fn parse_load_store_register(word: u32) -> Result<Operation, ParseError> {
+    let size = bits(word, 31, 30);
+    let bits_ = 8 << size;
+    let (load, signed, target_bits) = match (bits(word, 23, 22), size) {
+        (0b00, _) => (false, false, 64),
+        (0b01, _) => (true, false, 64),
+        // Prefetches.
+        (0b10, 0b11) => return Ok(Operation::Nop),
+        (0b10, _) => (true, true, 64),
+        (0b11, 0b00 | 0b01) => (true, true, 32),
+        _ => return Err(ParseError::InvalidInstruction),
+    };
+    let address = if bit(word, 24) {
+        Address::Offset((bits(word, 21, 10) as i64) << size)
+    } else if !bit(word, 21) {
+        let offset = signed_bits(word, 20, 12);
+        match bits(word, 11, 10) {
+            0b01 => Address::PostIndex(offset),
+            0b11 => Address::PreIndex(offset),
+            // The unscaled and the unprivileged variants.
+            _ => Address::Offset(offset),
+        }
+    } else if bits(word, 11, 10) == 0b10 {
+        let option = bits(word, 15, 13);
+        if option & 0b010 == 0 {
+            return Err(ParseError::InvalidInstruction);
+        }
+        Address::Register {
+            rm: rm(word),
+            extend: extend(option),
+            shift: if bit(word, 12) { size } else { 0 },
+        }
+    } else {
+        // Atomic memory operations.
+        return Err(ParseError::InvalidInstruction);
+    };
+    Ok(Operation::LoadStore {
+        load,
+        bits: bits_,
+        signed,
+        target_bits,
+        rt: rd(word),
+        rn: rn(word),
+        address,
+    })
+}
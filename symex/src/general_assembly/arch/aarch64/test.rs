@@ -0,0 +1,437 @@
+use std::collections::HashMap;
+
+use general_assembly::{condition::Condition, register::Register};
+
+use super::{
+    parser::{decode_bit_masks, parse, Address, Extend, Operation, LR},
+    timing::{self, cycle_count},
+    AArch64,
+    REGISTERS,
+};
+use crate::{
+    general_assembly::{
+        arch::{Arch, ArchError},
+        executor::PathResult,
+        instruction::CycleCount,
+        project::{
+            target_config::{AArch64Core, ArmMCore, CoreFamily, TargetConfiguration},
+            PCHook,
+            Project,
+        },
+        state::GAState,
+        vm::VM,
+        Endianness,
+        WordSize,
+    },
+    smt::{DContext, DSolver},
+};
+
+/// Sets up a vm running `code` from address 0 that returns to 0x1000, with
+/// the stack pointer at 0x2000_0000 and `registers` set to the given values.
+fn setup_test_vm_with_code(code: &[u32], registers: &[(&str, u64)]) -> VM<AArch64> {
+    setup_test_vm_with_target(code, registers, AArch64::default_target())
+}
+
+fn setup_test_vm_with_target(
+    code: &[u32],
+    registers: &[(&str, u64)],
+    target: TargetConfiguration,
+) -> VM<AArch64> {
+    // Instructions are read a double word at a time so the last word is padded.
+    let program: Vec<u8> = code
+        .iter()
+        .chain(&[0])
+        .flat_map(|word| word.to_le_bytes())
+        .collect();
+    let end = program.len() as u64;
+    let mut project = Box::new(Project::manual_project(
+        program,
+        0,
+        end,
+        WordSize::Bit64,
+        Endianness::Little,
+        HashMap::new(),
+        HashMap::new(),
+        Vec::new(),
+        Vec::new(),
+        HashMap::new(),
+        vec![],
+        HashMap::new(),
+        vec![],
+    ));
+    project.set_target(target);
+    let arch = AArch64;
+    project.add_hooks(&arch);
+    project.add_pc_hook(0x1000, PCHook::EndSuccess);
+
+    let project = Box::leak(project);
+    let context = Box::new(DContext::new());
+    let context = Box::leak(context);
+    let solver = DSolver::new(context);
+    let mut state = GAState::create_test_state(project, context, solver, 0, 0x2000_0000, arch);
+    state
+        .set_register(Register::LR, context.from_u64(0x1000, 64))
+        .unwrap();
+    for (register, value) in registers {
+        state
+            .set_register(REGISTERS.named(register), context.from_u64(*value, 64))
+            .unwrap();
+    }
+    VM::new_with_state(project, state)
+}
+
+/// Runs `code` to completion and returns the final state of the single path.
+fn run(code: &[u32], registers: &[(&str, u64)]) -> GAState<AArch64> {
+    let mut vm = setup_test_vm_with_code(code, registers);
+    let (result, state) = vm.run().unwrap().unwrap();
+    assert!(matches!(result, PathResult::Success(_)));
+    assert!(vm.run().unwrap().is_none(), "Expected a single path");
+    state
+}
+
+fn register(state: &mut GAState<AArch64>, register: &str) -> u64 {
+    state
+        .get_register(REGISTERS.named(register))
+        .unwrap()
+        .get_constant()
+        .expect("Could not get register as constant")
+}
+
+fn parsed(word: u32) -> Operation {
+    parse(&word.to_le_bytes()).unwrap()
+}
+
+#[test]
+fn test_parse() {
+    assert_eq!(parsed(0xf85f40ea), Operation::LoadStore {
+        load: true,
+        bits: 64,
+        signed: false,
+        target_bits: 64,
+        rt: 10,
+        rn: 7,
+        address: Address::Offset(-12),
+    });
+    assert_eq!(parsed(0x79802fe2), Operation::LoadStore {
+        load: true,
+        bits: 16,
+        signed: true,
+        target_bits: 64,
+        rt: 2,
+        rn: 31,
+        address: Address::Offset(22),
+    });
+    assert_eq!(parsed(0x78657ba6), Operation::LoadStore {
+        load: true,
+        bits: 16,
+        signed: false,
+        target_bits: 64,
+        rt: 6,
+        rn: 29,
+        address: Address::Register {
+            rm: 5,
+            extend: Extend {
+                signed: false,
+                bits: 64
+            },
+            shift: 1
+        },
+    });
+    assert_eq!(parsed(0xa9be7bfd), Operation::LoadStorePair {
+        load: false,
+        bits: 64,
+        signed: false,
+        rt: 29,
+        rt2: 30,
+        rn: 31,
+        address: Address::PreIndex(-32),
+    });
+    assert_eq!(parsed(0x540000c1), Operation::BranchConditional {
+        condition: Condition::NE,
+        offset: 24,
+    });
+    assert_eq!(parsed(0x360800a0), Operation::TestBranch {
+        nonzero: false,
+        rt: 0,
+        bit: 1,
+        offset: 20,
+    });
+    assert_eq!(parsed(0xd65f03c0), Operation::BranchRegister {
+        link: false,
+        rn: LR
+    });
+    // nop and dmb ish.
+    assert_eq!(parsed(0xd503201f), Operation::Nop);
+    assert_eq!(parsed(0xd5033bbf), Operation::Nop);
+
+    // fadd d0, d1, d2 is not part of the integer subset.
+    assert!(parse(&0x1e622820u32.to_le_bytes()).is_err());
+    assert!(parse(&[0x1f, 0x20]).is_err());
+}
+
+#[test]
+fn test_decode_bit_masks() {
+    // and x11, x0, #0xffff0000
+    assert_eq!(decode_bit_masks(true, 48, 15, true), Some(0xffff_0000));
+    // orr w12, wzr, #0x55555555
+    assert_eq!(
+        decode_bit_masks(false, 0, 0b111100, false),
+        Some(0x5555_5555)
+    );
+    // A 64 bit element is not valid for 32 bit operations.
+    assert_eq!(decode_bit_masks(true, 0, 0, false), None);
+    // All ones is not a valid immediate.
+    assert_eq!(decode_bit_masks(true, 0, 0b111111, true), None);
+}
+
+#[test]
+fn test_arithmetic_and_logic() {
+    let code = [
+        0xd2a24680, // movz x0, #0x1234, lsl #16
+        0xf28acf00, // movk x0, #0x5678
+        0x92800001, // mov x1, #-1
+        0x31000422, // adds w2, w1, #1
+        0x9a9f37e3, // cset x3, cs
+        0x9a9f17e4, // cset x4, eq
+        0xab001005, // adds x5, x0, x0, lsl #4
+        0x7119e006, // subs w6, w0, #0x678
+        0x9a9f97e7, // cset x7, hi
+        0x52b00008, // mov w8, #0x80000000
+        0x71000509, // subs w9, w8, #1
+        0x9a9f77ea, // cset x10, vs
+        0x92703c0b, // and x11, x0, #0xffff0000
+        0x3200f3ec, // orr w12, wzr, #0x55555555
+        0xca3f000d, // eon x13, x0, xzr
+        0x8a20002e, // bic x14, x1, x0
+        0xd3483c0f, // ubfx x15, x0, #8, #8
+        0x93431010, // sbfx x16, x0, #3, #2
+        0xd378dc11, // lsl x17, x0, #8
+        0xaa0103f2, // mov x18, x1
+        0xb3783c12, // bfi x18, x0, #8, #16
+        0x13047d13, // asr w19, w8, #4
+        0x13802014, // ror w20, w0, #8
+        0xd65f03c0, // ret
+    ];
+    let mut state = run(&code, &[]);
+    let x0 = 0x1234_5678;
+    assert_eq!(register(&mut state, "X0"), x0);
+    assert_eq!(register(&mut state, "X1"), u64::MAX);
+    // Writes to W registers clear the upper half.
+    assert_eq!(register(&mut state, "X2"), 0);
+    assert_eq!(register(&mut state, "X3"), 1);
+    assert_eq!(register(&mut state, "X4"), 1);
+    assert_eq!(register(&mut state, "X5"), x0 * 17);
+    assert_eq!(register(&mut state, "X6"), 0x1234_5000);
+    assert_eq!(register(&mut state, "X7"), 1);
+    assert_eq!(register(&mut state, "X8"), 0x8000_0000);
+    assert_eq!(register(&mut state, "X9"), 0x7fff_ffff);
+    assert_eq!(register(&mut state, "X10"), 1);
+    assert_eq!(register(&mut state, "X11"), 0x1234_0000);
+    assert_eq!(register(&mut state, "X12"), 0x5555_5555);
+    assert_eq!(register(&mut state, "X13"), !x0);
+    assert_eq!(register(&mut state, "X14"), !x0);
+    assert_eq!(register(&mut state, "X15"), 0x56);
+    assert_eq!(register(&mut state, "X16"), u64::MAX);
+    assert_eq!(register(&mut state, "X17"), x0 << 8);
+    assert_eq!(register(&mut state, "X18"), 0xffff_ffff_ff56_78ff);
+    assert_eq!(register(&mut state, "X19"), 0xf800_0000);
+    assert_eq!(register(&mut state, "X20"), 0x7812_3456);
+}
+
+#[test]
+fn test_add_sub_carry() {
+    let code = [
+        0xeb00001f, // cmp x0, x0
+        0xba010002, // adcs x2, x0, x1
+        0x9a9f77e6, // cset x6, vs
+        0x9a9f37e7, // cset x7, cs
+        0xfa040065, // sbcs x5, x3, x4
+        0x9a9f77e8, // cset x8, vs
+        0x9a9f37e9, // cset x9, cs
+        0x3a0c016a, // adcs w10, w11, w12
+        0x9a9f77ed, // cset x13, vs
+        0x9a9f37ee, // cset x14, cs
+        0xd65f03c0, // ret
+    ];
+    let registers = [
+        ("X0", i64::MAX as u64),
+        ("X1", 0),
+        ("X3", 1 << 63),
+        ("X4", 0),
+        ("X11", i32::MAX as u64),
+        ("X12", 0),
+    ];
+    let mut state = run(&code, &registers);
+    // The overflow flag uses the carry in, not the carry out.
+    assert_eq!(register(&mut state, "X2"), 1 << 63);
+    assert_eq!(register(&mut state, "X6"), 1);
+    assert_eq!(register(&mut state, "X7"), 0);
+    assert_eq!(register(&mut state, "X5"), i64::MAX as u64);
+    assert_eq!(register(&mut state, "X8"), 1);
+    assert_eq!(register(&mut state, "X9"), 1);
+    assert_eq!(register(&mut state, "X10"), 1 << 31);
+    assert_eq!(register(&mut state, "X13"), 1);
+    assert_eq!(register(&mut state, "X14"), 0);
+}
+
+#[test]
+fn test_multiply_divide() {
+    let code = [
+        0xd2800c80, // mov x0, #100
+        0xd28000e1, // mov x1, #7
+        0x9ac10802, // udiv x2, x0, x1
+        0x1adf0c23, // sdiv w3, w1, wzr
+        0x9b018044, // msub x4, x2, x1, x0
+        0x92800045, // mov x5, #-3
+        0x9b407ca6, // smulh x6, x5, x0
+        0x9bc07ca7, // umulh x7, x5, x0
+        0x9b2100a8, // smaddl x8, w5, w1, x0
+        0x5ac00809, // rev w9, w0
+        0xdac0100a, // clz x10, x0
+        0x5ac0100b, // clz w11, w0
+        0x5ac0002c, // rbit w12, w1
+        0xdac014ad, // cls x13, x5
+        0x9ac124ae, // lsr x14, x5, x1
+        0x9ac12c0f, // ror x15, x0, x1
+        0xdac00410, // rev16 x16, x0
+        0xd65f03c0, // ret
+    ];
+    let mut state = run(&code, &[]);
+    let x5 = -3i64 as u64;
+    assert_eq!(register(&mut state, "X2"), 14);
+    // Division by zero results in zero.
+    assert_eq!(register(&mut state, "X3"), 0);
+    assert_eq!(register(&mut state, "X4"), 2);
+    assert_eq!(register(&mut state, "X5"), x5);
+    assert_eq!(register(&mut state, "X6"), u64::MAX);
+    assert_eq!(register(&mut state, "X7"), 99);
+    assert_eq!(register(&mut state, "X8"), 79);
+    assert_eq!(register(&mut state, "X9"), 0x6400_0000);
+    assert_eq!(register(&mut state, "X10"), 57);
+    assert_eq!(register(&mut state, "X11"), 25);
+    assert_eq!(register(&mut state, "X12"), 0xe000_0000);
+    assert_eq!(register(&mut state, "X13"), 61);
+    assert_eq!(register(&mut state, "X14"), x5 >> 7);
+    assert_eq!(register(&mut state, "X15"), 100u64.rotate_right(7));
+    assert_eq!(register(&mut state, "X16"), 0x6400);
+}
+
+#[test]
+fn test_load_store() {
+    let code = [
+        0xa9be7bfd, // stp x29, x30, [sp, #-32]!
+        0x910003fd, // mov x29, sp
+        0xd2822440, // mov x0, #0x1122
+        0xf2f11320, // movk x0, #0x8899, lsl #48
+        0xf9000be0, // str x0, [sp, #16]
+        0x394043e1, // ldrb w1, [sp, #16]
+        0x79802fe2, // ldrsh x2, [sp, #22]
+        0xb94013e3, // ldr w3, [sp, #16]
+        0xb98017e4, // ldrsw x4, [sp, #20]
+        0xd2800105, // mov x5, #8
+        0x78657ba6, // ldrh w6, [x29, x5, lsl #1]
+        0x910063e7, // add x7, sp, #24
+        0xb80044e0, // str w0, [x7], #4
+        0xb9401be8, // ldr w8, [sp, #24]
+        0x910003e9, // mov x9, sp
+        0xcb0900e9, // sub x9, x7, x9
+        0xf85f40ea, // ldur x10, [x7, #-12]
+        0xa8c27bfd, // ldp x29, x30, [sp], #32
+        0xd65f03c0, // ret
+    ];
+    let mut state = run(&code, &[]);
+    assert_eq!(register(&mut state, "X1"), 0x22);
+    assert_eq!(register(&mut state, "X2"), 0xffff_ffff_ffff_8899);
+    assert_eq!(register(&mut state, "X3"), 0x1122);
+    assert_eq!(register(&mut state, "X4"), 0xffff_ffff_8899_0000);
+    assert_eq!(register(&mut state, "X6"), 0x1122);
+    assert_eq!(register(&mut state, "X8"), 0x1122);
+    assert_eq!(register(&mut state, "X9"), 28);
+    assert_eq!(register(&mut state, "X10"), 0x8899_0000_0000_1122);
+    assert_eq!(register(&mut state, "SP"), 0x2000_0000);
+}
+
+#[test]
+fn test_branches() {
+    let code = [
+        0xd28000a0, // mov x0, #5
+        0xaa1e03f3, // mov x19, x30
+        0x9400000d, // bl double
+        0xf100281f, // cmp x0, #10
+        0xb5000040, // cbnz x0, 1f
+        0xd2800000, // mov x0, #0
+        0x540000c1, // 1: b.ne fail
+        0x360800a0, // tbz w0, #1, fail
+        0x37000080, // tbnz w0, #0, fail
+        0xd2800021, // mov x1, #1
+        0xaa1303fe, // mov x30, x19
+        0xd65f03c0, // ret
+        0xd2800001, // fail: mov x1, #0
+        0xaa1303fe, // mov x30, x19
+        0xd65f03c0, // ret
+        0x8b000000, // double: add x0, x0, x0
+        0xd65f03c0, // ret
+    ];
+    let mut state = run(&code, &[]);
+    assert_eq!(register(&mut state, "X0"), 10);
+    // cbnz and tbz leave the flags of the cmp intact.
+    assert_eq!(register(&mut state, "X1"), 1);
+}
+
+#[test]
+fn test_conditional_select() {
+    let code = [
+        0xf100281f, // cmp x0, #10
+        0xfa433820, // ccmp x1, #3, #0, lo
+        0x9a810002, // csel x2, x0, x1, eq
+        0x1a9f27e3, // cset w3, lo
+        0xd65f03c0, // ret
+    ];
+    for (x0, x1, x2, x3) in [(5, 3, 5, 0), (5, 2, 2, 1), (10, 3, 3, 1)] {
+        let mut state = run(&code, &[("X0", x0), ("X1", x1)]);
+        assert_eq!(register(&mut state, "X2"), x2);
+        assert_eq!(register(&mut state, "X3"), x3);
+    }
+
+    // Conditional instructions do not fork on symbolic flags.
+    let mut state = run(&code, &[]);
+    assert!(state
+        .get_register(REGISTERS.named("X2"))
+        .unwrap()
+        .get_constant()
+        .is_none());
+}
+
+#[test]
+fn test_cycles() {
+    let cycles = |word: u32| match cycle_count(&parsed(word)) {
+        CycleCount::Value(cycles) => cycles,
+        CycleCount::Function(_) => panic!("Expected a fixed number of cycles"),
+    };
+    // adds x5, x0, x0, lsl #4
+    assert_eq!(cycles(0xab001005), timing::ALU_SHIFTED);
+    // udiv x2, x0, x1
+    assert_eq!(cycles(0x9ac10802), timing::DIV_64);
+    // ldrh w6, [x29, x5, lsl #1]
+    assert_eq!(cycles(0x78657ba6), timing::LOAD_REGISTER_OFFSET);
+    // b.ne
+    assert_eq!(cycles(0x540000c1), timing::BRANCH_MISPREDICTED);
+    // ret and blr x8
+    assert_eq!(cycles(0xd65f03c0), timing::BRANCH);
+    assert_eq!(cycles(0xd63f0100), timing::BRANCH_MISPREDICTED);
+}
+
+#[test]
+fn test_incompatible_target() {
+    let target = TargetConfiguration::zero_wait_states(CoreFamily::ArmM(ArmMCore::ArmM4));
+    let mut vm = setup_test_vm_with_target(&[], &[], target);
+    let state = vm.paths.get_path().unwrap().state;
+    assert!(matches!(
+        AArch64.translate(&0xd65f03c0u32.to_le_bytes(), &state),
+        Err(ArchError::IncompatibleTarget)
+    ));
+    assert_eq!(
+        AArch64::default_target().core,
+        CoreFamily::AArch64(AArch64Core::CortexA53)
+    );
+}
@@ -0,0 +1,106 @@
+//! Cycle model for the Cortex-A53.
+//!
+//! The counts are upper bounds on the latency of each instruction class
+//! taken from the Cortex-A53 software optimization guide.
+//! Dual issue is not modeled and every conditional or indirect branch is
+//! assumed to be mispredicted, loads are assumed to hit in the L1 cache
+//! unless the target configuration describes slower memory.
+
+use super::{
+    parser::{Address, DataOp2, Operation, ShiftType, LR},
+    AArch64,
+};
+use crate::general_assembly::instruction::CycleCount;
+
+/// Integer instructions without a shifted or extended operand.
+pub const ALU: usize = 1;
+
+/// Integer instructions with a shifted or extended register operand.
+pub const ALU_SHIFTED: usize = 2;
+
+/// 32 bit multiplications.
+pub const MUL_32: usize = 3;
+
+/// 64 bit and long multiplications.
+pub const MUL_64: usize = 5;
+
+/// 32 bit divisions.
+pub const DIV_32: usize = 12;
+
+/// 64 bit divisions.
+pub const DIV_64: usize = 20;
+
+/// Loads that hit in the L1 cache.
+pub const LOAD: usize = 3;
+
+/// Loads with a register offset.
+pub const LOAD_REGISTER_OFFSET: usize = 4;
+
+/// Stores.
+pub const STORE: usize = 1;
+
+/// Predicted branches.
+pub const BRANCH: usize = 1;
+
+/// Mispredicted branches.
+pub const BRANCH_MISPREDICTED: usize = 8;
+
+/// The number of cycles needed to execute `operation`.
+pub fn cycle_count(operation: &Operation) -> CycleCount<AArch64> {
+    CycleCount::Value(match operation {
+        Operation::AddSubShifted { amount: 0, .. } => ALU,
+        Operation::LogicalShifted {
+            amount: 0,
+            shift: ShiftType::Lsl,
+            ..
+        } => ALU,
+        Operation::AddSubShifted { .. }
+        | Operation::LogicalShifted { .. }
+        | Operation::AddSubExtended { .. } => ALU_SHIFTED,
+        Operation::AddSubImmediate { .. }
+        | Operation::AddSubCarry { .. }
+        | Operation::LogicalImmediate { .. }
+        | Operation::MoveWide { .. }
+        | Operation::Adr { .. }
+        | Operation::Bitfield { .. }
+        | Operation::Extract { .. }
+        | Operation::ConditionalSelect { .. }
+        | Operation::ConditionalCompare { .. }
+        | Operation::DataProcessing1 { .. }
+        | Operation::Nop => ALU,
+        Operation::DataProcessing2 {
+            op: DataOp2::Udiv | DataOp2::Sdiv,
+            sf,
+            ..
+        } => match sf {
+            true => DIV_64,
+            false => DIV_32,
+        },
+        Operation::DataProcessing2 { .. } => ALU,
+        Operation::MultiplyAdd { sf: false, .. } => MUL_32,
+        Operation::MultiplyAdd { .. }
+        | Operation::MultiplyLong { .. }
+        | Operation::MultiplyHigh { .. } => MUL_64,
+        Operation::LoadStore {
+            load: true,
+            address: Address::Register { .. },
+            ..
+        } => LOAD_REGISTER_OFFSET,
+        Operation::LoadStore { load: true, .. }
+        | Operation::LoadLiteral { .. }
+        | Operation::LoadStorePair { load: true, .. } => LOAD,
+        Operation::LoadStore { .. }
+        | Operation::StoreExclusive { .. }
+        | Operation::LoadStorePair { .. } => STORE,
+        // Returns are predicted by the return stack.
+        Operation::Branch { .. }
+        | Operation::BranchRegister {
+            link: false,
+            rn: LR,
+        } => BRANCH,
+        Operation::BranchConditional { .. }
+        | Operation::CompareBranch { .. }
+        | Operation::TestBranch { .. }
+        | Operation::BranchRegister { .. } => BRANCH_MISPREDICTED,
+    })
+}
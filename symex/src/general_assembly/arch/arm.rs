@@ -1,32 +1,96 @@
 //! Defines the supported ARM architectures
 pub mod v6;
 pub mod v7;
+pub mod v8;
 
+use general_assembly::register::RegisterSet;
 use object::ObjectSection;
 
-use super::ArchError;
+use super::{file_attributes, ntbs, uleb128, ArchError};
+use crate::general_assembly::Endianness;
+
+/// The registers of the M-profile architectures.
+///
+/// Armv8-M is translated with the Armv7-M decoder so the profiles share one
+/// set, the registers that a profile does not have are never accessed. `PC+`
+/// is the PC as read by an instruction, `SP&` is the SP with the alignment and
+/// stack limit checks and `LastAddr` is the address of the last load or store.
+pub const REGISTERS: RegisterSet = RegisterSet::new(&[
+    "R0",
+    "R1",
+    "R2",
+    "R3",
+    "R4",
+    "R5",
+    "R6",
+    "R7",
+    "R8",
+    "R9",
+    "R10",
+    "R11",
+    "R12",
+    "APSR",
+    "IAPSR",
+    "EAPSR",
+    "XPSR",
+    "IPSR",
+    "EPSR",
+    "IEPSR",
+    "MSP",
+    "PSP",
+    "PRIMASK",
+    "BASEPRI",
+    "FAULTMASK",
+    "CONTROL",
+    "MSPLIM",
+    "PSPLIM",
+    "PC+",
+    "SP&",
+    "LastAddr",
+]);
 
 #[non_exhaustive]
 #[allow(dead_code)]
 enum ArmIsa {
     ArmV6M,
     ArmV7EM,
+    ArmV8MBaseline,
+    ArmV8MMainline,
 }
 
-fn arm_isa<'a, T: ObjectSection<'a>>(section: &T) -> Result<ArmIsa, ArchError> {
+/// The `Tag_CPU_arch` attribute.
+const TAG_CPU_ARCH: u64 = 6;
+
+fn arm_isa<'a, T: ObjectSection<'a>>(
+    section: &T,
+    endianness: Endianness,
+) -> Result<ArmIsa, ArchError> {
     let data = section.data().map_err(|_| ArchError::MalformedSection)?;
-    // Magic extraction
-    //
-    // the index here is from
+    attributes_isa(data, endianness)
+}
+
+/// Finds the ISA from the contents of the `.ARM.attributes` section.
+fn attributes_isa(data: &[u8], endianness: Endianness) -> Result<ArmIsa, ArchError> {
+    // The tags and the f_cpu_arch values are listed in
     // https://github.com/ARM-software/abi-aa/blob/main/addenda32/addenda32.rst
-    //
-    // so are the f_cpu_arch values
-    //
-    // This offset might be a bit hacky
-    let f_cpu_arch = match data.get(6 * 4 - 1) {
-        Some(el) => Ok(el),
-        None => Err(ArchError::MalformedSection),
-    }?;
+    let mut attributes =
+        file_attributes(data, b"aeabi", endianness)?.ok_or(ArchError::MalformedSection)?;
+    let f_cpu_arch = loop {
+        if attributes.is_empty() {
+            return Err(ArchError::MalformedSection);
+        }
+        let (tag, rest) = uleb128(attributes)?;
+        attributes = match tag {
+            TAG_CPU_ARCH => break uleb128(rest)?.0,
+            // Tag_compatibility is a flag followed by a vendor name.
+            32 => ntbs(uleb128(rest)?.1)?.1,
+            // Tag_CPU_raw_name, Tag_CPU_name, Tag_conformance and the odd tags
+            // above 32 are strings, the rest are integers.
+            4 | 5 | 67 => ntbs(rest)?.1,
+            tag if tag > 32 && tag % 2 == 1 => ntbs(rest)?.1,
+            _ => uleb128(rest)?.1,
+        };
+    };
 
     match f_cpu_arch {
         // Cortex-m3, this should really be Arvm7M.
@@ -37,6 +101,12 @@ fn arm_isa<'a, T: ObjectSection<'a>>(section: &T) -> Result<ArmIsa, ArchError> {
         // Cortex-m4
         13 => Ok(ArmIsa::ArmV7EM),
 
+        // Cortex-m23
+        16 => Ok(ArmIsa::ArmV8MBaseline),
+
+        // Cortex-m33
+        17 => Ok(ArmIsa::ArmV8MMainline),
+
         _ => Err(ArchError::UnsuportedArchitechture),
     }
 }
@@ -6,20 +6,25 @@ pub mod timing;
 use std::fmt::Display;
 
 use armv6_m_instruction_parser::Error;
+use general_assembly::register::{Register, RegisterSet};
 use object::{File, Object};
 use regex::Regex;
-use tracing::trace;
-
-use super::{arm_isa, ArmIsa};
-use crate::{
-    elf_util::{ExpressionType, Variable},
-    general_assembly::{
-        arch::{Arch, ArchError, ParseError},
-        instruction::Instruction,
-        project::{MemoryHookAddress, MemoryReadHook, PCHook, RegisterReadHook, RegisterWriteHook},
-        state::GAState,
-        RunConfig,
+
+use super::{arm_isa, ArmIsa, REGISTERS};
+use crate::general_assembly::{
+    arch::{make_symbolic, Arch, ArchError, ParseError},
+    instruction::Instruction,
+    project::{
+        target_config::{ArmMCore, CoreFamily, MemoryRegion, TargetConfiguration},
+        MemoryHookAddress,
+        MemoryReadHook,
+        PCHook,
+        RegisterReadHook,
+        RegisterWriteHook,
     },
+    state::GAState,
+    Endianness,
+    RunConfig,
 };
 
 /// Type level denotation for the
@@ -28,45 +33,37 @@ use crate::{
 pub struct ArmV6M {}
 
 impl Arch for ArmV6M {
+    const REGISTERS: &'static RegisterSet = &REGISTERS;
+
     fn add_hooks(&self, cfg: &mut RunConfig<Self>) {
         let symbolic_sized = |state: &mut GAState<Self>| {
-            let value_ptr = state.get_register("R0".to_owned())?;
-            let size = state.get_register("R1".to_owned())?.get_constant().unwrap() * 8;
-            trace!(
-                "trying to create symbolic: addr: {:?}, size: {}",
-                value_ptr,
-                size
-            );
-            let name = "any".to_owned() + &state.marked_symbolic.len().to_string();
-            let symb_value = state.ctx.unconstrained(size as u32, &name);
-            state.marked_symbolic.push(Variable {
-                name: Some(name),
-                value: symb_value.clone(),
-                ty: ExpressionType::Integer(size as usize),
-            });
-            state.memory.write(&value_ptr, symb_value)?;
-
-            let lr = state.get_register("LR".to_owned())?;
-            state.set_register("PC".to_owned(), lr)?;
+            let pointer = const { REGISTERS.named("R0") };
+            let size = const { REGISTERS.named("R1") };
+            make_symbolic(state, pointer, size)?;
+
+            let lr = state.get_register(Register::LR)?;
+            state.set_register(Register::PC, lr)?;
             Ok(())
         };
 
         cfg.pc_hooks.push((
-            Regex::new(r"^symbolic_size<.+>$").unwrap(),
+            Regex::new(r"^(symbolic_size<.+>|symex_symbolic)$").unwrap(),
             PCHook::Intrinsic(symbolic_sized),
         ));
 
         let read_pc: RegisterReadHook<Self> = |state| {
             let two = state.ctx.from_u64(1, 32);
-            let pc = state.get_register("PC".to_owned()).unwrap();
+            let pc = state.get_register(Register::PC).unwrap();
             Ok(pc.add(&two))
         };
 
         let write_pc: RegisterWriteHook<Self> =
-            |state, value| state.set_register("PC".to_owned(), value);
+            |state, value| state.set_register(Register::PC, value);
 
-        cfg.register_read_hooks.push(("PC+".to_owned(), read_pc));
-        cfg.register_write_hooks.push(("PC+".to_owned(), write_pc));
+        cfg.register_read_hooks
+            .push((REGISTERS.named("PC+"), read_pc));
+        cfg.register_write_hooks
+            .push((REGISTERS.named("PC+"), write_pc));
 
         // reset allways done
         let read_reset_done: MemoryReadHook<Self> = |state, _addr| {
@@ -80,13 +77,22 @@ impl Arch for ArmV6M {
     fn translate(
         &self,
         buff: &[u8],
-        _state: &GAState<Self>,
+        state: &GAState<Self>,
     ) -> Result<Instruction<Self>, ArchError> {
         let ret = armv6_m_instruction_parser::parse(buff).map_err(map_err)?;
-        let to_exec = Self::expand(ret);
+        let CoreFamily::ArmM(core) = state.project.get_target().core else {
+            return Err(ArchError::IncompatibleTarget);
+        };
+        let to_exec = Self::expand(ret, core);
         Ok(to_exec)
     }
 
+    fn disassemble(&self, buff: &[u8]) -> Option<String> {
+        armv6_m_instruction_parser::parse(buff)
+            .ok()
+            .map(|instruction| format!("{:?}", instruction.operation))
+    }
+
     fn discover(file: &File) -> Result<Option<Self>, ArchError> {
         let f = match file {
             File::Elf32(f) => Ok(f),
@@ -96,12 +102,23 @@ impl Arch for ArmV6M {
             Some(section) => Ok(section),
             None => Err(ArchError::MissingSection(".ARM.attributes")),
         }?;
-        let isa = arm_isa(&section)?;
+        let isa = arm_isa(&section, Endianness::of(file))?;
         match isa {
             ArmIsa::ArmV6M => Ok(Some(ArmV6M {})),
             ArmIsa::ArmV7EM => Ok(None),
+            ArmIsa::ArmV8MBaseline | ArmIsa::ArmV8MMainline => Ok(None),
         }
     }
+
+    fn default_target() -> TargetConfiguration {
+        // The rp2040 with its single-cycle IO block.
+        let mut target =
+            TargetConfiguration::zero_wait_states(CoreFamily::ArmM(ArmMCore::ArmM0Plus));
+        target
+            .fast_io
+            .push(MemoryRegion::new(0xd000_0000, 0xe000_0000));
+        target
+    }
 }
 
 impl Display for ArmV6M {
@@ -10,13 +10,17 @@ use general_assembly::{
     condition::Condition,
     operand::{DataWord, Operand},
     operation::Operation as GAOperation,
+    register::Register as GARegister,
 };
 
-use super::ArmV6M;
-use crate::general_assembly::instruction::Instruction as GAInstruction;
+use super::{super::REGISTERS, ArmV6M};
+use crate::general_assembly::{
+    instruction::{Instruction as GAInstruction, MemoryAccess},
+    project::target_config::ArmMCore,
+};
 
 impl ArmV6M {
-    pub(super) fn expand(instr: Instruction) -> GAInstruction<ArmV6M> {
+    pub(super) fn expand(instr: Instruction, core: ArmMCore) -> GAInstruction<ArmV6M> {
         let operations = match &instr.operation {
             Operation::UDF { .. } => todo!(),
             Operation::ADCReg { m, n, d } => {
@@ -129,7 +133,7 @@ impl ArmV6M {
                 vec![
                     GAOperation::Add {
                         destination: Operand::Local("addr".to_owned()),
-                        operand1: Operand::Register("PC".to_owned()),
+                        operand1: Operand::Register(GARegister::PC),
                         operand2: Operand::Immediate(DataWord::Word32(2)),
                     },
                     GAOperation::And {
@@ -205,7 +209,7 @@ impl ArmV6M {
                 vec![
                     GAOperation::Add {
                         destination: Operand::Local("new_pc".to_owned()),
-                        operand1: Operand::Register("PC".to_owned()),
+                        operand1: Operand::Register(GARegister::PC),
                         operand2: Operand::Immediate(DataWord::Word32(imm)),
                     },
                     GAOperation::ConditionalJump {
@@ -234,10 +238,10 @@ impl ArmV6M {
             Operation::BL { imm } => vec![
                 GAOperation::Move {
                     destination: Operand::Local("PC".to_owned()),
-                    source: Operand::Register("PC".to_owned()),
+                    source: Operand::Register(GARegister::PC),
                 },
                 GAOperation::Move {
-                    destination: Operand::Register("LR".to_owned()),
+                    destination: Operand::Register(GARegister::LR),
                     source: Operand::Local("PC".to_owned()),
                 },
                 GAOperation::Add {
@@ -246,23 +250,23 @@ impl ArmV6M {
                     operand2: Operand::Immediate(DataWord::Word32(*imm)),
                 },
                 GAOperation::Move {
-                    destination: Operand::Register("PC".to_owned()),
+                    destination: Operand::Register(GARegister::PC),
                     source: Operand::Local("newPC".to_owned()),
                 },
             ],
             Operation::BLXReg { m } => vec![
                 GAOperation::Move {
                     destination: arm_register_to_ga_operand(&Register::LR),
-                    source: Operand::Register("PC".to_owned()),
+                    source: Operand::Register(GARegister::PC),
                 },
                 GAOperation::Move {
-                    destination: Operand::Register("PC".to_owned()),
+                    destination: Operand::Register(GARegister::PC),
                     source: arm_register_to_ga_operand(m),
                 },
             ],
             Operation::BX { m } => {
                 let reg = arm_register_to_ga_operand(m);
-                let destination = Operand::Register("PC".to_owned());
+                let destination = Operand::Register(GARegister::PC);
                 vec![GAOperation::Move {
                     destination,
                     source: reg,
@@ -343,10 +347,12 @@ impl ArmV6M {
                     },
                 ]
             }
-            Operation::CPS { im: _ } => {
-                // change processor state do nothig for now but should probably be modeled
+            Operation::CPS { im } => {
                 // in armv6-m it is only used to enable disable interupts
-                vec![]
+                vec![GAOperation::Move {
+                    destination: Operand::Register(const { REGISTERS.named("PRIMASK") }),
+                    source: Operand::Immediate(DataWord::Word32(*im as u32)),
+                }]
             }
             Operation::CPY => {
                 // this is not a real instruction is equvelatn to mov
@@ -410,7 +416,7 @@ impl ArmV6M {
                     operand2: Operand::Immediate(DataWord::Word32(*imm)),
                 },
                 GAOperation::Move {
-                    destination: Operand::Register("LastAddr".to_owned()),
+                    destination: Operand::Register(const { REGISTERS.named("LastAddr") }),
                     source: Operand::Local("addr".to_owned()),
                 },
                 GAOperation::Move {
@@ -421,7 +427,7 @@ impl ArmV6M {
             Operation::LDRLiteral { t, imm } => vec![
                 GAOperation::Add {
                     destination: Operand::Local("addr".to_owned()),
-                    operand1: Operand::Register("PC".to_owned()),
+                    operand1: Operand::Register(GARegister::PC),
                     operand2: Operand::Immediate(DataWord::Word32(2)),
                 },
                 GAOperation::And {
@@ -435,7 +441,7 @@ impl ArmV6M {
                     operand2: Operand::Immediate(DataWord::Word32(*imm)),
                 },
                 GAOperation::Move {
-                    destination: Operand::Register("LastAddr".to_owned()),
+                    destination: Operand::Register(const { REGISTERS.named("LastAddr") }),
                     source: Operand::Local("addr".to_owned()),
                 },
                 GAOperation::Move {
@@ -450,7 +456,7 @@ impl ArmV6M {
                     operand2: arm_register_to_ga_operand(m),
                 },
                 GAOperation::Move {
-                    destination: Operand::Register("LastAddr".to_owned()),
+                    destination: Operand::Register(const { REGISTERS.named("LastAddr") }),
                     source: Operand::Local("addr".to_owned()),
                 },
                 GAOperation::Move {
@@ -465,7 +471,7 @@ impl ArmV6M {
                     operand2: Operand::Immediate(DataWord::Word32(*imm)),
                 },
                 GAOperation::Move {
-                    destination: Operand::Register("LastAddr".to_owned()),
+                    destination: Operand::Register(const { REGISTERS.named("LastAddr") }),
                     source: Operand::Local("addr".to_owned()),
                 },
                 GAOperation::Move {
@@ -486,7 +492,7 @@ impl ArmV6M {
                     operand2: arm_register_to_ga_operand(m),
                 },
                 GAOperation::Move {
-                    destination: Operand::Register("LastAddr".to_owned()),
+                    destination: Operand::Register(const { REGISTERS.named("LastAddr") }),
                     source: Operand::Local("addr".to_owned()),
                 },
                 GAOperation::Move {
@@ -507,7 +513,7 @@ impl ArmV6M {
                     operand2: Operand::Immediate(DataWord::Word32(*imm)),
                 },
                 GAOperation::Move {
-                    destination: Operand::Register("LastAddr".to_owned()),
+                    destination: Operand::Register(const { REGISTERS.named("LastAddr") }),
                     source: Operand::Local("addr".to_owned()),
                 },
                 GAOperation::Move {
@@ -528,7 +534,7 @@ impl ArmV6M {
                     operand2: arm_register_to_ga_operand(m),
                 },
                 GAOperation::Move {
-                    destination: Operand::Register("LastAddr".to_owned()),
+                    destination: Operand::Register(const { REGISTERS.named("LastAddr") }),
                     source: Operand::Local("addr".to_owned()),
                 },
                 GAOperation::Move {
@@ -549,7 +555,7 @@ impl ArmV6M {
                     operand2: arm_register_to_ga_operand(m),
                 },
                 GAOperation::Move {
-                    destination: Operand::Register("LastAddr".to_owned()),
+                    destination: Operand::Register(const { REGISTERS.named("LastAddr") }),
                     source: Operand::Local("addr".to_owned()),
                 },
                 GAOperation::Move {
@@ -569,7 +575,7 @@ impl ArmV6M {
                     operand2: arm_register_to_ga_operand(m),
                 },
                 GAOperation::Move {
-                    destination: Operand::Register("LastAddr".to_owned()),
+                    destination: Operand::Register(const { REGISTERS.named("LastAddr") }),
                     source: Operand::Local("addr".to_owned()),
                 },
                 GAOperation::Move {
@@ -710,7 +716,7 @@ impl ArmV6M {
                 // set up base address
                 operations.push(GAOperation::Move {
                     destination: Operand::Local("Address".to_owned()),
-                    source: Operand::Register("SP".to_owned()),
+                    source: Operand::Register(GARegister::SP),
                 });
                 for reg in reg_list {
                     // write register to memory
@@ -727,8 +733,8 @@ impl ArmV6M {
                 }
                 // update SP
                 operations.push(GAOperation::Add {
-                    destination: Operand::Register("SP".to_owned()),
-                    operand1: Operand::Register("SP".to_owned()),
+                    destination: Operand::Register(GARegister::SP),
+                    operand1: Operand::Register(GARegister::SP),
                     operand2: Operand::Immediate(DataWord::Word32((4 * reg_list.len()) as u32)),
                 });
 
@@ -739,7 +745,7 @@ impl ArmV6M {
                 // set up base address
                 operations.push(GAOperation::Sub {
                     destination: Operand::Local("Address".to_owned()),
-                    operand1: Operand::Register("SP".to_owned()),
+                    operand1: Operand::Register(GARegister::SP),
                     operand2: Operand::Immediate(DataWord::Word32((4 * reg_list.len()) as u32)),
                 });
                 for reg in reg_list {
@@ -757,8 +763,8 @@ impl ArmV6M {
                 }
                 // update SP
                 operations.push(GAOperation::Sub {
-                    destination: Operand::Register("SP".to_owned()),
-                    operand1: Operand::Register("SP".to_owned()),
+                    destination: Operand::Register(GARegister::SP),
+                    operand1: Operand::Register(GARegister::SP),
                     operand2: Operand::Immediate(DataWord::Word32((4 * reg_list.len()) as u32)),
                 });
 
@@ -1126,7 +1132,7 @@ impl ArmV6M {
                         operand2: imm,
                     },
                     GAOperation::Move {
-                        destination: Operand::Register("LastAddr".to_owned()),
+                        destination: Operand::Register(const { REGISTERS.named("LastAddr") }),
                         source: Operand::Local("addr".to_owned()),
                     },
                     GAOperation::Move {
@@ -1149,7 +1155,7 @@ impl ArmV6M {
                         operand2: m,
                     },
                     GAOperation::Move {
-                        destination: Operand::Register("LastAddr".to_owned()),
+                        destination: Operand::Register(const { REGISTERS.named("LastAddr") }),
                         source: Operand::Local("addr".to_owned()),
                     },
                     GAOperation::Move {
@@ -1172,7 +1178,7 @@ impl ArmV6M {
                         operand2: imm,
                     },
                     GAOperation::Move {
-                        destination: Operand::Register("LastAddr".to_owned()),
+                        destination: Operand::Register(const { REGISTERS.named("LastAddr") }),
                         source: Operand::Local("addr".to_owned()),
                     },
                     GAOperation::Move {
@@ -1195,7 +1201,7 @@ impl ArmV6M {
                         operand2: m,
                     },
                     GAOperation::Move {
-                        destination: Operand::Register("LastAddr".to_owned()),
+                        destination: Operand::Register(const { REGISTERS.named("LastAddr") }),
                         source: Operand::Local("addr".to_owned()),
                     },
                     GAOperation::Move {
@@ -1218,7 +1224,7 @@ impl ArmV6M {
                         operand2: imm,
                     },
                     GAOperation::Move {
-                        destination: Operand::Register("LastAddr".to_owned()),
+                        destination: Operand::Register(const { REGISTERS.named("LastAddr") }),
                         source: Operand::Local("addr".to_owned()),
                     },
                     GAOperation::Move {
@@ -1241,7 +1247,7 @@ impl ArmV6M {
                         operand2: m,
                     },
                     GAOperation::Move {
-                        destination: Operand::Register("LastAddr".to_owned()),
+                        destination: Operand::Register(const { REGISTERS.named("LastAddr") }),
                         source: Operand::Local("addr".to_owned()),
                     },
                     GAOperation::Move {
@@ -1315,8 +1321,8 @@ impl ArmV6M {
                 ]
             }
             Operation::SUBImmSP { imm } => vec![GAOperation::Sub {
-                destination: Operand::Register("SP".to_owned()),
-                operand1: Operand::Register("SP".to_owned()),
+                destination: Operand::Register(GARegister::SP),
+                operand1: Operand::Register(GARegister::SP),
                 operand2: Operand::Immediate(DataWord::Word32(*imm)),
             }],
             Operation::SVC { imm: _ } => {
@@ -1389,52 +1395,55 @@ impl ArmV6M {
             armv6_m_instruction_parser::instructons::InstructionWidth::Bit16 => 16,
         };
 
-        let max_cycle_count = super::timing::cycle_count_m0plus_core(&instr.operation);
+        let max_cycle_count = match core {
+            ArmMCore::ArmM0 => super::timing::cycle_count_m0_core(&instr.operation),
+            _ => super::timing::cycle_count_m0plus_core(&instr.operation),
+        };
 
         GAInstruction {
             instruction_size: instruction_width,
             operations,
             max_cycle: max_cycle_count,
             // TODO! Add in checks for wether or not this is true
-            memory_access: false,
+            memory_access: MemoryAccess::None,
         }
     }
 }
 
 fn arm_register_to_ga_operand(reg: &Register) -> Operand {
     Operand::Register(match reg {
-        Register::R0 => "R0".to_owned(),
-        Register::R1 => "R1".to_owned(),
-        Register::R2 => "R2".to_owned(),
-        Register::R3 => "R3".to_owned(),
-        Register::R4 => "R4".to_owned(),
-        Register::R5 => "R5".to_owned(),
-        Register::R6 => "R6".to_owned(),
-        Register::R7 => "R7".to_owned(),
-        Register::R8 => "R8".to_owned(),
-        Register::R9 => "R9".to_owned(),
-        Register::R10 => "R10".to_owned(),
-        Register::R11 => "R11".to_owned(),
-        Register::R12 => "R12".to_owned(),
-        Register::SP => "SP".to_owned(),
-        Register::LR => "LR".to_owned(),
-        Register::PC => "PC+".to_owned(),
+        Register::R0 => const { REGISTERS.named("R0") },
+        Register::R1 => const { REGISTERS.named("R1") },
+        Register::R2 => const { REGISTERS.named("R2") },
+        Register::R3 => const { REGISTERS.named("R3") },
+        Register::R4 => const { REGISTERS.named("R4") },
+        Register::R5 => const { REGISTERS.named("R5") },
+        Register::R6 => const { REGISTERS.named("R6") },
+        Register::R7 => const { REGISTERS.named("R7") },
+        Register::R8 => const { REGISTERS.named("R8") },
+        Register::R9 => const { REGISTERS.named("R9") },
+        Register::R10 => const { REGISTERS.named("R10") },
+        Register::R11 => const { REGISTERS.named("R11") },
+        Register::R12 => const { REGISTERS.named("R12") },
+        Register::SP => GARegister::SP,
+        Register::LR => GARegister::LR,
+        Register::PC => const { REGISTERS.named("PC+") },
     })
 }
 
 fn arm_special_register_to_operand(reg: &SpecialRegister) -> Operand {
     Operand::Register(match reg {
-        SpecialRegister::APSR => "APSR".to_owned(),
-        SpecialRegister::IAPSR => "IAPSR".to_owned(),
-        SpecialRegister::EAPSR => "EAPSR".to_owned(),
-        SpecialRegister::XPSR => "XPSR".to_owned(),
-        SpecialRegister::IPSR => "IPSR".to_owned(),
-        SpecialRegister::EPSR => "EPSR".to_owned(),
-        SpecialRegister::IEPSR => "IEPSR".to_owned(),
-        SpecialRegister::MSP => "MSP".to_owned(),
-        SpecialRegister::PSP => "PSP".to_owned(),
-        SpecialRegister::PRIMASK => "PRIMASK".to_owned(),
-        SpecialRegister::CONTROL => "CONTROL".to_owned(),
+        SpecialRegister::APSR => const { REGISTERS.named("APSR") },
+        SpecialRegister::IAPSR => const { REGISTERS.named("IAPSR") },
+        SpecialRegister::EAPSR => const { REGISTERS.named("EAPSR") },
+        SpecialRegister::XPSR => const { REGISTERS.named("XPSR") },
+        SpecialRegister::IPSR => const { REGISTERS.named("IPSR") },
+        SpecialRegister::EPSR => const { REGISTERS.named("EPSR") },
+        SpecialRegister::IEPSR => const { REGISTERS.named("IEPSR") },
+        SpecialRegister::MSP => const { REGISTERS.named("MSP") },
+        SpecialRegister::PSP => const { REGISTERS.named("PSP") },
+        SpecialRegister::PRIMASK => const { REGISTERS.named("PRIMASK") },
+        SpecialRegister::CONTROL => const { REGISTERS.named("CONTROL") },
     })
 }
 
@@ -2,18 +2,24 @@
 
 use armv6_m_instruction_parser::{instructons::Operation, registers::Register};
 
-use super::ArmV6M;
+use super::{super::REGISTERS, ArmV6M};
 use crate::general_assembly::{instruction::CycleCount, state::GAState};
 
 pub(crate) fn cycle_count_m0plus_core(operation: &Operation) -> CycleCount<ArmV6M> {
-    // SIO based on the rp2040 make this configurable later
-    let address_max_cycle_function: fn(state: &GAState<ArmV6M>) -> usize = |state| {
-        let address = match state.registers.get("LastAddr").unwrap().get_constant() {
+    // Accesses to the single-cycle IO regions of the target, e.g. the SIO on
+    // the rp2040, completes in one cycle.
+    let address_max_cycle_function: fn(state: &mut GAState<ArmV6M>) -> usize = |state| {
+        let address = match state
+            .registers
+            .get(const { REGISTERS.named("LastAddr") })
+            .unwrap()
+            .get_constant()
+        {
             Some(v) => v,
             None => return 2,
         };
 
-        if (0xd0000000..=0xdfffffff).contains(&address) {
+        if state.project.get_target().is_fast_io(address) {
             1
         } else {
             2
@@ -33,7 +39,7 @@ pub(crate) fn cycle_count_m0plus_core(operation: &Operation) -> CycleCount<ArmV6
         Operation::ASRImm { imm: _, m: _, d: _ } => CycleCount::Value(1),
         Operation::ASRReg { m: _, dn: _ } => CycleCount::Value(1),
         Operation::B { cond: _, imm: _ } => {
-            let max_cycle: fn(state: &GAState<ArmV6M>) -> usize = |state| {
+            let max_cycle: fn(state: &mut GAState<ArmV6M>) -> usize = |state| {
                 if state.get_has_jumped() {
                     2
                 } else {
@@ -153,7 +159,6 @@ pub(crate) fn cycle_count_m0plus_core(operation: &Operation) -> CycleCount<ArmV6
     }
 }
 
-#[allow(dead_code)]
 pub(crate) fn cycle_count_m0_core(operation: &Operation) -> CycleCount<ArmV6M> {
     match operation {
         Operation::ADCReg { m: _, n: _, d: _ } => CycleCount::Value(1),
@@ -169,7 +174,7 @@ pub(crate) fn cycle_count_m0_core(operation: &Operation) -> CycleCount<ArmV6M> {
         Operation::ASRImm { imm: _, m: _, d: _ } => CycleCount::Value(1),
         Operation::ASRReg { m: _, dn: _ } => CycleCount::Value(1),
         Operation::B { cond: _, imm: _ } => {
-            let max_cycle: fn(state: &GAState<ArmV6M>) -> usize = |state| {
+            let max_cycle: fn(state: &mut GAState<ArmV6M>) -> usize = |state| {
                 if state.get_has_jumped() {
                     3
                 } else {
@@ -2,21 +2,30 @@ use std::{collections::HashSet, fmt::Display};
 
 use decoder::Convert;
 use disarmv7::prelude::{Operation as V7Operation, *};
-use general_assembly::operation::Operation;
+use general_assembly::{
+    operation::Operation,
+    register::{Register, RegisterSet},
+};
 use object::{File, Object};
 use regex::Regex;
+use timing_m7::M7Pipeline;
 use tracing::{trace, warn};
 
-use super::{arm_isa, ArmIsa};
-use crate::{
-    elf_util::{ExpressionType, Variable},
-    general_assembly::{
-        arch::{Arch, ArchError, ParseError},
-        instruction::Instruction,
-        project::{MemoryHookAddress, MemoryReadHook, PCHook, RegisterReadHook, RegisterWriteHook},
-        run_config::RunConfig,
-        state::GAState,
+use super::{arm_isa, ArmIsa, REGISTERS};
+use crate::general_assembly::{
+    arch::{make_symbolic, Arch, ArchError, ParseError},
+    instruction::Instruction,
+    project::{
+        target_config::{ArmMCore, CoreFamily, TargetConfiguration},
+        MemoryHookAddress,
+        MemoryReadHook,
+        PCHook,
+        RegisterReadHook,
+        RegisterWriteHook,
     },
+    run_config::RunConfig,
+    state::GAState,
+    Endianness,
 };
 
 #[rustfmt::skip]
@@ -25,34 +34,33 @@ pub mod compare;
 #[cfg(test)]
 pub mod test;
 pub mod timing;
+pub mod timing_m7;
 
 /// Type level denotation for the Armv7-EM ISA.
 #[derive(Debug, Default, Clone)]
 pub struct ArmV7EM {
     pub pc_writes: HashSet<u64>,
+
+    /// State of the Cortex-M7 pipeline on the current path.
+    pub m7_pipeline: M7Pipeline,
 }
 
 impl Arch for ArmV7EM {
+    const REGISTERS: &'static RegisterSet = &REGISTERS;
+
     fn add_hooks(&self, cfg: &mut RunConfig<Self>) {
         let symbolic_sized = |state: &mut GAState<Self>| {
-            let value_ptr = state.get_register("R0".to_owned())?;
-            let size = state.get_register("R1".to_owned())?.get_constant().unwrap() * 8;
-            let name = "any".to_owned() + &state.marked_symbolic.len().to_string();
-            let symb_value = state.ctx.unconstrained(size as u32, &name);
-            state.marked_symbolic.push(Variable {
-                name: Some(name),
-                value: symb_value.clone(),
-                ty: ExpressionType::Integer(size as usize),
-            });
-            state.memory.write(&value_ptr, symb_value)?;
-
-            let lr = state.get_register("LR".to_owned())?;
-            state.set_register("PC".to_owned(), lr)?;
+            let pointer = const { REGISTERS.named("R0") };
+            let size = const { REGISTERS.named("R1") };
+            make_symbolic(state, pointer, size)?;
+
+            let lr = state.get_register(Register::LR)?;
+            state.set_register(Register::PC, lr)?;
             Ok(())
         };
 
         cfg.pc_hooks.push((
-            Regex::new(r"^symbolic_size<.+>$").unwrap(),
+            Regex::new(r"^(symbolic_size<.+>|symex_symbolic)$").unwrap(),
             PCHook::Intrinsic(symbolic_sized),
         ));
         // §B1.4 Specifies that R[15] => Addr(Current instruction) + 4
@@ -74,36 +82,40 @@ impl Arch for ArmV7EM {
 
         let read_sp: RegisterReadHook<Self> = |state| {
             let two = state.ctx.from_u64((!(0b11u32)) as u64, 32);
-            let sp = state.get_register("SP".to_owned()).unwrap();
+            let sp = state.get_register(Register::SP).unwrap();
             let sp = sp.simplify();
             Ok(sp.and(&two))
         };
 
         let write_pc: RegisterWriteHook<Self> =
-            |state, value| state.set_register("PC".to_owned(), value);
+            |state, value| state.set_register(Register::PC, value);
         let write_sp: RegisterWriteHook<Self> = |state, value| {
             state.set_register(
-                "SP".to_owned(),
+                Register::SP,
                 value.and(&state.ctx.from_u64((!(0b11u32)) as u64, 32)),
             )?;
-            let sp = state.get_register("SP".to_owned()).unwrap();
+            let sp = state.get_register(Register::SP).unwrap();
             let sp = sp.simplify();
             let ret = sp.get_constant();
             if ret.is_none() {
                 warn!("Could not get static version of sp = {:?}", value);
-                return state.set_register("SP".to_owned(), sp);
+                return state.set_register(Register::SP, sp);
             }
             let value = unsafe { ret.unwrap_unchecked() };
 
             trace!("Wrote {value} to SP");
             state.architecture.pc_writes.insert(value);
-            state.set_register("SP".to_owned(), sp)
+            state.set_register(Register::SP, sp)
         };
 
-        cfg.register_read_hooks.push(("PC+".to_owned(), read_pc));
-        cfg.register_write_hooks.push(("PC+".to_owned(), write_pc));
-        cfg.register_read_hooks.push(("SP&".to_owned(), read_sp));
-        cfg.register_write_hooks.push(("SP&".to_owned(), write_sp));
+        cfg.register_read_hooks
+            .push((REGISTERS.named("PC+"), read_pc));
+        cfg.register_write_hooks
+            .push((REGISTERS.named("PC+"), write_pc));
+        cfg.register_read_hooks
+            .push((REGISTERS.named("SP&"), read_sp));
+        cfg.register_write_hooks
+            .push((REGISTERS.named("SP&"), write_sp));
 
         // reset allways done
         let read_reset_done: MemoryReadHook<Self> = |state, _addr| {
@@ -123,7 +135,13 @@ impl Arch for ArmV7EM {
 
         let instr = V7Operation::parse(&mut buff).map_err(|e| ArchError::ParsingError(e.into()))?;
         trace!("Running {:?}", instr.1);
-        let timing = Self::cycle_count_m4_core(&instr.1);
+        let timing = match state.project.get_target().core {
+            CoreFamily::ArmM(ArmMCore::ArmM7) => Self::cycle_count_m7_core(&instr.1),
+            CoreFamily::ArmM(_) => Self::cycle_count_m4_core(&instr.1),
+            CoreFamily::RiscV(_) | CoreFamily::AArch64(_) => {
+                return Err(ArchError::IncompatibleTarget)
+            }
+        };
         let ops: Vec<Operation> = instr.clone().convert(state.get_in_conditional_block());
 
         Ok(Instruction {
@@ -134,6 +152,13 @@ impl Arch for ArmV7EM {
         })
     }
 
+    fn disassemble(&self, buff: &[u8]) -> Option<String> {
+        let mut buff: disarmv7::buffer::PeekableBuffer<u8, _> = buff.iter().cloned().into();
+        V7Operation::parse(&mut buff)
+            .ok()
+            .map(|(_, operation)| format!("{operation:?}"))
+    }
+
     fn discover(file: &File) -> Result<Option<Self>, ArchError> {
         let f = match file {
             File::Elf32(f) => Ok(f),
@@ -143,16 +168,26 @@ impl Arch for ArmV7EM {
             Some(section) => Ok(section),
             None => Err(ArchError::MissingSection(".ARM.attributes")),
         }?;
-        let isa = arm_isa(&section)?;
+        let isa = arm_isa(&section, Endianness::of(file))?;
         match isa {
             ArmIsa::ArmV6M => Ok(None),
+            ArmIsa::ArmV8MBaseline | ArmIsa::ArmV8MMainline => Ok(None),
             ArmIsa::ArmV7EM => Ok(Some(ArmV7EM::default())),
         }
     }
 
+    fn default_target() -> TargetConfiguration {
+        TargetConfiguration::zero_wait_states(CoreFamily::ArmM(ArmMCore::ArmM4))
+    }
+
     fn get_stack_pointers(&self) -> Option<HashSet<u64>> {
         Some(self.pc_writes.clone())
     }
+
+    fn merge(&mut self, other: &Self) {
+        self.pc_writes.extend(&other.pc_writes);
+        self.m7_pipeline.merge(&other.m7_pipeline);
+    }
 }
 
 impl Display for ArmV7EM {
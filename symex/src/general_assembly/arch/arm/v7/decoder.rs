@@ -3,6 +3,7 @@ use general_assembly::{
     condition::Condition,
     operand::{DataWord, Operand},
     operation::Operation,
+    register::Register as GARegister,
     shift::Shift as GAShift,
 };
 use paste::paste;
@@ -16,6 +17,8 @@ use disarmv7::prelude::{
     Condition as ARMCondition
 };
 
+use crate::general_assembly::arch::arm::REGISTERS;
+
 macro_rules! consume {
     (($($id:ident$($(.$e:expr)+)?),*) from $name:ident) => {
         #[allow(unused_parens)]
@@ -549,7 +552,8 @@ impl Convert for (usize, V7Operation) {
                         Flag("Z") = old_z;
                     ])
                 }
-                V7Operation::Clrex(_) => todo!("This should not be needed for now"),
+                // The exclusive monitor is not modeled so there is nothing to clear.
+                V7Operation::Clrex(_) => vec![],
                 V7Operation::Clz(clz) => {
                     vec![Operation::CountLeadingZeroes{
                         destination: clz.rd.local_into(),
@@ -637,7 +641,7 @@ impl Convert for (usize, V7Operation) {
                         if affect_pri {
                             // force lsb to 1
                             ret.push(
-                                Operation::And { 
+                                Operation::Or { 
                                     destination: SpecialRegister::PRIMASK.local_into(), 
                                     operand1: SpecialRegister::PRIMASK.local_into(), 
                                     operand2: ((0b1u32).local_into()) 
@@ -647,7 +651,7 @@ impl Convert for (usize, V7Operation) {
                         if affect_fault {
                             // force lsb to 1
                             ret.push(
-                                Operation::And {
+                                Operation::Or {
                                     destination: SpecialRegister::FAULTMASK.local_into(),
                                     operand1: SpecialRegister::FAULTMASK.local_into(),
                                     operand2: ((0b1u32).local_into()) 
@@ -1037,9 +1041,45 @@ impl Convert for (usize, V7Operation) {
                         rt2 = LocalAddress(address,32);
                     ])
                 }
-                V7Operation::Ldrex(_) => todo!("Hardware semaphores"),
-                V7Operation::Ldrexb(_) => todo!("Hardware semaphores"),
-                V7Operation::Ldrexh(_) => todo!("Hardware semaphores"),
+                V7Operation::Ldrex(ldrex) => {
+                    consume!((
+                            rt.local_into(),
+                            rn.local_into(),
+                            imm.local_into()
+                            ) from ldrex
+                            );
+                    pseudo!([
+                            let address = rn + imm;
+                            // TODO! Add in exculisve addresses here
+                            rt = LocalAddress(address,32);
+                    ])
+                }
+                V7Operation::Ldrexb(ldrexb) => {
+                    consume!((
+                            rt.local_into(),
+                            rn.local_into()
+                            ) from ldrexb
+                            );
+                    pseudo!([
+                            let address = rn;
+                            // TODO! Add in exculisve addresses here
+                            let data = LocalAddress(address,8);
+                            rt = ZeroExtend(data,32);
+                    ])
+                }
+                V7Operation::Ldrexh(ldrexh) => {
+                    consume!((
+                            rt.local_into(),
+                            rn.local_into()
+                            ) from ldrexh
+                            );
+                    pseudo!([
+                            let address = rn;
+                            // TODO! Add in exculisve addresses here
+                            let data = LocalAddress(address,16);
+                            rt = ZeroExtend(data,32);
+                    ])
+                }
                 V7Operation::LdrhImmediate(ldrh) => {
                     consume!((
                             rt.local_into(),
@@ -1963,9 +2003,8 @@ impl Convert for (usize, V7Operation) {
                         int3 = rm<23:16>;
                         int4 = rm<31:24>;
                         int1 = int1 << (24).local_into();
-                        int2 = int2 << (8).local_into();
-                        int3 = int3 >> (8).local_into();
-                        int4 = int4 >> (24).local_into();
+                        int2 = int2 << (16).local_into();
+                        int3 = int3 << (8).local_into();
                         rd = zero;
                         rd = rd | int1;
                         rd = rd | int2;
@@ -1988,9 +2027,8 @@ impl Convert for (usize, V7Operation) {
                         int3 = rm<23:16>;
                         int4 = rm<31:24>;
                         int1 = int1 << 8.local_into();
-                        int2 = int2 >> 8.local_into();
-                        int3 = int3 << 8.local_into();
-                        int4 = int4 >> 8.local_into();
+                        int3 = int3 << 24.local_into();
+                        int4 = int4 << 16.local_into();
                         rd = zero;
                         rd = rd | int1;
                         rd = rd | int2;
@@ -2010,7 +2048,6 @@ impl Convert for (usize, V7Operation) {
                         int1 = rm<7:0>;
                         int2 = rm<15:8>;
                         int1 = int1 << 8.local_into();
-                        int2 = int2 >> 8.local_into();
                         rd = zero;
                         ]
                         );
@@ -2076,7 +2113,7 @@ impl Convert for (usize, V7Operation) {
                     let mask = (u32::MAX >> 1).local_into();
                     let lsb_mask = (1).local_into();
                     local!(lsb, result, msb);
-                    let carry = Operand::Flag("C".to_owned());
+                    let carry = Operand::Flag(GARegister::C);
                     let mut ret = Vec::with_capacity(10);
                     pseudo!(
                         ret.extend[
@@ -2125,7 +2162,7 @@ impl Convert for (usize, V7Operation) {
                     consume!((s,rd,rn,rm.local_into(), shift) from rsb);
                     let (rd, rn) = (rd.unwrap_or(rn).local_into(), rn.local_into());
                     let mut ret = Vec::with_capacity(10);
-                    let carry = Operand::Flag("C".to_owned());
+                    let carry = Operand::Flag(GARegister::C);
                     let one = 1.local_into();
 
                     local!(shifted, intermediate, old_carry);
@@ -2815,7 +2852,7 @@ impl Convert for (usize, V7Operation) {
                     consume!(
                         (
                             s.unwrap_or(false),
-                            rd.local_into().unwrap_or(Operand::Register("SP&".to_owned())),
+                            rd.local_into().unwrap_or(Register::SP.local_into()),
                             imm.local_into()
                         ) from sub
                     );
@@ -3453,19 +3490,19 @@ mod sealed {
     pub trait Into<T> {
         fn local_into(self) -> T;
     }
-    pub trait ToString {
-        fn to_string(self) -> String;
+    pub trait ToRegister {
+        fn to_register(self) -> super::GARegister;
     }
 }
 
 use sealed::Into;
 
-use self::sealed::ToString;
+use self::sealed::ToRegister;
 
 
 impl sealed::Into<Operand> for Register {
     fn local_into(self) -> Operand {
-        Operand::Register(self.to_string())
+        Operand::Register(self.to_register())
     }
 }
 
@@ -3510,48 +3547,48 @@ pub enum SpecialRegister {
 impl Into<Operand> for SpecialRegister {
     fn local_into(self) -> Operand {
         Operand::Register(match self {
-            SpecialRegister::APSR => "APSR".to_owned(),
-            SpecialRegister::IAPSR => "IAPSR".to_owned(),
-            SpecialRegister::EAPSR => "EAPSR".to_owned(),
-            SpecialRegister::XPSR => "XPSR".to_owned(),
-            SpecialRegister::IPSR => "IPSR".to_owned(),
-            SpecialRegister::EPSR => "EPSR".to_owned(),
-            SpecialRegister::IEPSR => "IEPSR".to_owned(),
-            SpecialRegister::MSP => "MSP".to_owned(),
-            SpecialRegister::PSP => "PSP".to_owned(),
-            SpecialRegister::PRIMASK => "PRIMASK".to_owned(),
-            SpecialRegister::CONTROL => "CONTROL".to_owned(),
-            SpecialRegister::FAULTMASK => "FAULTMASK".to_owned(),
-            SpecialRegister::BASEPRI => "BASEPRI".to_owned(),
+            SpecialRegister::APSR => const { REGISTERS.named("APSR") },
+            SpecialRegister::IAPSR => const { REGISTERS.named("IAPSR") },
+            SpecialRegister::EAPSR => const { REGISTERS.named("EAPSR") },
+            SpecialRegister::XPSR => const { REGISTERS.named("XPSR") },
+            SpecialRegister::IPSR => const { REGISTERS.named("IPSR") },
+            SpecialRegister::EPSR => const { REGISTERS.named("EPSR") },
+            SpecialRegister::IEPSR => const { REGISTERS.named("IEPSR") },
+            SpecialRegister::MSP => const { REGISTERS.named("MSP") },
+            SpecialRegister::PSP => const { REGISTERS.named("PSP") },
+            SpecialRegister::PRIMASK => const { REGISTERS.named("PRIMASK") },
+            SpecialRegister::CONTROL => const { REGISTERS.named("CONTROL") },
+            SpecialRegister::FAULTMASK => const { REGISTERS.named("FAULTMASK") },
+            SpecialRegister::BASEPRI => const { REGISTERS.named("BASEPRI") },
         })
     }
 }
 
-impl sealed::ToString for Register {
-    fn to_string(self) -> String {
+impl sealed::ToRegister for Register {
+    fn to_register(self) -> GARegister {
         match self {
-            Register::R0 => "R0".to_owned(),
-            Register::R1 => "R1".to_owned(),
-            Register::R2 => "R2".to_owned(),
-            Register::R3 => "R3".to_owned(),
-            Register::R4 => "R4".to_owned(),
-            Register::R5 => "R5".to_owned(),
-            Register::R6 => "R6".to_owned(),
-            Register::R7 => "R7".to_owned(),
-            Register::R8 => "R8".to_owned(),
-            Register::R9 => "R9".to_owned(),
-            Register::R10 => "R10".to_owned(),
-            Register::R11 => "R11".to_owned(),
-            Register::R12 => "R12".to_owned(),
-            Register::SP => "SP&".to_owned(),
-            Register::LR => "LR".to_owned(),
-            Register::PC => "PC+".to_owned(),
+            Register::R0 => const { REGISTERS.named("R0") },
+            Register::R1 => const { REGISTERS.named("R1") },
+            Register::R2 => const { REGISTERS.named("R2") },
+            Register::R3 => const { REGISTERS.named("R3") },
+            Register::R4 => const { REGISTERS.named("R4") },
+            Register::R5 => const { REGISTERS.named("R5") },
+            Register::R6 => const { REGISTERS.named("R6") },
+            Register::R7 => const { REGISTERS.named("R7") },
+            Register::R8 => const { REGISTERS.named("R8") },
+            Register::R9 => const { REGISTERS.named("R9") },
+            Register::R10 => const { REGISTERS.named("R10") },
+            Register::R11 => const { REGISTERS.named("R11") },
+            Register::R12 => const { REGISTERS.named("R12") },
+            Register::SP => const { REGISTERS.named("SP&") },
+            Register::LR => GARegister::LR,
+            Register::PC => const { REGISTERS.named("PC+") },
         }
     }
 }
 impl sealed::Into<Option<Operand>> for Option<Register> {
     fn local_into(self) -> Option<Operand> {
-        Some(Operand::Register(self?.to_string()))
+        Some(Operand::Register(self?.to_register()))
     }
 }
 impl sealed::Into<GAShift> for Shift {
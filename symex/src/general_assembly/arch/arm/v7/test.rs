@@ -4,18 +4,27 @@ use disarmv7::prelude::{operation::*, *};
 use general_assembly::{
     operand::{DataWord, Operand},
     operation::Operation as GAOperation,
+    register::Register as GARegister,
 };
 
-use super::ArmV7EM;
+use super::{super::REGISTERS, ArmV7EM};
 use crate::{
     general_assembly::{
-        arch::arm::v7::decoder::Convert,
-        executor::GAExecutor,
-        instruction::{CycleCount, Instruction},
-        project::Project,
-        state::GAState,
+        arch::{arm::v7::decoder::Convert, Arch},
+        executor::{GAExecutor, PathResult},
+        instruction::{CycleCount, Instruction, MemoryAccess},
+        project::{
+            snapshot::Snapshot,
+            target_config::{ArmMCore, Cache, CoreFamily, MemoryRegion, TargetConfiguration},
+            PCHook,
+            Project,
+            ProjectError,
+        },
+        replay::{replay, TraceFormat},
+        state::{ConcretizedBranch, GAState},
         vm::VM,
         Endianness,
+        GAError,
         WordSize,
     },
     smt::{DContext, DSolver},
@@ -23,7 +32,7 @@ use crate::{
 
 macro_rules! get_operand {
     ($exec:ident register $id:ident) => {{
-        let operand = Operand::Register(stringify!($id).to_owned());
+        let operand = Operand::Register(const { REGISTERS.named(stringify!($id)) });
         let local = HashMap::new();
         $exec
             .get_operand_value(&operand, &local)
@@ -32,7 +41,7 @@ macro_rules! get_operand {
             .expect("Could not get test specified register as constant")
     }};
     ($exec:ident flag $id:ident) => {{
-        let operand = Operand::Flag(stringify!($id).to_owned());
+        let operand = Operand::Flag(const { REGISTERS.named(stringify!($id)) });
         let local = HashMap::new();
         $exec
             .get_operand_value(&operand, &local)
@@ -96,7 +105,7 @@ macro_rules! test {
                 $(!= $neq_rhs)?
                 $(!= $eq_rhs_expr)?
                 $(== $neq_rhs_expr)?,
-                
+
                 stringify!(
                     $($reg)?
                     $($address)?
@@ -140,11 +149,11 @@ macro_rules! initiate {
     };
 
     ($exec:ident register $id:ident) => {
-        Operand::Register(stringify!($id).to_owned())
+        Operand::Register(const { REGISTERS.named(stringify!($id)) })
     };
 
     ($exec:ident flag $id:ident) => {
-        Operand::Flag(stringify!($id).to_owned())
+        Operand::Flag(const { REGISTERS.named(stringify!($id)) })
     };
 
     ($exec:ident address $id:literal $width:literal) => {
@@ -153,6 +162,10 @@ macro_rules! initiate {
 }
 
 fn setup_test_vm() -> VM<ArmV7EM> {
+    setup_test_vm_with_target(ArmV7EM::default_target())
+}
+
+fn setup_test_vm_with_target(target: TargetConfiguration) -> VM<ArmV7EM> {
     // create an empty project
     let mut project = Box::new(Project::manual_project(
         vec![],
@@ -162,13 +175,14 @@ fn setup_test_vm() -> VM<ArmV7EM> {
         Endianness::Little,
         HashMap::new(),
         HashMap::new(),
-        HashMap::new(),
-        HashMap::new(),
+        Vec::new(),
+        Vec::new(),
         HashMap::new(),
         vec![],
         HashMap::new(),
         vec![],
     ));
+    project.set_target(target);
     let mut arch = ArmV7EM::default();
     project.add_hooks(&mut arch);
 
@@ -206,7 +220,7 @@ fn test_adc_no_set_flag() {
 
     let instruction = Instruction {
         operations: (16, instruction).convert(false),
-        memory_access: false,
+        memory_access: MemoryAccess::None,
         instruction_size: 16,
         max_cycle: CycleCount::Value(0),
     };
@@ -245,7 +259,7 @@ fn test_adc_set_flag() {
 
     let instruction = Instruction {
         operations: (16, instruction).convert(false),
-        memory_access: false,
+        memory_access: MemoryAccess::None,
         instruction_size: 16,
         max_cycle: CycleCount::Value(0),
     };
@@ -276,7 +290,7 @@ fn test_adc_set_flag() {
 
     let instruction = Instruction {
         operations: (16, instruction).convert(false),
-        memory_access: false,
+        memory_access: MemoryAccess::None,
         instruction_size: 16,
         max_cycle: CycleCount::Value(0),
     };
@@ -311,7 +325,7 @@ fn test_adc_set_flag() {
 
     let instruction = Instruction {
         operations: (16, instruction).convert(true),
-        memory_access: false,
+        memory_access: MemoryAccess::None,
         instruction_size: 16,
         max_cycle: CycleCount::Value(0),
     };
@@ -346,7 +360,7 @@ fn test_adc_set_flag() {
 
     let instruction = Instruction {
         operations: (16, instruction).convert(true),
-        memory_access: false,
+        memory_access: MemoryAccess::None,
         instruction_size: 16,
         max_cycle: CycleCount::Value(0),
     };
@@ -384,7 +398,7 @@ fn test_adc_imm_no_set_flag() {
 
     let instruction = Instruction {
         operations: (16, instruction).convert(false),
-        memory_access: false,
+        memory_access: MemoryAccess::None,
         instruction_size: 16,
         max_cycle: CycleCount::Value(0),
     };
@@ -421,7 +435,7 @@ fn test_adc_immediate_set_flag() {
 
     let instruction = Instruction {
         operations: (16, instruction).convert(false),
-        memory_access: false,
+        memory_access: MemoryAccess::None,
         instruction_size: 16,
         max_cycle: CycleCount::Value(0),
     };
@@ -450,7 +464,7 @@ fn test_adc_immediate_set_flag() {
 
     let instruction = Instruction {
         operations: (16, instruction).convert(false),
-        memory_access: false,
+        memory_access: MemoryAccess::None,
         instruction_size: 16,
         max_cycle: CycleCount::Value(0),
     };
@@ -491,7 +505,7 @@ fn test_add_no_set_flag() {
 
     let instruction = Instruction {
         operations: (16, instruction).convert(false),
-        memory_access: false,
+        memory_access: MemoryAccess::None,
         instruction_size: 16,
         max_cycle: CycleCount::Value(0),
     };
@@ -530,7 +544,7 @@ fn test_add_set_flag() {
 
     let instruction = Instruction {
         operations: (16, instruction).convert(false),
-        memory_access: false,
+        memory_access: MemoryAccess::None,
         instruction_size: 16,
         max_cycle: CycleCount::Value(0),
     };
@@ -561,7 +575,7 @@ fn test_add_set_flag() {
 
     let instruction = Instruction {
         operations: (16, instruction).convert(false),
-        memory_access: false,
+        memory_access: MemoryAccess::None,
         instruction_size: 16,
         max_cycle: CycleCount::Value(0),
     };
@@ -596,7 +610,7 @@ fn test_add_set_flag() {
 
     let instruction = Instruction {
         operations: (16, instruction).convert(true),
-        memory_access: false,
+        memory_access: MemoryAccess::None,
         instruction_size: 16,
         max_cycle: CycleCount::Value(0),
     };
@@ -631,7 +645,7 @@ fn test_add_set_flag() {
 
     let instruction = Instruction {
         operations: (16, instruction).convert(true),
-        memory_access: false,
+        memory_access: MemoryAccess::None,
         instruction_size: 16,
         max_cycle: CycleCount::Value(0),
     };
@@ -669,7 +683,7 @@ fn test_add_imm_no_set_flag() {
 
     let instruction = Instruction {
         operations: (16, instruction).convert(false),
-        memory_access: false,
+        memory_access: MemoryAccess::None,
         instruction_size: 16,
         max_cycle: CycleCount::Value(0),
     };
@@ -707,7 +721,7 @@ fn test_add_immediate_set_flag() {
 
     let instruction = Instruction {
         operations: (16, instruction).convert(false),
-        memory_access: false,
+        memory_access: MemoryAccess::None,
         instruction_size: 16,
         max_cycle: CycleCount::Value(0),
     };
@@ -736,7 +750,7 @@ fn test_add_immediate_set_flag() {
 
     let instruction = Instruction {
         operations: (16, instruction).convert(false),
-        memory_access: false,
+        memory_access: MemoryAccess::None,
         instruction_size: 16,
         max_cycle: CycleCount::Value(0),
     };
@@ -774,7 +788,7 @@ fn test_add_sp_immediate() {
 
     let instruction = Instruction {
         operations: (16, instruction).convert(false),
-        memory_access: false,
+        memory_access: MemoryAccess::None,
         instruction_size: 16,
         max_cycle: CycleCount::Value(0),
     };
@@ -802,7 +816,7 @@ fn test_add_sp_immediate() {
 
     let instruction = Instruction {
         operations: (16, instruction).convert(false),
-        memory_access: false,
+        memory_access: MemoryAccess::None,
         instruction_size: 16,
         max_cycle: CycleCount::Value(0),
     };
@@ -830,7 +844,7 @@ fn test_add_sp_immediate() {
 
     let instruction = Instruction {
         operations: (16, instruction).convert(false),
-        memory_access: false,
+        memory_access: MemoryAccess::None,
         instruction_size: 16,
         max_cycle: CycleCount::Value(0),
     };
@@ -867,7 +881,7 @@ fn test_add_sp_reg() {
 
     let instruction = Instruction {
         operations: (16, instruction).convert(false),
-        memory_access: false,
+        memory_access: MemoryAccess::None,
         instruction_size: 16,
         max_cycle: CycleCount::Value(0),
     };
@@ -896,7 +910,7 @@ fn test_add_sp_reg() {
 
     let instruction = Instruction {
         operations: (16, instruction).convert(false),
-        memory_access: false,
+        memory_access: MemoryAccess::None,
         instruction_size: 16,
         max_cycle: CycleCount::Value(0),
     };
@@ -928,7 +942,7 @@ fn test_add_sp_reg() {
 
     let instruction = Instruction {
         operations: (16, instruction).convert(false),
-        memory_access: false,
+        memory_access: MemoryAccess::None,
         instruction_size: 16,
         max_cycle: CycleCount::Value(0),
     };
@@ -963,7 +977,7 @@ fn test_adr() {
 
     let instruction = Instruction {
         operations: (16, instruction).convert(false),
-        memory_access: false,
+        memory_access: MemoryAccess::None,
         instruction_size: 16,
         max_cycle: CycleCount::Value(0),
     };
@@ -989,7 +1003,7 @@ fn test_adr() {
 
     let instruction = Instruction {
         operations: (16, instruction).convert(false),
-        memory_access: false,
+        memory_access: MemoryAccess::None,
         instruction_size: 16,
         max_cycle: CycleCount::Value(0),
     };
@@ -1015,7 +1029,7 @@ fn test_adr() {
 
     let instruction = Instruction {
         operations: (16, instruction).convert(false),
-        memory_access: false,
+        memory_access: MemoryAccess::None,
         instruction_size: 16,
         max_cycle: CycleCount::Value(0),
     };
@@ -1056,7 +1070,7 @@ fn test_and_no_set_flag() {
 
     let instruction = Instruction {
         operations: (16, instruction).convert(false),
-        memory_access: false,
+        memory_access: MemoryAccess::None,
         instruction_size: 16,
         max_cycle: CycleCount::Value(0),
     };
@@ -1098,7 +1112,7 @@ fn test_and_set_flag() {
 
     let instruction = Instruction {
         operations: (16, instruction).convert(false),
-        memory_access: false,
+        memory_access: MemoryAccess::None,
         instruction_size: 16,
         max_cycle: CycleCount::Value(0),
     };
@@ -1132,7 +1146,7 @@ fn test_and_set_flag() {
 
     let instruction = Instruction {
         operations: (16, instruction).convert(false),
-        memory_access: false,
+        memory_access: MemoryAccess::None,
         instruction_size: 16,
         max_cycle: CycleCount::Value(0),
     };
@@ -1170,7 +1184,7 @@ fn test_and_set_flag() {
 
     let instruction = Instruction {
         operations: (16, instruction).convert(true),
-        memory_access: false,
+        memory_access: MemoryAccess::None,
         instruction_size: 16,
         max_cycle: CycleCount::Value(0),
     };
@@ -1208,7 +1222,7 @@ fn test_and_set_flag() {
 
     let instruction = Instruction {
         operations: (16, instruction).convert(false),
-        memory_access: false,
+        memory_access: MemoryAccess::None,
         instruction_size: 16,
         max_cycle: CycleCount::Value(0),
     };
@@ -1246,7 +1260,7 @@ fn test_and_set_flag() {
 
     let instruction = Instruction {
         operations: (16, instruction).convert(false),
-        memory_access: false,
+        memory_access: MemoryAccess::None,
         instruction_size: 16,
         max_cycle: CycleCount::Value(0),
     };
@@ -1284,7 +1298,7 @@ fn test_and_imm_no_set_flag() {
 
     let instruction = Instruction {
         operations: (16, instruction).convert(false),
-        memory_access: false,
+        memory_access: MemoryAccess::None,
         instruction_size: 16,
         max_cycle: CycleCount::Value(0),
     };
@@ -1321,7 +1335,7 @@ fn test_and_immediate_set_flag() {
 
     let instruction = Instruction {
         operations: (16, instruction).convert(false),
-        memory_access: false,
+        memory_access: MemoryAccess::None,
         instruction_size: 16,
         max_cycle: CycleCount::Value(0),
     };
@@ -1351,7 +1365,7 @@ fn test_and_immediate_set_flag() {
 
     let instruction = Instruction {
         operations: (16, instruction).convert(false),
-        memory_access: false,
+        memory_access: MemoryAccess::None,
         instruction_size: 16,
         max_cycle: CycleCount::Value(0),
     };
@@ -1390,7 +1404,7 @@ fn test_and_immediate_set_flag() {
 
     let instruction = Instruction {
         operations: (16, instruction).convert(false),
-        memory_access: false,
+        memory_access: MemoryAccess::None,
         instruction_size: 16,
         max_cycle: CycleCount::Value(0),
     };
@@ -1420,7 +1434,7 @@ fn test_and_immediate_set_flag() {
 
     let instruction = Instruction {
         operations: (16, instruction).convert(false),
-        memory_access: false,
+        memory_access: MemoryAccess::None,
         instruction_size: 16,
         max_cycle: CycleCount::Value(0),
     };
@@ -1458,7 +1472,7 @@ fn test_asr_immediate() {
 
     let instruction = Instruction {
         operations: (16, instruction).convert(false),
-        memory_access: false,
+        memory_access: MemoryAccess::None,
         instruction_size: 16,
         max_cycle: CycleCount::Value(0),
     };
@@ -1496,7 +1510,7 @@ fn test_asr_immediate_set_flag() {
 
     let instruction = Instruction {
         operations: (16, instruction).convert(false),
-        memory_access: false,
+        memory_access: MemoryAccess::None,
         instruction_size: 16,
         max_cycle: CycleCount::Value(0),
     };
@@ -1527,7 +1541,7 @@ fn test_asr_immediate_set_flag() {
 
     let instruction = Instruction {
         operations: (16, instruction).convert(false),
-        memory_access: false,
+        memory_access: MemoryAccess::None,
         instruction_size: 16,
         max_cycle: CycleCount::Value(0),
     };
@@ -1558,7 +1572,7 @@ fn test_asr_immediate_set_flag() {
 
     let instruction = Instruction {
         operations: (16, instruction).convert(true),
-        memory_access: false,
+        memory_access: MemoryAccess::None,
         instruction_size: 16,
         max_cycle: CycleCount::Value(0),
     };
@@ -1589,7 +1603,7 @@ fn test_asr_immediate_set_flag() {
 
     let instruction = Instruction {
         operations: (16, instruction).convert(false),
-        memory_access: false,
+        memory_access: MemoryAccess::None,
         instruction_size: 16,
         max_cycle: CycleCount::Value(0),
     };
@@ -1627,7 +1641,7 @@ fn test_asr() {
 
     let instruction = Instruction {
         operations: (16, instruction).convert(false),
-        memory_access: false,
+        memory_access: MemoryAccess::None,
         instruction_size: 16,
         max_cycle: CycleCount::Value(0),
     };
@@ -1666,7 +1680,7 @@ fn test_asr_set_flag() {
 
     let instruction = Instruction {
         operations: (16, instruction).convert(false),
-        memory_access: false,
+        memory_access: MemoryAccess::None,
         instruction_size: 16,
         max_cycle: CycleCount::Value(0),
     };
@@ -1698,7 +1712,7 @@ fn test_asr_set_flag() {
 
     let instruction = Instruction {
         operations: (16, instruction).convert(false),
-        memory_access: false,
+        memory_access: MemoryAccess::None,
         instruction_size: 16,
         max_cycle: CycleCount::Value(0),
     };
@@ -1730,7 +1744,7 @@ fn test_asr_set_flag() {
 
     let instruction = Instruction {
         operations: (16, instruction).convert(true),
-        memory_access: false,
+        memory_access: MemoryAccess::None,
         instruction_size: 16,
         max_cycle: CycleCount::Value(0),
     };
@@ -1762,7 +1776,7 @@ fn test_asr_set_flag() {
 
     let instruction = Instruction {
         operations: (16, instruction).convert(false),
-        memory_access: false,
+        memory_access: MemoryAccess::None,
         instruction_size: 16,
         max_cycle: CycleCount::Value(0),
     };
@@ -1799,7 +1813,7 @@ fn test_b() {
 
     let instruction = Instruction {
         operations: (16, instruction).convert(false),
-        memory_access: false,
+        memory_access: MemoryAccess::None,
         instruction_size: 16,
         max_cycle: CycleCount::Value(0),
     };
@@ -1834,7 +1848,7 @@ fn test_b_conditional() {
 
     let instruction = Instruction {
         operations: (16, instruction).convert(false),
-        memory_access: false,
+        memory_access: MemoryAccess::None,
         instruction_size: 16,
         max_cycle: CycleCount::Value(0),
     };
@@ -1861,7 +1875,7 @@ fn test_b_conditional() {
 
     let instruction = Instruction {
         operations: (16, instruction).convert(false),
-        memory_access: false,
+        memory_access: MemoryAccess::None,
         instruction_size: 16,
         max_cycle: CycleCount::Value(0),
     };
@@ -1888,7 +1902,7 @@ fn test_b_conditional() {
 
     let instruction = Instruction {
         operations: (16, instruction).convert(false),
-        memory_access: false,
+        memory_access: MemoryAccess::None,
         instruction_size: 16,
         max_cycle: CycleCount::Value(0),
     };
@@ -1915,7 +1929,7 @@ fn test_b_conditional() {
 
     let instruction = Instruction {
         operations: (16, instruction).convert(false),
-        memory_access: false,
+        memory_access: MemoryAccess::None,
         instruction_size: 16,
         max_cycle: CycleCount::Value(0),
     };
@@ -1947,7 +1961,7 @@ fn test_bx() {
 
     let instruction = Instruction {
         operations: (16, instruction).convert(false),
-        memory_access: false,
+        memory_access: MemoryAccess::None,
         instruction_size: 16,
         max_cycle: CycleCount::Value(0),
     };
@@ -1983,7 +1997,7 @@ fn test_bfc() {
 
     let instruction = Instruction {
         operations: (16, instruction).convert(false),
-        memory_access: false,
+        memory_access: MemoryAccess::None,
         instruction_size: 16,
         max_cycle: CycleCount::Value(0),
     };
@@ -2020,7 +2034,7 @@ fn test_bfi() {
 
     let instruction = Instruction {
         operations: (16, instruction).convert(false),
-        memory_access: false,
+        memory_access: MemoryAccess::None,
         instruction_size: 16,
         max_cycle: CycleCount::Value(0),
     };
@@ -2058,7 +2072,7 @@ fn test_bfi_panic() {
 
     let instruction = Instruction {
         operations: (16, instruction).convert(false),
-        memory_access: false,
+        memory_access: MemoryAccess::None,
         instruction_size: 16,
         max_cycle: CycleCount::Value(0),
     };
@@ -2096,7 +2110,7 @@ fn test_bic_imm() {
 
     let instruction = Instruction {
         operations: (16, instruction).convert(false),
-        memory_access: false,
+        memory_access: MemoryAccess::None,
         instruction_size: 16,
         max_cycle: CycleCount::Value(0),
     };
@@ -2135,7 +2149,7 @@ fn test_bic_imm_set_flags() {
 
     let instruction = Instruction {
         operations: (16, instruction).convert(false),
-        memory_access: false,
+        memory_access: MemoryAccess::None,
         instruction_size: 16,
         max_cycle: CycleCount::Value(0),
     };
@@ -2166,7 +2180,7 @@ fn test_bic_imm_set_flags() {
 
     let instruction = Instruction {
         operations: (16, instruction).convert(false),
-        memory_access: false,
+        memory_access: MemoryAccess::None,
         instruction_size: 16,
         max_cycle: CycleCount::Value(0),
     };
@@ -2206,7 +2220,7 @@ fn test_bic_reg() {
 
     let instruction = Instruction {
         operations: (16, instruction).convert(false),
-        memory_access: false,
+        memory_access: MemoryAccess::None,
         instruction_size: 16,
         max_cycle: CycleCount::Value(0),
     };
@@ -2248,7 +2262,7 @@ fn test_bic_reg_set_flags() {
 
     let instruction = Instruction {
         operations: (16, instruction).convert(false),
-        memory_access: false,
+        memory_access: MemoryAccess::None,
         instruction_size: 16,
         max_cycle: CycleCount::Value(0),
     };
@@ -2282,7 +2296,7 @@ fn test_bic_reg_set_flags() {
 
     let instruction = Instruction {
         operations: (16, instruction).convert(false),
-        memory_access: false,
+        memory_access: MemoryAccess::None,
         instruction_size: 16,
         max_cycle: CycleCount::Value(0),
     };
@@ -2307,10 +2321,10 @@ fn test_bl() {
     let v6 = vec![
         GAOperation::Move {
             destination: Operand::Local("PC".to_owned()),
-            source: Operand::Register("PC".to_owned()),
+            source: Operand::Register(GARegister::PC),
         },
         GAOperation::Move {
-            destination: Operand::Register("LR".to_owned()),
+            destination: Operand::Register(GARegister::LR),
             source: Operand::Local("PC".to_owned()),
         },
         GAOperation::Add {
@@ -2319,7 +2333,7 @@ fn test_bl() {
             operand2: Operand::Immediate(DataWord::Word32(0x4)),
         },
         GAOperation::Move {
-            destination: Operand::Register("PC".to_owned()),
+            destination: Operand::Register(GARegister::PC),
             source: Operand::Local("newPC".to_owned()),
         },
     ];
@@ -2336,7 +2350,7 @@ fn test_bl() {
 
     let instruction = Instruction {
         operations: (16, instruction).convert(false),
-        memory_access: false,
+        memory_access: MemoryAccess::None,
         instruction_size: 16,
         max_cycle: CycleCount::Value(0),
     };
@@ -2364,7 +2378,7 @@ fn test_bl() {
 
     let instruction = Instruction {
         operations: v6,
-        memory_access: false,
+        memory_access: MemoryAccess::None,
         instruction_size: 16,
         max_cycle: CycleCount::Value(0),
     };
@@ -2407,7 +2421,7 @@ fn test_cmp_imm() {
 
     let instruction = Instruction {
         operations: (16, instruction).convert(false),
-        memory_access: false,
+        memory_access: MemoryAccess::None,
         instruction_size: 16,
         max_cycle: CycleCount::Value(0),
     };
@@ -2438,7 +2452,7 @@ fn test_cmp_imm() {
 
     let instruction = Instruction {
         operations: (16, instruction).convert(false),
-        memory_access: false,
+        memory_access: MemoryAccess::None,
         instruction_size: 16,
         max_cycle: CycleCount::Value(0),
     };
@@ -2480,7 +2494,7 @@ fn test_ldr_imm() {
 
     let instruction = Instruction {
         operations: (16, instruction).convert(false),
-        memory_access: false,
+        memory_access: MemoryAccess::None,
         instruction_size: 16,
         max_cycle: CycleCount::Value(0),
     };
@@ -2512,7 +2526,7 @@ fn test_ldr_imm() {
 
     let instruction = Instruction {
         operations: (16, instruction).convert(false),
-        memory_access: false,
+        memory_access: MemoryAccess::None,
         instruction_size: 16,
         max_cycle: CycleCount::Value(0),
     };
@@ -2544,7 +2558,7 @@ fn test_ldr_imm() {
 
     let instruction = Instruction {
         operations: (16, instruction).convert(false),
-        memory_access: false,
+        memory_access: MemoryAccess::None,
         instruction_size: 16,
         max_cycle: CycleCount::Value(0),
     };
@@ -2576,7 +2590,7 @@ fn test_ldr_imm() {
 
     let instruction = Instruction {
         operations: (16, instruction).convert(false),
-        memory_access: false,
+        memory_access: MemoryAccess::None,
         instruction_size: 16,
         max_cycle: CycleCount::Value(0),
     };
@@ -2607,7 +2621,7 @@ fn test_ldr_imm() {
 
     let instruction = Instruction {
         operations: (16, instruction).convert(false),
-        memory_access: false,
+        memory_access: MemoryAccess::None,
         instruction_size: 16,
         max_cycle: CycleCount::Value(0),
     };
@@ -2644,7 +2658,7 @@ fn test_ldr_literal() {
 
     let instruction = Instruction {
         operations: (16, instruction).convert(false),
-        memory_access: false,
+        memory_access: MemoryAccess::None,
         instruction_size: 16,
         max_cycle: CycleCount::Value(0),
     };
@@ -2683,7 +2697,7 @@ fn test_ldr_register() {
 
     let instruction = Instruction {
         operations: (16, instruction).convert(false),
-        memory_access: false,
+        memory_access: MemoryAccess::None,
         instruction_size: 16,
         max_cycle: CycleCount::Value(0),
     };
@@ -2715,7 +2729,7 @@ fn test_ldr_register() {
 
     let instruction = Instruction {
         operations: (16, instruction).convert(false),
-        memory_access: false,
+        memory_access: MemoryAccess::None,
         instruction_size: 16,
         max_cycle: CycleCount::Value(0),
     };
@@ -2750,7 +2764,7 @@ fn test_ldr_register() {
 
     let instruction = Instruction {
         operations: (16, instruction).convert(false),
-        memory_access: false,
+        memory_access: MemoryAccess::None,
         instruction_size: 16,
         max_cycle: CycleCount::Value(0),
     };
@@ -2790,7 +2804,7 @@ fn test_ldrh_imm() {
 
     let instruction = Instruction {
         operations: (16, instruction).convert(false),
-        memory_access: false,
+        memory_access: MemoryAccess::None,
         instruction_size: 16,
         max_cycle: CycleCount::Value(0),
     };
@@ -2821,7 +2835,7 @@ fn test_ldrh_imm() {
 
     let instruction = Instruction {
         operations: (16, instruction).convert(false),
-        memory_access: false,
+        memory_access: MemoryAccess::None,
         instruction_size: 16,
         max_cycle: CycleCount::Value(0),
     };
@@ -2852,7 +2866,7 @@ fn test_ldrh_imm() {
 
     let instruction = Instruction {
         operations: (16, instruction).convert(false),
-        memory_access: false,
+        memory_access: MemoryAccess::None,
         instruction_size: 16,
         max_cycle: CycleCount::Value(0),
     };
@@ -2883,7 +2897,7 @@ fn test_ldrh_imm() {
 
     let instruction = Instruction {
         operations: (16, instruction).convert(false),
-        memory_access: false,
+        memory_access: MemoryAccess::None,
         instruction_size: 16,
         max_cycle: CycleCount::Value(0),
     };
@@ -2913,7 +2927,7 @@ fn test_ldrh_imm() {
 
     let instruction = Instruction {
         operations: (16, instruction).convert(false),
-        memory_access: false,
+        memory_access: MemoryAccess::None,
         instruction_size: 16,
         max_cycle: CycleCount::Value(0),
     };
@@ -2953,7 +2967,7 @@ fn test_ldrb_imm() {
 
     let instruction = Instruction {
         operations: (16, instruction).convert(false),
-        memory_access: false,
+        memory_access: MemoryAccess::None,
         instruction_size: 16,
         max_cycle: CycleCount::Value(0),
     };
@@ -2985,7 +2999,7 @@ fn test_ldrb_imm() {
 
     let instruction = Instruction {
         operations: (16, instruction).convert(false),
-        memory_access: false,
+        memory_access: MemoryAccess::None,
         instruction_size: 16,
         max_cycle: CycleCount::Value(0),
     };
@@ -3016,7 +3030,7 @@ fn test_ldrb_imm() {
 
     let instruction = Instruction {
         operations: (16, instruction).convert(false),
-        memory_access: false,
+        memory_access: MemoryAccess::None,
         instruction_size: 16,
         max_cycle: CycleCount::Value(0),
     };
@@ -3047,7 +3061,7 @@ fn test_ldrb_imm() {
 
     let instruction = Instruction {
         operations: (16, instruction).convert(false),
-        memory_access: false,
+        memory_access: MemoryAccess::None,
         instruction_size: 16,
         max_cycle: CycleCount::Value(0),
     };
@@ -3078,7 +3092,7 @@ fn test_ldrb_imm() {
 
     let instruction = Instruction {
         operations: (16, instruction).convert(false),
-        memory_access: false,
+        memory_access: MemoryAccess::None,
         instruction_size: 16,
         max_cycle: CycleCount::Value(0),
     };
@@ -3108,7 +3122,7 @@ fn test_ldrb_imm() {
 
     let instruction = Instruction {
         operations: (16, instruction).convert(false),
-        memory_access: false,
+        memory_access: MemoryAccess::None,
         instruction_size: 16,
         max_cycle: CycleCount::Value(0),
     };
@@ -3146,7 +3160,7 @@ fn test_lsl_immediate() {
 
     let instruction = Instruction {
         operations: (16, instruction).convert(false),
-        memory_access: false,
+        memory_access: MemoryAccess::None,
         instruction_size: 16,
         max_cycle: CycleCount::Value(0),
     };
@@ -3179,7 +3193,7 @@ fn test_lsl_immediate() {
 
     let instruction = Instruction {
         operations: (16, instruction).convert(false),
-        memory_access: false,
+        memory_access: MemoryAccess::None,
         instruction_size: 16,
         max_cycle: CycleCount::Value(0),
     };
@@ -3219,7 +3233,7 @@ fn test_lsr_immediate() {
 
     let instruction = Instruction {
         operations: (16, instruction).convert(false),
-        memory_access: false,
+        memory_access: MemoryAccess::None,
         instruction_size: 16,
         max_cycle: CycleCount::Value(0),
     };
@@ -3252,7 +3266,7 @@ fn test_lsr_immediate() {
 
     let instruction = Instruction {
         operations: (16, instruction).convert(false),
-        memory_access: false,
+        memory_access: MemoryAccess::None,
         instruction_size: 16,
         max_cycle: CycleCount::Value(0),
     };
@@ -3292,7 +3306,7 @@ fn test_mov_imm_no_set_flags() {
 
     let instruction = Instruction {
         operations: (16, instruction).convert(false),
-        memory_access: false,
+        memory_access: MemoryAccess::None,
         instruction_size: 16,
         max_cycle: CycleCount::Value(0),
     };
@@ -3332,7 +3346,7 @@ fn test_mov_imm_set_flags() {
 
     let instruction = Instruction {
         operations: (16, instruction).convert(false),
-        memory_access: false,
+        memory_access: MemoryAccess::None,
         instruction_size: 16,
         max_cycle: CycleCount::Value(0),
     };
@@ -3364,7 +3378,7 @@ fn test_mov_imm_set_flags() {
 
     let instruction = Instruction {
         operations: (16, instruction).convert(false),
-        memory_access: false,
+        memory_access: MemoryAccess::None,
         instruction_size: 16,
         max_cycle: CycleCount::Value(0),
     };
@@ -3404,7 +3418,7 @@ fn test_mov_reg_no_set_flags() {
 
     let instruction = Instruction {
         operations: (16, instruction).convert(false),
-        memory_access: false,
+        memory_access: MemoryAccess::None,
         instruction_size: 16,
         max_cycle: CycleCount::Value(0),
     };
@@ -3444,7 +3458,7 @@ fn test_mov_reg_set_flags() {
 
     let instruction = Instruction {
         operations: (16, instruction).convert(false),
-        memory_access: false,
+        memory_access: MemoryAccess::None,
         instruction_size: 16,
         max_cycle: CycleCount::Value(0),
     };
@@ -3476,7 +3490,7 @@ fn test_mov_reg_set_flags() {
 
     let instruction = Instruction {
         operations: (16, instruction).convert(false),
-        memory_access: false,
+        memory_access: MemoryAccess::None,
         instruction_size: 16,
         max_cycle: CycleCount::Value(0),
     };
@@ -3516,7 +3530,7 @@ fn test_mul() {
 
     let instruction = Instruction {
         operations: (16, instruction).convert(false),
-        memory_access: false,
+        memory_access: MemoryAccess::None,
         instruction_size: 16,
         max_cycle: CycleCount::Value(0),
     };
@@ -3549,7 +3563,7 @@ fn test_mul() {
 
     let instruction = Instruction {
         operations: (16, instruction).convert(false),
-        memory_access: false,
+        memory_access: MemoryAccess::None,
         instruction_size: 16,
         max_cycle: CycleCount::Value(0),
     };
@@ -3596,7 +3610,7 @@ fn test_pop() {
 
     let instruction = Instruction {
         operations: (16, instruction).convert(false),
-        memory_access: false,
+        memory_access: MemoryAccess::None,
         instruction_size: 16,
         max_cycle: CycleCount::Value(0),
     };
@@ -3642,7 +3656,7 @@ fn test_push() {
 
     let instruction = Instruction {
         operations: (16, instruction).convert(false),
-        memory_access: false,
+        memory_access: MemoryAccess::None,
         instruction_size: 16,
         max_cycle: CycleCount::Value(0),
     };
@@ -3684,7 +3698,7 @@ fn test_rsb() {
 
     let instruction = Instruction {
         operations: (16, instruction).convert(false),
-        memory_access: false,
+        memory_access: MemoryAccess::None,
         instruction_size: 16,
         max_cycle: CycleCount::Value(0),
     };
@@ -3715,7 +3729,7 @@ fn test_rsb() {
 
     let instruction = Instruction {
         operations: (16, instruction).convert(false),
-        memory_access: false,
+        memory_access: MemoryAccess::None,
         instruction_size: 16,
         max_cycle: CycleCount::Value(0),
     };
@@ -3753,7 +3767,7 @@ fn test_strb_imm() {
 
     let instruction = Instruction {
         operations: (16, instruction).convert(false),
-        memory_access: false,
+        memory_access: MemoryAccess::None,
         instruction_size: 16,
         max_cycle: CycleCount::Value(0),
     };
@@ -3785,7 +3799,7 @@ fn test_strb_imm() {
 
     let instruction = Instruction {
         operations: (16, instruction).convert(false),
-        memory_access: false,
+        memory_access: MemoryAccess::None,
         instruction_size: 16,
         max_cycle: CycleCount::Value(0),
     };
@@ -3817,7 +3831,7 @@ fn test_strb_imm() {
 
     let instruction = Instruction {
         operations: (16, instruction).convert(false),
-        memory_access: false,
+        memory_access: MemoryAccess::None,
         instruction_size: 16,
         max_cycle: CycleCount::Value(0),
     };
@@ -3849,7 +3863,7 @@ fn test_strb_imm() {
 
     let instruction = Instruction {
         operations: (16, instruction).convert(false),
-        memory_access: false,
+        memory_access: MemoryAccess::None,
         instruction_size: 16,
         max_cycle: CycleCount::Value(0),
     };
@@ -3892,7 +3906,7 @@ fn test_strh_imm() {
 
     let instruction = Instruction {
         operations: (16, instruction).convert(false),
-        memory_access: false,
+        memory_access: MemoryAccess::None,
         instruction_size: 16,
         max_cycle: CycleCount::Value(0),
     };
@@ -3927,7 +3941,7 @@ fn test_strh_imm() {
 
     let instruction = Instruction {
         operations: (16, instruction).convert(false),
-        memory_access: false,
+        memory_access: MemoryAccess::None,
         instruction_size: 16,
         max_cycle: CycleCount::Value(0),
     };
@@ -3962,7 +3976,7 @@ fn test_strh_imm() {
 
     let instruction = Instruction {
         operations: (16, instruction).convert(false),
-        memory_access: false,
+        memory_access: MemoryAccess::None,
         instruction_size: 16,
         max_cycle: CycleCount::Value(0),
     };
@@ -3997,7 +4011,7 @@ fn test_strh_imm() {
 
     let instruction = Instruction {
         operations: (16, instruction).convert(false),
-        memory_access: false,
+        memory_access: MemoryAccess::None,
         instruction_size: 16,
         max_cycle: CycleCount::Value(0),
     };
@@ -4040,7 +4054,7 @@ fn test_str_imm() {
 
     let instruction = Instruction {
         operations: (16, instruction).convert(false),
-        memory_access: false,
+        memory_access: MemoryAccess::None,
         instruction_size: 16,
         max_cycle: CycleCount::Value(0),
     };
@@ -4074,7 +4088,7 @@ fn test_str_imm() {
 
     let instruction = Instruction {
         operations: (16, instruction).convert(false),
-        memory_access: false,
+        memory_access: MemoryAccess::None,
         instruction_size: 16,
         max_cycle: CycleCount::Value(0),
     };
@@ -4108,7 +4122,7 @@ fn test_str_imm() {
 
     let instruction = Instruction {
         operations: (16, instruction).convert(false),
-        memory_access: false,
+        memory_access: MemoryAccess::None,
         instruction_size: 16,
         max_cycle: CycleCount::Value(0),
     };
@@ -4142,7 +4156,7 @@ fn test_str_imm() {
 
     let instruction = Instruction {
         operations: (16, instruction).convert(false),
-        memory_access: false,
+        memory_access: MemoryAccess::None,
         instruction_size: 16,
         max_cycle: CycleCount::Value(0),
     };
@@ -4182,7 +4196,7 @@ fn test_sub_imm_no_flags() {
 
     let instruction = Instruction {
         operations: (16, instruction).convert(false),
-        memory_access: false,
+        memory_access: MemoryAccess::None,
         instruction_size: 16,
         max_cycle: CycleCount::Value(0),
     };
@@ -4216,7 +4230,7 @@ fn test_sub_imm_no_flags() {
 
     let instruction = Instruction {
         operations: (16, instruction).convert(false),
-        memory_access: false,
+        memory_access: MemoryAccess::None,
         instruction_size: 16,
         max_cycle: CycleCount::Value(0),
     };
@@ -4251,7 +4265,7 @@ fn test_sub_imm_no_flags() {
 
     let instruction = Instruction {
         operations: (16, instruction).convert(false),
-        memory_access: false,
+        memory_access: MemoryAccess::None,
         instruction_size: 16,
         max_cycle: CycleCount::Value(0),
     };
@@ -4294,7 +4308,7 @@ fn test_sub_imm_set_flags() {
 
     let instruction = Instruction {
         operations: (16, instruction).convert(false),
-        memory_access: false,
+        memory_access: MemoryAccess::None,
         instruction_size: 16,
         max_cycle: CycleCount::Value(0),
     };
@@ -4329,7 +4343,7 @@ fn test_sub_imm_set_flags() {
 
     let instruction = Instruction {
         operations: (16, instruction).convert(false),
-        memory_access: false,
+        memory_access: MemoryAccess::None,
         instruction_size: 16,
         max_cycle: CycleCount::Value(0),
     };
@@ -4374,7 +4388,7 @@ fn test_sub_reg_no_flags() {
 
     let instruction = Instruction {
         operations: (16, instruction).convert(false),
-        memory_access: false,
+        memory_access: MemoryAccess::None,
         instruction_size: 16,
         max_cycle: CycleCount::Value(0),
     };
@@ -4411,7 +4425,7 @@ fn test_sub_reg_no_flags() {
 
     let instruction = Instruction {
         operations: (16, instruction).convert(false),
-        memory_access: false,
+        memory_access: MemoryAccess::None,
         instruction_size: 16,
         max_cycle: CycleCount::Value(0),
     };
@@ -4451,7 +4465,7 @@ fn test_sub_reg_no_flags() {
 
     let instruction = Instruction {
         operations: (16, instruction).convert(false),
-        memory_access: false,
+        memory_access: MemoryAccess::None,
         instruction_size: 16,
         max_cycle: CycleCount::Value(0),
     };
@@ -4496,7 +4510,7 @@ fn test_sub_reg_set_flags() {
 
     let instruction = Instruction {
         operations: (16, instruction).convert(false),
-        memory_access: false,
+        memory_access: MemoryAccess::None,
         instruction_size: 16,
         max_cycle: CycleCount::Value(0),
     };
@@ -4533,7 +4547,7 @@ fn test_sub_reg_set_flags() {
 
     let instruction = Instruction {
         operations: (16, instruction).convert(false),
-        memory_access: false,
+        memory_access: MemoryAccess::None,
         instruction_size: 16,
         max_cycle: CycleCount::Value(0),
     };
@@ -4575,7 +4589,7 @@ fn test_sub_sp_imm_no_flags() {
 
     let instruction = Instruction {
         operations: (16, instruction).convert(false),
-        memory_access: false,
+        memory_access: MemoryAccess::None,
         instruction_size: 16,
         max_cycle: CycleCount::Value(0),
     };
@@ -4609,7 +4623,7 @@ fn test_sub_sp_imm_no_flags() {
 
     let instruction = Instruction {
         operations: (16, instruction).convert(false),
-        memory_access: false,
+        memory_access: MemoryAccess::None,
         instruction_size: 16,
         max_cycle: CycleCount::Value(0),
     };
@@ -4643,7 +4657,7 @@ fn test_sub_sp_imm_no_flags() {
 
     let instruction = Instruction {
         operations: (16, instruction).convert(false),
-        memory_access: false,
+        memory_access: MemoryAccess::None,
         instruction_size: 16,
         max_cycle: CycleCount::Value(0),
     };
@@ -4685,7 +4699,7 @@ fn test_sub_sp_imm_set_flags() {
 
     let instruction = Instruction {
         operations: (16, instruction).convert(false),
-        memory_access: false,
+        memory_access: MemoryAccess::None,
         instruction_size: 16,
         max_cycle: CycleCount::Value(0),
     };
@@ -4719,7 +4733,7 @@ fn test_sub_sp_imm_set_flags() {
 
     let instruction = Instruction {
         operations: (16, instruction).convert(false),
-        memory_access: false,
+        memory_access: MemoryAccess::None,
         instruction_size: 16,
         max_cycle: CycleCount::Value(0),
     };
@@ -4761,7 +4775,7 @@ fn test_sub_uxth() {
 
     let instruction = Instruction {
         operations: (16, instruction).convert(false),
-        memory_access: false,
+        memory_access: MemoryAccess::None,
         instruction_size: 16,
         max_cycle: CycleCount::Value(0),
     };
@@ -4795,7 +4809,7 @@ fn test_sub_uxth() {
 
     let instruction = Instruction {
         operations: (16, instruction).convert(false),
-        memory_access: false,
+        memory_access: MemoryAccess::None,
         instruction_size: 16,
         max_cycle: CycleCount::Value(0),
     };
@@ -4840,7 +4854,7 @@ fn test_tb() {
 
     let instruction = Instruction {
         operations: (16, instruction).convert(false),
-        memory_access: false,
+        memory_access: MemoryAccess::None,
         instruction_size: 16,
         max_cycle: CycleCount::Value(0),
     };
@@ -4870,7 +4884,7 @@ fn test_tb() {
 
     let instruction = Instruction {
         operations: (16, instruction).convert(false),
-        memory_access: false,
+        memory_access: MemoryAccess::None,
         instruction_size: 16,
         max_cycle: CycleCount::Value(0),
     };
@@ -4912,7 +4926,7 @@ fn test_bfi_2() {
 
     let instruction = Instruction {
         operations: (16, instruction).convert(false),
-        memory_access: false,
+        memory_access: MemoryAccess::None,
         instruction_size: 16,
         max_cycle: CycleCount::Value(0),
     };
@@ -4945,7 +4959,7 @@ fn test_bfi_2() {
 
     let instruction = Instruction {
         operations: (16, instruction).convert(false),
-        memory_access: false,
+        memory_access: MemoryAccess::None,
         instruction_size: 16,
         max_cycle: CycleCount::Value(0),
     };
@@ -4957,3 +4971,850 @@ fn test_bfi_2() {
         register R2 == 0b100001
     });
 }
+
+/// Executes the operation and returns the number of cycles it took according to
+/// the cycle model of the target core.
+fn execute_timed(executor: &mut GAExecutor<ArmV7EM>, size: usize, operation: Operation) -> usize {
+    let max_cycle = match executor.state.project.get_target().core {
+        CoreFamily::ArmM(ArmMCore::ArmM7) => ArmV7EM::cycle_count_m7_core(&operation),
+        _ => ArmV7EM::cycle_count_m4_core(&operation),
+    };
+    let instruction = Instruction {
+        operations: (size, operation.clone()).convert(false),
+        memory_access: ArmV7EM::memory_access(&operation),
+        instruction_size: size as u32,
+        max_cycle,
+    };
+    let before = executor.state.cycle_count;
+    executor
+        .execute_instruction(&instruction)
+        .expect("Malformed instruction");
+    executor.state.increment_cycle_count();
+    executor.state.set_last_instruction(instruction);
+    executor.state.cycle_count - before
+}
+
+/// `LDR R0, [R1]`
+fn ldr() -> Operation {
+    LdrImmediate::builder()
+        .set_rn(Register::R1)
+        .set_rt(Register::R0)
+        .set_imm(0x0)
+        .set_w(Some(false))
+        .set_add(true)
+        .set_index(true)
+        .complete()
+        .into()
+}
+
+/// `STR R0, [R2]`
+fn str() -> Operation {
+    StrImmediate::builder()
+        .set_index(Some(true))
+        .set_add(true)
+        .set_w(Some(false))
+        .set_rt(Register::R0)
+        .set_rn(Register::R2)
+        .set_imm(0)
+        .complete()
+        .into()
+}
+
+/// `MOV R3, #1`
+fn mov() -> Operation {
+    MovImmediate::builder()
+        .set_rd(Register::R3)
+        .set_imm(1)
+        .set_s(None)
+        .set_carry(None)
+        .complete()
+        .into()
+}
+
+#[test]
+fn test_cycles_load_store_pipelining() {
+    let mut vm = setup_test_vm();
+    let project = vm.project;
+
+    let mut executor = GAExecutor::from_state(vm.paths.get_path().unwrap().state, &mut vm, project);
+
+    initiate!(executor {
+        register PC = 0x100;
+        register R1 = 0x1000;
+        register R2 = 0x2000
+    });
+
+    // LDR is 2 cycles, neighboring loads and stores are pipelined.
+    assert_eq!(execute_timed(&mut executor, 16, ldr()), 2);
+    assert_eq!(execute_timed(&mut executor, 16, ldr()), 1);
+    assert_eq!(execute_timed(&mut executor, 16, str()), 1);
+
+    // Nothing is pipelined after a store.
+    assert_eq!(execute_timed(&mut executor, 16, ldr()), 2);
+    assert_eq!(execute_timed(&mut executor, 16, str()), 1);
+    assert_eq!(execute_timed(&mut executor, 16, str()), 2);
+
+    // Nor after other instructions.
+    assert_eq!(execute_timed(&mut executor, 32, mov()), 1);
+    assert_eq!(execute_timed(&mut executor, 16, ldr()), 2);
+
+    // Or after a load or store multiple.
+    let push: Operation = Push::builder()
+        .set_registers(RegisterList {
+            registers: vec![Register::R4, Register::R5],
+        })
+        .complete()
+        .into();
+    assert_eq!(execute_timed(&mut executor, 16, push), 3);
+    assert_eq!(execute_timed(&mut executor, 16, ldr()), 2);
+
+    // PC relative loads might be delayed by the fetch unit and are never
+    // pipelined.
+    let literal: Operation = LdrLiteral::builder()
+        .set_add(true)
+        .set_rt(Register::R1)
+        .set_imm(0x100)
+        .complete()
+        .into();
+    assert_eq!(execute_timed(&mut executor, 16, literal), 3);
+}
+
+#[test]
+fn test_cycles_load_dependency() {
+    let mut vm = setup_test_vm();
+    let project = vm.project;
+
+    let mut executor = GAExecutor::from_state(vm.paths.get_path().unwrap().state, &mut vm, project);
+
+    initiate!(executor {
+        register PC = 0x100;
+        register R0 = 0x2000;
+        register R1 = 0x1000;
+        register R2 = 0x1000
+    });
+
+    // `LDR R2, [R0]`
+    let dependent: Operation = LdrImmediate::builder()
+        .set_rn(Register::R0)
+        .set_rt(Register::R2)
+        .set_imm(0x0)
+        .set_w(Some(false))
+        .set_add(true)
+        .set_index(true)
+        .complete()
+        .into();
+
+    // Stores 0x2000 at 0x1000 and loads it in to R0.
+    assert_eq!(execute_timed(&mut executor, 16, str()), 2);
+    assert_eq!(execute_timed(&mut executor, 16, ldr()), 2);
+
+    // The address of the second load is the loaded value.
+    assert_eq!(execute_timed(&mut executor, 16, dependent), 2);
+
+    // R1 is not written by the dependent load.
+    assert_eq!(execute_timed(&mut executor, 16, ldr()), 1);
+}
+
+#[test]
+fn test_cycles_branch() {
+    let mut vm = setup_test_vm();
+    let project = vm.project;
+
+    let mut executor = GAExecutor::from_state(vm.paths.get_path().unwrap().state, &mut vm, project);
+
+    initiate!(executor {
+        register PC = 0;
+        register R1 = 0x1002;
+        flag Z = 0
+    });
+
+    // B is 1 + P where P is 1 to 3.
+    //
+    // The target is forwarded and word aligned so it is fetched in one cycle.
+    let b: Operation = B::builder()
+        .set_condition(Condition::None)
+        .set_imm(0x1000 - 4)
+        .complete()
+        .into();
+    assert_eq!(execute_timed(&mut executor, 16, b), 1 + 1);
+    test!(executor {
+        register PC == 0x1000
+    });
+
+    // A not taken conditional branch is a single cycle.
+    let beq: Operation = B::builder()
+        .set_condition(Condition::Eq)
+        .set_imm(0x100)
+        .complete()
+        .into();
+    assert_eq!(execute_timed(&mut executor, 16, beq), 1);
+    test!(executor {
+        register PC == 0x1002
+    });
+
+    // The target of BX is not forwarded and the instruction at the target is
+    // unknown, so it might need two fetches.
+    let bx: Operation = Bx::builder().set_rm(Register::R1).complete().into();
+    assert_eq!(execute_timed(&mut executor, 16, bx), 1 + 3);
+
+    // POP with the PC is 1 + N + P.
+    initiate!(executor {
+        register SP = 0x2000;
+        address(0x2000, 32) = 0x3000;
+        address(0x2004, 32) = 0x4000
+    });
+    let pop: Operation = Pop::builder()
+        .set_registers(RegisterList {
+            registers: vec![Register::R4, Register::PC],
+        })
+        .complete()
+        .into();
+    assert_eq!(execute_timed(&mut executor, 16, pop), 1 + 2 + 3);
+}
+
+#[test]
+fn test_cycles_it_folding() {
+    let mut vm = setup_test_vm();
+    let project = vm.project;
+
+    let mut executor = GAExecutor::from_state(vm.paths.get_path().unwrap().state, &mut vm, project);
+
+    initiate!(executor {
+        register PC = 0x100;
+        flag Z = 1
+    });
+
+    let it = || -> Operation {
+        It::builder()
+            .set_conds(arch::ITCondition {
+                conditions: vec![Condition::Eq],
+            })
+            .complete()
+            .into()
+    };
+
+    // The first instruction can not be folded.
+    assert_eq!(execute_timed(&mut executor, 16, it()), 1);
+    assert_eq!(execute_timed(&mut executor, 16, mov()), 1);
+
+    // IT is folded on to a preceding 16 bit instruction.
+    assert_eq!(execute_timed(&mut executor, 16, it()), 0);
+    assert_eq!(execute_timed(&mut executor, 16, mov()), 1);
+
+    // But not on to a 32 bit instruction.
+    assert_eq!(execute_timed(&mut executor, 32, mov()), 1);
+    assert_eq!(execute_timed(&mut executor, 16, it()), 1);
+}
+
+#[test]
+fn test_cycles_flash_prefetch() {
+    let flash = MemoryRegion {
+        wait_states: 3,
+        line_size: 16,
+        prefetch: true,
+        ..MemoryRegion::new(0, 0x1_0000)
+    };
+    let mut target = ArmV7EM::default_target();
+    target.flash.push(flash);
+    let mut vm = setup_test_vm_with_target(target);
+    let project = vm.project;
+
+    let mut executor = GAExecutor::from_state(vm.paths.get_path().unwrap().state, &mut vm, project);
+
+    initiate!(executor {
+        register PC = 0x100
+    });
+
+    // The first fetch waits for the flash.
+    assert_eq!(execute_timed(&mut executor, 16, mov()), 1 + 3);
+    for _ in 0..7 {
+        assert_eq!(execute_timed(&mut executor, 16, mov()), 1);
+    }
+
+    // The next line was prefetched while the previous line executed.
+    assert_eq!(execute_timed(&mut executor, 16, mov()), 1);
+
+    // A branch target is not prefetched.
+    let b: Operation = B::builder()
+        .set_condition(Condition::None)
+        .set_imm(0x20c - 0x112 - 4)
+        .complete()
+        .into();
+    assert_eq!(execute_timed(&mut executor, 16, b), 1 + 1);
+    assert_eq!(execute_timed(&mut executor, 16, mov()), 1 + 3);
+
+    // And the prefetch of the next line only had two cycles to complete.
+    assert_eq!(execute_timed(&mut executor, 16, mov()), 1);
+    assert_eq!(execute_timed(&mut executor, 16, mov()), 1 + 1);
+}
+
+fn setup_test_vm_m7(target: impl FnOnce(&mut TargetConfiguration)) -> VM<ArmV7EM> {
+    let mut m7 = TargetConfiguration::zero_wait_states(CoreFamily::ArmM(ArmMCore::ArmM7));
+    target(&mut m7);
+    setup_test_vm_with_target(m7)
+}
+
+/// `MOV R4, #2`
+fn mov_r4() -> Operation {
+    MovImmediate::builder()
+        .set_rd(Register::R4)
+        .set_imm(2)
+        .set_s(None)
+        .set_carry(None)
+        .complete()
+        .into()
+}
+
+#[test]
+fn test_cycles_m7_dual_issue() {
+    let mut vm = setup_test_vm_m7(|_| ());
+    let project = vm.project;
+
+    let mut executor = GAExecutor::from_state(vm.paths.get_path().unwrap().state, &mut vm, project);
+
+    initiate!(executor {
+        register PC = 0x100;
+        register R1 = 0x1000;
+        register R2 = 0x2000
+    });
+
+    // Independent instructions are issued in pairs.
+    assert_eq!(execute_timed(&mut executor, 16, mov()), 1);
+    assert_eq!(execute_timed(&mut executor, 16, mov_r4()), 0);
+    assert_eq!(execute_timed(&mut executor, 16, mov()), 1);
+
+    // But not if they write the same register.
+    assert_eq!(execute_timed(&mut executor, 16, mov()), 1);
+
+    // A load can be paired with a data processing instruction.
+    assert_eq!(execute_timed(&mut executor, 16, ldr()), 0);
+
+    // Using the loaded value stalls the pipeline.
+    assert_eq!(execute_timed(&mut executor, 16, str()), 1 + 2);
+    assert_eq!(execute_timed(&mut executor, 16, mov()), 0);
+}
+
+#[test]
+fn test_cycles_m7_branch_prediction() {
+    let mut vm = setup_test_vm_m7(|_| ());
+    let project = vm.project;
+
+    let mut executor = GAExecutor::from_state(vm.paths.get_path().unwrap().state, &mut vm, project);
+
+    initiate!(executor {
+        register PC = 0x100;
+        flag Z = 0
+    });
+
+    let b = || -> Operation {
+        B::builder()
+            .set_condition(Condition::None)
+            .set_imm(-4i32 as u32)
+            .complete()
+            .into()
+    };
+    let beq = || -> Operation {
+        B::builder()
+            .set_condition(Condition::Eq)
+            .set_imm(-4i32 as u32)
+            .complete()
+            .into()
+    };
+
+    // The BTAC is empty when the analysis starts.
+    assert_eq!(execute_timed(&mut executor, 16, b()), 1 + 8);
+    test!(executor {
+        register PC == 0x100
+    });
+
+    // The second time the branch is predicted.
+    assert_eq!(execute_timed(&mut executor, 16, b()), 1);
+
+    // A branch that has been taken might be predicted as taken.
+    assert_eq!(execute_timed(&mut executor, 16, beq()), 1 + 8);
+    test!(executor {
+        register PC == 0x102
+    });
+
+    // A branch that has never been taken is predicted as not taken.
+    assert_eq!(execute_timed(&mut executor, 16, beq()), 1);
+}
+
+#[test]
+fn test_cycles_m7_memories() {
+    let mut vm = setup_test_vm_m7(|target| {
+        target.tcm.push(MemoryRegion::new(0, 0x1_0000));
+        target.flash.push(MemoryRegion {
+            wait_states: 7,
+            ..MemoryRegion::new(0x0800_0000, 0x0810_0000)
+        });
+        target.icache = Some(Cache {
+            size: 0x4000,
+            line_size: 32,
+            ways: 2,
+            miss_penalty: 4,
+        });
+    });
+    let project = vm.project;
+
+    let mut executor = GAExecutor::from_state(vm.paths.get_path().unwrap().state, &mut vm, project);
+
+    initiate!(executor {
+        register PC = 0x100
+    });
+
+    // Code in the ITCM has no wait states.
+    assert_eq!(execute_timed(&mut executor, 16, mov()), 1);
+
+    // The instruction cache is cold.
+    initiate!(executor {
+        register PC = 0x0800_0000
+    });
+    assert_eq!(execute_timed(&mut executor, 16, mov()), 1 + 7 + 4);
+
+    // The rest of the line is cached.
+    assert_eq!(execute_timed(&mut executor, 16, mov()), 1);
+    initiate!(executor {
+        register PC = 0x0800_0000
+    });
+    assert_eq!(execute_timed(&mut executor, 16, mov()), 1);
+}
+
+/// Creates a vm that runs `code` from address 0 and returns to `0x1000`.
+fn setup_test_vm_with_code(code: &[u16]) -> VM<ArmV7EM> {
+    setup_test_vm_with_symbols(code, HashMap::new(), |_| {})
+}
+
+/// Sets up a vm running `code` with the symbols in `symtab` and lets
+/// `configure` change the project before it is used.
+fn setup_test_vm_with_symbols(
+    code: &[u16],
+    symtab: HashMap<String, u64>,
+    configure: impl FnOnce(&mut Project<ArmV7EM>),
+) -> VM<ArmV7EM> {
+    // Instructions are read a word at a time so the last half word is padded.
+    let program: Vec<u8> = code
+        .iter()
+        .chain(&[0])
+        .flat_map(|half_word| half_word.to_le_bytes())
+        .collect();
+    setup_test_vm_with_program(program, Endianness::Little, symtab, configure)
+}
+
+/// Sets up a vm running the raw bytes in `program` with data in the byte order
+/// given by `endianness`.
+fn setup_test_vm_with_program(
+    program: Vec<u8>,
+    endianness: Endianness,
+    symtab: HashMap<String, u64>,
+    configure: impl FnOnce(&mut Project<ArmV7EM>),
+) -> VM<ArmV7EM> {
+    let end = program.len() as u64;
+    let mut project = Box::new(Project::manual_project(
+        program,
+        0,
+        end,
+        WordSize::Bit32,
+        endianness,
+        symtab,
+        HashMap::new(),
+        Vec::new(),
+        Vec::new(),
+        HashMap::new(),
+        vec![],
+        HashMap::new(),
+        vec![],
+    ));
+    let arch = ArmV7EM::default();
+    project.add_hooks(&arch);
+    project.add_pc_hook(0x1000, PCHook::EndSuccess);
+    configure(&mut project);
+
+    let project = Box::leak(project);
+    let context = Box::new(DContext::new());
+    let context = Box::leak(context);
+    let solver = DSolver::new(context);
+    let mut state = GAState::create_test_state(project, context, solver, 0, 0x2000_0000, arch);
+    state
+        .set_register(GARegister::LR, context.from_u64(0x1000, 32))
+        .unwrap();
+    VM::new_with_state(project, state)
+}
+
+/// Two independent if statements, the path where neither branch is taken is
+/// the slowest.
+const TWO_IFS: [u16; 9] = [
+    0x2800, // CMP R0, #0
+    0xd001, // BEQ 0x8
+    0x3201, // ADDS R2, #1
+    0x3201, // ADDS R2, #1
+    0x2900, // CMP R1, #0
+    0xd001, // BEQ 0x10
+    0x3201, // ADDS R2, #1
+    0x3201, // ADDS R2, #1
+    0x4770, // BX LR
+];
+
+#[test]
+fn test_merge_paths() {
+    let mut vm = setup_test_vm_with_code(&TWO_IFS);
+    let mut cycles = vec![];
+    while let Some((result, state)) = vm.run().unwrap() {
+        assert!(matches!(result, PathResult::Success(_)));
+        cycles.push(state.cycle_count);
+    }
+    assert_eq!(cycles.len(), 4);
+    assert_eq!(cycles.iter().max(), Some(&11));
+
+    // The paths are merged after each if statement.
+    let mut vm = setup_test_vm_with_code(&TWO_IFS);
+    let mut finished = vm.run_merged().unwrap();
+    assert_eq!(finished.len(), 1);
+    let (result, state) = finished.pop().unwrap();
+    assert!(matches!(result, PathResult::Success(_)));
+
+    let solver = &state.constraints;
+    solver.push();
+    solver.assert(&state.path_condition);
+    let cycle_count = state.get_cycle_count_expr();
+    assert_eq!(solver.max_value(&cycle_count).unwrap(), 11);
+
+    // Only inputs that take neither branch reach the worst case.
+    solver.assert(&cycle_count._eq(&state.ctx.from_u64(11, 64)));
+    let zero = state.ctx.from_u64(0, 32);
+    for register in ["R0", "R1"] {
+        let input = state
+            .marked_symbolic
+            .iter()
+            .find(|variable| variable.name.as_deref() == Some(register))
+            .unwrap();
+        assert!(!solver.can_equal(&input.value, &zero).unwrap());
+    }
+    solver.pop();
+}
+
+/// Branches to one of two targets depending on the lowest bit of R0.
+const COMPUTED_BRANCH: [u16; 10] = [
+    0x07c0, // LSLS R0, R0, #31
+    0x0f00, // LSRS R0, R0, #28
+    0x3009, // ADDS R0, #9
+    0x4700, // BX R0
+    0x4770, // BX LR
+    0xbf00, // NOP
+    0xbf00, // NOP
+    0xbf00, // NOP
+    0x2101, // MOVS R1, #1
+    0x4770, // BX LR
+];
+
+#[test]
+fn test_symbolic_branch() {
+    let mut vm = setup_test_vm_with_code(&COMPUTED_BRANCH);
+    let mut targets = vec![];
+    while let Some((result, state)) = vm.run().unwrap() {
+        assert!(matches!(result, PathResult::Success(_)));
+        assert_eq!(state.concretized_branches.len(), 1);
+        let branch = &state.concretized_branches[0];
+        assert_eq!(branch.address, 0x6);
+        assert_eq!(branch.targets, 2);
+        assert!(!branch.function_entries);
+        targets.push(branch.target);
+    }
+    targets.sort();
+    assert_eq!(targets, [0x8, 0x10]);
+
+    // With too many targets only the function entry points are used.
+    let symtab = HashMap::from([("handler".to_owned(), 0x11)]);
+    let mut vm = setup_test_vm_with_symbols(&COMPUTED_BRANCH, symtab, |project| {
+        project.set_max_branch_targets(1)
+    });
+    let (result, mut state) = vm.run().unwrap().unwrap();
+    assert!(matches!(result, PathResult::Success(_)));
+    assert!(vm.run().unwrap().is_none());
+    assert_eq!(state.concretized_branches, [ConcretizedBranch {
+        address: 0x6,
+        target: 0x10,
+        targets: 1,
+        function_entries: true,
+    }]);
+    let r1 = state.get_register(REGISTERS.named("R1")).unwrap();
+    assert_eq!(r1.get_constant(), Some(1));
+}
+
+#[test]
+fn test_instruction_cache() {
+    let mut vm = setup_test_vm_with_code(&TWO_IFS);
+    let project = vm.project;
+    let mut state = vm.paths.get_path().unwrap().state;
+
+    // ADDS R2, #1 only sets the flags outside of a conditional block.
+    let outside = project.get_instruction(0x4, &state).unwrap();
+    state.add_instruction_conditions(&vec![general_assembly::condition::Condition::EQ]);
+    let inside = project.get_instruction(0x4, &state).unwrap();
+    assert!(inside.operations.len() < outside.operations.len());
+
+    let inside_cached = project.get_instruction(0x4, &state).unwrap();
+    assert_eq!(
+        format!("{:?}", inside_cached.operations),
+        format!("{:?}", inside.operations)
+    );
+}
+
+/// Reads a 32 bit length, a 16 bit port and a signed 16 bit offset in network
+/// byte order from the packet after the code and stores the length back in
+/// network byte order, as built for a little-endian target.
+const PACKET_LITTLE_ENDIAN: [u16; 14] = [
+    0xa006, // ADR R0, packet
+    0x6801, // LDR R1, [R0]
+    0xba09, // REV R1, R1
+    0x8882, // LDRH R2, [R0, #4]
+    0xba52, // REV16 R2, R2
+    0x88c3, // LDRH R3, [R0, #6]
+    0xbadb, // REVSH R3, R3
+    0xb082, // SUB SP, #8
+    0xba0c, // REV R4, R1
+    0x9400, // STR R4, [SP]
+    0xf89d, 0x5000, // LDRB.W R5, [SP]
+    0xb002, // ADD SP, #8
+    0x4770, // BX LR
+];
+
+/// The same function as [`PACKET_LITTLE_ENDIAN`] built for a big-endian
+/// target.
+const PACKET_BIG_ENDIAN: [u16; 12] = [
+    0xa005, // ADR R0, packet
+    0x6801, // LDR R1, [R0]
+    0x8882, // LDRH R2, [R0, #4]
+    0xf9b0, 0x3006, // LDRSH.W R3, [R0, #6]
+    0xb082, // SUB SP, #8
+    0x9100, // STR R1, [SP]
+    0xf89d, 0x5000, // LDRB.W R5, [SP]
+    0xb002, // ADD SP, #8
+    0x4770, // BX LR
+    0xbf00, // NOP
+];
+
+const PACKET: [u8; 8] = [0x12, 0x34, 0x56, 0x78, 0x00, 0x50, 0xff, 0xfe];
+
+/// Runs one of the packet functions and returns the registers of each path.
+fn run_packet(
+    code: &[u16],
+    endianness: Endianness,
+    instruction_endianness: Endianness,
+) -> Vec<[Option<u64>; 4]> {
+    let mut program: Vec<u8> = code
+        .iter()
+        .flat_map(|half_word| match instruction_endianness {
+            Endianness::Little => half_word.to_le_bytes(),
+            Endianness::Big => half_word.to_be_bytes(),
+        })
+        .collect();
+    program.extend(PACKET);
+    let mut vm = setup_test_vm_with_program(program, endianness, HashMap::new(), |project| {
+        project.set_instruction_endianness(instruction_endianness)
+    });
+    let mut paths = vec![];
+    while let Some((result, mut state)) = vm.run().unwrap() {
+        assert!(matches!(result, PathResult::Success(_)));
+        paths.push(["R1", "R2", "R3", "R5"].map(|register| {
+            state
+                .get_register(REGISTERS.named(register))
+                .unwrap()
+                .get_constant()
+        }));
+    }
+    paths
+}
+
+#[test]
+fn test_big_endian() {
+    let little = run_packet(
+        &PACKET_LITTLE_ENDIAN,
+        Endianness::Little,
+        Endianness::Little,
+    );
+    assert_eq!(little, [[
+        Some(0x1234_5678),
+        Some(0x50),
+        Some(0xffff_fffe),
+        Some(0x12)
+    ]]);
+
+    // BE8 stores the instructions in little-endian and BE32 in big-endian.
+    for instruction_endianness in [Endianness::Little, Endianness::Big] {
+        let big = run_packet(&PACKET_BIG_ENDIAN, Endianness::Big, instruction_endianness);
+        assert_eq!(big, little);
+    }
+
+    // The byte swaps are still needed in a big-endian build that reads little
+    // endian data.
+    let swapped = run_packet(&PACKET_LITTLE_ENDIAN, Endianness::Big, Endianness::Big);
+    assert_eq!(swapped, [[
+        Some(0x7856_3412),
+        Some(0x5000),
+        Some(0xffff_feff),
+        Some(0x12)
+    ]]);
+}
+
+/// `if *r0 > 5 { 2 } else { 1 }`.
+const LOAD_AND_COMPARE: [u16; 8] = [
+    0x6801, // LDR R1, [R0]
+    0x2905, // CMP R1, #5
+    0xdc01, // BGT 0xa
+    0x2001, // MOVS R0, #1
+    0x4770, // BX LR
+    0x2002, // MOVS R0, #2
+    0x4770, // BX LR
+    0xbf00, // NOP
+];
+
+fn run_snapshot(snapshot: &str) -> Vec<(Option<u64>, Option<u64>)> {
+    let program = LOAD_AND_COMPARE
+        .iter()
+        .flat_map(|i| i.to_le_bytes())
+        .collect();
+    let mut vm = setup_test_vm_with_program(program, Endianness::Little, HashMap::new(), |_| ());
+    let snapshot = Snapshot::from_toml(snapshot).unwrap();
+    vm = VM::from_snapshot(
+        vm.project,
+        Box::leak(Box::new(DContext::new())),
+        &snapshot,
+        0x1000,
+        ArmV7EM::default(),
+    )
+    .unwrap();
+
+    let mut paths = vec![];
+    while let Some((result, mut state)) = vm.run().unwrap() {
+        assert!(matches!(result, PathResult::Success(_)));
+        let symbolic = state.marked_symbolic.first().map(|variable| {
+            let value = state.constraints.get_value(&variable.value).unwrap();
+            value.get_constant().unwrap()
+        });
+        paths.push((
+            state
+                .get_register(REGISTERS.named("R0"))
+                .unwrap()
+                .get_constant(),
+            symbolic,
+        ));
+    }
+    paths
+}
+
+#[test]
+fn test_snapshot() {
+    // The analysis resumes at the PC with the dumped memory and ends when the
+    // function returns even though the snapshot has another LR.
+    let snapshot = r#"
+        [registers]
+        PC = 0x0
+        SP = 0x2000_1000
+        LR = 0x1235
+        R0 = 0x2000_0000
+
+        [[memory]]
+        address = 0x2000_0000
+        data = "07 00 00 00"
+    "#;
+    assert_eq!(run_snapshot(snapshot), [(Some(2), None)]);
+
+    // Symbolic memory explores both branches.
+    let snapshot = format!("{snapshot}\n[[symbolic]]\naddress = 0x2000_0000\nsize = 4");
+    let paths = run_snapshot(&snapshot);
+    assert_eq!(paths.len(), 2);
+    for (result, value) in paths {
+        assert_eq!(result, Some(if value.unwrap() as i32 > 5 { 2 } else { 1 }));
+    }
+
+    // Names that are not registers of the architecture are rejected.
+    let program = LOAD_AND_COMPARE
+        .iter()
+        .flat_map(|i| i.to_le_bytes())
+        .collect();
+    let vm = setup_test_vm_with_program(program, Endianness::Little, HashMap::new(), |_| ());
+    for snapshot in [
+        "[registers]\nPC = 0x0\nSP = 0x2000_1000\nX0 = 0x0",
+        "[registers]\nPC = 0x0\nSP = 0x2000_1000\n[flags]\nR0 = 0x0",
+    ] {
+        let snapshot = Snapshot::from_toml(snapshot).unwrap();
+        let context = Box::leak(Box::new(DContext::new()));
+        let result = VM::from_snapshot(vm.project, context, &snapshot, 0x1000, ArmV7EM::default());
+        assert!(matches!(
+            result,
+            Err(GAError::ProjectError(ProjectError::UnableToParseSnapshot(
+                _
+            )))
+        ));
+    }
+}
+
+#[test]
+fn test_replay() {
+    let program = LOAD_AND_COMPARE
+        .iter()
+        .flat_map(|i| i.to_le_bytes())
+        .collect();
+    let vm = setup_test_vm_with_program(program, Endianness::Little, HashMap::new(), |_| ());
+    let snapshot = Snapshot::from_toml(
+        r#"
+        [registers]
+        PC = 0x0
+        SP = 0x2000_1000
+        R0 = 0x2000_0000
+
+        [[symbolic]]
+        address = 0x2000_0000
+        size = 4
+        "#,
+    )
+    .unwrap();
+    let mut vm = VM::from_snapshot(
+        vm.project,
+        Box::leak(Box::new(DContext::new())),
+        &snapshot,
+        0x1000,
+        ArmV7EM::default(),
+    )
+    .unwrap();
+
+    let mut traces = 0;
+    while let Some((result, state)) = vm.run().unwrap() {
+        assert!(matches!(result, PathResult::Success(_)));
+        let trace = replay(&vm.initial_state, &state).unwrap();
+        assert_eq!(trace.result, "success");
+
+        // The loaded value decides which branch the replay takes.
+        let loaded = &trace.steps[0];
+        assert_eq!(loaded.pc, 0);
+        let (_, value) = loaded
+            .registers
+            .iter()
+            .find(|(register, _)| register == "R1")
+            .unwrap();
+        let (pcs, r0) = if *value as i32 > 5 {
+            (vec![0, 2, 4, 0xa, 0xc], 2)
+        } else {
+            (vec![0, 2, 4, 6, 8], 1)
+        };
+        assert_eq!(
+            trace.steps.iter().map(|step| step.pc).collect::<Vec<_>>(),
+            pcs
+        );
+        assert!(trace.steps[3].registers.contains(&("R0".to_owned(), r0)));
+        assert_eq!(trace.steps.last().unwrap().total_cycles, state.cycle_count);
+
+        let mut text = vec![];
+        trace.write(&mut text, 1, TraceFormat::JsonLines).unwrap();
+        let text = String::from_utf8(text).unwrap();
+        assert_eq!(text.lines().count(), pcs.len() + 1);
+        assert!(text
+            .lines()
+            .last()
+            .unwrap()
+            .contains("\"result\":\"success\""));
+        traces += 1;
+    }
+    assert_eq!(traces, 2);
+}
@@ -1,11 +1,124 @@
+//! Cycle model for the Cortex-M4.
+//!
+//! The cycle counts follow the
+//! [Cortex-M4 instruction timings](https://developer.arm.com/documentation/ddi0439/b/CHDDIGAC).
+//! Where the documentation gives a range the model uses the previous
+//! instruction and the branch target to select the largest value that can
+//! occur, so the count is always an upper bound.
+
 use disarmv7::prelude::{Condition, Operation as V7Operation, Register};
+use general_assembly::{operand::DataHalfWord, register::Register as GARegister};
 
-use super::ArmV7EM;
 // use general_assembly::operation::Operation;
-use crate::general_assembly::{instruction::CycleCount, state::GAState};
+use crate::general_assembly::{
+    arch::Arch,
+    instruction::{CycleCount, MemoryAccess},
+    state::GAState,
+};
+
+/// The largest number of cycles needed to refill the pipeline.
+const MAX_REFILL: usize = 3;
+
+/// Number of cycles needed to refill the pipeline after a taken branch, `P` in
+/// the documentation.
+///
+/// The target is fetched in a single cycle if it is word aligned or a 16 bit
+/// instruction, otherwise a second fetch is needed. Branches with an immediate
+/// offset have their target forwarded to the fetch unit from the decode stage,
+/// other branches have to wait for the execute stage which adds one cycle.
+fn refill<A: Arch>(state: &GAState<A>, forwarded: bool) -> usize {
+    let target = state
+        .registers
+        .get(GARegister::PC)
+        .and_then(|pc| pc.get_constant());
+    let fetches = match target {
+        Some(target) if single_fetch(state, target) => 1,
+        _ => 2,
+    };
+    match forwarded {
+        true => fetches,
+        false => fetches + 1,
+    }
+}
+
+/// Checks if the instruction at `address` can be fetched in a single access.
+fn single_fetch<A: Arch>(state: &GAState<A>, address: u64) -> bool {
+    if address % 4 == 0 {
+        return true;
+    }
+    // The first half word of all 32 bit instructions start with 0b11101,
+    // 0b11110 or 0b11111.
+    match state.project.get_half_word(address) {
+        Ok(DataHalfWord::HalfWord32(half_word)) => half_word >> 11 < 0b11101,
+        _ => false,
+    }
+}
+
+/// A taken branch with an immediate offset.
+fn branch_forwarded<A: Arch>(state: &mut GAState<A>) -> usize {
+    1 + refill(state, true)
+}
+
+/// A branch to a register or computed value.
+pub(crate) fn branch_register<A: Arch>(state: &mut GAState<A>) -> usize {
+    1 + refill(state, false)
+}
+
+/// A conditional branch with an immediate offset, a single cycle if not taken.
+fn branch_conditional<A: Arch>(state: &mut GAState<A>) -> usize {
+    match state.get_has_jumped() {
+        true => branch_forwarded(state),
+        false => 1,
+    }
+}
+
+/// A single load or store.
+///
+/// Neighboring loads and stores pipeline their address and data phases so a
+/// load or store directly after a single load completes in one cycle, unless
+/// its address is computed from the loaded value. Nothing is pipelined after
+/// a store, a load or store multiple or when the instruction is a branch
+/// target.
+fn load_store<A: Arch>(state: &mut GAState<A>) -> usize {
+    let pipelined = match (&state.last_instruction, &state.current_instruction) {
+        (Some(last), Some(current)) => {
+            last.memory_access == MemoryAccess::Load
+                && state.get_sequential_fetch()
+                && !current.address_depends_on(last)
+        }
+        _ => false,
+    };
+    match pipelined {
+        true => 1,
+        false => 2,
+    }
+}
+
+/// A load to the PC.
+fn load_pc<A: Arch>(state: &mut GAState<A>) -> usize {
+    2 + refill(state, false)
+}
+
+/// A load of multiple registers, `1 + N` or `1 + N + P` if the PC is loaded.
+///
+/// The PC is counted as one of the `N` registers. The loaded branch target is
+/// not known when the instruction is decoded so the largest refill is used.
+fn load_multiple<A: Arch>(registers: &[Register]) -> CycleCount<A> {
+    let n = registers.len();
+    match registers.contains(&Register::PC) {
+        true => CycleCount::Value(1 + n + MAX_REFILL),
+        false => CycleCount::Value(1 + n),
+    }
+}
+
+/// A load relative to the PC.
+///
+/// These are never pipelined and might add a cycle because of contention with
+/// the fetch unit.
+const LOAD_LITERAL: usize = 2 + 1;
 
 impl super::ArmV7EM {
-    pub fn memory_access(instr: &V7Operation) -> bool {
+    pub fn memory_access(instr: &V7Operation) -> MemoryAccess {
         use V7Operation::*;
         match instr {
             AdcImmediate(_) | AdcRegister(_) | AddImmediate(_) | AddRegister(_)
@@ -13,23 +126,30 @@ impl super::ArmV7EM {
             | AsrImmediate(_) | AsrRegister(_) | B(_) | Bfc(_) | Bfi(_) | BicImmediate(_)
             | BicRegister(_) | Bkpt(_) | Bl(_) | Blx(_) | Bx(_) | Cbz(_) | Clrex(_) | Clz(_)
             | CmnImmediate(_) | CmnRegister(_) | CmpImmediate(_) | CmpRegister(_) | Cps(_)
-            | Dbg(_) | Dmb(_) | Dsb(_) | EorImmediate(_) | EorRegister(_) | Isb(_) | It(_) => false,
-            Ldm(_) | Ldmdb(_) | LdrImmediate(_) | LdrLiteral(_) | LdrRegister(_)
-            | LdrbImmediate(_) | LdrbLiteral(_) | LdrbRegister(_) | Ldrbt(_) | LdrdImmediate(_)
-            | LdrdLiteral(_) | Ldrex(_) | Ldrexb(_) | Ldrexh(_) | LdrhImmediate(_)
-            | LdrhLiteral(_) | LdrhRegister(_) | Ldrht(_) | LdrsbImmediate(_) | LdrsbLiteral(_)
+            | Dbg(_) | Dmb(_) | Dsb(_) | EorImmediate(_) | EorRegister(_) | Isb(_) | It(_) => {
+                MemoryAccess::None
+            }
+            LdrImmediate(_) | LdrLiteral(_) | LdrRegister(_) | LdrbImmediate(_)
+            | LdrbLiteral(_) | LdrbRegister(_) | Ldrbt(_) | LdrhImmediate(_) | LdrhLiteral(_)
+            | LdrhRegister(_) | Ldrht(_) | LdrsbImmediate(_) | LdrsbLiteral(_)
             | LdrsbRegister(_) | Ldrsbt(_) | LdrshImmediate(_) | LdrshLiteral(_)
-            | LdrshRegister(_) | Ldrsht(_) | Ldrt(_) => true,
+            | LdrshRegister(_) | Ldrsht(_) | Ldrt(_) => MemoryAccess::Load,
+            Ldm(_) | Ldmdb(_) | LdrdImmediate(_) | LdrdLiteral(_) | Ldrex(_) | Ldrexb(_)
+            | Ldrexh(_) => MemoryAccess::Other,
             /* LdcImmediate(_) | LdcLiteral(_) | */
-            LslImmediate(_) | LslRegister(_) | LsrImmediate(_) | LsrRegister(_) => false,
+            LslImmediate(_) | LslRegister(_) | LsrImmediate(_) | LsrRegister(_) => {
+                MemoryAccess::None
+            }
             Mcrr(_) | Mla(_) | Mls(_) | MovImmediate(_) | MovRegister(_) | Movt(_) | Mrrc(_)
             | Mrs(_) | Msr(_) | Mul(_) | MvnImmediate(_) | MvnRegister(_) | Nop(_)
-            | OrnImmediate(_) | OrnRegister(_) | OrrImmediate(_) | OrrRegister(_) | Pkh(_) => false,
+            | OrnImmediate(_) | OrnRegister(_) | OrrImmediate(_) | OrrRegister(_) | Pkh(_) => {
+                MemoryAccess::None
+            }
             PldImmediate(_) | PldLiteral(_) | PldRegister(_) | PliImmediate(_) | PliRegister(_) => {
-                true
+                MemoryAccess::Other
             }
-            Pop(_) => true,
-            Push(_) => true,
+            Pop(_) => MemoryAccess::Other,
+            Push(_) => MemoryAccess::Other,
             Qadd(_) | Qadd16(_) | Qadd8(_) | Qasx(_) | Qdadd(_) | Qdsub(_) | Qsax(_) | Qsub(_)
             | Qsub16(_) | Qsub8(_) | Rbit(_) | Rev(_) | Rev16(_) | Revsh(_) | RorImmediate(_)
             | RorRegister(_) | Rrx(_) | RsbImmediate(_) | RsbRegister(_) | Sadd16(_) | Sadd8(_)
@@ -37,10 +157,14 @@ impl super::ArmV7EM {
             | Shadd16(_) | Shadd8(_) | Shasx(_) | Shsax(_) | Shsub16(_) | Shsub8(_) | Smla(_)
             | Smlad(_) | Smlal(_) | SmlalSelective(_) | Smlald(_) | Smlaw(_) | Smlsd(_)
             | Smlsld(_) | Smmla(_) | Smmls(_) | Smmul(_) | Smuad(_) | Smul(_) | Smull(_)
-            | Smulw(_) | Smusd(_) | Ssat(_) | Ssat16(_) | Ssax(_) | Ssub16(_) | Ssub8(_) => false,
-            Stm(_) | Stmdb(_) | StrImmediate(_) | StrRegister(_) | StrbImmediate(_)
-            | StrbRegister(_) | Strbt(_) | StrdImmediate(_) | Strex(_) | Strexb(_) | Strexh(_)
-            | StrhImmediate(_) | StrhRegister(_) | Strht(_) | Strt(_) => true,
+            | Smulw(_) | Smusd(_) | Ssat(_) | Ssat16(_) | Ssax(_) | Ssub16(_) | Ssub8(_) => {
+                MemoryAccess::None
+            }
+            StrImmediate(_) | StrRegister(_) | StrbImmediate(_) | StrbRegister(_) | Strbt(_)
+            | StrhImmediate(_) | StrhRegister(_) | Strht(_) | Strt(_) => MemoryAccess::Store,
+            Stm(_) | Stmdb(_) | StrdImmediate(_) | Strex(_) | Strexb(_) | Strexh(_) => {
+                MemoryAccess::Other
+            }
             Stc(_)
             | SubImmediate(_)
             | SubRegister(_)
@@ -100,83 +224,42 @@ impl super::ArmV7EM {
             | Mrc(_)
             | Cdp(_)
             | LdcImmediate(_)
-            | LdcLiteral(_) => false,
+            | LdcLiteral(_) => MemoryAccess::None,
         }
     }
 
-    pub fn cycle_count_m4_core(instr: &V7Operation) -> CycleCount<Self> {
-        let p = 3;
-        let pipeline = |state: &GAState<ArmV7EM>| match state.get_last_instruction() {
-            Some(instr) => match instr.memory_access {
-                true => 1,
-                false => 2,
-            },
-            _ => 2,
-        };
-        let if_pc = |reg: Register, value: usize| {
+    /// Cycle count of `instr` on a Cortex-M4.
+    ///
+    /// This is generic over the architecture so that the Armv8-M cores with the
+    /// same pipeline can use it.
+    pub fn cycle_count_m4_core<A: Arch>(instr: &V7Operation) -> CycleCount<A> {
+        let if_pc = |reg: Register| {
             if reg == Register::PC {
-                return CycleCount::Value(value + p);
+                return CycleCount::Function(branch_register);
             }
-            CycleCount::Value(value)
+            CycleCount::Value(1)
         };
         match instr {
             V7Operation::AdcImmediate(_) | V7Operation::AdcRegister(_) => CycleCount::Value(1),
-            V7Operation::AddImmediate(add) => if_pc(add.rd.unwrap_or(add.rn), 1),
-            V7Operation::AddRegister(add) => if_pc(add.rd.unwrap_or(add.rn), 1),
-            V7Operation::AddSPImmediate(add) => if_pc(add.rd.unwrap_or(Register::SP), 1),
-            V7Operation::AddSPRegister(add) => if_pc(add.rd.unwrap_or(Register::SP), 1),
+            V7Operation::AddImmediate(add) => if_pc(add.rd.unwrap_or(add.rn)),
+            V7Operation::AddRegister(add) => if_pc(add.rd.unwrap_or(add.rn)),
+            V7Operation::AddSPImmediate(add) => if_pc(add.rd.unwrap_or(Register::SP)),
+            V7Operation::AddSPRegister(add) => if_pc(add.rd.unwrap_or(Register::SP)),
             V7Operation::Adr(_) => CycleCount::Value(1),
             V7Operation::AndImmediate(_) | V7Operation::AndRegister(_) => CycleCount::Value(1),
             V7Operation::AsrImmediate(_) | V7Operation::AsrRegister(_) => CycleCount::Value(1),
-            V7Operation::B(b) => {
-                if b.condition != Condition::None {
-                    let counter = |state: &GAState<ArmV7EM>| {
-                        // match (state.get_next_instruction(), state.get_has_jumped()) {
-                        //     (
-                        //         Ok(crate::general_assembly::state::HookOrInstruction::Instruction(
-                        //             instr,
-                        //         )),
-                        //         true,
-                        //     ) => {
-                        //         let ops = instr.operations.len();
-                        //         match (ops, instr.operations.get(0)) {
-                        //             (1, Some(Operation::Nop)) => {
-                        //                 return 2;
-                        //             }
-                        //             _ => {}
-                        //         }
-                        //     }
-                        //
-                        //     _ => {}
-                        // }
-                        //
-                        match state.get_has_jumped() {
-                            true => 1 + 3,
-                            false => 1,
-                        }
-                    };
-                    CycleCount::Function(counter)
-                } else {
-                    // CycleCount::Value(1 + 3)
-
-                    // This is a gross over estimation, it should be more like 1+1
-                    CycleCount::Value(1 + 3)
-                }
-            }
+            V7Operation::B(b) => match b.condition {
+                Condition::None => CycleCount::Function(branch_forwarded),
+                _ => CycleCount::Function(branch_conditional),
+            },
             V7Operation::Bfc(_) => CycleCount::Value(1),
             V7Operation::Bfi(_) => CycleCount::Value(1),
             V7Operation::BicImmediate(_) | V7Operation::BicRegister(_) => CycleCount::Value(1),
             V7Operation::Bkpt(_) => CycleCount::Value(0),
-            V7Operation::Bl(_) => CycleCount::Value(1 + 3),
-            V7Operation::Blx(_) => CycleCount::Value(1 + 3),
-            V7Operation::Bx(_) => CycleCount::Value(1 + 3),
-            V7Operation::Cbz(_) => {
-                let counter = |state: &GAState<ArmV7EM>| match state.get_has_jumped() {
-                    true => 1 + 3,
-                    false => 1,
-                };
-                CycleCount::Function(counter)
-            }
+            V7Operation::Bl(_) => CycleCount::Function(branch_forwarded),
+            V7Operation::Blx(_) => CycleCount::Function(branch_register),
+            V7Operation::Bx(_) => CycleCount::Function(branch_register),
+            V7Operation::Cbz(_) => CycleCount::Function(branch_conditional),
             V7Operation::Clrex(_) => CycleCount::Value(1),
             V7Operation::Clz(_) => CycleCount::Value(1),
             V7Operation::CmnImmediate(_) | V7Operation::CmnRegister(_) => CycleCount::Value(1),
@@ -188,88 +271,60 @@ impl super::ArmV7EM {
             V7Operation::Dsb(_) => todo!("This requires a model of barriers"),
             V7Operation::EorImmediate(_) | V7Operation::EorRegister(_) => CycleCount::Value(1),
             V7Operation::Isb(_) => todo!("This requires a model of barriers"),
-            // TODO! Add detection for wether this is folded or not, if it is the value here is 0
+            // The IT instruction is folded on to a preceding 16 bit instruction
             V7Operation::It(_) => {
-                let counter = |state: &GAState<ArmV7EM>| match state.get_last_instruction() {
-                    Some(instr) => match instr.instruction_size {
-                        16 => 0,
-                        _ => 1,
-                    },
-                    None => 1,
+                let counter = |state: &mut GAState<A>| match &state.last_instruction {
+                    Some(last) if last.instruction_size == 16 && state.get_sequential_fetch() => 0,
+                    _ => 1,
                 };
 
                 CycleCount::Function(counter)
             }
-            V7Operation::Ldm(ldm) => {
-                let pc = ldm.registers.registers.contains(&Register::PC);
-                let n = ldm.registers.registers.len();
-                let mut count = 1 + n;
-                if pc {
-                    // TODO! Model pipeline better
-                    count += 3;
-                }
-                CycleCount::Value(count)
-            }
-            V7Operation::Ldmdb(ldm) => {
-                let pc = ldm.registers.registers.contains(&Register::PC);
-                let n = ldm.registers.registers.len();
-                let mut count = 1 + n;
-                if pc {
-                    // TODO! Model pipeline better
-                    count += 3;
-                }
-                CycleCount::Value(count)
-            }
+            V7Operation::Ldm(ldm) => load_multiple(&ldm.registers.registers),
+            V7Operation::Ldmdb(ldm) => load_multiple(&ldm.registers.registers),
             // TODO! Add in pre load hints
             V7Operation::LdrImmediate(el) => match (el.rt, el.rn) {
-                (_, Register::PC) => CycleCount::Value(2),
-                (Register::PC, _) => CycleCount::Value(2 + 3),
-                _ => CycleCount::Function(pipeline),
+                (Register::PC, _) => CycleCount::Function(load_pc),
+                (_, Register::PC) => CycleCount::Value(LOAD_LITERAL),
+                _ => CycleCount::Function(load_store),
             },
             V7Operation::LdrLiteral(el) => match el.rt {
-                Register::PC => CycleCount::Value(2 + 3),
-                _ => CycleCount::Function(pipeline),
+                Register::PC => CycleCount::Function(load_pc),
+                _ => CycleCount::Value(LOAD_LITERAL),
             },
             V7Operation::LdrRegister(el) => match (el.rt, el.rn) {
-                (Register::PC, Register::PC) => CycleCount::Value(2),
-                (Register::PC, _) => CycleCount::Value(2 + 3),
-                _ => CycleCount::Function(pipeline),
+                (Register::PC, _) => CycleCount::Function(load_pc),
+                (_, Register::PC) => CycleCount::Value(LOAD_LITERAL),
+                _ => CycleCount::Function(load_store),
             },
+            V7Operation::LdrbLiteral(_)
+            | V7Operation::LdrhLiteral(_)
+            | V7Operation::LdrsbLiteral(_)
+            | V7Operation::LdrshLiteral(_) => CycleCount::Value(LOAD_LITERAL),
             V7Operation::LdrbImmediate(_)
-            | V7Operation::LdrbLiteral(_)
-            | V7Operation::LdrbRegister(_) => CycleCount::Value(2),
-            V7Operation::Ldrbt(_) => CycleCount::Value(2),
+            | V7Operation::LdrbRegister(_)
+            | V7Operation::Ldrbt(_) => CycleCount::Function(load_store),
             V7Operation::LdrdImmediate(_ldrd) => CycleCount::Value(1 + 2),
             V7Operation::LdrdLiteral(_) => CycleCount::Value(1 + 2),
             // TODO! This requires a model of semaphores
             V7Operation::Ldrex(_) | V7Operation::Ldrexb(_) | V7Operation::Ldrexh(_) => {
                 CycleCount::Value(2)
             }
-            // TODO! Add in model of contigous loads to allow next load to be single cycle
             V7Operation::LdrhImmediate(_)
-            | V7Operation::LdrhLiteral(_)
             | V7Operation::LdrhRegister(_)
             | V7Operation::Ldrht(_)
             | V7Operation::LdrsbImmediate(_)
-            | V7Operation::LdrsbLiteral(_)
             | V7Operation::LdrsbRegister(_)
             | V7Operation::Ldrsbt(_)
             | V7Operation::LdrshImmediate(_)
-            | V7Operation::LdrshLiteral(_)
             | V7Operation::LdrshRegister(_)
             | V7Operation::Ldrsht(_)
-            | V7Operation::Ldrt(_) => CycleCount::Function(pipeline),
+            | V7Operation::Ldrt(_) => CycleCount::Function(load_store),
             V7Operation::LslImmediate(_) | V7Operation::LslRegister(_) => CycleCount::Value(1),
             V7Operation::LsrImmediate(_) | V7Operation::LsrRegister(_) => CycleCount::Value(1),
             V7Operation::Mla(_) | V7Operation::Mls(_) => CycleCount::Value(2),
-            V7Operation::MovImmediate(mov) => match mov.rd {
-                Register::PC => CycleCount::Value(1 + p),
-                _ => CycleCount::Value(1),
-            },
-            V7Operation::MovRegister(mov) => match mov.rd {
-                Register::PC => CycleCount::Value(1 + 3),
-                _ => CycleCount::Value(1),
-            },
+            V7Operation::MovImmediate(mov) => if_pc(mov.rd),
+            V7Operation::MovRegister(mov) => if_pc(mov.rd),
             V7Operation::Movt(_) => CycleCount::Value(1),
             V7Operation::Mrs(_) => CycleCount::Value(2),
             V7Operation::Msr(_) => CycleCount::Value(2),
@@ -284,19 +339,7 @@ impl super::ArmV7EM {
             V7Operation::PldRegister(_) => todo!("Add in preload hints"),
             V7Operation::PliImmediate(_) => todo!("Add in preload hints"),
             V7Operation::PliRegister(_) => todo!("Add in preload hints"),
-            V7Operation::Pop(pop) => {
-                // TODO! Validate this, it might be incorrect
-                // The documentation gives us
-                // POP {<reglist>, PC} => 1 + N + P
-                // This seems to count N as the number or registers in <reglist>
-                // if we assume this to be the case we can subtract one from P
-                // as that is the same as subtracting one from N
-                let ret = match pop.registers.registers.contains(&Register::PC) {
-                    true => p - 1,
-                    _ => 0,
-                };
-                CycleCount::Value(1 + pop.registers.registers.len() + ret)
-            }
+            V7Operation::Pop(pop) => load_multiple(&pop.registers.registers),
             V7Operation::Push(push) => CycleCount::Value(1 + push.registers.registers.len()),
             V7Operation::Qadd(_) => CycleCount::Value(1),
             V7Operation::Qadd16(_) => CycleCount::Value(1),
@@ -353,12 +396,12 @@ impl super::ArmV7EM {
             V7Operation::Stm(stm) => CycleCount::Value(1 + stm.registers.registers.len()),
             V7Operation::Stmdb(stm) => CycleCount::Value(1 + stm.registers.registers.len()),
             V7Operation::StrImmediate(_) | V7Operation::StrRegister(_) => {
-                CycleCount::Function(pipeline)
+                CycleCount::Function(load_store)
             }
             V7Operation::StrbImmediate(_) | V7Operation::StrbRegister(_) => {
-                CycleCount::Function(pipeline)
+                CycleCount::Function(load_store)
             }
-            V7Operation::Strbt(_) => CycleCount::Value(2),
+            V7Operation::Strbt(_) => CycleCount::Function(load_store),
             // N is two here
             V7Operation::StrdImmediate(_strd) => CycleCount::Value(1 + 2),
             V7Operation::Strex(_) => CycleCount::Value(2),
@@ -367,7 +410,7 @@ impl super::ArmV7EM {
             V7Operation::StrhImmediate(_)
             | V7Operation::StrhRegister(_)
             | V7Operation::Strht(_)
-            | V7Operation::Strt(_) => CycleCount::Function(pipeline),
+            | V7Operation::Strt(_) => CycleCount::Function(load_store),
             V7Operation::SubImmediate(_) | V7Operation::SubRegister(_) => CycleCount::Value(1),
             V7Operation::SubSpMinusImmediate(_) => CycleCount::Value(1),
             V7Operation::SubSpMinusRegister(_) => CycleCount::Value(1),
@@ -379,7 +422,7 @@ impl super::ArmV7EM {
             V7Operation::Sxtb(_) => CycleCount::Value(1),
             V7Operation::Sxtb16(_) => CycleCount::Value(1),
             V7Operation::Sxth(_) => CycleCount::Value(1),
-            V7Operation::Tb(_) => CycleCount::Value(2 + p),
+            V7Operation::Tb(_) => CycleCount::Function(|state| 2 + refill(state, false)),
             // TODO!  The docs do not mention any cycle count for this
             // might be incorret
             V7Operation::TeqImmediate(_) | V7Operation::TeqRegister(_) => CycleCount::Value(1),
@@ -0,0 +1,285 @@
+//! Cycle model for the Cortex-M7.
+//!
+//! The Cortex-M7 has a six stage pipeline that can issue two instructions per
+//! cycle and a branch target address cache (BTAC) that predicts taken
+//! branches. The timings are not documented to the same extent as for the
+//! Cortex-M4, so the model is conservative:
+//!
+//! - An instruction is only issued together with the previous one if one of
+//!   them is a simple data processing instruction, the other is a data
+//!   processing instruction or a single load or store and they do not share any
+//!   registers or flags.
+//! - A use of a loaded register directly after the load stalls the pipeline.
+//! - The BTAC is assumed to be empty when the analysis starts and only branches
+//!   with an immediate offset are assumed to be allocated in it. A taken branch
+//!   is only counted as predicted if it was the last taken branch, a branch
+//!   that has not been taken before is predicted as not taken. All other
+//!   branches are counted as mispredicted.
+//! - Instructions that are not covered by the above use the Cortex-M4 cycle
+//!   counts.
+//!
+//! Caches and tightly coupled memories are described by the target
+//! configuration.
+
+use std::collections::HashSet;
+
+use disarmv7::prelude::{Operation as V7Operation, Register};
+use general_assembly::{operation::Operation, register::Register as GARegister};
+
+use super::ArmV7EM;
+use crate::general_assembly::{
+    instruction::{CycleCount, Instruction, MemoryAccess},
+    state::GAState,
+};
+
+/// Cycles lost when a branch is mispredicted and the pipeline has to be
+/// refilled.
+const MISPREDICT_PENALTY: usize = 8;
+
+/// Cycles stalled when an instruction uses the result of the load before it.
+const LOAD_USE_STALL: usize = 2;
+
+/// Per path state of the Cortex-M7 pipeline model.
+#[derive(Debug, Default, Clone)]
+pub struct M7Pipeline {
+    /// The address and target of the last taken branch.
+    last_taken: Option<(u64, u64)>,
+
+    /// Addresses of all branches that have been taken.
+    taken: HashSet<u64>,
+
+    /// Whether the last instruction was issued together with the instruction
+    /// before it.
+    dual_issued: bool,
+}
+
+impl M7Pipeline {
+    /// Combines the pipeline state of two merged paths.
+    ///
+    /// A branch might be predicted as taken if it was taken on either path and
+    /// is only known to be the last taken branch if it was on both.
+    pub fn merge(&mut self, other: &Self) {
+        if self.last_taken != other.last_taken {
+            self.last_taken = None;
+        }
+        self.taken.extend(&other.taken);
+        self.dual_issued = false;
+    }
+}
+
+/// Checks if the instruction only uses the integer pipelines.
+fn is_alu(instruction: &Instruction<ArmV7EM>) -> bool {
+    instruction.memory_access == MemoryAccess::None
+        && instruction.operations.iter().all(|operation| {
+            matches!(
+                operation,
+                Operation::Nop
+                    | Operation::Move { .. }
+                    | Operation::Add { .. }
+                    | Operation::Adc { .. }
+                    | Operation::Sub { .. }
+                    | Operation::And { .. }
+                    | Operation::Or { .. }
+                    | Operation::Xor { .. }
+                    | Operation::Not { .. }
+                    | Operation::Shift { .. }
+                    | Operation::Sl { .. }
+                    | Operation::Srl { .. }
+                    | Operation::Sra { .. }
+                    | Operation::Sror { .. }
+                    | Operation::ZeroExtend { .. }
+                    | Operation::SignExtend { .. }
+                    | Operation::Resize { .. }
+                    | Operation::SetNFlag(_)
+                    | Operation::SetZFlag(_)
+                    | Operation::SetCFlag { .. }
+                    | Operation::SetCFlagShiftLeft { .. }
+                    | Operation::SetCFlagSrl { .. }
+                    | Operation::SetCFlagSra { .. }
+                    | Operation::SetCFlagRor(_)
+                    | Operation::SetVFlag { .. }
+            )
+        })
+        && !instruction.writes_register(GARegister::PC)
+}
+
+/// Number of cycles the current instruction waits for a load before it.
+fn load_use(state: &GAState<ArmV7EM>) -> usize {
+    match (&state.last_instruction, &state.current_instruction) {
+        (Some(last), Some(current))
+            if last.memory_access == MemoryAccess::Load && current.depends_on(last) =>
+        {
+            LOAD_USE_STALL
+        }
+        _ => 0,
+    }
+}
+
+/// Checks if the current instruction is issued together with the last one.
+fn dual_issue(state: &GAState<ArmV7EM>, current_is_alu: bool) -> bool {
+    if !state.get_sequential_fetch() || state.architecture.m7_pipeline.dual_issued {
+        return false;
+    }
+    let (Some(last), Some(current)) = (&state.last_instruction, &state.current_instruction) else {
+        return false;
+    };
+    let last_is_load_store = matches!(last.memory_access, MemoryAccess::Load | MemoryAccess::Store);
+    let pairs = match current_is_alu {
+        true => is_alu(last) || last_is_load_store,
+        false => is_alu(last),
+    };
+    pairs && !current.depends_on(last)
+}
+
+fn issue(state: &mut GAState<ArmV7EM>, current_is_alu: bool) -> usize {
+    let stall = load_use(state);
+    let dual = stall == 0 && dual_issue(state, current_is_alu);
+    state.architecture.m7_pipeline.dual_issued = dual;
+    match dual {
+        true => 0,
+        false => 1 + stall,
+    }
+}
+
+/// A data processing instruction.
+fn alu(state: &mut GAState<ArmV7EM>) -> usize {
+    issue(state, true)
+}
+
+/// A single load or store.
+fn load_store(state: &mut GAState<ArmV7EM>) -> usize {
+    issue(state, false)
+}
+
+/// A branch with an immediate offset, predicted by the BTAC.
+fn branch_immediate(state: &mut GAState<ArmV7EM>) -> usize {
+    let address = state.last_pc;
+    let target = state
+        .registers
+        .get(GARegister::PC)
+        .and_then(|pc| pc.get_constant());
+    let taken = state.get_has_jumped();
+    let pipeline = &mut state.architecture.m7_pipeline;
+    pipeline.dual_issued = false;
+
+    let predicted = match taken {
+        true => target.is_some() && pipeline.last_taken == target.map(|target| (address, target)),
+        false => !pipeline.taken.contains(&address),
+    };
+    if taken {
+        pipeline.last_taken = target.map(|target| (address, target));
+        pipeline.taken.insert(address);
+    }
+
+    match predicted {
+        true => 1,
+        false => 1 + MISPREDICT_PENALTY,
+    }
+}
+
+/// A branch to a register or loaded value, never predicted.
+fn branch_register(state: &mut GAState<ArmV7EM>) -> usize {
+    state.architecture.m7_pipeline.dual_issued = false;
+    1 + MISPREDICT_PENALTY
+}
+
+impl ArmV7EM {
+    pub fn cycle_count_m7_core(instr: &V7Operation) -> CycleCount<Self> {
+        let if_pc = |reg: Register| {
+            if reg == Register::PC {
+                return CycleCount::Function(branch_register);
+            }
+            CycleCount::Function(alu)
+        };
+        let load_multiple = |registers: &Vec<Register>| {
+            if registers.contains(&Register::PC) {
+                return CycleCount::Value(1 + registers.len() + MISPREDICT_PENALTY);
+            }
+            CycleCount::Value(1 + registers.len())
+        };
+        match instr {
+            V7Operation::B(_) | V7Operation::Bl(_) | V7Operation::Cbz(_) => {
+                CycleCount::Function(branch_immediate)
+            }
+            V7Operation::Bx(_) | V7Operation::Blx(_) => CycleCount::Function(branch_register),
+            V7Operation::Tb(_) => CycleCount::Value(2 + MISPREDICT_PENALTY),
+            V7Operation::Ldm(ldm) => load_multiple(&ldm.registers.registers),
+            V7Operation::Ldmdb(ldm) => load_multiple(&ldm.registers.registers),
+            V7Operation::Pop(pop) => load_multiple(&pop.registers.registers),
+            V7Operation::LdrImmediate(el) if el.rt == Register::PC => {
+                CycleCount::Value(2 + MISPREDICT_PENALTY)
+            }
+            V7Operation::LdrLiteral(el) if el.rt == Register::PC => {
+                CycleCount::Value(2 + MISPREDICT_PENALTY)
+            }
+            V7Operation::LdrRegister(el) if el.rt == Register::PC => {
+                CycleCount::Value(2 + MISPREDICT_PENALTY)
+            }
+            V7Operation::AddImmediate(add) => if_pc(add.rd.unwrap_or(add.rn)),
+            V7Operation::AddRegister(add) => if_pc(add.rd.unwrap_or(add.rn)),
+            V7Operation::AddSPImmediate(add) => if_pc(add.rd.unwrap_or(Register::SP)),
+            V7Operation::AddSPRegister(add) => if_pc(add.rd.unwrap_or(Register::SP)),
+            V7Operation::MovImmediate(mov) => if_pc(mov.rd),
+            V7Operation::MovRegister(mov) => if_pc(mov.rd),
+            V7Operation::AdcImmediate(_)
+            | V7Operation::AdcRegister(_)
+            | V7Operation::Adr(_)
+            | V7Operation::AndImmediate(_)
+            | V7Operation::AndRegister(_)
+            | V7Operation::AsrImmediate(_)
+            | V7Operation::AsrRegister(_)
+            | V7Operation::Bfc(_)
+            | V7Operation::Bfi(_)
+            | V7Operation::BicImmediate(_)
+            | V7Operation::BicRegister(_)
+            | V7Operation::Clz(_)
+            | V7Operation::CmnImmediate(_)
+            | V7Operation::CmnRegister(_)
+            | V7Operation::CmpImmediate(_)
+            | V7Operation::CmpRegister(_)
+            | V7Operation::EorImmediate(_)
+            | V7Operation::EorRegister(_)
+            | V7Operation::LslImmediate(_)
+            | V7Operation::LslRegister(_)
+            | V7Operation::LsrImmediate(_)
+            | V7Operation::LsrRegister(_)
+            | V7Operation::Movt(_)
+            | V7Operation::MvnImmediate(_)
+            | V7Operation::MvnRegister(_)
+            | V7Operation::Nop(_)
+            | V7Operation::OrnImmediate(_)
+            | V7Operation::OrnRegister(_)
+            | V7Operation::OrrImmediate(_)
+            | V7Operation::OrrRegister(_)
+            | V7Operation::Rbit(_)
+            | V7Operation::Rev(_)
+            | V7Operation::Rev16(_)
+            | V7Operation::Revsh(_)
+            | V7Operation::RorImmediate(_)
+            | V7Operation::RorRegister(_)
+            | V7Operation::Rrx(_)
+            | V7Operation::RsbImmediate(_)
+            | V7Operation::RsbRegister(_)
+            | V7Operation::SbcImmediate(_)
+            | V7Operation::SbcRegister(_)
+            | V7Operation::Sbfx(_)
+            | V7Operation::SubImmediate(_)
+            | V7Operation::SubRegister(_)
+            | V7Operation::SubSpMinusImmediate(_)
+            | V7Operation::SubSpMinusRegister(_)
+            | V7Operation::Sxtb(_)
+            | V7Operation::Sxth(_)
+            | V7Operation::TeqImmediate(_)
+            | V7Operation::TeqRegister(_)
+            | V7Operation::TstImmediate(_)
+            | V7Operation::TstRegister(_)
+            | V7Operation::Ubfx(_)
+            | V7Operation::Uxtb(_)
+            | V7Operation::Uxth(_) => CycleCount::Function(alu),
+            _ => match Self::memory_access(instr) {
+                MemoryAccess::Load | MemoryAccess::Store => CycleCount::Function(load_store),
+                _ => Self::cycle_count_m4_core(instr),
+            },
+        }
+    }
+}
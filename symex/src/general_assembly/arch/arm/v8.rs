@@ -0,0 +1,298 @@
+//! Defines the Armv8-M Baseline and Mainline architectures.
+//!
+//! Armv8-M Mainline extends Armv7-M and Baseline extends Armv6-M with a subset
+//! of the Armv7-M instructions, so both are parsed with the Armv7-M parser
+//! after the instructions that are new in Armv8-M. The security extension is
+//! not modeled, all code runs in a single security state.
+
+use std::{collections::HashSet, fmt::Display};
+
+use disarmv7::prelude::{Operation as V7Operation, *};
+use general_assembly::{
+    operation::Operation,
+    register::{Register, RegisterSet},
+};
+use object::{File, Object};
+use regex::Regex;
+use tracing::{trace, warn};
+
+use super::{arm_isa, v7::decoder::Convert, ArmIsa, REGISTERS};
+use crate::general_assembly::{
+    arch::{make_symbolic, Arch, ArchError},
+    instruction::Instruction,
+    project::{
+        target_config::{ArmMCore, CoreFamily, TargetConfiguration},
+        PCHook,
+        RegisterReadHook,
+        RegisterWriteHook,
+    },
+    run_config::RunConfig,
+    state::GAState,
+    Endianness,
+};
+
+pub mod decoder;
+#[cfg(test)]
+pub mod test;
+pub mod timing;
+
+/// The profiles of Armv8-M.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum ArmV8MProfile {
+    /// Baseline, e.g. the Cortex-M23.
+    Baseline,
+    /// Mainline, e.g. the Cortex-M33.
+    #[default]
+    Mainline,
+}
+
+/// Type level denotation for the Armv8-M ISA.
+#[derive(Debug, Default, Clone)]
+pub struct ArmV8M {
+    pub profile: ArmV8MProfile,
+
+    pub pc_writes: HashSet<u64>,
+}
+
+impl ArmV8M {
+    pub fn new(profile: ArmV8MProfile) -> Self {
+        Self {
+            profile,
+            pc_writes: HashSet::new(),
+        }
+    }
+
+    /// Checks if the instruction in `buff` parsed by the Armv7-M parser is in
+    /// Baseline, i.e. if it is in Armv6-M or is one of the few wide
+    /// instructions that Baseline has in addition.
+    fn in_baseline(buff: &[u8], size: usize, operation: &V7Operation) -> bool {
+        match operation {
+            V7Operation::It(_) => false,
+            _ if size == 16 => true,
+            V7Operation::Bl(_)
+            | V7Operation::Dmb(_)
+            | V7Operation::Dsb(_)
+            | V7Operation::Isb(_)
+            | V7Operation::Mrs(_)
+            | V7Operation::Msr(_)
+            | V7Operation::Movt(_)
+            | V7Operation::Sdiv(_)
+            | V7Operation::Udiv(_)
+            | V7Operation::Ldrex(_)
+            | V7Operation::Ldrexb(_)
+            | V7Operation::Ldrexh(_)
+            | V7Operation::Strex(_)
+            | V7Operation::Strexb(_)
+            | V7Operation::Strexh(_)
+            | V7Operation::Udf(_) => true,
+            // The unconditional B.W.
+            V7Operation::B(b) => b.condition == Condition::None,
+            // MOVW, the wide MOV with a modified immediate is Mainline only.
+            V7Operation::MovImmediate(_) => {
+                u16::from_le_bytes([buff[0], buff[1]]) & 0xfbf0 == 0xf240
+            }
+            _ => false,
+        }
+    }
+}
+
+impl Arch for ArmV8M {
+    const REGISTERS: &'static RegisterSet = &REGISTERS;
+
+    fn add_hooks(&self, cfg: &mut RunConfig<Self>) {
+        let symbolic_sized = |state: &mut GAState<Self>| {
+            let pointer = const { REGISTERS.named("R0") };
+            let size = const { REGISTERS.named("R1") };
+            make_symbolic(state, pointer, size)?;
+
+            let lr = state.get_register(Register::LR)?;
+            state.set_register(Register::PC, lr)?;
+            Ok(())
+        };
+
+        cfg.pc_hooks.push((
+            Regex::new(r"^(symbolic_size<.+>|symex_symbolic)$").unwrap(),
+            PCHook::Intrinsic(symbolic_sized),
+        ));
+
+        // Reads the PC as the address of the current instruction + 4, see the
+        // Armv7-M hooks.
+        let read_pc: RegisterReadHook<Self> = |state| {
+            let new_pc = state
+                .ctx
+                .from_u64(state.last_pc + 4, state.project.get_word_size())
+                .simplify();
+            Ok(new_pc)
+        };
+
+        let read_sp: RegisterReadHook<Self> = |state| {
+            let two = state.ctx.from_u64((!(0b11u32)) as u64, 32);
+            let sp = state.get_register(Register::SP).unwrap();
+            let sp = sp.simplify();
+            Ok(sp.and(&two))
+        };
+
+        let write_pc: RegisterWriteHook<Self> =
+            |state, value| state.set_register(Register::PC, value);
+
+        // The stack pointers are not banked, SP is checked against the limit of
+        // the stack that CONTROL.SPSEL selects. Moving SP past the limit ends
+        // the path before the rest of the instruction is executed.
+        let write_sp: RegisterWriteHook<Self> = |state, value| {
+            let sp = value
+                .and(&state.ctx.from_u64((!(0b11u32)) as u64, 32))
+                .simplify();
+            let control = state
+                .get_register(const { REGISTERS.named("CONTROL") })?
+                .get_constant()
+                .unwrap_or(0);
+            let limit_register = match control & 0b10 {
+                0 => const { REGISTERS.named("MSPLIM") },
+                _ => const { REGISTERS.named("PSPLIM") },
+            };
+            let limit = state.get_register(limit_register)?;
+            let overflow = sp.ult(&limit).simplify();
+            if state.constraints.is_sat_with_constraint(&overflow)? {
+                trace!("Stack overflow, SP {sp:?} can be below {limit_register} {limit:?}");
+                state.fail("stack overflow", overflow.clone());
+                // The paths where SP is within the limit continue with it.
+                if !state.constraints.is_sat_with_constraint(&overflow.not())? {
+                    return Ok(());
+                }
+            }
+
+            match sp.get_constant() {
+                Some(value) => {
+                    trace!("Wrote {value} to SP");
+                    state.architecture.pc_writes.insert(value);
+                }
+                None => warn!("Could not get static version of sp = {:?}", sp),
+            }
+            state.set_register(Register::SP, sp)
+        };
+
+        cfg.register_read_hooks
+            .push((REGISTERS.named("PC+"), read_pc));
+        cfg.register_write_hooks
+            .push((REGISTERS.named("PC+"), write_pc));
+        cfg.register_read_hooks
+            .push((REGISTERS.named("SP&"), read_sp));
+        cfg.register_write_hooks
+            .push((REGISTERS.named("SP&"), write_sp));
+
+        // The stack limits are zero after reset, i.e. no limit.
+        let read_msplim: RegisterReadHook<Self> = |state| {
+            Ok(
+                match state.registers.get(const { REGISTERS.named("MSPLIM") }) {
+                    Some(limit) => limit.clone(),
+                    None => state.ctx.from_u64(0, 32),
+                },
+            )
+        };
+        let read_psplim: RegisterReadHook<Self> = |state| {
+            Ok(
+                match state.registers.get(const { REGISTERS.named("PSPLIM") }) {
+                    Some(limit) => limit.clone(),
+                    None => state.ctx.from_u64(0, 32),
+                },
+            )
+        };
+        // CONTROL is zero after reset, i.e. the main stack is used.
+        let read_control: RegisterReadHook<Self> = |state| {
+            Ok(
+                match state.registers.get(const { REGISTERS.named("CONTROL") }) {
+                    Some(control) => control.clone(),
+                    None => state.ctx.from_u64(0, 32),
+                },
+            )
+        };
+        cfg.register_read_hooks
+            .push((REGISTERS.named("MSPLIM"), read_msplim));
+        cfg.register_read_hooks
+            .push((REGISTERS.named("PSPLIM"), read_psplim));
+        cfg.register_read_hooks
+            .push((REGISTERS.named("CONTROL"), read_control));
+    }
+
+    fn translate(
+        &self,
+        buff: &[u8],
+        state: &GAState<Self>,
+    ) -> Result<Instruction<Self>, ArchError> {
+        let CoreFamily::ArmM(core) = state.project.get_target().core else {
+            return Err(ArchError::IncompatibleTarget);
+        };
+        if let Some((size, operation)) = decoder::parse(buff)? {
+            trace!("Running {:?}", operation);
+            return Ok(Self::expand(size, operation, core));
+        }
+
+        let mut stream: disarmv7::buffer::PeekableBuffer<u8, _> = buff.iter().cloned().into();
+        let instr =
+            V7Operation::parse(&mut stream).map_err(|e| ArchError::ParsingError(e.into()))?;
+        trace!("Running {:?}", instr.1);
+        if self.profile == ArmV8MProfile::Baseline && !Self::in_baseline(buff, instr.0, &instr.1) {
+            return Err(ArchError::UnsupportedInstruction);
+        }
+        let timing = match core {
+            ArmMCore::ArmM23 => Self::cycle_count_m23_core(&instr.1),
+            _ => Self::cycle_count_m33_core(&instr.1),
+        };
+        let ops: Vec<Operation> = instr.clone().convert(state.get_in_conditional_block());
+
+        Ok(Instruction {
+            instruction_size: instr.0 as u32,
+            operations: ops,
+            max_cycle: timing,
+            memory_access: super::v7::ArmV7EM::memory_access(&instr.1),
+        })
+    }
+
+    fn disassemble(&self, buff: &[u8]) -> Option<String> {
+        if let Some((_, operation)) = decoder::parse(buff).ok()? {
+            return Some(format!("{operation:?}"));
+        }
+        let mut buff: disarmv7::buffer::PeekableBuffer<u8, _> = buff.iter().cloned().into();
+        V7Operation::parse(&mut buff)
+            .ok()
+            .map(|(_, operation)| format!("{operation:?}"))
+    }
+
+    fn discover(file: &File) -> Result<Option<Self>, ArchError> {
+        let f = match file {
+            File::Elf32(f) => Ok(f),
+            _ => Err(ArchError::IncorrectFileType),
+        }?;
+        let section = match f.section_by_name(".ARM.attributes") {
+            Some(section) => Ok(section),
+            None => Err(ArchError::MissingSection(".ARM.attributes")),
+        }?;
+        let isa = arm_isa(&section, Endianness::of(file))?;
+        match isa {
+            ArmIsa::ArmV8MBaseline => Ok(Some(ArmV8M::new(ArmV8MProfile::Baseline))),
+            ArmIsa::ArmV8MMainline => Ok(Some(ArmV8M::new(ArmV8MProfile::Mainline))),
+            ArmIsa::ArmV6M | ArmIsa::ArmV7EM => Ok(None),
+        }
+    }
+
+    fn default_target() -> TargetConfiguration {
+        TargetConfiguration::zero_wait_states(CoreFamily::ArmM(ArmMCore::ArmM33))
+    }
+
+    fn get_stack_pointers(&self) -> Option<HashSet<u64>> {
+        Some(self.pc_writes.clone())
+    }
+
+    fn merge(&mut self, other: &Self) {
+        self.pc_writes.extend(&other.pc_writes);
+    }
+}
+
+impl Display for ArmV8M {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.profile {
+            ArmV8MProfile::Baseline => write!(f, "ARMv8-M Baseline"),
+            ArmV8MProfile::Mainline => write!(f, "ARMv8-M Mainline"),
+        }
+    }
+}
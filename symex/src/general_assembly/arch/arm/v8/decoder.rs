@@ -0,0 +1,298 @@
+//! Parses and translates the instructions that were added in Armv8-M.
+//!
+//! The rest of the instruction set is shared with Armv7-M and is handled by
+//! the Armv7-M parser and decoder.
+
+use general_assembly::{
+    operand::{DataWord, Operand},
+    operation::Operation,
+    register::Register,
+};
+
+use super::{super::REGISTERS, ArmV8M};
+use crate::general_assembly::{
+    arch::ArchError,
+    instruction::{Instruction, MemoryAccess},
+    project::target_config::ArmMCore,
+};
+
+/// The special registers that are handled by the Armv8-M decoder.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SpecialRegister {
+    /// `MSPLIM`, the limit of the main stack.
+    MainStackLimit,
+    /// `PSPLIM`, the limit of the process stack.
+    ProcessStackLimit,
+    /// `CONTROL`, where `SPSEL` selects the active stack pointer.
+    Control,
+}
+
+impl SpecialRegister {
+    pub fn register(&self) -> Register {
+        match self {
+            SpecialRegister::MainStackLimit => const { REGISTERS.named("MSPLIM") },
+            SpecialRegister::ProcessStackLimit => const { REGISTERS.named("PSPLIM") },
+            SpecialRegister::Control => const { REGISTERS.named("CONTROL") },
+        }
+    }
+}
+
+/// An instruction that only exists in Armv8-M.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum V8Operation {
+    /// `LDA`, `LDAB`, `LDAH` and the exclusive `LDAEX`, `LDAEXB`, `LDAEXH`.
+    LoadAcquire {
+        rt: u8,
+        rn: u8,
+        bits: u32,
+        exclusive: bool,
+    },
+    /// `STL`, `STLB`, `STLH` and the exclusive `STLEX`, `STLEXB`, `STLEXH`
+    /// that write their status to `rd`.
+    StoreRelease {
+        rd: Option<u8>,
+        rt: u8,
+        rn: u8,
+        bits: u32,
+    },
+    /// `TT`, `TTT`, `TTA` and `TTAT`.
+    TestTarget {
+        rd: u8,
+        rn: u8,
+        alternate: bool,
+        unprivileged: bool,
+    },
+    /// `SG`.
+    SecureGateway,
+    /// `BXNS` and `BLXNS`.
+    BranchNonSecure { rm: u8, link: bool },
+    /// `MSR` to a stack limit register or `CONTROL`.
+    Msr { rn: u8, register: SpecialRegister },
+    /// `MRS` from a stack limit register or `CONTROL`.
+    Mrs { rd: u8, register: SpecialRegister },
+}
+
+/// Parses the instructions that are new in Armv8-M, returns the size of the
+/// instruction in bits and the operation or `None` if it is an Armv7-M
+/// instruction.
+pub fn parse(buff: &[u8]) -> Result<Option<(u32, V8Operation)>, ArchError> {
+    let hw1 = match buff {
+        [b0, b1, ..] => u16::from_le_bytes([*b0, *b1]),
+        _ => return Ok(None),
+    };
+    if hw1 >> 11 < 0b11101 {
+        let rm = ((hw1 >> 3) & 0xf) as u8;
+        return Ok(match hw1 & 0xff87 {
+            0x4704 => Some((16, V8Operation::BranchNonSecure { rm, link: false })),
+            0x4784 => Some((16, V8Operation::BranchNonSecure { rm, link: true })),
+            _ => None,
+        });
+    }
+    let hw2 = match buff {
+        [_, _, b2, b3, ..] => u16::from_le_bytes([*b2, *b3]),
+        _ => return Ok(None),
+    };
+    let low = (hw1 & 0xf) as u8;
+    let rt = (hw2 >> 12) as u8;
+    let op = (hw2 >> 4) & 0xf;
+    let bits = match op & 0b11 {
+        0b00 => 8,
+        0b01 => 16,
+        _ => 32,
+    };
+    let operation = match hw1 & 0xfff0 {
+        0xe8d0 if hw2 & 0x0f0f == 0x0f0f && matches!(op, 0x8..=0xa | 0xc..=0xe) => {
+            V8Operation::LoadAcquire {
+                rt,
+                rn: low,
+                bits,
+                exclusive: op >= 0xc,
+            }
+        }
+        0xe8c0 if hw2 & 0x0f00 == 0x0f00 && matches!(op, 0xc..=0xe) => V8Operation::StoreRelease {
+            rd: Some((hw2 & 0xf) as u8),
+            rt,
+            rn: low,
+            bits,
+        },
+        0xe8c0 if hw2 & 0x0f0f == 0x0f0f && matches!(op, 0x8..=0xa) => V8Operation::StoreRelease {
+            rd: None,
+            rt,
+            rn: low,
+            bits,
+        },
+        // Shares the encoding space with STREX but has the unpredictable
+        // Rt = PC.
+        0xe840 if hw2 & 0xf03f == 0xf000 => V8Operation::TestTarget {
+            rd: ((hw2 >> 8) & 0xf) as u8,
+            rn: low,
+            alternate: hw2 & (1 << 7) != 0,
+            unprivileged: hw2 & (1 << 6) != 0,
+        },
+        0xe970 if hw1 == 0xe97f && hw2 == 0xe97f => V8Operation::SecureGateway,
+        0xf380 if hw2 & 0xff00 == 0x8800 => match special_register(hw2) {
+            Some(register) => V8Operation::Msr { rn: low, register },
+            None => return Ok(None),
+        },
+        0xf3e0 if hw1 == 0xf3ef && hw2 & 0xf000 == 0x8000 => match special_register(hw2) {
+            Some(register) => V8Operation::Mrs {
+                rd: ((hw2 >> 8) & 0xf) as u8,
+                register,
+            },
+            None => return Ok(None),
+        },
+        _ => return Ok(None),
+    };
+    Ok(Some((32, operation)))
+}
+
+fn special_register(hw2: u16) -> Option<SpecialRegister> {
+    match hw2 & 0xff {
+        0x0a => Some(SpecialRegister::MainStackLimit),
+        0x0b => Some(SpecialRegister::ProcessStackLimit),
+        0x14 => Some(SpecialRegister::Control),
+        _ => None,
+    }
+}
+
+/// The registers as they are named by the Armv7-M decoder.
+const CORE_REGISTERS: [Register; 16] = REGISTERS.all_named([
+    "R0", "R1", "R2", "R3", "R4", "R5", "R6", "R7", "R8", "R9", "R10", "R11", "R12", "SP&", "LR",
+    "PC+",
+]);
+
+/// A register in the Armv7-M decoder.
+fn register(register: u8) -> Operand {
+    Operand::Register(CORE_REGISTERS[register as usize])
+}
+
+fn local(name: &str) -> Operand {
+    Operand::Local(name.to_owned())
+}
+
+fn immediate(value: u32) -> Operand {
+    Operand::Immediate(DataWord::Word32(value))
+}
+
+/// The result of `TT` when there is no MPU or SAU, all addresses are readable
+/// and writable from the non-secure state.
+pub const TT_RESULT: u32 = (1 << 18) | (1 << 19);
+
+impl ArmV8M {
+    pub(super) fn expand(size: u32, operation: V8Operation, core: ArmMCore) -> Instruction<Self> {
+        let operations = match operation {
+            // The exclusive monitor is not modeled, like LDREX and STREX in
+            // Armv7-M.
+            V8Operation::LoadAcquire { rt, rn, bits, .. } => vec![
+                Operation::Move {
+                    destination: local("address"),
+                    source: register(rn),
+                },
+                Operation::Move {
+                    destination: local("data"),
+                    source: Operand::AddressInLocal("address".to_owned(), bits),
+                },
+                Operation::ZeroExtend {
+                    destination: register(rt),
+                    operand: local("data"),
+                    bits,
+                    target_bits: 32,
+                },
+            ],
+            V8Operation::StoreRelease { rd, rt, rn, bits } => {
+                let mut operations = vec![
+                    Operation::Move {
+                        destination: local("address"),
+                        source: register(rn),
+                    },
+                    Operation::Move {
+                        destination: Operand::AddressInLocal("address".to_owned(), bits),
+                        source: register(rt),
+                    },
+                ];
+                if let Some(rd) = rd {
+                    operations.push(Operation::Move {
+                        destination: register(rd),
+                        source: immediate(0),
+                    });
+                }
+                operations
+            }
+            V8Operation::TestTarget { rd, .. } => vec![Operation::Move {
+                destination: register(rd),
+                source: immediate(TT_RESULT),
+            }],
+            // There is no security state so these behave like NOP, BX and BLX.
+            V8Operation::SecureGateway => vec![],
+            V8Operation::BranchNonSecure { rm, link } => {
+                let mut operations = vec![Operation::And {
+                    destination: local("target"),
+                    operand1: register(rm),
+                    operand2: immediate(!1),
+                }];
+                if link {
+                    operations.extend([
+                        Operation::Sub {
+                            destination: local("next"),
+                            operand1: register(15),
+                            operand2: immediate(2),
+                        },
+                        Operation::Or {
+                            destination: register(14),
+                            operand1: local("next"),
+                            operand2: immediate(1),
+                        },
+                    ]);
+                }
+                operations.push(Operation::Move {
+                    destination: register(15),
+                    source: local("target"),
+                });
+                operations
+            }
+            // Only nPRIV and SPSEL are kept in CONTROL as there is no
+            // floating point state, the limits are double word aligned.
+            V8Operation::Msr {
+                rn,
+                register: special,
+            } => vec![Operation::And {
+                destination: Operand::Register(special.register()),
+                operand1: register(rn),
+                operand2: immediate(match special {
+                    SpecialRegister::Control => 0b11,
+                    _ => !0b111,
+                }),
+            }],
+            V8Operation::Mrs {
+                rd,
+                register: special,
+            } => vec![Operation::Move {
+                destination: register(rd),
+                source: Operand::Register(special.register()),
+            }],
+        };
+
+        Instruction {
+            instruction_size: size,
+            operations,
+            max_cycle: match core {
+                ArmMCore::ArmM23 => Self::cycle_count_m23_v8(&operation),
+                _ => Self::cycle_count_m33_v8(&operation),
+            },
+            memory_access: Self::memory_access_v8(&operation),
+        }
+    }
+
+    pub fn memory_access_v8(operation: &V8Operation) -> MemoryAccess {
+        match operation {
+            V8Operation::LoadAcquire {
+                exclusive: false, ..
+            } => MemoryAccess::Load,
+            V8Operation::StoreRelease { rd: None, .. } => MemoryAccess::Store,
+            V8Operation::LoadAcquire { .. } | V8Operation::StoreRelease { .. } => {
+                MemoryAccess::Other
+            }
+            _ => MemoryAccess::None,
+        }
+    }
+}
@@ -0,0 +1,396 @@
+use std::collections::HashMap;
+
+use general_assembly::register::Register;
+
+use super::{
+    decoder::{parse, SpecialRegister, V8Operation, TT_RESULT},
+    ArmV8M,
+    ArmV8MProfile,
+    REGISTERS,
+};
+use crate::{
+    general_assembly::{
+        arch::{
+            arm::{attributes_isa, ArmIsa},
+            Arch,
+            ArchError,
+        },
+        executor::PathResult,
+        instruction::CycleCount,
+        project::{
+            target_config::{ArmMCore, CoreFamily, TargetConfiguration},
+            PCHook,
+            Project,
+        },
+        state::GAState,
+        vm::VM,
+        Endianness,
+        WordSize,
+    },
+    smt::{DContext, DSolver},
+};
+
+/// Sets up a vm running `code` from address 0 that returns to 0x1000.
+fn setup_test_vm_with_code(code: &[u16], core: ArmMCore) -> VM<ArmV8M> {
+    // Instructions are read a word at a time so the last half word is padded.
+    let program: Vec<u8> = code
+        .iter()
+        .chain(&[0])
+        .flat_map(|half_word| half_word.to_le_bytes())
+        .collect();
+    let end = program.len() as u64;
+    let mut project = Box::new(Project::manual_project(
+        program,
+        0,
+        end,
+        WordSize::Bit32,
+        Endianness::Little,
+        HashMap::new(),
+        HashMap::new(),
+        Vec::new(),
+        Vec::new(),
+        HashMap::new(),
+        vec![],
+        HashMap::new(),
+        vec![],
+    ));
+    project.set_target(TargetConfiguration::zero_wait_states(CoreFamily::ArmM(
+        core,
+    )));
+    let arch = ArmV8M::default();
+    project.add_hooks(&arch);
+    project.add_pc_hook(0x1000, PCHook::EndSuccess);
+
+    let project = Box::leak(project);
+    let context = Box::new(DContext::new());
+    let context = Box::leak(context);
+    let solver = DSolver::new(context);
+    let mut state = GAState::create_test_state(project, context, solver, 0, 0x2000_0000, arch);
+    state
+        .set_register(Register::LR, context.from_u64(0x1000, 32))
+        .unwrap();
+    VM::new_with_state(project, state)
+}
+
+fn register(state: &mut GAState<ArmV8M>, register: &str) -> u64 {
+    state
+        .get_register(REGISTERS.named(register))
+        .unwrap()
+        .get_constant()
+        .expect("Could not get register as constant")
+}
+
+fn bytes(half_words: &[u16]) -> Vec<u8> {
+    half_words
+        .iter()
+        .flat_map(|half_word| half_word.to_le_bytes())
+        .collect()
+}
+
+#[test]
+fn test_parse() {
+    let parsed = |half_words: &[u16]| parse(&bytes(half_words)).unwrap().unwrap().1;
+    assert_eq!(parsed(&[0xe8d2, 0x1faf]), V8Operation::LoadAcquire {
+        rt: 1,
+        rn: 2,
+        bits: 32,
+        exclusive: false
+    });
+    assert_eq!(parsed(&[0xe8d2, 0x1fdf]), V8Operation::LoadAcquire {
+        rt: 1,
+        rn: 2,
+        bits: 16,
+        exclusive: true
+    });
+    assert_eq!(parsed(&[0xe8c2, 0x1f8f]), V8Operation::StoreRelease {
+        rd: None,
+        rt: 1,
+        rn: 2,
+        bits: 8
+    });
+    assert_eq!(parsed(&[0xe8c2, 0x1fe3]), V8Operation::StoreRelease {
+        rd: Some(3),
+        rt: 1,
+        rn: 2,
+        bits: 32
+    });
+    assert_eq!(parsed(&[0xe842, 0xf1c0]), V8Operation::TestTarget {
+        rd: 1,
+        rn: 2,
+        alternate: true,
+        unprivileged: true
+    });
+    assert_eq!(parsed(&[0xe97f, 0xe97f]), V8Operation::SecureGateway);
+    assert_eq!(parsed(&[0x4794]), V8Operation::BranchNonSecure {
+        rm: 2,
+        link: true
+    });
+    assert_eq!(parsed(&[0xf382, 0x880b]), V8Operation::Msr {
+        rn: 2,
+        register: SpecialRegister::ProcessStackLimit
+    });
+    assert_eq!(parsed(&[0xf3ef, 0x810a]), V8Operation::Mrs {
+        rd: 1,
+        register: SpecialRegister::MainStackLimit
+    });
+    assert_eq!(parsed(&[0xf381, 0x8814]), V8Operation::Msr {
+        rn: 1,
+        register: SpecialRegister::Control
+    });
+
+    // Armv7-M instructions are left to the Armv7-M parser.
+    for code in [&[0xe842, 0x1000][..], &[0xf3ef, 0x8108], &[0x4710], &[
+        0xf240, 0x0000,
+    ]] {
+        assert_eq!(parse(&bytes(code)).unwrap(), None);
+    }
+}
+
+#[test]
+fn test_acquire_release() {
+    let code = [
+        0xf240, 0x0000, // MOVW R0, #0
+        0xf2c1, 0x0000, // MOVT R0, #0x1000
+        0x2185, // MOVS R1, #0x85
+        0xe8c0, 0x1faf, // STL R1, [R0]
+        0xe8d0, 0x2faf, // LDA R2, [R0]
+        0xe8d0, 0x3fcf, // LDAEXB R3, [R0]
+        0xe8c0, 0x1fe4, // STLEX R4, R1, [R0]
+        0xe840, 0xf500, // TT R5, R0
+        0xe97f, 0xe97f, // SG
+        0x4770, // BX LR
+    ];
+    let mut vm = setup_test_vm_with_code(&code, ArmMCore::ArmM33);
+    let (result, mut state) = vm.run().unwrap().unwrap();
+    assert!(matches!(result, PathResult::Success(_)));
+    assert_eq!(register(&mut state, "R0"), 0x1000_0000);
+    assert_eq!(register(&mut state, "R2"), 0x85);
+    assert_eq!(register(&mut state, "R3"), 0x85);
+    assert_eq!(register(&mut state, "R4"), 0);
+    assert_eq!(register(&mut state, "R5"), TT_RESULT as u64);
+}
+
+/// Sets the main stack limit to 0x1fff_ff00 and moves the stack pointer down
+/// by 0x80 and then by `second` bytes.
+fn stack_limit_code(second: u16) -> [u16; 11] {
+    [
+        0xf64f,
+        0x7000, // MOVW R0, #0xff00
+        0xf6c1,
+        0x70ff, // MOVT R0, #0x1fff
+        0xf380,
+        0x880a, // MSR MSPLIM, R0
+        0xf3ef,
+        0x860a,                 // MRS R6, MSPLIM
+        0xb0a0,                 // SUB SP, #0x80
+        0xb080 | (second >> 2), // SUB SP, #second
+        0x4770,                 // BX LR
+    ]
+}
+
+#[test]
+fn test_stack_limit() {
+    let mut vm = setup_test_vm_with_code(&stack_limit_code(0x80), ArmMCore::ArmM33);
+    let (result, mut state) = vm.run().unwrap().unwrap();
+    assert!(matches!(result, PathResult::Success(_)));
+    assert_eq!(register(&mut state, "R6"), 0x1fff_ff00);
+    assert_eq!(register(&mut state, "SP"), 0x1fff_ff00);
+
+    let mut vm = setup_test_vm_with_code(&stack_limit_code(0x100), ArmMCore::ArmM33);
+    let (result, mut state) = vm.run().unwrap().unwrap();
+    assert!(matches!(result, PathResult::Failure("stack overflow")));
+    // The stack pointer is not moved past the limit.
+    assert_eq!(register(&mut state, "SP"), 0x1fff_ff80);
+}
+
+#[test]
+fn test_symbolic_stack_limit() {
+    let code = [
+        0xf64f, 0x7100, // MOVW R1, #0xff00
+        0xf6c1, 0x71ff, // MOVT R1, #0x1fff
+        0xf381, 0x880a, // MSR MSPLIM, R1
+        0x4685, // MOV SP, R0
+        0x4770, // BX LR
+    ];
+    let mut vm = setup_test_vm_with_code(&code, ArmMCore::ArmM33);
+
+    // The path forks on the symbolic R0, the path below the limit fails.
+    let mut results = vec![];
+    while let Some((result, mut state)) = vm.run().unwrap() {
+        let sp = state.get_register(Register::SP).unwrap();
+        let below = sp.ult(&state.ctx.from_u64(0x1fff_ff00, 32));
+        let always_below = !state
+            .constraints
+            .is_sat_with_constraint(&below.not())
+            .unwrap();
+        results.push((result, always_below));
+    }
+    assert_eq!(results.len(), 2);
+    assert!(matches!(
+        results[0],
+        (PathResult::Failure("stack overflow"), true)
+    ));
+    assert!(matches!(results[1], (PathResult::Success(_), false)));
+}
+
+#[test]
+fn test_baseline() {
+    let mut vm = setup_test_vm_with_code(&[], ArmMCore::ArmM23);
+    let state = vm.paths.get_path().unwrap().state;
+    let translate = |profile, half_words: &[u16]| {
+        let mut code = bytes(half_words);
+        code.extend([0, 0]);
+        ArmV8M::new(profile).translate(&code, &state).map(|_| ())
+    };
+
+    // The Armv6-M instructions and the wide instructions that Baseline adds.
+    for code in [
+        &[0x3001][..],     // ADDS R0, #1
+        &[0xf240, 0x0005], // MOVW R0, #5
+        &[0xfb91, 0xf0f2], // SDIV R0, R1, R2
+        &[0xf000, 0xb880], // B.W 0x104
+        &[0xe8d2, 0x1faf], // LDA R1, [R2]
+    ] {
+        assert_eq!(translate(ArmV8MProfile::Baseline, code), Ok(()));
+    }
+
+    // Mainline only instructions.
+    for code in [
+        &[0xf101, 0x0001][..], // ADD.W R0, R1, #1
+        &[0xf04f, 0x0005],     // MOV.W R0, #5
+        &[0xf000, 0x8080],     // BEQ.W 0x104
+        &[0xbf08],             // IT EQ
+    ] {
+        assert_eq!(translate(ArmV8MProfile::Mainline, code), Ok(()));
+        assert_eq!(
+            translate(ArmV8MProfile::Baseline, code),
+            Err(ArchError::UnsupportedInstruction)
+        );
+    }
+}
+
+/// Sets the limit selected by `msr` and switches to the process stack before
+/// moving the stack pointer 0x180 bytes down.
+fn process_stack_limit_code(msr: u16) -> [u16; 14] {
+    [
+        0xf64f, 0x7000, // MOVW R0, #0xff00
+        0xf6c1, 0x70ff, // MOVT R0, #0x1fff
+        0xf380, msr,    // MSR MSPLIM/PSPLIM, R0
+        0x2102, // MOVS R1, #2
+        0xf381, 0x8814, // MSR CONTROL, R1
+        0xf3ef, 0x8614, // MRS R6, CONTROL
+        0xb0a0, // SUB SP, #0x80
+        0xb0c0, // SUB SP, #0x100
+        0x4770, // BX LR
+    ]
+}
+
+#[test]
+fn test_process_stack_limit() {
+    let mut vm = setup_test_vm_with_code(&process_stack_limit_code(0x880b), ArmMCore::ArmM33);
+    let (result, mut state) = vm.run().unwrap().unwrap();
+    assert!(matches!(result, PathResult::Failure("stack overflow")));
+    assert_eq!(register(&mut state, "R6"), 0b10);
+    assert_eq!(register(&mut state, "SP"), 0x1fff_ff80);
+
+    // MSPLIM does not limit the process stack.
+    let mut vm = setup_test_vm_with_code(&process_stack_limit_code(0x880a), ArmMCore::ArmM33);
+    let (result, mut state) = vm.run().unwrap().unwrap();
+    assert!(matches!(result, PathResult::Success(_)));
+    assert_eq!(register(&mut state, "SP"), 0x1fff_fe80);
+}
+
+#[test]
+fn test_cycles() {
+    let sdiv = bytes(&[0xfb91, 0xf0f2, 0x0000]);
+    let cycles = |core| {
+        let mut vm = setup_test_vm_with_code(&[], core);
+        let state = vm.paths.get_path().unwrap().state;
+        match ArmV8M::default()
+            .translate(&sdiv, &state)
+            .unwrap()
+            .max_cycle
+        {
+            CycleCount::Value(cycles) => cycles,
+            CycleCount::Function(_) => panic!("Division should take a fixed number of cycles"),
+        }
+    };
+    assert_eq!(cycles(ArmMCore::ArmM23), 17);
+    assert_eq!(cycles(ArmMCore::ArmM33), 11);
+}
+
+/// Builds an `.ARM.attributes` section with a CPU name before the
+/// architecture, as emitted by GCC.
+fn attributes(cpu_name: &[u8], cpu_arch: u8, endianness: Endianness) -> Vec<u8> {
+    let length = |length: usize| match endianness {
+        Endianness::Little => (length as u32).to_le_bytes(),
+        Endianness::Big => (length as u32).to_be_bytes(),
+    };
+    let mut attributes = vec![5];
+    attributes.extend(cpu_name);
+    attributes.extend([0, 6, cpu_arch, 7, b'M']);
+    let mut file = vec![1];
+    file.extend(length(attributes.len() + 5));
+    file.extend(attributes);
+    let mut subsection = b"aeabi\0".to_vec();
+    subsection.extend(file);
+    let mut section = vec![b'A'];
+    section.extend(length(subsection.len() + 4));
+    section.extend(subsection);
+    section
+}
+
+#[test]
+fn test_discover_isa() {
+    assert!(matches!(
+        attributes_isa(
+            &attributes(b"Cortex-M33", 17, Endianness::Little),
+            Endianness::Little
+        ),
+        Ok(ArmIsa::ArmV8MMainline)
+    ));
+    assert!(matches!(
+        attributes_isa(
+            &attributes(b"8-M.BASE", 16, Endianness::Little),
+            Endianness::Little
+        ),
+        Ok(ArmIsa::ArmV8MBaseline)
+    ));
+    assert!(matches!(
+        attributes_isa(
+            &attributes(b"7E-M", 13, Endianness::Little),
+            Endianness::Little
+        ),
+        Ok(ArmIsa::ArmV7EM)
+    ));
+
+    // The attributes of an rp2040 binary built by rustc.
+    let rp2040 = [
+        0x41, 0x31, 0x00, 0x00, 0x00, 0x61, 0x65, 0x61, 0x62, 0x69, 0x00, 0x01, 0x27, 0x00, 0x00,
+        0x00, 0x43, 0x32, 0x2e, 0x30, 0x39, 0x00, 0x06, 0x0c, 0x07, 0x4d, 0x08, 0x00, 0x09, 0x01,
+        0x0e, 0x00, 0x11, 0x01, 0x14, 0x01, 0x15, 0x01, 0x17, 0x03, 0x18, 0x01, 0x19, 0x01, 0x1e,
+        0x02, 0x22, 0x00, 0x26, 0x01,
+    ];
+    assert!(matches!(
+        attributes_isa(&rp2040, Endianness::Little),
+        Ok(ArmIsa::ArmV6M)
+    ));
+
+    // The lengths are stored in the byte order of the file.
+    let big_endian = attributes(b"Cortex-M33", 17, Endianness::Big);
+    assert!(matches!(
+        attributes_isa(&big_endian, Endianness::Big),
+        Ok(ArmIsa::ArmV8MMainline)
+    ));
+    assert!(attributes_isa(&big_endian, Endianness::Little).is_err());
+
+    assert!(attributes_isa(
+        &attributes(b"8.1-M.Main", 21, Endianness::Little),
+        Endianness::Little
+    )
+    .is_err());
+    assert_eq!(
+        ArmV8M::new(ArmV8MProfile::Baseline).to_string(),
+        "ARMv8-M Baseline"
+    );
+}
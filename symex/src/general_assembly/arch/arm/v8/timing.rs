@@ -0,0 +1,103 @@
+//! Cycle models for the Cortex-M23 and Cortex-M33.
+//!
+//! The Cortex-M33 has the same three stage pipeline as the Cortex-M4 and uses
+//! its cycle model, except for the faster divider. The Cortex-M23 has a two
+//! stage pipeline like the Cortex-M0+ and uses the
+//! [Cortex-M0+ instruction timings](https://developer.arm.com/documentation/ddi0484/c/CHDCICDF)
+//! for the instructions the two share.
+
+use disarmv7::prelude::{Condition, Operation as V7Operation, Register};
+
+use super::{decoder::V8Operation, ArmV8M};
+use crate::general_assembly::{
+    arch::arm::v7::{timing::branch_register, ArmV7EM},
+    instruction::{CycleCount, MemoryAccess},
+    state::GAState,
+};
+
+/// Number of cycles for a division on the Cortex-M23 with the default fast
+/// divider.
+const DIV_M23: usize = 17;
+
+/// The largest number of cycles for a division on the Cortex-M33.
+const DIV_M33: usize = 11;
+
+/// A conditional branch on the Cortex-M23, two cycles if taken.
+fn branch_conditional_m23(state: &mut GAState<ArmV8M>) -> usize {
+    match state.get_has_jumped() {
+        true => 2,
+        false => 1,
+    }
+}
+
+impl ArmV8M {
+    /// Cycle count of `instr` on a Cortex-M33.
+    pub fn cycle_count_m33_core(instr: &V7Operation) -> CycleCount<Self> {
+        match instr {
+            V7Operation::Sdiv(_) | V7Operation::Udiv(_) => CycleCount::Value(DIV_M33),
+            _ => ArmV7EM::cycle_count_m4_core(instr),
+        }
+    }
+
+    /// Cycle count of `instr` on a Cortex-M23.
+    pub fn cycle_count_m23_core(instr: &V7Operation) -> CycleCount<Self> {
+        let if_pc = |reg: Register| match reg {
+            Register::PC => CycleCount::Value(2),
+            _ => CycleCount::Value(1),
+        };
+        let multiple = |registers: &[Register]| match registers.contains(&Register::PC) {
+            true => CycleCount::Value(3 + registers.len()),
+            false => CycleCount::Value(1 + registers.len()),
+        };
+        match instr {
+            V7Operation::B(b) => match b.condition {
+                Condition::None => CycleCount::Value(2),
+                _ => CycleCount::Function(branch_conditional_m23),
+            },
+            V7Operation::Cbz(_) => CycleCount::Function(branch_conditional_m23),
+            V7Operation::Bl(_) => CycleCount::Value(3),
+            V7Operation::Bx(_) | V7Operation::Blx(_) => CycleCount::Value(2),
+            V7Operation::AddRegister(add) => if_pc(add.rd.unwrap_or(add.rn)),
+            V7Operation::MovRegister(mov) => if_pc(mov.rd),
+            V7Operation::Ldm(ldm) => multiple(&ldm.registers.registers),
+            V7Operation::Pop(pop) => multiple(&pop.registers.registers),
+            V7Operation::Stm(stm) => CycleCount::Value(1 + stm.registers.registers.len()),
+            V7Operation::Push(push) => CycleCount::Value(1 + push.registers.registers.len()),
+            V7Operation::Sdiv(_) | V7Operation::Udiv(_) => CycleCount::Value(DIV_M23),
+            V7Operation::Mrs(_)
+            | V7Operation::Msr(_)
+            | V7Operation::Dmb(_)
+            | V7Operation::Dsb(_)
+            | V7Operation::Isb(_) => CycleCount::Value(3),
+            _ => match ArmV7EM::memory_access(instr) {
+                MemoryAccess::None => CycleCount::Value(1),
+                _ => CycleCount::Value(2),
+            },
+        }
+    }
+
+    /// Cycle count of an Armv8-M instruction on a Cortex-M33.
+    pub fn cycle_count_m33_v8(operation: &V8Operation) -> CycleCount<Self> {
+        match operation {
+            // The acquire and release semantics prevent pipelining.
+            V8Operation::LoadAcquire { .. } | V8Operation::StoreRelease { .. } => {
+                CycleCount::Value(2)
+            }
+            V8Operation::BranchNonSecure { .. } => CycleCount::Function(branch_register),
+            V8Operation::Msr { .. } | V8Operation::Mrs { .. } => CycleCount::Value(2),
+            V8Operation::TestTarget { .. } | V8Operation::SecureGateway => CycleCount::Value(1),
+        }
+    }
+
+    /// Cycle count of an Armv8-M instruction on a Cortex-M23.
+    pub fn cycle_count_m23_v8(operation: &V8Operation) -> CycleCount<Self> {
+        match operation {
+            V8Operation::LoadAcquire { .. } | V8Operation::StoreRelease { .. } => {
+                CycleCount::Value(2)
+            }
+            V8Operation::BranchNonSecure { .. } => CycleCount::Value(2),
+            V8Operation::Msr { .. } | V8Operation::Mrs { .. } => CycleCount::Value(3),
+            V8Operation::TestTarget { .. } | V8Operation::SecureGateway => CycleCount::Value(1),
+        }
+    }
+}
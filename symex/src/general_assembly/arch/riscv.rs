@@ -0,0 +1,222 @@
+//! Defines the RV32IMAC architecture, the 32 bit RISC-V base integer
+//! instruction set with the multiplication, atomic and compressed extensions.
+pub mod decoder;
+pub mod parser;
+#[cfg(test)]
+pub mod test;
+pub mod timing;
+
+use std::fmt::Display;
+
+use general_assembly::register::{Register, RegisterSet};
+use object::{elf::EM_RISCV, read::elf::FileHeader, File, Object, ObjectSection};
+use regex::Regex;
+use tracing::trace;
+
+use crate::general_assembly::{
+    arch::{file_attributes, make_symbolic, ntbs, uleb128, Arch, ArchError},
+    instruction::Instruction,
+    project::{
+        target_config::{CoreFamily, RiscVCore, TargetConfiguration},
+        PCHook,
+        RegisterReadHook,
+        RegisterWriteHook,
+    },
+    run_config::RunConfig,
+    state::GAState,
+    Endianness,
+};
+
+/// Type level denotation for the RV32IMAC ISA.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct RiscV32IMAC;
+
+/// The registers of RV32IMAC.
+///
+/// The integer registers are named by their ABI names and `pc` is the address
+/// of the instruction being executed. `csr` holds the control and status
+/// registers that are not named.
+pub const REGISTERS: RegisterSet = RegisterSet::new(&[
+    "zero", "ra", "sp", "gp", "tp", "t0", "t1", "t2", "s0", "s1", "a0", "a1", "a2", "a3", "a4",
+    "a5", "a6", "a7", "s2", "s3", "s4", "s5", "s6", "s7", "s8", "s9", "s10", "s11", "t3", "t4",
+    "t5", "t6", "pc", "mstatus", "misa", "mie", "mtvec", "mscratch", "mepc", "mcause", "mtval",
+    "mip", "mcycle", "minstret", "cycle", "time", "instret", "mhartid", "csr",
+]);
+
+impl Arch for RiscV32IMAC {
+    const REGISTERS: &'static RegisterSet = &REGISTERS;
+
+    fn add_hooks(&self, cfg: &mut RunConfig<Self>) {
+        let symbolic_sized = |state: &mut GAState<Self>| {
+            let pointer = const { REGISTERS.named("a0") };
+            let size = const { REGISTERS.named("a1") };
+            make_symbolic(state, pointer, size)?;
+
+            let ra = state.get_register(const { REGISTERS.named("ra") })?;
+            state.set_register(Register::PC, ra)?;
+            Ok(())
+        };
+
+        cfg.pc_hooks.push((
+            Regex::new(r"^(symbolic_size<.+>|symex_symbolic)$").unwrap(),
+            PCHook::Intrinsic(symbolic_sized),
+        ));
+
+        // x0 is hard wired to zero.
+        let read_zero: RegisterReadHook<Self> =
+            |state| Ok(state.ctx.from_u64(0, state.project.get_word_size()));
+        let write_zero: RegisterWriteHook<Self> = |_state, _value| Ok(());
+
+        // The return address and stack pointer are aliased to the registers
+        // the rest of the crate uses to start and end a function.
+        let read_ra: RegisterReadHook<Self> = |state| state.get_register(Register::LR);
+        let write_ra: RegisterWriteHook<Self> =
+            |state, value| state.set_register(Register::LR, value);
+        let read_sp: RegisterReadHook<Self> = |state| state.get_register(Register::SP);
+        let write_sp: RegisterWriteHook<Self> =
+            |state, value| state.set_register(Register::SP, value);
+
+        // Offsets are relative to the address of the current instruction while
+        // PC points to the next instruction.
+        let read_pc: RegisterReadHook<Self> = |state| {
+            Ok(state
+                .ctx
+                .from_u64(state.last_pc, state.project.get_word_size()))
+        };
+        let write_pc: RegisterWriteHook<Self> =
+            |state, value| state.set_register(Register::PC, value);
+
+        cfg.register_read_hooks
+            .push((REGISTERS.named("zero"), read_zero));
+        cfg.register_write_hooks
+            .push((REGISTERS.named("zero"), write_zero));
+        cfg.register_read_hooks
+            .push((REGISTERS.named("ra"), read_ra));
+        cfg.register_write_hooks
+            .push((REGISTERS.named("ra"), write_ra));
+        cfg.register_read_hooks
+            .push((REGISTERS.named("sp"), read_sp));
+        cfg.register_write_hooks
+            .push((REGISTERS.named("sp"), write_sp));
+        cfg.register_read_hooks
+            .push((REGISTERS.named("pc"), read_pc));
+        cfg.register_write_hooks
+            .push((REGISTERS.named("pc"), write_pc));
+    }
+
+    fn translate(
+        &self,
+        buff: &[u8],
+        state: &GAState<Self>,
+    ) -> Result<Instruction<Self>, ArchError> {
+        let instr = parser::parse(buff)?;
+        trace!("Running {:?}", instr);
+        let cycles = state
+            .project
+            .get_target()
+            .riscv_cycles()
+            .ok_or(ArchError::IncompatibleTarget)?;
+        Ok(Self::expand(instr, &cycles))
+    }
+
+    fn disassemble(&self, buff: &[u8]) -> Option<String> {
+        parser::parse(buff).ok().map(|instr| format!("{instr:?}"))
+    }
+
+    fn discover(file: &File) -> Result<Option<Self>, ArchError> {
+        let f = match file {
+            File::Elf32(f) => Ok(f),
+            _ => Err(ArchError::IncorrectFileType),
+        }?;
+        if f.raw_header().e_machine(f.endian()) != EM_RISCV {
+            return Ok(None);
+        }
+        // Toolchains that do not emit the attributes are assumed to target a
+        // supported core.
+        let section = match f.section_by_name(".riscv.attributes") {
+            Some(section) => section,
+            None => return Ok(Some(RiscV32IMAC)),
+        };
+        let data = section.data().map_err(|_| ArchError::MalformedSection)?;
+        let arch = match riscv_arch(data, Endianness::of(file))? {
+            Some(arch) => arch,
+            None => return Ok(Some(RiscV32IMAC)),
+        };
+        match supported_arch(&arch) {
+            Some(true) => Ok(Some(RiscV32IMAC)),
+            Some(false) => Err(ArchError::UnsuportedArchitechture),
+            None => Ok(None),
+        }
+    }
+
+    fn default_target() -> TargetConfiguration {
+        TargetConfiguration::zero_wait_states(CoreFamily::RiscV(RiscVCore::Rv32Imac))
+    }
+}
+
+impl Display for RiscV32IMAC {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "RV32IMAC")
+    }
+}
+
+/// The `Tag_RISCV_arch` attribute.
+const TAG_ARCH: u64 = 5;
+
+/// Extracts the architecture string from the contents of the
+/// `.riscv.attributes` section.
+///
+/// The layout is described in the
+/// [RISC-V ELF psABI](https://github.com/riscv-non-isa/riscv-elf-psabi-doc/blob/master/riscv-elf.adoc#attributes).
+fn riscv_arch(data: &[u8], endianness: Endianness) -> Result<Option<String>, ArchError> {
+    let Some(mut attributes) = file_attributes(data, b"riscv", endianness)? else {
+        return Ok(None);
+    };
+    while !attributes.is_empty() {
+        let (tag, rest) = uleb128(attributes)?;
+        // Odd tags are strings and even tags are integers.
+        if tag % 2 == 1 {
+            let (value, rest) = ntbs(rest)?;
+            if tag == TAG_ARCH {
+                return Ok(Some(String::from_utf8_lossy(value).into_owned()));
+            }
+            attributes = rest;
+        } else {
+            attributes = uleb128(rest)?.1;
+        }
+    }
+    Ok(None)
+}
+
+/// Multi-letter extensions that do not add anything outside of RV32IMAC.
+const SUPPORTED_EXTENSIONS: [&str; 9] = [
+    "zicsr",
+    "zifencei",
+    "zmmul",
+    "zaamo",
+    "zalrsc",
+    "zca",
+    "zicntr",
+    "zihpm",
+    "zihintpause",
+];
+
+/// Checks if an architecture string such as `rv32i2p1_m2p0_zicsr2p0` only
+/// uses supported extensions, `None` if it is not a 32 bit architecture.
+fn supported_arch(arch: &str) -> Option<bool> {
+    let arch = arch.to_ascii_lowercase();
+    let extensions = arch.strip_prefix("rv32")?;
+    Some(extensions.split('_').all(|extension| {
+        let name = extension.trim_end_matches(|c: char| c.is_ascii_digit() || c == 'p');
+        match name.chars().next() {
+            // Multi-letter extensions start with z, s or x.
+            Some('z' | 's' | 'x') => SUPPORTED_EXTENSIONS.contains(&name),
+            // Single letter extensions may be grouped and followed by their
+            // version, e.g. `imac` or `i2p1m2p0`.
+            _ => extension
+                .chars()
+                .filter(|c| c.is_ascii_alphabetic() && *c != 'p')
+                .all(|c| matches!(c, 'i' | 'e' | 'm' | 'a' | 'c')),
+        }
+    }))
+}
@@ -0,0 +1,650 @@
+//! Defines translation rules from parsed RV32IMAC [`Operation`]s to general
+//! assembly [`Operation`](GAOperation)s.
+//!
+//! RISC-V has no flags, comparisons set the flags used by the ARM backends as
+//! scratch values and read them back right away. The flags are never live
+//! between two instructions.
+
+use general_assembly::{
+    condition::Condition,
+    operand::{DataWord, Operand},
+    operation::Operation as GAOperation,
+    register::Register as GARegister,
+};
+
+use super::{
+    parser::{
+        AluOp,
+        AmoOp,
+        BranchCondition,
+        CsrOp,
+        CsrSource,
+        Instruction,
+        MulOp,
+        Operation,
+        Register,
+    },
+    timing,
+    RiscV32IMAC,
+    REGISTERS,
+};
+use crate::general_assembly::{
+    instruction::{Instruction as GAInstruction, MemoryAccess},
+    project::target_config::RiscVCycles,
+};
+
+/// The integer registers, by their ABI names.
+pub const INTEGER_REGISTERS: [GARegister; 32] = REGISTERS.all_named([
+    "zero", "ra", "sp", "gp", "tp", "t0", "t1", "t2", "s0", "s1", "a0", "a1", "a2", "a3", "a4",
+    "a5", "a6", "a7", "s2", "s3", "s4", "s5", "s6", "s7", "s8", "s9", "s10", "s11", "t3", "t4",
+    "t5", "t6",
+]);
+
+fn register(register: Register) -> Operand {
+    Operand::Register(INTEGER_REGISTERS[register as usize])
+}
+
+fn local(name: &str) -> Operand {
+    Operand::Local(name.to_owned())
+}
+
+fn immediate(value: u32) -> Operand {
+    Operand::Immediate(DataWord::Word32(value))
+}
+
+/// The address of the instruction being executed, `PC` holds the address of
+/// the next instruction.
+fn pc() -> Operand {
+    Operand::Register(const { REGISTERS.named("pc") })
+}
+
+impl RiscV32IMAC {
+    pub(super) fn expand(instr: Instruction, cycles: &RiscVCycles) -> GAInstruction<RiscV32IMAC> {
+        let operations = match instr.operation {
+            Operation::Lui { rd, imm } => vec![GAOperation::Move {
+                destination: register(rd),
+                source: immediate(imm),
+            }],
+            Operation::Auipc { rd, imm } => vec![GAOperation::Add {
+                destination: register(rd),
+                operand1: pc(),
+                operand2: immediate(imm),
+            }],
+            Operation::Jal { rd, offset } => jump(rd, vec![GAOperation::Add {
+                destination: local("target"),
+                operand1: pc(),
+                operand2: immediate(offset as u32),
+            }]),
+            Operation::Jalr { rd, rs1, offset } => jump(rd, vec![
+                GAOperation::Add {
+                    destination: local("target"),
+                    operand1: register(rs1),
+                    operand2: immediate(offset as u32),
+                },
+                GAOperation::And {
+                    destination: local("target"),
+                    operand1: local("target"),
+                    operand2: immediate(!1),
+                },
+            ]),
+            Operation::Branch {
+                condition,
+                rs1,
+                rs2,
+                offset,
+            } => {
+                let mut operations = compare(register(rs1), register(rs2));
+                operations.extend([
+                    GAOperation::Add {
+                        destination: local("target"),
+                        operand1: pc(),
+                        operand2: immediate(offset as u32),
+                    },
+                    GAOperation::ConditionalJump {
+                        destination: local("target"),
+                        condition: match condition {
+                            BranchCondition::Eq => Condition::EQ,
+                            BranchCondition::Ne => Condition::NE,
+                            BranchCondition::Lt => Condition::LT,
+                            BranchCondition::Ge => Condition::GE,
+                            BranchCondition::Ltu => Condition::CC,
+                            BranchCondition::Geu => Condition::CS,
+                        },
+                    },
+                ]);
+                operations
+            }
+            Operation::Load {
+                rd,
+                rs1,
+                offset,
+                bits,
+                signed,
+            } => {
+                let mut operations = vec![
+                    GAOperation::Add {
+                        destination: local("addr"),
+                        operand1: register(rs1),
+                        operand2: immediate(offset as u32),
+                    },
+                    GAOperation::Move {
+                        destination: local("value"),
+                        source: Operand::AddressInLocal("addr".to_owned(), bits),
+                    },
+                ];
+                operations.push(match (bits, signed) {
+                    (32, _) => GAOperation::Move {
+                        destination: register(rd),
+                        source: local("value"),
+                    },
+                    (_, true) => GAOperation::SignExtend {
+                        destination: register(rd),
+                        operand: local("value"),
+                        bits,
+                    },
+                    (_, false) => GAOperation::ZeroExtend {
+                        destination: register(rd),
+                        operand: local("value"),
+                        bits,
+                        target_bits: 32,
+                    },
+                });
+                operations
+            }
+            Operation::Store {
+                rs1,
+                rs2,
+                offset,
+                bits,
+            } => vec![
+                GAOperation::Add {
+                    destination: local("addr"),
+                    operand1: register(rs1),
+                    operand2: immediate(offset as u32),
+                },
+                GAOperation::Move {
+                    destination: Operand::AddressInLocal("addr".to_owned(), bits),
+                    source: register(rs2),
+                },
+            ],
+            Operation::OpImm { op, rd, rs1, imm } => {
+                alu(op, register(rd), register(rs1), immediate(imm as u32))
+            }
+            Operation::Op { op, rd, rs1, rs2 } => match op {
+                // Only the low five bits of the register are used as the shift amount.
+                AluOp::Sll | AluOp::Srl | AluOp::Sra => {
+                    let mut operations = vec![GAOperation::And {
+                        destination: local("shift"),
+                        operand1: register(rs2),
+                        operand2: immediate(0x1f),
+                    }];
+                    operations.extend(alu(op, register(rd), register(rs1), local("shift")));
+                    operations
+                }
+                _ => alu(op, register(rd), register(rs1), register(rs2)),
+            },
+            Operation::MulDiv { op, rd, rs1, rs2 } => mul_div(op, register(rd), rs1, rs2),
+            Operation::LoadReserved { rd, rs1 } => vec![
+                GAOperation::Move {
+                    destination: local("addr"),
+                    source: register(rs1),
+                },
+                GAOperation::Move {
+                    destination: register(rd),
+                    source: Operand::AddressInLocal("addr".to_owned(), 32),
+                },
+            ],
+            // There is only a single hart so the reservation is never lost.
+            Operation::StoreConditional { rd, rs1, rs2 } => vec![
+                GAOperation::Move {
+                    destination: local("addr"),
+                    source: register(rs1),
+                },
+                GAOperation::Move {
+                    destination: Operand::AddressInLocal("addr".to_owned(), 32),
+                    source: register(rs2),
+                },
+                GAOperation::Move {
+                    destination: register(rd),
+                    source: immediate(0),
+                },
+            ],
+            Operation::Amo { op, rd, rs1, rs2 } => amo(op, register(rd), register(rs1), rs2),
+            Operation::Csr {
+                op,
+                rd,
+                csr,
+                source,
+            } => {
+                let csr = Operand::Register(csr_register(csr));
+                let source = match source {
+                    CsrSource::Register(rs1) => register(rs1),
+                    CsrSource::Immediate(imm) => immediate(imm),
+                };
+                let mut operations = vec![
+                    GAOperation::Move {
+                        destination: local("old"),
+                        source: csr.clone(),
+                    },
+                    GAOperation::Move {
+                        destination: local("source"),
+                        source,
+                    },
+                ];
+                match op {
+                    CsrOp::ReadWrite => operations.push(GAOperation::Move {
+                        destination: csr,
+                        source: local("source"),
+                    }),
+                    CsrOp::ReadSet => operations.push(GAOperation::Or {
+                        destination: csr,
+                        operand1: local("old"),
+                        operand2: local("source"),
+                    }),
+                    CsrOp::ReadClear => operations.extend([
+                        GAOperation::Not {
+                            destination: local("source"),
+                            operand: local("source"),
+                        },
+                        GAOperation::And {
+                            destination: csr,
+                            operand1: local("old"),
+                            operand2: local("source"),
+                        },
+                    ]),
+                }
+                operations.push(GAOperation::Move {
+                    destination: register(rd),
+                    source: local("old"),
+                });
+                operations
+            }
+            Operation::Mret => vec![GAOperation::ConditionalJump {
+                destination: Operand::Register(const { REGISTERS.named("mepc") }),
+                condition: Condition::None,
+            }],
+            // Nothing to do with a single hart and no traps.
+            Operation::Fence | Operation::Ecall | Operation::Ebreak | Operation::Wfi => vec![],
+        };
+
+        GAInstruction {
+            instruction_size: instr.size,
+            operations,
+            max_cycle: timing::cycle_count(&instr.operation, cycles),
+            memory_access: Self::memory_access(&instr.operation),
+        }
+    }
+
+    pub fn memory_access(operation: &Operation) -> MemoryAccess {
+        match operation {
+            Operation::Load { .. } | Operation::LoadReserved { .. } => MemoryAccess::Load,
+            Operation::Store { .. } | Operation::StoreConditional { .. } => MemoryAccess::Store,
+            Operation::Amo { .. } => MemoryAccess::Other,
+            _ => MemoryAccess::None,
+        }
+    }
+}
+
+/// Links the return address in `rd` and jumps to the local `target` computed
+/// by `operations`.
+fn jump(rd: Register, mut operations: Vec<GAOperation>) -> Vec<GAOperation> {
+    operations.extend([
+        GAOperation::Move {
+            destination: register(rd),
+            source: Operand::Register(GARegister::PC),
+        },
+        GAOperation::ConditionalJump {
+            destination: local("target"),
+            condition: Condition::None,
+        },
+    ]);
+    operations
+}
+
+/// Sets the flags as if `operand2` was subtracted from `operand1`.
+fn compare(operand1: Operand, operand2: Operand) -> Vec<GAOperation> {
+    vec![
+        GAOperation::Sub {
+            destination: local("difference"),
+            operand1: operand1.clone(),
+            operand2: operand2.clone(),
+        },
+        GAOperation::SetNFlag(local("difference")),
+        GAOperation::SetZFlag(local("difference")),
+        GAOperation::SetCFlag {
+            operand1: operand1.clone(),
+            operand2: operand2.clone(),
+            sub: true,
+            carry: false,
+        },
+        GAOperation::SetVFlag {
+            operand1,
+            operand2,
+            sub: true,
+            carry: false,
+        },
+    ]
+}
+
+/// Writes 1 to `destination` if `operand1` is less than `operand2`.
+fn less_than(
+    destination: Operand,
+    operand1: Operand,
+    operand2: Operand,
+    signed: bool,
+) -> Vec<GAOperation> {
+    let mut operations = compare(operand1, operand2);
+    operations.push(match signed {
+        true => GAOperation::Xor {
+            destination,
+            operand1: Operand::Flag(GARegister::N),
+            operand2: Operand::Flag(GARegister::V),
+        },
+        // The carry flag is set when there is no borrow.
+        false => GAOperation::Xor {
+            destination,
+            operand1: Operand::Flag(GARegister::C),
+            operand2: immediate(1),
+        },
+    });
+    operations
+}
+
+fn alu(op: AluOp, destination: Operand, operand1: Operand, operand2: Operand) -> Vec<GAOperation> {
+    match op {
+        AluOp::Add => vec![GAOperation::Add {
+            destination,
+            operand1,
+            operand2,
+        }],
+        AluOp::Sub => vec![GAOperation::Sub {
+            destination,
+            operand1,
+            operand2,
+        }],
+        AluOp::Xor => vec![GAOperation::Xor {
+            destination,
+            operand1,
+            operand2,
+        }],
+        AluOp::Or => vec![GAOperation::Or {
+            destination,
+            operand1,
+            operand2,
+        }],
+        AluOp::And => vec![GAOperation::And {
+            destination,
+            operand1,
+            operand2,
+        }],
+        AluOp::Sll => vec![GAOperation::Sl {
+            destination,
+            operand: operand1,
+            shift: operand2,
+        }],
+        AluOp::Srl => vec![GAOperation::Srl {
+            destination,
+            operand: operand1,
+            shift: operand2,
+        }],
+        AluOp::Sra => vec![GAOperation::Sra {
+            destination,
+            operand: operand1,
+            shift: operand2,
+        }],
+        AluOp::Slt => less_than(destination, operand1, operand2, true),
+        AluOp::Sltu => less_than(destination, operand1, operand2, false),
+    }
+}
+
+/// The high word of the unsigned product of `rs1` and `rs2`, stored in the
+/// local `high`.
+fn mul_high_unsigned(rs1: Register, rs2: Register) -> Vec<GAOperation> {
+    vec![
+        GAOperation::ZeroExtend {
+            destination: local("op1"),
+            operand: register(rs1),
+            bits: 32,
+            target_bits: 64,
+        },
+        GAOperation::ZeroExtend {
+            destination: local("op2"),
+            operand: register(rs2),
+            bits: 32,
+            target_bits: 64,
+        },
+        GAOperation::Mul {
+            destination: local("product"),
+            operand1: local("op1"),
+            operand2: local("op2"),
+        },
+        GAOperation::Srl {
+            destination: local("product"),
+            operand: local("product"),
+            shift: Operand::Immediate(DataWord::Word64(32)),
+        },
+        GAOperation::Resize {
+            destination: local("high"),
+            operand: local("product"),
+            bits: 32,
+        },
+    ]
+}
+
+/// Subtracts `subtrahend` from the local `high` if `negative` is negative,
+/// turning the unsigned high word of a product in to a signed one.
+fn correct_sign(negative: Register, subtrahend: Register) -> Vec<GAOperation> {
+    vec![
+        GAOperation::Sra {
+            destination: local("mask"),
+            operand: register(negative),
+            shift: immediate(31),
+        },
+        GAOperation::And {
+            destination: local("mask"),
+            operand1: local("mask"),
+            operand2: register(subtrahend),
+        },
+        GAOperation::Sub {
+            destination: local("high"),
+            operand1: local("high"),
+            operand2: local("mask"),
+        },
+    ]
+}
+
+/// Translates the M extension.
+///
+/// The SMT division matches RISC-V for unsigned division by zero and signed
+/// overflow. A signed division by zero is forced to -1 and the remainders are
+/// computed from the quotient, which gives the dividend on division by zero.
+fn mul_div(op: MulOp, destination: Operand, rs1: Register, rs2: Register) -> Vec<GAOperation> {
+    let (operand1, operand2) = (register(rs1), register(rs2));
+    let mut operations = match op {
+        MulOp::Mul => vec![GAOperation::Mul {
+            destination: local("result"),
+            operand1,
+            operand2,
+        }],
+        MulOp::Mulhu => mul_high_unsigned(rs1, rs2),
+        MulOp::Mulh => [
+            mul_high_unsigned(rs1, rs2),
+            correct_sign(rs1, rs2),
+            correct_sign(rs2, rs1),
+        ]
+        .concat(),
+        MulOp::Mulhsu => [mul_high_unsigned(rs1, rs2), correct_sign(rs1, rs2)].concat(),
+        MulOp::Div => vec![
+            GAOperation::SDiv {
+                destination: local("result"),
+                operand1,
+                operand2: operand2.clone(),
+            },
+            GAOperation::SetZFlag(operand2),
+            GAOperation::Sub {
+                destination: local("mask"),
+                operand1: immediate(0),
+                operand2: Operand::Flag(GARegister::Z),
+            },
+            GAOperation::Or {
+                destination: local("result"),
+                operand1: local("result"),
+                operand2: local("mask"),
+            },
+        ],
+        MulOp::Divu => vec![GAOperation::UDiv {
+            destination: local("result"),
+            operand1,
+            operand2,
+        }],
+        MulOp::Rem | MulOp::Remu => vec![
+            match op {
+                MulOp::Rem => GAOperation::SDiv {
+                    destination: local("quotient"),
+                    operand1: operand1.clone(),
+                    operand2: operand2.clone(),
+                },
+                _ => GAOperation::UDiv {
+                    destination: local("quotient"),
+                    operand1: operand1.clone(),
+                    operand2: operand2.clone(),
+                },
+            },
+            GAOperation::Mul {
+                destination: local("quotient"),
+                operand1: local("quotient"),
+                operand2,
+            },
+            GAOperation::Sub {
+                destination: local("result"),
+                operand1,
+                operand2: local("quotient"),
+            },
+        ],
+    };
+    let result = match op {
+        MulOp::Mulh | MulOp::Mulhsu | MulOp::Mulhu => local("high"),
+        _ => local("result"),
+    };
+    operations.push(GAOperation::Move {
+        destination,
+        source: result,
+    });
+    operations
+}
+
+/// Translates the atomic memory operations, the old value is written to
+/// `destination` after the memory is updated.
+fn amo(op: AmoOp, destination: Operand, address: Operand, rs2: Register) -> Vec<GAOperation> {
+    let operand = register(rs2);
+    let mut operations = vec![
+        GAOperation::Move {
+            destination: local("addr"),
+            source: address,
+        },
+        GAOperation::Move {
+            destination: local("old"),
+            source: Operand::AddressInLocal("addr".to_owned(), 32),
+        },
+    ];
+    let new = local("new");
+    match op {
+        AmoOp::Swap => operations.push(GAOperation::Move {
+            destination: new.clone(),
+            source: operand,
+        }),
+        AmoOp::Add => operations.push(GAOperation::Add {
+            destination: new.clone(),
+            operand1: local("old"),
+            operand2: operand,
+        }),
+        AmoOp::Xor => operations.push(GAOperation::Xor {
+            destination: new.clone(),
+            operand1: local("old"),
+            operand2: operand,
+        }),
+        AmoOp::And => operations.push(GAOperation::And {
+            destination: new.clone(),
+            operand1: local("old"),
+            operand2: operand,
+        }),
+        AmoOp::Or => operations.push(GAOperation::Or {
+            destination: new.clone(),
+            operand1: local("old"),
+            operand2: operand,
+        }),
+        AmoOp::Min | AmoOp::Max | AmoOp::Minu | AmoOp::Maxu => {
+            let signed = matches!(op, AmoOp::Min | AmoOp::Max);
+            operations.extend(less_than(
+                local("select"),
+                local("old"),
+                operand.clone(),
+                signed,
+            ));
+            if matches!(op, AmoOp::Max | AmoOp::Maxu) {
+                operations.push(GAOperation::Xor {
+                    destination: local("select"),
+                    operand1: local("select"),
+                    operand2: immediate(1),
+                });
+            }
+            // new = operand ^ ((old ^ operand) & -select)
+            operations.extend([
+                GAOperation::Sub {
+                    destination: local("select"),
+                    operand1: immediate(0),
+                    operand2: local("select"),
+                },
+                GAOperation::Xor {
+                    destination: new.clone(),
+                    operand1: local("old"),
+                    operand2: operand.clone(),
+                },
+                GAOperation::And {
+                    destination: new.clone(),
+                    operand1: new.clone(),
+                    operand2: local("select"),
+                },
+                GAOperation::Xor {
+                    destination: new.clone(),
+                    operand1: new.clone(),
+                    operand2: operand,
+                },
+            ]);
+        }
+    }
+    operations.extend([
+        GAOperation::Move {
+            destination: Operand::AddressInLocal("addr".to_owned(), 32),
+            source: new,
+        },
+        GAOperation::Move {
+            destination,
+            source: local("old"),
+        },
+    ]);
+    operations
+}
+
+/// The control and status register `csr`, the registers that are not modeled
+/// separately share one register.
+fn csr_register(csr: u16) -> GARegister {
+    match csr {
+        0x300 => const { REGISTERS.named("mstatus") },
+        0x301 => const { REGISTERS.named("misa") },
+        0x304 => const { REGISTERS.named("mie") },
+        0x305 => const { REGISTERS.named("mtvec") },
+        0x340 => const { REGISTERS.named("mscratch") },
+        0x341 => const { REGISTERS.named("mepc") },
+        0x342 => const { REGISTERS.named("mcause") },
+        0x343 => const { REGISTERS.named("mtval") },
+        0x344 => const { REGISTERS.named("mip") },
+        0xb00 => const { REGISTERS.named("mcycle") },
+        0xb02 => const { REGISTERS.named("minstret") },
+        0xc00 => const { REGISTERS.named("cycle") },
+        0xc01 => const { REGISTERS.named("time") },
+        0xc02 => const { REGISTERS.named("instret") },
+        0xf14 => const { REGISTERS.named("mhartid") },
+        _ => const { REGISTERS.named("csr") },
+    }
+}
@@ -0,0 +1,620 @@
+//! Parses RV32IMAC machine code.
+//!
+//! Compressed instructions are expanded to the base instruction they are
+//! defined as, so the rest of the backend only has to handle the base
+//! instructions.
+
+use crate::general_assembly::arch::ParseError;
+
+/// Index of an integer register, `x0` to `x31`.
+pub type Register = u8;
+
+pub const ZERO: Register = 0;
+pub const RA: Register = 1;
+pub const SP: Register = 2;
+
+/// Operations of the register-register and register-immediate instructions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AluOp {
+    Add,
+    Sub,
+    Sll,
+    Slt,
+    Sltu,
+    Xor,
+    Srl,
+    Sra,
+    Or,
+    And,
+}
+
+/// Operations of the M extension.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MulOp {
+    Mul,
+    Mulh,
+    Mulhsu,
+    Mulhu,
+    Div,
+    Divu,
+    Rem,
+    Remu,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BranchCondition {
+    Eq,
+    Ne,
+    Lt,
+    Ge,
+    Ltu,
+    Geu,
+}
+
+/// Atomic memory operations of the A extension.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AmoOp {
+    Swap,
+    Add,
+    Xor,
+    And,
+    Or,
+    Min,
+    Max,
+    Minu,
+    Maxu,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CsrOp {
+    ReadWrite,
+    ReadSet,
+    ReadClear,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CsrSource {
+    Register(Register),
+    Immediate(u32),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Operation {
+    Lui {
+        rd: Register,
+        imm: u32,
+    },
+    Auipc {
+        rd: Register,
+        imm: u32,
+    },
+    Jal {
+        rd: Register,
+        offset: i32,
+    },
+    Jalr {
+        rd: Register,
+        rs1: Register,
+        offset: i32,
+    },
+    Branch {
+        condition: BranchCondition,
+        rs1: Register,
+        rs2: Register,
+        offset: i32,
+    },
+    Load {
+        rd: Register,
+        rs1: Register,
+        offset: i32,
+        bits: u32,
+        signed: bool,
+    },
+    Store {
+        rs1: Register,
+        rs2: Register,
+        offset: i32,
+        bits: u32,
+    },
+    OpImm {
+        op: AluOp,
+        rd: Register,
+        rs1: Register,
+        imm: i32,
+    },
+    Op {
+        op: AluOp,
+        rd: Register,
+        rs1: Register,
+        rs2: Register,
+    },
+    MulDiv {
+        op: MulOp,
+        rd: Register,
+        rs1: Register,
+        rs2: Register,
+    },
+    LoadReserved {
+        rd: Register,
+        rs1: Register,
+    },
+    StoreConditional {
+        rd: Register,
+        rs1: Register,
+        rs2: Register,
+    },
+    Amo {
+        op: AmoOp,
+        rd: Register,
+        rs1: Register,
+        rs2: Register,
+    },
+    Csr {
+        op: CsrOp,
+        rd: Register,
+        csr: u16,
+        source: CsrSource,
+    },
+    Fence,
+    Ecall,
+    Ebreak,
+    Wfi,
+    Mret,
+}
+
+/// A parsed instruction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Instruction {
+    pub operation: Operation,
+
+    /// Size of the encoding in bits, 16 for compressed instructions.
+    pub size: u32,
+}
+
+/// Parses the instruction at the start of `buff`.
+pub fn parse(buff: &[u8]) -> Result<Instruction, ParseError> {
+    let low = match buff {
+        [b0, b1, ..] => u16::from_le_bytes([*b0, *b1]),
+        _ => return Err(ParseError::InsufficientInput),
+    };
+    if low & 0b11 != 0b11 {
+        return Ok(Instruction {
+            operation: parse_compressed(low)?,
+            size: 16,
+        });
+    }
+    let word = match buff {
+        [b0, b1, b2, b3, ..] => u32::from_le_bytes([*b0, *b1, *b2, *b3]),
+        _ => return Err(ParseError::InsufficientInput),
+    };
+    Ok(Instruction {
+        operation: parse_base(word)?,
+        size: 32,
+    })
+}
+
+/// Extracts the bits `high..=low` of `word`.
+fn bits(word: u32, high: u32, low: u32) -> u32 {
+    (word >> low) & ((1 << (high - low + 1)) - 1)
+}
+
+fn bit(word: u32, index: u32) -> u32 {
+    (word >> index) & 1
+}
+
+/// Sign extends the lowest `bits` bits of `value`.
+fn sign_extend(value: u32, bits: u32) -> i32 {
+    ((value << (32 - bits)) as i32) >> (32 - bits)
+}
+
+fn parse_base(word: u32) -> Result<Operation, ParseError> {
+    let rd = bits(word, 11, 7) as Register;
+    let rs1 = bits(word, 19, 15) as Register;
+    let rs2 = bits(word, 24, 20) as Register;
+    let funct3 = bits(word, 14, 12);
+    let funct7 = bits(word, 31, 25);
+
+    let i_imm = sign_extend(bits(word, 31, 20), 12);
+    let s_imm = sign_extend((bits(word, 31, 25) << 5) | bits(word, 11, 7), 12);
+    let b_imm = sign_extend(
+        (bit(word, 31) << 12)
+            | (bit(word, 7) << 11)
+            | (bits(word, 30, 25) << 5)
+            | (bits(word, 11, 8) << 1),
+        13,
+    );
+    let j_imm = sign_extend(
+        (bit(word, 31) << 20)
+            | (bits(word, 19, 12) << 12)
+            | (bit(word, 20) << 11)
+            | (bits(word, 30, 21) << 1),
+        21,
+    );
+
+    Ok(match bits(word, 6, 0) {
+        0b011_0111 => Operation::Lui {
+            rd,
+            imm: word & 0xffff_f000,
+        },
+        0b001_0111 => Operation::Auipc {
+            rd,
+            imm: word & 0xffff_f000,
+        },
+        0b110_1111 => Operation::Jal { rd, offset: j_imm },
+        0b110_0111 if funct3 == 0 => Operation::Jalr {
+            rd,
+            rs1,
+            offset: i_imm,
+        },
+        0b110_0011 => Operation::Branch {
+            condition: match funct3 {
+                0b000 => BranchCondition::Eq,
+                0b001 => BranchCondition::Ne,
+                0b100 => BranchCondition::Lt,
+                0b101 => BranchCondition::Ge,
+                0b110 => BranchCondition::Ltu,
+                0b111 => BranchCondition::Geu,
+                _ => return Err(ParseError::InvalidInstruction),
+            },
+            rs1,
+            rs2,
+            offset: b_imm,
+        },
+        0b000_0011 => {
+            let (bits, signed) = match funct3 {
+                0b000 => (8, true),
+                0b001 => (16, true),
+                0b010 => (32, false),
+                0b100 => (8, false),
+                0b101 => (16, false),
+                _ => return Err(ParseError::InvalidInstruction),
+            };
+            Operation::Load {
+                rd,
+                rs1,
+                offset: i_imm,
+                bits,
+                signed,
+            }
+        }
+        0b010_0011 => Operation::Store {
+            rs1,
+            rs2,
+            offset: s_imm,
+            bits: match funct3 {
+                0b000 => 8,
+                0b001 => 16,
+                0b010 => 32,
+                _ => return Err(ParseError::InvalidInstruction),
+            },
+        },
+        0b001_0011 => {
+            let (op, imm) = match (funct3, funct7) {
+                (0b000, _) => (AluOp::Add, i_imm),
+                (0b010, _) => (AluOp::Slt, i_imm),
+                (0b011, _) => (AluOp::Sltu, i_imm),
+                (0b100, _) => (AluOp::Xor, i_imm),
+                (0b110, _) => (AluOp::Or, i_imm),
+                (0b111, _) => (AluOp::And, i_imm),
+                (0b001, 0b000_0000) => (AluOp::Sll, rs2 as i32),
+                (0b101, 0b000_0000) => (AluOp::Srl, rs2 as i32),
+                (0b101, 0b010_0000) => (AluOp::Sra, rs2 as i32),
+                _ => return Err(ParseError::InvalidInstruction),
+            };
+            Operation::OpImm { op, rd, rs1, imm }
+        }
+        0b011_0011 if funct7 == 0b000_0001 => Operation::MulDiv {
+            op: match funct3 {
+                0b000 => MulOp::Mul,
+                0b001 => MulOp::Mulh,
+                0b010 => MulOp::Mulhsu,
+                0b011 => MulOp::Mulhu,
+                0b100 => MulOp::Div,
+                0b101 => MulOp::Divu,
+                0b110 => MulOp::Rem,
+                _ => MulOp::Remu,
+            },
+            rd,
+            rs1,
+            rs2,
+        },
+        0b011_0011 => Operation::Op {
+            op: match (funct3, funct7) {
+                (0b000, 0b000_0000) => AluOp::Add,
+                (0b000, 0b010_0000) => AluOp::Sub,
+                (0b001, 0b000_0000) => AluOp::Sll,
+                (0b010, 0b000_0000) => AluOp::Slt,
+                (0b011, 0b000_0000) => AluOp::Sltu,
+                (0b100, 0b000_0000) => AluOp::Xor,
+                (0b101, 0b000_0000) => AluOp::Srl,
+                (0b101, 0b010_0000) => AluOp::Sra,
+                (0b110, 0b000_0000) => AluOp::Or,
+                (0b111, 0b000_0000) => AluOp::And,
+                _ => return Err(ParseError::InvalidInstruction),
+            },
+            rd,
+            rs1,
+            rs2,
+        },
+        0b000_1111 => Operation::Fence,
+        0b111_0011 => match funct3 {
+            0b000 => match word {
+                0x0000_0073 => Operation::Ecall,
+                0x0010_0073 => Operation::Ebreak,
+                0x1050_0073 => Operation::Wfi,
+                0x3020_0073 => Operation::Mret,
+                _ => return Err(ParseError::InvalidInstruction),
+            },
+            0b100 => return Err(ParseError::InvalidInstruction),
+            _ => Operation::Csr {
+                op: match funct3 & 0b11 {
+                    0b01 => CsrOp::ReadWrite,
+                    0b10 => CsrOp::ReadSet,
+                    _ => CsrOp::ReadClear,
+                },
+                rd,
+                csr: bits(word, 31, 20) as u16,
+                source: match funct3 & 0b100 {
+                    0 => CsrSource::Register(rs1),
+                    _ => CsrSource::Immediate(rs1 as u32),
+                },
+            },
+        },
+        0b010_1111 if funct3 == 0b010 => match bits(word, 31, 27) {
+            0b00010 if rs2 == 0 => Operation::LoadReserved { rd, rs1 },
+            0b00011 => Operation::StoreConditional { rd, rs1, rs2 },
+            funct5 => Operation::Amo {
+                op: match funct5 {
+                    0b00001 => AmoOp::Swap,
+                    0b00000 => AmoOp::Add,
+                    0b00100 => AmoOp::Xor,
+                    0b01100 => AmoOp::And,
+                    0b01000 => AmoOp::Or,
+                    0b10000 => AmoOp::Min,
+                    0b10100 => AmoOp::Max,
+                    0b11000 => AmoOp::Minu,
+                    0b11100 => AmoOp::Maxu,
+                    _ => return Err(ParseError::InvalidInstruction),
+                },
+                rd,
+                rs1,
+                rs2,
+            },
+        },
+        _ => return Err(ParseError::InvalidInstruction),
+    })
+}
+
+/// Expands a compressed instruction to its base instruction.
+fn parse_compressed(half: u16) -> Result<Operation, ParseError> {
+    let half = half as u32;
+    let rd = bits(half, 11, 7) as Register;
+    let rs2 = bits(half, 6, 2) as Register;
+    // The three bit register fields address x8 to x15.
+    let rd_prime = bits(half, 4, 2) as Register + 8;
+    let rs1_prime = bits(half, 9, 7) as Register + 8;
+
+    let ci_imm = sign_extend((bit(half, 12) << 5) | bits(half, 6, 2), 6);
+    let cj_offset = sign_extend(
+        (bit(half, 12) << 11)
+            | (bit(half, 11) << 4)
+            | (bits(half, 10, 9) << 8)
+            | (bit(half, 8) << 10)
+            | (bit(half, 7) << 6)
+            | (bit(half, 6) << 7)
+            | (bits(half, 5, 3) << 1)
+            | (bit(half, 2) << 5),
+        12,
+    );
+    let cb_offset = sign_extend(
+        (bit(half, 12) << 8)
+            | (bits(half, 11, 10) << 3)
+            | (bits(half, 6, 5) << 6)
+            | (bits(half, 4, 3) << 1)
+            | (bit(half, 2) << 5),
+        9,
+    );
+    let cl_offset = ((bits(half, 12, 10) << 3) | (bit(half, 6) << 2) | (bit(half, 5) << 6)) as i32;
+
+    Ok(match (half & 0b11, bits(half, 15, 13)) {
+        (_, _) if half == 0 => return Err(ParseError::InvalidInstruction),
+        // C.ADDI4SPN
+        (0b00, 0b000) => {
+            let imm = (bits(half, 12, 11) << 4)
+                | (bits(half, 10, 7) << 6)
+                | (bit(half, 6) << 2)
+                | (bit(half, 5) << 3);
+            if imm == 0 {
+                return Err(ParseError::InvalidInstruction);
+            }
+            Operation::OpImm {
+                op: AluOp::Add,
+                rd: rd_prime,
+                rs1: SP,
+                imm: imm as i32,
+            }
+        }
+        // C.LW
+        (0b00, 0b010) => Operation::Load {
+            rd: rd_prime,
+            rs1: rs1_prime,
+            offset: cl_offset,
+            bits: 32,
+            signed: false,
+        },
+        // C.SW
+        (0b00, 0b110) => Operation::Store {
+            rs1: rs1_prime,
+            rs2: rd_prime,
+            offset: cl_offset,
+            bits: 32,
+        },
+        // C.NOP and C.ADDI
+        (0b01, 0b000) => Operation::OpImm {
+            op: AluOp::Add,
+            rd,
+            rs1: rd,
+            imm: ci_imm,
+        },
+        // C.JAL
+        (0b01, 0b001) => Operation::Jal {
+            rd: RA,
+            offset: cj_offset,
+        },
+        // C.LI
+        (0b01, 0b010) => Operation::OpImm {
+            op: AluOp::Add,
+            rd,
+            rs1: ZERO,
+            imm: ci_imm,
+        },
+        // C.ADDI16SP
+        (0b01, 0b011) if rd == SP => {
+            let imm = sign_extend(
+                (bit(half, 12) << 9)
+                    | (bit(half, 6) << 4)
+                    | (bit(half, 5) << 6)
+                    | (bits(half, 4, 3) << 7)
+                    | (bit(half, 2) << 5),
+                10,
+            );
+            if imm == 0 {
+                return Err(ParseError::InvalidInstruction);
+            }
+            Operation::OpImm {
+                op: AluOp::Add,
+                rd: SP,
+                rs1: SP,
+                imm,
+            }
+        }
+        // C.LUI
+        (0b01, 0b011) => {
+            if ci_imm == 0 {
+                return Err(ParseError::InvalidInstruction);
+            }
+            Operation::Lui {
+                rd,
+                imm: (ci_imm << 12) as u32,
+            }
+        }
+        (0b01, 0b100) => {
+            let rd = rs1_prime;
+            match bits(half, 11, 10) {
+                // C.SRLI and C.SRAI, shift amounts above 31 are reserved on RV32.
+                0b00 | 0b01 if bit(half, 12) == 1 => return Err(ParseError::InvalidInstruction),
+                0b00 => Operation::OpImm {
+                    op: AluOp::Srl,
+                    rd,
+                    rs1: rd,
+                    imm: bits(half, 6, 2) as i32,
+                },
+                0b01 => Operation::OpImm {
+                    op: AluOp::Sra,
+                    rd,
+                    rs1: rd,
+                    imm: bits(half, 6, 2) as i32,
+                },
+                // C.ANDI
+                0b10 => Operation::OpImm {
+                    op: AluOp::And,
+                    rd,
+                    rs1: rd,
+                    imm: ci_imm,
+                },
+                _ if bit(half, 12) == 1 => return Err(ParseError::InvalidInstruction),
+                _ => Operation::Op {
+                    op: match bits(half, 6, 5) {
+                        0b00 => AluOp::Sub,
+                        0b01 => AluOp::Xor,
+                        0b10 => AluOp::Or,
+                        _ => AluOp::And,
+                    },
+                    rd,
+                    rs1: rd,
+                    rs2: rd_prime,
+                },
+            }
+        }
+        // C.J
+        (0b01, 0b101) => Operation::Jal {
+            rd: ZERO,
+            offset: cj_offset,
+        },
+        // C.BEQZ and C.BNEZ
+        (0b01, 0b110 | 0b111) => Operation::Branch {
+            condition: match bits(half, 15, 13) {
+                0b110 => BranchCondition::Eq,
+                _ => BranchCondition::Ne,
+            },
+            rs1: rs1_prime,
+            rs2: ZERO,
+            offset: cb_offset,
+        },
+        // C.SLLI
+        (0b10, 0b000) => {
+            if bit(half, 12) == 1 {
+                return Err(ParseError::InvalidInstruction);
+            }
+            Operation::OpImm {
+                op: AluOp::Sll,
+                rd,
+                rs1: rd,
+                imm: bits(half, 6, 2) as i32,
+            }
+        }
+        // C.LWSP
+        (0b10, 0b010) => {
+            if rd == ZERO {
+                return Err(ParseError::InvalidInstruction);
+            }
+            Operation::Load {
+                rd,
+                rs1: SP,
+                offset: ((bit(half, 12) << 5) | (bits(half, 6, 4) << 2) | (bits(half, 3, 2) << 6))
+                    as i32,
+                bits: 32,
+                signed: false,
+            }
+        }
+        (0b10, 0b100) => match (bit(half, 12), rd, rs2) {
+            (0, ZERO, ZERO) => return Err(ParseError::InvalidInstruction),
+            // C.JR
+            (0, _, ZERO) => Operation::Jalr {
+                rd: ZERO,
+                rs1: rd,
+                offset: 0,
+            },
+            // C.MV
+            (0, _, _) => Operation::Op {
+                op: AluOp::Add,
+                rd,
+                rs1: ZERO,
+                rs2,
+            },
+            (_, ZERO, ZERO) => Operation::Ebreak,
+            // C.JALR
+            (_, _, ZERO) => Operation::Jalr {
+                rd: RA,
+                rs1: rd,
+                offset: 0,
+            },
+            // C.ADD
+            (_, _, _) => Operation::Op {
+                op: AluOp::Add,
+                rd,
+                rs1: rd,
+                rs2,
+            },
+        },
+        // C.SWSP
+        (0b10, 0b110) => Operation::Store {
+            rs1: SP,
+            rs2,
+            offset: ((bits(half, 12, 9) << 2) | (bits(half, 8, 7) << 6)) as i32,
+            bits: 32,
+        },
+        // Floating point loads and stores.
+        _ => return Err(ParseError::InvalidInstruction),
+    })
+}
@@ -0,0 +1,266 @@
+use std::collections::HashMap;
+
+use general_assembly::register::Register;
+
+use super::{
+    parser::{parse, AluOp, Operation},
+    riscv_arch,
+    supported_arch,
+    RiscV32IMAC,
+    REGISTERS,
+};
+use crate::{
+    general_assembly::{
+        arch::Arch,
+        executor::PathResult,
+        project::{
+            target_config::{ArmMCore, CoreFamily, TargetConfiguration},
+            PCHook,
+            Project,
+        },
+        state::GAState,
+        vm::VM,
+        Endianness,
+        WordSize,
+    },
+    smt::{DContext, DSolver},
+};
+
+/// Sets up a vm running `code` from address 0 that returns to 0x1000.
+fn setup_test_vm_with_code(code: &[u8]) -> VM<RiscV32IMAC> {
+    setup_test_vm_with_target(code, RiscV32IMAC::default_target())
+}
+
+fn setup_test_vm_with_target(code: &[u8], target: TargetConfiguration) -> VM<RiscV32IMAC> {
+    // Instructions are read a word at a time so the code is padded.
+    let program: Vec<u8> = code.iter().chain(&[0, 0]).copied().collect();
+    let end = program.len() as u64;
+    let mut project = Box::new(Project::manual_project(
+        program,
+        0,
+        end,
+        WordSize::Bit32,
+        Endianness::Little,
+        HashMap::new(),
+        HashMap::new(),
+        Vec::new(),
+        Vec::new(),
+        HashMap::new(),
+        vec![],
+        HashMap::new(),
+        vec![],
+    ));
+    project.set_target(target);
+    let arch = RiscV32IMAC;
+    project.add_hooks(&arch);
+    project.add_pc_hook(0x1000, PCHook::EndSuccess);
+
+    let project = Box::leak(project);
+    let context = Box::new(DContext::new());
+    let context = Box::leak(context);
+    let solver = DSolver::new(context);
+    let mut state = GAState::create_test_state(project, context, solver, 0, 0x2000_0000, arch);
+    state
+        .set_register(Register::LR, context.from_u64(0x1000, 32))
+        .unwrap();
+    VM::new_with_state(project, state)
+}
+
+/// Runs `code` to the end of the first path and returns the final state.
+fn run(code: &[u8]) -> GAState<RiscV32IMAC> {
+    let mut vm = setup_test_vm_with_code(code);
+    let (result, state) = vm.run().unwrap().unwrap();
+    assert!(matches!(result, PathResult::Success(_)));
+    state
+}
+
+fn register(state: &mut GAState<RiscV32IMAC>, register: &str) -> u64 {
+    state
+        .get_register(REGISTERS.named(register))
+        .unwrap()
+        .get_constant()
+        .expect("Could not get register as constant")
+}
+
+#[test]
+fn test_parse_compressed() {
+    // c.addi4spn a0, sp, 16 is addi a0, sp, 16
+    let compressed = parse(&[0x08, 0x08]).unwrap();
+    let base = parse(&0x0101_0513u32.to_le_bytes()).unwrap();
+    assert_eq!(compressed.size, 16);
+    assert_eq!(base.size, 32);
+    assert_eq!(compressed.operation, base.operation);
+    assert_eq!(base.operation, Operation::OpImm {
+        op: AluOp::Add,
+        rd: 10,
+        rs1: 2,
+        imm: 16
+    });
+
+    // c.lw a0, 12(sp) and c.sw a0, 12(sp)
+    assert_eq!(
+        parse(&[0x32, 0x45]).unwrap().operation,
+        parse(&0x00c1_2503u32.to_le_bytes()).unwrap().operation
+    );
+    assert_eq!(
+        parse(&[0x2a, 0xc6]).unwrap().operation,
+        parse(&0x00a1_2623u32.to_le_bytes()).unwrap().operation
+    );
+
+    assert!(parse(&[0x00, 0x00]).is_err());
+    assert!(parse(&[0x13]).is_err());
+}
+
+#[test]
+fn test_alu() {
+    let mut state = run(&[
+        0x65, 0x55, // li a0, -7
+        0x8d, 0x45, // li a1, 3
+        0x33, 0x26, 0xb5, 0x00, // slt a2, a0, a1
+        0xb3, 0x36, 0xb5, 0x00, // sltu a3, a0, a1
+        0x13, 0x57, 0x15, 0x40, // srai a4, a0, 1
+        0x93, 0x02, 0x10, 0x02, // li t0, 33
+        0xb3, 0x97, 0x55, 0x00, // sll a5, a1, t0
+        0x13, 0x00, 0x50, 0x00, // li zero, 5
+        0x33, 0x88, 0xa5, 0x40, // sub a6, a1, a0
+        0x82, 0x80, // ret
+    ]);
+    assert_eq!(register(&mut state, "a2"), 1);
+    assert_eq!(register(&mut state, "a3"), 0);
+    assert_eq!(register(&mut state, "a4"), (-4i32) as u32 as u64);
+    // Only the five lowest bits of the shift amount are used.
+    assert_eq!(register(&mut state, "a5"), 6);
+    assert_eq!(register(&mut state, "zero"), 0);
+    assert_eq!(register(&mut state, "a6"), 10);
+}
+
+#[test]
+fn test_mul_div() {
+    let mut state = run(&[
+        0x9d, 0x45, // li a1, 7
+        0x81, 0x42, // li t0, 0
+        0x33, 0xc5, 0x55, 0x02, // div a0, a1, t0
+        0x33, 0xe6, 0x55, 0x02, // rem a2, a1, t0
+        0xb3, 0xd6, 0x55, 0x02, // divu a3, a1, t0
+        0x37, 0x03, 0x00, 0x80, // lui t1, 0x80000
+        0xfd, 0x53, // li t2, -1
+        0x33, 0x47, 0x73, 0x02, // div a4, t1, t2
+        0xb3, 0x67, 0x73, 0x02, // rem a5, t1, t2
+        0xf9, 0x52, // li t0, -2
+        0x0d, 0x4e, // li t3, 3
+        0x33, 0x98, 0xc2, 0x03, // mulh a6, t0, t3
+        0xb3, 0xb8, 0x73, 0x02, // mulhu a7, t2, t2
+        0x33, 0xa9, 0x73, 0x02, // mulhsu s2, t2, t2
+        0xb3, 0x89, 0xc2, 0x03, // mul s3, t0, t3
+        0x82, 0x80, // ret
+    ]);
+    // Division by zero gives all ones and the remainder is the dividend.
+    assert_eq!(register(&mut state, "a0"), 0xffff_ffff);
+    assert_eq!(register(&mut state, "a2"), 7);
+    assert_eq!(register(&mut state, "a3"), 0xffff_ffff);
+
+    // Signed overflow gives the dividend and a zero remainder.
+    assert_eq!(register(&mut state, "a4"), 0x8000_0000);
+    assert_eq!(register(&mut state, "a5"), 0);
+
+    assert_eq!(register(&mut state, "a6"), 0xffff_ffff);
+    assert_eq!(register(&mut state, "a7"), 0xffff_fffe);
+    assert_eq!(register(&mut state, "s2"), 0xffff_ffff);
+    assert_eq!(register(&mut state, "s3"), (-6i32) as u32 as u64);
+}
+
+#[test]
+fn test_load_store_atomic() {
+    let mut state = run(&[
+        0x37, 0x05, 0x00, 0x10, // lui a0, 0x10000
+        0x93, 0x05, 0x00, 0xf8, // li a1, -128
+        0x4c, 0xc1, // sw a1, 4(a0)
+        0x03, 0x06, 0x45, 0x00, // lb a2, 4(a0)
+        0x83, 0x46, 0x45, 0x00, // lbu a3, 4(a0)
+        0x03, 0x57, 0x45, 0x00, // lhu a4, 4(a0)
+        0x95, 0x42, // li t0, 5
+        0x23, 0x20, 0x55, 0x00, // sw t0, 0(a0)
+        0xaf, 0x27, 0x55, 0x00, // amoadd.w a5, t0, (a0)
+        0x03, 0x28, 0x05, 0x00, // lw a6, 0(a0)
+        0x2f, 0x23, 0xb5, 0xe0, // amomaxu.w t1, a1, (a0)
+        0x83, 0x28, 0x05, 0x00, // lw a7, 0(a0)
+        0x2f, 0x29, 0x05, 0x10, // lr.w s2, (a0)
+        0xaf, 0x29, 0x55, 0x18, // sc.w s3, t0, (a0)
+        0x82, 0x80, // ret
+    ]);
+    assert_eq!(register(&mut state, "a2"), 0xffff_ff80);
+    assert_eq!(register(&mut state, "a3"), 0x80);
+    assert_eq!(register(&mut state, "a4"), 0xff80);
+
+    assert_eq!(register(&mut state, "a5"), 5);
+    assert_eq!(register(&mut state, "a6"), 10);
+    assert_eq!(register(&mut state, "t1"), 10);
+    assert_eq!(register(&mut state, "a7"), 0xffff_ff80);
+
+    // The reservation is never lost so the store conditional succeeds.
+    assert_eq!(register(&mut state, "s2"), 0xffff_ff80);
+    assert_eq!(register(&mut state, "s3"), 0);
+    let address = state.ctx.from_u64(0x1000_0000, 32);
+    let stored = state.memory.read(&address, 32).unwrap();
+    assert_eq!(stored.get_constant(), Some(5));
+}
+
+/// Calls a function that branches on a0.
+const CALL: [u8; 18] = [
+    0x06, 0x84, // mv s0, ra
+    0x19, 0x20, // jal 6
+    0xa2, 0x80, // mv ra, s0
+    0x82, 0x80, // ret
+    0x19, 0xc1, // beqz a0, 6
+    0x85, 0x45, // li a1, 1
+    0x82, 0x80, // ret
+    0x89, 0x45, // li a1, 2
+    0x82, 0x80, // ret
+];
+
+#[test]
+fn test_call_and_branch() {
+    let mut vm = setup_test_vm_with_code(&CALL);
+    let mut paths = vec![];
+    while let Some((result, mut state)) = vm.run().unwrap() {
+        assert!(matches!(result, PathResult::Success(_)));
+        paths.push((register(&mut state, "a1"), state.cycle_count));
+    }
+    paths.sort();
+    // One alu instruction, a call and two returns around the branch.
+    let calls = 1 + 3 + 1 + 3 + 1 + 3;
+    assert_eq!(paths, [(1, calls + 1), (2, calls + 3)]);
+}
+
+#[test]
+fn test_attributes() {
+    // Built from a section emitted by GCC for -march=rv32imac_zicsr.
+    let arch = b"rv32i2p1_m2p0_a2p1_c2p0_zicsr2p0\0";
+    let mut attributes = vec![1];
+    attributes.extend((5 + 3 + arch.len() as u32).to_le_bytes());
+    attributes.extend([4, 16, 5]);
+    attributes.extend(arch);
+    let mut subsection = b"riscv\0".to_vec();
+    subsection.extend(attributes);
+    let mut section = vec![b'A'];
+    section.extend((subsection.len() as u32 + 4).to_le_bytes());
+    section.extend(subsection);
+
+    let found = riscv_arch(&section, Endianness::Little).unwrap().unwrap();
+    assert_eq!(found, "rv32i2p1_m2p0_a2p1_c2p0_zicsr2p0");
+    assert_eq!(supported_arch(&found), Some(true));
+
+    assert_eq!(supported_arch("rv32imac"), Some(true));
+    assert_eq!(supported_arch("rv32imafc"), Some(false));
+    assert_eq!(supported_arch("rv32i2p1_zba1p0"), Some(false));
+    assert_eq!(supported_arch("rv64imac"), None);
+    assert!(riscv_arch(b"B", Endianness::Little).is_err());
+    assert!(riscv_arch(&section[..section.len() - 4], Endianness::Little).is_err());
+}
+
+#[test]
+fn test_incompatible_target() {
+    let arm = TargetConfiguration::zero_wait_states(CoreFamily::ArmM(ArmMCore::ArmM4));
+    let mut vm = setup_test_vm_with_target(&CALL, arm);
+    assert!(vm.run().is_err());
+}
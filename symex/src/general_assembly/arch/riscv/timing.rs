@@ -0,0 +1,53 @@
+//! Cycle model for the RV32IMAC cores.
+//!
+//! Every instruction class has a fixed cycle count given by the
+//! [`RiscVCycles`] of the target, only conditional branches depend on the
+//! execution state.
+
+use super::{
+    parser::{MulOp, Operation},
+    RiscV32IMAC,
+};
+use crate::general_assembly::{
+    instruction::CycleCount,
+    project::target_config::RiscVCycles,
+    state::GAState,
+};
+
+/// A conditional branch, the count depends on whether the branch was taken.
+fn branch(state: &mut GAState<RiscV32IMAC>) -> usize {
+    let cycles = state
+        .project
+        .get_target()
+        .riscv_cycles()
+        .unwrap_or_default();
+    match state.get_has_jumped() {
+        true => cycles.branch_taken,
+        false => cycles.branch_not_taken,
+    }
+}
+
+/// The number of cycles needed to execute `operation`.
+pub fn cycle_count(operation: &Operation, cycles: &RiscVCycles) -> CycleCount<RiscV32IMAC> {
+    CycleCount::Value(match operation {
+        Operation::Branch { .. } => return CycleCount::Function(branch),
+        Operation::Lui { .. }
+        | Operation::Auipc { .. }
+        | Operation::OpImm { .. }
+        | Operation::Op { .. } => cycles.alu,
+        Operation::Jal { .. } | Operation::Jalr { .. } | Operation::Mret => cycles.jump,
+        Operation::Load { .. } | Operation::LoadReserved { .. } => cycles.load,
+        Operation::Store { .. } | Operation::StoreConditional { .. } => cycles.store,
+        Operation::MulDiv {
+            op: MulOp::Mul | MulOp::Mulh | MulOp::Mulhsu | MulOp::Mulhu,
+            ..
+        } => cycles.mul,
+        Operation::MulDiv { .. } => cycles.div,
+        Operation::Amo { .. } => cycles.amo,
+        Operation::Csr { .. }
+        | Operation::Fence
+        | Operation::Ecall
+        | Operation::Ebreak
+        | Operation::Wfi => cycles.system,
+    })
+}
@@ -5,6 +5,7 @@ use std::collections::HashMap;
 use general_assembly::{
     operand::{DataWord, Operand},
     operation::Operation,
+    register::Register,
     shift::Shift,
 };
 use tracing::{debug, trace};
@@ -13,15 +14,22 @@ use super::{
     arch::Arch,
     instruction::Instruction,
     project::Project,
-    state::{ContinueInsideInstruction, GAState},
+    state::{ConcretizedBranch, ContinueInsideInstruction, GAState},
     vm::VM,
     Result,
+    WordSize,
 };
 use crate::{
     general_assembly::{path_selection::Path, state::HookOrInstruction},
     smt::{smt_boolector::BoolectorSolverContext, DExpr, SolverError},
 };
 
+/// Checks if `register` is the program counter or the program counter as
+/// read by an instruction.
+fn is_pc<A: Arch>(register: Register) -> bool {
+    register == Register::PC || Some(register) == const { A::REGISTERS.get("PC+") }
+}
+
 pub struct GAExecutor<'vm, A: Arch> {
     pub vm: &'vm mut VM<A>,
     pub state: GAState<A>,
@@ -56,57 +64,98 @@ impl<'vm, A: Arch> GAExecutor<'vm, A> {
     }
 
     pub fn resume_execution(&mut self) -> Result<PathResult> {
+        loop {
+            if let Some(result) = self.step()? {
+                return Ok(result);
+            }
+        }
+    }
+
+    /// Executes the next instruction or hook, returns the result of the path
+    /// if it ended.
+    ///
+    /// If the path was forked inside of an instruction the rest of that
+    /// instruction is executed first.
+    pub fn step(&mut self) -> Result<Option<PathResult>> {
         let possible_continue = self.state.continue_in_instruction.to_owned();
 
         if let Some(i) = possible_continue {
             self.continue_executing_instruction(&i)?;
             self.state.continue_in_instruction = None;
+            if let Some(result) = self.failed() {
+                return Ok(Some(result));
+            }
+            self.state.increment_cycle_count();
             self.state.set_last_instruction(i.instruction);
+            return Ok(None);
         }
 
-        loop {
-            let instruction = match self.state.get_next_instruction()? {
-                HookOrInstruction::Instruction(v) => v,
-                HookOrInstruction::PcHook(hook) => match hook {
-                    crate::general_assembly::project::PCHook::Continue => {
-                        debug!("Continuing");
-                        let lr = self.state.get_register("LR".to_owned()).unwrap();
-                        self.state.set_register("PC".to_owned(), lr)?;
-                        continue;
-                    }
-                    crate::general_assembly::project::PCHook::EndSuccess => {
-                        debug!("Symbolic execution ended successfully");
-                        self.state.increment_cycle_count();
-                        return Ok(PathResult::Success(None));
-                    }
-                    crate::general_assembly::project::PCHook::EndFailure(reason) => {
-                        debug!("Symbolic execution ended unsuccessfully");
-                        let data = *reason;
-                        self.state.increment_cycle_count();
-                        return Ok(PathResult::Failure(data));
-                    }
-                    crate::general_assembly::project::PCHook::Suppress => {
-                        self.state.increment_cycle_count();
-                        return Ok(PathResult::Suppress);
-                    }
-                    crate::general_assembly::project::PCHook::Intrinsic(f) => {
-                        f(&mut self.state)?;
+        let instruction = match self.state.get_next_instruction()? {
+            HookOrInstruction::Instruction(v) => v,
+            HookOrInstruction::PcHook(hook) => match hook {
+                crate::general_assembly::project::PCHook::Continue => {
+                    debug!("Continuing");
+                    let lr = self.state.get_register(Register::LR).unwrap();
+                    self.state.set_register(Register::PC, lr)?;
+                    return Ok(None);
+                }
+                crate::general_assembly::project::PCHook::EndSuccess => {
+                    debug!("Symbolic execution ended successfully");
+                    return Ok(Some(PathResult::Success(None)));
+                }
+                crate::general_assembly::project::PCHook::EndFailure(reason) => {
+                    debug!("Symbolic execution ended unsuccessfully");
+                    let data = *reason;
+                    return Ok(Some(PathResult::Failure(data)));
+                }
+                crate::general_assembly::project::PCHook::Suppress => {
+                    return Ok(Some(PathResult::Suppress));
+                }
+                crate::general_assembly::project::PCHook::Intrinsic(f) => {
+                    f(&mut self.state)?;
 
-                        // set last instruction to empty to no count instruction twice
-                        self.state.last_instruction = None;
-                        continue;
-                    }
-                },
-            };
+                    // the intrinsic replaces a call so nothing is left in the pipeline
+                    self.state.reset_pipeline();
+                    return Ok(None);
+                }
+            },
+        };
 
-            // Add cycles to cycle count
-            self.state.increment_cycle_count();
+        trace!("executing instruction: {:?}", instruction);
+        self.execute_instruction(&instruction)?;
+        if let Some(result) = self.failed() {
+            return Ok(Some(result));
+        }
 
-            trace!("executing instruction: {:?}", instruction);
-            self.execute_instruction(&instruction)?;
+        // Add cycles to cycle count, the last instruction is still the one
+        // before this so that the cost can depend on it.
+        self.state.increment_cycle_count();
 
-            self.state.set_last_instruction(instruction);
+        self.state.set_last_instruction(instruction);
+        Ok(None)
+    }
+
+    /// Returns the result of the path if an operation failed it.
+    fn failed(&mut self) -> Option<PathResult> {
+        let (reason, _) = self.state.take_failure()?;
+        debug!("Symbolic execution ended unsuccessfully: {reason}");
+        Some(PathResult::Failure(reason))
+    }
+
+    /// Forks the paths where a failure raised by the current operation does
+    /// not happen so that they continue after it, returns true if this path
+    /// failed.
+    fn fork_failure(&mut self, local: &HashMap<String, DExpr>) -> Result<bool> {
+        let Some((reason, condition)) = self.state.take_failure() else {
+            return Ok(false);
+        };
+        if condition.get_constant_bool() != Some(true) {
+            self.continue_forks_at(self.current_operation_index + 1, local);
+            self.fork(condition.not())?;
+            self.state.assert(&condition);
         }
+        self.state.fail(reason, condition);
+        Ok(true)
     }
 
     // Fork execution. Will create a new path with `constraint`.
@@ -116,6 +165,9 @@ impl<'vm, A: Arch> GAExecutor<'vm, A> {
         let path = Path::new(forked_state, Some(constraint));
 
         self.vm.paths.save_path(path);
+
+        // This path continues executing the instruction directly.
+        self.state.continue_in_instruction = None;
         Ok(())
     }
 
@@ -133,6 +185,7 @@ impl<'vm, A: Arch> GAExecutor<'vm, A> {
     /// memory.
     fn get_memory(&mut self, address: u64, bits: u32) -> Result<DExpr> {
         trace!("Getting memory addr: {:?}", address);
+        self.state.data_accesses.push(address);
         // check for hook and return early
         if let Some(hook) = self.project.get_memory_read_hook(address) {
             return hook(&mut self.state, address);
@@ -151,6 +204,11 @@ impl<'vm, A: Arch> GAExecutor<'vm, A> {
                     .state
                     .ctx
                     .from_u64(self.project.get_byte(address)? as u64, 8))
+            } else if bits == 16 {
+                // half word on 64 bit architectures
+                Ok(self.get_dexpr_from_dataword(
+                    self.project.get_word_internal(address, WordSize::Bit16)?,
+                ))
             } else {
                 todo!()
             }
@@ -167,6 +225,8 @@ impl<'vm, A: Arch> GAExecutor<'vm, A> {
     /// Sets the memory at `address` to `data`.
     fn set_memory(&mut self, data: DExpr, address: u64, bits: u32) -> Result<()> {
         trace!("Setting memory addr: {:?}", address);
+        self.state.data_accesses.push(address);
+        self.state.memory_writes.push((address, data.clone()));
         // check for hook and return early
         if let Some(hook) = self.project.get_memory_write_hook(address) {
             return hook(&mut self.state, address, data, bits);
@@ -193,7 +253,7 @@ impl<'vm, A: Arch> GAExecutor<'vm, A> {
         local: &HashMap<String, DExpr>,
     ) -> Result<DExpr> {
         let ret = match operand {
-            Operand::Register(name) => Ok(self.state.get_register(name.to_owned())?),
+            Operand::Register(register) => Ok(self.state.get_register(*register)?),
             Operand::Immediate(v) => Ok(self.get_dexpr_from_dataword(v.to_owned())),
             Operand::Address(address, width) => {
                 let address = self.get_dexpr_from_dataword(*address);
@@ -213,7 +273,7 @@ impl<'vm, A: Arch> GAExecutor<'vm, A> {
                 self.get_memory(address, *width)
             }
             Operand::Flag(f) => {
-                let value = self.state.get_flag(f.clone());
+                let value = self.state.get_flag(*f);
                 match value {
                     Some(value) => Ok(value.resize_unsigned(self.project.get_word_size())),
                     None => todo!(),
@@ -232,9 +292,13 @@ impl<'vm, A: Arch> GAExecutor<'vm, A> {
         local: &mut HashMap<String, DExpr>,
     ) -> Result<()> {
         match operand {
+            Operand::Register(v) if is_pc::<A>(*v) => {
+                trace!("Setting register {} to {:?}", v, value);
+                self.set_branch_target(*v, value, local)?
+            }
             Operand::Register(v) => {
                 trace!("Setting register {} to {:?}", v, value);
-                self.state.set_register(v.to_owned(), value)?
+                self.state.set_register(*v, value)?
             }
             Operand::Immediate(_) => panic!(), // not prohibited change to error later
             Operand::AddressInLocal(local_name, width) => {
@@ -261,7 +325,7 @@ impl<'vm, A: Arch> GAExecutor<'vm, A> {
                 //
                 // Might be a good thing to throw an error here if the value is not 0 or 1.
                 self.state
-                    .set_flag(f.clone(), value.resize_unsigned(1).simplify());
+                    .set_flag(*f, value.resize_unsigned(1).simplify());
             }
         }
         Ok(())
@@ -289,27 +353,7 @@ impl<'vm, A: Arch> GAExecutor<'vm, A> {
 
                 // create paths for all but the first address
                 for addr in &addresses[1..] {
-                    if self.current_operation_index
-                        < self
-                            .state
-                            .current_instruction
-                            .as_ref()
-                            .unwrap()
-                            .operations
-                            .len()
-                            - 1
-                    {
-                        self.state.continue_in_instruction = Some(ContinueInsideInstruction {
-                            instruction: self
-                                .state
-                                .current_instruction
-                                .as_ref()
-                                .unwrap()
-                                .to_owned(),
-                            index: self.current_operation_index,
-                            local: local.clone(),
-                        })
-                    }
+                    self.continue_forks_at(self.current_operation_index, local);
 
                     let constraint = address._eq(addr);
                     self.fork(constraint)?;
@@ -317,14 +361,103 @@ impl<'vm, A: Arch> GAExecutor<'vm, A> {
 
                 // assert first address and return concrete
                 let concrete_address = &addresses[0];
-                self.state
-                    .constraints
-                    .assert(&address._eq(concrete_address));
+                self.state.assert(&address._eq(concrete_address));
                 Ok(concrete_address.get_constant().unwrap())
             }
         }
     }
 
+    /// Writes the target of a branch to `register`.
+    ///
+    /// If the target is symbolic the path is forked for every possible target
+    /// and the target is concrete on each path. When there are more possible
+    /// targets than [`Project::get_max_branch_targets`] only function entry
+    /// points are considered.
+    fn set_branch_target(
+        &mut self,
+        register: Register,
+        target: DExpr,
+        local: &HashMap<String, DExpr>,
+    ) -> Result<()> {
+        if target.get_constant().is_some() {
+            return self.state.set_register(register, target);
+        }
+
+        let max_targets = self.project.get_max_branch_targets();
+        let (targets, function_entries) =
+            match self.state.constraints.get_values(&target, max_targets)? {
+                crate::smt::Solutions::Exactly(targets) => (
+                    targets
+                        .iter()
+                        .map(|value| (value.get_constant().unwrap(), target._eq(value)))
+                        .collect::<Vec<_>>(),
+                    false,
+                ),
+                crate::smt::Solutions::AtLeast(_) => {
+                    debug!(
+                        "More than {} targets for branch at {:#X}, using function entries",
+                        max_targets, self.state.last_pc
+                    );
+                    // The thumb bit is not part of the entry point, nor of the address that is
+                    // written.
+                    let mask = self.state.ctx.from_u64(!1, self.project.get_ptr_size());
+                    let mut entries = vec![];
+                    for entry in self.project.get_function_entries() {
+                        let entry = entry & !1;
+                        let address = self.state.ctx.from_u64(entry, self.project.get_ptr_size());
+                        let constraint = target.and(&mask)._eq(&address);
+                        if self.state.constraints.is_sat_with_constraint(&constraint)? {
+                            entries.push((entry, constraint));
+                        }
+                    }
+                    if entries.len() > max_targets {
+                        return Err(SolverError::TooManySolutions.into());
+                    }
+                    (entries, true)
+                }
+            };
+        trace!("{} possible branch targets", targets.len());
+
+        let address = self.state.last_pc;
+        let branch = |target| ConcretizedBranch {
+            address,
+            target,
+            targets: targets.len(),
+            function_entries,
+        };
+        let Some(((first, first_constraint), rest)) = targets.split_first() else {
+            return Err(SolverError::Unsat.into());
+        };
+
+        // create paths for all but the first target
+        for (other, constraint) in rest {
+            let record = branch(*other);
+            self.state.concretized_branches.push(record);
+            let value = self.state.ctx.from_u64(*other, self.project.get_ptr_size());
+            self.state.set_register(register, value)?;
+
+            self.continue_forks_at(self.current_operation_index + 1, local);
+            self.fork(constraint.to_owned())?;
+            self.state.concretized_branches.pop();
+        }
+
+        self.state.concretized_branches.push(branch(*first));
+        self.state.assert(first_constraint);
+        let value = self.state.ctx.from_u64(*first, self.project.get_ptr_size());
+        self.state.set_register(register, value)
+    }
+
+    /// Makes the next forked path continue the current instruction from the
+    /// operation at `index`, so that the instruction is completed and its
+    /// cycles are counted on the forked path as well.
+    fn continue_forks_at(&mut self, index: usize, local: &HashMap<String, DExpr>) {
+        self.state.continue_in_instruction = Some(ContinueInsideInstruction {
+            instruction: self.state.current_instruction.as_ref().unwrap().to_owned(),
+            index,
+            local: local.to_owned(),
+        });
+    }
+
     fn continue_executing_instruction(
         &mut self,
         inst_to_continue: &ContinueInsideInstruction<A>,
@@ -335,6 +468,9 @@ impl<'vm, A: Arch> GAExecutor<'vm, A> {
             let operation = &inst_to_continue.instruction.operations[i];
             self.current_operation_index = i;
             self.execute_operation(operation, &mut local)?;
+            if self.fork_failure(&local)? {
+                break;
+            }
         }
         Ok(())
     }
@@ -342,12 +478,12 @@ impl<'vm, A: Arch> GAExecutor<'vm, A> {
     /// Execute a single instruction.
     pub(crate) fn execute_instruction(&mut self, i: &Instruction<A>) -> Result<()> {
         // update last pc
-        let new_pc = self.state.get_register("PC".to_owned())?;
+        let new_pc = self.state.get_register(Register::PC)?;
         self.state.last_pc = new_pc.get_constant().unwrap();
 
         // Always increment pc before executing the operations
         self.state.set_register(
-            "PC".to_owned(),
+            Register::PC,
             new_pc.add(
                 &self
                     .state
@@ -358,6 +494,8 @@ impl<'vm, A: Arch> GAExecutor<'vm, A> {
 
         // reset has branched before execution of instruction.
         self.state.reset_has_jumped();
+        self.state.data_accesses.clear();
+        self.state.memory_writes.clear();
 
         // increment instruction count before execution
         // so that forked path count this instruction
@@ -374,8 +512,10 @@ impl<'vm, A: Arch> GAExecutor<'vm, A> {
                     let false_possible = self.state.constraints.is_sat_with_constraint(&c.not())?;
 
                     if true_possible && false_possible {
+                        // The forked path skips the instruction.
+                        self.continue_forks_at(i.operations.len(), &HashMap::new());
                         self.fork(c.not())?;
-                        self.state.constraints.assert(&c);
+                        self.state.assert(&c);
                     }
 
                     true_possible
@@ -390,6 +530,9 @@ impl<'vm, A: Arch> GAExecutor<'vm, A> {
             for (n, operation) in i.operations.iter().enumerate() {
                 self.current_operation_index = n;
                 self.execute_operation(operation, &mut local)?;
+                if self.fork_failure(&local)? {
+                    break;
+                }
             }
         }
 
@@ -522,7 +665,7 @@ impl<'vm, A: Arch> GAExecutor<'vm, A> {
                         ret.or(&self
                             .state
                             // Set the carry bit right above the last bit
-                            .get_flag("C".to_owned())
+                            .get_flag(Register::C)
                             .unwrap()
                             .sll(&shift_amount.add(&self.state.ctx.from_u64(1, 32))))
                     }
@@ -595,7 +738,7 @@ impl<'vm, A: Arch> GAExecutor<'vm, A> {
                     if constant_c {
                         self.state.set_has_jumped();
                         let destination = dest_value;
-                        self.state.set_register("PC".to_owned(), destination)?;
+                        self.set_branch_target(Register::PC, destination, local)?;
                     }
                     return Ok(());
                 }
@@ -610,29 +753,9 @@ impl<'vm, A: Arch> GAExecutor<'vm, A> {
 
                 let destination: DExpr = match (true_possible, false_possible) {
                     (true, true) => {
-                        if self.current_operation_index
-                            < (self
-                                .state
-                                .current_instruction
-                                .as_ref()
-                                .unwrap()
-                                .operations
-                                .len()
-                                - 1)
-                        {
-                            self.state.continue_in_instruction = Some(ContinueInsideInstruction {
-                                instruction: self
-                                    .state
-                                    .current_instruction
-                                    .as_ref()
-                                    .unwrap()
-                                    .to_owned(),
-                                index: self.current_operation_index + 1,
-                                local: local.to_owned(),
-                            });
-                        }
+                        self.continue_forks_at(self.current_operation_index + 1, local);
                         self.fork(c.not())?;
-                        self.state.constraints.assert(&c);
+                        self.state.assert(&c);
                         self.state.set_has_jumped();
                         Ok(dest_value)
                     }
@@ -640,28 +763,27 @@ impl<'vm, A: Arch> GAExecutor<'vm, A> {
                         self.state.set_has_jumped();
                         Ok(dest_value)
                     }
-                    (false, true) => Ok(self.state.get_register("PC".to_owned())?), /* safe to asume PC exist */
+                    (false, true) => Ok(self.state.get_register(Register::PC)?), /* safe to asume PC
+                                                                           * exist */
                     (false, false) => Err(SolverError::Unsat),
                 }?;
 
-                self.state.set_register("PC".to_owned(), destination)?;
+                self.set_branch_target(Register::PC, destination, local)?;
             }
             Operation::ConditionalExecution { conditions } => {
                 self.state.add_instruction_conditions(conditions);
             }
             Operation::SetNFlag(operand) => {
                 let value = self.get_operand_value(operand, local)?;
-                let shift = self
-                    .state
-                    .ctx
-                    .from_u64((self.project.get_word_size() - 1) as u64, 32);
+                let word_size = self.project.get_word_size();
+                let shift = self.state.ctx.from_u64((word_size - 1) as u64, word_size);
                 let result = value.srl(&shift).resize_unsigned(1);
-                self.state.set_flag("N".to_owned(), result);
+                self.state.set_flag(Register::N, result);
             }
             Operation::SetZFlag(operand) => {
                 let value = self.get_operand_value(operand, local)?;
                 let result = value._eq(&self.state.ctx.zero(self.project.get_word_size()));
-                self.state.set_flag("Z".to_owned(), result);
+                self.state.set_flag(Register::Z, result);
             }
             Operation::SetCFlag {
                 operand1,
@@ -677,7 +799,7 @@ impl<'vm, A: Arch> GAExecutor<'vm, A> {
                     (true, true) => {
                         // I do not now if this part is used in any ISA but it is here for
                         // completeness.
-                        let carry_in = self.state.get_flag("C".to_owned()).unwrap();
+                        let carry_in = self.state.get_flag(Register::C).unwrap();
                         let op2 = op2.not();
 
                         // Check for carry on twos complement of op2
@@ -699,14 +821,14 @@ impl<'vm, A: Arch> GAExecutor<'vm, A> {
                         add_with_carry(&lhs, &rhs, &one, self.project.get_word_size()).carry_out
                     }
                     (false, true) => {
-                        let carry_in = self.state.get_flag("C".to_owned()).unwrap();
+                        let carry_in = self.state.get_flag(Register::C).unwrap();
                         add_with_carry(&op1, &op2, &carry_in, self.project.get_word_size())
                             .carry_out
                     }
                     (false, false) => op1.uaddo(&op2),
                 };
 
-                self.state.set_flag("C".to_owned(), result);
+                self.state.set_flag(Register::C, result);
             }
             Operation::SetVFlag {
                 operand1,
@@ -721,7 +843,7 @@ impl<'vm, A: Arch> GAExecutor<'vm, A> {
                 let result = match (sub, carry) {
                     (true, true) => {
                         // slightly wrong at op2 = 0
-                        let carry_in = self.state.get_flag("C".to_owned()).unwrap();
+                        let carry_in = self.state.get_flag(Register::C).unwrap();
                         let op2 = op2.not().add(&one);
                         add_with_carry(&op1, &op2, &carry_in, self.project.get_word_size()).overflow
                     }
@@ -730,13 +852,13 @@ impl<'vm, A: Arch> GAExecutor<'vm, A> {
                             .overflow
                     }
                     (false, true) => {
-                        let carry_in = self.state.get_flag("C".to_owned()).unwrap();
+                        let carry_in = self.state.get_flag(Register::C).unwrap();
                         add_with_carry(&op1, &op2, &carry_in, self.project.get_word_size()).overflow
                     }
                     (false, false) => op1.saddo(&op2),
                 };
 
-                self.state.set_flag("V".to_owned(), result);
+                self.state.set_flag(Register::V, result);
             }
             Operation::ForEach {
                 operands: _,
@@ -783,7 +905,7 @@ impl<'vm, A: Arch> GAExecutor<'vm, A> {
                 let op2 = self.get_operand_value(operand2, local)?;
                 let carry = self
                     .state
-                    .get_flag("C".to_owned())
+                    .get_flag(Register::C)
                     .unwrap()
                     .zero_ext(self.project.get_word_size());
                 let result =
@@ -805,7 +927,7 @@ impl<'vm, A: Arch> GAExecutor<'vm, A> {
                         self.project.get_word_size() + 1,
                     ))
                     .resize_unsigned(1);
-                self.state.set_flag("C".to_owned(), carry);
+                self.state.set_flag(Register::C, carry);
             }
             Operation::SetCFlagSrl { operand, shift } => {
                 let op = self
@@ -817,7 +939,7 @@ impl<'vm, A: Arch> GAExecutor<'vm, A> {
                     .zero_ext(1 + self.project.get_word_size());
                 let result = op.srl(&shift);
                 let carry = result.resize_unsigned(1);
-                self.state.set_flag("C".to_owned(), carry);
+                self.state.set_flag(Register::C, carry);
             }
             Operation::SetCFlagSra { operand, shift } => {
                 let op = self
@@ -829,7 +951,7 @@ impl<'vm, A: Arch> GAExecutor<'vm, A> {
                     .zero_ext(1 + self.project.get_word_size());
                 let result = op.sra(&shift);
                 let carry = result.resize_unsigned(1);
-                self.state.set_flag("C".to_owned(), carry);
+                self.state.set_flag(Register::C, carry);
             }
             Operation::SetCFlagRor(operand) => {
                 // this is right for armv6-m but may be wrong for other architectures
@@ -840,7 +962,7 @@ impl<'vm, A: Arch> GAExecutor<'vm, A> {
                 );
                 // result = srl(op, shift) OR sll(op, word_size - shift)
                 let c = result.srl(&word_size_minus_one).resize_unsigned(1);
-                self.state.set_flag("C".to_owned(), c);
+                self.state.set_flag(Register::C, c);
             }
             Operation::CountOnes {
                 destination,
@@ -960,14 +1082,15 @@ mod test {
         condition::Condition,
         operand::{DataWord, Operand},
         operation::Operation,
+        register::Register,
     };
 
     use super::{count_leading_ones, count_ones, count_zeroes};
     use crate::{
         general_assembly::{
-            arch::arm::v6::ArmV6M,
+            arch::arm::{v6::ArmV6M, REGISTERS},
             executor::{add_with_carry, count_leading_zeroes, GAExecutor},
-            instruction::{CycleCount, Instruction},
+            instruction::{CycleCount, Instruction, MemoryAccess},
             project::Project,
             state::GAState,
             vm::VM,
@@ -1126,8 +1249,8 @@ mod test {
             Endianness::Little,
             HashMap::new(),
             HashMap::new(),
-            HashMap::new(),
-            HashMap::new(),
+            Vec::new(),
+            Vec::new(),
             HashMap::new(),
             vec![],
             HashMap::new(),
@@ -1150,7 +1273,7 @@ mod test {
         let mut executor =
             GAExecutor::from_state(vm.paths.get_path().unwrap().state, &mut vm, project);
         let mut local = HashMap::new();
-        let operand_r0 = Operand::Register("R0".to_owned());
+        let operand_r0 = Operand::Register(REGISTERS.named("R0"));
 
         // move imm into reg
         let operation = Operation::Move {
@@ -1224,7 +1347,7 @@ mod test {
             GAExecutor::from_state(vm.paths.get_path().unwrap().state, &mut vm, project);
         let mut local = HashMap::new();
 
-        let r0 = Operand::Register("R0".to_owned());
+        let r0 = Operand::Register(REGISTERS.named("R0"));
         let imm_42 = Operand::Immediate(DataWord::Word32(42));
         let imm_umax = Operand::Immediate(DataWord::Word32(u32::MAX));
         let imm_16 = Operand::Immediate(DataWord::Word32(16));
@@ -1302,13 +1425,13 @@ mod test {
         let imm_42 = Operand::Immediate(DataWord::Word32(42));
         let imm_12 = Operand::Immediate(DataWord::Word32(12));
         let imm_umax = Operand::Immediate(DataWord::Word32(u32::MAX));
-        let r0 = Operand::Register("R0".to_owned());
+        let r0 = Operand::Register(REGISTERS.named("R0"));
 
         let true_dexpr = executor.state.ctx.from_bool(true);
         let false_dexpr = executor.state.ctx.from_bool(false);
 
         // test normal add
-        executor.state.set_flag("C".to_owned(), false_dexpr.clone());
+        executor.state.set_flag(Register::C, false_dexpr.clone());
         let operation = Operation::Adc {
             destination: r0.clone(),
             operand1: imm_42.clone(),
@@ -1325,7 +1448,7 @@ mod test {
         assert_eq!(result, 54);
 
         // test add with overflow
-        executor.state.set_flag("C".to_owned(), false_dexpr.clone());
+        executor.state.set_flag(Register::C, false_dexpr.clone());
         let operation = Operation::Adc {
             destination: r0.clone(),
             operand1: imm_umax.clone(),
@@ -1342,7 +1465,7 @@ mod test {
         assert_eq!(result, 11);
 
         // test add with carry in
-        executor.state.set_flag("C".to_owned(), true_dexpr.clone());
+        executor.state.set_flag(Register::C, true_dexpr.clone());
         let operation = Operation::Adc {
             destination: r0.clone(),
             operand1: imm_42.clone(),
@@ -1367,7 +1490,7 @@ mod test {
             GAExecutor::from_state(vm.paths.get_path().unwrap().state, &mut vm, project);
         let mut local = HashMap::new();
 
-        let r0 = Operand::Register("R0".to_owned());
+        let r0 = Operand::Register(REGISTERS.named("R0"));
         let imm_42 = Operand::Immediate(DataWord::Word32(42));
         let imm_imin = Operand::Immediate(DataWord::Word32(i32::MIN as u32));
         let imm_16 = Operand::Immediate(DataWord::Word32(16));
@@ -1442,7 +1565,7 @@ mod test {
             GAExecutor::from_state(vm.paths.get_path().unwrap().state, &mut vm, project);
         let mut local = HashMap::new();
 
-        let r0 = Operand::Register("R0".to_owned());
+        let r0 = Operand::Register(REGISTERS.named("R0"));
         let imm_42 = Operand::Immediate(DataWord::Word32(42));
         let imm_minus_42 = Operand::Immediate(DataWord::Word32(-42i32 as u32));
         let imm_16 = Operand::Immediate(DataWord::Word32(16));
@@ -1533,7 +1656,7 @@ mod test {
 
         let v_flag = executor
             .state
-            .get_flag("V".to_owned())
+            .get_flag(Register::V)
             .unwrap()
             .get_constant_bool()
             .unwrap();
@@ -1550,7 +1673,7 @@ mod test {
 
         let v_flag = executor
             .state
-            .get_flag("V".to_owned())
+            .get_flag(Register::V)
             .unwrap()
             .get_constant_bool()
             .unwrap();
@@ -1567,7 +1690,7 @@ mod test {
 
         let v_flag = executor
             .state
-            .get_flag("V".to_owned())
+            .get_flag(Register::V)
             .unwrap()
             .get_constant_bool()
             .unwrap();
@@ -1583,14 +1706,14 @@ mod test {
         let imm_0 = Operand::Immediate(DataWord::Word32(0));
         let imm_1 = Operand::Immediate(DataWord::Word32(1));
         let local = HashMap::new();
-        let r0 = Operand::Register("R0".to_owned());
+        let r0 = Operand::Register(REGISTERS.named("R0"));
 
         let program1 = vec![
             Instruction {
                 instruction_size: 32,
                 operations: vec![Operation::SetZFlag(imm_0.clone())],
                 max_cycle: CycleCount::Value(0),
-                memory_access: false,
+                memory_access: MemoryAccess::None,
             },
             Instruction {
                 instruction_size: 32,
@@ -1598,7 +1721,7 @@ mod test {
                     conditions: vec![Condition::EQ, Condition::NE],
                 }],
                 max_cycle: CycleCount::Value(0),
-                memory_access: false,
+                memory_access: MemoryAccess::None,
             },
             Instruction {
                 instruction_size: 32,
@@ -1607,7 +1730,7 @@ mod test {
                     source: imm_1,
                 }],
                 max_cycle: CycleCount::Value(0),
-                memory_access: false,
+                memory_access: MemoryAccess::None,
             },
             Instruction {
                 instruction_size: 32,
@@ -1616,7 +1739,7 @@ mod test {
                     source: imm_0,
                 }],
                 max_cycle: CycleCount::Value(0),
-                memory_access: false,
+                memory_access: MemoryAccess::None,
             },
         ];
 
@@ -1,6 +1,8 @@
 //! Describes a general assembly instruction.
 
-use general_assembly::operation::Operation;
+use std::collections::{HashMap, HashSet};
+
+use general_assembly::{operand::Operand, operation::Operation, register::Register};
 
 use super::{arch::Arch, state::GAState};
 
@@ -10,8 +12,9 @@ pub enum CycleCount<A: Arch> {
     /// Cycle count is a precalculated value
     Value(usize),
 
-    /// Cycle count depends on execution state
-    Function(fn(state: &GAState<A>) -> usize),
+    /// Cycle count depends on execution state. The function may update the
+    /// state of models of the micro architecture, such as a branch predictor.
+    Function(fn(state: &mut GAState<A>) -> usize),
 }
 
 /// Represents a general assembly instruction.
@@ -26,10 +29,320 @@ pub struct Instruction<A: Arch> {
 
     /// The maximum number of cycles the instruction will take.
     /// This can depend on state and will be evaluated after the
-    /// instruction has executed but before the next instruction.
+    /// instruction has executed but before the next instruction. At that point
+    /// the last instruction of the state is the instruction executed before
+    /// this one.
     pub max_cycle: CycleCount<A>,
 
-    /// Denotes wether or not the instruction required access to the underlying
-    /// memory or not.
-    pub memory_access: bool,
+    /// Denotes what kind of access to the underlying memory the instruction
+    /// requires.
+    pub memory_access: MemoryAccess,
+}
+
+impl<A: Arch> Instruction<A> {
+    /// Checks if the instruction uses a register or flag that `other` writes.
+    ///
+    /// Registers are found from the operations of the instructions, so this
+    /// is only as precise as the translation of the instructions. Conditions
+    /// are counted as reading all flags.
+    pub fn depends_on(&self, other: &Instruction<A>) -> bool {
+        let written = Usage::of(&other.operations).written;
+        let usage = Usage::of(&self.operations);
+        !written.is_disjoint(&usage.read) || !written.is_disjoint(&usage.written)
+    }
+
+    /// Checks if the instruction computes a memory address from a register
+    /// that `other` writes.
+    pub fn address_depends_on(&self, other: &Instruction<A>) -> bool {
+        let written = Usage::of(&other.operations).written;
+        !written.is_disjoint(&Usage::of(&self.operations).addresses)
+    }
+
+    /// Checks if the instruction writes `register`.
+    pub fn writes_register(&self, register: Register) -> bool {
+        Usage::of(&self.operations).written.contains(&register)
+    }
+}
+
+/// The registers and flags used by a sequence of operations.
+#[derive(Default)]
+struct Usage {
+    read: HashSet<Register>,
+    written: HashSet<Register>,
+    /// The registers that memory addresses are computed from.
+    addresses: HashSet<Register>,
+    /// The registers that the value of each local is computed from.
+    locals: HashMap<String, HashSet<Register>>,
+}
+
+impl Usage {
+    fn of(operations: &[Operation]) -> Self {
+        let mut usage = Self::default();
+        registers(operations, &mut usage);
+        usage
+    }
+}
+
+/// Collects the registers and flags read and written by `operations`.
+fn registers(operations: &[Operation], usage: &mut Usage) {
+    for operation in operations {
+        // The registers read by this operation.
+        let mut read = HashSet::new();
+        match operation {
+            Operation::Nop => (),
+            Operation::Move {
+                destination,
+                source: operand,
+            }
+            | Operation::Not {
+                destination,
+                operand,
+            }
+            | Operation::ZeroExtend {
+                destination,
+                operand,
+                ..
+            }
+            | Operation::CountOnes {
+                destination,
+                operand,
+            }
+            | Operation::CountZeroes {
+                destination,
+                operand,
+            }
+            | Operation::CountLeadingOnes {
+                destination,
+                operand,
+            }
+            | Operation::CountLeadingZeroes {
+                destination,
+                operand,
+            }
+            | Operation::SignExtend {
+                destination,
+                operand,
+                ..
+            }
+            | Operation::Resize {
+                destination,
+                operand,
+                ..
+            } => {
+                operand_registers(operand, &mut read, usage);
+                destination_registers(destination, &read, usage);
+            }
+            Operation::Add {
+                destination,
+                operand1,
+                operand2,
+            }
+            | Operation::Sub {
+                destination,
+                operand1,
+                operand2,
+            }
+            | Operation::Mul {
+                destination,
+                operand1,
+                operand2,
+            }
+            | Operation::SDiv {
+                destination,
+                operand1,
+                operand2,
+            }
+            | Operation::UDiv {
+                destination,
+                operand1,
+                operand2,
+            }
+            | Operation::And {
+                destination,
+                operand1,
+                operand2,
+            }
+            | Operation::Or {
+                destination,
+                operand1,
+                operand2,
+            }
+            | Operation::Xor {
+                destination,
+                operand1,
+                operand2,
+            }
+            | Operation::Sl {
+                destination,
+                operand: operand1,
+                shift: operand2,
+            }
+            | Operation::Srl {
+                destination,
+                operand: operand1,
+                shift: operand2,
+            }
+            | Operation::Sra {
+                destination,
+                operand: operand1,
+                shift: operand2,
+            }
+            | Operation::Sror {
+                destination,
+                operand: operand1,
+                shift: operand2,
+            } => {
+                operand_registers(operand1, &mut read, usage);
+                operand_registers(operand2, &mut read, usage);
+                destination_registers(destination, &read, usage);
+            }
+            Operation::Adc {
+                destination,
+                operand1,
+                operand2,
+            } => {
+                operand_registers(operand1, &mut read, usage);
+                operand_registers(operand2, &mut read, usage);
+                read.insert(Register::C);
+                destination_registers(destination, &read, usage);
+            }
+            Operation::Shift {
+                destination,
+                operand,
+                shift_n,
+                ..
+            } => {
+                // Rotate right with extend reads the carry flag.
+                operand_registers(operand, &mut read, usage);
+                operand_registers(shift_n, &mut read, usage);
+                read.insert(Register::C);
+                destination_registers(destination, &read, usage);
+            }
+            Operation::ConditionalJump { destination, .. } => {
+                operand_registers(destination, &mut read, usage);
+                read.extend(Register::FLAGS);
+                usage.written.insert(Register::PC);
+            }
+            Operation::SetNFlag(operand) => {
+                operand_registers(operand, &mut read, usage);
+                usage.written.insert(Register::N);
+            }
+            Operation::SetZFlag(operand) => {
+                operand_registers(operand, &mut read, usage);
+                usage.written.insert(Register::Z);
+            }
+            Operation::SetCFlag {
+                operand1, operand2, ..
+            } => {
+                operand_registers(operand1, &mut read, usage);
+                operand_registers(operand2, &mut read, usage);
+                read.insert(Register::C);
+                usage.written.insert(Register::C);
+            }
+            Operation::SetCFlagShiftLeft { operand, shift }
+            | Operation::SetCFlagSrl { operand, shift }
+            | Operation::SetCFlagSra { operand, shift } => {
+                operand_registers(operand, &mut read, usage);
+                operand_registers(shift, &mut read, usage);
+                usage.written.insert(Register::C);
+            }
+            Operation::SetCFlagRor(operand) => {
+                operand_registers(operand, &mut read, usage);
+                usage.written.insert(Register::C);
+            }
+            Operation::SetVFlag {
+                operand1, operand2, ..
+            } => {
+                operand_registers(operand1, &mut read, usage);
+                operand_registers(operand2, &mut read, usage);
+                read.insert(Register::C);
+                usage.written.insert(Register::V);
+            }
+            Operation::ForEach {
+                operands,
+                operations,
+            } => {
+                for operand in operands {
+                    operand_registers(operand, &mut read, usage);
+                }
+                registers(operations, usage);
+            }
+            Operation::ConditionalExecution { .. } => read.extend(Register::FLAGS),
+        }
+        usage.read.extend(read);
+    }
+}
+
+fn destination_registers(destination: &Operand, read: &HashSet<Register>, usage: &mut Usage) {
+    match destination {
+        Operand::Register(register) => {
+            usage.written.insert(*register);
+        }
+        Operand::Flag(flag) => {
+            usage.written.insert(*flag);
+        }
+        Operand::Local(local) => {
+            usage.locals.insert(local.to_owned(), read.clone());
+        }
+        // A store reads the registers used to compute the address.
+        operand => {
+            let mut read = HashSet::new();
+            operand_registers(operand, &mut read, usage);
+            usage.read.extend(read);
+        }
+    }
+}
+
+fn operand_registers(operand: &Operand, read: &mut HashSet<Register>, usage: &mut Usage) {
+    match operand {
+        Operand::Register(register) => {
+            read.insert(*register);
+        }
+        Operand::Flag(flag) => {
+            read.insert(*flag);
+        }
+        Operand::Local(local) => {
+            if let Some(registers) = usage.locals.get(local) {
+                read.extend(registers);
+            }
+        }
+        Operand::AddressInLocal(local, _) => {
+            if let Some(registers) = usage.locals.get(local) {
+                read.extend(registers);
+                usage.addresses.extend(registers);
+            }
+        }
+        Operand::AddressWithOffset { offset_reg, .. } => {
+            read.insert(*offset_reg);
+            usage.addresses.insert(*offset_reg);
+        }
+        Operand::Immediate(_) | Operand::Address(_, _) => (),
+    }
+}
+
+/// The kind of memory access made by an instruction.
+///
+/// Used by the cycle models to decide if neighbouring memory accesses can be
+/// pipelined.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MemoryAccess {
+    /// The instruction does not access memory.
+    None,
+
+    /// The instruction loads a single value from memory.
+    Load,
+
+    /// The instruction stores a single value to memory.
+    Store,
+
+    /// The instruction makes several or otherwise special accesses, e.g. load
+    /// or store multiple.
+    Other,
+}
+
+impl MemoryAccess {
+    /// Checks if the instruction accesses memory at all.
+    pub fn accesses_memory(&self) -> bool {
+        *self != MemoryAccess::None
+    }
 }
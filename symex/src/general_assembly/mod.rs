@@ -6,6 +6,7 @@ pub mod executor;
 pub mod instruction;
 pub mod path_selection;
 pub mod project;
+pub mod replay;
 pub mod run_config;
 pub mod state;
 pub mod vm;
@@ -29,11 +30,20 @@ pub enum GAError {
     #[error("Writing to static memory not permited.")]
     WritingToStaticMemoryProhibited,
 
+    #[error("PC set to a symbolic value outside of a branch.")]
+    SymbolicPC,
+
+    #[error("The size of a symbolic value is not concrete.")]
+    SymbolicSize,
+
     #[error("Solver error.")]
     SolverError(#[from] SolverError),
 
     #[error("Architecture error.")]
     ArchError(#[from] ArchError),
+
+    #[error("Unable to write trace: {0}")]
+    WriteTrace(String),
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -44,12 +54,31 @@ pub enum WordSize {
     Bit8,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Endianness {
     Little,
     Big,
 }
 
+impl Endianness {
+    /// The byte order of the data in `file`.
+    pub fn of(file: &object::File) -> Self {
+        match object::Object::is_little_endian(file) {
+            true => Endianness::Little,
+            false => Endianness::Big,
+        }
+    }
+
+    /// Reads a 32 bit value from the first four bytes of `data`.
+    pub fn read_u32(self, data: &[u8]) -> Option<u32> {
+        let bytes = data.get(..4)?.try_into().ok()?;
+        Some(match self {
+            Endianness::Little => u32::from_le_bytes(bytes),
+            Endianness::Big => u32::from_be_bytes(bytes),
+        })
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct Config {
     /// Maximum call stack depth. Default is `1000`.
@@ -1,8 +1,20 @@
-use std::{collections::HashMap, fmt::Debug};
+use std::{cell::RefCell, collections::HashMap, fmt::Debug};
 
-use general_assembly::operand::{DataHalfWord, DataWord, RawDataWord};
+use general_assembly::{
+    operand::{DataHalfWord, DataWord, RawDataWord},
+    register::Register,
+};
 use gimli::{DebugAbbrev, DebugInfo, DebugStr};
-use object::{File, Object, ObjectSection, ObjectSymbol};
+use object::{
+    elf::EF_ARM_BE8,
+    Architecture,
+    File,
+    FileFlags,
+    Object,
+    ObjectSection,
+    ObjectSymbol,
+    SymbolKind,
+};
 use tracing::{debug, trace};
 
 use self::segments::Segments;
@@ -14,13 +26,18 @@ use super::{
     Result as SuperResult,
     RunConfig,
     WordSize,
+    DEFAULT_MAX_BRANCH_TARGETS,
 };
 use crate::{general_assembly::arch::Arch, memory::MemoryError, smt::DExpr};
 
 mod dwarf_helper;
 use dwarf_helper::*;
 
+pub mod image;
 pub mod segments;
+pub mod snapshot;
+pub mod target_config;
+use target_config::TargetConfiguration;
 
 pub type Result<T> = std::result::Result<T, ProjectError>;
 
@@ -37,6 +54,18 @@ pub enum ProjectError {
 
     #[error("Architecture specific error")]
     ArchError(#[from] ArchError),
+
+    #[error("Unable to parse target configuration: {0}")]
+    UnableToParseTargetConfiguration(String),
+
+    #[error("Unable to parse image: {0}")]
+    UnableToParseImage(String),
+
+    #[error("Unable to parse symbol map: {0}")]
+    UnableToParseSymbolMap(String),
+
+    #[error("Unable to parse snapshot: {0}")]
+    UnableToParseSnapshot(String),
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -52,11 +81,13 @@ pub type PCHooks<A> = HashMap<u64, PCHook<A>>;
 
 /// Hook for a register read.
 pub type RegisterReadHook<A> = fn(state: &mut GAState<A>) -> SuperResult<DExpr>;
-pub type RegisterReadHooks<A> = HashMap<String, RegisterReadHook<A>>;
+/// Register read hooks indexed by [`Register::index`].
+pub type RegisterReadHooks<A> = Vec<Option<RegisterReadHook<A>>>;
 
 /// Hook for a register write.
 pub type RegisterWriteHook<A> = fn(state: &mut GAState<A>, value: DExpr) -> SuperResult<()>;
-pub type RegisterWriteHooks<A> = HashMap<String, RegisterWriteHook<A>>;
+/// Register write hooks indexed by [`Register::index`].
+pub type RegisterWriteHooks<A> = Vec<Option<RegisterWriteHook<A>>>;
 
 #[derive(Debug, Clone)]
 pub enum MemoryHookAddress {
@@ -81,7 +112,13 @@ pub struct Project<A: Arch> {
     segments: Segments,
     word_size: WordSize,
     endianness: Endianness,
+    /// The byte order of the instructions, which is not the byte order of the
+    /// data for big-endian Arm code in the BE8 format.
+    instruction_endianness: Endianness,
     symtab: HashMap<String, u64>,
+    function_entries: Vec<u64>,
+    max_branch_targets: usize,
+    target: TargetConfiguration,
     pc_hooks: PCHooks<A>,
     reg_read_hooks: RegisterReadHooks<A>,
     reg_write_hooks: RegisterWriteHooks<A>,
@@ -89,27 +126,20 @@ pub struct Project<A: Arch> {
     range_memory_read_hooks: RangeMemoryReadHooks<A>,
     single_memory_write_hooks: SingleMemoryWriteHooks<A>,
     range_memory_write_hooks: RangeMemoryWriteHooks<A>,
+    instruction_cache: RefCell<InstructionCache<A>>,
 }
 
-fn construct_register_read_hooks<A: Arch>(
-    hooks: Vec<(String, RegisterReadHook<A>)>,
-) -> RegisterReadHooks<A> {
-    let mut ret = HashMap::new();
-    for (register, hook) in hooks {
-        ret.insert(register, hook);
-    }
-    ret
-}
-
-fn construct_register_write_hooks<A: Arch>(
-    hooks: Vec<(String, RegisterWriteHook<A>)>,
-) -> RegisterWriteHooks<A> {
-    let mut ret = HashMap::new();
+/// Decoded instructions keyed by address and if they are inside of a
+/// conditional block, which changes how some instructions are translated.
+type InstructionCache<A> = HashMap<(u64, bool), Instruction<A>>;
 
+/// Places the hooks in a vector indexed by the register so that looking up a
+/// hook is a single index.
+fn construct_register_hooks<A: Arch, H>(hooks: Vec<(Register, H)>) -> Vec<Option<H>> {
+    let mut ret: Vec<Option<H>> = (0..A::REGISTERS.len()).map(|_| None).collect();
     for (register, hook) in hooks {
-        ret.insert(register, hook);
+        ret[register.index()] = Some(hook);
     }
-
     ret
 }
 
@@ -153,6 +183,22 @@ fn construct_memory_read_hooks<A: Arch>(
     (single_hooks, range_hooks)
 }
 
+/// Finds the byte order of the instructions in `file`.
+///
+/// Instructions are little-endian in AArch64 and RISC-V files and in Arm files
+/// linked as BE8, only big-endian Arm files without the `EF_ARM_BE8` flag, the
+/// legacy BE32 format and relocatable objects, store them in big-endian.
+fn instruction_endianness(file: &File) -> Endianness {
+    match (Endianness::of(file), file.architecture(), file.flags()) {
+        (Endianness::Big, Architecture::Arm, FileFlags::Elf { e_flags, .. })
+            if e_flags & EF_ARM_BE8 == 0 =>
+        {
+            Endianness::Big
+        }
+        _ => Endianness::Little,
+    }
+}
+
 impl<A: Arch> Project<A> {
     pub fn manual_project(
         program_memory: Vec<u8>,
@@ -162,8 +208,8 @@ impl<A: Arch> Project<A> {
         endianness: Endianness,
         symtab: HashMap<String, u64>,
         pc_hooks: PCHooks<A>,
-        reg_read_hooks: RegisterReadHooks<A>,
-        reg_write_hooks: RegisterWriteHooks<A>,
+        reg_read_hooks: Vec<(Register, RegisterReadHook<A>)>,
+        reg_write_hooks: Vec<(Register, RegisterWriteHook<A>)>,
         single_memory_read_hooks: SingleMemoryReadHooks<A>,
         range_memory_read_hooks: RangeMemoryReadHooks<A>,
         single_memory_write_hooks: SingleMemoryWriteHooks<A>,
@@ -173,18 +219,24 @@ impl<A: Arch> Project<A> {
             segments: Segments::from_single_segment(program_memory, start_addr, end_addr),
             word_size,
             endianness,
+            instruction_endianness: Endianness::Little,
+            function_entries: symtab.values().copied().collect(),
             symtab,
+            max_branch_targets: DEFAULT_MAX_BRANCH_TARGETS,
+            target: A::default_target(),
             pc_hooks,
-            reg_read_hooks,
-            reg_write_hooks,
+            reg_read_hooks: construct_register_hooks::<A, _>(reg_read_hooks),
+            reg_write_hooks: construct_register_hooks::<A, _>(reg_write_hooks),
             single_memory_read_hooks,
             range_memory_read_hooks,
             single_memory_write_hooks,
             range_memory_write_hooks,
+            instruction_cache: RefCell::new(HashMap::new()),
         }
     }
 
-    #[cfg(test)]
+    /// Adds the hooks of `arch` to a project that was not read from an ELF
+    /// file.
     pub fn add_hooks(&mut self, arch: &A) {
         let mut cfg = RunConfig {
             memory_read_hooks: Vec::new(),
@@ -193,11 +245,15 @@ impl<A: Arch> Project<A> {
             register_read_hooks: Vec::new(),
             register_write_hooks: Vec::new(),
             show_path_results: false,
+            target: None,
+            merge_paths: false,
+            max_branch_targets: DEFAULT_MAX_BRANCH_TARGETS,
+            trace: None,
         };
         arch.add_hooks(&mut cfg);
 
-        let reg_read_hooks = construct_register_read_hooks(cfg.register_read_hooks);
-        let reg_write_hooks = construct_register_write_hooks(cfg.register_write_hooks);
+        let reg_read_hooks = construct_register_hooks::<A, _>(cfg.register_read_hooks);
+        let reg_write_hooks = construct_register_hooks::<A, _>(cfg.register_write_hooks);
 
         let (single_memory_write_hooks, range_memory_write_hooks) =
             construct_memory_write(cfg.memory_write_hooks);
@@ -214,11 +270,8 @@ impl<A: Arch> Project<A> {
 
     pub fn from_path(cfg: &mut RunConfig<A>, obj_file: File, architecture: &A) -> Result<Self> {
         let segments = Segments::from_file(&obj_file);
-        let endianness = if obj_file.is_little_endian() {
-            Endianness::Little
-        } else {
-            Endianness::Big
-        };
+        let endianness = Endianness::of(&obj_file);
+        let instruction_endianness = instruction_endianness(&obj_file);
 
         // Do not catch 16 or 8 bit architectures but will do for now.
         let word_size = if obj_file.is_64() {
@@ -228,6 +281,7 @@ impl<A: Arch> Project<A> {
         };
 
         let mut symtab = HashMap::new();
+        let mut function_entries = vec![];
         for symbol in obj_file.symbols() {
             symtab.insert(
                 match symbol.name() {
@@ -236,7 +290,12 @@ impl<A: Arch> Project<A> {
                 },
                 symbol.address(),
             );
+            if symbol.kind() == SymbolKind::Text {
+                function_entries.push(symbol.address());
+            }
         }
+        function_entries.sort_unstable();
+        function_entries.dedup();
 
         let gimli_endian = match endianness {
             Endianness::Little => gimli::RunTimeEndian::Little,
@@ -254,26 +313,95 @@ impl<A: Arch> Project<A> {
 
         trace!("Running for Architecture {}", architecture);
         architecture.add_hooks(cfg);
-        let pc_hooks = &cfg.pc_hooks;
-
         let pc_hooks =
-            construct_pc_hooks_no_index(pc_hooks, &debug_info, &debug_abbrev, &debug_str);
+            construct_pc_hooks_no_index(&cfg.pc_hooks, &debug_info, &debug_abbrev, &debug_str);
+
+        Ok(Self::from_parts(
+            cfg,
+            segments,
+            word_size,
+            endianness,
+            instruction_endianness,
+            symtab,
+            function_entries,
+            pc_hooks,
+        ))
+    }
+
+    /// Creates a project from the memory contents of a binary image that is
+    /// not an ELF file, see [`image`].
+    ///
+    /// The PC hooks are placed at the addresses of the symbols in `symtab`
+    /// since there is no debug information, and all symbols are treated as
+    /// function entries. Instructions are read in little-endian.
+    pub fn from_image(
+        cfg: &mut RunConfig<A>,
+        segments: Segments,
+        symtab: HashMap<String, u64>,
+        word_size: WordSize,
+        endianness: Endianness,
+        architecture: &A,
+    ) -> Result<Self> {
+        trace!("Running for Architecture {}", architecture);
+        architecture.add_hooks(cfg);
+
+        let mut pc_hooks = HashMap::new();
+        for (name, address) in &symtab {
+            if let Some((_, hook)) = cfg.pc_hooks.iter().find(|(regex, _)| regex.is_match(name)) {
+                trace!("Found hook for {} at {:#X}", name, address);
+                pc_hooks.insert(address & !1, hook.clone());
+            }
+        }
+
+        let mut function_entries: Vec<u64> = symtab.values().copied().collect();
+        function_entries.sort_unstable();
+        function_entries.dedup();
+
+        Ok(Self::from_parts(
+            cfg,
+            segments,
+            word_size,
+            endianness,
+            Endianness::Little,
+            symtab,
+            function_entries,
+            pc_hooks,
+        ))
+    }
+
+    /// Adds the hooks of `cfg` to the parts read from the file.
+    fn from_parts(
+        cfg: &RunConfig<A>,
+        segments: Segments,
+        word_size: WordSize,
+        endianness: Endianness,
+        instruction_endianness: Endianness,
+        symtab: HashMap<String, u64>,
+        function_entries: Vec<u64>,
+        pc_hooks: PCHooks<A>,
+    ) -> Self {
+        let target = cfg.target.clone().unwrap_or_else(A::default_target);
+        debug!("Using target configuration: {:?}", target);
 
         debug!("Created pc hooks: {:?}", pc_hooks);
 
-        let reg_read_hooks = construct_register_read_hooks(cfg.register_read_hooks.clone());
-        let reg_write_hooks = construct_register_write_hooks(cfg.register_write_hooks.clone());
+        let reg_read_hooks = construct_register_hooks::<A, _>(cfg.register_read_hooks.clone());
+        let reg_write_hooks = construct_register_hooks::<A, _>(cfg.register_write_hooks.clone());
 
         let (single_memory_write_hooks, range_memory_write_hooks) =
             construct_memory_write(cfg.memory_write_hooks.clone());
         let (single_memory_read_hooks, range_memory_read_hooks) =
             construct_memory_read_hooks(cfg.memory_read_hooks.clone());
 
-        Ok(Project {
+        Project {
             segments,
             word_size,
             endianness,
+            instruction_endianness,
             symtab,
+            function_entries,
+            max_branch_targets: cfg.max_branch_targets,
+            target,
             pc_hooks,
             reg_read_hooks,
             reg_write_hooks,
@@ -281,7 +409,35 @@ impl<A: Arch> Project<A> {
             range_memory_read_hooks,
             single_memory_write_hooks,
             range_memory_write_hooks,
-        })
+            instruction_cache: RefCell::new(HashMap::new()),
+        }
+    }
+
+    /// Get the description of the target hardware.
+    pub fn get_target(&self) -> &TargetConfiguration {
+        &self.target
+    }
+
+    /// Replace the description of the target hardware.
+    pub fn set_target(&mut self, target: TargetConfiguration) {
+        self.target = target;
+        // The cycle counts of the decoded instructions depend on the core.
+        self.instruction_cache.get_mut().clear();
+    }
+
+    /// Get the maximum number of targets of a branch to a symbolic address.
+    pub fn get_max_branch_targets(&self) -> usize {
+        self.max_branch_targets
+    }
+
+    /// Set the maximum number of targets of a branch to a symbolic address.
+    pub fn set_max_branch_targets(&mut self, max_branch_targets: usize) {
+        self.max_branch_targets = max_branch_targets;
+    }
+
+    /// Get the addresses of all functions in the symbol table.
+    pub fn get_function_entries(&self) -> &[u64] {
+        &self.function_entries
     }
 
     pub fn get_pc_hook(&self, pc: u64) -> Option<&PCHook<A>> {
@@ -292,12 +448,12 @@ impl<A: Arch> Project<A> {
         self.pc_hooks.insert(pc, hook);
     }
 
-    pub fn get_register_read_hook(&self, register: &str) -> Option<RegisterReadHook<A>> {
-        self.reg_read_hooks.get(register).copied()
+    pub fn get_register_read_hook(&self, register: Register) -> Option<RegisterReadHook<A>> {
+        self.reg_read_hooks[register.index()]
     }
 
-    pub fn get_register_write_hook(&self, register: &str) -> Option<RegisterWriteHook<A>> {
-        self.reg_write_hooks.get(register).copied()
+    pub fn get_register_write_hook(&self, register: Register) -> Option<RegisterWriteHook<A>> {
+        self.reg_write_hooks[register.index()]
     }
 
     pub fn get_memory_write_hook(&self, address: u64) -> Option<MemoryWriteHook<A>> {
@@ -337,7 +493,19 @@ impl<A: Arch> Project<A> {
     }
 
     pub fn get_endianness(&self) -> Endianness {
-        self.endianness.clone()
+        self.endianness
+    }
+
+    /// Get the byte order of the instructions.
+    pub fn get_instruction_endianness(&self) -> Endianness {
+        self.instruction_endianness
+    }
+
+    /// Set the byte order of the instructions, projects that are not loaded
+    /// from a file use little-endian instructions.
+    pub fn set_instruction_endianness(&mut self, endianness: Endianness) {
+        self.instruction_endianness = endianness;
+        self.instruction_cache.get_mut().clear();
     }
 
     pub fn get_ptr_size(&self) -> u32 {
@@ -356,15 +524,27 @@ impl<A: Arch> Project<A> {
         self.symtab.get(symbol).copied()
     }
 
-    /// Get the instruction att a address
+    /// Get the instruction at `address`.
+    ///
+    /// Program memory can not be written so each instruction is only decoded
+    /// once for each of the contexts it is translated differently in.
     pub fn get_instruction(&self, address: u64, state: &GAState<A>) -> Result<Instruction<A>> {
+        let key = (address, state.get_in_conditional_block());
+        if let Some(instruction) = self.instruction_cache.borrow().get(&key) {
+            return Ok(instruction.clone());
+        }
+
         trace!("Reading instruction from address: {:#010X}", address);
-        match self.get_raw_word(address)? {
+        let instruction = match self.get_raw_word(address)? {
             RawDataWord::Word64(d) => self.instruction_from_array_ptr(&d, state),
             RawDataWord::Word32(d) => self.instruction_from_array_ptr(&d, state),
             RawDataWord::Word16(d) => self.instruction_from_array_ptr(&d, state),
             RawDataWord::Word8(_) => todo!(),
-        }
+        }?;
+        self.instruction_cache
+            .borrow_mut()
+            .insert(key, instruction.clone());
+        Ok(instruction)
     }
 
     fn instruction_from_array_ptr(
@@ -372,7 +552,32 @@ impl<A: Arch> Project<A> {
         data: &[u8],
         state: &GAState<A>,
     ) -> Result<Instruction<A>> {
-        state.instruction_from_array_ptr(data)
+        match self.instruction_endianness {
+            Endianness::Little => state.instruction_from_array_ptr(data),
+            Endianness::Big => state.instruction_from_array_ptr(&self.swap_half_words(data)),
+        }
+    }
+
+    /// Only Thumb code is stored in big-endian, one half word at a time.
+    fn swap_half_words(&self, data: &[u8]) -> Vec<u8> {
+        data.chunks(2)
+            .flat_map(|half_word| half_word.iter().rev())
+            .copied()
+            .collect()
+    }
+
+    /// Describes the instruction at `address`, see [`Arch::disassemble`].
+    pub fn disassemble(&self, address: u64, architecture: &A) -> Option<String> {
+        let data = match self.get_raw_word(address).ok()? {
+            RawDataWord::Word64(d) => d.to_vec(),
+            RawDataWord::Word32(d) => d.to_vec(),
+            RawDataWord::Word16(d) => d.to_vec(),
+            RawDataWord::Word8(d) => d.to_vec(),
+        };
+        match self.instruction_endianness {
+            Endianness::Little => architecture.disassemble(&data),
+            Endianness::Big => architecture.disassemble(&self.swap_half_words(&data)),
+        }
     }
 
     /// Get a byte of data from program memory.
@@ -383,7 +588,7 @@ impl<A: Arch> Project<A> {
         }
     }
 
-    fn get_word_internal(&self, address: u64, width: WordSize) -> Result<DataWord> {
+    pub(crate) fn get_word_internal(&self, address: u64, width: WordSize) -> Result<DataWord> {
         Ok(match width {
             WordSize::Bit64 => match self.segments.read_raw_bytes(address, 8) {
                 Some(v) => {
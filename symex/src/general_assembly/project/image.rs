@@ -0,0 +1,353 @@
+//! Loaders for binary images that are not ELF files, raw binaries, Intel HEX
+//! and Motorola S-record files, and for the symbol maps that name the
+//! addresses in them.
+//!
+//! The architecture of an image can not be discovered from the file so it has
+//! to be given by the user, see
+//! [`ElfConfig::image`](crate::run_elf::ElfConfig::image).
+
+use std::collections::HashMap;
+
+use super::{segments::Segments, ProjectError, Result};
+
+/// The format of a binary image.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImageFormat {
+    /// The raw contents of memory starting at `base`.
+    Binary { base: u64 },
+    /// Intel HEX records.
+    IntelHex,
+    /// Motorola S-records.
+    SRecord,
+}
+
+/// Loads the memory contents of the image in `data`.
+pub fn load(data: &[u8], format: ImageFormat) -> Result<Segments> {
+    let chunks = match format {
+        ImageFormat::Binary { base } => vec![(base, data.to_vec())],
+        ImageFormat::IntelHex => merge(intel_hex(text(data)?)?)?,
+        ImageFormat::SRecord => merge(s_record(text(data)?)?)?,
+    };
+    Ok(Segments::from_chunks(chunks))
+}
+
+/// Parses an address given in hex with a `0x` prefix or in decimal.
+pub fn parse_address(address: &str) -> Option<u64> {
+    let address = address.trim().replace('_', "");
+    match address
+        .strip_prefix("0x")
+        .or_else(|| address.strip_prefix("0X"))
+    {
+        Some(hex) => u64::from_str_radix(hex, 16).ok(),
+        None => address.parse().ok(),
+    }
+}
+
+/// Reads the symbols from a GNU linker map file or from a list of
+/// `name = address` lines, where `#` starts a comment.
+///
+/// Lines of the map file that do not name a symbol are skipped.
+pub fn parse_symbol_map(map: &str) -> Result<HashMap<String, u64>> {
+    let mut symbols = HashMap::new();
+    for (number, line) in map.lines().enumerate() {
+        let line = line.split('#').next().unwrap_or_default();
+        let words: Vec<&str> = line.split_whitespace().collect();
+        match words[..] {
+            // `name = address`
+            [name, "=", address] if is_symbol(name) => {
+                let address = parse_address(address).ok_or_else(|| {
+                    ProjectError::UnableToParseSymbolMap(format!(
+                        "invalid address {address} on line {}",
+                        number + 1
+                    ))
+                })?;
+                symbols.insert(name.to_owned(), address);
+            }
+            // `address name` or `address name = expression` in a map file.
+            [address, name, ..]
+                if address.starts_with("0x")
+                    && is_symbol(name)
+                    && (words.len() == 2 || words[2] == "=") =>
+            {
+                if let Some(address) = parse_address(address) {
+                    symbols.insert(name.to_owned(), address);
+                }
+            }
+            _ => (),
+        }
+    }
+    Ok(symbols)
+}
+
+/// Checks that `name` can be a symbol and not a section, a file or an
+/// expression.
+fn is_symbol(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|first| first.is_ascii_alphabetic() || first == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '.' | '$'))
+}
+
+fn text(data: &[u8]) -> Result<&str> {
+    std::str::from_utf8(data)
+        .map_err(|_| ProjectError::UnableToParseImage("the image is not a text file".to_owned()))
+}
+
+fn image_error(line: usize, message: &str) -> ProjectError {
+    ProjectError::UnableToParseImage(format!("{message} on line {}", line + 1))
+}
+
+/// Decodes the hex digits of a record in to bytes.
+fn record_bytes(line: usize, digits: &str) -> Result<Vec<u8>> {
+    if !digits.len().is_multiple_of(2) {
+        return Err(image_error(line, "odd number of hex digits"));
+    }
+    (0..digits.len())
+        .step_by(2)
+        .map(|i| {
+            u8::from_str_radix(&digits[i..i + 2], 16)
+                .map_err(|_| image_error(line, "invalid hex digit"))
+        })
+        .collect()
+}
+
+/// Parses Intel HEX records in to `(address, data)` chunks.
+///
+/// The start address records are ignored.
+fn intel_hex(text: &str) -> Result<Vec<(u64, Vec<u8>)>> {
+    let mut chunks = vec![];
+    let mut base = 0;
+    for (line, record) in text.lines().enumerate() {
+        let record = record.trim();
+        if record.is_empty() {
+            continue;
+        }
+        let digits = record
+            .strip_prefix(':')
+            .ok_or_else(|| image_error(line, "missing start code"))?;
+        let bytes = record_bytes(line, digits)?;
+        if bytes.len() < 5 || bytes.len() != bytes[0] as usize + 5 {
+            return Err(image_error(line, "invalid record length"));
+        }
+        if bytes.iter().fold(0u8, |sum, byte| sum.wrapping_add(*byte)) != 0 {
+            return Err(image_error(line, "invalid checksum"));
+        }
+        let address = u16::from_be_bytes([bytes[1], bytes[2]]) as u64;
+        let data = &bytes[4..bytes.len() - 1];
+        let value = || data.iter().fold(0, |value, byte| value << 8 | *byte as u64);
+        match bytes[3] {
+            0x00 => chunks.push((base + address, data.to_vec())),
+            0x01 => return Ok(chunks),
+            // Extended segment address.
+            0x02 => base = value() << 4,
+            // Extended linear address.
+            0x04 => base = value() << 16,
+            0x03 | 0x05 => (),
+            _ => return Err(image_error(line, "unknown record type")),
+        }
+    }
+    Err(ProjectError::UnableToParseImage(
+        "missing end of file record".to_owned(),
+    ))
+}
+
+/// Parses Motorola S-records in to `(address, data)` chunks.
+///
+/// The header, count and start address records are ignored.
+fn s_record(text: &str) -> Result<Vec<(u64, Vec<u8>)>> {
+    let mut chunks = vec![];
+    for (line, record) in text.lines().enumerate() {
+        let record = record.trim();
+        if record.is_empty() {
+            continue;
+        }
+        let (kind, digits) = match record.strip_prefix('S') {
+            Some(rest) if !rest.is_empty() => rest.split_at(1),
+            _ => return Err(image_error(line, "missing start code")),
+        };
+        let bytes = record_bytes(line, digits)?;
+        if bytes.is_empty() || bytes.len() != bytes[0] as usize + 1 {
+            return Err(image_error(line, "invalid record length"));
+        }
+        if bytes.iter().fold(0u8, |sum, byte| sum.wrapping_add(*byte)) != 0xff {
+            return Err(image_error(line, "invalid checksum"));
+        }
+        let address_bytes = match kind {
+            "0" | "1" | "5" | "9" => 2,
+            "2" | "6" | "8" => 3,
+            "3" | "7" => 4,
+            _ => return Err(image_error(line, "unknown record type")),
+        };
+        if bytes.len() < address_bytes + 2 {
+            return Err(image_error(line, "invalid record length"));
+        }
+        let address = bytes[1..=address_bytes]
+            .iter()
+            .fold(0, |address, byte| address << 8 | *byte as u64);
+        let data = &bytes[address_bytes + 1..bytes.len() - 1];
+        match kind {
+            "1" | "2" | "3" => chunks.push((address, data.to_vec())),
+            "7" | "8" | "9" => return Ok(chunks),
+            _ => (),
+        }
+    }
+    Ok(chunks)
+}
+
+/// Sorts the chunks and joins the ones that follow each other.
+fn merge(mut chunks: Vec<(u64, Vec<u8>)>) -> Result<Vec<(u64, Vec<u8>)>> {
+    chunks.sort_by_key(|(address, _)| *address);
+    let mut merged: Vec<(u64, Vec<u8>)> = vec![];
+    for (address, data) in chunks {
+        match merged.last_mut() {
+            Some((start, previous)) if *start + previous.len() as u64 > address => {
+                return Err(ProjectError::UnableToParseImage(format!(
+                    "overlapping data at {address:#x}"
+                )));
+            }
+            Some((start, previous)) if *start + previous.len() as u64 == address => {
+                previous.extend(data)
+            }
+            _ => merged.push((address, data)),
+        }
+    }
+    Ok(merged)
+}
+
+#[cfg(test)]
+pub(crate) mod test {
+    use std::path::PathBuf;
+
+    use super::{intel_hex, load, merge, parse_address, parse_symbol_map, s_record, ImageFormat};
+    use crate::{
+        general_assembly::Endianness,
+        run_elf::{run_elf, ElfConfig, Entry, ImageArchitecture, ImageOptions},
+    };
+
+    /// `if r0 > 5 { 2 } else { 1 }` in Thumb at 0x0800_0000.
+    const FUNCTION_HEX: &str = ":020000040800F2\n\
+                                :10000000052801DC012070470220704700BF00BFB7\n\
+                                :00000001FF\n";
+
+    /// An Intel HEX file with a function at `main` for tests that run an
+    /// image, the file is removed when this is dropped.
+    pub(crate) struct TestImage {
+        pub(crate) path: PathBuf,
+        pub(crate) options: ImageOptions,
+    }
+
+    impl TestImage {
+        /// Writes the image to a temporary file, `name` keeps the files of
+        /// tests that run at the same time apart.
+        pub(crate) fn new(name: &str) -> Self {
+            let file = format!("symex-{name}-{}.hex", std::process::id());
+            let path = std::env::temp_dir().join(file);
+            std::fs::write(&path, FUNCTION_HEX).unwrap();
+            let options = ImageOptions {
+                format: ImageFormat::IntelHex,
+                architecture: "armv7e-m".parse().unwrap(),
+                endianness: Endianness::Little,
+                symbols: parse_symbol_map("main = 0x08000001\n_stack_start = 0x20001000").unwrap(),
+            };
+            Self { path, options }
+        }
+    }
+
+    impl Drop for TestImage {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_file(&self.path);
+        }
+    }
+
+    #[test]
+    fn test_intel_hex() {
+        let hex = ":020000040800F2\n\
+                   :0400000001020304F2\n\
+                   :020004000506EB\n\
+                   :00000001FF\n";
+        // The last data record has an invalid checksum.
+        assert!(intel_hex(hex).is_err());
+
+        let hex = hex.replace(":020004000506EB", ":020004000506EF");
+        let chunks = merge(intel_hex(&hex).unwrap()).unwrap();
+        assert_eq!(chunks, [(0x0800_0000, vec![1, 2, 3, 4, 5, 6])]);
+
+        assert!(intel_hex(":0400000001020304F2\n").is_err());
+        assert!(intel_hex("0400000001020304F2\n:00000001FF").is_err());
+    }
+
+    #[test]
+    fn test_s_record() {
+        let srec = "S00600004844521B\n\
+                    S107010001020304ED\n\
+                    S30920000000AABBCCDDC8\n\
+                    S9030000FC\n";
+        let chunks = merge(s_record(srec).unwrap()).unwrap();
+        assert_eq!(chunks, [
+            (0x0100, vec![1, 2, 3, 4]),
+            (0x2000_0000, vec![0xaa, 0xbb, 0xcc, 0xdd])
+        ]);
+
+        assert!(s_record("S107010001020304EE\n").is_err());
+        assert!(s_record("S4030000FC\n").is_err());
+    }
+
+    #[test]
+    fn test_binary() {
+        let segments = load(&[1, 2, 3], ImageFormat::Binary { base: 0x1000 }).unwrap();
+        assert_eq!(segments.read_raw_bytes(0x1001, 2), Some(&[2, 3][..]));
+        assert_eq!(segments.read_raw_bytes(0x1003, 1), None);
+        assert!(merge(vec![(0, vec![0; 4]), (2, vec![0; 4])]).is_err());
+    }
+
+    #[test]
+    fn test_symbol_map() {
+        let map = "\
+            # Symbols of the bootloader.\n\
+            main = 0x0800_0101\n\
+            _stack_start = 536936448\n\
+            \n\
+            .text          0x08000000      0x1c4\n\
+             .text.main    0x08000100       0x1c target/main.o\n\
+                           0x08000100                main2\n\
+                           0x08000180                _ZN4core9panicking5panic17h1E\n\
+                           0x20010000                _heap_end = ORIGIN (RAM) + 0x10000\n\
+            LOAD target/main.o\n";
+        let symbols = parse_symbol_map(map).unwrap();
+        assert_eq!(symbols.len(), 5);
+        assert_eq!(symbols["main"], 0x0800_0101);
+        assert_eq!(symbols["_stack_start"], 0x2001_0000);
+        assert_eq!(symbols["main2"], 0x0800_0100);
+        assert_eq!(symbols["_ZN4core9panicking5panic17h1E"], 0x0800_0180);
+        assert_eq!(symbols["_heap_end"], 0x2001_0000);
+
+        assert!(parse_symbol_map("main = main2").is_err());
+        assert_eq!(parse_address("0X10"), Some(16));
+        assert_eq!(parse_address("main"), None);
+    }
+
+    #[test]
+    fn test_run_image() {
+        let image = TestImage::new("image");
+        let path = &image.path;
+        let mut options = image.options.clone();
+        assert_eq!(options.architecture, ImageArchitecture::ArmV7EM);
+        let run = |entry, options| {
+            let cfg = ElfConfig {
+                image: Some(options),
+                ..ElfConfig::default()
+            };
+            run_elf(path, Entry::Function(entry), cfg).unwrap()
+        };
+        let results = run("main", options.clone());
+        assert_eq!(results.len(), 2);
+
+        // The entry can be an address and hooks are placed at the symbols.
+        options
+            .symbols
+            .insert("suppress_path".to_owned(), 0x0800_0008);
+        let results = run("0x08000000", options);
+        assert_eq!(results.len(), 1);
+    }
+}
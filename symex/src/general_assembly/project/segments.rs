@@ -1,6 +1,11 @@
 //! A loader that can load all segments from a elf file properly.
 
-use object::{read::elf::ProgramHeader, File, Object};
+use object::{
+    elf::PT_LOAD,
+    read::elf::{ElfFile, FileHeader, ProgramHeader},
+    File,
+    ReadRef,
+};
 pub struct Segment {
     data: Vec<u8>,
     start_address: u64,
@@ -18,21 +23,39 @@ impl Segments {
         }])
     }
 
+    /// Creates one segment for each of the `(address, data)` chunks.
+    pub fn from_chunks(chunks: Vec<(u64, Vec<u8>)>) -> Self {
+        Segments(
+            chunks
+                .into_iter()
+                .map(|(start_address, data)| Segment {
+                    end_address: start_address + data.len() as u64,
+                    start_address,
+                    data,
+                })
+                .collect(),
+        )
+    }
+
     pub fn from_file(file: &File) -> Self {
-        let elf_file = match file {
-            File::Elf32(elf_file) => elf_file,
-            File::Elf64(_elf_file) => todo!(),
+        match file {
+            File::Elf32(elf_file) => Self::from_elf(elf_file),
+            File::Elf64(elf_file) => Self::from_elf(elf_file),
             _ => todo!(),
-        };
+        }
+    }
 
+    /// Loads the LOAD segments of a 32 or 64 bit elf file.
+    fn from_elf<'data, Elf: FileHeader, R: ReadRef<'data>>(
+        elf_file: &ElfFile<'data, Elf, R>,
+    ) -> Self {
+        let endian = elf_file.endian();
         let mut ret = vec![];
         for segment in elf_file.raw_segments() {
-            let segment_type = segment.p_type.get(file.endianness());
-            if segment_type == 1 {
-                // if it is a LOAD segment
-                let addr_start = segment.p_vaddr.get(file.endianness()) as u64;
-                //let size = segment.p_memsz.get(file.endianness());
-                let data = segment.data(file.endianness(), elf_file.data()).unwrap();
+            if segment.p_type(endian) == PT_LOAD {
+                let addr_start: u64 = segment.p_vaddr(endian).into();
+                //let size = segment.p_memsz(endian);
+                let data = segment.data(endian, elf_file.data()).unwrap();
 
                 ret.push(Segment {
                     data: data.to_owned(),
@@ -48,8 +71,7 @@ impl Segments {
         for segment in &self.0 {
             if address >= segment.start_address && address < segment.end_address {
                 let offset = (address - segment.start_address) as usize;
-                let data_slice = &segment.data[offset..(offset + bytes)];
-                return Some(data_slice);
+                return segment.data.get(offset..(offset + bytes));
             }
         }
 
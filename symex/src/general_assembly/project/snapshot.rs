@@ -0,0 +1,251 @@
+//! A snapshot of the system state to start the analysis from, supplied by the
+//! user.
+//!
+//! Instead of starting at a function entry with empty RAM the analysis resumes
+//! at the PC of the snapshot with the registers and RAM contents that were
+//! observed on the target, e.g. when stopped at a breakpoint in a handler. A
+//! snapshot is loaded from a toml file on the form
+//!
+//! ```toml
+//! # Registers that are symbolic instead of holding the value below.
+//! symbolic_registers = ["R1"]
+//!
+//! [registers]
+//! PC = 0x0800_0120
+//! SP = 0x2000_fe00
+//! R0 = 0x2000_0010
+//! R1 = 0
+//!
+//! [flags]
+//! Z = 1
+//!
+//! [[memory]]
+//! address = 0x2000_0000
+//! data = "00 11 22 33 44 55 66 77"
+//!
+//! # A raw binary dump, relative to the snapshot file.
+//! [[memory]]
+//! address = 0x2000_0100
+//! file = "ram.bin"
+//!
+//! # Memory that is symbolic instead of holding the dumped value.
+//! [[symbolic]]
+//! address = 0x2000_0010
+//! size = 4
+//! name = "buffer"
+//! ```
+//!
+//! Registers use the names of the architecture. Registers and memory that are
+//! not in the snapshot start as they do at a function entry, the stack pointer
+//! at `_stack_start` and RAM unconstrained. The link register is set to the
+//! end of the analysis, so that the analysis ends when the function the
+//! snapshot was taken in returns, unless `keep_lr = true` is given.
+//!
+//! Memory that is part of the program file can not be written and is read from
+//! the file, so dumps of those addresses are ignored.
+
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use serde::Deserialize;
+
+use super::{ProjectError, Result};
+
+/// The state of the system at a point in time.
+#[derive(Debug, Clone, Default)]
+pub struct Snapshot {
+    /// The value of each register, must contain the PC.
+    pub registers: HashMap<String, u64>,
+
+    /// The value of each flag.
+    pub flags: HashMap<String, u64>,
+
+    /// Dumps of memory as `(address, data)`.
+    pub memory: Vec<(u64, Vec<u8>)>,
+
+    /// Registers that are symbolic.
+    pub symbolic_registers: Vec<String>,
+
+    /// Memory that is symbolic.
+    pub symbolic_memory: Vec<SymbolicMemory>,
+
+    /// Whether the link register keeps the value of the snapshot instead of
+    /// ending the analysis on return.
+    pub keep_lr: bool,
+}
+
+/// A range of memory that is symbolic.
+#[derive(Debug, Clone, Deserialize)]
+pub struct SymbolicMemory {
+    /// First address of the range.
+    pub address: u64,
+
+    /// Size of the range in bytes.
+    pub size: u64,
+
+    /// Name of the symbolic value in the results, the default is based on the
+    /// address.
+    pub name: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SnapshotFile {
+    #[serde(default)]
+    registers: HashMap<String, u64>,
+    #[serde(default)]
+    flags: HashMap<String, u64>,
+    #[serde(default)]
+    memory: Vec<MemoryDump>,
+    #[serde(default)]
+    symbolic_registers: Vec<String>,
+    #[serde(default)]
+    symbolic: Vec<SymbolicMemory>,
+    #[serde(default)]
+    keep_lr: bool,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct MemoryDump {
+    address: u64,
+    data: Option<String>,
+    file: Option<PathBuf>,
+}
+
+impl Snapshot {
+    /// Reads a snapshot from a toml file, memory dumps in files are relative to
+    /// the snapshot.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let data = fs::read_to_string(path)
+            .map_err(|e| ProjectError::UnableToParseSnapshot(format!("{}: {e}", path.display())))?;
+        Self::parse(&data, path.parent().unwrap_or(Path::new(".")))
+    }
+
+    /// Parses a snapshot from a toml string, memory dumps in files are relative
+    /// to the current directory.
+    pub fn from_toml(data: &str) -> Result<Self> {
+        Self::parse(data, Path::new("."))
+    }
+
+    fn parse(data: &str, directory: &Path) -> Result<Self> {
+        let file: SnapshotFile =
+            toml::from_str(data).map_err(|e| ProjectError::UnableToParseSnapshot(e.to_string()))?;
+        if !file.registers.contains_key("PC") {
+            return Err(ProjectError::UnableToParseSnapshot(
+                "the snapshot has no PC".to_owned(),
+            ));
+        }
+
+        let mut memory = vec![];
+        for dump in file.memory {
+            let data = match (dump.data, dump.file) {
+                (Some(data), None) => parse_hex(&data).ok_or_else(|| {
+                    ProjectError::UnableToParseSnapshot(format!(
+                        "invalid data at {:#X}",
+                        dump.address
+                    ))
+                })?,
+                (None, Some(file)) => {
+                    let path = directory.join(file);
+                    fs::read(&path).map_err(|e| {
+                        ProjectError::UnableToParseSnapshot(format!("{}: {e}", path.display()))
+                    })?
+                }
+                _ => {
+                    return Err(ProjectError::UnableToParseSnapshot(format!(
+                        "the memory at {:#X} needs either data or a file",
+                        dump.address
+                    )))
+                }
+            };
+            memory.push((dump.address, data));
+        }
+
+        Ok(Snapshot {
+            registers: file.registers,
+            flags: file.flags,
+            memory,
+            symbolic_registers: file.symbolic_registers,
+            symbolic_memory: file.symbolic,
+            keep_lr: file.keep_lr,
+        })
+    }
+
+    /// Get the PC the analysis resumes at.
+    pub fn pc(&self) -> Option<u64> {
+        self.registers.get("PC").copied()
+    }
+}
+
+/// Parses bytes written as hex digits, optionally separated by whitespace.
+fn parse_hex(data: &str) -> Option<Vec<u8>> {
+    let digits: Vec<char> = data.chars().filter(|c| !c.is_whitespace()).collect();
+    if !digits.len().is_multiple_of(2) {
+        return None;
+    }
+    digits
+        .chunks(2)
+        .map(|byte| u8::from_str_radix(&byte.iter().collect::<String>(), 16).ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::Snapshot;
+
+    #[test]
+    fn test_parse_snapshot() {
+        let snapshot = Snapshot::from_toml(
+            r#"
+            symbolic_registers = ["R1"]
+
+            [registers]
+            PC = 0x0800_0120
+            SP = 0x2000_fe00
+
+            [flags]
+            Z = 1
+
+            [[memory]]
+            address = 0x2000_0000
+            data = "00 11 2233"
+
+            [[symbolic]]
+            address = 0x2000_0010
+            size = 4
+            "#,
+        )
+        .unwrap();
+        assert_eq!(snapshot.pc(), Some(0x0800_0120));
+        assert_eq!(snapshot.registers["SP"], 0x2000_fe00);
+        assert_eq!(snapshot.flags["Z"], 1);
+        assert_eq!(snapshot.memory, [(0x2000_0000, vec![
+            0x00, 0x11, 0x22, 0x33
+        ])]);
+        assert_eq!(snapshot.symbolic_registers, ["R1"]);
+        assert_eq!(snapshot.symbolic_memory[0].size, 4);
+        assert!(!snapshot.keep_lr);
+    }
+
+    #[test]
+    fn test_parse_snapshot_invalid() {
+        // No PC.
+        assert!(Snapshot::from_toml("[registers]\nSP = 0").is_err());
+        // Odd number of digits.
+        assert!(Snapshot::from_toml(
+            "[registers]\nPC = 0\n[[memory]]\naddress = 0\ndata = \"001\""
+        )
+        .is_err());
+        // Both data and a file.
+        assert!(Snapshot::from_toml(
+            "[registers]\nPC = 0\n[[memory]]\naddress = 0\ndata = \"00\"\nfile = \"ram.bin\""
+        )
+        .is_err());
+        assert!(Snapshot::from_toml("[registers]\nPC = 0\n[[memory]]\naddress = 0").is_err());
+    }
+}
@@ -1,14 +1,555 @@
 //! Information about the specific target hardware supplied by the user.
+//!
+//! The target configuration describes which core the code runs on and the
+//! memory map of the device. It is used by the cycle counting to add wait
+//! states for slow memories and to find regions that can be accessed in a
+//! single cycle.
+//!
+//! A configuration can be loaded from a toml file on the form
+//!
+//! ```toml
+//! core = "cortex-m4"
+//!
+//! [[flash]]
+//! start = 0x0800_0000
+//! end = 0x0810_0000
+//! wait_states = 5
+//! line_size = 16
+//! prefetch = true
+//!
+//! [[ram]]
+//! start = 0x2000_0000
+//! end = 0x2002_0000
+//!
+//! [[fast_io]]
+//! start = 0xd000_0000
+//! end = 0xe000_0000
+//! ```
+//!
+//! All regions are given as `start..end`, i.e. the end address is not part of
+//! the region.
+//!
+//! Cores with caches and tightly coupled memories, such as the Cortex-M7, can
+//! describe these as well
+//!
+//! ```toml
+//! core = "cortex-m7"
+//!
+//! # code in ITCM
+//! [[tcm]]
+//! start = 0x0000_0000
+//! end = 0x0001_0000
+//!
+//! [[flash]]
+//! start = 0x0800_0000
+//! end = 0x0810_0000
+//! wait_states = 7
+//!
+//! [icache]
+//! size = 0x4000
+//! line_size = 32
+//! ways = 2
+//! miss_penalty = 4
+//! ```
+//!
+//! The caches are assumed to be cold when the analysis starts and an access is
+//! only counted as a hit if it is guaranteed to hit regardless of the
+//! replacement policy.
+//!
+//! RISC-V cores use a cycle count per instruction class. The counts of the
+//! core can be replaced, classes that are left out take the value of the
+//! generic `rv32imac` core
+//!
+//! ```toml
+//! core = "esp32c3"
+//!
+//! [riscv_cycles]
+//! load = 3
+//! div = 20
+//! ```
 
+use std::{collections::HashMap, fs, path::Path};
+
+use serde::Deserialize;
+
+use super::{ProjectError, Result};
+
+/// Description of the target hardware.
+#[derive(Debug, Clone, Deserialize)]
 pub struct TargetConfiguration {
-    core: CoreFamily,
+    /// The core that executes the code.
+    pub core: CoreFamily,
+
+    /// Flash (or other non volatile) memory regions.
+    #[serde(default)]
+    pub flash: Vec<MemoryRegion>,
+
+    /// RAM regions.
+    #[serde(default)]
+    pub ram: Vec<MemoryRegion>,
+
+    /// Regions that are accessible in a single cycle, such as the single-cycle
+    /// IO port on the Cortex-M0+.
+    #[serde(default)]
+    pub fast_io: Vec<MemoryRegion>,
+
+    /// Tightly coupled memories. These are never cached.
+    #[serde(default)]
+    pub tcm: Vec<MemoryRegion>,
+
+    /// Instruction cache in front of the flash and RAM regions.
+    #[serde(default)]
+    pub icache: Option<Cache>,
+
+    /// Data cache in front of the flash and RAM regions.
+    #[serde(default)]
+    pub dcache: Option<Cache>,
+
+    /// Cycle counts used instead of the built in counts for RISC-V cores.
+    #[serde(default)]
+    pub riscv_cycles: Option<RiscVCycles>,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(untagged)]
 pub enum CoreFamily {
     ArmM(ArmMCore),
+    RiscV(RiscVCore),
+    AArch64(AArch64Core),
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
 pub enum ArmMCore {
+    #[serde(rename = "cortex-m0")]
     ArmM0,
+    #[serde(rename = "cortex-m0plus")]
     ArmM0Plus,
-}
\ No newline at end of file
+    #[serde(rename = "cortex-m3")]
+    ArmM3,
+    #[serde(rename = "cortex-m4")]
+    ArmM4,
+    #[serde(rename = "cortex-m7")]
+    ArmM7,
+    #[serde(rename = "cortex-m23")]
+    ArmM23,
+    #[serde(rename = "cortex-m33")]
+    ArmM33,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub enum RiscVCore {
+    /// A generic in order RV32IMAC core.
+    #[serde(rename = "rv32imac")]
+    Rv32Imac,
+    /// The ESP32-C3.
+    #[serde(rename = "esp32c3")]
+    Esp32C3,
+    /// The Nuclei Bumblebee core used in the GD32VF103.
+    #[serde(rename = "bumblebee", alias = "gd32vf103")]
+    Bumblebee,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub enum AArch64Core {
+    #[serde(rename = "cortex-a53")]
+    CortexA53,
+}
+
+/// Number of cycles for each class of RISC-V instructions, not counting wait
+/// states.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct RiscVCycles {
+    /// Integer register and immediate instructions, `lui` and `auipc`.
+    pub alu: usize,
+
+    /// Loads, including `lr.w`.
+    pub load: usize,
+
+    /// Stores, including `sc.w`.
+    pub store: usize,
+
+    /// Conditional branches that are taken.
+    pub branch_taken: usize,
+
+    /// Conditional branches that are not taken.
+    pub branch_not_taken: usize,
+
+    /// `jal` and `jalr`.
+    pub jump: usize,
+
+    /// Multiplications.
+    pub mul: usize,
+
+    /// Divisions and remainders.
+    pub div: usize,
+
+    /// Atomic memory operations.
+    pub amo: usize,
+
+    /// CSR accesses, fences and environment calls.
+    pub system: usize,
+}
+
+impl Default for RiscVCycles {
+    fn default() -> Self {
+        RiscVCore::Rv32Imac.cycles()
+    }
+}
+
+impl RiscVCore {
+    /// The built in cycle counts of the core.
+    ///
+    /// The counts are upper bounds taken from the vendor documentation where
+    /// it exists, the generic core uses the counts of a simple five stage
+    /// pipeline without branch prediction.
+    pub fn cycles(&self) -> RiscVCycles {
+        match self {
+            RiscVCore::Rv32Imac => RiscVCycles {
+                alu: 1,
+                load: 2,
+                store: 1,
+                branch_taken: 3,
+                branch_not_taken: 1,
+                jump: 3,
+                mul: 1,
+                div: 34,
+                amo: 3,
+                system: 1,
+            },
+            RiscVCore::Esp32C3 => RiscVCycles {
+                alu: 1,
+                load: 2,
+                store: 1,
+                branch_taken: 3,
+                branch_not_taken: 1,
+                jump: 2,
+                mul: 1,
+                div: 34,
+                amo: 4,
+                system: 1,
+            },
+            RiscVCore::Bumblebee => RiscVCycles {
+                alu: 1,
+                load: 2,
+                store: 1,
+                branch_taken: 2,
+                branch_not_taken: 1,
+                jump: 2,
+                mul: 17,
+                div: 33,
+                amo: 4,
+                system: 1,
+            },
+        }
+    }
+}
+
+/// A contiguous region of memory.
+#[derive(Debug, Clone, Deserialize)]
+pub struct MemoryRegion {
+    /// First address in the region.
+    pub start: u64,
+
+    /// First address after the region.
+    pub end: u64,
+
+    /// Number of wait states for each access to the region.
+    #[serde(default)]
+    pub wait_states: usize,
+
+    /// Number of bytes read from the region in a single access. Only used for
+    /// instruction fetches. Default is `4`.
+    #[serde(default = "default_line_size")]
+    pub line_size: u64,
+
+    /// Whether the region has a prefetch buffer that fetches the next line
+    /// while the current line is executing.
+    #[serde(default)]
+    pub prefetch: bool,
+}
+
+fn default_line_size() -> u64 {
+    4
+}
+
+/// Description of a set associative cache.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Cache {
+    /// Size of the cache in bytes.
+    pub size: u64,
+
+    /// Number of bytes in a cache line.
+    pub line_size: u64,
+
+    /// Number of ways in each set.
+    pub ways: u64,
+
+    /// Cycles needed to fill a line on a miss, in addition to the wait states
+    /// of the region that is read.
+    #[serde(default)]
+    pub miss_penalty: usize,
+}
+
+/// The lines that are guaranteed to be in a cache.
+///
+/// The replacement policy is not modeled, so a miss in a set might evict any
+/// line in that set. Only lines that were accessed after the last miss in
+/// their set are known to be cached.
+#[derive(Debug, Clone, Default)]
+pub struct CacheState {
+    sets: HashMap<u64, Vec<u64>>,
+}
+
+impl CacheState {
+    /// Accesses `address`, returns `true` if the access is guaranteed to hit.
+    pub fn access(&mut self, cache: &Cache, address: u64) -> bool {
+        let line = address / cache.line_size.max(1);
+        let sets = (cache.size / (cache.line_size * cache.ways).max(1)).max(1);
+        let lines = self.sets.entry(line % sets).or_default();
+        if lines.contains(&line) {
+            return true;
+        }
+        lines.clear();
+        lines.push(line);
+        false
+    }
+
+    /// Keeps only the lines that are known to be cached in both states.
+    pub fn join(&mut self, other: &CacheState) {
+        self.sets.retain(|set, lines| match other.sets.get(set) {
+            Some(other_lines) => {
+                lines.retain(|line| other_lines.contains(line));
+                !lines.is_empty()
+            }
+            None => false,
+        });
+    }
+}
+
+impl MemoryRegion {
+    /// Creates a region without wait states.
+    pub fn new(start: u64, end: u64) -> Self {
+        Self {
+            start,
+            end,
+            wait_states: 0,
+            line_size: default_line_size(),
+            prefetch: false,
+        }
+    }
+
+    pub fn contains(&self, address: u64) -> bool {
+        address >= self.start && address < self.end
+    }
+
+    /// Returns the address of the fetch line that contains `address`.
+    pub fn line_of(&self, address: u64) -> u64 {
+        address - address % self.line_size.max(1)
+    }
+}
+
+impl TargetConfiguration {
+    /// Creates a configuration for `core` where all memory is accessed without
+    /// wait states.
+    pub fn zero_wait_states(core: CoreFamily) -> Self {
+        Self {
+            core,
+            flash: vec![],
+            ram: vec![],
+            fast_io: vec![],
+            tcm: vec![],
+            icache: None,
+            dcache: None,
+            riscv_cycles: None,
+        }
+    }
+
+    /// Reads a configuration from a toml file.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let data = fs::read_to_string(path).map_err(|e| {
+            ProjectError::UnableToParseTargetConfiguration(format!("{}: {e}", path.display()))
+        })?;
+        Self::from_toml(&data)
+    }
+
+    /// Parses a configuration from a toml string.
+    pub fn from_toml(data: &str) -> Result<Self> {
+        let ret: Self = toml::from_str(data)
+            .map_err(|e| ProjectError::UnableToParseTargetConfiguration(e.to_string()))?;
+        for region in ret
+            .flash
+            .iter()
+            .chain(&ret.ram)
+            .chain(&ret.fast_io)
+            .chain(&ret.tcm)
+        {
+            if region.start >= region.end {
+                return Err(ProjectError::UnableToParseTargetConfiguration(format!(
+                    "empty memory region {:#X}..{:#X}",
+                    region.start, region.end
+                )));
+            }
+        }
+        for cache in ret.icache.iter().chain(&ret.dcache) {
+            if cache.line_size == 0 || cache.ways == 0 || cache.size < cache.line_size * cache.ways
+            {
+                return Err(ProjectError::UnableToParseTargetConfiguration(
+                    "invalid cache geometry".to_owned(),
+                ));
+            }
+        }
+        Ok(ret)
+    }
+
+    /// Returns the TCM, flash or RAM region containing `address` if any.
+    pub fn region_of(&self, address: u64) -> Option<&MemoryRegion> {
+        self.tcm
+            .iter()
+            .chain(&self.flash)
+            .chain(&self.ram)
+            .find(|region| region.contains(address))
+    }
+
+    /// Cycle counts of the RISC-V core, `None` if the core is not a RISC-V
+    /// core.
+    pub fn riscv_cycles(&self) -> Option<RiscVCycles> {
+        match self.core {
+            CoreFamily::RiscV(core) => Some(self.riscv_cycles.unwrap_or(core.cycles())),
+            CoreFamily::ArmM(_) | CoreFamily::AArch64(_) => None,
+        }
+    }
+
+    /// Checks if `address` is in a tightly coupled memory.
+    pub fn is_tcm(&self, address: u64) -> bool {
+        self.tcm.iter().any(|region| region.contains(address))
+    }
+
+    /// Checks if `address` is in a single cycle region.
+    pub fn is_fast_io(&self, address: u64) -> bool {
+        self.fast_io.iter().any(|region| region.contains(address))
+    }
+
+    /// Number of wait states for a data access to `address` if it is not
+    /// cached.
+    pub fn data_wait_states(&self, address: u64) -> usize {
+        if self.is_fast_io(address) {
+            return 0;
+        }
+        self.region_of(address)
+            .map(|region| region.wait_states)
+            .unwrap_or(0)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{AArch64Core, ArmMCore, CacheState, CoreFamily, RiscVCore, TargetConfiguration};
+
+    #[test]
+    fn test_parse_target() {
+        let target = TargetConfiguration::from_toml(
+            r#"
+            core = "cortex-m4"
+
+            [[flash]]
+            start = 0x0800_0000
+            end = 0x0810_0000
+            wait_states = 5
+            line_size = 16
+
+            [[ram]]
+            start = 0x2000_0000
+            end = 0x2002_0000
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(target.core, CoreFamily::ArmM(ArmMCore::ArmM4));
+        assert_eq!(target.data_wait_states(0x0800_0100), 5);
+        assert_eq!(target.data_wait_states(0x2000_0100), 0);
+        assert_eq!(target.data_wait_states(0x4000_0000), 0);
+        assert_eq!(
+            target.region_of(0x0800_0106).unwrap().line_of(0x0800_0106),
+            0x0800_0100
+        );
+        assert!(target.region_of(0x0810_0000).is_none());
+        assert!(!target.is_fast_io(0xd000_0000));
+    }
+
+    #[test]
+    fn test_cache_state() {
+        let target = TargetConfiguration::from_toml(
+            r#"
+            core = "cortex-m7"
+
+            [[tcm]]
+            start = 0x0000_0000
+            end = 0x0001_0000
+
+            [dcache]
+            size = 0x100
+            line_size = 32
+            ways = 2
+            "#,
+        )
+        .unwrap();
+        assert_eq!(target.core, CoreFamily::ArmM(ArmMCore::ArmM7));
+        assert!(target.is_tcm(0x100));
+
+        // 4 sets of 2 lines.
+        let cache = target.dcache.as_ref().unwrap();
+        let mut state = CacheState::default();
+        assert!(!state.access(cache, 0x2000_0000));
+        assert!(state.access(cache, 0x2000_001c));
+        assert!(!state.access(cache, 0x2000_0020));
+        assert!(state.access(cache, 0x2000_0000));
+
+        // A miss in the same set might evict the line.
+        assert!(!state.access(cache, 0x2000_0080));
+        assert!(!state.access(cache, 0x2000_0000));
+    }
+
+    #[test]
+    fn test_parse_riscv_target() {
+        let target = TargetConfiguration::from_toml(r#"core = "gd32vf103""#).unwrap();
+        assert_eq!(target.core, CoreFamily::RiscV(RiscVCore::Bumblebee));
+        assert_eq!(target.riscv_cycles().unwrap().mul, 17);
+
+        let target = TargetConfiguration::from_toml(
+            r#"
+            core = "esp32c3"
+
+            [riscv_cycles]
+            load = 3
+            "#,
+        )
+        .unwrap();
+        let cycles = target.riscv_cycles().unwrap();
+        assert_eq!(cycles.load, 3);
+        assert_eq!(cycles.div, RiscVCore::Rv32Imac.cycles().div);
+    }
+
+    #[test]
+    fn test_parse_aarch64_target() {
+        let target = TargetConfiguration::from_toml(r#"core = "cortex-a53""#).unwrap();
+        assert_eq!(target.core, CoreFamily::AArch64(AArch64Core::CortexA53));
+        assert!(target.riscv_cycles().is_none());
+    }
+
+    #[test]
+    fn test_parse_target_invalid() {
+        assert!(TargetConfiguration::from_toml(r#"core = "cortex-a72""#).is_err());
+        assert!(TargetConfiguration::from_toml(
+            r#"
+            core = "cortex-m0plus"
+
+            [[fast_io]]
+            start = 0xe000_0000
+            end = 0xd000_0000
+            "#,
+        )
+        .is_err());
+    }
+}
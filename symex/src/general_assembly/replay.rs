@@ -0,0 +1,292 @@
+//! Concrete replay of a path with an instruction trace.
+//!
+//! A finished path is replayed by running the executor again from the initial
+//! state with every input fixed to a value from a model of the path. Each step
+//! of the replay records the PC, the instruction, the registers, flags and
+//! memory it changed and its cycle count, which makes it possible to walk
+//! through a path one instruction at a time and compare the cycle counts to a
+//! trace from hardware.
+//!
+//! Inputs that are read from uninitialized memory are fixed the first time
+//! they are read.
+
+use std::{collections::HashMap, fmt, io::Write};
+
+use serde::Serialize;
+
+use super::{
+    arch::Arch,
+    executor::{GAExecutor, PathResult},
+    path_selection::DFSPathSelection,
+    state::GAState,
+    vm::VM,
+    GAError,
+    Result,
+};
+use crate::smt::{DExpr, DSolver};
+
+/// The format traces are written in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TraceFormat {
+    /// Human readable text.
+    Text,
+    /// One JSON object for each step.
+    JsonLines,
+}
+
+/// Which paths to trace and where to write the traces.
+pub struct TraceConfig {
+    /// The writer all traces are written to.
+    pub writer: Box<dyn Write>,
+
+    /// The format of the traces.
+    pub format: TraceFormat,
+
+    /// Only trace paths that ended in a failure.
+    pub only_failures: bool,
+}
+
+/// The replay of a path.
+#[derive(Debug, Clone, Serialize)]
+pub struct Trace {
+    /// The executed instructions and hooks in order.
+    pub steps: Vec<TraceStep>,
+
+    /// How the replayed path ended.
+    pub result: String,
+}
+
+/// An instruction or hook executed on a replayed path.
+#[derive(Debug, Clone, Serialize)]
+pub struct TraceStep {
+    /// The address of the instruction.
+    pub pc: u64,
+
+    /// The decoded instruction, or `hook` if a hook was executed instead.
+    pub instruction: String,
+
+    /// The registers that changed value.
+    pub registers: Vec<(String, u64)>,
+
+    /// The flags that changed value.
+    pub flags: Vec<(String, u64)>,
+
+    /// The values written to memory as `(address, value, bits)`.
+    pub memory: Vec<(u64, u64, u32)>,
+
+    /// The cycles the step took.
+    pub cycles: usize,
+
+    /// The cycle count after the step.
+    pub total_cycles: usize,
+}
+
+/// Replays `path`, a state at the end of a path that started in `initial`.
+///
+/// The solver must hold the constraints of the path, i.e. the path is replayed
+/// before the next path is run.
+pub fn replay<A: Arch>(initial: &GAState<A>, path: &GAState<A>) -> Result<Trace> {
+    let constraints = path.constraints.clone();
+    constraints.push();
+    constraints.assert(&path.path_condition);
+    let trace = replay_constrained(initial, path);
+    constraints.pop();
+    trace
+}
+
+fn replay_constrained<A: Arch>(initial: &GAState<A>, path: &GAState<A>) -> Result<Trace> {
+    let constraints = &path.constraints;
+    let mut inputs = HashMap::new();
+    for variable in &path.marked_symbolic {
+        let value = concretize(constraints, &variable.value)?;
+        if let Some(name) = &variable.name {
+            inputs.insert(name.to_owned(), value);
+        }
+    }
+
+    let mut state = initial.clone();
+    state.replay_inputs = Some(inputs);
+    concretize_state(&mut state)?;
+
+    let mut vm = VM {
+        project: state.project,
+        paths: DFSPathSelection::new(),
+        initial_state: state.clone(),
+    };
+    let trace = run(GAExecutor::from_state(state, &mut vm, initial.project));
+
+    // The path is fixed so the replay does not fork, but drop any saved path
+    // to restore the solver.
+    while vm.paths.get_path().is_some() {}
+    trace
+}
+
+fn run<A: Arch>(mut executor: GAExecutor<A>) -> Result<Trace> {
+    let mut registers = register_values(&executor.state);
+    let mut flags = flag_values(&executor.state);
+    let mut steps = vec![];
+
+    let result = loop {
+        let state = &executor.state;
+        let pc = state.get_pc();
+        let cycles = state.cycle_count;
+        let instruction = match state.project.get_pc_hook(pc) {
+            Some(_) => "hook".to_owned(),
+            None => state
+                .project
+                .disassemble(pc, &state.architecture)
+                .unwrap_or_else(|| "unknown".to_owned()),
+        };
+
+        if let Some(result) = executor.step()? {
+            break result;
+        }
+        concretize_state(&mut executor.state)?;
+        let state = &executor.state;
+
+        let mut memory = vec![];
+        for (address, value) in &state.memory_writes {
+            let bits = value.len();
+            let value = concretize(&state.constraints, value)?;
+            memory.push((*address, value.get_constant().unwrap_or_default(), bits));
+        }
+        let new_registers = register_values(state);
+        let new_flags = flag_values(state);
+        steps.push(TraceStep {
+            pc,
+            instruction,
+            registers: changed(&registers, &new_registers),
+            flags: changed(&flags, &new_flags),
+            memory,
+            cycles: state.cycle_count - cycles,
+            total_cycles: state.cycle_count,
+        });
+        registers = new_registers;
+        flags = new_flags;
+    };
+
+    Ok(Trace {
+        steps,
+        result: match result {
+            PathResult::Success(_) => "success".to_owned(),
+            PathResult::Failure(reason) => format!("failure: {reason}"),
+            PathResult::AssumptionUnsat => "unsatisfiable assumption".to_owned(),
+            PathResult::Suppress => "suppressed".to_owned(),
+        },
+    })
+}
+
+/// Fixes the value of `expr` to a value that satisfies the constraints.
+fn concretize(constraints: &DSolver, expr: &DExpr) -> Result<DExpr> {
+    if expr.get_constant().is_some() {
+        return Ok(expr.clone());
+    }
+    let value = constraints.get_value(expr)?;
+    constraints.assert(&expr._eq(&value));
+    Ok(value)
+}
+
+/// Fixes the values of all registers and flags that are not constant.
+fn concretize_state<A: Arch>(state: &mut GAState<A>) -> Result<()> {
+    let registers: Vec<_> = state
+        .registers
+        .iter()
+        .filter(|(_, value)| value.get_constant().is_none())
+        .map(|(register, value)| (register, value.clone()))
+        .collect();
+    for (register, value) in registers {
+        let value = concretize(&state.constraints, &value)?;
+        state.registers.insert(register, value);
+    }
+
+    let flags: Vec<_> = state
+        .get_flags()
+        .filter(|(_, value)| value.get_constant().is_none())
+        .map(|(flag, value)| (flag, value.clone()))
+        .collect();
+    for (flag, value) in flags {
+        let value = concretize(&state.constraints, &value)?;
+        state.set_flag(flag, value);
+    }
+    Ok(())
+}
+
+fn register_values<A: Arch>(state: &GAState<A>) -> HashMap<String, u64> {
+    state
+        .registers
+        .iter()
+        .filter_map(|(register, value)| Some((register.name().to_owned(), value.get_constant()?)))
+        .collect()
+}
+
+fn flag_values<A: Arch>(state: &GAState<A>) -> HashMap<String, u64> {
+    state
+        .get_flags()
+        .filter_map(|(flag, value)| Some((flag.name().to_owned(), value.get_constant()?)))
+        .collect()
+}
+
+/// The values in `new` that are not in `old`, sorted by name.
+fn changed(old: &HashMap<String, u64>, new: &HashMap<String, u64>) -> Vec<(String, u64)> {
+    let mut changed: Vec<_> = new
+        .iter()
+        .filter(|(name, value)| old.get(*name) != Some(value))
+        .map(|(name, value)| (name.to_owned(), *value))
+        .collect();
+    changed.sort();
+    changed
+}
+
+impl Trace {
+    /// Writes the trace of path number `path` in `format`.
+    pub fn write(&self, writer: &mut dyn Write, path: usize, format: TraceFormat) -> Result<()> {
+        let result = match format {
+            TraceFormat::Text => write!(writer, "PATH {path}\n{self}"),
+            TraceFormat::JsonLines => self.write_json_lines(writer, path),
+        };
+        result.map_err(|e| GAError::WriteTrace(e.to_string()))
+    }
+
+    fn write_json_lines(&self, writer: &mut dyn Write, path: usize) -> std::io::Result<()> {
+        #[derive(Serialize)]
+        struct Step<'a> {
+            path: usize,
+            #[serde(flatten)]
+            step: &'a TraceStep,
+        }
+        #[derive(Serialize)]
+        struct End<'a> {
+            path: usize,
+            result: &'a str,
+        }
+
+        for step in &self.steps {
+            serde_json::to_writer(&mut *writer, &Step { path, step })?;
+            writeln!(writer)?;
+        }
+        serde_json::to_writer(&mut *writer, &End {
+            path,
+            result: &self.result,
+        })?;
+        writeln!(writer)
+    }
+}
+
+impl fmt::Display for Trace {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for step in &self.steps {
+            writeln!(
+                f,
+                "{:#010x}  {:<48} {:>4} cycles {:>8} total",
+                step.pc, step.instruction, step.cycles, step.total_cycles
+            )?;
+            for (name, value) in step.registers.iter().chain(&step.flags) {
+                writeln!(f, "    {name} = {value:#x}")?;
+            }
+            for (address, value, bits) in &step.memory {
+                writeln!(f, "    [{address:#010x}] = {value:#x} ({bits}-bits)")?;
+            }
+        }
+        writeln!(f, "result: {}", self.result)
+    }
+}
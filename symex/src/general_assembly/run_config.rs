@@ -8,11 +8,13 @@
 //! carried out. Therefore it is advised that one familiarizes oneself with the
 //! inner workings of Symex executor before writing a hook function.
 
+use general_assembly::register::Register;
 use regex::Regex;
 
 use super::{
     arch::Arch,
     project::{
+        target_config::TargetConfiguration,
         MemoryHookAddress,
         MemoryReadHook,
         MemoryWriteHook,
@@ -20,8 +22,12 @@ use super::{
         RegisterReadHook,
         RegisterWriteHook,
     },
+    replay::TraceConfig,
 };
 
+/// Default value of [`RunConfig::max_branch_targets`].
+pub const DEFAULT_MAX_BRANCH_TARGETS: usize = 256;
+
 /// Configures a symbolic execution run.
 pub struct RunConfig<A: Arch> {
     /// Indicate if the result of a completed path should be printed out or not.
@@ -35,11 +41,11 @@ pub struct RunConfig<A: Arch> {
 
     /// A register read hook will run a function instead of reading from a
     /// specified register. There can only be one hook on a single register.
-    pub register_read_hooks: Vec<(String, RegisterReadHook<A>)>,
+    pub register_read_hooks: Vec<(Register, RegisterReadHook<A>)>,
 
     /// A register write hook will run a function instead of writing to a
     /// specified register. There can only be one hook on a single register.
-    pub register_write_hooks: Vec<(String, RegisterWriteHook<A>)>,
+    pub register_write_hooks: Vec<(Register, RegisterWriteHook<A>)>,
 
     /// A memory write hook will run a function instead of writing to a single
     /// address or range of addresses. There can only be one hook on a
@@ -60,6 +66,27 @@ pub struct RunConfig<A: Arch> {
     /// order is preserved it is recommended to ensure that there are no
     /// overlapping ranges.
     pub memory_read_hooks: Vec<(MemoryHookAddress, MemoryReadHook<A>)>,
+
+    /// Description of the target hardware used when counting cycles. If this
+    /// is `None` the default target for the architecture is used.
+    pub target: Option<TargetConfiguration>,
+
+    /// Merge paths that branched apart when they reach the same instruction
+    /// again instead of exploring each path separately. The cycle count of a
+    /// merged path is symbolic and the reported count is its maximum. Default
+    /// is `false`.
+    pub merge_paths: bool,
+
+    /// Maximum number of targets a path is forked to when branching to a
+    /// symbolic address. If the address can take more values than this the
+    /// targets are restricted to the function entry points in `.symtab`, if
+    /// there are still too many the path fails. Default is
+    /// [`DEFAULT_MAX_BRANCH_TARGETS`].
+    pub max_branch_targets: usize,
+
+    /// Replay each finished path with concrete inputs and write its
+    /// instruction trace, see [`replay`](super::replay). Default is `None`.
+    pub trace: Option<TraceConfig>,
 }
 
 impl<A: Arch> Default for RunConfig<A> {
@@ -71,6 +98,10 @@ impl<A: Arch> Default for RunConfig<A> {
             register_write_hooks: vec![],
             memory_write_hooks: vec![],
             memory_read_hooks: vec![],
+            target: None,
+            merge_paths: false,
+            max_branch_targets: DEFAULT_MAX_BRANCH_TARGETS,
+            trace: None,
         }
     }
 }
@@ -1,11 +1,23 @@
 //! Holds the state in general assembly execution.
 
-use std::collections::{HashMap, VecDeque};
+use std::{
+    any::Any,
+    collections::{HashMap, VecDeque},
+    rc::Rc,
+};
 
-use general_assembly::{condition::Condition, operand::DataWord};
+use general_assembly::{
+    condition::Condition,
+    operand::DataWord,
+    register::{Register, RegisterSet},
+};
 use tracing::{debug, trace};
 
-use super::{arch::Arch, instruction::Instruction, project::Project};
+use super::{
+    arch::Arch,
+    instruction::Instruction,
+    project::{image, snapshot::Snapshot, target_config::CacheState, Project},
+};
 use crate::{
     elf_util::{ExpressionType, Variable},
     general_assembly::{
@@ -29,28 +41,138 @@ pub struct ContinueInsideInstruction<A: Arch> {
     pub local: HashMap<String, DExpr>,
 }
 
+/// A branch to a symbolic address that was resolved to a concrete target.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ConcretizedBranch {
+    /// Address of the branch instruction.
+    pub address: u64,
+
+    /// The target this path continued at.
+    pub target: u64,
+
+    /// Number of targets the path was forked to.
+    pub targets: usize,
+
+    /// True if the targets were restricted to function entry points as there
+    /// were too many possible targets.
+    pub function_entries: bool,
+}
+
+/// Register and flag values indexed by [`Register::index`].
+///
+/// Registers that have not been written to yet have no value.
+#[derive(Clone, Debug)]
+pub struct RegisterFile {
+    set: &'static RegisterSet,
+    values: Box<[Option<DExpr>]>,
+}
+
+impl RegisterFile {
+    /// An empty register file for the registers in `set`.
+    pub fn new(set: &'static RegisterSet) -> Self {
+        Self {
+            set,
+            values: vec![None; set.len()].into_boxed_slice(),
+        }
+    }
+
+    /// Get the value of a register if it has been set.
+    pub fn get(&self, register: Register) -> Option<&DExpr> {
+        self.values[register.index()].as_ref()
+    }
+
+    /// Set the value of a register.
+    pub fn insert(&mut self, register: Register, value: DExpr) {
+        self.values[register.index()] = Some(value);
+    }
+
+    /// Checks if the register has been set.
+    pub fn contains_key(&self, register: Register) -> bool {
+        self.get(register).is_some()
+    }
+
+    /// Iterates over all registers that have been set, the flags are not
+    /// included.
+    pub fn iter(&self) -> impl Iterator<Item = (Register, &DExpr)> {
+        self.all().filter(|(register, _)| !register.is_flag())
+    }
+
+    /// Iterates over all flags that have been set.
+    pub fn flags(&self) -> impl Iterator<Item = (Register, &DExpr)> {
+        self.all().filter(|(register, _)| register.is_flag())
+    }
+
+    fn all(&self) -> impl Iterator<Item = (Register, &DExpr)> {
+        self.set
+            .iter()
+            .filter_map(|register| Some((register, self.get(register)?)))
+    }
+
+    /// All registers that have been set.
+    pub fn keys(&self) -> impl Iterator<Item = Register> + '_ {
+        self.iter().map(|(register, _)| register)
+    }
+}
+
+/// An event recorded by a hook, see [`GAState::record_event`].
+pub type Event = Rc<dyn Any>;
+
+/// The events of type `E` in `events` with their cycle counts.
+pub fn events_of<E: Any>(events: &[(usize, Event)]) -> impl Iterator<Item = (usize, &E)> {
+    events
+        .iter()
+        .filter_map(|(cycles, event)| Some((*cycles, event.downcast_ref::<E>()?)))
+}
+
 #[derive(Clone, Debug)]
 pub struct GAState<A: Arch> {
     pub project: &'static Project<A>,
     pub ctx: &'static DContext,
     pub constraints: DSolver,
+    /// All constraints added on this path, as a single expression.
+    pub path_condition: DExpr,
     pub marked_symbolic: Vec<Variable>,
     pub memory: ArrayMemory,
     pub count_cycles: bool,
     pub cycle_count: usize,
+    /// Cycles counted before paths were merged in to this state, the total
+    /// cycle count is this plus `cycle_count`.
+    pub merged_cycle_count: Option<DExpr>,
     pub cycle_laps: Vec<(usize, String)>,
+    /// Events recorded by hooks, with the cycle count at the end of the
+    /// instruction that recorded them, see [`GAState::record_event`].
+    pub events: Vec<(usize, Event)>,
     pub last_instruction: Option<Instruction<A>>,
     pub last_pc: u64,
-    pub registers: HashMap<String, DExpr>,
+    pub registers: RegisterFile,
     pub continue_in_instruction: Option<ContinueInsideInstruction<A>>,
     pub current_instruction: Option<Instruction<A>>,
     pub architecture: A,
     pub inital_sp: u64,
+    /// Addresses accessed in memory by the instruction being executed.
+    pub data_accesses: Vec<u64>,
+    /// Values written to memory by the instruction being executed.
+    pub memory_writes: Vec<(u64, DExpr)>,
+    /// Values of the inputs when a path is replayed, see
+    /// [`replay`](super::replay::replay).
+    pub replay_inputs: Option<HashMap<String, DExpr>>,
+    /// Branches to symbolic addresses taken on this path.
+    pub concretized_branches: Vec<ConcretizedBranch>,
+    last_fetch_line: Option<u64>,
+    cycles_since_fetch: usize,
+    sequential_fetch: bool,
+    /// Events of the current instruction, the cycle count is added when it
+    /// completes.
+    pending_events: Vec<Event>,
+    icache: CacheState,
+    dcache: CacheState,
     pc_register: u64, // this register is special
-    flags: HashMap<String, DExpr>,
     instruction_counter: usize,
     has_jumped: bool,
     instruction_conditions: VecDeque<Condition>,
+    /// The reason the path fails and the condition under which it does, set
+    /// by a hook with [`GAState::fail`].
+    failure: Option<(&'static str, DExpr)>,
 }
 
 impl<A: Arch> GAState<A> {
@@ -63,52 +185,180 @@ impl<A: Arch> GAState<A> {
         end_address: u64,
         architecture: A,
     ) -> Result<Self> {
-        let pc_reg = match project.get_symbol_address(function) {
+        // The entry can also be given as an address for images without symbols.
+        let pc_reg = match project
+            .get_symbol_address(function)
+            .or_else(|| image::parse_address(function))
+        {
             Some(a) => a,
             None => return Err(GAError::EntryFunctionNotFound(function.to_owned())),
         };
         debug!("Found function at addr: {:#X}.", pc_reg);
+        let sp_reg = Self::stack_start(project, &architecture)?;
+
+        Ok(Self::at(
+            ctx,
+            project,
+            constraints,
+            pc_reg,
+            sp_reg,
+            end_address,
+            architecture,
+        ))
+    }
+
+    /// Create a state that resumes at the PC of `snapshot` with the registers
+    /// and memory of the snapshot, see [`Snapshot`].
+    pub fn from_snapshot(
+        ctx: &'static DContext,
+        project: &'static Project<A>,
+        constraints: DSolver,
+        snapshot: &Snapshot,
+        end_address: u64,
+        architecture: A,
+    ) -> Result<Self> {
+        let pc_reg = snapshot.pc().ok_or_else(|| {
+            ProjectError::UnableToParseSnapshot("the snapshot has no PC".to_owned())
+        })?;
+        let sp_reg = match snapshot.registers.get("SP") {
+            Some(sp) => *sp,
+            None => Self::stack_start(project, &architecture)?,
+        };
+        let mut state = Self::at(
+            ctx,
+            project,
+            constraints,
+            pc_reg,
+            sp_reg,
+            end_address,
+            architecture,
+        );
         let ptr_size = project.get_ptr_size();
 
-        let sp_reg = match project.get_symbol_address("_stack_start") {
+        for (name, value) in &snapshot.registers {
+            let register = Self::snapshot_register(name, false)?;
+            if register != Register::PC && (register != Register::LR || snapshot.keep_lr) {
+                state.set_register(register, ctx.from_u64(*value, ptr_size))?;
+            }
+        }
+        for (name, value) in &snapshot.flags {
+            let flag = Self::snapshot_register(name, true)?;
+            state.set_flag(flag, ctx.from_u64(*value, 1));
+        }
+
+        for (address, data) in &snapshot.memory {
+            for (offset, byte) in data.iter().enumerate() {
+                let address = address + offset as u64;
+                // Program memory is read from the file.
+                if !project.address_in_range(address) {
+                    let address = ctx.from_u64(address, ptr_size);
+                    state
+                        .memory
+                        .write(&address, ctx.from_u64(*byte as u64, 8))?;
+                }
+            }
+        }
+
+        for name in &snapshot.symbolic_registers {
+            let register = Self::snapshot_register(name, false)?;
+            let value = ctx.unconstrained(ptr_size, name);
+            state.marked_symbolic.push(Variable {
+                name: Some(name.to_owned()),
+                value: value.clone(),
+                ty: ExpressionType::Integer(ptr_size as usize),
+            });
+            state.set_register(register, value)?;
+        }
+        for symbolic in &snapshot.symbolic_memory {
+            let name = match &symbolic.name {
+                Some(name) => name.to_owned(),
+                None => format!("memory_{:#x}", symbolic.address),
+            };
+            let size = symbolic.size as u32 * 8;
+            let value = ctx.unconstrained(size, &name);
+            state.marked_symbolic.push(Variable {
+                name: Some(name),
+                value: value.clone(),
+                ty: ExpressionType::Integer(size as usize),
+            });
+            let address = ctx.from_u64(symbolic.address, ptr_size);
+            state.memory.write(&address, value)?;
+        }
+
+        Ok(state)
+    }
+
+    /// Resolves the name of a register or a flag in a snapshot.
+    fn snapshot_register(name: &str, flag: bool) -> Result<Register> {
+        match A::REGISTERS.get(name) {
+            Some(register) if register.is_flag() == flag => Ok(register),
+            _ => Err(ProjectError::UnableToParseSnapshot(match flag {
+                true => format!("unknown flag {name}"),
+                false => format!("unknown register {name}"),
+            })
+            .into()),
+        }
+    }
+
+    /// Get the start of the stack from the `_stack_start` symbol or the
+    /// default of the architecture.
+    fn stack_start(project: &Project<A>, architecture: &A) -> Result<u64> {
+        let sp_reg = match project
+            .get_symbol_address("_stack_start")
+            .or_else(|| architecture.default_stack_start())
+        {
             Some(a) => Ok(a),
             None => Err(ProjectError::UnableToParseElf(
                 "start of stack not found".to_owned(),
             )),
         }?;
         debug!("Found stack start at addr: {:#X}.", sp_reg);
+        Ok(sp_reg)
+    }
 
+    /// Create a state that starts at `pc_reg` with an empty memory.
+    fn at(
+        ctx: &'static DContext,
+        project: &'static Project<A>,
+        constraints: DSolver,
+        pc_reg: u64,
+        sp_reg: u64,
+        end_address: u64,
+        architecture: A,
+    ) -> Self {
+        let ptr_size = project.get_ptr_size();
         let memory = ArrayMemory::new(ctx, ptr_size, project.get_endianness());
-        let mut registers = HashMap::new();
+        let mut registers = RegisterFile::new(A::REGISTERS);
         let pc_expr = ctx.from_u64(pc_reg, ptr_size);
-        registers.insert("PC".to_owned(), pc_expr);
+        registers.insert(Register::PC, pc_expr);
 
         let sp_expr = ctx.from_u64(sp_reg, ptr_size);
-        registers.insert("SP".to_owned(), sp_expr);
+        registers.insert(Register::SP, sp_expr);
 
         // set the link register to max value to detect when returning from a function
         let end_pc_expr = ctx.from_u64(end_address, ptr_size);
-        registers.insert("LR".to_owned(), end_pc_expr);
+        registers.insert(Register::LR, end_pc_expr);
 
-        let mut flags = HashMap::new();
-        flags.insert("N".to_owned(), ctx.unconstrained(1, "flags.N"));
-        flags.insert("Z".to_owned(), ctx.unconstrained(1, "flags.Z"));
-        flags.insert("C".to_owned(), ctx.unconstrained(1, "flags.C"));
-        flags.insert("V".to_owned(), ctx.unconstrained(1, "flags.V"));
+        for flag in Register::FLAGS {
+            registers.insert(flag, ctx.unconstrained(1, &format!("flags.{flag}")));
+        }
 
-        Ok(GAState {
+        GAState {
             project,
             ctx,
             constraints,
+            path_condition: ctx.from_bool(true),
             marked_symbolic: Vec::new(),
             memory,
             cycle_count: 0,
+            merged_cycle_count: None,
             cycle_laps: vec![],
+            events: vec![],
             registers,
             pc_register: pc_reg,
-            flags,
             instruction_counter: 0,
             has_jumped: false,
+            failure: None,
             last_instruction: None,
             last_pc: pc_reg,
             count_cycles: true,
@@ -116,8 +366,32 @@ impl<A: Arch> GAState<A> {
             current_instruction: None,
             instruction_conditions: VecDeque::new(),
             architecture,
+            data_accesses: vec![],
+            memory_writes: vec![],
+            replay_inputs: None,
+            concretized_branches: vec![],
+            last_fetch_line: None,
+            cycles_since_fetch: 0,
+            sequential_fetch: false,
+            pending_events: vec![],
+            icache: CacheState::default(),
+            dcache: CacheState::default(),
             inital_sp: sp_reg,
-        })
+        }
+    }
+
+    /// Ends the path with `reason` if `condition` holds, the executor stops
+    /// the instruction after the current operation.
+    ///
+    /// When `condition` is symbolic the paths where it does not hold are
+    /// forked and continue after the operation.
+    pub fn fail(&mut self, reason: &'static str, condition: DExpr) {
+        self.failure = Some((reason, condition));
+    }
+
+    /// Takes the failure set with [`GAState::fail`].
+    pub fn take_failure(&mut self) -> Option<(&'static str, DExpr)> {
+        self.failure.take()
     }
 
     pub fn reset_has_jumped(&mut self) {
@@ -145,36 +419,265 @@ impl<A: Arch> GAState<A> {
     }
 
     /// Gets the last instruction that was executed.
+    ///
+    /// While the cycle count of an instruction is calculated this is the
+    /// instruction executed before it.
     pub fn get_last_instruction(&self) -> Option<Instruction<A>> {
         self.last_instruction.clone()
     }
 
+    /// Checks if the current instruction was reached without a jump, i.e. if
+    /// it was fetched directly after the last instruction.
+    pub fn get_sequential_fetch(&self) -> bool {
+        self.sequential_fetch
+    }
+
+    /// Marks the pipeline as empty, e.g. after an intrinsic has been executed
+    /// in place of a function call.
+    pub fn reset_pipeline(&mut self) {
+        self.last_instruction = None;
+        self.sequential_fetch = false;
+    }
+
     /// Checks if the execution is currently inside of a conditional block.
     pub fn get_in_conditional_block(&self) -> bool {
         !self.instruction_conditions.is_empty()
     }
 
-    /// Increment the cycle counter with the cycle count of the last
+    /// Adds a constraint to the path.
+    pub fn assert(&mut self, constraint: &DExpr) {
+        self.constraints.assert(constraint);
+        self.path_condition = self.path_condition.and(constraint);
+    }
+
+    /// Gets the address of the next instruction.
+    pub fn get_pc(&self) -> u64 {
+        self.pc_register
+    }
+
+    /// Restarts the cycle count from zero.
+    pub fn reset_cycle_count(&mut self) {
+        self.cycle_count = 0;
+        self.merged_cycle_count = None;
+    }
+
+    /// Gets the cycle count as a 64 bit expression. It is only symbolic if
+    /// paths with different cycle counts have been merged.
+    pub fn get_cycle_count_expr(&self) -> DExpr {
+        let cycles = self.ctx.from_u64(self.cycle_count as u64, 64);
+        match &self.merged_cycle_count {
+            Some(merged) => merged.add(&cycles),
+            None => cycles,
+        }
+    }
+
+    /// Checks if the state can be merged with `other`.
+    ///
+    /// The states have to be about to execute the same instruction from the
+    /// same function call, i.e. with the same stack and link register, and
+    /// neither can be in the middle of an instruction or a conditional block.
+    pub fn can_merge(&self, other: &Self) -> bool {
+        let same_constant = |register: Register| match (
+            self.registers.get(register),
+            other.registers.get(register),
+        ) {
+            (Some(lhs), Some(rhs)) => {
+                lhs.get_constant().is_some() && lhs.get_constant() == rhs.get_constant()
+            }
+            (None, None) => true,
+            _ => false,
+        };
+        self.pc_register == other.pc_register
+            && self.continue_in_instruction.is_none()
+            && other.continue_in_instruction.is_none()
+            && self.instruction_conditions.is_empty()
+            && other.instruction_conditions.is_empty()
+            && self.count_cycles == other.count_cycles
+            && same_constant(Register::SP)
+            && same_constant(Register::LR)
+    }
+
+    /// Merges `other` in to this state.
+    ///
+    /// Registers, flags, memory and the cycle count take the value from this
+    /// state if its path condition holds and the value from `other` otherwise.
+    /// The pipeline is assumed to be empty after the merge and only cache
+    /// lines cached in both states are kept. Cycle laps are kept from this
+    /// state.
+    pub fn merge(&mut self, mut other: Self) -> Result<()> {
+        let condition = self.path_condition.clone();
+        let select = |lhs: &DExpr, rhs: &DExpr| match lhs == rhs {
+            true => lhs.clone(),
+            false => condition.ite(lhs, rhs),
+        };
+
+        // Registers that have not been used in one of the paths are unconstrained.
+        let mut registers: Vec<Register> = self.registers.keys().collect();
+        registers.extend(other.registers.keys());
+        registers.sort();
+        registers.dedup();
+        for register in registers {
+            let lhs = self.get_register(register)?;
+            let rhs = other.get_register(register)?;
+            self.registers.insert(register, select(&lhs, &rhs));
+        }
+        for (flag, rhs) in other.registers.flags() {
+            if let Some(lhs) = self.registers.get(flag) {
+                let value = select(lhs, rhs);
+                self.registers.insert(flag, value);
+            }
+        }
+        self.memory.merge(&condition, &other.memory);
+        for variable in other.marked_symbolic.drain(..) {
+            if !self
+                .marked_symbolic
+                .iter()
+                .any(|known| known.name == variable.name)
+            {
+                self.marked_symbolic.push(variable);
+            }
+        }
+        for branch in other.concretized_branches.drain(..) {
+            if !self.concretized_branches.contains(&branch) {
+                self.concretized_branches.push(branch);
+            }
+        }
+
+        let cycles = select(&self.get_cycle_count_expr(), &other.get_cycle_count_expr());
+        self.merged_cycle_count = Some(cycles);
+        self.cycle_count = 0;
+        self.path_condition = self.path_condition.or(&other.path_condition);
+        self.instruction_counter = self.instruction_counter.max(other.instruction_counter);
+
+        self.reset_pipeline();
+        self.last_fetch_line = None;
+        self.cycles_since_fetch = 0;
+        self.icache.join(&other.icache);
+        self.dcache.join(&other.dcache);
+        self.architecture.merge(&other.architecture);
+        Ok(())
+    }
+
+    /// Increment the cycle counter with the cycle count of the current
     /// instruction.
+    ///
+    /// This should be called after the instruction has executed but before it
+    /// is set as the last instruction.
     pub fn increment_cycle_count(&mut self) {
         // do nothing if cycles should not be counted
         if !self.count_cycles {
+            self.complete_events();
             return;
         }
 
-        let cycles = match &self.last_instruction {
-            Some(i) => match i.max_cycle {
-                super::instruction::CycleCount::Value(v) => v,
-                super::instruction::CycleCount::Function(f) => f(self),
-            },
+        let cycles = match &self.current_instruction {
+            Some(i) => {
+                let size = i.instruction_size;
+                let cycles = match i.max_cycle {
+                    super::instruction::CycleCount::Value(v) => v,
+                    super::instruction::CycleCount::Function(f) => f(self),
+                };
+                cycles + self.wait_states(size, cycles)
+            }
             None => 0,
         };
         trace!(
             "Incrementing cycles: {}, for {:?}",
             cycles,
-            self.last_instruction
+            self.current_instruction
         );
         self.cycle_count += cycles;
+        self.sequential_fetch = !self.has_jumped;
+        self.complete_events();
+    }
+
+    /// Records an event made by the current instruction, it is given the cycle
+    /// count at the end of the instruction when the instruction completes.
+    ///
+    /// The event can be of any type, the hooks that record it read it back
+    /// with [`GAState::events_of`].
+    pub fn record_event<E: Any>(&mut self, event: E) {
+        self.pending_events.push(Rc::new(event));
+    }
+
+    /// The recorded events of type `E` with their cycle counts.
+    pub fn events_of<E: Any>(&self) -> impl Iterator<Item = (usize, &E)> {
+        events_of(&self.events)
+    }
+
+    /// Gives the events of the current instruction the cycle count at its end.
+    fn complete_events(&mut self) {
+        let cycle_count = self.cycle_count;
+        self.events.extend(
+            self.pending_events
+                .drain(..)
+                .map(|event| (cycle_count, event)),
+        );
+    }
+
+    /// Wait states for fetching the current instruction and for the memory
+    /// accesses it made, as described by the target configuration.
+    ///
+    /// A fetch only incurs wait states when the instruction is not in the
+    /// same fetch line as the previously fetched instruction. If the region
+    /// has a prefetch buffer the next line is fetched while the current line
+    /// executes, so sequential execution only stalls for the wait states that
+    /// were not hidden by the instructions in the previous line. Accesses that
+    /// are guaranteed to hit in a cache have no wait states.
+    fn wait_states(&mut self, instruction_size: u32, cycles: usize) -> usize {
+        let target = self.project.get_target();
+        let mut stall = 0;
+
+        let first = self.last_pc;
+        let last = first + (instruction_size as u64 / 8).saturating_sub(1);
+        for address in [first, last] {
+            if let Some(region) = target.region_of(address) {
+                let line = region.line_of(address);
+                if self.last_fetch_line != Some(line) {
+                    let wait_states = match (&target.icache, target.is_tcm(address)) {
+                        (Some(cache), false) => match self.icache.access(cache, address) {
+                            true => 0,
+                            false => region.wait_states + cache.miss_penalty,
+                        },
+                        _ => region.wait_states,
+                    };
+                    let prefetched = region.prefetch
+                        && (address != first || self.sequential_fetch)
+                        && self.last_fetch_line == line.checked_sub(region.line_size);
+                    stall += match prefetched {
+                        true => wait_states.saturating_sub(self.cycles_since_fetch),
+                        false => wait_states,
+                    };
+                    self.last_fetch_line = Some(line);
+                    self.cycles_since_fetch = 0;
+                }
+            }
+        }
+
+        let mut data_stall = 0;
+        for address in &self.data_accesses {
+            let wait_states = target.data_wait_states(*address);
+            data_stall += match &target.dcache {
+                Some(cache)
+                    if target.region_of(*address).is_some()
+                        && !target.is_tcm(*address)
+                        && !target.is_fast_io(*address) =>
+                {
+                    match self.dcache.access(cache, *address) {
+                        true => 0,
+                        false => wait_states + cache.miss_penalty,
+                    }
+                }
+                _ => wait_states,
+            };
+        }
+
+        // Slow data accesses occupy the memory and restart any ongoing prefetch.
+        match data_stall {
+            0 => self.cycles_since_fetch += cycles,
+            _ => self.cycles_since_fetch = 0,
+        }
+        stall + data_stall
     }
 
     /// Update the last instruction that was executed.
@@ -211,33 +714,34 @@ impl<A: Arch> GAState<A> {
         debug!("Found stack start at addr: {:#X}.", sp_reg);
 
         let memory = ArrayMemory::new(ctx, ptr_size, project.get_endianness());
-        let mut registers = HashMap::new();
+        let mut registers = RegisterFile::new(A::REGISTERS);
         let pc_expr = ctx.from_u64(pc_reg, ptr_size);
-        registers.insert("PC".to_owned(), pc_expr);
+        registers.insert(Register::PC, pc_expr);
 
         let sp_expr = ctx.from_u64(sp_reg, ptr_size);
-        registers.insert("SP".to_owned(), sp_expr);
+        registers.insert(Register::SP, sp_expr);
 
-        let mut flags = HashMap::new();
-        flags.insert("N".to_owned(), ctx.unconstrained(1, "flags.N"));
-        flags.insert("Z".to_owned(), ctx.unconstrained(1, "flags.Z"));
-        flags.insert("C".to_owned(), ctx.unconstrained(1, "flags.C"));
-        flags.insert("V".to_owned(), ctx.unconstrained(1, "flags.V"));
+        for flag in Register::FLAGS {
+            registers.insert(flag, ctx.unconstrained(1, &format!("flags.{flag}")));
+        }
 
         GAState {
             project,
             ctx,
             constraints,
+            path_condition: ctx.from_bool(true),
             marked_symbolic: Vec::new(),
             memory,
             cycle_count: 0,
+            merged_cycle_count: None,
             cycle_laps: vec![],
+            events: vec![],
             registers,
             pc_register: pc_reg,
-            flags,
             inital_sp: start_pc,
             instruction_counter: 0,
             has_jumped: false,
+            failure: None,
             last_instruction: None,
             last_pc: pc_reg,
             count_cycles: true,
@@ -245,42 +749,33 @@ impl<A: Arch> GAState<A> {
             current_instruction: None,
             instruction_conditions: VecDeque::new(),
             architecture,
+            data_accesses: vec![],
+            memory_writes: vec![],
+            replay_inputs: None,
+            concretized_branches: vec![],
+            last_fetch_line: None,
+            cycles_since_fetch: 0,
+            sequential_fetch: false,
+            pending_events: vec![],
+            icache: CacheState::default(),
+            dcache: CacheState::default(),
         }
     }
 
     /// Set a value to a register.
-    pub fn set_register(&mut self, register: String, expr: DExpr) -> Result<()> {
+    pub fn set_register(&mut self, register: Register, expr: DExpr) -> Result<()> {
         // crude solution should prbobly change
-        if register == "PC" {
+        if register == Register::PC {
+            // Symbolic targets are resolved by the executor before they are
+            // written.
             let value = match expr.get_constant() {
-                Some(v) => {
-                    // assert!(v % 4 == 0);
-                    v
-                }
-                None => {
-                    trace!("not a concrete pc try to generate possible values");
-                    let values: Vec<u64> = match self.constraints.get_values(&expr, 500).unwrap() {
-                        crate::smt::Solutions::Exactly(v) => v
-                            .iter()
-                            .map(|n| match n.get_constant() {
-                                Some(v) => v,
-                                None => todo!("e"),
-                            })
-                            .collect(),
-                        crate::smt::Solutions::AtLeast(_v) => todo!("Handle with lower bound, this should likely be done using a sub sample of the signal"),
-                    };
-                    trace!("{} possible PC values", values.len());
-                    for v in values {
-                        trace!("Possible PC: {:#X}", v);
-                    }
-
-                    todo!("handel symbolic branch")
-                }
+                Some(v) => v,
+                None => return Err(GAError::SymbolicPC),
             };
             self.pc_register = value;
         }
 
-        match self.project.get_register_write_hook(&register) {
+        match self.project.get_register_write_hook(register) {
             Some(hook) => hook(self, expr),
             None => {
                 self.registers.insert(register, expr);
@@ -290,41 +785,57 @@ impl<A: Arch> GAState<A> {
     }
 
     /// Get the value stored at a register.
-    pub fn get_register(&mut self, register: String) -> Result<DExpr> {
+    pub fn get_register(&mut self, register: Register) -> Result<DExpr> {
         // check register hooks
-        match self.project.get_register_read_hook(&register) {
+        match self.project.get_register_read_hook(register) {
             // run hook if found
             Some(hook) => Ok(hook(self)?),
             // if no hook found read like normal
-            None => match self.registers.get(&register) {
+            None => match self.registers.get(register) {
                 Some(v) => Ok(v.to_owned()),
                 None => {
                     // If register do not exist yet create it with unconstrained value.
-                    let value = self
-                        .ctx
-                        .unconstrained(self.project.get_word_size(), &register);
+                    let value = self.new_input(self.project.get_word_size(), register.name());
                     self.marked_symbolic.push(Variable {
-                        name: Some(register.to_owned()),
+                        name: Some(register.name().to_owned()),
                         value: value.clone(),
                         ty: ExpressionType::Integer(self.project.get_word_size() as usize),
                     });
-                    self.registers.insert(register.to_owned(), value.to_owned());
+                    self.registers.insert(register, value.to_owned());
                     Ok(value)
                 }
             },
         }
     }
 
+    /// Creates an unconstrained input named `name`, or the value the input
+    /// had on the path that is replayed.
+    pub fn new_input(&self, bits: u32, name: &str) -> DExpr {
+        match self
+            .replay_inputs
+            .as_ref()
+            .and_then(|inputs| inputs.get(name))
+        {
+            Some(value) => value.clone(),
+            None => self.ctx.unconstrained(bits, name),
+        }
+    }
+
+    /// Get the values of all flags.
+    pub fn get_flags(&self) -> impl Iterator<Item = (Register, &DExpr)> {
+        self.registers.flags()
+    }
+
     /// Set the value of a flag.
-    pub fn set_flag(&mut self, flag: String, expr: DExpr) {
+    pub fn set_flag(&mut self, flag: Register, expr: DExpr) {
         let expr = expr.simplify().simplify();
         trace!("flag {} set to {:?}", flag, expr);
-        self.flags.insert(flag, expr);
+        self.registers.insert(flag, expr);
     }
 
     /// Get the value of a flag.
-    pub fn get_flag(&mut self, flag: String) -> Option<DExpr> {
-        match self.flags.get(&flag) {
+    pub fn get_flag(&mut self, flag: Register) -> Option<DExpr> {
+        match self.registers.get(flag) {
             Some(v) => Some(v.to_owned()),
             None => todo!(),
         }
@@ -333,44 +844,44 @@ impl<A: Arch> GAState<A> {
     /// Get the expression for a condition based on the current flag values.
     pub fn get_expr(&mut self, condition: &Condition) -> Result<DExpr> {
         Ok(match condition {
-            Condition::EQ => self.get_flag("Z".to_owned()).unwrap(),
-            Condition::NE => self.get_flag("Z".to_owned()).unwrap().not(),
-            Condition::CS => self.get_flag("C".to_owned()).unwrap(),
-            Condition::CC => self.get_flag("C".to_owned()).unwrap().not(),
-            Condition::MI => self.get_flag("N".to_owned()).unwrap(),
-            Condition::PL => self.get_flag("N".to_owned()).unwrap().not(),
-            Condition::VS => self.get_flag("V".to_owned()).unwrap(),
-            Condition::VC => self.get_flag("V".to_owned()).unwrap().not(),
+            Condition::EQ => self.get_flag(Register::Z).unwrap(),
+            Condition::NE => self.get_flag(Register::Z).unwrap().not(),
+            Condition::CS => self.get_flag(Register::C).unwrap(),
+            Condition::CC => self.get_flag(Register::C).unwrap().not(),
+            Condition::MI => self.get_flag(Register::N).unwrap(),
+            Condition::PL => self.get_flag(Register::N).unwrap().not(),
+            Condition::VS => self.get_flag(Register::V).unwrap(),
+            Condition::VC => self.get_flag(Register::V).unwrap().not(),
             Condition::HI => {
-                let c = self.get_flag("C".to_owned()).unwrap();
-                let z = self.get_flag("Z".to_owned()).unwrap().not();
+                let c = self.get_flag(Register::C).unwrap();
+                let z = self.get_flag(Register::Z).unwrap().not();
                 c.and(&z)
             }
             Condition::LS => {
-                let c = self.get_flag("C".to_owned()).unwrap().not();
-                let z = self.get_flag("Z".to_owned()).unwrap();
+                let c = self.get_flag(Register::C).unwrap().not();
+                let z = self.get_flag(Register::Z).unwrap();
                 c.or(&z)
             }
             Condition::GE => {
-                let n = self.get_flag("N".to_owned()).unwrap();
-                let v = self.get_flag("V".to_owned()).unwrap();
+                let n = self.get_flag(Register::N).unwrap();
+                let v = self.get_flag(Register::V).unwrap();
                 n.xor(&v).not()
             }
             Condition::LT => {
-                let n = self.get_flag("N".to_owned()).unwrap();
-                let v = self.get_flag("V".to_owned()).unwrap();
+                let n = self.get_flag(Register::N).unwrap();
+                let v = self.get_flag(Register::V).unwrap();
                 n._ne(&v)
             }
             Condition::GT => {
-                let z = self.get_flag("Z".to_owned()).unwrap();
-                let n = self.get_flag("N".to_owned()).unwrap();
-                let v = self.get_flag("V".to_owned()).unwrap();
+                let z = self.get_flag(Register::Z).unwrap();
+                let n = self.get_flag(Register::N).unwrap();
+                let v = self.get_flag(Register::V).unwrap();
                 z.not().and(&n._eq(&v))
             }
             Condition::LE => {
-                let z = self.get_flag("Z".to_owned()).unwrap();
-                let n = self.get_flag("N".to_owned()).unwrap();
-                let v = self.get_flag("V".to_owned()).unwrap();
+                let z = self.get_flag(Register::Z).unwrap();
+                let n = self.get_flag(Register::N).unwrap();
+                let v = self.get_flag(Register::V).unwrap();
                 z.and(&n._ne(&v))
             }
             Condition::None => self.ctx.from_bool(true),
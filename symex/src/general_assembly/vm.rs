@@ -4,7 +4,7 @@ use super::{
     arch::Arch,
     executor::{GAExecutor, PathResult},
     path_selection::DFSPathSelection,
-    project::Project,
+    project::{snapshot::Snapshot, Project},
     Result,
 };
 use crate::{
@@ -16,6 +16,8 @@ use crate::{
 pub struct VM<A: Arch> {
     pub project: &'static Project<A>,
     pub paths: DFSPathSelection<A>,
+    /// The state all paths start from, used to replay the paths.
+    pub initial_state: GAState<A>,
 }
 
 impl<A: Arch> VM<A> {
@@ -26,23 +28,31 @@ impl<A: Arch> VM<A> {
         end_pc: u64,
         architecture: A,
     ) -> Result<Self> {
-        let mut vm = Self {
-            project,
-            paths: DFSPathSelection::new(),
-        };
-
         let solver = DSolver::new(ctx);
         let state = GAState::<A>::new(ctx, project, solver, fn_name, end_pc, architecture)?;
+        Ok(Self::new_with_state(project, state))
+    }
 
-        vm.paths.save_path(Path::new(state, None));
-
-        Ok(vm)
+    /// Creates a VM that resumes at the PC of `snapshot`, see
+    /// [`GAState::from_snapshot`].
+    pub fn from_snapshot(
+        project: &'static Project<A>,
+        ctx: &'static DContext,
+        snapshot: &Snapshot,
+        end_pc: u64,
+        architecture: A,
+    ) -> Result<Self> {
+        let solver = DSolver::new(ctx);
+        let state =
+            GAState::<A>::from_snapshot(ctx, project, solver, snapshot, end_pc, architecture)?;
+        Ok(Self::new_with_state(project, state))
     }
 
     pub fn new_with_state(project: &'static Project<A>, state: GAState<A>) -> Self {
         let mut vm = Self {
             project,
             paths: DFSPathSelection::new(),
+            initial_state: state.clone(),
         };
 
         vm.paths.save_path(Path::new(state, None));
@@ -56,7 +66,7 @@ impl<A: Arch> VM<A> {
             let mut executor = GAExecutor::from_state(path.state, self, self.project);
 
             for constraint in path.constraints {
-                executor.state.constraints.assert(&constraint);
+                executor.state.assert(&constraint);
             }
 
             let result = executor.resume_execution()?;
@@ -64,4 +74,59 @@ impl<A: Arch> VM<A> {
         }
         Ok(None)
     }
+
+    /// Runs all paths to completion, merging paths that reach the same
+    /// instruction.
+    ///
+    /// The paths are executed one instruction at a time, always advancing the
+    /// path with the lowest program counter so that paths that branched apart
+    /// are merged where they rejoin. The returned states are not constrained
+    /// by the solver, their constraints are in their path condition.
+    pub fn run_merged(&mut self) -> Result<Vec<(PathResult, GAState<A>)>> {
+        let mut states: Vec<GAState<A>> = vec![];
+        let mut finished = vec![];
+        self.take_paths(&mut states)?;
+
+        while let Some(index) = (0..states.len()).min_by_key(|index| states[*index].get_pc()) {
+            let state = states.swap_remove(index);
+            state.constraints.push();
+            state.constraints.assert(&state.path_condition);
+
+            let mut executor = GAExecutor::from_state(state, self, self.project);
+            let result = executor.step()?;
+            let state = executor.state;
+            self.take_paths(&mut states)?;
+            state.constraints.pop();
+
+            match result {
+                Some(result) => finished.push((result, state)),
+                None => Self::merge_into(&mut states, state)?,
+            }
+        }
+        Ok(finished)
+    }
+
+    /// Moves all saved paths in to `states`.
+    fn take_paths(&mut self, states: &mut Vec<GAState<A>>) -> Result<()> {
+        while let Some(path) = self.paths.get_path() {
+            let mut state = path.state;
+            for constraint in path.constraints {
+                state.path_condition = state.path_condition.and(&constraint);
+            }
+            Self::merge_into(states, state)?;
+        }
+        Ok(())
+    }
+
+    /// Merges `state` with a waiting state at the same instruction if there is
+    /// one, otherwise adds it to the waiting states.
+    fn merge_into(states: &mut Vec<GAState<A>>, state: GAState<A>) -> Result<()> {
+        match states.iter_mut().find(|waiting| waiting.can_merge(&state)) {
+            Some(waiting) => waiting.merge(state),
+            None => {
+                states.push(state);
+                Ok(())
+            }
+        }
+    }
 }
@@ -7,9 +7,11 @@
 // TODO: Remove this and add crate level docs
 #![allow(rustdoc::missing_crate_level_docs)]
 
+pub mod analysis;
 pub mod elf_util;
 pub mod general_assembly;
 pub mod memory;
+pub mod rtic;
 //#[cfg(not(feature = "llvm"))]
 pub mod run_elf;
 #[cfg(feature = "llvm")]
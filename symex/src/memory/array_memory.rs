@@ -10,9 +10,11 @@
 //! to other memory models, and in general this memory model is slower compared
 //! to e.g. object memory. However, it may provide better performance in certain
 //! situations.
+use std::collections::HashMap;
+
 use tracing::trace;
 
-use super::{MemoryError, BITS_IN_BYTE};
+use super::{linear_allocator::LinearAllocator, MemoryError, BITS_IN_BYTE};
 use crate::{
     general_assembly::Endianness,
     smt::{DArray, DContext, DExpr},
@@ -32,6 +34,12 @@ pub struct ArrayMemory {
 
     /// Memory endianess
     endianness: Endianness,
+
+    /// Allocator used to generate new addresses.
+    allocator: LinearAllocator,
+
+    /// Size in bits of the allocations, by start address.
+    allocations: HashMap<u64, u64>,
 }
 
 impl ArrayMemory {
@@ -68,9 +76,32 @@ impl ArrayMemory {
             ptr_size,
             memory,
             endianness,
+            allocator: LinearAllocator::new(),
+            allocations: HashMap::new(),
         }
     }
 
+    /// Allocate `bits` of memory returning the newly allocated address.
+    ///
+    /// The memory is not tracked in any other way, so accesses outside of the
+    /// allocation are not detected.
+    pub fn allocate(&mut self, bits: u64, align: u64) -> Result<u64, MemoryError> {
+        let (address, _bytes) = self.allocator.get_address(bits, align)?;
+        self.allocations.insert(address, bits);
+        Ok(address)
+    }
+
+    /// Returns the size in bits of the allocation starting at `address`.
+    pub fn allocation_size(&self, address: u64) -> Option<u64> {
+        self.allocations.get(&address).copied()
+    }
+
+    /// Combines the memory with the memory of another path, the result reads
+    /// as `self` if `condition` holds and as `other` otherwise.
+    pub fn merge(&mut self, condition: &DExpr, other: &Self) {
+        self.memory = condition.ite_array(&self.memory, &other.memory);
+    }
+
     /// Reads an u8 from the given address.
     fn read_u8(&self, addr: &DExpr) -> DExpr {
         self.memory.read(addr)
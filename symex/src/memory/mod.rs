@@ -57,6 +57,29 @@ pub enum MemoryError {
     #[error("Out of bounds")]
     OutOfBounds,
 
+    /// An access that is outside of the object the address is closest to,
+    /// which is allocated at the given address.
+    #[error("Out of bounds access to the object at {0:#x}")]
+    ObjectOutOfBounds(u64),
+
+    /// The address points into an allocation that has been freed, e.g. a
+    /// stack allocation in a function that has returned.
+    #[error("Use after free of the object at {0:#x}")]
+    UseAfterFree(u64),
+
+    /// The object at the given address has already been freed.
+    #[error("Double free of the object at {0:#x}")]
+    DoubleFree(u64),
+
+    /// The address passed to free is not the start of an allocation.
+    #[error("Free of {0:#x} which is not an allocated object")]
+    InvalidFree(u64),
+
+    /// Deallocation with a size or alignment that differs from the
+    /// allocation.
+    #[error("Deallocation of the object at {0:#x} with mismatched size or alignment")]
+    MismatchedDealloc(u64),
+
     /// Errors passed on from the solver.
     #[error(transparent)]
     Solver(#[from] SolverError),
@@ -15,6 +15,11 @@ pub struct MemoryObject {
 
     size: u64,
 
+    align: u64,
+
+    /// Description of where the object was allocated, used in error reports.
+    site: Option<String>,
+
     bv: DExpr,
 }
 
@@ -22,6 +27,14 @@ impl MemoryObject {
     pub fn bit_size(&self) -> u64 {
         self.size
     }
+
+    pub fn site(&self) -> Option<&str> {
+        self.site.as_deref()
+    }
+
+    fn bytes(&self) -> u64 {
+        self.size.div_ceil(8)
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -33,6 +46,12 @@ pub struct ObjectMemory {
 
     objects: BTreeMap<u64, MemoryObject>,
 
+    /// Objects that have been freed.
+    ///
+    /// Addresses are never reused, so these are kept to detect uses after
+    /// free and double frees.
+    freed: BTreeMap<u64, MemoryObject>,
+
     solver: DSolver,
 
     ptr_size: u32,
@@ -46,6 +65,7 @@ impl ObjectMemory {
             ctx,
             allocator: LinearAllocator::new(),
             objects: BTreeMap::new(),
+            freed: BTreeMap::new(),
             ptr_size,
             alloc_id: 0,
             solver,
@@ -56,9 +76,36 @@ impl ObjectMemory {
         self.objects.get(&address)
     }
 
+    /// Get the object allocated at `address`, even if it has been freed.
+    pub fn get_allocated_object(&self, address: u64) -> Option<&MemoryObject> {
+        self.objects
+            .get(&address)
+            .or_else(|| self.freed.get(&address))
+    }
+
+    /// Number of bytes from `address` to the end of the object it is in.
+    pub fn bytes_to_end(&self, address: u64) -> Option<u64> {
+        let (start, object) = self.objects.range(0..=address).next_back()?;
+        let end = start + object.bytes();
+        (address < end).then_some(end - address)
+    }
+
     /// Allocate `bits` of memory returning the newly allocated address.
-    #[tracing::instrument(skip(self))]
     pub fn allocate(&mut self, bits: u64, align: u64) -> Result<u64, MemoryError> {
+        self.allocate_at(bits, align, None)
+    }
+
+    /// Allocate `bits` of memory returning the newly allocated address.
+    ///
+    /// `site` describes where the allocation is made, and is available in
+    /// [`MemoryObject::site`].
+    #[tracing::instrument(skip(self))]
+    pub fn allocate_at(
+        &mut self,
+        bits: u64,
+        align: u64,
+        site: Option<String>,
+    ) -> Result<u64, MemoryError> {
         let (addr, _bytes) = self.allocator.get_address(bits, align)?;
 
         let name = format!("alloc{}-{}", self.alloc_id, rand::random::<u32>());
@@ -69,6 +116,8 @@ impl ObjectMemory {
             //name: name.clone(),
             address: addr,
             size: bits,
+            align,
+            site,
             bv: self.ctx.unconstrained(bits as u32, &name),
         };
         self.objects.insert(addr, obj);
@@ -76,6 +125,33 @@ impl ObjectMemory {
         Ok(addr)
     }
 
+    /// Free the object allocated at `address`.
+    ///
+    /// Any later access to the object results in
+    /// [`MemoryError::UseAfterFree`].
+    pub fn free(&mut self, address: u64) -> Result<(), MemoryError> {
+        match self.objects.remove(&address) {
+            Some(object) => {
+                trace!("free addr={address:x}");
+                self.freed.insert(address, object);
+                Ok(())
+            }
+            None if self.freed.contains_key(&address) => Err(MemoryError::DoubleFree(address)),
+            None => Err(MemoryError::InvalidFree(address)),
+        }
+    }
+
+    /// Free the object allocated at `address`, checking that `bits` and
+    /// `align` are the same as when it was allocated.
+    pub fn deallocate(&mut self, address: u64, bits: u64, align: u64) -> Result<(), MemoryError> {
+        if let Some(object) = self.objects.get(&address) {
+            if object.size != bits || object.align != align {
+                return Err(MemoryError::MismatchedDealloc(address));
+            }
+        }
+        self.free(address)
+    }
+
     /// Read `bits` from `address`.
     #[tracing::instrument(skip(self))]
     pub fn read(&self, addr: &DExpr, bits: u32) -> Result<DExpr, MemoryError> {
@@ -84,6 +160,9 @@ impl ObjectMemory {
 
         let (addr, value) = self.resolve_address(addr)?;
         let offset = (addr - value.address) as u32 * 8;
+        if (offset + bits) as u64 > value.size {
+            return Err(MemoryError::ObjectOutOfBounds(value.address));
+        }
         let val = value.bv.slice(offset, offset + bits - 1);
 
         trace!("Return {val:?}, value: {value:x?}");
@@ -98,6 +177,9 @@ impl ObjectMemory {
 
         let (addr, val) = self.resolve_address_mut(addr)?;
         let offset = (addr - val.address) * 8;
+        if offset + value.len() as u64 > val.size {
+            return Err(MemoryError::ObjectOutOfBounds(val.address));
+        }
 
         if value.len() == val.size as u32 {
             val.bv = value;
@@ -142,16 +224,8 @@ impl ObjectMemory {
 
     fn resolve_address(&self, address: &DExpr) -> Result<(u64, &MemoryObject), MemoryError> {
         let address = address.get_constant().unwrap();
-
-        // Get the memory object with the address that is the closest below the passed
-        // address.
-
-        // TODO: Add bounds check
-        if let Some(object) = self.objects.range(0..=address).rev().take(1).next() {
-            return Ok((address, object.1));
-        }
-
-        panic!("Memory object not found");
+        let key = self.find_object(address)?;
+        Ok((address, &self.objects[&key]))
     }
 
     fn resolve_address_mut(
@@ -159,14 +233,56 @@ impl ObjectMemory {
         address: &DExpr,
     ) -> Result<(u64, &mut MemoryObject), MemoryError> {
         let address = address.get_constant().unwrap();
+        let key = self.find_object(address)?;
+        Ok((address, self.objects.get_mut(&key).unwrap()))
+    }
 
+    /// Get the start address of the memory object that `address` is in.
+    fn find_object(&self, address: u64) -> Result<u64, MemoryError> {
         // Get the memory object with the address that is the closest below the passed
         // address.
-        if let Some(object) = self.objects.range_mut(0..=address).rev().take(1).next() {
-            // TODO: Perform bounds check.
-            return Ok((address, object.1));
+        let object = self.objects.range(0..=address).next_back();
+        if let Some((start, object)) = object {
+            if address < start + object.bytes() {
+                return Ok(*start);
+            }
+        }
+
+        let freed = self.freed.range(0..=address).next_back();
+        if let Some((start, freed)) = freed {
+            if address < start + freed.bytes() {
+                return Err(MemoryError::UseAfterFree(*start));
+            }
+        }
+
+        match object {
+            Some((start, _)) => Err(MemoryError::ObjectOutOfBounds(*start)),
+            None if address == 0 => Err(MemoryError::NullPointer),
+            None => Err(MemoryError::OutOfBounds),
         }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::ObjectMemory;
+    use crate::smt::{DContext, DSolver};
+
+    fn setup_test_memory() -> ObjectMemory {
+        let ctx = Box::new(DContext::new());
+        let ctx = Box::leak(ctx);
+        ObjectMemory::new(ctx, 32, DSolver::new(ctx))
+    }
+
+    #[test]
+    fn test_bytes_to_end() {
+        let mut memory = setup_test_memory();
+        let address = memory.allocate(32, 4).unwrap();
+        assert_eq!(memory.bytes_to_end(address), Some(4));
+        assert_eq!(memory.bytes_to_end(address + 3), Some(1));
+        assert_eq!(memory.bytes_to_end(address + 4), None);
 
-        panic!("Memory object not found");
+        memory.free(address).unwrap();
+        assert_eq!(memory.bytes_to_end(address), None);
     }
 }
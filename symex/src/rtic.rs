@@ -0,0 +1,1042 @@
+//! Response time analysis of RTIC applications.
+//!
+//! The tasks of the application are found from the binary. Hardware tasks and
+//! the dispatchers of software tasks are interrupt handlers, so every
+//! interrupt that is given a priority by `main` before interrupts are enabled
+//! is a task. The handler is found in the vector table and named by the
+//! symbol at its address.
+//!
+//! Each task is then executed symbolically on its own to find its worst case
+//! execution time. The critical sections of a task are found by watching how
+//! the task masks interrupts:
+//!
+//! - On armv7-m a resource is locked by raising `BASEPRI` to the ceiling of the
+//!   resource.
+//! - On armv6-m a resource is locked by disabling the interrupts of all tasks
+//!   with a priority up to the ceiling in the NVIC (`ICER`) and unlocked by
+//!   enabling them again (`ISER`).
+//! - On both, a resource with the highest ceiling is locked by disabling all
+//!   interrupts with `PRIMASK`.
+//!
+//! A critical section is measured from the end of the instruction that locks
+//! the resource to the end of the instruction that unlocks it.
+//!
+//! Finally the task set is checked with response time analysis under the
+//! stack resource policy (SRP). A task `i` is blocked at most once, by the
+//! longest critical section of a lower priority task with a ceiling at or
+//! above the priority of `i`. The response time is the smallest solution to
+//!
+//! ```text
+//! R(i) = C(i) + B(i) + sum over j in hp(i) of ceil(R(i) / T(j)) * C(j)
+//! ```
+//!
+//! where `C` is the WCET, `B` the blocking time and `T` the period or minimum
+//! inter-arrival time given by the user. Tasks with the same priority are
+//! counted as interfering with each other which is safe but pessimistic. A
+//! task is schedulable if the response time is at most its period.
+//!
+//! Priorities and ceilings are given as the value written to the priority
+//! registers of the NVIC, i.e. a lower value is a higher priority. The WCET
+//! does not include the exception entry and return.
+//!
+//! The periods are read from a toml file mapping task names to cycles
+//!
+//! ```toml
+//! UART0 = 48_000
+//! TIMER_IRQ_0 = 1_000_000
+//! ```
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt::{self, Display},
+    fs,
+    path::Path,
+};
+
+use ::general_assembly::register::Register;
+use object::{Object, ObjectSection, ObjectSymbol, SymbolKind};
+use tracing::{debug, trace};
+
+use crate::{
+    general_assembly::{
+        self,
+        arch::{
+            arm::{v6::ArmV6M, v7::ArmV7EM},
+            Arch,
+        },
+        executor::GAExecutor,
+        project::{target_config::TargetConfiguration, MemoryHookAddress, PCHook, ProjectError},
+        state::{events_of, GAState},
+        GAError,
+        Result as GAResult,
+        RunConfig,
+    },
+    run_elf::{add_architecture_independent_hooks, run_elf_configured},
+    smt::{DContext, DExpr},
+};
+
+/// Interrupt set enable registers of the NVIC.
+const NVIC_ISER: u64 = 0xE000_E100;
+
+/// Interrupt clear enable registers of the NVIC.
+const NVIC_ICER: u64 = 0xE000_E180;
+
+/// Size of the set and clear enable register banks.
+const NVIC_ENABLE_BANK: u64 = 0x40;
+
+/// Interrupt priority registers of the NVIC.
+const NVIC_IPR: u64 = 0xE000_E400;
+
+/// Size of the priority register bank.
+const NVIC_IPR_BANK: u64 = 0x1F0;
+
+/// Offset of the first device interrupt in the vector table.
+const VECTOR_TABLE_IRQ_OFFSET: usize = 16 * 4;
+
+/// Number of instructions `main` is allowed to execute before interrupts must
+/// have been enabled.
+const MAX_INIT_INSTRUCTIONS: usize = 1_000_000;
+
+#[derive(Debug, thiserror::Error)]
+pub enum RticError {
+    #[error("{0}")]
+    GAError(#[from] GAError),
+
+    #[error("Unable to read task periods: {0}")]
+    UnableToReadPeriods(String),
+
+    #[error("No period given for task {0}.")]
+    MissingPeriod(String),
+
+    #[error("No vector table found in the elf file.")]
+    NoVectorTable,
+
+    #[error("No tasks found, no interrupt priorities were set before interrupts were enabled.")]
+    NoTasks,
+}
+
+/// The longest critical section of a task at a ceiling.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CriticalSection {
+    /// The ceiling of the locked resources.
+    pub ceiling: u8,
+
+    /// Worst case length of the critical section in cycles.
+    pub cycles: usize,
+}
+
+/// A task of an RTIC application.
+#[derive(Debug, Clone)]
+pub struct Task {
+    /// Name of the interrupt handler.
+    pub name: String,
+
+    /// The interrupt number the task is bound to.
+    pub interrupt: usize,
+
+    /// Priority of the task as written to the NVIC.
+    pub priority: u8,
+
+    /// Worst case execution time in cycles.
+    pub wcet: usize,
+
+    /// Period or minimum inter-arrival time in cycles, also used as the
+    /// deadline.
+    pub period: u64,
+
+    /// The longest critical section for each ceiling the task locks.
+    pub critical_sections: Vec<CriticalSection>,
+
+    /// The longest time the task can be blocked by lower priority tasks.
+    pub blocking: usize,
+
+    /// The worst case response time, `None` if it exceeds the period.
+    pub response_time: Option<u64>,
+}
+
+impl Task {
+    /// Returns true if the task always finishes within its period.
+    pub fn schedulable(&self) -> bool {
+        self.response_time
+            .is_some_and(|response_time| response_time <= self.period)
+    }
+
+    /// Checks if the task has strictly lower priority than `other`.
+    fn lower_priority_than(&self, other: &Task) -> bool {
+        self.priority > other.priority
+    }
+}
+
+/// Result of a response time analysis.
+#[derive(Debug, Clone)]
+pub struct RticAnalysis {
+    /// All tasks ordered from highest to lowest priority.
+    pub tasks: Vec<Task>,
+}
+
+impl RticAnalysis {
+    /// Creates an analysis of the tasks and computes the blocking and response
+    /// times.
+    pub fn new(mut tasks: Vec<Task>) -> Self {
+        tasks.sort_by_key(|task| (task.priority, task.interrupt));
+        let mut analysis = Self { tasks };
+        analysis.blocking_times();
+        analysis.response_times();
+        analysis
+    }
+
+    /// Returns true if all tasks are schedulable.
+    pub fn schedulable(&self) -> bool {
+        self.tasks.iter().all(Task::schedulable)
+    }
+
+    fn blocking_times(&mut self) {
+        for i in 0..self.tasks.len() {
+            let task = &self.tasks[i];
+            self.tasks[i].blocking = self
+                .tasks
+                .iter()
+                .filter(|other| other.lower_priority_than(task))
+                .flat_map(|other| &other.critical_sections)
+                .filter(|section| section.ceiling <= task.priority)
+                .map(|section| section.cycles)
+                .max()
+                .unwrap_or(0);
+        }
+    }
+
+    fn response_times(&mut self) {
+        for i in 0..self.tasks.len() {
+            let task = &self.tasks[i];
+            let interference: Vec<(u64, u64)> = self
+                .tasks
+                .iter()
+                .enumerate()
+                .filter(|(j, other)| *j != i && !other.lower_priority_than(task))
+                .map(|(_, other)| (other.wcet as u64, other.period))
+                .collect();
+
+            let base = (task.wcet + task.blocking) as u64;
+            let mut response_time = base;
+            self.tasks[i].response_time = loop {
+                let next = base
+                    + interference
+                        .iter()
+                        .map(|(wcet, period)| response_time.div_ceil(*period) * wcet)
+                        .sum::<u64>();
+                if next == response_time {
+                    break Some(response_time);
+                }
+                if next > self.tasks[i].period {
+                    break None;
+                }
+                response_time = next;
+            };
+        }
+    }
+}
+
+impl Display for RticAnalysis {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{:<24} {:>4} {:>8} {:>10} {:>10} {:>10} {:>10}",
+            "task", "irq", "priority", "wcet", "blocking", "period", "response"
+        )?;
+        for task in &self.tasks {
+            let response_time = match task.response_time {
+                Some(response_time) => response_time.to_string(),
+                None => "-".to_owned(),
+            };
+            writeln!(
+                f,
+                "{:<24} {:>4} {:>#8x} {:>10} {:>10} {:>10} {:>10}",
+                task.name,
+                task.interrupt,
+                task.priority,
+                task.wcet,
+                task.blocking,
+                task.period,
+                response_time
+            )?;
+            for section in &task.critical_sections {
+                writeln!(
+                    f,
+                    "  critical section at ceiling {:#x}: {} cycles",
+                    section.ceiling, section.cycles
+                )?;
+            }
+        }
+        match self.schedulable() {
+            true => write!(f, "The task set is schedulable."),
+            false => write!(f, "The task set is NOT schedulable."),
+        }
+    }
+}
+
+/// Reads the period or minimum inter-arrival time in cycles of each task from
+/// a toml file.
+pub fn read_periods<P: AsRef<Path>>(path: P) -> Result<HashMap<String, u64>, RticError> {
+    let path = path.as_ref();
+    let data = fs::read_to_string(path)
+        .map_err(|e| RticError::UnableToReadPeriods(format!("{}: {e}", path.display())))?;
+    let periods: HashMap<String, u64> =
+        toml::from_str(&data).map_err(|e| RticError::UnableToReadPeriods(e.to_string()))?;
+    if let Some((name, _)) = periods.iter().find(|(_, period)| **period == 0) {
+        return Err(RticError::UnableToReadPeriods(format!(
+            "the period of {name} is zero"
+        )));
+    }
+    Ok(periods)
+}
+
+/// Finds the tasks of the RTIC application in the elf file at `path` and
+/// checks if they are schedulable with the given periods.
+///
+/// If no `target` is given the default target for the architecture is used
+/// when counting cycles.
+pub fn analyze<P: AsRef<Path>>(
+    path: P,
+    periods: &HashMap<String, u64>,
+    target: Option<TargetConfiguration>,
+) -> Result<RticAnalysis, RticError> {
+    let path = path.as_ref().display().to_string();
+    debug!("Parsing elf file: {}", path);
+    let file = fs::read(&path).expect("Unable to open file.");
+    let obj_file = match object::File::parse(&*file) {
+        Ok(x) => x,
+        Err(e) => {
+            debug!("Error: {}", e);
+            return Err(GAError::from(ProjectError::UnableToParseElf(path)))?;
+        }
+    };
+
+    if let Some(v7) = ArmV7EM::discover(&obj_file).map_err(GAError::from)? {
+        return analyze_arch(&path, &file, periods, target, v7);
+    }
+    if let Some(v6) = ArmV6M::discover(&obj_file).map_err(GAError::from)? {
+        return analyze_arch(&path, &file, periods, target, v6);
+    }
+    Err(GAError::from(ProjectError::UnableToParseElf(
+        "UnsuportedArchitechture".to_string(),
+    )))?
+}
+
+fn analyze_arch<A: Arch>(
+    path: &str,
+    data: &[u8],
+    periods: &HashMap<String, u64>,
+    target: Option<TargetConfiguration>,
+    architecture: A,
+) -> Result<RticAnalysis, RticError> {
+    let priorities = interrupt_priorities(data, target.clone(), architecture.clone())?;
+    let handlers = interrupt_handlers(data)?;
+
+    let mut tasks = vec![];
+    for (&interrupt, &priority) in &priorities {
+        let Some(name) = handlers.get(&interrupt) else {
+            debug!("Interrupt {interrupt} has a priority but no handler");
+            continue;
+        };
+        let period = *periods
+            .get(name)
+            .ok_or_else(|| RticError::MissingPeriod(name.to_owned()))?;
+
+        let mut cfg = RunConfig {
+            show_path_results: false,
+            target: target.clone(),
+            ..RunConfig::default()
+        };
+        add_mask_hooks(&mut cfg);
+        debug!("Analyzing task {name}");
+        let results = run_elf_configured(path, name, architecture.clone(), cfg)?;
+
+        let mut wcet = 0;
+        let mut longest: BTreeMap<u8, usize> = BTreeMap::new();
+        for result in results {
+            wcet = wcet.max(result.max_cycles);
+            let events: Vec<_> = events_of::<InterruptEvent>(&result.events)
+                .map(|(cycles, event)| (cycles, *event))
+                .collect();
+            for section in critical_sections(&events, result.max_cycles, &priorities) {
+                let cycles = longest.entry(section.ceiling).or_default();
+                *cycles = (*cycles).max(section.cycles);
+            }
+        }
+
+        tasks.push(Task {
+            name: name.to_owned(),
+            interrupt,
+            priority,
+            wcet,
+            period,
+            critical_sections: longest
+                .into_iter()
+                .map(|(ceiling, cycles)| CriticalSection { ceiling, cycles })
+                .collect(),
+            blocking: 0,
+            response_time: None,
+        });
+    }
+
+    if tasks.is_empty() {
+        return Err(RticError::NoTasks);
+    }
+    Ok(RticAnalysis::new(tasks))
+}
+
+/// Runs `main` until interrupts are enabled and collects the priorities
+/// written to the NVIC.
+fn interrupt_priorities<A: Arch>(
+    data: &[u8],
+    target: Option<TargetConfiguration>,
+    architecture: A,
+) -> Result<HashMap<usize, u8>, RticError> {
+    let context = Box::leak(Box::new(DContext::new()));
+    let end_pc = 0xFFFFFFFE;
+
+    let mut cfg = RunConfig {
+        show_path_results: false,
+        target,
+        ..RunConfig::default()
+    };
+    add_architecture_independent_hooks(&mut cfg);
+    add_mask_hooks(&mut cfg);
+    cfg.memory_write_hooks.push((
+        MemoryHookAddress::Range(NVIC_IPR, NVIC_IPR + NVIC_IPR_BANK),
+        write_priority::<A>,
+    ));
+
+    let obj_file = object::File::parse(data)
+        .map_err(|e| GAError::from(ProjectError::UnableToParseElf(e.to_string())))?;
+    let project = Box::leak(Box::new(
+        general_assembly::project::Project::from_path(&mut cfg, obj_file, &architecture)
+            .map_err(GAError::from)?,
+    ));
+    project.add_pc_hook(end_pc, PCHook::EndSuccess);
+
+    let mut vm = general_assembly::vm::VM::new(project, context, "main", end_pc, architecture)?;
+    let mut state = vm.paths.get_path().expect("vm starts with a path").state;
+
+    // The priority registers are reset to zero.
+    let zero = state.ctx.from_u64(0, 32);
+    for address in (NVIC_IPR..NVIC_IPR + NVIC_IPR_BANK).step_by(4) {
+        let address = state.ctx.from_u64(address, project.get_ptr_size());
+        state
+            .memory
+            .write(&address, zero.clone())
+            .map_err(GAError::from)?;
+    }
+
+    let mut executor = GAExecutor::from_state(state, &mut vm, project);
+    for _ in 0..MAX_INIT_INSTRUCTIONS {
+        if executor.step()?.is_some() {
+            break;
+        }
+        let enabled = executor
+            .state
+            .events_of::<InterruptEvent>()
+            .last()
+            .is_some_and(|(_, event)| *event == InterruptEvent::Primask(false));
+        if enabled {
+            trace!("Interrupts enabled in main");
+            break;
+        }
+    }
+
+    let mut priorities = HashMap::new();
+    for (_, event) in executor.state.events_of::<InterruptEvent>() {
+        if let InterruptEvent::Priority {
+            interrupt,
+            priority,
+        } = *event
+        {
+            priorities.insert(interrupt, priority);
+        }
+    }
+    Ok(priorities)
+}
+
+/// Maps interrupt numbers to the name of their handler.
+fn interrupt_handlers(data: &[u8]) -> Result<HashMap<usize, String>, RticError> {
+    let obj_file = object::File::parse(data)
+        .map_err(|e| GAError::from(ProjectError::UnableToParseElf(e.to_string())))?;
+    let section = obj_file
+        .section_by_name(".vector_table")
+        .ok_or(RticError::NoVectorTable)?;
+    let data = section.data().map_err(|_| RticError::NoVectorTable)?;
+    let data = data.get(VECTOR_TABLE_IRQ_OFFSET..).unwrap_or_default();
+
+    let mut functions: HashMap<u64, String> = HashMap::new();
+    for symbol in obj_file.symbols() {
+        let Ok(name) = symbol.name() else {
+            continue;
+        };
+        if symbol.kind() != SymbolKind::Text || name.is_empty() {
+            continue;
+        }
+        functions
+            .entry(symbol.address() & !1)
+            .or_insert_with(|| name.to_owned());
+    }
+
+    let mut handlers = HashMap::new();
+    for (interrupt, entry) in data.chunks_exact(4).enumerate() {
+        let entry = [entry[0], entry[1], entry[2], entry[3]];
+        let address = match obj_file.is_little_endian() {
+            true => u32::from_le_bytes(entry),
+            false => u32::from_be_bytes(entry),
+        } as u64
+            & !1;
+        match functions.get(&address) {
+            Some(name) if !name.starts_with("DefaultHandler") => {
+                handlers.insert(interrupt, name.to_owned());
+            }
+            _ => {}
+        }
+    }
+    Ok(handlers)
+}
+
+/// A change of the interrupt masking or priorities.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InterruptEvent {
+    /// All interrupts disabled or enabled.
+    Primask(bool),
+
+    /// Interrupts with a lower priority than the value are masked.
+    Basepri(u8),
+
+    /// Interrupts in the mask disabled, `bank` selects the register.
+    Clear { bank: usize, mask: u32 },
+
+    /// Interrupts in the mask enabled, `bank` selects the register.
+    Set { bank: usize, mask: u32 },
+
+    /// The priority of an interrupt set.
+    Priority { interrupt: usize, priority: u8 },
+}
+
+impl Display for InterruptEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InterruptEvent::Primask(masked) => write!(f, "PRIMASK={}", *masked as u8),
+            InterruptEvent::Basepri(value) => write!(f, "BASEPRI={value}"),
+            InterruptEvent::Clear { bank, mask } => write!(f, "ICER{bank}={mask}"),
+            InterruptEvent::Set { bank, mask } => write!(f, "ISER{bank}={mask}"),
+            InterruptEvent::Priority {
+                interrupt,
+                priority,
+            } => write!(f, "IPR{interrupt}={priority}"),
+        }
+    }
+}
+
+/// Finds the critical sections of a path from its masking events. Sections
+/// that are still open when the path ends last until `end`.
+fn critical_sections(
+    events: &[(usize, InterruptEvent)],
+    end: usize,
+    priorities: &HashMap<usize, u8>,
+) -> Vec<CriticalSection> {
+    // A BASEPRI of zero masks nothing.
+    let level = |basepri: u8| match basepri {
+        0 => u16::MAX,
+        value => value as u16,
+    };
+
+    let mut sections = vec![];
+    let mut primask = None;
+    let mut basepri: Vec<(u8, usize)> = vec![];
+    let mut masks: Vec<(Option<u8>, usize)> = vec![];
+    for (cycles, event) in events.iter().copied() {
+        match event {
+            InterruptEvent::Primask(true) => {
+                primask.get_or_insert(cycles);
+            }
+            InterruptEvent::Primask(false) => {
+                if let Some(start) = primask.take() {
+                    sections.push(CriticalSection {
+                        ceiling: 0,
+                        cycles: cycles - start,
+                    });
+                }
+            }
+            InterruptEvent::Basepri(value) => {
+                while let Some((ceiling, start)) =
+                    basepri.pop_if(|(ceiling, _)| level(*ceiling) < level(value))
+                {
+                    sections.push(CriticalSection {
+                        ceiling,
+                        cycles: cycles - start,
+                    });
+                }
+                let raised = match basepri.last() {
+                    Some((ceiling, _)) => level(value) < level(*ceiling),
+                    None => value != 0,
+                };
+                if raised {
+                    basepri.push((value, cycles));
+                }
+            }
+            InterruptEvent::Clear { bank, mask } if mask != 0 => {
+                let ceiling = (0..32)
+                    .filter(|bit| mask & (1 << bit) != 0)
+                    .filter_map(|bit| priorities.get(&(bank * 32 + bit)))
+                    .min()
+                    .copied();
+                masks.push((ceiling, cycles));
+            }
+            InterruptEvent::Set { mask, .. } if mask != 0 => {
+                if let Some((Some(ceiling), start)) = masks.pop() {
+                    sections.push(CriticalSection {
+                        ceiling,
+                        cycles: cycles - start,
+                    });
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let open = primask
+        .map(|start| (Some(0), start))
+        .into_iter()
+        .chain(
+            basepri
+                .into_iter()
+                .map(|(ceiling, start)| (Some(ceiling), start)),
+        )
+        .chain(masks);
+    for (ceiling, start) in open {
+        if let Some(ceiling) = ceiling {
+            sections.push(CriticalSection {
+                ceiling,
+                cycles: end - start,
+            });
+        }
+    }
+    sections
+}
+
+/// Records the event, it is given the cycle count at the end of the current
+/// instruction when the instruction completes.
+fn record<A: Arch>(state: &mut GAState<A>, event: InterruptEvent) {
+    trace!(
+        "Recorded {event} in the instruction at cycle {}",
+        state.cycle_count
+    );
+    state.record_event(event);
+}
+
+/// Reads a special register that is zero at reset.
+fn read_mask_register<A: Arch>(state: &mut GAState<A>, register: Register) -> DExpr {
+    match state.registers.get(register) {
+        Some(value) => value.clone(),
+        None => state.ctx.from_u64(0, 32),
+    }
+}
+
+fn read_primask<A: Arch>(state: &mut GAState<A>) -> GAResult<DExpr> {
+    Ok(read_mask_register(
+        state,
+        const { A::REGISTERS.named("PRIMASK") },
+    ))
+}
+
+fn read_basepri<A: Arch>(state: &mut GAState<A>) -> GAResult<DExpr> {
+    Ok(read_mask_register(
+        state,
+        const { A::REGISTERS.named("BASEPRI") },
+    ))
+}
+
+fn write_primask<A: Arch>(state: &mut GAState<A>, value: DExpr) -> GAResult<()> {
+    if let Some(value) = value.get_constant() {
+        record(state, InterruptEvent::Primask(value & 1 != 0));
+    }
+    state
+        .registers
+        .insert(const { A::REGISTERS.named("PRIMASK") }, value);
+    Ok(())
+}
+
+fn write_basepri<A: Arch>(state: &mut GAState<A>, value: DExpr) -> GAResult<()> {
+    if let Some(value) = value.get_constant() {
+        record(state, InterruptEvent::Basepri(value as u8));
+    }
+    state
+        .registers
+        .insert(const { A::REGISTERS.named("BASEPRI") }, value);
+    Ok(())
+}
+
+fn write_set_enable<A: Arch>(
+    state: &mut GAState<A>,
+    address: u64,
+    value: DExpr,
+    _bits: u32,
+) -> GAResult<()> {
+    if let Some(mask) = value.get_constant() {
+        let bank = ((address - NVIC_ISER) / 4) as usize;
+        record(state, InterruptEvent::Set {
+            bank,
+            mask: mask as u32,
+        });
+    }
+    Ok(())
+}
+
+fn write_clear_enable<A: Arch>(
+    state: &mut GAState<A>,
+    address: u64,
+    value: DExpr,
+    _bits: u32,
+) -> GAResult<()> {
+    if let Some(mask) = value.get_constant() {
+        let bank = ((address - NVIC_ICER) / 4) as usize;
+        record(state, InterruptEvent::Clear {
+            bank,
+            mask: mask as u32,
+        });
+    }
+    Ok(())
+}
+
+fn write_priority<A: Arch>(
+    state: &mut GAState<A>,
+    address: u64,
+    value: DExpr,
+    bits: u32,
+) -> GAResult<()> {
+    for byte in 0..bits / 8 {
+        if let Some(priority) = value.slice(byte * 8, byte * 8 + 7).get_constant() {
+            let interrupt = (address - NVIC_IPR) as usize + byte as usize;
+            record(state, InterruptEvent::Priority {
+                interrupt,
+                priority: priority as u8,
+            });
+        }
+    }
+    let address = state.ctx.from_u64(address, state.project.get_ptr_size());
+    state
+        .memory
+        .write(&address, value.resize_unsigned(bits).simplify())?;
+    Ok(())
+}
+
+/// Adds the hooks that record changes to the interrupt masking.
+fn add_mask_hooks<A: Arch>(cfg: &mut RunConfig<A>) {
+    cfg.register_read_hooks.extend([
+        (
+            const { A::REGISTERS.named("PRIMASK") },
+            read_primask::<A> as _,
+        ),
+        (
+            const { A::REGISTERS.named("BASEPRI") },
+            read_basepri::<A> as _,
+        ),
+    ]);
+    cfg.register_write_hooks.extend([
+        (
+            const { A::REGISTERS.named("PRIMASK") },
+            write_primask::<A> as _,
+        ),
+        (
+            const { A::REGISTERS.named("BASEPRI") },
+            write_basepri::<A> as _,
+        ),
+    ]);
+    cfg.memory_write_hooks.extend([
+        (
+            MemoryHookAddress::Range(NVIC_ISER, NVIC_ISER + NVIC_ENABLE_BANK),
+            write_set_enable::<A> as _,
+        ),
+        (
+            MemoryHookAddress::Range(NVIC_ICER, NVIC_ICER + NVIC_ENABLE_BANK),
+            write_clear_enable::<A> as _,
+        ),
+    ]);
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashMap;
+
+    use super::{analyze, critical_sections, CriticalSection, InterruptEvent, RticAnalysis, Task};
+
+    /// Address of the code in [`elf`], the vector table is placed at zero.
+    const TEXT: u32 = 0x100;
+
+    /// Offset of the program in [`elf`], after the file and program headers.
+    const PROGRAM_OFFSET: u32 = 0x100;
+
+    /// Builds a Cortex-M4 elf file with the `(name, address, code)` functions
+    /// and a vector table with the named interrupt handlers.
+    fn elf(functions: &[(&str, u32, &[u16])], handlers: &[&str]) -> Vec<u8> {
+        // The lowest bit of a function address marks Thumb code.
+        let address = |name: &str| {
+            let (_, address, _) = functions.iter().find(|(n, ..)| *n == name).unwrap();
+            address | 1
+        };
+
+        let mut program = vec![0u8; 16 * 4];
+        for handler in handlers {
+            program.extend(address(handler).to_le_bytes());
+        }
+        let vector_table_size = program.len() as u32;
+        for (_, address, code) in functions {
+            program.resize(*address as usize, 0);
+            program.extend(code.iter().flat_map(|half_word| half_word.to_le_bytes()));
+        }
+        // Instructions are read a word at a time.
+        program.extend([0; 4]);
+
+        // Tag_CPU_arch v7E-M and Tag_CPU_arch_profile M.
+        let attributes = [6, 13, 7, b'M'];
+        let mut arm_attributes = vec![b'A'];
+        arm_attributes.extend((4 + 6 + 5 + attributes.len() as u32).to_le_bytes());
+        arm_attributes.extend(b"aeabi\0");
+        arm_attributes.push(1);
+        arm_attributes.extend((5 + attributes.len() as u32).to_le_bytes());
+        arm_attributes.extend(attributes);
+
+        // Global functions in `.text` and the absolute start of the stack.
+        let symbols = functions
+            .iter()
+            .map(|(name, ..)| (*name, address(name), 0x12, 2))
+            .chain([("_stack_start", 0x2001_0000, 0x10, 0xfff1)]);
+        let mut strtab = vec![0];
+        let mut symtab = vec![0; 16];
+        for (name, value, info, section) in symbols {
+            symtab.extend((strtab.len() as u32).to_le_bytes());
+            symtab.extend(value.to_le_bytes());
+            symtab.extend(0u32.to_le_bytes());
+            symtab.extend([info, 0]);
+            symtab.extend((section as u16).to_le_bytes());
+            strtab.extend(name.as_bytes());
+            strtab.push(0);
+        }
+
+        // The loaded sections point into the program, the rest are placed
+        // after it.
+        let text_size = program.len() as u32 - TEXT;
+        let mut sections: [(&str, u32, u32, u32, u32, Vec<u8>, u32, u32, u32); 9] = [
+            (".vector_table", 1, 2, 0, vector_table_size, vec![], 0, 0, 0),
+            (".text", 1, 6, TEXT, text_size, vec![], 0, 0, 0),
+            (
+                ".ARM.attributes",
+                0x7000_0003,
+                0,
+                0,
+                0,
+                arm_attributes,
+                0,
+                0,
+                0,
+            ),
+            (".debug_info", 1, 0, 0, 0, vec![], 0, 0, 0),
+            (".debug_abbrev", 1, 0, 0, 0, vec![], 0, 0, 0),
+            (".debug_str", 1, 0, 0, 0, vec![], 0, 0, 0),
+            (".symtab", 2, 0, 0, 0, symtab, 8, 1, 16),
+            (".strtab", 3, 0, 0, 0, strtab, 0, 0, 0),
+            (".shstrtab", 3, 0, 0, 0, vec![], 0, 0, 0),
+        ];
+        let mut shstrtab = vec![0];
+        let mut names = vec![];
+        for (name, ..) in &sections {
+            names.push(shstrtab.len() as u32);
+            shstrtab.extend(name.as_bytes());
+            shstrtab.push(0);
+        }
+        sections[8].5 = shstrtab;
+
+        let mut data = vec![0; PROGRAM_OFFSET as usize];
+        data.extend(&program);
+        let mut headers = vec![0; 40];
+        for ((_, kind, flags, address, size, content, link, info, entry_size), name) in
+            sections.into_iter().zip(names)
+        {
+            let (offset, size) = match flags {
+                0 => (data.len() as u32, content.len() as u32),
+                _ => (PROGRAM_OFFSET + address, size),
+            };
+            data.extend(content);
+            for field in [
+                name, kind, flags, address, offset, size, link, info, 4, entry_size,
+            ] {
+                headers.extend(field.to_le_bytes());
+            }
+        }
+        let section_headers = data.len() as u32;
+        data.extend(headers);
+
+        let mut header = b"\x7fELF\x01\x01\x01".to_vec();
+        header.resize(16, 0);
+        // An ARM executable with version 5 of the EABI.
+        header.extend(2u16.to_le_bytes());
+        header.extend(40u16.to_le_bytes());
+        for field in [1, address(functions[0].0), 52, section_headers, 0x0500_0000] {
+            header.extend(field.to_le_bytes());
+        }
+        for field in [52u16, 32, 1, 40, 10, 9] {
+            header.extend(field.to_le_bytes());
+        }
+        // The program is loaded as a single readable and executable segment.
+        let size = program.len() as u32;
+        for field in [1, PROGRAM_OFFSET, 0, 0, size, size, 5, 4] {
+            header.extend(field.to_le_bytes());
+        }
+        data[..header.len()].copy_from_slice(&header);
+        data
+    }
+
+    fn task(name: &str, priority: u8, wcet: usize, period: u64) -> Task {
+        Task {
+            name: name.to_owned(),
+            interrupt: priority as usize,
+            priority,
+            wcet,
+            period,
+            critical_sections: vec![],
+            blocking: 0,
+            response_time: None,
+        }
+    }
+
+    #[test]
+    fn test_response_times() {
+        let high = task("high", 0x20, 10, 50);
+        let medium = task("medium", 0x40, 20, 100);
+        let mut low = task("low", 0x60, 30, 200);
+        low.critical_sections = vec![CriticalSection {
+            ceiling: 0x20,
+            cycles: 5,
+        }];
+
+        let analysis = RticAnalysis::new(vec![low, medium, high]);
+        let names: Vec<_> = analysis.tasks.iter().map(|t| t.name.as_str()).collect();
+        assert_eq!(names, ["high", "medium", "low"]);
+
+        let blocking: Vec<_> = analysis.tasks.iter().map(|t| t.blocking).collect();
+        assert_eq!(blocking, [5, 5, 0]);
+
+        // high: 10 + 5, medium: 20 + 5 + 10, low: 30 + 2 * 10 + 20 as high
+        // arrives twice.
+        let response_times: Vec<_> = analysis.tasks.iter().map(|t| t.response_time).collect();
+        assert_eq!(response_times, [Some(15), Some(35), Some(70)]);
+        assert!(analysis.schedulable());
+
+        let overloaded =
+            RticAnalysis::new(vec![task("high", 0x20, 30, 50), task("low", 0x40, 30, 60)]);
+        assert_eq!(overloaded.tasks[1].response_time, None);
+        assert!(!overloaded.schedulable());
+    }
+
+    #[test]
+    fn test_analyze() {
+        // `main` gives `UART0` priority 0x80 and `TIMER_IRQ_0` priority 0x40
+        // and enables interrupts, `UART0` locks a resource shared with
+        // `TIMER_IRQ_0`.
+        let main: &[u16] = &[
+            0xf24e, 0x4000, // movw r0, #0xe400
+            0xf2ce, 0x0000, // movt r0, #0xe000
+            0xf244, 0x0180, // movw r1, #0x4080
+            0x6001, // str r1, [r0]
+            0xb662, // cpsie i
+            0x4770, // bx lr
+        ];
+        let uart: &[u16] = &[
+            0x2040, // movs r0, #0x40
+            0xf380, 0x8811, // msr basepri, r0
+            0xbf00, // nop
+            0xbf00, // nop
+            0x2000, // movs r0, #0
+            0xf380, 0x8811, // msr basepri, r0
+            0x4770, // bx lr
+        ];
+        let timer: &[u16] = &[
+            0xbf00, // nop
+            0x4770, // bx lr
+        ];
+        let data = elf(
+            &[
+                ("main", TEXT, main),
+                ("UART0", TEXT + 0x20, uart),
+                ("TIMER_IRQ_0", TEXT + 0x40, timer),
+            ],
+            &["UART0", "TIMER_IRQ_0"],
+        );
+        let path = std::env::temp_dir().join(format!("symex-rtic-{}.elf", std::process::id()));
+        std::fs::write(&path, data).unwrap();
+
+        let periods = HashMap::from([("UART0".to_owned(), 1000), ("TIMER_IRQ_0".to_owned(), 100)]);
+        let analysis = analyze(&path, &periods, None);
+        std::fs::remove_file(&path).unwrap();
+        let analysis = analysis.unwrap();
+
+        let names: Vec<_> = analysis.tasks.iter().map(|t| t.name.as_str()).collect();
+        assert_eq!(names, ["TIMER_IRQ_0", "UART0"]);
+        let priorities: Vec<_> = analysis.tasks.iter().map(|t| t.priority).collect();
+        assert_eq!(priorities, [0x40, 0x80]);
+        let wcets: Vec<_> = analysis.tasks.iter().map(|t| t.wcet).collect();
+        assert_eq!(wcets, [5, 12]);
+
+        // From the end of the first MSR to the end of the second.
+        assert_eq!(analysis.tasks[1].critical_sections, [CriticalSection {
+            ceiling: 0x40,
+            cycles: 5
+        }]);
+        let blocking: Vec<_> = analysis.tasks.iter().map(|t| t.blocking).collect();
+        assert_eq!(blocking, [5, 0]);
+        let response_times: Vec<_> = analysis.tasks.iter().map(|t| t.response_time).collect();
+        assert_eq!(response_times, [Some(10), Some(17)]);
+    }
+
+    #[test]
+    fn test_critical_sections() {
+        // Nested BASEPRI locks.
+        let events = [
+            (10, InterruptEvent::Basepri(0x80)),
+            (15, InterruptEvent::Basepri(0x40)),
+            (20, InterruptEvent::Basepri(0x80)),
+            (30, InterruptEvent::Basepri(0)),
+            (40, InterruptEvent::Primask(true)),
+            (42, InterruptEvent::Primask(false)),
+        ];
+        let sections = critical_sections(&events, 50, &HashMap::new());
+        assert_eq!(sections, [
+            CriticalSection {
+                ceiling: 0x40,
+                cycles: 5
+            },
+            CriticalSection {
+                ceiling: 0x80,
+                cycles: 20
+            },
+            CriticalSection {
+                ceiling: 0,
+                cycles: 2
+            },
+        ]);
+
+        // NVIC masking, interrupt 33 has the highest priority in the mask.
+        let priorities = HashMap::from([(32, 0x80), (33, 0x40)]);
+        let events = [
+            (10, InterruptEvent::Clear { bank: 1, mask: 3 }),
+            (25, InterruptEvent::Set { bank: 1, mask: 3 }),
+            (30, InterruptEvent::Clear { bank: 1, mask: 1 }),
+        ];
+        let sections = critical_sections(&events, 50, &priorities);
+        assert_eq!(sections, [
+            CriticalSection {
+                ceiling: 0x40,
+                cycles: 15
+            },
+            CriticalSection {
+                ceiling: 0x80,
+                cycles: 20
+            },
+        ]);
+    }
+}
@@ -1,6 +1,7 @@
 //! Simple runner that starts symbolic execution on LLVM bitcode.
-use std::{fs, path::Path, time::Instant};
+use std::{collections::HashMap, fs, path::Path, str::FromStr, time::Instant};
 
+use ::general_assembly::register::Register;
 use object::{Architecture, Object};
 use regex::Regex;
 use tracing::{debug, trace};
@@ -10,27 +11,46 @@ use crate::{
     general_assembly::{
         self,
         arch::{
-            arm::{v6::ArmV6M, v7::ArmV7EM},
+            aarch64::AArch64,
+            arm::{
+                v6::ArmV6M,
+                v7::ArmV7EM,
+                v8::{ArmV8M, ArmV8MProfile},
+            },
+            riscv::RiscV32IMAC,
             Arch,
         },
         executor::PathResult,
-        project::{PCHook, ProjectError},
+        project::{
+            image::{self, ImageFormat},
+            segments::Segments,
+            snapshot::Snapshot,
+            target_config::TargetConfiguration,
+            PCHook,
+            Project,
+            ProjectError,
+        },
+        replay::{self, TraceConfig},
         state::GAState,
+        vm::VM,
+        Endianness,
         GAError,
         RunConfig,
+        WordSize,
+        DEFAULT_MAX_BRANCH_TARGETS,
     },
     smt::DContext,
 };
 
-fn add_architecture_independent_hooks<A: Arch>(cfg: &mut RunConfig<A>) {
+pub(crate) fn add_architecture_independent_hooks<A: Arch>(cfg: &mut RunConfig<A>) {
     // intrinsic functions
     let start_cyclecount = |state: &mut GAState<A>| {
-        state.cycle_count = 0;
+        state.reset_cycle_count();
         trace!("Reset the cycle count (cycle count: {})", state.cycle_count);
 
         // jump back to where the function was called from
-        let lr = state.get_register("LR".to_owned()).unwrap();
-        state.set_register("PC".to_owned(), lr)?;
+        let lr = state.get_register(Register::LR).unwrap();
+        state.set_register(Register::PC, lr)?;
         Ok(())
     };
     let end_cyclecount = |state: &mut GAState<A>| {
@@ -42,8 +62,8 @@ fn add_architecture_independent_hooks<A: Arch>(cfg: &mut RunConfig<A>) {
         );
 
         // jump back to where the function was called from
-        let lr = state.get_register("LR".to_owned()).unwrap();
-        state.set_register("PC".to_owned(), lr)?;
+        let lr = state.get_register(Register::LR).unwrap();
+        state.set_register(Register::PC, lr)?;
         Ok(())
     };
 
@@ -61,7 +81,10 @@ fn add_architecture_independent_hooks<A: Arch>(cfg: &mut RunConfig<A>) {
             Regex::new(r"^panic_bounds_check$").unwrap(),
             PCHook::EndFailure("bounds check panic"),
         ),
-        (Regex::new(r"^suppress_path$").unwrap(), PCHook::Suppress),
+        (
+            Regex::new(r"^(symex_)?suppress_path$").unwrap(),
+            PCHook::Suppress,
+        ),
         (
             Regex::new(r"^unreachable_unchecked$").unwrap(),
             PCHook::EndFailure("reach a unreachable unchecked call undefined behavior"),
@@ -81,20 +104,24 @@ fn add_architecture_independent_hooks<A: Arch>(cfg: &mut RunConfig<A>) {
     ]);
 }
 
-/// Run symbolic execution on a elf file where `path` is the path to the ELF
-/// file and `function` is the function the execution starts at.
-/// During runtime it will determin the target architecture and select the
-/// appropriate executor for that enviornement.
+/// Run symbolic execution on the binary at `path`, starting at `entry`.
+///
+/// The file is read as an ELF file and the target architecture is determined
+/// from it, unless [`ElfConfig::image`] describes how to load it as an image.
+/// The rest of `cfg` selects how the paths are run, e.g. if they are merged or
+/// traced.
 pub fn run_elf<P: AsRef<Path>>(
     path: P,
-    function: &str,
-    show_path_results: bool,
+    entry: Entry,
+    mut cfg: ElfConfig,
 ) -> Result<Vec<VisualPathResult>, GAError> {
+    if let Some(options) = cfg.image.take() {
+        return run_image(path, entry, options, cfg);
+    }
+
     let context = Box::new(DContext::new());
     let context = Box::leak(context);
 
-    let end_pc = 0xFFFFFFFE;
-
     let str_version = path.as_ref().display().to_string();
     debug!("Parsing elf file: {}", str_version);
     let file = fs::read(path).expect("Unable to open file.");
@@ -113,51 +140,24 @@ pub fn run_elf<P: AsRef<Path>>(
         Architecture::Arm => {
             // Run the paths with architecture specific data.
             if let Some(v7) = ArmV7EM::discover(&obj_file)? {
-                let mut cfg = RunConfig {
-                    show_path_results,
-                    pc_hooks: Vec::new(),
-                    register_read_hooks: Vec::new(),
-                    register_write_hooks: Vec::new(),
-                    memory_write_hooks: Vec::new(),
-                    memory_read_hooks: Vec::new(),
-                };
-
-                cfg.show_path_results = show_path_results;
-
-                add_architecture_independent_hooks(&mut cfg);
-                let project = Box::new(general_assembly::project::Project::from_path(
-                    &mut cfg, obj_file, &v7,
-                )?);
-                let project = Box::leak(project);
-                project.add_pc_hook(end_pc, PCHook::EndSuccess);
-                debug!("Created project: {:?}", project);
-
-                let mut vm = general_assembly::vm::VM::new(project, context, function, end_pc, v7)?;
-
-                return run_elf_paths(&mut vm, &cfg);
+                return run_discovered(obj_file, context, entry, v7, cfg);
             } else if let Some(v6) = ArmV6M::discover(&obj_file)? {
-                let mut cfg = RunConfig {
-                    show_path_results,
-                    pc_hooks: Vec::new(),
-                    register_read_hooks: Vec::new(),
-                    register_write_hooks: Vec::new(),
-                    memory_write_hooks: Vec::new(),
-                    memory_read_hooks: Vec::new(),
-                };
-
-                add_architecture_independent_hooks(&mut cfg);
-                let project = Box::new(general_assembly::project::Project::from_path(
-                    &mut cfg, obj_file, &v6,
-                )?);
-                let project = Box::leak(project);
-                project.add_pc_hook(end_pc, PCHook::EndSuccess);
-                debug!("Created project: {:?}", project);
-
-                let mut vm = general_assembly::vm::VM::new(project, context, function, end_pc, v6)?;
-                return run_elf_paths(&mut vm, &cfg);
+                return run_discovered(obj_file, context, entry, v6, cfg);
+            } else if let Some(v8) = ArmV8M::discover(&obj_file)? {
+                return run_discovered(obj_file, context, entry, v8, cfg);
+            }
+        }
+        Architecture::Aarch64 => {
+            if let Some(aarch64) = AArch64::discover(&obj_file)? {
+                return run_discovered(obj_file, context, entry, aarch64, cfg);
+            }
+        }
+        Architecture::Riscv32 => {
+            if let Some(rv32) = RiscV32IMAC::discover(&obj_file)? {
+                return run_discovered(obj_file, context, entry, rv32, cfg);
             }
         }
-        _ => todo!(),
+        _ => (),
     }
 
     Err(ProjectError::UnableToParseElf(
@@ -165,6 +165,223 @@ pub fn run_elf<P: AsRef<Path>>(
     ))?
 }
 
+/// Where the execution starts.
+#[derive(Debug, Clone, Copy)]
+pub enum Entry<'a> {
+    /// The entry of a function, or an address.
+    Function(&'a str),
+    /// The PC of a snapshot, see [`Snapshot`].
+    Snapshot(&'a Snapshot),
+}
+
+/// How [`run_elf`] loads and runs a binary.
+pub struct ElfConfig {
+    /// How to load the file as a raw binary, Intel HEX or S-record image, it
+    /// is read as an ELF file if this is `None`. Default is `None`.
+    pub image: Option<ImageOptions>,
+
+    /// The target used when counting cycles, the default target of the
+    /// architecture is used if this is `None`. Default is `None`.
+    pub target: Option<TargetConfiguration>,
+
+    /// Print each path as it finishes. Default is `false`.
+    pub show_path_results: bool,
+
+    /// Merge paths where they rejoin, see [`RunConfig::merge_paths`]. Each
+    /// result then holds the worst case cycle count of a merged path and an
+    /// input that reaches it. Default is `false`.
+    pub merge_paths: bool,
+
+    /// Replay each finished path with concrete inputs and write its
+    /// instruction trace, see [`replay`](general_assembly::replay). Default
+    /// is `None`.
+    pub trace: Option<TraceConfig>,
+
+    /// Maximum number of targets a branch to a symbolic address is forked to.
+    /// Default is [`DEFAULT_MAX_BRANCH_TARGETS`].
+    pub max_branch_targets: usize,
+}
+
+impl Default for ElfConfig {
+    fn default() -> Self {
+        Self {
+            image: None,
+            target: None,
+            show_path_results: false,
+            merge_paths: false,
+            trace: None,
+            max_branch_targets: DEFAULT_MAX_BRANCH_TARGETS,
+        }
+    }
+}
+
+/// Runs all paths from `entry` for the discovered `architecture`.
+fn run_discovered<A: Arch>(
+    obj_file: object::File,
+    context: &'static DContext,
+    entry: Entry,
+    architecture: A,
+    elf_cfg: ElfConfig,
+) -> Result<Vec<VisualPathResult>, GAError> {
+    let mut cfg = run_config(elf_cfg);
+    let project = Project::from_path(&mut cfg, obj_file, &architecture)?;
+    run_project(project, context, entry, architecture, &mut cfg)
+}
+
+/// Creates the [`RunConfig`] with the architecture independent hooks.
+fn run_config<A: Arch>(elf_cfg: ElfConfig) -> RunConfig<A> {
+    let mut cfg = RunConfig {
+        show_path_results: elf_cfg.show_path_results,
+        pc_hooks: Vec::new(),
+        register_read_hooks: Vec::new(),
+        register_write_hooks: Vec::new(),
+        memory_write_hooks: Vec::new(),
+        memory_read_hooks: Vec::new(),
+        target: elf_cfg.target,
+        merge_paths: elf_cfg.merge_paths,
+        max_branch_targets: elf_cfg.max_branch_targets,
+        trace: elf_cfg.trace,
+    };
+    add_architecture_independent_hooks(&mut cfg);
+    cfg
+}
+
+/// Runs all paths from `entry` in `project`.
+fn run_project<A: Arch>(
+    project: Project<A>,
+    context: &'static DContext,
+    entry: Entry,
+    architecture: A,
+    cfg: &mut RunConfig<A>,
+) -> Result<Vec<VisualPathResult>, GAError> {
+    let end_pc = 0xFFFFFFFE;
+    let project = Box::leak(Box::new(project));
+    project.add_pc_hook(end_pc, PCHook::EndSuccess);
+    debug!("Created project: {:?}", project);
+
+    let mut vm = match entry {
+        Entry::Function(function) => VM::new(project, context, function, end_pc, architecture)?,
+        Entry::Snapshot(snapshot) => {
+            VM::from_snapshot(project, context, snapshot, end_pc, architecture)?
+        }
+    };
+    run_elf_paths(&mut vm, cfg)
+}
+
+/// The architecture of a binary image, which can not be discovered from the
+/// image itself.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImageArchitecture {
+    ArmV6M,
+    ArmV7EM,
+    ArmV8MBaseline,
+    ArmV8MMainline,
+    RiscV32IMAC,
+    AArch64,
+}
+
+impl FromStr for ImageArchitecture {
+    type Err = ProjectError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s.to_lowercase().as_str() {
+            "armv6m" | "armv6-m" => Self::ArmV6M,
+            "armv7m" | "armv7-m" | "armv7em" | "armv7e-m" => Self::ArmV7EM,
+            "armv8m.base" | "armv8-m.base" => Self::ArmV8MBaseline,
+            "armv8m.main" | "armv8-m.main" => Self::ArmV8MMainline,
+            "rv32imac" | "riscv32imac" => Self::RiscV32IMAC,
+            "aarch64" | "arm64" => Self::AArch64,
+            _ => {
+                return Err(ProjectError::UnableToParseImage(format!(
+                    "unknown architecture {s}"
+                )))
+            }
+        })
+    }
+}
+
+/// Describes how to load a binary image, see [`ElfConfig::image`].
+#[derive(Debug, Clone)]
+pub struct ImageOptions {
+    pub format: ImageFormat,
+    pub architecture: ImageArchitecture,
+    pub endianness: Endianness,
+    /// The symbols of the image, e.g. from
+    /// [`parse_symbol_map`](general_assembly::project::image::parse_symbol_map).
+    pub symbols: HashMap<String, u64>,
+}
+
+/// Runs an image, the stack starts at the `_stack_start` symbol, or the
+/// default of the architecture if it has one, and the PC hooks are placed at
+/// the addresses of the symbols with matching names.
+fn run_image<P: AsRef<Path>>(
+    path: P,
+    entry: Entry,
+    options: ImageOptions,
+    cfg: ElfConfig,
+) -> Result<Vec<VisualPathResult>, GAError> {
+    let context = Box::leak(Box::new(DContext::new()));
+
+    let str_version = path.as_ref().display().to_string();
+    debug!("Loading image: {}", str_version);
+    let data = fs::read(path)
+        .map_err(|e| ProjectError::UnableToParseImage(format!("{str_version}: {e}")))?;
+    let image = LoadedImage {
+        segments: image::load(&data, options.format)?,
+        symbols: options.symbols,
+        word_size: match options.architecture {
+            ImageArchitecture::AArch64 => WordSize::Bit64,
+            _ => WordSize::Bit32,
+        },
+        endianness: options.endianness,
+        cfg,
+    };
+
+    match options.architecture {
+        ImageArchitecture::ArmV6M => run_loaded(image, context, entry, ArmV6M {}),
+        ImageArchitecture::ArmV7EM => run_loaded(image, context, entry, ArmV7EM::default()),
+        ImageArchitecture::ArmV8MBaseline => {
+            let v8 = ArmV8M {
+                profile: ArmV8MProfile::Baseline,
+                ..Default::default()
+            };
+            run_loaded(image, context, entry, v8)
+        }
+        ImageArchitecture::ArmV8MMainline => run_loaded(image, context, entry, ArmV8M::default()),
+        ImageArchitecture::RiscV32IMAC => run_loaded(image, context, entry, RiscV32IMAC),
+        ImageArchitecture::AArch64 => run_loaded(image, context, entry, AArch64),
+    }
+}
+
+/// The contents of an image and the options of [`run_elf`] that are used once
+/// the architecture is selected.
+struct LoadedImage {
+    segments: Segments,
+    symbols: HashMap<String, u64>,
+    word_size: WordSize,
+    endianness: Endianness,
+    cfg: ElfConfig,
+}
+
+/// Runs all paths from `entry` in a loaded image.
+fn run_loaded<A: Arch>(
+    image: LoadedImage,
+    context: &'static DContext,
+    entry: Entry,
+    architecture: A,
+) -> Result<Vec<VisualPathResult>, GAError> {
+    let mut cfg = run_config(image.cfg);
+    let project = Project::from_image(
+        &mut cfg,
+        image.segments,
+        image.symbols,
+        image.word_size,
+        image.endianness,
+        &architecture,
+    )?;
+    run_project(project, context, entry, architecture, &mut cfg)
+}
+
 /// Run symbolic execution on a elf file where `path` is the path to the ELF
 /// file and `function` is the function the execution starts at.
 /// Execution will use the provided [`RunConfig`] and allows for pre-configured
@@ -202,14 +419,17 @@ pub fn run_elf_configured<A: Arch>(
     debug!("Created project: {:?}", project);
 
     let mut vm = general_assembly::vm::VM::new(project, context, function, end_pc, architecture)?;
-    run_elf_paths(&mut vm, &cfg)
+    run_elf_paths(&mut vm, &mut cfg)
 }
 
 /// Runs all paths in the vm
 fn run_elf_paths<A: Arch>(
     vm: &mut general_assembly::vm::VM<A>,
-    cfg: &RunConfig<A>,
+    cfg: &mut RunConfig<A>,
 ) -> Result<Vec<VisualPathResult>, GAError> {
+    if cfg.merge_paths {
+        return run_elf_merged_paths(vm, cfg);
+    }
     let mut path_num = 0;
     let start = Instant::now();
     let mut path_results = vec![];
@@ -224,18 +444,11 @@ fn run_elf_paths<A: Arch>(
         }
 
         path_num += 1;
+        if let Some(trace) = &mut cfg.trace {
+            write_trace(vm, &state, &path_result, path_num, trace)?;
+        }
 
-        let v_path_result = match path_result {
-            general_assembly::executor::PathResult::Success(_v) => PathStatus::Ok(None),
-            general_assembly::executor::PathResult::Failure(reason) => {
-                PathStatus::Failed(ErrorReason {
-                    error_message: reason.to_owned(),
-                })
-            }
-            general_assembly::executor::PathResult::AssumptionUnsat => todo!(),
-            general_assembly::executor::PathResult::Suppress => todo!(),
-        };
-
+        let v_path_result = path_status(path_result);
         let result = VisualPathResult::from_state(state, path_num, v_path_result)?;
 
         if cfg.show_path_results {
@@ -248,3 +461,76 @@ fn run_elf_paths<A: Arch>(
     }
     Ok(path_results)
 }
+
+/// Runs all paths in the vm, merging paths where they rejoin. The cycle count
+/// of each path is the maximum of its symbolic cycle count.
+fn run_elf_merged_paths<A: Arch>(
+    vm: &mut general_assembly::vm::VM<A>,
+    cfg: &mut RunConfig<A>,
+) -> Result<Vec<VisualPathResult>, GAError> {
+    let start = Instant::now();
+    let mut path_results = vec![];
+    for (path_result, state) in vm.run_merged()? {
+        if matches!(
+            path_result,
+            PathResult::Suppress | PathResult::AssumptionUnsat
+        ) {
+            debug!("Ignoring suppressed or unsatisfiable path");
+            continue;
+        }
+
+        let constraints = state.constraints.clone();
+        constraints.push();
+        constraints.assert(&state.path_condition);
+
+        // Find the worst case and constrain the inputs to reach it.
+        let cycles = state.get_cycle_count_expr();
+        let wcet = constraints.max_value(&cycles)?;
+        constraints.assert(&cycles._eq(&state.ctx.from_u64(wcet, 64)));
+        if let Some(trace) = &mut cfg.trace {
+            write_trace(vm, &state, &path_result, path_results.len() + 1, trace)?;
+        }
+
+        let v_path_result = path_status(path_result);
+        let result = VisualPathResult::from_state(state, path_results.len() + 1, v_path_result);
+        constraints.pop();
+        let mut result = result?;
+        result.max_cycles = wcet as usize;
+
+        if cfg.show_path_results {
+            println!("{}", result);
+        }
+        path_results.push(result);
+    }
+    if cfg.show_path_results {
+        println!("time: {:?}", start.elapsed());
+    }
+    Ok(path_results)
+}
+
+/// Replays a finished path and writes its trace.
+fn write_trace<A: Arch>(
+    vm: &general_assembly::vm::VM<A>,
+    state: &GAState<A>,
+    path_result: &PathResult,
+    path_num: usize,
+    trace: &mut TraceConfig,
+) -> Result<(), GAError> {
+    if trace.only_failures && !matches!(path_result, PathResult::Failure(_)) {
+        return Ok(());
+    }
+    replay::replay(&vm.initial_state, state)?.write(&mut *trace.writer, path_num, trace.format)
+}
+
+fn path_status(path_result: PathResult) -> PathStatus {
+    match path_result {
+        PathResult::Success(_v) => PathStatus::Ok(None),
+        PathResult::Failure(reason) => PathStatus::Failed(ErrorReason {
+            error_message: reason.to_owned(),
+            error_location: None,
+            stack_trace: Vec::new(),
+        }),
+        PathResult::AssumptionUnsat => todo!(),
+        PathResult::Suppress => todo!(),
+    }
+}
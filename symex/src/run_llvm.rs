@@ -4,13 +4,24 @@ use std::{
     time::{Duration, Instant},
 };
 
+use llvm_ir::{GlobalValue, Type};
 use rustc_demangle::demangle;
 use tracing::{debug, info};
 
+pub use crate::analysis::SolveFor;
 use crate::{
     smt::DContext,
     util::{ErrorReason, ExpressionType, LineTrace, PathStatus, Variable, VisualPathResult},
-    vm::{AnalysisError, LLVMExecutorError, LLVMState, PathResult, Project, VM},
+    vm::{
+        expression_type,
+        AnalysisError,
+        Config,
+        LLVMExecutorError,
+        LLVMState,
+        PathResult,
+        Project,
+        VM,
+    },
 };
 
 #[derive(Debug)]
@@ -27,6 +38,9 @@ pub struct RunConfig {
 
     /// If concretized output values should be shown.
     pub solve_output: bool,
+
+    /// If the result of each path should be printed.
+    pub show_path_results: bool,
 }
 
 impl RunConfig {
@@ -37,30 +51,37 @@ impl RunConfig {
     fn should_solve(&self, result: &PathResult) -> bool {
         match self.solve_for {
             SolveFor::All => true,
-            SolveFor::Error => matches!(result, PathResult::Success(_)),
-            SolveFor::Success => matches!(result, PathResult::Failure(_)),
+            SolveFor::Error => matches!(result, PathResult::Failure(_)),
+            SolveFor::Success => matches!(result, PathResult::Success(_)),
         }
     }
 }
 
-/// Determine for which types of paths the solver should be invoked on.
-#[derive(Debug)]
-pub enum SolveFor {
-    /// All paths.
-    All,
-
-    /// Paths which return errors. Currently this is both internal executor
-    /// errors and program errors.
-    Error,
+pub fn run(
+    path: impl AsRef<Path>,
+    function: impl AsRef<str>,
+    cfg: &RunConfig,
+) -> Result<Vec<VisualPathResult>, LLVMExecutorError> {
+    run_modules([path], function, cfg)
+}
 
-    /// Paths which are sucessful.
-    Success,
+/// Run on several bitcode files which are linked together before the analysis
+/// starts, see [`Project::from_paths`] for how symbol clashes are handled.
+pub fn run_modules<P: AsRef<Path>>(
+    paths: impl IntoIterator<Item = P>,
+    function: impl AsRef<str>,
+    cfg: &RunConfig,
+) -> Result<Vec<VisualPathResult>, LLVMExecutorError> {
+    run_modules_with_config(paths, function, cfg, Config::default())
 }
 
-pub fn run(
-    path: impl AsRef<Path>,
+/// Run on several bitcode files like [`run_modules`], with the VM configured
+/// by `vm_cfg`, e.g. its limits and memory model.
+pub fn run_modules_with_config<P: AsRef<Path>>(
+    paths: impl IntoIterator<Item = P>,
     function: impl AsRef<str>,
     cfg: &RunConfig,
+    vm_cfg: Config,
 ) -> Result<Vec<VisualPathResult>, LLVMExecutorError> {
     // As a temporary measure both the smt context and project get leaked, this is
     // only so I don't have to care about those lifetimes, since they always
@@ -68,15 +89,24 @@ pub fn run(
     let context = Box::new(DContext::new());
     let context = Box::leak(context);
 
-    let project = Box::new(Project::from_path(path).unwrap());
+    let project = Box::new(Project::from_paths(paths)?);
     let project = Box::leak(project);
 
+    // The return value is presented based on the return type of the entry
+    // function.
+    let output_ty = match project.find_entry_function(function.as_ref())?.value_type() {
+        Type::Function(t) => expression_type(&t.return_type(), project.ptr_size),
+        _ => ExpressionType::Unknown,
+    };
+
     info!("create VM");
-    let mut vm = VM::new(project, context, function.as_ref())?;
+    let mut vm = VM::new_with_config(project, context, function.as_ref(), vm_cfg)?;
     info!("run paths");
-    let result = run_paths(&mut vm, cfg)?;
+    let result = run_paths(&mut vm, cfg, &output_ty)?;
 
-    println!("Paths: {}, took: {:?}", result.num_paths, result.duration);
+    if cfg.show_path_results {
+        println!("Paths: {}, took: {:?}", result.num_paths, result.duration);
+    }
     // println!(
     //     "Instructions processed: {}",
     //     vm.stats.instructions_processed
@@ -91,7 +121,11 @@ struct RunnerResult {
     results: Vec<VisualPathResult>,
 }
 
-fn run_paths(vm: &mut VM, cfg: &RunConfig) -> Result<RunnerResult, LLVMExecutorError> {
+fn run_paths(
+    vm: &mut VM,
+    cfg: &RunConfig,
+    output_ty: &ExpressionType,
+) -> Result<RunnerResult, LLVMExecutorError> {
     // Go through all paths.
 
     let mut results = Vec::new();
@@ -134,7 +168,7 @@ fn run_paths(vm: &mut VM, cfg: &RunConfig) -> Result<RunnerResult, LLVMExecutorE
                             } else {
                                 value
                             },
-                            ty: ExpressionType::Unknown,
+                            ty: output_ty.clone(),
                         })
                     } else {
                         None
@@ -154,7 +188,9 @@ fn run_paths(vm: &mut VM, cfg: &RunConfig) -> Result<RunnerResult, LLVMExecutorE
                 inputs,
                 symbolics,
             };
-            println!("{}", path_result);
+            if cfg.show_path_results {
+                println!("{}", path_result);
+            }
 
             results.push(path_result);
         }
@@ -167,8 +203,15 @@ fn run_paths(vm: &mut VM, cfg: &RunConfig) -> Result<RunnerResult, LLVMExecutorE
     })
 }
 
-fn create_error_reason(state: &mut LLVMState, error: AnalysisError) -> ErrorReason {
-    let error_message = format!("{:?}", error);
+pub(crate) fn create_error_reason(state: &mut LLVMState, error: AnalysisError) -> ErrorReason {
+    let error_message = match error {
+        AnalysisError::Memory {
+            error,
+            allocation_site: Some(site),
+        } => format!("{error}, allocated at {site}"),
+        AnalysisError::Memory { error, .. } => format!("{error}"),
+        error => format!("{:?}", error),
+    };
 
     let error_location = state
         .stack_frames
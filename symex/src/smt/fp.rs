@@ -0,0 +1,750 @@
+//! IEEE-754 binary floating point on bit-vectors.
+//!
+//! The solver has no floating point theory, so floating point values are kept
+//! in their IEEE-754 bit representation and each operation is expanded in to
+//! bit-vector operations. The results are bit exact, including subnormals,
+//! signed zeros and infinities.
+//!
+//! All operations round to nearest, ties to even, which is the rounding mode
+//! LLVM assumes for the regular floating point instructions. An operation that
+//! produces a NaN returns the canonical quiet NaN, LLVM does not specify the
+//! payload.
+
+use super::{DContext, DExpr};
+
+/// Width of the exponents in intermediate results.
+///
+/// Wide enough to hold any exponent of a product, quotient or normalized
+/// subnormal of the supported formats.
+const EXPONENT_BITS: u32 = 32;
+
+/// An IEEE-754 binary interchange format.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FpFormat {
+    /// Number of bits in the exponent field.
+    pub exponent: u32,
+
+    /// Number of bits in the fraction field, the significand without the
+    /// implicit leading bit.
+    pub fraction: u32,
+}
+
+impl FpFormat {
+    /// 16-bit brain floating point.
+    pub const BFLOAT: Self = Self::new(8, 7);
+    /// 64-bit double precision.
+    pub const DOUBLE: Self = Self::new(11, 52);
+    /// 16-bit half precision.
+    pub const HALF: Self = Self::new(5, 10);
+    /// 128-bit quadruple precision.
+    pub const QUAD: Self = Self::new(15, 112);
+    /// 32-bit single precision.
+    pub const SINGLE: Self = Self::new(8, 23);
+
+    pub const fn new(exponent: u32, fraction: u32) -> Self {
+        Self { exponent, fraction }
+    }
+
+    /// Width of a value in bits.
+    pub fn bits(&self) -> u32 {
+        1 + self.exponent + self.fraction
+    }
+
+    /// Number of bits in the significand including the implicit leading bit.
+    fn precision(&self) -> u32 {
+        self.fraction + 1
+    }
+
+    fn bias(&self) -> i64 {
+        (1 << (self.exponent - 1)) - 1
+    }
+
+    fn min_exponent(&self) -> i64 {
+        1 - self.bias()
+    }
+
+    fn max_exponent(&self) -> i64 {
+        self.bias()
+    }
+}
+
+/// A value split in to its fields.
+///
+/// A finite value is `(-1)^sign * significand * 2^(exponent - (precision -
+/// 1))`, i.e. the binary point is right after the leading bit of the
+/// significand.
+struct Unpacked {
+    sign: DExpr,
+    exponent: DExpr,
+    significand: DExpr,
+    nan: DExpr,
+    inf: DExpr,
+    zero: DExpr,
+}
+
+/// Creates a constant of any width, `DContext::from_u64` sign extends values
+/// wider than 64 bits.
+fn constant(ctx: &DContext, value: u64, bits: u32) -> DExpr {
+    match bits {
+        64.. => ctx.from_u64(value, 64).zero_ext(bits),
+        _ => ctx.from_u64(value & ((1 << bits) - 1), bits),
+    }
+}
+
+fn exponent(ctx: &DContext, value: i64) -> DExpr {
+    ctx.from_u64(value as u64 & 0xffff_ffff, EXPONENT_BITS)
+}
+
+fn ones(ctx: &DContext, bits: u32) -> DExpr {
+    ctx.unsigned_max(bits)
+}
+
+fn unpack(ctx: &DContext, format: FpFormat, value: &DExpr) -> Unpacked {
+    assert_eq!(value.len(), format.bits());
+    let sign = value.slice(format.bits() - 1, format.bits() - 1);
+    let biased = value.slice(format.fraction, format.fraction + format.exponent - 1);
+    let fraction = value.slice(0, format.fraction - 1);
+
+    let max_exponent = biased._eq(&ones(ctx, format.exponent));
+    let min_exponent = biased._eq(&ctx.zero(format.exponent));
+    let zero_fraction = fraction._eq(&ctx.zero(format.fraction));
+
+    // Subnormals have no implicit leading one and the same exponent as the
+    // smallest normal numbers.
+    let significand = min_exponent.not().concat(&fraction);
+    let exponent = min_exponent.ite(
+        &exponent(ctx, format.min_exponent()),
+        &biased
+            .zero_ext(EXPONENT_BITS)
+            .sub(&exponent(ctx, format.bias())),
+    );
+
+    Unpacked {
+        sign,
+        exponent,
+        significand,
+        nan: max_exponent.and(&zero_fraction.not()),
+        inf: max_exponent.and(&zero_fraction),
+        zero: min_exponent.and(&zero_fraction),
+    }
+}
+
+/// The canonical quiet NaN.
+pub fn nan(ctx: &DContext, format: FpFormat) -> DExpr {
+    let quiet = 1 << (format.fraction - 1);
+    ctx.zero(1)
+        .concat(&ones(ctx, format.exponent))
+        .concat(&constant(ctx, quiet, format.fraction))
+}
+
+/// Infinity with the sign `sign`.
+pub fn infinity(ctx: &DContext, format: FpFormat, sign: &DExpr) -> DExpr {
+    sign.concat(&ones(ctx, format.exponent))
+        .concat(&ctx.zero(format.fraction))
+}
+
+/// Zero with the sign `sign`.
+pub fn zero(ctx: &DContext, format: FpFormat, sign: &DExpr) -> DExpr {
+    sign.concat(&ctx.zero(format.exponent + format.fraction))
+}
+
+/// Shifts `value` left until the leading bit is set.
+///
+/// Returns the shifted value and the number of shifted bits, the shift is
+/// unspecified for zero.
+fn normalize(ctx: &DContext, value: &DExpr) -> (DExpr, DExpr) {
+    let bits = value.len();
+    let mut value = value.clone();
+    let mut shift = exponent(ctx, 0);
+
+    // Count the leading zeros by a binary search, from the largest power of two
+    // below the width.
+    let mut step = 1;
+    while step * 2 < bits {
+        step *= 2;
+    }
+    while step > 0 && step < bits {
+        let leading_zeros = value.slice(bits - step, bits - 1)._eq(&ctx.zero(step));
+        value = leading_zeros.ite(&value.sll(&constant(ctx, step as u64, bits)), &value);
+        shift = leading_zeros.ite(&shift.add(&exponent(ctx, step as i64)), &shift);
+        step /= 2;
+    }
+    (value, shift)
+}
+
+/// Shifts `value` right by `shift`, a 32-bit unsigned amount, and sets the
+/// lowest bit if any set bit was shifted out.
+fn shift_right_sticky(ctx: &DContext, value: &DExpr, shift: &DExpr) -> DExpr {
+    let bits = value.len();
+    let max = exponent(ctx, bits as i64);
+    let shift = shift.ugt(&max).ite(&max, shift).resize_unsigned(bits);
+
+    let lost_mask = ones(ctx, bits).sll(&shift).not();
+    let lost = value.and(&lost_mask)._ne(&ctx.zero(bits));
+    value.srl(&shift).or(&lost.zero_ext(bits))
+}
+
+/// Rounds `(-1)^sign * significand * 2^(exponent - (width - 1))` to the
+/// nearest value of `format`, where `width` is the width of `significand`.
+///
+/// `significand` does not have to be normalized, a zero significand gives a
+/// zero with the sign `sign`.
+fn round(
+    ctx: &DContext,
+    format: FpFormat,
+    sign: &DExpr,
+    exponent: &DExpr,
+    significand: &DExpr,
+) -> DExpr {
+    let precision = format.precision();
+
+    // Make room for the guard bit and at least one sticky bit.
+    let significand = match significand.len() < precision + 2 {
+        true => significand.concat(&ctx.zero(precision + 2 - significand.len())),
+        false => significand.clone(),
+    };
+    let bits = significand.len();
+
+    let (significand, shift) = normalize(ctx, &significand);
+    let exponent = exponent.sub(&shift);
+
+    // Values below the normal range are shifted to the exponent of the
+    // subnormals.
+    let min_exponent = self::exponent(ctx, format.min_exponent());
+    let subnormal = exponent.slt(&min_exponent);
+    let significand = subnormal.ite(
+        &shift_right_sticky(ctx, &significand, &min_exponent.sub(&exponent)),
+        &significand,
+    );
+    let exponent = subnormal.ite(&min_exponent, &exponent);
+
+    let mantissa = significand.slice(bits - precision, bits - 1);
+    let guard = significand.slice(bits - precision - 1, bits - precision - 1);
+    let sticky = significand
+        .slice(0, bits - precision - 2)
+        ._ne(&ctx.zero(bits - precision - 1));
+    let odd = mantissa.slice(0, 0);
+    let round_up = guard.and(&sticky.or(&odd));
+
+    let mantissa = mantissa
+        .zero_ext(precision + 1)
+        .add(&round_up.zero_ext(precision + 1));
+    let carry = mantissa.slice(precision, precision);
+    let exponent = carry.ite(&exponent.add(&self::exponent(ctx, 1)), &exponent);
+    let mantissa = carry.ite(
+        &mantissa.slice(1, precision),
+        &mantissa.slice(0, precision - 1),
+    );
+
+    // Subnormals keep their leading zero after rounding, unless they rounded up
+    // to the smallest normal number.
+    let normal = mantissa.slice(precision - 1, precision - 1);
+    let biased = normal.ite(
+        &exponent.add(&self::exponent(ctx, format.bias())),
+        &self::exponent(ctx, 0),
+    );
+    let overflow = normal.and(&exponent.sgt(&self::exponent(ctx, format.max_exponent())));
+
+    let result = sign
+        .concat(&biased.slice(0, format.exponent - 1))
+        .concat(&mantissa.slice(0, format.fraction - 1));
+    overflow.ite(&infinity(ctx, format, sign), &result)
+}
+
+/// Negates `value`, only the sign is changed, even for NaN.
+pub fn neg(ctx: &DContext, format: FpFormat, value: &DExpr) -> DExpr {
+    let sign = constant(ctx, 1, format.bits()).sll(&constant(
+        ctx,
+        format.bits() as u64 - 1,
+        format.bits(),
+    ));
+    value.xor(&sign)
+}
+
+/// Computes `lhs + rhs`.
+pub fn add(ctx: &DContext, format: FpFormat, lhs: &DExpr, rhs: &DExpr) -> DExpr {
+    let x = unpack(ctx, format, lhs);
+    let y = unpack(ctx, format, rhs);
+    let precision = format.precision();
+
+    // Order the operands by magnitude so that the difference of the
+    // significands is never negative.
+    let swap = lhs
+        .slice(0, format.bits() - 2)
+        .ult(&rhs.slice(0, format.bits() - 2));
+    let (big, small) = (&x, &y);
+    let big_sign = swap.ite(&small.sign, &big.sign);
+    let big_exponent = swap.ite(&small.exponent, &big.exponent);
+    let small_exponent = swap.ite(&big.exponent, &small.exponent);
+    let big_significand = swap.ite(&small.significand, &big.significand);
+    let small_significand = swap.ite(&big.significand, &small.significand);
+
+    // One bit for the carry and three for guard, round and sticky.
+    let widen = |significand: &DExpr| significand.zero_ext(precision + 1).concat(&ctx.zero(3));
+    let big_significand = widen(&big_significand);
+    let small_significand = shift_right_sticky(
+        ctx,
+        &widen(&small_significand),
+        &big_exponent.sub(&small_exponent),
+    );
+
+    let same_sign = x.sign._eq(&y.sign);
+    let sum = same_sign.ite(
+        &big_significand.add(&small_significand),
+        &big_significand.sub(&small_significand),
+    );
+
+    // An exact zero is positive unless both operands are negative.
+    let exact_zero = sum._eq(&ctx.zero(sum.len()));
+    let sign = exact_zero.ite(&x.sign.and(&y.sign), &big_sign);
+    let result = round(
+        ctx,
+        format,
+        &sign,
+        &big_exponent.add(&exponent(ctx, 1)),
+        &sum,
+    );
+
+    let nan_result = x
+        .nan
+        .or(&y.nan)
+        .or(&x.inf.and(&y.inf).and(&same_sign.not()));
+    nan_result.ite(&nan(ctx, format), &x.inf.ite(lhs, &y.inf.ite(rhs, &result)))
+}
+
+/// Computes `lhs - rhs`.
+pub fn sub(ctx: &DContext, format: FpFormat, lhs: &DExpr, rhs: &DExpr) -> DExpr {
+    add(ctx, format, lhs, &neg(ctx, format, rhs))
+}
+
+/// Computes `lhs * rhs`.
+pub fn mul(ctx: &DContext, format: FpFormat, lhs: &DExpr, rhs: &DExpr) -> DExpr {
+    let x = unpack(ctx, format, lhs);
+    let y = unpack(ctx, format, rhs);
+    let bits = 2 * format.precision();
+
+    let sign = x.sign.xor(&y.sign);
+    let product = x
+        .significand
+        .zero_ext(bits)
+        .mul(&y.significand.zero_ext(bits));
+    let result = round(
+        ctx,
+        format,
+        &sign,
+        &x.exponent.add(&y.exponent).add(&exponent(ctx, 1)),
+        &product,
+    );
+
+    let nan_result = x
+        .nan
+        .or(&y.nan)
+        .or(&x.inf.and(&y.zero))
+        .or(&x.zero.and(&y.inf));
+    nan_result.ite(
+        &nan(ctx, format),
+        &x.inf.or(&y.inf).ite(&infinity(ctx, format, &sign), &result),
+    )
+}
+
+/// Computes `lhs / rhs`.
+pub fn div(ctx: &DContext, format: FpFormat, lhs: &DExpr, rhs: &DExpr) -> DExpr {
+    let x = unpack(ctx, format, lhs);
+    let y = unpack(ctx, format, rhs);
+    let precision = format.precision();
+    let bits = 2 * precision + 3;
+
+    // With both significands normalized the quotient has at least `precision +
+    // 2` bits, enough for the guard bit, and the remainder gives the sticky
+    // bit.
+    let (dividend, dividend_shift) = normalize(ctx, &x.significand);
+    let (divisor, divisor_shift) = normalize(ctx, &y.significand);
+    let dividend = dividend
+        .zero_ext(bits)
+        .sll(&constant(ctx, precision as u64 + 2, bits));
+    let divisor = divisor.zero_ext(bits);
+    let quotient = dividend.udiv(&divisor);
+    let inexact = dividend.urem(&divisor)._ne(&ctx.zero(bits));
+    let quotient = quotient
+        .sll(&constant(ctx, 1, bits))
+        .or(&inexact.zero_ext(bits));
+
+    let sign = x.sign.xor(&y.sign);
+    let result_exponent = x
+        .exponent
+        .sub(&dividend_shift)
+        .sub(&y.exponent.sub(&divisor_shift))
+        .add(&exponent(ctx, precision as i64 - 1));
+    let result = round(ctx, format, &sign, &result_exponent, &quotient);
+
+    let nan_result = x
+        .nan
+        .or(&y.nan)
+        .or(&x.zero.and(&y.zero))
+        .or(&x.inf.and(&y.inf));
+    nan_result.ite(
+        &nan(ctx, format),
+        &x.inf.or(&y.zero).ite(
+            &infinity(ctx, format, &sign),
+            &x.zero.or(&y.inf).ite(&zero(ctx, format, &sign), &result),
+        ),
+    )
+}
+
+/// Returns true if `value` is a NaN.
+pub fn is_nan(ctx: &DContext, format: FpFormat, value: &DExpr) -> DExpr {
+    unpack(ctx, format, value).nan
+}
+
+/// Returns true if neither value is NaN and `lhs == rhs`, where the zeros are
+/// equal.
+pub fn eq(ctx: &DContext, format: FpFormat, lhs: &DExpr, rhs: &DExpr) -> DExpr {
+    let x = unpack(ctx, format, lhs);
+    let y = unpack(ctx, format, rhs);
+    let ordered = x.nan.or(&y.nan).not();
+    ordered.and(&lhs._eq(rhs).or(&x.zero.and(&y.zero)))
+}
+
+/// Returns true if neither value is NaN and `lhs < rhs`.
+pub fn lt(ctx: &DContext, format: FpFormat, lhs: &DExpr, rhs: &DExpr) -> DExpr {
+    let x = unpack(ctx, format, lhs);
+    let y = unpack(ctx, format, rhs);
+    let ordered = x.nan.or(&y.nan).not();
+
+    // Compare the magnitudes as integers, negative values are ordered in
+    // reverse.
+    let lhs_magnitude = lhs.slice(0, format.bits() - 2);
+    let rhs_magnitude = rhs.slice(0, format.bits() - 2);
+    let less = x.sign._eq(&y.sign).ite(
+        &x.sign.ite(
+            &lhs_magnitude.ugt(&rhs_magnitude),
+            &lhs_magnitude.ult(&rhs_magnitude),
+        ),
+        &x.sign,
+    );
+    ordered.and(&x.zero.and(&y.zero).not()).and(&less)
+}
+
+/// Returns true if neither value is NaN and `lhs <= rhs`.
+pub fn le(ctx: &DContext, format: FpFormat, lhs: &DExpr, rhs: &DExpr) -> DExpr {
+    lt(ctx, format, lhs, rhs).or(&eq(ctx, format, lhs, rhs))
+}
+
+/// Converts `value` from the format `from` to the format `to`.
+pub fn convert(ctx: &DContext, from: FpFormat, to: FpFormat, value: &DExpr) -> DExpr {
+    let x = unpack(ctx, from, value);
+    let result = round(ctx, to, &x.sign, &x.exponent, &x.significand);
+    x.nan.ite(
+        &nan(ctx, to),
+        &x.inf.ite(&infinity(ctx, to, &x.sign), &result),
+    )
+}
+
+/// Converts the unsigned integer `value` to the nearest value of `format`.
+pub fn from_unsigned(ctx: &DContext, format: FpFormat, value: &DExpr) -> DExpr {
+    let bits = value.len() as i64;
+    round(ctx, format, &ctx.zero(1), &exponent(ctx, bits - 1), value)
+}
+
+/// Converts the signed integer `value` to the nearest value of `format`.
+pub fn from_signed(ctx: &DContext, format: FpFormat, value: &DExpr) -> DExpr {
+    let bits = value.len();
+    let sign = value.slice(bits - 1, bits - 1);
+    let magnitude = sign.ite(&ctx.zero(bits).sub(value), value);
+    round(
+        ctx,
+        format,
+        &sign,
+        &exponent(ctx, bits as i64 - 1),
+        &magnitude,
+    )
+}
+
+/// Converts `value` to an integer of width `bits`, rounding towards zero.
+///
+/// Returns the magnitude, which is wide enough for any value in range.
+fn to_integer(ctx: &DContext, format: FpFormat, value: &DExpr, bits: u32) -> (DExpr, DExpr) {
+    let x = unpack(ctx, format, value);
+    let width = bits.max(format.precision()) + 1;
+    let significand = x.significand.zero_ext(width);
+
+    // The value is `significand * 2^(exponent - (precision - 1))`.
+    let shift = x
+        .exponent
+        .sub(&exponent(ctx, format.precision() as i64 - 1));
+    let negative_shift = shift.slt(&exponent(ctx, 0));
+    let max = exponent(ctx, width as i64);
+    let cap = |shift: DExpr| shift.ugt(&max).ite(&max, &shift).resize_unsigned(width);
+    let magnitude = negative_shift.ite(
+        &significand.srl(&cap(exponent(ctx, 0).sub(&shift))),
+        &significand.sll(&cap(shift)),
+    );
+    (x.sign, magnitude)
+}
+
+/// Converts `value` to an unsigned integer of width `bits`, rounding towards
+/// zero.
+///
+/// NaN, infinities and values out of range give an unspecified result, LLVM
+/// makes these poison.
+pub fn to_unsigned(ctx: &DContext, format: FpFormat, value: &DExpr, bits: u32) -> DExpr {
+    let (_, magnitude) = to_integer(ctx, format, value, bits);
+    magnitude.slice(0, bits - 1)
+}
+
+/// Converts `value` to a signed integer of width `bits`, rounding towards zero.
+///
+/// NaN, infinities and values out of range give an unspecified result, LLVM
+/// makes these poison.
+pub fn to_signed(ctx: &DContext, format: FpFormat, value: &DExpr, bits: u32) -> DExpr {
+    let (sign, magnitude) = to_integer(ctx, format, value, bits);
+    let magnitude = magnitude.slice(0, bits - 1);
+    sign.ite(&ctx.zero(bits).sub(&magnitude), &magnitude)
+}
+
+/// Creates a constant of `format` from the nearest value to `value`.
+pub fn from_f64(ctx: &DContext, format: FpFormat, value: f64) -> DExpr {
+    match format {
+        FpFormat::DOUBLE => ctx.from_u64(value.to_bits(), 64),
+        FpFormat::SINGLE => ctx.from_u64((value as f32).to_bits() as u64, 32),
+        _ => convert(
+            ctx,
+            FpFormat::DOUBLE,
+            format,
+            &ctx.from_u64(value.to_bits(), 64),
+        ),
+    }
+}
+
+/// Converts the bits of a value of `format` to a `f64`.
+///
+/// `format` can be at most as wide as a double.
+pub fn to_f64(format: FpFormat, bits: u64) -> f64 {
+    assert!(format.exponent <= 11 && format.fraction <= 52);
+    let fraction = bits & ((1 << format.fraction) - 1);
+    let biased = (bits >> format.fraction) & ((1 << format.exponent) - 1);
+    let sign = match (bits >> (format.bits() - 1)) & 1 {
+        1 => -1.0,
+        _ => 1.0,
+    };
+
+    let value = if biased == (1 << format.exponent) - 1 {
+        match fraction {
+            0 => f64::INFINITY,
+            _ => f64::NAN,
+        }
+    } else {
+        let (significand, exponent) = match biased {
+            0 => (fraction, format.min_exponent()),
+            _ => (
+                fraction | 1 << format.fraction,
+                biased as i64 - format.bias(),
+            ),
+        };
+        significand as f64 * 2f64.powi((exponent - format.fraction as i64) as i32)
+    };
+    sign * value
+}
+
+#[cfg(test)]
+mod test {
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    use super::*;
+    use crate::smt::DSolver;
+
+    fn evaluate(ctx: &DContext, expr: &DExpr) -> u64 {
+        DSolver::new(ctx)
+            .get_value(expr)
+            .unwrap()
+            .get_constant()
+            .unwrap()
+    }
+
+    fn f32_values() -> Vec<f32> {
+        let mut values = vec![
+            0.0,
+            -0.0,
+            1.0,
+            -1.5,
+            0.1,
+            3.0,
+            1e-40,
+            -1.2e-38,
+            f32::MIN_POSITIVE,
+            f32::from_bits(1),
+            f32::MAX,
+            -f32::MAX,
+            16777217.0,
+            f32::INFINITY,
+            f32::NEG_INFINITY,
+            f32::NAN,
+        ];
+        let mut rng = StdRng::seed_from_u64(0);
+        values.extend((0..6).map(|_| f32::from_bits(rng.gen())));
+        values
+    }
+
+    fn assert_f32(ctx: &DContext, expr: &DExpr, expected: f32, op: &str) {
+        let result = f32::from_bits(evaluate(ctx, expr) as u32);
+        assert!(
+            result.to_bits() == expected.to_bits() || result.is_nan() && expected.is_nan(),
+            "{op}: expected {expected:e} got {result:e}"
+        );
+    }
+
+    #[test]
+    fn test_f32_arithmetic() {
+        let ctx = DContext::new();
+        let format = FpFormat::SINGLE;
+        let values = f32_values();
+        for a in values.iter().copied() {
+            for b in values.iter().copied() {
+                let lhs = ctx.from_u64(a.to_bits() as u64, 32);
+                let rhs = ctx.from_u64(b.to_bits() as u64, 32);
+                assert_f32(
+                    &ctx,
+                    &add(&ctx, format, &lhs, &rhs),
+                    a + b,
+                    &format!("{a:e} + {b:e}"),
+                );
+                assert_f32(
+                    &ctx,
+                    &sub(&ctx, format, &lhs, &rhs),
+                    a - b,
+                    &format!("{a:e} - {b:e}"),
+                );
+                assert_f32(
+                    &ctx,
+                    &mul(&ctx, format, &lhs, &rhs),
+                    a * b,
+                    &format!("{a:e} * {b:e}"),
+                );
+                assert_f32(
+                    &ctx,
+                    &div(&ctx, format, &lhs, &rhs),
+                    a / b,
+                    &format!("{a:e} / {b:e}"),
+                );
+
+                let compare = |expr: DExpr, expected: bool, op: &str| {
+                    assert_eq!(evaluate(&ctx, &expr) == 1, expected, "{a:e} {op} {b:e}");
+                };
+                compare(eq(&ctx, format, &lhs, &rhs), a == b, "==");
+                compare(lt(&ctx, format, &lhs, &rhs), a < b, "<");
+                compare(le(&ctx, format, &lhs, &rhs), a <= b, "<=");
+            }
+        }
+    }
+
+    #[test]
+    fn test_f64_arithmetic() {
+        let ctx = DContext::new();
+        let format = FpFormat::DOUBLE;
+        let values: [f64; 7] = [0.1, -3.5, 1e300, 5e-324, 2.2250738585072014e-308, -0.0, 7.0];
+        for a in values {
+            for b in values {
+                let lhs = ctx.from_u64(a.to_bits(), 64);
+                let rhs = ctx.from_u64(b.to_bits(), 64);
+                let cases = [
+                    (add(&ctx, format, &lhs, &rhs), a + b),
+                    (mul(&ctx, format, &lhs, &rhs), a * b),
+                    (div(&ctx, format, &lhs, &rhs), a / b),
+                ];
+                for (expr, expected) in cases {
+                    let result = f64::from_bits(evaluate(&ctx, &expr));
+                    assert!(
+                        result.to_bits() == expected.to_bits()
+                            || result.is_nan() && expected.is_nan(),
+                        "{a:e}, {b:e}: expected {expected:e} got {result:e}"
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_conversions() {
+        let ctx = DContext::new();
+        for value in f32_values() {
+            let single = ctx.from_u64(value.to_bits() as u64, 32);
+            let double = convert(&ctx, FpFormat::SINGLE, FpFormat::DOUBLE, &single);
+            let expected = value as f64;
+            let result = f64::from_bits(evaluate(&ctx, &double));
+            assert!(result.to_bits() == expected.to_bits() || result.is_nan() && expected.is_nan());
+
+            let back = convert(&ctx, FpFormat::DOUBLE, FpFormat::SINGLE, &double);
+            assert_f32(&ctx, &back, value, "round trip");
+
+            if value.is_finite() && value.abs() < 2e9 {
+                let signed = to_signed(&ctx, FpFormat::SINGLE, &single, 32);
+                assert_eq!(
+                    evaluate(&ctx, &signed) as u32 as i32,
+                    value as i32,
+                    "{value:e}"
+                );
+            }
+            if value.is_finite() && (0.0..4e9).contains(&value) {
+                let unsigned = to_unsigned(&ctx, FpFormat::SINGLE, &single, 32);
+                assert_eq!(evaluate(&ctx, &unsigned) as u32, value as u32, "{value:e}");
+            }
+        }
+
+        // Rounding of doubles to single, with ties to even.
+        for value in [0.1f64, 1e-45, 1e39, 1.0 + f32::EPSILON as f64 / 2.0, -7e-46] {
+            let double = ctx.from_u64(f64::to_bits(value), 64);
+            let single = convert(&ctx, FpFormat::DOUBLE, FpFormat::SINGLE, &double);
+            assert_f32(&ctx, &single, value as f32, &format!("{value:e}"));
+        }
+
+        for value in [0i64, 1, -1, 16777217, -16777219, i64::MIN, i64::MAX] {
+            let integer = ctx.from_u64(value as u64, 64);
+            let signed = from_signed(&ctx, FpFormat::SINGLE, &integer);
+            assert_f32(&ctx, &signed, value as f32, &format!("{value}"));
+            let unsigned = from_unsigned(&ctx, FpFormat::DOUBLE, &integer);
+            assert_eq!(evaluate(&ctx, &unsigned), (value as u64 as f64).to_bits());
+        }
+    }
+
+    #[test]
+    fn test_half() {
+        let ctx = DContext::new();
+        let half = from_f64(&ctx, FpFormat::HALF, 65504.0);
+        assert_eq!(evaluate(&ctx, &half), 0x7bff);
+        assert_eq!(to_f64(FpFormat::HALF, 0x7bff), 65504.0);
+
+        // Rounds to infinity.
+        let half = from_f64(&ctx, FpFormat::HALF, 65520.0);
+        assert_eq!(evaluate(&ctx, &half), 0x7c00);
+
+        // The smallest subnormal.
+        let half = from_f64(&ctx, FpFormat::HALF, -5.960464477539063e-8);
+        assert_eq!(evaluate(&ctx, &half), 0x8001);
+        assert_eq!(to_f64(FpFormat::HALF, 0x8001), -5.960464477539063e-8);
+
+        let sum = add(&ctx, FpFormat::HALF, &half, &half);
+        assert_eq!(evaluate(&ctx, &sum), 0x8002);
+        assert!(to_f64(FpFormat::HALF, evaluate(&ctx, &nan(&ctx, FpFormat::HALF))).is_nan());
+    }
+
+    #[test]
+    fn test_symbolic() {
+        let ctx = DContext::new();
+        let solver = DSolver::new(&ctx);
+        let format = FpFormat::SINGLE;
+
+        // x * 2 == 3 has the unique solution 1.5.
+        let x = ctx.unconstrained(32, "x");
+        let two = from_f64(&ctx, format, 2.0);
+        let three = from_f64(&ctx, format, 3.0);
+        solver.assert(&eq(&ctx, format, &mul(&ctx, format, &x, &two), &three));
+        let value = solver.get_value(&x).unwrap().get_constant().unwrap();
+        assert_eq!(f32::from_bits(value as u32), 1.5);
+
+        // No value is both less than and greater than NaN.
+        let nan = nan(&ctx, format);
+        assert!(!solver
+            .is_sat_with_constraint(&lt(&ctx, format, &x, &nan))
+            .unwrap());
+    }
+}
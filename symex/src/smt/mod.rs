@@ -1,5 +1,6 @@
 use std::fmt::Debug;
 
+pub mod fp;
 pub mod smt_boolector;
 
 pub type DExpr = smt_boolector::BoolectorExpr;
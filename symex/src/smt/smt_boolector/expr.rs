@@ -3,7 +3,7 @@ use std::{cmp::Ordering, rc::Rc};
 
 use boolector::{Btor, BV};
 
-use super::BoolectorSolverContext;
+use super::{BoolectorArray, BoolectorSolverContext};
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct BoolectorExpr(pub(crate) BV<Rc<Btor>>);
@@ -190,6 +190,16 @@ impl BoolectorExpr {
         Self(self.0.cond_bv(&then_bv.0, &else_bv.0))
     }
 
+    /// Selects between two arrays, `self` has to be a boolean.
+    pub fn ite_array(
+        &self,
+        then_array: &BoolectorArray,
+        else_array: &BoolectorArray,
+    ) -> BoolectorArray {
+        assert_eq!(self.len(), 1);
+        BoolectorArray(self.0.cond_array(&then_array.0, &else_array.0))
+    }
+
     pub fn concat(&self, other: &Self) -> Self {
         Self(self.0.concat(&other.0))
     }
@@ -150,6 +150,35 @@ impl BoolectorIncrementalSolver {
         self.is_sat_with_constraint(&lhs._eq(rhs))
     }
 
+    /// Find the largest unsigned value `expr` can take under the current
+    /// constraints.
+    ///
+    /// The maximum is found by a binary search, starting from the value in a
+    /// model of the constraints. `expr` can be at most 64 bits wide.
+    pub fn max_value(&self, expr: &BoolectorExpr) -> Result<u64, SolverError> {
+        let expr = expr.clone().simplify();
+        if let Some(value) = expr.get_constant() {
+            return Ok(value);
+        }
+
+        let bits = expr.len();
+        assert!(bits <= 64);
+        let mut low = self.get_value(&expr)?.get_constant().unwrap();
+        let mut high = match bits {
+            64 => u64::MAX,
+            _ => (1 << bits) - 1,
+        };
+        while low < high {
+            let mid = low + (high - low).div_ceil(2);
+            let bound = BoolectorExpr(BV::from_u64(self.ctx.clone(), mid, bits));
+            match self.is_sat_with_constraint(&expr.ugte(&bound))? {
+                true => low = mid,
+                false => high = mid - 1,
+            }
+        }
+        Ok(low)
+    }
+
     /// Find solutions to `expr`.
     ///
     /// Returns concrete solutions up to a maximum of `upper_bound`. If more
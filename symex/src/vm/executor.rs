@@ -1,9 +1,18 @@
 use llvm_ir::{
-    instruction::{self, BasicBlock, Instruction, LLVMAtomicRMWBinOp, LLVMIntPredicate},
+    instruction::{
+        self,
+        BasicBlock,
+        Instruction,
+        LLVMAtomicRMWBinOp,
+        LLVMIntPredicate,
+        LLVMRealPredicate,
+    },
+    FloatingPointType,
     Function,
     Type,
     Value,
 };
+use rustc_demangle::demangle;
 use tracing::{debug, trace, warn};
 
 use super::{
@@ -18,8 +27,15 @@ use super::{
     Result,
 };
 use crate::{
-    memory::to_bytes_u32,
-    smt::{DContext, DExpr, SolverError},
+    memory::{to_bytes_u32, MemoryError},
+    smt::{
+        fp::{self, FpFormat},
+        DContext,
+        DExpr,
+        Solutions,
+        SolverError,
+    },
+    util::ExpressionType,
     vm::{Overriden, StackFrame},
 };
 
@@ -47,6 +63,7 @@ pub struct CallFn {
 pub enum CallResult {
     CallFn(CallFn),
     Return(Option<DExpr>),
+    Unwind(Option<DExpr>),
     AnalysisError(AnalysisError),
 }
 
@@ -54,6 +71,7 @@ pub enum BlockResult {
     Branch(BasicBlock),
     CallFn(CallFn),
     Return(Option<DExpr>),
+    Unwind(Option<DExpr>),
     AnalysisError(AnalysisError),
 }
 
@@ -63,6 +81,9 @@ pub enum InstructionResult {
     Branch(BasicBlock),
     CallFn(CallFn),
     Return(Option<DExpr>),
+    /// Start unwinding the stack, optionally with an exception value for the
+    /// landing pad.
+    Unwind(Option<DExpr>),
     AnalysisError(AnalysisError),
 }
 
@@ -97,19 +118,7 @@ impl<'vm> LLVMExecutor<'vm> {
                         return Ok(PathResult::Success(value));
                     }
 
-                    // Assign return values from functions.
-                    if let Some(result) = value {
-                        let current_instruction =
-                            self.state.current_frame()?.current_instruction().cloned().expect(
-                                "Basic block should not be empty. Should have a terminator instruction",
-                            );
-
-                        let register = Value::Instruction(current_instruction);
-                        self.assign_result(register, result)?;
-                    }
-
-                    // Resume execution on the next instruction.
-                    self.state.current_frame_mut()?.increase_pc();
+                    self.return_to_caller(value)?;
                 }
 
                 // We are calling another function. This will push a new stack frame and resume
@@ -142,30 +151,35 @@ impl<'vm> LLVMExecutor<'vm> {
                                 ResolvedFunction::Function(_) => unreachable!(),
                                 ResolvedFunction::Instrinic(i) => i(self, &call.arguments),
                                 ResolvedFunction::Hook(i) => i(self, &call.arguments),
-                            }?;
-
-                            let PathResult::Success(value) = result else {
-                                return Ok(result);
+                            };
+                            let result = match result {
+                                Ok(result) => result,
+                                Err(error) => PathResult::Failure(self.path_error(error)?),
                             };
 
-                            if let Some(value) = value {
-                                let current_instruction = self
-                                    .state
-                                    .current_frame()?
-                                    .current_instruction()
-                                    .cloned()
-                                    .expect(
-                                        "Basic block should not be empty. Should have a terminator instruction",
-                                    );
-
-                                let register = Value::Instruction(current_instruction);
-                                self.assign_result(register, value)?;
+                            match result {
+                                PathResult::Success(value) => self.return_to_caller(value)?,
+
+                                // Panics unwind from the call site, so drop glue and handlers
+                                // in the callers get to run.
+                                PathResult::Failure(AnalysisError::Panic) => {
+                                    if let Some(result) = self.unwind(None)? {
+                                        return Ok(result);
+                                    }
+                                }
+                                _ => return Ok(result),
                             }
-                            self.state.current_frame_mut()?.increase_pc();
                         }
                     }
                 }
 
+                // Unwinding out of the current function, continue in the closest landing pad.
+                CallResult::Unwind(exception) => {
+                    if let Some(result) = self.unwind(exception)? {
+                        return Ok(result);
+                    }
+                }
+
                 // End execution.
                 CallResult::AnalysisError(error) => return Ok(PathResult::Failure(error)),
             }
@@ -189,12 +203,15 @@ impl<'vm> LLVMExecutor<'vm> {
 
                 // Both of these will leave the current function, so return control to the caller.
                 BlockResult::Return(value) => {
-                    self.state.stack_frames.pop();
+                    self.pop_frame()?;
                     return Ok(CallResult::Return(value));
                 }
                 BlockResult::CallFn(call_fn) => {
                     return Ok(CallResult::CallFn(call_fn));
                 }
+
+                // Frames are popped by the caller once it knows where the unwind ends.
+                BlockResult::Unwind(exception) => return Ok(CallResult::Unwind(exception)),
                 BlockResult::AnalysisError(error) => return Ok(CallResult::AnalysisError(error)),
             }
         }
@@ -215,7 +232,10 @@ impl<'vm> LLVMExecutor<'vm> {
                 .cloned()
                 .expect("Basic block should not be empty. Should have a terminator instruction");
 
-            let result = self.execute_instruction(&instruction)?;
+            let result = match self.execute_instruction(&instruction) {
+                Ok(result) => result,
+                Err(error) => InstructionResult::AnalysisError(self.path_error(error)?),
+            };
             match result {
                 // Continue execution in the same basic block.
                 InstructionResult::Continue => {}
@@ -232,6 +252,7 @@ impl<'vm> LLVMExecutor<'vm> {
                 }
                 InstructionResult::Branch(branch) => return Ok(BlockResult::Branch(branch)),
                 InstructionResult::CallFn(call_fn) => return Ok(BlockResult::CallFn(call_fn)),
+                InstructionResult::Unwind(exception) => return Ok(BlockResult::Unwind(exception)),
                 InstructionResult::AnalysisError(error) => {
                     return Ok(BlockResult::AnalysisError(error))
                 }
@@ -241,6 +262,119 @@ impl<'vm> LLVMExecutor<'vm> {
         }
     }
 
+    /// Return control to the call site in the current stack frame.
+    ///
+    /// The returned value is assigned to the call instruction. Execution
+    /// resumes on the next instruction, or in the normal destination for an
+    /// `invoke`.
+    fn return_to_caller(&mut self, value: Option<DExpr>) -> Result<()> {
+        let current_instruction = self
+            .state
+            .current_frame()?
+            .current_instruction()
+            .cloned()
+            .expect("Basic block should not be empty. Should have a terminator instruction");
+
+        if let Some(value) = value {
+            let register = Value::Instruction(current_instruction.clone());
+            self.assign_result(register, value)?;
+        }
+
+        match current_instruction {
+            Instruction::Invoke(invoke) => {
+                let frame = self.state.current_frame_mut()?;
+                frame.set_basic_block(invoke.normal_destination())?;
+            }
+            _ => self.state.current_frame_mut()?.increase_pc(),
+        }
+        Ok(())
+    }
+
+    /// Unwind the stack to the closest frame that is inside an `invoke`, and
+    /// continue execution in its unwind destination.
+    ///
+    /// If no frame handles the unwind the path ends in a panic. The stack
+    /// frames are kept so the error can report where the unwind started.
+    fn unwind(&mut self, exception: Option<DExpr>) -> Result<Option<PathResult>> {
+        let handler =
+            self.state.stack_frames.iter().rposition(|frame| {
+                matches!(frame.current_instruction(), Some(Instruction::Invoke(_)))
+            });
+        let Some(handler) = handler else {
+            return Ok(Some(PathResult::Failure(AnalysisError::Panic)));
+        };
+
+        while self.state.stack_frames.len() > handler + 1 {
+            self.pop_frame()?;
+        }
+        let frame = self.state.current_frame_mut()?;
+        let Some(Instruction::Invoke(invoke)) = frame.current_instruction().cloned() else {
+            unreachable!()
+        };
+        frame.set_basic_block(invoke.unwind_destination())?;
+        self.state.exception = exception;
+
+        Ok(None)
+    }
+
+    /// Pop the current stack frame and free the stack allocations made in it.
+    fn pop_frame(&mut self) -> Result<()> {
+        if let Some(mut frame) = self.state.stack_frames.pop() {
+            for address in frame.pop_allocations(0) {
+                self.state.memory.free(address)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Errors caused by the analyzed program end the path with a failure, all
+    /// other errors are returned.
+    fn path_error(&self, error: LLVMExecutorError) -> Result<AnalysisError> {
+        let error = match error {
+            LLVMExecutorError::MemoryError(error) => error,
+            LLVMExecutorError::SolverError(SolverError::TooManySolutions) => {
+                return Ok(AnalysisError::TooManySolutions);
+            }
+            error => return Err(error),
+        };
+
+        let address = match error {
+            MemoryError::ObjectOutOfBounds(address)
+            | MemoryError::UseAfterFree(address)
+            | MemoryError::DoubleFree(address)
+            | MemoryError::MismatchedDealloc(address) => Some(address),
+            MemoryError::InvalidFree(_) | MemoryError::OutOfBounds | MemoryError::NullPointer => {
+                None
+            }
+            error => return Err(LLVMExecutorError::MemoryError(error)),
+        };
+
+        let allocation_site = address
+            .and_then(|address| self.state.memory.allocation_site(address))
+            .map(str::to_owned);
+        Ok(AnalysisError::Memory {
+            error,
+            allocation_site,
+        })
+    }
+
+    /// Describes the current instruction as an allocation site, by its debug
+    /// location if there is one and otherwise by the function it is in.
+    pub(crate) fn allocation_site(&self) -> Option<String> {
+        let frame = self.state.current_frame().ok()?;
+        let location = frame
+            .current_instruction()
+            .and_then(|instruction| instruction.debug_location());
+
+        match location {
+            Some(location) => Some(format!("{location}")),
+            None => {
+                let name = frame.function().name().to_string_lossy();
+                Some(format!("{:#}", demangle(&name)))
+            }
+        }
+    }
+
     fn assign_result(&mut self, value: Value, result: DExpr) -> Result<()> {
         self.state.current_frame_mut()?.set_register(value, result);
         Ok(())
@@ -325,6 +459,56 @@ impl<'vm> LLVMExecutor<'vm> {
         }
     }
 
+    /// Get a concrete value for `value`, forking a new path for each of the
+    /// other solutions.
+    ///
+    /// At most `upper_bound` solutions are explored. If there are more the
+    /// solutions that were found are forked and this path fails with
+    /// [`AnalysisError::TooManySolutions`], `what` describes the value in the
+    /// warning.
+    pub(crate) fn concretize(
+        &mut self,
+        value: &DExpr,
+        upper_bound: usize,
+        what: &str,
+    ) -> Result<u64> {
+        if let Some(value) = value.get_constant() {
+            return Ok(value);
+        }
+
+        let solutions = match self.state.constraints.get_values(value, upper_bound)? {
+            Solutions::Exactly(v) => v,
+            // Explore the solutions that were found on paths of their own, this path takes the
+            // rest and fails.
+            Solutions::AtLeast(v) => {
+                warn!(
+                    "More than {} solutions found for {}, the rest are not explored",
+                    upper_bound, what
+                );
+                for solution in v.iter() {
+                    let constraint = value._eq(solution);
+                    self.fork(constraint)?;
+                }
+                for solution in v.iter() {
+                    self.state.constraints.assert(&value._ne(solution));
+                }
+                return Err(SolverError::TooManySolutions.into());
+            }
+        };
+
+        let (solution, others) = solutions.split_first().unwrap();
+
+        // Fork other paths.
+        for solution in others.iter() {
+            let constraint = value._eq(solution);
+            self.fork(constraint)?;
+        }
+
+        let constraint = value._eq(solution);
+        self.state.constraints.assert(&constraint);
+        Ok(solution.get_constant().unwrap()) // Know this is constant.
+    }
+
     pub fn fork(&mut self, constraint: DExpr) -> Result<()> {
         trace!("Save backtracking path: constraint={:?}", constraint);
         let forked_state = self.state.clone();
@@ -426,7 +610,7 @@ impl<'vm> LLVMExecutor<'vm> {
         let address = self.state.get_expr(&i.address())?;
         let address = self.resolve_address(address)?;
 
-        let size = bit_size(&i.result_type(), self.project.ptr_size).unwrap();
+        let size = bit_size(&i.result_type(), self.project.ptr_size)?;
         let value = self.state.memory.read(&address, size)?;
         Ok(InstructionResult::Assign(value))
     }
@@ -444,14 +628,12 @@ impl<'vm> LLVMExecutor<'vm> {
 
     fn alloca(&mut self, i: &instruction::Alloca) -> Result<InstructionResult> {
         debug!("{i}");
-        let num_elements = i.num_elements();
-        let num_elements = match self.state.get_expr(&num_elements)?.get_constant() {
-            Some(c) => c,
-            None => todo!("Handle non-constant alloca"),
-        };
+        let num_elements = self.state.get_expr(&i.num_elements())?;
+        let upper_bound = self.vm.cfg.max_alloca_concretizations;
+        let num_elements = self.concretize(&num_elements, upper_bound, "alloca size")?;
 
         let allocated_type = i.allocated_type();
-        let allocated_size = bit_size(&allocated_type, self.project.ptr_size).unwrap() as u64;
+        let allocated_size = bit_size(&allocated_type, self.project.ptr_size)? as u64;
         let allocated_size = match allocated_size * num_elements {
             0 => {
                 warn!("Zero-sized alloca");
@@ -462,7 +644,12 @@ impl<'vm> LLVMExecutor<'vm> {
         };
 
         let alignment = i.alignment() as u64;
-        let address = self.state.memory.allocate(allocated_size, alignment)?;
+        let site = self.allocation_site();
+        let address = self
+            .state
+            .memory
+            .allocate_at(allocated_size, alignment, site)?;
+        self.state.current_frame_mut()?.push_allocation(address);
         let address = self.state.ctx.from_u64(address, self.project.ptr_size);
 
         Ok(InstructionResult::Assign(address))
@@ -546,40 +733,91 @@ impl<'vm> LLVMExecutor<'vm> {
         Ok(InstructionResult::Assign(result))
     }
 
-    fn fadd(&mut self, _i: &instruction::FAdd) -> Result<InstructionResult> {
-        Err(LLVMExecutorError::UnsupportedInstruction("fadd".to_owned()))
+    fn fadd(&mut self, i: &instruction::FAdd) -> Result<InstructionResult> {
+        debug!("{i}");
+        let result = fp_binop(&mut self.state, &i.lhs(), &i.rhs(), fp::add)?;
+        Ok(InstructionResult::Assign(result))
     }
 
-    fn fsub(&mut self, _i: &instruction::FSub) -> Result<InstructionResult> {
-        Err(LLVMExecutorError::UnsupportedInstruction("fsub".to_owned()))
+    fn fsub(&mut self, i: &instruction::FSub) -> Result<InstructionResult> {
+        debug!("{i}");
+        let result = fp_binop(&mut self.state, &i.lhs(), &i.rhs(), fp::sub)?;
+        Ok(InstructionResult::Assign(result))
     }
 
-    fn fmul(&mut self, _i: &instruction::FMul) -> Result<InstructionResult> {
-        Err(LLVMExecutorError::UnsupportedInstruction("fmul".to_owned()))
+    fn fmul(&mut self, i: &instruction::FMul) -> Result<InstructionResult> {
+        debug!("{i}");
+        let result = fp_binop(&mut self.state, &i.lhs(), &i.rhs(), fp::mul)?;
+        Ok(InstructionResult::Assign(result))
     }
 
-    fn fdiv(&mut self, _i: &instruction::FDiv) -> Result<InstructionResult> {
-        Err(LLVMExecutorError::UnsupportedInstruction("fdiv".to_owned()))
+    fn fdiv(&mut self, i: &instruction::FDiv) -> Result<InstructionResult> {
+        debug!("{i}");
+        let result = fp_binop(&mut self.state, &i.lhs(), &i.rhs(), fp::div)?;
+        Ok(InstructionResult::Assign(result))
     }
 
-    fn frem(&mut self, _i: &instruction::FRem) -> Result<InstructionResult> {
-        Err(LLVMExecutorError::UnsupportedInstruction("frem".to_owned()))
+    fn frem(&mut self, i: &instruction::FRem) -> Result<InstructionResult> {
+        debug!("{i}");
+        // The remainder is only computed for constant operands, expanding the
+        // long division for symbolic operands is not supported.
+        let lhs = self.state.get_expr(&i.lhs())?.simplify();
+        let rhs = self.state.get_expr(&i.rhs())?.simplify();
+        let result = match (i.lhs().ty(), lhs.get_constant(), rhs.get_constant()) {
+            (Type::Float(t), Some(lhs), Some(rhs)) => match t {
+                FloatingPointType::Float => {
+                    let result = f32::from_bits(lhs as u32) % f32::from_bits(rhs as u32);
+                    self.state.ctx.from_u64(result.to_bits() as u64, 32)
+                }
+                FloatingPointType::Double => {
+                    let result = f64::from_bits(lhs) % f64::from_bits(rhs);
+                    self.state.ctx.from_u64(result.to_bits(), 64)
+                }
+                _ => {
+                    return Err(LLVMExecutorError::UnsupportedInstruction(format!(
+                        "frem on {t:?}"
+                    )))
+                }
+            },
+            _ => {
+                return Err(LLVMExecutorError::UnsupportedInstruction(
+                    "frem with symbolic or vector operands".to_owned(),
+                ))
+            }
+        };
+        Ok(InstructionResult::Assign(result))
     }
 
-    fn fneg(&mut self, _i: &instruction::FNeg) -> Result<InstructionResult> {
-        Err(LLVMExecutorError::UnsupportedInstruction("fneg".to_owned()))
+    fn fneg(&mut self, i: &instruction::FNeg) -> Result<InstructionResult> {
+        debug!("{i}");
+        let format = fp_format(&i.to_type())?;
+        let ctx = self.state.ctx;
+        let f = |value: DExpr, _| fp::neg(ctx, format, &value);
+        let result = convert_to_map(&mut self.state, i.value(), &i.to_type(), f)?;
+        Ok(InstructionResult::Assign(result))
     }
 
-    fn extract_element(&mut self, _i: &instruction::ExtractElement) -> Result<InstructionResult> {
-        todo!()
+    fn extract_element(&mut self, i: &instruction::ExtractElement) -> Result<InstructionResult> {
+        debug!("{i}");
+        let result = extract_element(&mut self.state, &i.aggregate(), &i.index())?;
+        Ok(InstructionResult::Assign(result))
     }
 
-    fn insert_element(&mut self, _i: &instruction::InsertElement) -> Result<InstructionResult> {
-        todo!()
+    fn insert_element(&mut self, i: &instruction::InsertElement) -> Result<InstructionResult> {
+        debug!("{i}");
+        let result = insert_element(&mut self.state, &i.aggregate(), &i.element(), &i.index())?;
+        Ok(InstructionResult::Assign(result))
     }
 
-    fn shuffle_vector(&mut self, _i: &instruction::ShuffleVector) -> Result<InstructionResult> {
-        todo!()
+    fn shuffle_vector(&mut self, i: &instruction::ShuffleVector) -> Result<InstructionResult> {
+        debug!("{i}");
+        let mask = i.mask().ok_or_else(|| {
+            LLVMExecutorError::UnsupportedInstruction(
+                "shufflevector on scalable vectors".to_owned(),
+            )
+        })?;
+        let result = shuffle_vector(&mut self.state, &i.lhs(), &i.rhs(), &mask)?;
+        Ok(InstructionResult::Assign(result))
     }
 
     fn extract_value(&mut self, i: &instruction::ExtractValue) -> Result<InstructionResult> {
@@ -741,28 +979,40 @@ impl<'vm> LLVMExecutor<'vm> {
         Ok(InstructionResult::Assign(result))
     }
 
-    fn fp_trunc(&mut self, _i: &instruction::FPTrunc) -> Result<InstructionResult> {
-        todo!()
+    fn fp_trunc(&mut self, i: &instruction::FPTrunc) -> Result<InstructionResult> {
+        debug!("{i}");
+        let result = fp_cast(&mut self.state, i.value(), &i.to_type(), false)?;
+        Ok(InstructionResult::Assign(result))
     }
 
-    fn fp_ext(&mut self, _i: &instruction::FPExt) -> Result<InstructionResult> {
-        todo!()
+    fn fp_ext(&mut self, i: &instruction::FPExt) -> Result<InstructionResult> {
+        debug!("{i}");
+        let result = fp_cast(&mut self.state, i.value(), &i.to_type(), false)?;
+        Ok(InstructionResult::Assign(result))
     }
 
-    fn fp_to_ui(&mut self, _i: &instruction::FPToUI) -> Result<InstructionResult> {
-        todo!()
+    fn fp_to_ui(&mut self, i: &instruction::FPToUI) -> Result<InstructionResult> {
+        debug!("{i}");
+        let result = fp_cast(&mut self.state, i.value(), &i.to_type(), false)?;
+        Ok(InstructionResult::Assign(result))
     }
 
-    fn fp_to_si(&mut self, _i: &instruction::FPToSI) -> Result<InstructionResult> {
-        todo!()
+    fn fp_to_si(&mut self, i: &instruction::FPToSI) -> Result<InstructionResult> {
+        debug!("{i}");
+        let result = fp_cast(&mut self.state, i.value(), &i.to_type(), true)?;
+        Ok(InstructionResult::Assign(result))
     }
 
-    fn ui_to_fp(&mut self, _i: &instruction::UIToFP) -> Result<InstructionResult> {
-        todo!()
+    fn ui_to_fp(&mut self, i: &instruction::UIToFP) -> Result<InstructionResult> {
+        debug!("{i}");
+        let result = fp_cast(&mut self.state, i.value(), &i.to_type(), false)?;
+        Ok(InstructionResult::Assign(result))
     }
 
-    fn si_to_fp(&mut self, _i: &instruction::SIToFP) -> Result<InstructionResult> {
-        todo!()
+    fn si_to_fp(&mut self, i: &instruction::SIToFP) -> Result<InstructionResult> {
+        debug!("{i}");
+        let result = fp_cast(&mut self.state, i.value(), &i.to_type(), true)?;
+        Ok(InstructionResult::Assign(result))
     }
 
     fn ptr_to_int(&mut self, i: &instruction::PtrToInt) -> Result<InstructionResult> {
@@ -809,8 +1059,13 @@ impl<'vm> LLVMExecutor<'vm> {
         Ok(InstructionResult::Assign(result))
     }
 
-    fn fcmp(&mut self, _i: &instruction::FCmp) -> Result<InstructionResult> {
-        todo!()
+    fn fcmp(&mut self, i: &instruction::FCmp) -> Result<InstructionResult> {
+        debug!("{i}");
+        let format = fp_format(&i.lhs().ty())?;
+        let ctx = self.state.ctx;
+        let f = |lhs: &DExpr, rhs: &DExpr| fp_compare(ctx, format, i.predicate(), lhs, rhs);
+        let result = binop(&mut self.state, &i.lhs(), &i.rhs(), f)?;
+        Ok(InstructionResult::Assign(result))
     }
 
     fn phi(&mut self, i: &instruction::Phi) -> Result<InstructionResult> {
@@ -876,8 +1131,13 @@ impl<'vm> LLVMExecutor<'vm> {
         Ok(InstructionResult::Assign(result))
     }
 
-    fn freeze(&mut self, _i: &instruction::Freeze) -> Result<InstructionResult> {
-        todo!()
+    fn freeze(&mut self, i: &instruction::Freeze) -> Result<InstructionResult> {
+        debug!("{i}");
+        // Undefined values become a new unconstrained symbol on each use. Assigning the
+        // symbol to the result makes every use of the result see the same
+        // value, which is all freeze requires.
+        let value = self.state.get_expr(&i.value())?;
+        Ok(InstructionResult::Assign(value))
     }
 
     fn call(&mut self, i: &instruction::Call) -> Result<InstructionResult> {
@@ -893,16 +1153,30 @@ impl<'vm> LLVMExecutor<'vm> {
         todo!()
     }
 
-    fn landing_pad(&mut self, _i: &instruction::LandingPad) -> Result<InstructionResult> {
-        todo!()
+    fn landing_pad(&mut self, i: &instruction::LandingPad) -> Result<InstructionResult> {
+        debug!("{i}");
+        // Exception types are not modelled, so every landing pad is entered. The result
+        // is the exception being unwound, or zero for a panic raised by a hook.
+        let exception = match self.state.exception.take() {
+            Some(exception) => exception,
+            None => {
+                let size = bit_size(&i.result_type(), self.project.ptr_size)?;
+                self.state.ctx.zero(size)
+            }
+        };
+        Ok(InstructionResult::Assign(exception))
     }
 
-    fn catch_pad(&mut self, _i: &instruction::CatchPad) -> Result<InstructionResult> {
-        todo!()
+    fn catch_pad(&mut self, i: &instruction::CatchPad) -> Result<InstructionResult> {
+        debug!("{i}");
+        // The pad only produces a token, which is never read.
+        Ok(InstructionResult::Continue)
     }
 
-    fn cleanup_pad(&mut self, _i: &instruction::CleanupPad) -> Result<InstructionResult> {
-        todo!()
+    fn cleanup_pad(&mut self, i: &instruction::CleanupPad) -> Result<InstructionResult> {
+        debug!("{i}");
+        // The pad only produces a token, which is never read.
+        Ok(InstructionResult::Continue)
     }
 
     fn ret(&mut self, i: &instruction::Ret) -> Result<InstructionResult> {
@@ -1003,12 +1277,21 @@ impl<'vm> LLVMExecutor<'vm> {
         todo!("indirect_br")
     }
 
-    fn invoke(&mut self, _i: &instruction::Invoke) -> Result<InstructionResult> {
-        todo!()
+    fn invoke(&mut self, i: &instruction::Invoke) -> Result<InstructionResult> {
+        debug!("{i}");
+        // Calls the function like `call`, the successor is picked when the call returns
+        // or unwinds.
+        let call_fn = CallFn {
+            function: i.called_value(),
+            arguments: i.arguments(),
+        };
+        Ok(InstructionResult::CallFn(call_fn))
     }
 
-    fn resume(&mut self, _i: &instruction::Resume) -> Result<InstructionResult> {
-        todo!()
+    fn resume(&mut self, i: &instruction::Resume) -> Result<InstructionResult> {
+        debug!("{i}");
+        let exception = self.state.get_expr(&i.exception())?;
+        Ok(InstructionResult::Unwind(Some(exception)))
     }
 
     fn unreachable(&mut self, _i: &instruction::Unreachable) -> Result<InstructionResult> {
@@ -1016,16 +1299,30 @@ impl<'vm> LLVMExecutor<'vm> {
         Ok(InstructionResult::AnalysisError(AnalysisError::Unreachable))
     }
 
-    fn cleanup_ret(&mut self, _i: &instruction::CleanupRet) -> Result<InstructionResult> {
-        todo!()
+    fn cleanup_ret(&mut self, i: &instruction::CleanupRet) -> Result<InstructionResult> {
+        debug!("{i}");
+        match i.unwind_destination() {
+            Some(target) => Ok(InstructionResult::Branch(target)),
+            None => Ok(InstructionResult::Unwind(None)),
+        }
     }
 
-    fn catch_ret(&mut self, _i: &instruction::CatchRet) -> Result<InstructionResult> {
-        todo!()
+    fn catch_ret(&mut self, i: &instruction::CatchRet) -> Result<InstructionResult> {
+        debug!("{i}");
+        Ok(InstructionResult::Branch(i.successor()))
     }
 
-    fn catch_switch(&mut self, _i: &instruction::CatchSwitch) -> Result<InstructionResult> {
-        todo!()
+    fn catch_switch(&mut self, i: &instruction::CatchSwitch) -> Result<InstructionResult> {
+        debug!("{i}");
+        // Same as for landing pads, exception types are not modelled so the first
+        // handler always catches the exception.
+        match i.handlers().into_iter().next() {
+            Some(handler) => Ok(InstructionResult::Branch(handler)),
+            None => match i.unwind_destination() {
+                Some(target) => Ok(InstructionResult::Branch(target)),
+                None => Ok(InstructionResult::Unwind(None)),
+            },
+        }
     }
 
     fn call_br(&mut self, _i: &instruction::CallBr) -> Result<InstructionResult> {
@@ -1035,7 +1332,7 @@ impl<'vm> LLVMExecutor<'vm> {
 
 /// Perform a binary operation on two operands, returning the result.
 ///
-/// The input types must be either integers or a vector of integers. Vector
+/// The input types must be either integers, floats or a vector of them. Vector
 /// operations are performed on a per element basis.
 ///
 /// TODO: No operations currently care about overflows and such.
@@ -1058,6 +1355,9 @@ where
         // Some may support pointer operands, such as icmp, which work the same as integer ones.
         (Type::Pointer(_), Type::Pointer(_)) => Ok(operation(&lhs, &rhs)),
 
+        // Floating point operations work on the IEEE-754 representation.
+        (Type::Float(_), Type::Float(_)) => Ok(operation(&lhs, &rhs)),
+
         // The docs do not really specify how the vector operations should work. But I'll assume it
         // is the operation on a per element basis.
         (Type::Vector(t0), Type::Vector(t1)) if !(t0.is_scalable() || t1.is_scalable()) => {
@@ -1091,6 +1391,10 @@ where
     }
 }
 
+/// Calculates the size of the type in bits.
+///
+/// Scalable vectors are sized with `vscale` set to one, which is what
+/// `llvm.vscale` returns.
 pub(crate) fn bit_size(ty: &Type, ptr_size: u32) -> Result<u32> {
     match ty {
         Type::Void => Ok(0),
@@ -1104,16 +1408,140 @@ pub(crate) fn bit_size(ty: &Type, ptr_size: u32) -> Result<u32> {
             bit_size(&t.element_type(), ptr_size).map(|size| size * t.num_elements() as u32)
         }
         Type::Structure(t) => t.fields().into_iter().map(|f| bit_size(&f, ptr_size)).sum(),
-        Type::OpaqueStructure => todo!("Cannot get size of opaque structure"),
+        Type::X86Mmx => Ok(64),
+        Type::X86Amx => Ok(8192),
 
-        // TODO: How are these sized?
-        Type::Function(_)
-        | Type::Label
-        | Type::Token
-        | Type::Metadata
-        | Type::X86Amx
-        | Type::X86Mmx
-        | Type::TargetExtension(_) => todo!("Getting size for {ty:?} is unsupported"),
+        // Opaque structures have no body, and the layout of target extension types is not
+        // available through the C API.
+        Type::OpaqueStructure | Type::TargetExtension(_) => Err(LLVMExecutorError::NoSize),
+
+        // These are not first class types and cannot be stored in memory.
+        Type::Function(_) | Type::Label | Type::Token | Type::Metadata => {
+            Err(LLVMExecutorError::NoSize)
+        }
+    }
+}
+
+/// Get the type information used when presenting values of `ty`.
+pub(crate) fn expression_type(ty: &Type, ptr_size: u32) -> ExpressionType {
+    match ty {
+        Type::Integer(t) => ExpressionType::Integer(t.bits() as usize),
+        Type::Pointer(_) => ExpressionType::Integer(ptr_size as usize),
+        Type::Float(_) => match fp_format(ty) {
+            Ok(format) => ExpressionType::Float(format),
+            Err(_) => ExpressionType::Unknown,
+        },
+        Type::Vector(t) if !t.is_scalable() => ExpressionType::Array(
+            Box::new(expression_type(&t.element_type(), ptr_size)),
+            t.num_elements() as usize,
+        ),
+        Type::Array(t) => ExpressionType::Array(
+            Box::new(expression_type(&t.element_type(), ptr_size)),
+            t.num_elements() as usize,
+        ),
+        Type::Structure(t) => ExpressionType::Struct(
+            t.fields()
+                .iter()
+                .map(|field| expression_type(field, ptr_size))
+                .collect(),
+        ),
+        _ => ExpressionType::Unknown,
+    }
+}
+
+/// Perform a floating point operation on two operands, see [`binop`].
+pub(crate) fn fp_binop<F>(
+    state: &mut LLVMState,
+    op0: &Value,
+    op1: &Value,
+    operation: F,
+) -> Result<DExpr>
+where
+    F: Fn(&DContext, FpFormat, &DExpr, &DExpr) -> DExpr,
+{
+    let format = fp_format(&op0.ty())?;
+    let ctx = state.ctx;
+    binop(state, op0, op1, |lhs, rhs| operation(ctx, format, lhs, rhs))
+}
+
+/// Compare two floating point values with the predicate of an `fcmp`.
+pub(crate) fn fp_compare(
+    ctx: &DContext,
+    format: FpFormat,
+    predicate: LLVMRealPredicate,
+    lhs: &DExpr,
+    rhs: &DExpr,
+) -> DExpr {
+    let unordered = || fp::is_nan(ctx, format, lhs).or(&fp::is_nan(ctx, format, rhs));
+    match predicate {
+        LLVMRealPredicate::LLVMRealPredicateFalse => ctx.from_bool(false),
+        LLVMRealPredicate::LLVMRealOEQ => fp::eq(ctx, format, lhs, rhs),
+        LLVMRealPredicate::LLVMRealOGT => fp::lt(ctx, format, rhs, lhs),
+        LLVMRealPredicate::LLVMRealOGE => fp::le(ctx, format, rhs, lhs),
+        LLVMRealPredicate::LLVMRealOLT => fp::lt(ctx, format, lhs, rhs),
+        LLVMRealPredicate::LLVMRealOLE => fp::le(ctx, format, lhs, rhs),
+        LLVMRealPredicate::LLVMRealONE => unordered().or(&fp::eq(ctx, format, lhs, rhs)).not(),
+        LLVMRealPredicate::LLVMRealORD => unordered().not(),
+        LLVMRealPredicate::LLVMRealUNO => unordered(),
+        LLVMRealPredicate::LLVMRealUEQ => unordered().or(&fp::eq(ctx, format, lhs, rhs)),
+        LLVMRealPredicate::LLVMRealUGT => unordered().or(&fp::lt(ctx, format, rhs, lhs)),
+        LLVMRealPredicate::LLVMRealUGE => unordered().or(&fp::le(ctx, format, rhs, lhs)),
+        LLVMRealPredicate::LLVMRealULT => unordered().or(&fp::lt(ctx, format, lhs, rhs)),
+        LLVMRealPredicate::LLVMRealULE => unordered().or(&fp::le(ctx, format, lhs, rhs)),
+        LLVMRealPredicate::LLVMRealUNE => fp::eq(ctx, format, lhs, rhs).not(),
+        LLVMRealPredicate::LLVMRealPredicateTrue => ctx.from_bool(true),
+    }
+}
+
+/// Converts between floating point formats, or between floating point and
+/// integer values, for the fp casts.
+///
+/// `signed` selects if the integer side of the conversion is signed.
+pub(crate) fn fp_cast(
+    state: &mut LLVMState,
+    value: Value,
+    to_type: &Type,
+    signed: bool,
+) -> Result<DExpr> {
+    let is_float = |ty: &Type| match ty {
+        Type::Vector(t) => matches!(t.element_type(), Type::Float(_)),
+        ty => matches!(ty, Type::Float(_)),
+    };
+    let from = value.ty();
+    let from = is_float(&from).then(|| fp_format(&from)).transpose()?;
+    let to = is_float(to_type).then(|| fp_format(to_type)).transpose()?;
+
+    let ctx = state.ctx;
+    let f = |value: DExpr, target_size: u32| match (from, to, signed) {
+        (Some(from), Some(to), _) => fp::convert(ctx, from, to, &value),
+        (Some(from), None, false) => fp::to_unsigned(ctx, from, &value, target_size),
+        (Some(from), None, true) => fp::to_signed(ctx, from, &value, target_size),
+        (None, Some(to), false) => fp::from_unsigned(ctx, to, &value),
+        (None, Some(to), true) => fp::from_signed(ctx, to, &value),
+        (None, None, _) => value,
+    };
+    match (from, to) {
+        (None, None) => Err(LLVMExecutorError::MalformedInstruction),
+        _ => convert_to_map(state, value, to_type, f),
+    }
+}
+
+/// Get the floating point format of a float, or of the elements of a vector of
+/// floats.
+pub(crate) fn fp_format(ty: &Type) -> Result<FpFormat> {
+    match ty {
+        Type::Float(t) => match t {
+            FloatingPointType::Half => Ok(FpFormat::HALF),
+            FloatingPointType::BFloat => Ok(FpFormat::BFLOAT),
+            FloatingPointType::Float => Ok(FpFormat::SINGLE),
+            FloatingPointType::Double => Ok(FpFormat::DOUBLE),
+            FloatingPointType::Fp128 => Ok(FpFormat::QUAD),
+            FloatingPointType::X86Fp80 | FloatingPointType::PpcFp128 => Err(
+                LLVMExecutorError::UnsupportedInstruction(format!("floating point type {t:?}")),
+            ),
+        },
+        Type::Vector(t) => fp_format(&t.element_type()),
+        _ => Err(LLVMExecutorError::MalformedInstruction),
     }
 }
 
@@ -1156,18 +1584,15 @@ pub(crate) fn get_bit_offset_concrete(
     let ptr_size = project.ptr_size;
 
     match ty {
-        // Can this work with opaque pointers? Don't think so.
-        Type::Pointer(_) => todo!(),
-
         Type::Vector(t) => {
             let element_type = t.element_type();
-            let element_size = bit_size(&element_type, ptr_size).unwrap();
+            let element_size = bit_size(&element_type, ptr_size)?;
             Ok((element_size * index, element_type))
         }
 
         Type::Array(t) => {
             let element_type = t.element_type();
-            let element_size = bit_size(&element_type, ptr_size).unwrap();
+            let element_size = bit_size(&element_type, ptr_size)?;
             Ok((element_size * index, element_type))
         }
 
@@ -1188,8 +1613,10 @@ pub(crate) fn get_bit_offset_concrete(
         }
         Type::OpaqueStructure => Err(LLVMExecutorError::NoSize),
 
-        // We cannot index into these types.
-        Type::X86Amx
+        // We cannot index into these types. Pointers are not aggregates, the first index of a
+        // `getelementptr` is handled by the caller.
+        Type::Pointer(_)
+        | Type::X86Amx
         | Type::X86Mmx
         | Type::Void
         | Type::Function(_)
@@ -1198,7 +1625,7 @@ pub(crate) fn get_bit_offset_concrete(
         | Type::Label
         | Type::Token
         | Type::Metadata
-        | Type::TargetExtension(_) => panic!("Cannot index into type: {ty:?}"),
+        | Type::TargetExtension(_) => Err(LLVMExecutorError::MalformedInstruction),
     }
 }
 
@@ -1213,8 +1640,6 @@ pub(crate) fn byte_offset(
     ctx: &DContext,
 ) -> Result<(DExpr, Type)> {
     match ty {
-        Type::Pointer(_) => todo!(),
-
         Type::Vector(t) => {
             let element_type = t.element_type();
             let element_size = byte_size(&element_type, ptr_size)?;
@@ -1257,24 +1682,27 @@ pub(crate) fn byte_offset(
         }
         Type::OpaqueStructure => Err(LLVMExecutorError::NoSize),
 
-        // We cannot index into these types.
-        Type::Void => todo!(),
-        Type::Integer(_) => todo!(),
-        Type::Float(_) => todo!(),
-        Type::Function(_) => todo!(),
-        Type::Label => todo!(),
-        Type::Token => todo!(),
-        Type::Metadata => todo!(),
-        Type::X86Amx => todo!(),
-        Type::X86Mmx => todo!(),
-        Type::TargetExtension(_) => todo!(),
+        // We cannot index into these types. Pointers are not aggregates, the first index of a
+        // `getelementptr` is handled by the caller.
+        Type::Pointer(_)
+        | Type::Void
+        | Type::Integer(_)
+        | Type::Float(_)
+        | Type::Function(_)
+        | Type::Label
+        | Type::Token
+        | Type::Metadata
+        | Type::X86Amx
+        | Type::X86Mmx
+        | Type::TargetExtension(_) => Err(LLVMExecutorError::MalformedInstruction),
     }
 }
 
-/// Converts integers, pointers, or vectors.
+/// Converts integers, pointers, floats, or vectors.
 ///
-/// Performs a conversion on either (int,int), (ptr,int), (int,ptr), or
-/// (vector,vector) with the passed mapping function.
+/// Performs a conversion on either (int,int), (ptr,int), (int,ptr), (float,
+/// float), (float,int), (int,float), or (vector,vector) with the passed mapping
+/// function.
 ///
 /// No type checking is done, if this is of interest they have to be checked
 /// before calling this function.
@@ -1292,10 +1720,14 @@ where
 
     match (value_type, to_type) {
         // Integer to integer conversion are done by trunc, zext, and sext. While the ptr->int,
-        // int->ptr are done by ptrtoint and inttoptr. All these should be supported.
+        // int->ptr are done by ptrtoint and inttoptr, and the floating point conversions by the
+        // fp casts. All these should be supported.
         (Type::Integer(_), Type::Integer(_))
         | (Type::Integer(_), Type::Pointer(_))
-        | (Type::Pointer(_), Type::Integer(_)) => {
+        | (Type::Pointer(_), Type::Integer(_))
+        | (Type::Float(_), Type::Float(_))
+        | (Type::Float(_), Type::Integer(_))
+        | (Type::Integer(_), Type::Float(_)) => {
             let target_bits = bit_size(to_type, state.project.ptr_size)?;
             Ok(map(value, target_bits))
         }
@@ -1327,48 +1759,172 @@ where
     }
 }
 
+/// Get the element type and the number of elements of a fixed length vector.
+fn vector_type(ty: &Type) -> Result<(Type, u32)> {
+    match ty {
+        Type::Vector(t) if t.is_scalable() => Err(LLVMExecutorError::UnsupportedInstruction(
+            "scalable vectors".to_owned(),
+        )),
+        Type::Vector(t) => Ok((t.element_type(), t.num_elements())),
+        _ => Err(LLVMExecutorError::MalformedInstruction),
+    }
+}
+
+/// Get an element of a vector.
+///
+/// A symbolic index selects between all the elements. An index out of bounds
+/// gives the last element, LLVM makes the result poison in that case.
+pub(crate) fn extract_element(
+    state: &mut LLVMState,
+    vector: &Value,
+    index: &Value,
+) -> Result<DExpr> {
+    let (element_type, num_elements) = vector_type(&vector.ty())?;
+    let bits = bit_size(&element_type, state.project.ptr_size)?;
+    let vector = state.get_expr(vector)?;
+    let index = state.get_expr(index)?.simplify();
+    let element = |i: u32| vector.slice(i * bits, (i + 1) * bits - 1);
+
+    if let Some(i) = index.get_constant() {
+        return Ok(element((i as u32).min(num_elements - 1)));
+    }
+
+    let result = (0..num_elements - 1)
+        .rev()
+        .fold(element(num_elements - 1), |acc, i| {
+            let is_index = index._eq(&state.ctx.from_u64(i.into(), index.len()));
+            is_index.ite(&element(i), &acc)
+        });
+    Ok(result)
+}
+
+/// Replace an element of a vector.
+///
+/// A symbolic index may replace any of the elements. An index out of bounds
+/// leaves the vector unchanged, LLVM makes the result poison in that case.
+pub(crate) fn insert_element(
+    state: &mut LLVMState,
+    vector: &Value,
+    element: &Value,
+    index: &Value,
+) -> Result<DExpr> {
+    let (element_type, num_elements) = vector_type(&vector.ty())?;
+    let bits = bit_size(&element_type, state.project.ptr_size)?;
+    let vector = state.get_expr(vector)?;
+    let element = state.get_expr(element)?;
+    let index = state.get_expr(index)?.simplify();
+    assert_eq!(element.len(), bits);
+
+    if let Some(i) = index.get_constant() {
+        return Ok(match i < num_elements.into() {
+            true => vector.replace_part(i as u32 * bits, element),
+            false => vector,
+        });
+    }
+
+    (0..num_elements)
+        .map(|i| {
+            let is_index = index._eq(&state.ctx.from_u64(i.into(), index.len()));
+            is_index.ite(&element, &vector.slice(i * bits, (i + 1) * bits - 1))
+        })
+        .reduce(|acc, v| v.concat(&acc))
+        .ok_or(LLVMExecutorError::MalformedInstruction)
+}
+
+/// Create a vector from the elements of two vectors.
+///
+/// Each element of `mask` picks an element of the concatenation of `lhs` and
+/// `rhs`, an undefined mask element gives an undefined element.
+pub(crate) fn shuffle_vector(
+    state: &mut LLVMState,
+    lhs: &Value,
+    rhs: &Value,
+    mask: &[Option<u32>],
+) -> Result<DExpr> {
+    let (element_type, num_elements) = vector_type(&lhs.ty())?;
+    let bits = bit_size(&element_type, state.project.ptr_size)?;
+    let lhs = state.get_expr(lhs)?;
+    let rhs = state.get_expr(rhs)?;
+
+    mask.iter()
+        .map(|index| match index {
+            Some(i) if *i < num_elements => lhs.slice(i * bits, (i + 1) * bits - 1),
+            Some(i) => {
+                let i = i - num_elements;
+                rhs.slice(i * bits, (i + 1) * bits - 1)
+            }
+            None => {
+                let name = format!("undef_{}", rand::random::<u32>());
+                state.ctx.unconstrained(bits, &name)
+            }
+        })
+        .reduce(|acc, v| v.concat(&acc))
+        .ok_or(LLVMExecutorError::MalformedInstruction)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::{
+        run_llvm::create_error_reason,
+        util::ErrorReason,
+        vm::{test, Config, MemoryModel},
+    };
 
     fn run(fn_name: &str) -> Vec<Option<i64>> {
-        // let subscriber = tracing_subscriber::FmtSubscriber::builder()
-        //     .with_max_level(tracing::Level::TRACE)
-        //     .finish();
-        // tracing::subscriber::set_global_default(subscriber)
-        //     .expect("setting default subscriber failed");
-
-        let path = format!("tests/unit_tests/instructions.bc");
-        let project = Box::new(Project::from_path(&path).expect("Failed to created project"));
-        let project = Box::leak(project);
-
-        let context = Box::new(DContext::new());
-        let context = Box::leak(context);
-        let mut vm = VM::new(project, context, fn_name).expect("Failed to create VM");
-
-        let mut path_results = Vec::new();
-        while let Some((path_result, state)) = vm.run().expect("Failed to run path") {
-            let result = match path_result {
-                PathResult::Success(Some(value)) => {
-                    let value = state
-                        .constraints
-                        .get_value(&value)
-                        .expect("Failed to get concrete value");
-                    let binary_str = value.to_binary_string();
-                    Some(u128::from_str_radix(&binary_str, 2).unwrap() as i64)
-                }
-                PathResult::Success(None) => None,
-                PathResult::Failure(error) => {
-                    panic!("Did not expect any paths to fail, reason: {error:?}")
-                }
-                PathResult::Suppress => panic!("Did not expect any paths to be suppressed"),
-                PathResult::AssumptionUnsat => panic!("Did not expect any paths to be unsat"),
-            };
-            path_results.push(result);
-        }
+        run_with_config(fn_name, Config::new())
+    }
 
-        eprintln!("{path_results:x?}");
-        path_results
+    fn vm(fn_name: &str, cfg: Config) -> VM {
+        test::vm(project(), fn_name, cfg)
+    }
+
+    fn project() -> Project {
+        Project::from_path("tests/unit_tests/instructions.bc").expect("Failed to created project")
+    }
+
+    /// Runs `fn_name` which is expected to have a single path that fails,
+    /// returns the error and the error reason that is reported for it.
+    fn run_failure(fn_name: &str) -> (AnalysisError, ErrorReason) {
+        let mut vm = vm(fn_name, Config::new());
+        let (path_result, mut state) = vm
+            .run()
+            .expect("Failed to run path")
+            .expect("Expected a path");
+        assert!(vm.run().expect("Failed to run path").is_none());
+
+        let PathResult::Failure(error) = path_result else {
+            panic!("Expected the path to fail");
+        };
+        let reason = create_error_reason(&mut state, error.clone());
+        (error, reason)
+    }
+
+    /// Runs `fn_name` which is expected to fail with a memory error, returns
+    /// the error, where the object was allocated and where the error happened.
+    fn run_memory_error(fn_name: &str) -> (MemoryError, Option<String>, Option<String>) {
+        let (error, reason) = run_failure(fn_name);
+        let AnalysisError::Memory {
+            error,
+            allocation_site,
+        } = error
+        else {
+            panic!("Expected a memory error, got {error:?}");
+        };
+        (error, allocation_site, reason.error_location)
+    }
+
+    /// Checks that `location` is line `line` in `instructions.rs`.
+    fn assert_line(location: Option<String>, line: u32) {
+        let location = location.expect("Expected a location");
+        assert!(
+            location.ends_with(&format!("instructions.rs:{line}")),
+            "{location} is not line {line}"
+        );
+    }
+
+    fn run_with_config(fn_name: &str, cfg: Config) -> Vec<Option<i64>> {
+        test::run(project(), fn_name, cfg)
     }
 
     #[test]
@@ -1442,6 +1998,62 @@ mod tests {
         assert_eq!(res[0], Some(-3));
     }
 
+    #[test]
+    fn test_fneg() {
+        let res = run("test_fneg");
+        assert_eq!(res.len(), 1);
+        assert_eq!(res[0], Some(0xBFC0_0000));
+    }
+
+    #[test]
+    fn test_fadd() {
+        let res = run("test_fadd");
+        assert_eq!(res.len(), 1);
+        assert_eq!(res[0], Some(0x3FD3_3333_3333_3334));
+    }
+
+    #[test]
+    fn test_fadd_vector() {
+        let res = run("test_fadd_vector");
+        assert_eq!(res.len(), 1);
+        assert_eq!(res[0], Some(0x4010_0000_3FC0_0000));
+    }
+
+    #[test]
+    fn test_fsub() {
+        let res = run("test_fsub");
+        assert_eq!(res.len(), 1);
+        assert_eq!(res[0], Some(0xC030_0000));
+    }
+
+    #[test]
+    fn test_fmul() {
+        let res = run("test_fmul");
+        assert_eq!(res.len(), 1);
+        assert_eq!(res[0], Some(0xC018_0000_0000_0000_u64 as i64));
+    }
+
+    #[test]
+    fn test_fdiv() {
+        let res = run("test_fdiv");
+        assert_eq!(res.len(), 1);
+        assert_eq!(res[0], Some(0x3EAA_AAAB));
+    }
+
+    #[test]
+    fn test_fdiv_zero() {
+        let res = run("test_fdiv_zero");
+        assert_eq!(res.len(), 1);
+        assert_eq!(res[0], Some(0x7FF0_0000_0000_0000));
+    }
+
+    #[test]
+    fn test_frem() {
+        let res = run("test_frem");
+        assert_eq!(res.len(), 1);
+        assert_eq!(res[0], Some(0x3FF8_0000_0000_0000));
+    }
+
     #[test]
     fn test_and() {
         let res = run("test_and");
@@ -1491,6 +2103,48 @@ mod tests {
         assert_eq!(res[0], Some(221));
     }
 
+    #[test]
+    fn test_extract_element() {
+        let res = run("test_extract_element");
+        assert_eq!(res.len(), 1);
+        assert_eq!(res[0], Some(3));
+    }
+
+    #[test]
+    fn test_extract_element_symbolic() {
+        let res = run("test_extract_element_symbolic");
+        assert_eq!(res.len(), 1);
+        assert_eq!(res[0], Some(7));
+    }
+
+    #[test]
+    fn test_insert_element() {
+        let res = run("test_insert_element");
+        assert_eq!(res.len(), 1);
+        assert_eq!(res[0], Some(0x04030901));
+    }
+
+    #[test]
+    fn test_insert_element_symbolic() {
+        let res = run("test_insert_element_symbolic");
+        assert_eq!(res.len(), 1);
+        assert_eq!(res[0], Some(0x0505));
+    }
+
+    #[test]
+    fn test_shuffle_vector() {
+        let res = run("test_shuffle_vector");
+        assert_eq!(res.len(), 1);
+        assert_eq!(res[0], Some(0x05030801));
+    }
+
+    #[test]
+    fn test_shuffle_vector_splat() {
+        let res = run("test_shuffle_vector_splat");
+        assert_eq!(res.len(), 1);
+        assert_eq!(res[0], Some(0x1234123412341234));
+    }
+
     #[test]
     fn test_extract_value_arr1() {
         let res = run("test_extract_value_arr1");
@@ -1680,6 +2334,75 @@ mod tests {
         assert_eq!(res[0], Some(2));
     }
 
+    #[test]
+    fn test_alloca_symbolic() {
+        let res = run("test_alloca_symbolic");
+        assert_eq!(res.len(), 3);
+        assert!(res.contains(&Some(1)));
+        assert!(res.contains(&Some(2)));
+        assert!(res.contains(&Some(3)));
+    }
+
+    #[test]
+    fn test_use_after_return() {
+        let (error, allocation_site, location) = run_memory_error("test_use_after_return");
+        assert!(matches!(error, MemoryError::UseAfterFree(_)));
+        // Without debug information the site is the function of the alloca.
+        assert_eq!(allocation_site.as_deref(), Some("local_address"));
+        assert_eq!(location, None);
+    }
+
+    #[test]
+    fn test_heap() {
+        let res = run("test_heap");
+        assert_eq!(res.len(), 1);
+        assert_eq!(res[0], Some(5));
+    }
+
+    #[test]
+    fn test_heap_use_after_free() {
+        let (error, allocation_site, location) = run_memory_error("test_heap_use_after_free");
+        assert!(matches!(error, MemoryError::UseAfterFree(_)));
+        assert_line(allocation_site, 11);
+        assert_line(location, 14);
+    }
+
+    #[test]
+    fn test_heap_use_after_free_array_memory() {
+        // Array memory does not keep track of objects, so this is not detected.
+        let cfg = Config {
+            memory_model: MemoryModel::Array,
+            ..Config::new()
+        };
+        let res = run_with_config("test_heap_use_after_free", cfg);
+        assert_eq!(res.len(), 1);
+        assert_eq!(res[0], Some(5));
+    }
+
+    #[test]
+    fn test_heap_double_free() {
+        let (error, allocation_site, location) = run_memory_error("test_heap_double_free");
+        assert!(matches!(error, MemoryError::DoubleFree(_)));
+        assert_line(allocation_site, 21);
+        assert_line(location, 23);
+    }
+
+    #[test]
+    fn test_heap_mismatched_dealloc() {
+        let (error, allocation_site, location) = run_memory_error("test_heap_mismatched_dealloc");
+        assert!(matches!(error, MemoryError::MismatchedDealloc(_)));
+        assert_line(allocation_site, 31);
+        assert_line(location, 32);
+    }
+
+    #[test]
+    fn test_heap_out_of_bounds() {
+        let (error, allocation_site, location) = run_memory_error("test_heap_out_of_bounds");
+        assert!(matches!(error, MemoryError::ObjectOutOfBounds(_)));
+        assert_line(allocation_site, 41);
+        assert_line(location, 43);
+    }
+
     #[test]
     fn test_bitcast1() {
         let res = run("test_bitcast1");
@@ -1722,6 +2445,48 @@ mod tests {
         assert_eq!(res[0], Some(0xFFFF));
     }
 
+    #[test]
+    fn test_fptrunc() {
+        let res = run("test_fptrunc");
+        assert_eq!(res.len(), 1);
+        assert_eq!(res[0], Some(0x3DCC_CCCD));
+    }
+
+    #[test]
+    fn test_fpext() {
+        let res = run("test_fpext");
+        assert_eq!(res.len(), 1);
+        assert_eq!(res[0], Some(0xC006_0000_0000_0000_u64 as i64));
+    }
+
+    #[test]
+    fn test_fptoui() {
+        let res = run("test_fptoui");
+        assert_eq!(res.len(), 1);
+        assert_eq!(res[0], Some(300));
+    }
+
+    #[test]
+    fn test_fptosi() {
+        let res = run("test_fptosi");
+        assert_eq!(res.len(), 1);
+        assert_eq!(res[0], Some(-7));
+    }
+
+    #[test]
+    fn test_uitofp() {
+        let res = run("test_uitofp");
+        assert_eq!(res.len(), 1);
+        assert_eq!(res[0], Some(0x4F80_0000));
+    }
+
+    #[test]
+    fn test_sitofp() {
+        let res = run("test_sitofp");
+        assert_eq!(res.len(), 1);
+        assert_eq!(res[0], Some(0xC008_0000_0000_0000_u64 as i64));
+    }
+
     #[test]
     fn test_inttoptr_trunc() {
         let res = run("test_inttoptr_trunc");
@@ -1918,6 +2683,48 @@ mod tests {
         assert_eq!(res[0], Some(0x5));
     }
 
+    #[test]
+    fn test_fcmp_olt() {
+        let res = run("test_fcmp_olt");
+        assert_eq!(res.len(), 1);
+        assert_eq!(res[0], Some(1));
+    }
+
+    #[test]
+    fn test_fcmp_oeq_zero() {
+        let res = run("test_fcmp_oeq_zero");
+        assert_eq!(res.len(), 1);
+        assert_eq!(res[0], Some(1));
+    }
+
+    #[test]
+    fn test_fcmp_ogt_nan() {
+        let res = run("test_fcmp_ogt_nan");
+        assert_eq!(res.len(), 1);
+        assert_eq!(res[0], Some(0));
+    }
+
+    #[test]
+    fn test_fcmp_uno() {
+        let res = run("test_fcmp_uno");
+        assert_eq!(res.len(), 1);
+        assert_eq!(res[0], Some(1));
+    }
+
+    #[test]
+    fn test_fcmp_une_nan() {
+        let res = run("test_fcmp_une_nan");
+        assert_eq!(res.len(), 1);
+        assert_eq!(res[0], Some(1));
+    }
+
+    #[test]
+    fn test_freeze() {
+        let res = run("test_freeze");
+        assert_eq!(res.len(), 1);
+        assert_eq!(res[0], Some(10));
+    }
+
     #[test]
     fn test_phi1() {
         let res = run("test_phi1");
@@ -1967,6 +2774,79 @@ mod tests {
         assert_eq!(res[0], Some(0xabcd));
     }
 
+    #[test]
+    fn test_invoke() {
+        let res = run("test_invoke");
+        assert_eq!(res.len(), 1);
+        assert_eq!(res[0], Some(0xabcd));
+    }
+
+    #[test]
+    fn test_invoke_panic() {
+        let res = run("test_invoke_panic");
+        assert_eq!(res.len(), 1);
+        assert_eq!(res[0], Some(1));
+    }
+
+    #[test]
+    fn test_unwind_through_call() {
+        let res = run("test_unwind_through_call");
+        assert_eq!(res.len(), 1);
+        assert_eq!(res[0], Some(1));
+    }
+
+    #[test]
+    fn test_resume() {
+        let res = run("test_resume");
+        assert_eq!(res.len(), 1);
+        assert_eq!(res[0], Some(0x23));
+    }
+
+    #[test]
+    fn test_resume_out_of_entry() {
+        let (error, _) = run_failure("test_resume_out_of_entry");
+        assert_eq!(error, AnalysisError::Panic);
+    }
+
+    #[test]
+    fn test_entry_parameters() {
+        let res = run("test_entry_parameters");
+        assert_eq!(res.len(), 2);
+        assert!(res.contains(&Some(0)));
+        assert!(res.contains(&Some(1)));
+    }
+
+    #[test]
+    fn test_entry_pointer() {
+        let res = run("test_entry_pointer");
+        assert_eq!(res.len(), 2);
+        assert!(res.contains(&Some(0)));
+        assert!(res.contains(&Some(1)));
+    }
+
+    #[test]
+    fn test_entry_nested_pointer() {
+        let res = run("test_entry_nested_pointer");
+        assert_eq!(res.len(), 2);
+        assert!(res.contains(&Some(0)));
+        assert!(res.contains(&Some(1)));
+    }
+
+    #[test]
+    fn test_entry_pointer_callee() {
+        let res = run("test_entry_pointer_callee");
+        assert_eq!(res.len(), 2);
+        assert!(res.contains(&Some(0)));
+        assert!(res.contains(&Some(1)));
+    }
+
+    #[test]
+    fn test_entry_slice() {
+        let res = run("test_entry_slice");
+        assert_eq!(res.len(), 1);
+        assert_eq!(res[0], Some(1));
+    }
+
     #[test]
     fn test_vector_constant() {
         let res = run("test_vector_constant");
@@ -41,6 +41,7 @@ impl Hooks {
         // These are not mangled, so these can be called from e.g. C.
         hooks.add("symex_assume", assume);
         hooks.add("symex_symbolic", symbolic_no_type);
+        hooks.add("symex_suppress_path", ignore);
 
         hooks.add("__rust_alloc", rust_alloc);
         hooks.add("__rust_dealloc", rust_dealloc);
@@ -146,12 +147,10 @@ pub fn symbolic(
             // sometimes only want part of the memory object to be reset to
             // entirely symbolic.
             let addr = addr_expr.get_constant().expect("expected constant addr");
-            let obj = vm
-                .state
+            vm.state
                 .memory
-                .get_object(addr)
-                .expect("coult not find object");
-            obj.bit_size()
+                .object_size(addr)
+                .expect("coult not find object")
         };
 
         // let size = vm.project.bit_size(inner_ty.as_ref())?;
@@ -223,7 +222,8 @@ fn rust_alloc(vm: &mut LLVMExecutor<'_>, args: &[Value]) -> Result<PathResult, L
 
     let align = get_single_u64_from_op(vm, &args[1])?;
 
-    let addr = vm.state.memory.allocate(size_in_bits, align)?;
+    let site = vm.allocation_site();
+    let addr = vm.state.memory.allocate_at(size_in_bits, align, site)?;
     let addr = vm.state.ctx.from_u64(addr, vm.project.ptr_size);
 
     Ok(PathResult::Success(Some(addr)))
@@ -231,10 +231,17 @@ fn rust_alloc(vm: &mut LLVMExecutor<'_>, args: &[Value]) -> Result<PathResult, L
 
 // fn __rust_dealloc(ptr: *mut u8, size: usize, align: usize);
 fn rust_dealloc(
-    _vm: &mut LLVMExecutor<'_>,
-    _args: &[Value],
+    vm: &mut LLVMExecutor<'_>,
+    args: &[Value],
 ) -> Result<PathResult, LLVMExecutorError> {
-    // Currently, de-allocating is not supported.
+    assert_eq!(args.len(), 3);
+
+    let addr = get_single_u64_from_op(vm, &args[0])?;
+    let size_in_bytes = get_single_u64_from_op(vm, &args[1])?;
+    let size_in_bits = size_in_bytes * BITS_IN_BYTE as u64;
+    let align = get_single_u64_from_op(vm, &args[2])?;
+
+    vm.state.memory.deallocate(addr, size_in_bits, align)?;
     Ok(PathResult::Success(None))
 }
 
@@ -246,17 +253,32 @@ fn rust_realloc(
 ) -> Result<PathResult, LLVMExecutorError> {
     assert_eq!(args.len(), 4);
 
+    // A symbolic address is resolved to the allocations it can point to, with a
+    // path for each.
     let addr = vm.state.get_expr(&args[0])?;
-    let size = get_single_u64_from_op(vm, &args[1])?;
+    let upper_bound = vm.vm.cfg.max_intrinsic_concretizations;
+    let old_addr = vm.concretize(&addr, upper_bound, "address in __rust_realloc")?;
+    let addr = vm.state.ctx.from_u64(old_addr, vm.project.ptr_size);
+    let old_size_in_bytes = get_single_u64_from_op(vm, &args[1])?;
     let align = get_single_u64_from_op(vm, &args[2])?;
     let size_in_bytes = get_single_u64_from_op(vm, &args[3])?;
     let size_in_bits = size_in_bytes * BITS_IN_BYTE as u64;
 
-    let new_addr = vm.state.memory.allocate(size_in_bits, align)?;
+    let site = vm.allocation_site();
+    let new_addr = vm.state.memory.allocate_at(size_in_bits, align, site)?;
     let new_addr = vm.state.ctx.from_u64(new_addr, vm.project.ptr_size);
 
-    let old_data = vm.state.memory.read(&addr, size as u32)?;
-    vm.state.memory.write(&new_addr, old_data)?;
+    // Copy the part of the old data that fits in the new allocation.
+    let copy_bits = old_size_in_bytes.min(size_in_bytes) as u32 * BITS_IN_BYTE;
+    if copy_bits > 0 {
+        let old_data = vm.state.memory.read(&addr, copy_bits)?;
+        vm.state.memory.write(&new_addr, old_data)?;
+    }
+
+    let old_size_in_bits = old_size_in_bytes * BITS_IN_BYTE as u64;
+    vm.state
+        .memory
+        .deallocate(old_addr, old_size_in_bits, align)?;
 
     Ok(PathResult::Success(Some(new_addr)))
 }
@@ -273,7 +295,8 @@ fn rust_alloc_zeroed(
 
     let align = get_single_u64_from_op(vm, &args[1])?;
 
-    let addr = vm.state.memory.allocate(size_in_bits, align)?;
+    let site = vm.allocation_site();
+    let addr = vm.state.memory.allocate_at(size_in_bits, align, site)?;
     let addr = vm.state.ctx.from_u64(addr, vm.project.ptr_size);
 
     let zeroes = vm.state.ctx.zero(size_in_bits as u32);
@@ -12,9 +12,8 @@ use tracing::{debug, trace, warn};
 use super::LLVMExecutor;
 use crate::{
     memory::BITS_IN_BYTE,
-    smt::{DExpr, Solutions},
-    vm::{binop, bit_size, LLVMExecutorError, PathResult, Result},
-    MAX_INTRINSIC_CONCRETIZATIONS,
+    smt::{DExpr, SolverError},
+    vm::{binop, bit_size, LLVMExecutorError, PathResult, Result, SymbolicLength},
 };
 
 /// Check if the given name is an LLVM intrinsic.
@@ -65,6 +64,9 @@ impl Intrinsics {
         s.add_variable("llvm.memmove.", llvm_memmove);
         s.add_variable("llvm.memset.", llvm_memset);
         s.add_variable("llvm.umax.", llvm_umax);
+        s.add_variable("llvm.umin.", llvm_umin);
+        s.add_variable("llvm.smax.", llvm_smax);
+        s.add_variable("llvm.smin.", llvm_smin);
 
         s.add_variable("llvm.sadd.with.overflow.", llvm_sadd_with_overflow);
         s.add_variable("llvm.uadd.with.overflow.", llvm_uadd_with_overflow);
@@ -78,8 +80,22 @@ impl Intrinsics {
         s.add_variable("llvm.ssub.sat.", llvm_ssub_sat);
         s.add_variable("llvm.usub.sat.", llvm_usub_sat);
 
+        s.add_variable("llvm.vector.reduce.add.", llvm_vector_reduce_add);
+        s.add_variable("llvm.vector.reduce.mul.", llvm_vector_reduce_mul);
+        s.add_variable("llvm.vector.reduce.and.", llvm_vector_reduce_and);
+        s.add_variable("llvm.vector.reduce.or.", llvm_vector_reduce_or);
+        s.add_variable("llvm.vector.reduce.xor.", llvm_vector_reduce_xor);
+        s.add_variable("llvm.vector.reduce.umax.", llvm_vector_reduce_umax);
+        s.add_variable("llvm.vector.reduce.umin.", llvm_vector_reduce_umin);
+        s.add_variable("llvm.vector.reduce.smax.", llvm_vector_reduce_smax);
+        s.add_variable("llvm.vector.reduce.smin.", llvm_vector_reduce_smin);
+
         s.add_variable("llvm.expect.", llvm_expect);
 
+        s.add_variable("llvm.stacksave", llvm_stacksave);
+        s.add_variable("llvm.stackrestore", llvm_stackrestore);
+        s.add_variable("llvm.vscale.", llvm_vscale);
+
         // Temporary.
         s.add_variable("llvm.dbg", noop);
         s.add_variable("llvm.lifetime", noop);
@@ -138,16 +154,20 @@ pub fn llvm_memcpy(vm: &mut LLVMExecutor<'_>, args: &[Value]) -> Result<PathResu
     let src = vm.state.get_expr(&args[1])?;
     let len = vm.state.get_expr(&args[2])?;
 
-    if let Some(len) = len.get_constant() {
-        if len > 0 {
+    match length(vm, &len, "llvm.memcpy.*", &[&dst, &src])? {
+        Length::Concrete(0) => warn!("memcpy with size 0"),
+        Length::Concrete(len) => {
             let len = len as u32 * BITS_IN_BYTE;
             let value = vm.state.memory.read(&src, len)?;
             vm.state.memory.write(&dst, value)?;
-        } else {
-            warn!("memcpy with size 0");
         }
-    } else {
-        todo!("symbolic length in llvm.memcpy.*");
+        Length::Bounded(max_len) => {
+            for byte in 0..max_len {
+                let offset = vm.state.ctx.from_u64(byte, vm.project.ptr_size);
+                let value = vm.state.memory.read(&src.add(&offset), BITS_IN_BYTE)?;
+                write_byte_below(vm, &dst, byte, &len, value)?;
+            }
+        }
     }
 
     Ok(PathResult::Success(None))
@@ -167,15 +187,20 @@ pub fn llvm_memset(vm: &mut LLVMExecutor<'_>, args: &[Value]) -> Result<PathResu
     let len = vm.state.get_expr(&args[2])?;
 
     assert_eq!(val.len(), BITS_IN_BYTE);
-    if let Some(len) = len.get_constant() {
-        for byte in 0..len {
-            let offset = vm.state.ctx.from_u64(byte, vm.project.ptr_size);
-            let addr = dst.add(&offset);
+    match length(vm, &len, "llvm.memset.*", &[&dst])? {
+        Length::Concrete(len) => {
+            for byte in 0..len {
+                let offset = vm.state.ctx.from_u64(byte, vm.project.ptr_size);
+                let addr = dst.add(&offset);
 
-            vm.state.memory.write(&addr, val.clone())?;
+                vm.state.memory.write(&addr, val.clone())?;
+            }
+        }
+        Length::Bounded(max_len) => {
+            for byte in 0..max_len {
+                write_byte_below(vm, &dst, byte, &len, val.clone())?;
+            }
         }
-    } else {
-        todo!("symbolic length in llvm.memset.*");
     }
 
     Ok(PathResult::Success(None))
@@ -194,62 +219,176 @@ pub fn llvm_memmove(vm: &mut LLVMExecutor<'_>, args: &[Value]) -> Result<PathRes
     let src = vm.state.get_expr(&args[1])?;
     let len = vm.state.get_expr(&args[2])?;
 
-    // TODO: Not sure about the exact semantics when the locations overlap. So copy
-    // the bytes one by one for now.
-
-    let len = match len.get_constant() {
-        Some(len) => len,
-        None => {
-            warn!("symbolic length in llvm.memmove.* is experimental");
-            let concretizations = MAX_INTRINSIC_CONCRETIZATIONS;
-            let solutions = vm.state.constraints.get_values(&len, concretizations)?;
-            let solutions = match solutions {
-                Solutions::Exactly(v) => v,
-                Solutions::AtLeast(v) => {
-                    warn!(
-                        "More than {} solutions found for length in memmove",
-                        concretizations
-                    );
-                    v
-                }
-            };
-
-            let (solution, others) = solutions.split_first().unwrap();
-
-            // Fork other paths.
-            for solution in others.iter() {
-                let constraint = len._eq(&solution);
-                vm.fork(constraint)?;
+    match length(vm, &len, "llvm.memmove.*", &[&dst, &src])? {
+        // TODO: Not sure about the exact semantics when the locations overlap. So copy
+        // the bytes one by one for now.
+        Length::Concrete(len) => {
+            for i in 0..len {
+                let increment = vm.state.ctx.from_u64(i, vm.project.ptr_size);
+                let src_addr = src.add(&increment);
+                let dst_addr = dst.add(&increment);
+
+                let value = vm.state.memory.read(&src_addr, BITS_IN_BYTE)?;
+                vm.state.memory.write(&dst_addr, value)?;
             }
+        }
 
-            // OK, lets go.
-            let constraint = len._eq(&solution);
-            vm.state.constraints.assert(&constraint);
-            solution.get_constant().unwrap() // Know this is constant.
+        // Read the whole source before writing, so overlapping bytes are not read after
+        // they have been overwritten.
+        Length::Bounded(max_len) => {
+            let mut values = Vec::new();
+            for byte in 0..max_len {
+                let offset = vm.state.ctx.from_u64(byte, vm.project.ptr_size);
+                values.push(vm.state.memory.read(&src.add(&offset), BITS_IN_BYTE)?);
+            }
+            for (byte, value) in values.into_iter().enumerate() {
+                write_byte_below(vm, &dst, byte as u64, &len, value)?;
+            }
         }
-    };
+    }
+
+    Ok(PathResult::Success(None))
+}
+
+/// Length of a memory intrinsic.
+enum Length {
+    /// The number of bytes to access.
+    Concrete(u64),
 
-    for i in 0..len {
-        let increment = vm.state.ctx.from_u64(i, vm.project.ptr_size);
-        let src_addr = src.add(&increment);
-        let dst_addr = dst.add(&increment);
+    /// The length is symbolic, at most this many bytes are accessed.
+    Bounded(u64),
+}
 
-        let value = vm.state.memory.read(&src_addr, BITS_IN_BYTE)?;
-        vm.state.memory.write(&dst_addr, value)?;
+/// Resolve the length argument of a memory intrinsic.
+///
+/// Symbolic lengths are handled by the [`SymbolicLength`] strategy in the
+/// config. Forking concretizes the length on this path, while if-then-else
+/// keeps it symbolic and only bounds it.
+///
+/// With if-then-else the lengths that reach past the end of any of the
+/// objects at `addresses` are forked to a path of their own, where the
+/// intrinsic fails with an out of bounds access. Otherwise one length that is
+/// too long would fail the lengths that fit as well.
+///
+/// With either strategy at most `max_intrinsic_concretizations` lengths, or
+/// lengths up to it with if-then-else, are explored. The other lengths end on
+/// a path of their own with [`AnalysisError::TooManySolutions`] rather than
+/// being dropped.
+///
+/// [`AnalysisError::TooManySolutions`]: crate::vm::AnalysisError::TooManySolutions
+fn length(
+    vm: &mut LLVMExecutor<'_>,
+    len: &DExpr,
+    intrinsic: &str,
+    addresses: &[&DExpr],
+) -> Result<Length> {
+    if let Some(len) = len.get_constant() {
+        return Ok(Length::Concrete(len));
     }
 
-    Ok(PathResult::Success(None))
+    let concretizations = vm.vm.cfg.max_intrinsic_concretizations;
+    match vm.vm.cfg.symbolic_length {
+        SymbolicLength::Fork => {
+            let what = format!("length in {intrinsic}");
+            let len = vm.concretize(len, concretizations, &what)?;
+            Ok(Length::Concrete(len))
+        }
+        SymbolicLength::Ite => {
+            let mut max_len = vm.state.constraints.max_value(len)?;
+            let bound = concretizations as u64;
+            if max_len > bound {
+                // The longer lengths are forked to a path of their own that fails.
+                let within = len.ulte(&vm.state.ctx.from_u64(bound, len.len()));
+                if !vm.state.constraints.is_sat_with_constraint(&within)? {
+                    return Err(SolverError::TooManySolutions.into());
+                }
+                warn!(
+                    "Length in {} can be up to {}, only lengths up to {} are explored",
+                    intrinsic, max_len, bound
+                );
+                vm.fork(within.not())?;
+                vm.state.constraints.assert(&within);
+                max_len = bound;
+            }
+
+            let room = addresses
+                .iter()
+                .filter_map(|address| address.get_constant())
+                .filter_map(|address| vm.state.memory.bytes_to_end(address))
+                .min();
+            match room {
+                Some(room) if room < max_len => {
+                    let fits = len.ulte(&vm.state.ctx.from_u64(room, len.len()));
+                    if !vm.state.constraints.is_sat_with_constraint(&fits)? {
+                        return Ok(Length::Bounded(max_len));
+                    }
+                    vm.fork(fits.not())?;
+                    vm.state.constraints.assert(&fits);
+                    Ok(Length::Bounded(room))
+                }
+                _ => Ok(Length::Bounded(max_len)),
+            }
+        }
+    }
 }
 
+/// Write `value` to byte `byte` of `dst` if `byte < len`, otherwise the byte
+/// keeps its old value.
+fn write_byte_below(
+    vm: &mut LLVMExecutor<'_>,
+    dst: &DExpr,
+    byte: u64,
+    len: &DExpr,
+    value: DExpr,
+) -> Result<()> {
+    let offset = vm.state.ctx.from_u64(byte, vm.project.ptr_size);
+    let addr = dst.add(&offset);
+
+    let old = vm.state.memory.read(&addr, BITS_IN_BYTE)?;
+    let in_range = vm.state.ctx.from_u64(byte, len.len()).ult(len);
+    vm.state.memory.write(&addr, in_range.ite(&value, &old))?;
+    Ok(())
+}
+
+/// Unsigned maximum of two integers or two vectors of integers.
 pub fn llvm_umax(vm: &mut LLVMExecutor<'_>, args: &[Value]) -> Result<PathResult> {
     assert_eq!(args.len(), 2);
     trace!("llvm_umax");
+    min_max(vm, args, DExpr::ugt)
+}
+
+/// Unsigned minimum of two integers or two vectors of integers.
+pub fn llvm_umin(vm: &mut LLVMExecutor<'_>, args: &[Value]) -> Result<PathResult> {
+    assert_eq!(args.len(), 2);
+    trace!("llvm_umin");
+    min_max(vm, args, DExpr::ult)
+}
+
+/// Signed maximum of two integers or two vectors of integers.
+pub fn llvm_smax(vm: &mut LLVMExecutor<'_>, args: &[Value]) -> Result<PathResult> {
+    assert_eq!(args.len(), 2);
+    trace!("llvm_smax");
+    min_max(vm, args, DExpr::sgt)
+}
 
+/// Signed minimum of two integers or two vectors of integers.
+pub fn llvm_smin(vm: &mut LLVMExecutor<'_>, args: &[Value]) -> Result<PathResult> {
+    assert_eq!(args.len(), 2);
+    trace!("llvm_smin");
+    min_max(vm, args, DExpr::slt)
+}
+
+/// Picks `lhs` if `compare(lhs, rhs)` holds and `rhs` otherwise.
+fn min_max(
+    vm: &mut LLVMExecutor<'_>,
+    args: &[Value],
+    compare: fn(&DExpr, &DExpr) -> DExpr,
+) -> Result<PathResult> {
     let lhs = &args[0];
     let rhs = &args[1];
 
     let operation = |lhs: &DExpr, rhs: &DExpr| {
-        let condition = lhs.ugt(rhs);
+        let condition = compare(lhs, rhs);
         condition.ite(lhs, rhs)
     };
     let result = binop(&mut vm.state, lhs, rhs, operation)?;
@@ -434,6 +573,81 @@ pub fn llvm_ssub_sat(vm: &mut LLVMExecutor<'_>, args: &[Value]) -> Result<PathRe
     binary_op_saturate(vm, args, BinaryOpSaturate::SSub)
 }
 
+// -------------------------------------------------------------------------------------------------
+// Vector reduction intrinsics
+// -------------------------------------------------------------------------------------------------
+
+/// Combines all elements of a vector of integers with `operation`.
+fn vector_reduce(
+    vm: &mut LLVMExecutor<'_>,
+    args: &[Value],
+    operation: impl Fn(&DExpr, &DExpr) -> DExpr,
+) -> Result<PathResult> {
+    assert_eq!(args.len(), 1);
+    let Type::Vector(ty) = args[0].ty() else {
+        return Err(LLVMExecutorError::MalformedInstruction);
+    };
+    if ty.is_scalable() {
+        return Err(LLVMExecutorError::UnsupportedInstruction(
+            "reduction of scalable vectors".to_owned(),
+        ));
+    }
+
+    let vector = vm.state.get_expr(&args[0])?;
+    let bits = bit_size(&ty.element_type(), vm.project.ptr_size)?;
+    let result = (0..ty.num_elements())
+        .map(|i| vector.slice(i * bits, (i + 1) * bits - 1))
+        .reduce(|acc, element| operation(&acc, &element))
+        .ok_or(LLVMExecutorError::MalformedInstruction)?;
+
+    Ok(PathResult::Success(Some(result)))
+}
+
+/// Sum of the elements of a vector, wrapping on overflow.
+pub fn llvm_vector_reduce_add(vm: &mut LLVMExecutor<'_>, args: &[Value]) -> Result<PathResult> {
+    vector_reduce(vm, args, DExpr::add)
+}
+
+/// Product of the elements of a vector, wrapping on overflow.
+pub fn llvm_vector_reduce_mul(vm: &mut LLVMExecutor<'_>, args: &[Value]) -> Result<PathResult> {
+    vector_reduce(vm, args, DExpr::mul)
+}
+
+/// Bitwise and of the elements of a vector.
+pub fn llvm_vector_reduce_and(vm: &mut LLVMExecutor<'_>, args: &[Value]) -> Result<PathResult> {
+    vector_reduce(vm, args, DExpr::and)
+}
+
+/// Bitwise or of the elements of a vector.
+pub fn llvm_vector_reduce_or(vm: &mut LLVMExecutor<'_>, args: &[Value]) -> Result<PathResult> {
+    vector_reduce(vm, args, DExpr::or)
+}
+
+/// Bitwise xor of the elements of a vector.
+pub fn llvm_vector_reduce_xor(vm: &mut LLVMExecutor<'_>, args: &[Value]) -> Result<PathResult> {
+    vector_reduce(vm, args, DExpr::xor)
+}
+
+/// Unsigned maximum of the elements of a vector.
+pub fn llvm_vector_reduce_umax(vm: &mut LLVMExecutor<'_>, args: &[Value]) -> Result<PathResult> {
+    vector_reduce(vm, args, |acc, v| acc.ugt(v).ite(acc, v))
+}
+
+/// Unsigned minimum of the elements of a vector.
+pub fn llvm_vector_reduce_umin(vm: &mut LLVMExecutor<'_>, args: &[Value]) -> Result<PathResult> {
+    vector_reduce(vm, args, |acc, v| acc.ult(v).ite(acc, v))
+}
+
+/// Signed maximum of the elements of a vector.
+pub fn llvm_vector_reduce_smax(vm: &mut LLVMExecutor<'_>, args: &[Value]) -> Result<PathResult> {
+    vector_reduce(vm, args, |acc, v| acc.sgt(v).ite(acc, v))
+}
+
+/// Signed minimum of the elements of a vector.
+pub fn llvm_vector_reduce_smin(vm: &mut LLVMExecutor<'_>, args: &[Value]) -> Result<PathResult> {
+    vector_reduce(vm, args, |acc, v| acc.slt(v).ite(acc, v))
+}
+
 // -------------------------------------------------------------------------------------------------
 // General intrinsics
 // -------------------------------------------------------------------------------------------------
@@ -453,53 +667,106 @@ pub fn llvm_assume(vm: &mut LLVMExecutor<'_>, args: &[Value]) -> Result<PathResu
     Ok(PathResult::Success(None))
 }
 
+/// Save the state of the stack, the returned pointer can be passed to
+/// `llvm.stackrestore` to free all `alloca`s made after this call.
+pub fn llvm_stacksave(vm: &mut LLVMExecutor<'_>, args: &[Value]) -> Result<PathResult> {
+    assert_eq!(args.len(), 0);
+
+    // The pointer is opaque to the program, so the number of allocations in the
+    // frame is used.
+    let num_allocations = vm.state.current_frame()?.num_allocations();
+    let saved = vm
+        .state
+        .ctx
+        .from_u64(num_allocations as u64, vm.project.ptr_size);
+    Ok(PathResult::Success(Some(saved)))
+}
+
+/// Restore the stack to a state saved by `llvm.stacksave`.
+pub fn llvm_stackrestore(vm: &mut LLVMExecutor<'_>, args: &[Value]) -> Result<PathResult> {
+    assert_eq!(args.len(), 1);
+
+    let saved = vm.state.get_expr(&args[0])?;
+    let Some(num_allocations) = saved.get_constant() else {
+        return Err(LLVMExecutorError::MalformedInstruction);
+    };
+
+    let frame = vm.state.current_frame_mut()?;
+    for address in frame.pop_allocations(num_allocations as usize) {
+        vm.state.memory.free(address)?;
+    }
+    Ok(PathResult::Success(None))
+}
+
+/// Returns `vscale` for scalable vectors, which is always one.
+pub fn llvm_vscale(vm: &mut LLVMExecutor<'_>, args: &[Value]) -> Result<PathResult> {
+    assert_eq!(args.len(), 0);
+
+    let call = vm
+        .state
+        .current_frame()?
+        .current_instruction()
+        .cloned()
+        .ok_or(LLVMExecutorError::MalformedInstruction)?;
+    let bits = bit_size(&Value::Instruction(call).ty(), vm.project.ptr_size)?;
+    Ok(PathResult::Success(Some(vm.state.ctx.from_u64(1, bits))))
+}
+
 #[cfg(test)]
 mod tests {
     use crate::{
-        smt::DContext,
-        vm::{PathResult, Project, VM},
+        memory::MemoryError,
+        vm::{test, AnalysisError, Config, PathResult, Project, SymbolicLength, VM},
     };
 
     fn run(fn_name: &str) -> Vec<Option<i64>> {
-        // let subscriber = tracing_subscriber::FmtSubscriber::builder()
-        //     .with_max_level(tracing::Level::TRACE)
-        //     .finish();
-        // tracing::subscriber::set_global_default(subscriber)
-        //     .expect("setting default subscriber failed");
+        run_with_config(fn_name, Config::new())
+    }
 
-        let path = format!("tests/unit_tests/intrinsics.bc");
-        let project = Box::new(Project::from_path(&path).expect("Failed to created project"));
-        let project = Box::leak(project);
+    fn vm(fn_name: &str, cfg: Config) -> VM {
+        test::vm(project(), fn_name, cfg)
+    }
 
-        let context = Box::new(DContext::new());
-        let context = Box::leak(context);
-        let mut vm = VM::new(project, context, fn_name).expect("Failed to create VM");
+    fn project() -> Project {
+        Project::from_path("tests/unit_tests/intrinsics.bc").expect("Failed to created project")
+    }
 
+    /// Runs all paths and returns their results, failing paths included.
+    fn run_paths(fn_name: &str, cfg: Config) -> Vec<PathResult> {
+        let mut vm = vm(fn_name, cfg);
         let mut path_results = Vec::new();
-        while let Some((path_result, state)) = vm.run().expect("Failed to run path") {
-            let result = match path_result {
-                PathResult::Success(Some(value)) => {
-                    let value = state
-                        .constraints
-                        .get_value(&value)
-                        .expect("Failed to get concrete value");
-                    let binary_str = value.to_binary_string();
-                    Some(u128::from_str_radix(&binary_str, 2).unwrap() as i64)
-                }
-                PathResult::Success(None) => None,
-                PathResult::Failure(error) => {
-                    panic!("Did not expect any paths to fail, reason: {error:?}")
-                }
-                PathResult::Suppress => panic!("Did not expect any paths to be suppressed"),
-                PathResult::AssumptionUnsat => panic!("Did not expect any paths to be unsat"),
-            };
-            path_results.push(result);
+        while let Some((path_result, _)) = vm.run().expect("Failed to run path") {
+            path_results.push(path_result);
         }
-
-        eprintln!("{path_results:x?}");
         path_results
     }
 
+    /// Counts the successful paths and the paths with an out of bounds access.
+    fn out_of_bounds(results: &[PathResult]) -> (usize, usize) {
+        let successes = results
+            .iter()
+            .filter(|result| matches!(result, PathResult::Success(_)))
+            .count();
+        let out_of_bounds = results
+            .iter()
+            .filter(|result| {
+                matches!(
+                    result,
+                    PathResult::Failure(AnalysisError::Memory {
+                        error: MemoryError::ObjectOutOfBounds(_),
+                        ..
+                    })
+                )
+            })
+            .count();
+        assert_eq!(successes + out_of_bounds, results.len());
+        (successes, out_of_bounds)
+    }
+
+    fn run_with_config(fn_name: &str, cfg: Config) -> Vec<Option<i64>> {
+        test::run(project(), fn_name, cfg)
+    }
+
     #[test]
     fn test_memcpy() {
         let res = run("test_memcpy");
@@ -536,6 +803,42 @@ mod tests {
         assert!(res == possible_res0 || res == possible_res1);
     }
 
+    #[test]
+    fn test_memmove_symbolic_len_ite() {
+        let cfg = Config {
+            symbolic_length: SymbolicLength::Ite,
+            ..Config::new()
+        };
+        let res = run_with_config("test_memmove_symbolic_len", cfg);
+        assert_eq!(res.len(), 1);
+        assert!(
+            res[0] == Some(0x00000003_0034abcd_u64 as i64)
+                || res[0] == Some(0x00000004_1234abcd_u64 as i64)
+        );
+    }
+
+    #[test]
+    fn test_memcpy_symbolic_len() {
+        let res = run("test_memcpy_symbolic_len");
+        assert_eq!(res.len(), 2);
+        assert!(res.contains(&Some(0x00000003_0034abcd_u64 as i64)));
+        assert!(res.contains(&Some(0x00000004_1234abcd_u64 as i64)));
+    }
+
+    #[test]
+    fn test_memcpy_symbolic_len_ite() {
+        let cfg = Config {
+            symbolic_length: SymbolicLength::Ite,
+            ..Config::new()
+        };
+        let res = run_with_config("test_memcpy_symbolic_len", cfg);
+        assert_eq!(res.len(), 1);
+        assert!(
+            res[0] == Some(0x00000003_0034abcd_u64 as i64)
+                || res[0] == Some(0x00000004_1234abcd_u64 as i64)
+        );
+    }
+
     #[test]
     fn test_memset() {
         let res = run("test_memset");
@@ -543,6 +846,94 @@ mod tests {
         assert_eq!(res[0], Some(-6076574517859464245i64)); // 0xababababcbcbcbcb
     }
 
+    #[test]
+    fn test_memset_symbolic_len() {
+        let res = run("test_memset_symbolic_len");
+        assert_eq!(res.len(), 2);
+        assert!(res.contains(&Some(0x00000003_abcbcbcb_u64 as i64)));
+        assert!(res.contains(&Some(0x00000004_cbcbcbcb_u64 as i64)));
+    }
+
+    #[test]
+    fn test_memset_symbolic_len_ite() {
+        let cfg = Config {
+            symbolic_length: SymbolicLength::Ite,
+            ..Config::new()
+        };
+        let res = run_with_config("test_memset_symbolic_len", cfg);
+        assert_eq!(res.len(), 1);
+        assert!(
+            res[0] == Some(0x00000003_abcbcbcb_u64 as i64)
+                || res[0] == Some(0x00000004_cbcbcbcb_u64 as i64)
+        );
+    }
+
+    #[test]
+    fn test_memset_symbolic_len_short() {
+        let res = run_paths("test_memset_symbolic_len_short", Config::new());
+        assert_eq!(out_of_bounds(&res), (2, 2));
+    }
+
+    #[test]
+    fn test_memset_symbolic_len_short_ite() {
+        let cfg = Config {
+            symbolic_length: SymbolicLength::Ite,
+            ..Config::new()
+        };
+        // The lengths that fit in the buffer do not fail with the ones that do
+        // not.
+        let res = run_paths("test_memset_symbolic_len_short", cfg);
+        assert_eq!(out_of_bounds(&res), (1, 1));
+    }
+
+    #[test]
+    fn test_memcpy_symbolic_len_short_ite() {
+        let cfg = Config {
+            symbolic_length: SymbolicLength::Ite,
+            ..Config::new()
+        };
+        let res = run_paths("test_memcpy_symbolic_len_short", cfg);
+        assert_eq!(out_of_bounds(&res), (1, 1));
+    }
+
+    /// Counts the successful paths and the paths with lengths that were not
+    /// explored.
+    fn too_many_solutions(results: &[PathResult]) -> (usize, usize) {
+        let successes = results
+            .iter()
+            .filter(|result| matches!(result, PathResult::Success(_)))
+            .count();
+        let too_many = results
+            .iter()
+            .filter(|result| matches!(result, PathResult::Failure(AnalysisError::TooManySolutions)))
+            .count();
+        assert_eq!(successes + too_many, results.len());
+        (successes, too_many)
+    }
+
+    #[test]
+    fn test_memset_symbolic_len_too_many() {
+        // Only one of the two lengths is explored, the other one fails rather
+        // than being dropped.
+        let cfg = Config {
+            max_intrinsic_concretizations: 1,
+            ..Config::new()
+        };
+        let res = run_paths("test_memset_symbolic_len", cfg);
+        assert_eq!(too_many_solutions(&res), (1, 1));
+    }
+
+    #[test]
+    fn test_memset_symbolic_len_too_many_ite() {
+        let cfg = Config {
+            symbolic_length: SymbolicLength::Ite,
+            max_intrinsic_concretizations: 3,
+            ..Config::new()
+        };
+        let res = run_paths("test_memset_symbolic_len", cfg);
+        assert_eq!(too_many_solutions(&res), (1, 1));
+    }
+
     #[test]
     fn test_umax() {
         let res = run("test_umax");
@@ -557,6 +948,55 @@ mod tests {
         assert_eq!(res[0], Some(0x000043210000bcef));
     }
 
+    #[test]
+    fn test_umin() {
+        let res = run("test_umin");
+        assert_eq!(res.len(), 1);
+        assert_eq!(res[0], Some(0xabcd));
+    }
+
+    #[test]
+    fn test_smax_vec() {
+        let res = run("test_smax_vec");
+        assert_eq!(res.len(), 1);
+        assert_eq!(res[0], Some(0x0302));
+    }
+
+    #[test]
+    fn test_smin() {
+        let res = run("test_smin");
+        assert_eq!(res.len(), 1);
+        assert_eq!(res[0], Some(0xfb));
+    }
+
+    #[test]
+    fn test_vector_reduce_add() {
+        let res = run("test_vector_reduce_add");
+        assert_eq!(res.len(), 1);
+        assert_eq!(res[0], Some(10));
+    }
+
+    #[test]
+    fn test_vector_reduce_xor() {
+        let res = run("test_vector_reduce_xor");
+        assert_eq!(res.len(), 1);
+        assert_eq!(res[0], Some(4));
+    }
+
+    #[test]
+    fn test_vector_reduce_umax() {
+        let res = run("test_vector_reduce_umax");
+        assert_eq!(res.len(), 1);
+        assert_eq!(res[0], Some(0xffff));
+    }
+
+    #[test]
+    fn test_vector_reduce_smin() {
+        let res = run("test_vector_reduce_smin");
+        assert_eq!(res.len(), 1);
+        assert_eq!(res[0], Some(0xffff));
+    }
+
     #[test]
     fn test_sadd_sat0() {
         let res = run("test_sadd_sat0");
@@ -703,4 +1143,33 @@ mod tests {
         assert_eq!(res.len(), 1);
         assert_eq!(res[0], Some(5));
     }
+
+    #[test]
+    fn test_stackrestore() {
+        let res = run("test_stackrestore");
+        assert_eq!(res.len(), 1);
+        assert_eq!(res[0], Some(2));
+    }
+
+    #[test]
+    fn test_stackrestore_freed() {
+        let res = run_paths("test_stackrestore_freed", Config::new());
+        assert_eq!(res.len(), 1);
+        let PathResult::Failure(AnalysisError::Memory {
+            error,
+            allocation_site,
+        }) = &res[0]
+        else {
+            panic!("Expected a memory error");
+        };
+        assert!(matches!(error, MemoryError::UseAfterFree(_)));
+        assert_eq!(allocation_site.as_deref(), Some("test_stackrestore_freed"));
+    }
+
+    #[test]
+    fn test_vscale() {
+        let res = run("test_vscale");
+        assert_eq!(res.len(), 1);
+        assert_eq!(res[0], Some(1));
+    }
 }
@@ -0,0 +1,118 @@
+//! Memory used by the LLVM executor.
+//!
+//! Which memory model is used is selected with [`MemoryModel`]. Only the
+//! object memory keeps track of the bounds of each allocation, so invalid
+//! accesses and frees are only detected when using it.
+use crate::{
+    general_assembly::Endianness,
+    memory::{ArrayMemory, MemoryError, ObjectMemory},
+    smt::{DContext, DExpr, DSolver},
+    vm::MemoryModel,
+};
+
+#[derive(Debug, Clone)]
+pub enum LLVMMemory {
+    Array(ArrayMemory),
+    Object(ObjectMemory),
+}
+
+impl LLVMMemory {
+    pub fn new(model: MemoryModel, ctx: &'static DContext, ptr_size: u32, solver: DSolver) -> Self {
+        match model {
+            // LLVM targets that are supported are all little endian.
+            MemoryModel::Array => Self::Array(ArrayMemory::new(ctx, ptr_size, Endianness::Little)),
+            MemoryModel::Object => Self::Object(ObjectMemory::new(ctx, ptr_size, solver)),
+        }
+    }
+
+    /// Allocate `bits` of memory returning the newly allocated address.
+    pub fn allocate(&mut self, bits: u64, align: u64) -> Result<u64, MemoryError> {
+        match self {
+            Self::Array(memory) => memory.allocate(bits, align),
+            Self::Object(memory) => memory.allocate(bits, align),
+        }
+    }
+
+    /// Allocate `bits` of memory returning the newly allocated address,
+    /// `site` is used to report where the object was allocated on errors.
+    pub fn allocate_at(
+        &mut self,
+        bits: u64,
+        align: u64,
+        site: Option<String>,
+    ) -> Result<u64, MemoryError> {
+        match self {
+            Self::Array(memory) => memory.allocate(bits, align),
+            Self::Object(memory) => memory.allocate_at(bits, align, site),
+        }
+    }
+
+    /// Free the object allocated at `address`.
+    pub fn free(&mut self, address: u64) -> Result<(), MemoryError> {
+        match self {
+            Self::Array(_) => Ok(()),
+            Self::Object(memory) => memory.free(address),
+        }
+    }
+
+    /// Free the object allocated at `address`, checking that the size and
+    /// alignment match the allocation.
+    pub fn deallocate(&mut self, address: u64, bits: u64, align: u64) -> Result<(), MemoryError> {
+        match self {
+            Self::Array(_) => Ok(()),
+            Self::Object(memory) => memory.deallocate(address, bits, align),
+        }
+    }
+
+    /// Returns the size in bits of the object allocated at `address`.
+    pub fn object_size(&self, address: u64) -> Option<u64> {
+        match self {
+            Self::Array(memory) => memory.allocation_size(address),
+            Self::Object(memory) => memory.get_object(address).map(|object| object.bit_size()),
+        }
+    }
+
+    /// Returns the number of bytes from `address` to the end of the object it
+    /// is in, only the object memory keeps track of this.
+    pub fn bytes_to_end(&self, address: u64) -> Option<u64> {
+        match self {
+            Self::Array(_) => None,
+            Self::Object(memory) => memory.bytes_to_end(address),
+        }
+    }
+
+    /// Returns where the object at `address` was allocated, if it is known.
+    pub fn allocation_site(&self, address: u64) -> Option<&str> {
+        match self {
+            Self::Array(_) => None,
+            Self::Object(memory) => memory
+                .get_allocated_object(address)
+                .and_then(|object| object.site()),
+        }
+    }
+
+    pub fn read(&self, address: &DExpr, bits: u32) -> Result<DExpr, MemoryError> {
+        match self {
+            Self::Array(memory) => memory.read(address, bits),
+            Self::Object(memory) => memory.read(address, bits),
+        }
+    }
+
+    pub fn write(&mut self, address: &DExpr, value: DExpr) -> Result<(), MemoryError> {
+        match self {
+            Self::Array(memory) => memory.write(address, value),
+            Self::Object(memory) => memory.write(address, value),
+        }
+    }
+
+    pub fn resolve_addresses(
+        &self,
+        address: &DExpr,
+        upper_bound: usize,
+    ) -> Result<Vec<DExpr>, MemoryError> {
+        match self {
+            Self::Array(memory) => memory.resolve_addresses(address, upper_bound),
+            Self::Object(memory) => memory.resolve_addresses(address, upper_bound),
+        }
+    }
+}
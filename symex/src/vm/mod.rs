@@ -1,6 +1,7 @@
 mod executor;
 mod hooks;
 mod intrinsic;
+mod memory;
 mod path_selection;
 mod project;
 mod state;
@@ -9,6 +10,8 @@ mod vm;
 pub use executor::*;
 pub use hooks::*;
 pub use intrinsic::*;
+use llvm_ir::ModuleError;
+pub use memory::*;
 pub use path_selection::*;
 pub use project::*;
 pub use state::*;
@@ -28,10 +31,156 @@ pub enum AnalysisError {
     // NoPath,
     Panic,
     Unreachable,
+
+    /// Invalid memory access or free, e.g. a use after free or an out of
+    /// bounds access.
+    Memory {
+        error: MemoryError,
+
+        /// Where the object involved was allocated, if known.
+        allocation_site: Option<String>,
+    },
+
+    /// A symbolic value has more solutions than are explored, e.g. the length
+    /// of a memory intrinsic. The solutions that are explored get paths of
+    /// their own.
+    TooManySolutions,
 }
 
 pub type Result<T> = std::result::Result<T, LLVMExecutorError>;
 
+#[derive(Debug, Clone)]
+pub struct Config {
+    /// Maximum amount of concretizations for memmove, memcpy, memset and other
+    /// intrisic functions. Default is `20`.
+    pub max_intrinsic_concretizations: usize,
+
+    /// Maximum amount of concretizations for the number of elements in an
+    /// `alloca`, a path is forked for each. Default is `20`.
+    pub max_alloca_concretizations: usize,
+
+    /// How memmove, memcpy and memset handle a symbolic length. Default is
+    /// [`SymbolicLength::Fork`].
+    pub symbolic_length: SymbolicLength,
+
+    /// Levels of pointers in the entry function parameters that get a symbolic
+    /// pointee, pointers past this are unconstrained. Default is `2`.
+    pub max_pointee_depth: usize,
+
+    /// Number of elements allocated for a slice parameter of the entry
+    /// function, the length of the slice is at most this. Default is `8`.
+    pub slice_len: usize,
+    /// Memory model used for the analysis. Default is
+    /// [`MemoryModel::Object`].
+    pub memory_model: MemoryModel,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Config {
+    /// Creates a new `Config` with default values.
+    ///
+    /// Current defaults are set to
+    ///
+    /// - `max_intrinsic_concretizations`: 20
+    /// - `max_alloca_concretizations`: 20
+    /// - `symbolic_length`: [`SymbolicLength::Fork`]
+    /// - `max_pointee_depth`: 2
+    /// - `slice_len`: 8
+    /// - `memory_model`: [`MemoryModel::Object`]
+    pub fn new() -> Self {
+        Self {
+            max_intrinsic_concretizations: 20,
+            max_alloca_concretizations: 20,
+            symbolic_length: SymbolicLength::Fork,
+            max_pointee_depth: 2,
+            slice_len: 8,
+            memory_model: MemoryModel::Object,
+        }
+    }
+}
+
+/// Memory model used by the LLVM executor.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MemoryModel {
+    /// Theories-of-array memory, supports fully symbolic addresses but does not
+    /// detect invalid accesses.
+    Array,
+
+    /// Object memory, each allocation is a separate object so out of bounds
+    /// accesses, uses after free and invalid frees are reported as failing
+    /// paths.
+    Object,
+}
+
+/// Strategy for memory intrinsics with a symbolic length.
+///
+/// Both are bounded by [`Config::max_intrinsic_concretizations`], lengths past
+/// the bound are not explored.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SymbolicLength {
+    /// Fork a path for every feasible length.
+    Fork,
+
+    /// Stay on the same path and guard every byte up to the largest feasible
+    /// length by `byte < len` with an if-then-else.
+    Ite,
+}
+
+#[cfg(test)]
+pub(crate) mod test {
+    use super::{Config, PathResult, Project, VM};
+    use crate::smt::DContext;
+
+    /// Creates a VM that starts in `fn_name` of `project`, the project and the
+    /// context are leaked to live for the rest of the test.
+    pub(crate) fn vm(project: Project, fn_name: &str, cfg: Config) -> VM {
+        // let subscriber = tracing_subscriber::FmtSubscriber::builder()
+        //     .with_max_level(tracing::Level::TRACE)
+        //     .finish();
+        // tracing::subscriber::set_global_default(subscriber)
+        //     .expect("setting default subscriber failed");
+
+        let project = Box::leak(Box::new(project));
+        let context = Box::leak(Box::new(DContext::new()));
+        VM::new_with_config(project, context, fn_name, cfg).expect("Failed to create VM")
+    }
+
+    /// Runs all paths of `fn_name` in `project` and returns the concrete return
+    /// value of each, panics if any path does not succeed.
+    pub(crate) fn run(project: Project, fn_name: &str, cfg: Config) -> Vec<Option<i64>> {
+        let mut vm = vm(project, fn_name, cfg);
+
+        let mut path_results = Vec::new();
+        while let Some((path_result, state)) = vm.run().expect("Failed to run path") {
+            let result = match path_result {
+                PathResult::Success(Some(value)) => {
+                    let value = state
+                        .constraints
+                        .get_value(&value)
+                        .expect("Failed to get concrete value");
+                    let binary_str = value.to_binary_string();
+                    Some(u128::from_str_radix(&binary_str, 2).unwrap() as i64)
+                }
+                PathResult::Success(None) => None,
+                PathResult::Failure(error) => {
+                    panic!("Did not expect any paths to fail, reason: {error:?}")
+                }
+                PathResult::Suppress => panic!("Did not expect any paths to be suppressed"),
+                PathResult::AssumptionUnsat => panic!("Did not expect any paths to be unsat"),
+            };
+            path_results.push(result);
+        }
+
+        eprintln!("{path_results:x?}");
+        path_results
+    }
+}
+
 #[derive(Clone, Debug, Eq, PartialEq, thiserror::Error)]
 pub enum LLVMExecutorError {
     #[error("Abort {0}")]
@@ -67,6 +216,12 @@ pub enum LLVMExecutorError {
 
     #[error("Solver error")]
     SolverError(#[from] SolverError),
+    #[error("Module error")]
+    ModuleError(#[from] ModuleError),
+
+    /// A project was created without any modules.
+    #[error("No modules to create a project from")]
+    NoModules,
 }
 
 // /// Errors why a certain path failed.
@@ -1,10 +1,10 @@
 use std::{ffi::CStr, path::Path};
 
-use llvm_ir::{Function, Module};
+use llvm_ir::{Function, GlobalValue, LLVMLinkage, Module};
 use rustc_demangle::demangle;
-use tracing::debug;
+use tracing::{debug, warn};
 
-use crate::vm::Result;
+use crate::vm::{LLVMExecutorError, Result};
 
 pub enum ProjectError {
     InvalidModule,
@@ -41,7 +41,7 @@ pub enum Overriden {
 }
 
 pub struct Project {
-    /// All [Module]s.
+    /// All loaded modules, linked into a single [Module].
     pub module: Module,
 
     /// Size of pointers across all module. The system does not support
@@ -60,7 +60,28 @@ pub struct Project {
 
 impl Project {
     pub fn from_path(path: impl AsRef<Path>) -> Result<Self> {
-        let module = Module::load(path).unwrap();
+        let module = Module::load(path)?;
+        Self::from_module(module)
+    }
+
+    /// Load and link several bitcode files into one project.
+    ///
+    /// Declarations are resolved against definitions in any of the modules.
+    /// When a symbol with external linkage is defined in more than one module
+    /// the definition from the module that comes first in `paths` is used, and
+    /// a warning is emitted. Weak and `linkonce` definitions follow the usual
+    /// LLVM rules, and clashing internal symbols are renamed by the linker.
+    pub fn from_paths<P: AsRef<Path>>(paths: impl IntoIterator<Item = P>) -> Result<Self> {
+        let mut paths = paths.into_iter();
+        let first = paths.next().ok_or(LLVMExecutorError::NoModules)?;
+        let mut module = Module::load(first)?;
+
+        for path in paths {
+            let other = module.load_into_context(path)?;
+            resolve_clashes(&module, &other);
+            module.link(other)?;
+        }
+
         Self::from_module(module)
     }
 
@@ -158,3 +179,95 @@ impl Project {
         None
     }
 }
+
+/// Demote definitions in `other` that would clash with strong definitions
+/// already in `module`, so the first definition wins when linking.
+fn resolve_clashes(module: &Module, other: &Module) {
+    let is_strong = |linkage| linkage == LLVMLinkage::LLVMExternalLinkage;
+
+    for function in other.functions() {
+        if function.is_declaration() || !is_strong(function.linkage()) {
+            continue;
+        }
+        let Some(existing) = module.function(function.name()) else {
+            continue;
+        };
+        if !existing.is_declaration() && is_strong(existing.linkage()) {
+            warn!(
+                "Function {:?} in {:?} is already defined in {:?}, ignoring it",
+                function.name(),
+                other.identifier(),
+                module.identifier()
+            );
+            function.set_linkage(LLVMLinkage::LLVMLinkOnceAnyLinkage);
+        }
+    }
+
+    for global in other.globals() {
+        if global.is_declaration() || !is_strong(global.linkage()) {
+            continue;
+        }
+        let Some(existing) = module.global(global.name()) else {
+            continue;
+        };
+        if !existing.is_declaration() && is_strong(existing.linkage()) {
+            warn!(
+                "Global {:?} in {:?} is already defined in {:?}, ignoring it",
+                global.name(),
+                other.identifier(),
+                module.identifier()
+            );
+            global.set_linkage(LLVMLinkage::LLVMLinkOnceAnyLinkage);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vm::{test, Config};
+
+    fn run(fn_name: &str) -> Vec<Option<i64>> {
+        let paths = [
+            "tests/unit_tests/linking_main.bc",
+            "tests/unit_tests/linking_lib.bc",
+        ];
+        let project = Project::from_paths(paths).expect("Failed to created project");
+        test::run(project, fn_name, Config::new())
+    }
+
+    #[test]
+    fn test_link_call() {
+        let res = run("test_link_call");
+        assert_eq!(res.len(), 1);
+        assert_eq!(res[0], Some(15));
+    }
+
+    #[test]
+    fn test_link_global() {
+        let res = run("test_link_global");
+        assert_eq!(res.len(), 1);
+        assert_eq!(res[0], Some(7));
+    }
+
+    #[test]
+    fn test_link_clash() {
+        let res = run("test_link_clash");
+        assert_eq!(res.len(), 1);
+        assert_eq!(res[0], Some(1));
+    }
+
+    #[test]
+    fn test_link_internal() {
+        let res = run("test_link_internal");
+        assert_eq!(res.len(), 1);
+        assert_eq!(res[0], Some(100));
+    }
+
+    #[test]
+    fn test_no_modules() {
+        let paths: [&str; 0] = [];
+        let result = Project::from_paths(paths);
+        assert!(matches!(result, Err(LLVMExecutorError::NoModules)));
+    }
+}
@@ -12,10 +12,23 @@ use tracing::{debug, trace, warn};
 
 use super::{binop, bit_size, project::Project};
 use crate::{
-    memory::ObjectMemory,
-    smt::{DContext, DExpr, DSolver},
+    smt::{fp, DContext, DExpr, DSolver},
     util::Variable,
-    vm::{executor::convert_to_map, LLVMExecutorError, Result},
+    vm::{
+        executor::{
+            convert_to_map,
+            extract_element,
+            fp_binop,
+            fp_cast,
+            fp_compare,
+            fp_format,
+            insert_element,
+        },
+        LLVMExecutorError,
+        LLVMMemory,
+        MemoryModel,
+        Result,
+    },
 };
 
 /// Stack frame keeps track of information related to a specific stack frame.
@@ -28,6 +41,10 @@ pub struct StackFrame {
     registers: HashMap<Value, DExpr>,
 
     location: Location,
+
+    /// Addresses of the objects allocated with `alloca` in this frame, these
+    /// are freed when the frame is popped.
+    allocations: Vec<u64>,
 }
 
 impl StackFrame {
@@ -40,6 +57,7 @@ impl StackFrame {
             function,
             registers: HashMap::new(),
             location: Location::new(basic_block)?,
+            allocations: Vec::new(),
         })
     }
 
@@ -61,6 +79,7 @@ impl StackFrame {
             function,
             registers,
             location: Location::new(basic_block)?,
+            allocations: Vec::new(),
         })
     }
 
@@ -94,6 +113,21 @@ impl StackFrame {
     pub fn increase_pc(&mut self) {
         self.location.increase_pc();
     }
+
+    /// Record a stack allocation made in this frame.
+    pub fn push_allocation(&mut self, address: u64) {
+        self.allocations.push(address);
+    }
+
+    pub fn num_allocations(&self) -> usize {
+        self.allocations.len()
+    }
+
+    /// Remove the allocations made after the first `len`, returning their
+    /// addresses.
+    pub fn pop_allocations(&mut self, len: usize) -> Vec<u64> {
+        self.allocations.split_off(len.min(self.allocations.len()))
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -156,7 +190,7 @@ pub struct LLVMState {
     /// List of variables marked as symbolic.
     pub marked_symbolic: Vec<Variable>,
 
-    pub memory: ObjectMemory,
+    pub memory: LLVMMemory,
 
     pub stack_frames: Vec<StackFrame>,
 
@@ -167,6 +201,9 @@ pub struct LLVMState {
     pub global_lookup_rev: HashMap<u64, Value>,
     pub global_lookup: HashMap<Value, u64>,
     pub init_global: HashSet<u64>,
+
+    /// Exception being unwound, read by the landing pad that catches it.
+    pub exception: Option<DExpr>,
 }
 
 impl std::fmt::Debug for LLVMState {
@@ -186,8 +223,9 @@ impl LLVMState {
         project: &'static Project,
         constraints: DSolver,
         function: Function,
+        memory_model: MemoryModel,
     ) -> Result<Self> {
-        let memory = ObjectMemory::new(ctx, project.ptr_size, constraints.clone());
+        let memory = LLVMMemory::new(memory_model, ctx, project.ptr_size, constraints.clone());
 
         let stack_frame = StackFrame::new(function)?;
         Ok(Self {
@@ -200,6 +238,7 @@ impl LLVMState {
             global_lookup: HashMap::new(),
             global_lookup_rev: HashMap::new(),
             init_global: HashSet::new(),
+            exception: None,
         })
     }
 
@@ -337,7 +376,10 @@ pub(crate) fn const_to_expr_zero_size(
             Ok(Some(state.ctx.from_u64(constant.value(), bits)))
         }
 
-        Constant::Float(_) => todo!("const fp"),
+        Constant::Float(constant) => {
+            let format = fp_format(&ty)?;
+            Ok(Some(fp::from_f64(state.ctx, format, constant.value())))
+        }
 
         Constant::Array(array) => {
             let elements = array
@@ -378,12 +420,12 @@ pub(crate) fn const_to_expr_zero_size(
                 let operation = |value: DExpr, target_size: u32| value.sign_ext(target_size);
                 convert_to_map(state, i.value(), &i.to_type(), operation)
             }
-            Expression::FPTrunc(_) => todo!(),
-            Expression::FPExt(_) => todo!(),
-            Expression::FPToUI(_) => todo!(),
-            Expression::FPToSI(_) => todo!(),
-            Expression::UIToFP(_) => todo!(),
-            Expression::SIToFP(_) => todo!(),
+            Expression::FPTrunc(i) => fp_cast(state, i.value(), &i.to_type(), false),
+            Expression::FPExt(i) => fp_cast(state, i.value(), &i.to_type(), false),
+            Expression::FPToUI(i) => fp_cast(state, i.value(), &i.to_type(), false),
+            Expression::FPToSI(i) => fp_cast(state, i.value(), &i.to_type(), true),
+            Expression::UIToFP(i) => fp_cast(state, i.value(), &i.to_type(), false),
+            Expression::SIToFP(i) => fp_cast(state, i.value(), &i.to_type(), true),
             Expression::PtrToInt(i) => {
                 let operation = |value: DExpr, target_size: u32| value.resize_unsigned(target_size);
                 convert_to_map(state, i.value(), &i.to_type(), operation)
@@ -410,16 +452,27 @@ pub(crate) fn const_to_expr_zero_size(
                 };
                 binop(state, &i.lhs(), &i.rhs(), f)
             }
-            Expression::FCmp(_) => todo!(),
-            Expression::ExtractElement(_) => todo!(),
-            Expression::InsertElement(_) => todo!(),
-            Expression::ShuffleVector(_) => todo!(),
+            Expression::FCmp(i) => {
+                let format = fp_format(&i.lhs().ty())?;
+                let ctx = state.ctx;
+                let f = |lhs: &DExpr, rhs: &DExpr| fp_compare(ctx, format, i.predicate(), lhs, rhs);
+                binop(state, &i.lhs(), &i.rhs(), f)
+            }
+            Expression::ExtractElement(i) => extract_element(state, &i.aggregate(), &i.index()),
+            Expression::InsertElement(i) => {
+                insert_element(state, &i.aggregate(), &i.element(), &i.index())
+            }
+            // The C API can only read the mask of shufflevector instructions, LLVM folds those with
+            // constant operands so the expression form is rare.
+            Expression::ShuffleVector(_) => Err(LLVMExecutorError::UnsupportedInstruction(
+                "shufflevector constant expression".to_owned(),
+            )),
             Expression::Add(i) => binop(state, &i.lhs(), &i.rhs(), DExpr::add),
-            Expression::FAdd(_) => todo!(),
+            Expression::FAdd(i) => fp_binop(state, &i.lhs(), &i.rhs(), fp::add),
             Expression::Sub(i) => binop(state, &i.lhs(), &i.rhs(), DExpr::sub),
-            Expression::FSub(_) => todo!(),
+            Expression::FSub(i) => fp_binop(state, &i.lhs(), &i.rhs(), fp::sub),
             Expression::Mul(i) => binop(state, &i.lhs(), &i.rhs(), DExpr::mul),
-            Expression::FMul(_) => todo!(),
+            Expression::FMul(i) => fp_binop(state, &i.lhs(), &i.rhs(), fp::mul),
             Expression::Shl(i) => binop(state, &i.lhs(), &i.rhs(), DExpr::sll),
             Expression::LShr(i) => binop(state, &i.lhs(), &i.rhs(), DExpr::srl),
             Expression::AShr(i) => binop(state, &i.lhs(), &i.rhs(), DExpr::sra),
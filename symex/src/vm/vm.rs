@@ -1,18 +1,22 @@
-use llvm_ir::{Global, GlobalValue, Value};
+use std::collections::VecDeque;
+
+use llvm_ir::{instruction::Instruction, Argument, Function, Global, GlobalValue, Type, Value};
 use tracing::trace;
 
 use super::{
     path_selection::{DFSPathSelection, Path},
     project::Project,
-    state::LLVMState,
+    state::{LLVMState, StackFrame},
+    Config,
     LLVMExecutor,
     LLVMExecutorError,
     PathResult,
 };
 use crate::{
-    smt::{DContext, DSolver},
-    util::Variable,
-    vm::bit_size,
+    memory::BITS_IN_BYTE,
+    smt::{DContext, DExpr, DSolver},
+    util::{ExpressionType, Variable},
+    vm::{bit_size, expression_type},
 };
 
 pub struct VM {
@@ -21,6 +25,8 @@ pub struct VM {
     pub(crate) paths: DFSPathSelection,
 
     pub inputs: Vec<Variable>,
+
+    pub cfg: Config,
 }
 
 impl VM {
@@ -28,24 +34,36 @@ impl VM {
         project: &'static Project,
         ctx: &'static DContext,
         fn_name: &str,
+    ) -> Result<Self, LLVMExecutorError> {
+        Self::new_with_config(project, ctx, fn_name, Config::new())
+    }
+
+    /// Create a VM that starts execution in `fn_name`.
+    ///
+    /// The parameters of the function are created as named symbolic inputs,
+    /// pointers get a symbolic pointee.
+    pub fn new_with_config(
+        project: &'static Project,
+        ctx: &'static DContext,
+        fn_name: &str,
+        cfg: Config,
     ) -> Result<Self, LLVMExecutorError> {
         let function = project.find_entry_function(fn_name)?;
-        if function.parameters().count() > 0 {
-            panic!(
-                "Function {:?} has parameters which isn't allowed",
-                function.name()
-            );
-        }
 
+        let memory_model = cfg.memory_model;
         let mut vm = Self {
             project,
             paths: DFSPathSelection::new(),
             inputs: Vec::new(),
+            cfg,
         };
 
         let solver = DSolver::new(ctx);
-        let mut state = LLVMState::new(ctx, project, solver, function)?;
+        let mut state = LLVMState::new(ctx, project, solver, function.clone(), memory_model)?;
         vm.initialize_global_references(&mut state)?;
+
+        let arguments = vm.create_arguments(&mut state, &function)?;
+        state.stack_frames = vec![StackFrame::new_from_function(function, &arguments)?];
         vm.paths.save_path(Path::new(state, None));
 
         Ok(vm)
@@ -120,4 +138,334 @@ impl VM {
 
         Ok(())
     }
+
+    /// Create symbolic values for the parameters of the entry function.
+    ///
+    /// Every parameter becomes a named input, using the source names from the
+    /// debug info when available. Pointers get a freshly allocated symbolic
+    /// pointee, and a pointer followed by a length that belong to the same
+    /// source variable is treated as a slice of [`Config::slice_len`]
+    /// elements.
+    fn create_arguments(
+        &mut self,
+        state: &mut LLVMState,
+        function: &Function,
+    ) -> Result<Vec<DExpr>, LLVMExecutorError> {
+        let parameters = function
+            .parameters()
+            .map(|parameter| match parameter {
+                Value::Argument(argument) => argument,
+                _ => unreachable!("parameters are arguments"),
+            })
+            .collect::<Vec<_>>();
+        let names = argument_names(function, &parameters);
+
+        let mut arguments = Vec::new();
+        let mut index = 0;
+        while index < parameters.len() {
+            let parameter = &parameters[index];
+            let ty = parameter.ty();
+
+            // Slices are passed as a data pointer and a length.
+            if let (Some(name), Some(len)) = (&names[index], parameters.get(index + 1)) {
+                let is_len =
+                    matches!(len.ty(), Type::Integer(t) if t.bits() == self.project.ptr_size);
+                if ty.is_pointer() && is_len && names[index + 1].as_ref() == Some(name) {
+                    let (data, len) = self.create_slice(state, function, parameter, name)?;
+                    arguments.extend([data, len]);
+                    index += 2;
+                    continue;
+                }
+            }
+
+            // Values split over multiple parameters get the index of the part.
+            let name = match &names[index] {
+                Some(name) if names.iter().filter(|n| n.as_ref() == Some(name)).count() > 1 => {
+                    let part = names[..index]
+                        .iter()
+                        .filter(|n| n.as_ref() == Some(name))
+                        .count();
+                    format!("{name}.{part}")
+                }
+                Some(name) => name.clone(),
+                None => match parameter.name() {
+                    Some(name) => name.to_string_lossy().into_owned(),
+                    None => format!("arg{index}"),
+                },
+            };
+
+            let argument = match ty.is_pointer() {
+                true => {
+                    let pointers = [(function.clone(), Value::Argument(parameter.clone()))];
+                    let size = parameter.dereferenceable_bytes().unwrap_or(0) * BITS_IN_BYTE as u64;
+                    self.create_pointer(state, &pointers, size, &name, 1)?
+                }
+                false => self.create_value(state, &ty, &name),
+            };
+            arguments.push(argument);
+            index += 1;
+        }
+
+        Ok(arguments)
+    }
+
+    /// Create an unconstrained input value of type `ty`.
+    fn create_value(&mut self, state: &LLVMState, ty: &Type, name: &str) -> DExpr {
+        let bits = bit_size(ty, self.project.ptr_size).unwrap_or(self.project.ptr_size);
+        let value = state.ctx.unconstrained(bits, name);
+        self.inputs.push(Variable {
+            name: Some(name.to_owned()),
+            value: value.clone(),
+            ty: expression_type(ty, self.project.ptr_size),
+        });
+        value
+    }
+
+    /// Create a pointer to a symbolic pointee.
+    ///
+    /// With opaque pointers the pointee type is taken from how memory is
+    /// accessed through `pointers`, in the functions they are used in and the
+    /// functions they are passed to. The pointee is large enough for the
+    /// largest access and at least `size` bits. A pointee that is itself a
+    /// pointer gets a pointee of its own, up to a depth of
+    /// [`Config::max_pointee_depth`]. Past that the pointer is
+    /// left unconstrained.
+    fn create_pointer(
+        &mut self,
+        state: &mut LLVMState,
+        pointers: &[(Function, Value)],
+        size: u64,
+        name: &str,
+        depth: usize,
+    ) -> Result<DExpr, LLVMExecutorError> {
+        let ptr_size = self.project.ptr_size;
+        if depth > self.cfg.max_pointee_depth {
+            return Ok(state.ctx.unconstrained(ptr_size, name));
+        }
+
+        let uses = pointer_uses(pointers, ptr_size);
+        let ty_size = match &uses.ty {
+            Some(ty) => bit_size(ty, ptr_size)? as u64,
+            None => 0,
+        };
+        let size = size.max(uses.size).max(BITS_IN_BYTE as u64);
+        let address = state
+            .memory
+            .allocate(size, self.project.default_alignment as u64)?;
+        let address = state.ctx.from_u64(address, ptr_size);
+
+        let name = format!("*{name}");
+        let pointee = match uses.ty {
+            Some(Type::Pointer(_)) if size == ptr_size as u64 => {
+                self.create_pointer(state, &uses.loaded, 0, &name, depth + 1)?
+            }
+            ty => {
+                let value = state.ctx.unconstrained(size as u32, &name);
+                let ty = match ty {
+                    Some(ty) if ty_size == size => expression_type(&ty, ptr_size),
+                    _ => ExpressionType::Unknown,
+                };
+                self.inputs.push(Variable {
+                    name: Some(name),
+                    value: value.clone(),
+                    ty,
+                });
+                value
+            }
+        };
+        state.memory.write(&address, pointee)?;
+
+        Ok(address)
+    }
+
+    /// Create a slice of [`Config::slice_len`] symbolic elements, returning the
+    /// data pointer and the length.
+    ///
+    /// The length is symbolic but at most the number of allocated elements.
+    fn create_slice(
+        &mut self,
+        state: &mut LLVMState,
+        function: &Function,
+        data: &Argument,
+        name: &str,
+    ) -> Result<(DExpr, DExpr), LLVMExecutorError> {
+        let ptr_size = self.project.ptr_size;
+        let max_len = self.cfg.slice_len;
+
+        let pointers = [(function.clone(), Value::Argument(data.clone()))];
+        let element_ty = pointer_uses(&pointers, ptr_size).ty;
+        let (element_size, element_ty) = match element_ty {
+            Some(ty) => (bit_size(&ty, ptr_size)?, expression_type(&ty, ptr_size)),
+            None => (BITS_IN_BYTE, ExpressionType::Integer(BITS_IN_BYTE as usize)),
+        };
+
+        let size = (element_size as u64 * max_len as u64).max(BITS_IN_BYTE as u64);
+        let address = state
+            .memory
+            .allocate(size, self.project.default_alignment as u64)?;
+        let address = state.ctx.from_u64(address, ptr_size);
+
+        let elements = state.ctx.unconstrained(size as u32, name);
+        state.memory.write(&address, elements.clone())?;
+        self.inputs.push(Variable {
+            name: Some(name.to_owned()),
+            value: elements,
+            ty: ExpressionType::Array(Box::new(element_ty), max_len),
+        });
+
+        let len_name = format!("{name}.len");
+        let len = state.ctx.unconstrained(ptr_size, &len_name);
+        let max = state.ctx.from_u64(max_len as u64, ptr_size);
+        state.constraints.assert(&len.ulte(&max));
+        self.inputs.push(Variable {
+            name: Some(len_name),
+            value: len.clone(),
+            ty: ExpressionType::Integer(ptr_size as usize),
+        });
+
+        Ok((address, len))
+    }
+}
+
+/// Source names of the parameters from the debug info, `None` for parameters
+/// without one.
+///
+/// Debug info either refers to the argument itself, or to the stack slot the
+/// argument is stored to on entry. Arguments stored to fields of the same slot
+/// are parts of the same variable.
+fn argument_names(function: &Function, parameters: &[Argument]) -> Vec<Option<String>> {
+    let mut variables = Vec::new();
+    let mut stores = Vec::new();
+    for block in function.basic_blocks() {
+        for instruction in block.instructions() {
+            match instruction {
+                Instruction::Call(call) => variables.extend(call.debug_variable()),
+                Instruction::Store(store) => stores.push(store),
+                _ => {}
+            }
+        }
+    }
+
+    let mut names = vec![None; parameters.len()];
+    for variable in variables {
+        for (parameter, name) in parameters.iter().zip(names.iter_mut()) {
+            let argument = Value::Argument(parameter.clone());
+            let is_stored = || {
+                stores.iter().any(|store| {
+                    store.value() == argument && base_address(store.address()) == variable.value
+                })
+            };
+            if name.is_none() && (variable.value == argument || is_stored()) {
+                *name = Some(variable.name.clone());
+            }
+        }
+    }
+    names
+}
+
+/// Address with all field offsets removed.
+fn base_address(address: Value) -> Value {
+    match address {
+        Value::Instruction(Instruction::GetElementPtr(gep)) => base_address(gep.address()),
+        address => address,
+    }
+}
+
+/// How memory is accessed through a set of pointers.
+struct PointerUses {
+    /// Type of the first access through the pointers.
+    ty: Option<Type>,
+
+    /// Size in bits of the largest access through the pointers.
+    size: u64,
+
+    /// Pointers loaded through the pointers, with the function they are loaded
+    /// in.
+    loaded: Vec<(Function, Value)>,
+}
+
+impl PointerUses {
+    /// Record an access of type `ty`, accesses of unsized types only give the
+    /// type.
+    fn access(&mut self, ty: Type, ptr_size: u32) {
+        let size = bit_size(&ty, ptr_size).map_or(0, |size| size as u64);
+        self.size = self.size.max(size);
+        self.ty.get_or_insert(ty);
+    }
+}
+
+/// Find how the functions use `pointers`, following the pointers through the
+/// stack slots they are spilled to and into the functions they are passed to.
+fn pointer_uses(pointers: &[(Function, Value)], ptr_size: u32) -> PointerUses {
+    let mut uses = PointerUses {
+        ty: None,
+        size: 0,
+        loaded: Vec::new(),
+    };
+
+    let mut pending: VecDeque<_> = pointers.iter().cloned().collect();
+    let mut visited = Vec::new();
+    while let Some((function, pointer)) = pending.pop_front() {
+        if visited.contains(&(function.clone(), pointer.clone())) {
+            continue;
+        }
+        visited.push((function.clone(), pointer.clone()));
+
+        let mut pointers = vec![pointer];
+        let mut spills = Vec::new();
+        for block in function.basic_blocks() {
+            for instruction in block.instructions() {
+                match &instruction {
+                    Instruction::Store(store) if pointers.contains(&store.value()) => {
+                        if let address @ Value::Instruction(Instruction::Alloca(_)) =
+                            store.address()
+                        {
+                            spills.push(address);
+                        }
+                    }
+                    Instruction::Store(store) if pointers.contains(&store.address()) => {
+                        uses.access(store.value().ty(), ptr_size);
+                    }
+                    Instruction::Load(load) if spills.contains(&load.address()) => {
+                        pointers.push(Value::Instruction(instruction.clone()));
+                    }
+                    Instruction::Load(load) if pointers.contains(&load.address()) => {
+                        let ty = instruction.result_type();
+                        if ty.is_pointer() {
+                            let loaded = Value::Instruction(instruction.clone());
+                            uses.loaded.push((function.clone(), loaded));
+                        }
+                        uses.access(ty, ptr_size);
+                    }
+                    Instruction::GetElementPtr(gep) if pointers.contains(&gep.address()) => {
+                        uses.access(gep.source_element_type(), ptr_size);
+                    }
+                    Instruction::Call(call) => {
+                        let arguments = call.arguments();
+                        pending.extend(passed_to(call.called_value(), &arguments, &pointers));
+                    }
+                    Instruction::Invoke(invoke) => {
+                        let arguments = invoke.arguments();
+                        pending.extend(passed_to(invoke.called_value(), &arguments, &pointers));
+                    }
+                    _ => {}
+                }
+            }
+        }
+    }
+    uses
+}
+
+/// The parameters of the function called by `called` with `arguments` that
+/// get one of `pointers`.
+fn passed_to(called: Value, arguments: &[Value], pointers: &[Value]) -> Vec<(Function, Value)> {
+    let Value::Function(callee) = called else {
+        return Vec::new();
+    };
+    callee
+        .parameters()
+        .zip(arguments)
+        .filter(|(_, argument)| pointers.contains(argument))
+        .map(|(parameter, _)| (callee.clone(), parameter))
+        .collect()
 }
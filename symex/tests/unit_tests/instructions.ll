@@ -5,15 +5,21 @@ target triple = "x86_64-pc-linux-gnu"
 ; --------------------------------------------------------------------------------------------------
 ; Unary Operations
 ;
-; Unsupported: fneg
+; fneg
 ; --------------------------------------------------------------------------------------------------
 
+; Test fneg
+define dso_local i32 @test_fneg() #0 {
+    %1 = fneg float 1.5
+    %2 = bitcast float %1 to i32
+    ret i32 %2 ; expect -1.5 = 0xBFC00000
+}
+
 
 ; --------------------------------------------------------------------------------------------------
 ; Binary Operations
 ;
-; add, sub, mul, udiv, sdiv, urem, srem
-; Unsupported: fadd, fsub, fmul, fdiv, frem
+; add, sub, mul, udiv, sdiv, urem, srem, fadd, fsub, fmul, fdiv, frem
 ; --------------------------------------------------------------------------------------------------
 
 ; Test add
@@ -80,6 +86,55 @@ define dso_local i64 @test_srem2() #0 {
     ret i64 %1 ; expect -3
 }
 
+; Test fadd, the result is rounded to the nearest double.
+define dso_local i64 @test_fadd() #0 {
+    %1 = fadd double 0.1, 0.2
+    %2 = bitcast double %1 to i64
+    ret i64 %2 ; expect 0.30000000000000004 = 0x3FD3333333333334
+}
+
+; Test fadd vectors
+define dso_local i64 @test_fadd_vector() #0 {
+    %1 = fadd <2 x float> <float 1.0, float 2.0>, <float 0.5, float 0.25>
+    %2 = bitcast <2 x float> %1 to i64
+    ret i64 %2 ; expect <1.5, 2.25> = <0x3FC00000, 0x40100000>
+}
+
+; Test fsub
+define dso_local i32 @test_fsub() #0 {
+    %1 = fsub float 0.25, 3.0
+    %2 = bitcast float %1 to i32
+    ret i32 %2 ; expect -2.75 = 0xC0300000
+}
+
+; Test fmul
+define dso_local i64 @test_fmul() #0 {
+    %1 = fmul double 1.5, -4.0
+    %2 = bitcast double %1 to i64
+    ret i64 %2 ; expect -6.0 = 0xC018000000000000
+}
+
+; Test fdiv
+define dso_local i32 @test_fdiv() #0 {
+    %1 = fdiv float 1.0, 3.0
+    %2 = bitcast float %1 to i32
+    ret i32 %2 ; expect 0.33333334 = 0x3EAAAAAB
+}
+
+; Test fdiv by zero
+define dso_local i64 @test_fdiv_zero() #0 {
+    %1 = fdiv double 1.0, 0.0
+    %2 = bitcast double %1 to i64
+    ret i64 %2 ; expect inf = 0x7FF0000000000000
+}
+
+; Test frem
+define dso_local i64 @test_frem() #0 {
+    %1 = frem double 7.5, 2.0
+    %2 = bitcast double %1 to i64
+    ret i64 %2 ; expect 1.5 = 0x3FF8000000000000
+}
+
 ; --------------------------------------------------------------------------------------------------
 ; Bitwise Binary Operations
 ;
@@ -124,9 +179,47 @@ define dso_local i8 @test_ashr2() #0 {
 ; --------------------------------------------------------------------------------------------------
 ; Vector Operations
 ;
-; Unsupported: extractelement, insertelement, shufflevector
+; extractelement, insertelement, shufflevector
 ; --------------------------------------------------------------------------------------------------
 
+define dso_local i32 @test_extract_element() #0 {
+    %1 = extractelement <4 x i32> <i32 1, i32 2, i32 3, i32 4>, i32 2
+    ret i32 %1 ; expect 3
+}
+
+; The index is symbolic, but all elements are the same.
+define dso_local i32 @test_extract_element_symbolic() #0 {
+    %index = freeze i32 undef
+    %1 = extractelement <4 x i32> <i32 7, i32 7, i32 7, i32 7>, i32 %index
+    ret i32 %1 ; expect 7
+}
+
+define dso_local i32 @test_insert_element() #0 {
+    %1 = insertelement <4 x i8> <i8 1, i8 2, i8 3, i8 4>, i8 9, i32 1
+    %2 = bitcast <4 x i8> %1 to i32
+    ret i32 %2 ; expect <1, 9, 3, 4> -> 0x04030901
+}
+
+define dso_local i16 @test_insert_element_symbolic() #0 {
+    %index = freeze i32 undef
+    %1 = insertelement <2 x i8> <i8 5, i8 5>, i8 5, i32 %index
+    %2 = bitcast <2 x i8> %1 to i16
+    ret i16 %2 ; expect 0x0505
+}
+
+define dso_local i32 @test_shuffle_vector() #0 {
+    %1 = shufflevector <4 x i8> <i8 1, i8 2, i8 3, i8 4>, <4 x i8> <i8 5, i8 6, i8 7, i8 8>, <4 x i32> <i32 0, i32 7, i32 2, i32 4>
+    %2 = bitcast <4 x i8> %1 to i32
+    ret i32 %2 ; expect <1, 8, 3, 5> -> 0x05030801
+}
+
+; Splat the first element, the usual way to broadcast a scalar.
+define dso_local i64 @test_shuffle_vector_splat() #0 {
+    %1 = shufflevector <2 x i16> <i16 u0x1234, i16 u0x5678>, <2 x i16> undef, <4 x i32> zeroinitializer
+    %2 = bitcast <4 x i16> %1 to i64
+    ret i64 %2 ; expect 0x1234123412341234
+}
+
 ; --------------------------------------------------------------------------------------------------
 ; Aggregate Operations
 ;
@@ -345,11 +438,78 @@ define dso_local i32 @test_gep_arr2() #0 {
     ret i32 %2 ; expect 2 ([2])
 }
 
+declare void @llvm.assume(i1)
+
+; The number of elements is symbolic, a path is created for each possible size.
+define dso_local i64 @test_alloca_symbolic(i64 %n) #0 {
+    %last = sub i64 %n, 1
+    %1 = icmp ult i64 %last, 3
+    call void @llvm.assume(i1 %1) ; 1 <= n <= 3
+    %ptr = alloca i32, i64 %n, align 4
+    %2 = getelementptr inbounds i32, ptr %ptr, i64 %last
+    store i32 7, ptr %2
+    ret i64 %n ; expect 1, 2 and 3
+}
+
+define internal ptr @local_address() #0 {
+    %ptr = alloca i32, align 4
+    store i32 1, ptr %ptr
+    ret ptr %ptr
+}
+
+; Stack allocations are freed when the function returns.
+define dso_local i32 @test_use_after_return() #0 {
+    %ptr = call ptr @local_address()
+    %1 = load i32, ptr %ptr
+    ret i32 %1 ; expect use after free
+}
+
+declare ptr @__rust_alloc(i64, i64)
+declare void @__rust_dealloc(ptr, i64, i64)
+
+define dso_local i32 @test_heap() #0 {
+    %ptr = call ptr @__rust_alloc(i64 4, i64 4)
+    store i32 5, ptr %ptr
+    %1 = load i32, ptr %ptr
+    call void @__rust_dealloc(ptr %ptr, i64 4, i64 4)
+    ret i32 %1 ; expect 5
+}
+
+; The heap errors have debug locations, so the allocation site and the location of the error can be
+; checked.
+define dso_local i32 @test_heap_use_after_free() #0 !dbg !110 {
+    %ptr = call ptr @__rust_alloc(i64 4, i64 4), !dbg !111
+    store i32 5, ptr %ptr
+    call void @__rust_dealloc(ptr %ptr, i64 4, i64 4), !dbg !112
+    %1 = load i32, ptr %ptr, !dbg !113
+    ret i32 %1 ; expect use after free
+}
+
+define dso_local void @test_heap_double_free() #0 !dbg !114 {
+    %ptr = call ptr @__rust_alloc(i64 4, i64 4), !dbg !115
+    call void @__rust_dealloc(ptr %ptr, i64 4, i64 4)
+    call void @__rust_dealloc(ptr %ptr, i64 4, i64 4), !dbg !116
+    ret void ; expect double free
+}
+
+define dso_local void @test_heap_mismatched_dealloc() #0 !dbg !117 {
+    %ptr = call ptr @__rust_alloc(i64 4, i64 4), !dbg !118
+    call void @__rust_dealloc(ptr %ptr, i64 8, i64 4), !dbg !119
+    ret void ; expect mismatched size
+}
+
+define dso_local void @test_heap_out_of_bounds() #0 !dbg !120 {
+    %ptr = call ptr @__rust_alloc(i64 8, i64 4), !dbg !121
+    %1 = getelementptr inbounds i32, ptr %ptr, i64 2
+    store i32 1, ptr %1, !dbg !122
+    ret void ; expect out of bounds
+}
+
 ; --------------------------------------------------------------------------------------------------
 ; Conversion Operations
 ;
-; trunc, zext, sext, ptrtoint, inttoptr, bitcast, addrspacecast
-; Unsupported: fptrunc, fpext, fptoui, fptosi, uitpfp, sitofp
+; trunc, zext, sext, fptrunc, fpext, fptoui, fptosi, uitofp, sitofp, ptrtoint, inttoptr, bitcast,
+; addrspacecast
 ; --------------------------------------------------------------------------------------------------
 
 define dso_local i8 @test_trunc() #0 {
@@ -372,6 +532,40 @@ define dso_local i16 @test_sext() #0 {
     ret i16 %1 ; expect: 0xFFFF
 }
 
+define dso_local i32 @test_fptrunc() #0 {
+    %1 = fptrunc double 0.1 to float
+    %2 = bitcast float %1 to i32
+    ret i32 %2 ; expect: 0x3DCCCCCD
+}
+
+define dso_local i64 @test_fpext() #0 {
+    %1 = fpext float -2.75 to double
+    %2 = bitcast double %1 to i64
+    ret i64 %2 ; expect: 0xC006000000000000
+}
+
+define dso_local i16 @test_fptoui() #0 {
+    %1 = fptoui double 300.75 to i16
+    ret i16 %1 ; expect: 300
+}
+
+define dso_local i64 @test_fptosi() #0 {
+    %1 = fptosi double -7.75 to i64
+    ret i64 %1 ; expect: -7
+}
+
+define dso_local i32 @test_uitofp() #0 {
+    %1 = uitofp i32 4294967295 to float
+    %2 = bitcast float %1 to i32
+    ret i32 %2 ; expect: 4294967296.0 = 0x4F800000
+}
+
+define dso_local i64 @test_sitofp() #0 {
+    %1 = sitofp i64 -3 to double
+    %2 = bitcast double %1 to i64
+    ret i64 %2 ; expect: -3.0 = 0xC008000000000000
+}
+
 ; on the current architecture the pointer size is 64 bits.
 
 ; inttoptr
@@ -436,8 +630,8 @@ define dso_local i32 addrspace(1)* @test_addrspacecast() #0 {
 ; --------------------------------------------------------------------------------------------------
 ; Other Operations
 ;
-; icmp, phi, select, call
-; Unsupported: fcmp, freeze, va_arg, landingpad, catchpad, cleanuppad
+; icmp, fcmp, phi, select, freeze, call, landingpad, catchpad, cleanuppad
+; Unsupported: va_arg
 ; --------------------------------------------------------------------------------------------------
 
 ; eq: lhs == rhs
@@ -556,6 +750,42 @@ define dso_local <3 x i1> @test_icmp_eq_vec() #0 {
     ret <3 x i1> %1 ; expect: <0x1, 0x0, 0x1> -> 0b0101 -> 0x5
 }
 
+; olt: ordered and lhs < rhs
+define dso_local i1 @test_fcmp_olt() #0 {
+    %1 = fcmp olt double -0.5, 0.25
+    ret i1 %1 ; expect: 0x1
+}
+
+; oeq: both zeros are equal
+define dso_local i1 @test_fcmp_oeq_zero() #0 {
+    %1 = fcmp oeq float 0.0, -0.0
+    ret i1 %1 ; expect: 0x1
+}
+
+; ogt: NaN is unordered
+define dso_local i1 @test_fcmp_ogt_nan() #0 {
+    %1 = fcmp ogt double 0x7FF8000000000000, 1.0
+    ret i1 %1 ; expect: 0x0
+}
+
+; uno: either operand is NaN
+define dso_local i1 @test_fcmp_uno() #0 {
+    %1 = fcmp uno double 0x7FF8000000000000, 1.0
+    ret i1 %1 ; expect: 0x1
+}
+
+; une: NaN is not equal to itself
+define dso_local i1 @test_fcmp_une_nan() #0 {
+    %1 = fcmp une double 0x7FF8000000000000, 0x7FF8000000000000
+    ret i1 %1 ; expect: 0x1
+}
+
+; freeze
+define dso_local i32 @test_freeze() #0 {
+    %1 = freeze i32 10
+    ret i32 %1 ; expect 10
+}
+
 ; phi
 
 define dso_local i32 @test_phi1() #0 {
@@ -629,6 +859,163 @@ define dso_local i32 @test_call() #0 {
     ret i32 %1 ; expect 0xabcd
 }
 
+; --------------------------------------------------------------------------------------------------
+; Unwinding
+;
+; invoke, resume
+; --------------------------------------------------------------------------------------------------
+
+; core::panicking::panic, hooked as a panic.
+declare void @_ZN4core9panicking5panic17h2c5f1b0e8d3a7c94E(ptr, i64, ptr)
+
+declare i32 @rust_eh_personality(...)
+
+define dso_local i32 @test_invoke() #0 personality ptr @rust_eh_personality {
+    %1 = invoke i32 @test_call_called_function()
+        to label %normal unwind label %cleanup
+normal:
+    ret i32 %1 ; expect 0xabcd
+cleanup:
+    %2 = landingpad { ptr, i32 } cleanup
+    ret i32 0
+}
+
+define dso_local i32 @test_invoke_panic() #0 personality ptr @rust_eh_personality {
+    invoke void @_ZN4core9panicking5panic17h2c5f1b0e8d3a7c94E(ptr null, i64 0, ptr null)
+        to label %normal unwind label %cleanup
+normal:
+    ret i32 0
+cleanup:
+    %1 = landingpad { ptr, i32 } cleanup
+    ret i32 1 ; expect 1
+}
+
+; Panics without a landing pad of its own.
+define dso_local void @test_unwind_call_panics() personality ptr @rust_eh_personality {
+    call void @_ZN4core9panicking5panic17h2c5f1b0e8d3a7c94E(ptr null, i64 0, ptr null)
+    unreachable
+}
+
+define dso_local i32 @test_unwind_through_call() #0 personality ptr @rust_eh_personality {
+    invoke void @test_unwind_call_panics()
+        to label %normal unwind label %cleanup
+normal:
+    ret i32 0
+cleanup:
+    %1 = landingpad { ptr, i32 } cleanup
+    ret i32 1 ; expect 1
+}
+
+; Runs "drop glue" that writes to the pointer before resuming the unwind, the exception is
+; passed on to the caller.
+define dso_local void @test_unwind_drop_glue(ptr %0) personality ptr @rust_eh_personality {
+    invoke void @_ZN4core9panicking5panic17h2c5f1b0e8d3a7c94E(ptr null, i64 0, ptr null)
+        to label %normal unwind label %cleanup
+normal:
+    ret void
+cleanup:
+    %landing = landingpad { ptr, i32 } cleanup
+    store i32 u0x20, ptr %0
+    %exception = insertvalue { ptr, i32 } %landing, i32 u0x3, 1
+    resume { ptr, i32 } %exception
+}
+
+define dso_local i32 @test_resume() #0 personality ptr @rust_eh_personality {
+    %1 = alloca i32
+    store i32 0, ptr %1
+    invoke void @test_unwind_drop_glue(ptr %1)
+        to label %normal unwind label %cleanup
+normal:
+    ret i32 0
+cleanup:
+    %2 = landingpad { ptr, i32 } cleanup
+    %3 = extractvalue { ptr, i32 } %2, 1
+    %4 = load i32, ptr %1
+    %5 = add i32 %3, %4
+    ret i32 %5 ; expect 0x23
+}
+
+; Unwinding out of the entry function ends the path in a panic.
+define dso_local i32 @test_resume_out_of_entry() #0 personality ptr @rust_eh_personality {
+    invoke void @_ZN4core9panicking5panic17h2c5f1b0e8d3a7c94E(ptr null, i64 0, ptr null)
+        to label %normal unwind label %cleanup
+normal:
+    ret i32 0
+cleanup:
+    %1 = landingpad { ptr, i32 } cleanup
+    resume { ptr, i32 } %1
+}
+
+; --------------------------------------------------------------------------------------------------
+; Entry function parameters
+; --------------------------------------------------------------------------------------------------
+
+define dso_local i32 @test_entry_parameters(i32 %a, i32 %b) #0 {
+    %1 = icmp ult i32 %a, %b
+    br i1 %1, label %less, label %not_less
+less:
+    ret i32 1 ; expect 1
+not_less:
+    ret i32 0 ; expect 0
+}
+
+; The pointee type is found from the load through the spilled pointer.
+define dso_local i32 @test_entry_pointer(ptr %0) #0 {
+    %p = alloca ptr
+    store ptr %0, ptr %p
+    %2 = load ptr, ptr %p
+    %3 = load i32, ptr %2
+    %4 = icmp eq i32 %3, 7
+    br i1 %4, label %seven, label %other
+seven:
+    ret i32 1 ; expect 1
+other:
+    ret i32 0 ; expect 0
+}
+
+define dso_local i32 @test_entry_nested_pointer(ptr dereferenceable(8) %0) #0 {
+    %2 = load ptr, ptr %0
+    %3 = load i32, ptr %2
+    %4 = icmp eq i32 %3, 7
+    br i1 %4, label %seven, label %other
+seven:
+    ret i32 1 ; expect 1
+other:
+    ret i32 0 ; expect 0
+}
+
+; The pointer is only used by a callee, the pointee is large enough for the load there.
+define dso_local i32 @test_entry_pointer_callee(ptr %0) #0 {
+    %2 = call i32 @read_pointee(ptr %0)
+    ret i32 %2
+}
+
+define dso_local i32 @read_pointee(ptr %0) #0 {
+    %2 = load i64, ptr %0
+    %3 = icmp eq i64 %2, 7
+    br i1 %3, label %seven, label %other
+seven:
+    ret i32 1 ; expect 1
+other:
+    ret i32 0 ; expect 0
+}
+
+; A slice `s: &[i32]` is passed as a data pointer and a length, the length is at most the number
+; of allocated elements.
+define dso_local i32 @test_entry_slice(ptr %0, i64 %1) #0 !dbg !103 {
+    call void @llvm.dbg.value(metadata ptr %0, metadata !107, metadata !DIExpression(DW_OP_LLVM_fragment, 0, 64)), !dbg !108
+    call void @llvm.dbg.value(metadata i64 %1, metadata !107, metadata !DIExpression(DW_OP_LLVM_fragment, 64, 64)), !dbg !108
+    %3 = icmp ugt i64 %1, 8
+    br i1 %3, label %too_long, label %ok
+too_long:
+    unreachable
+ok:
+    %4 = load i32, ptr %0
+    ret i32 1 ; expect 1
+}
+
+declare void @llvm.dbg.value(metadata, metadata, metadata)
+
 ; --------------------------------------------------------------------------------------------------
 ; Constants
 ; --------------------------------------------------------------------------------------------------
@@ -657,3 +1044,30 @@ declare void @assume(i32) #1
 
 attributes #0 = { noinline nounwind optnone sspstrong uwtable "frame-pointer"="all" "min-legal-vector-width"="0" "no-trapping-math"="true" "stack-protector-buffer-size"="8" "target-cpu"="x86-64" "target-features"="+cx8,+fxsr,+mmx,+sse,+sse2,+x87" "tune-cpu"="generic" }
 attributes #1 = { "frame-pointer"="all" "no-trapping-math"="true" "stack-protector-buffer-size"="8" "target-cpu"="x86-64" "target-features"="+cx8,+fxsr,+mmx,+sse,+sse2,+x87" "tune-cpu"="generic" }
+
+!llvm.dbg.cu = !{!100}
+!llvm.module.flags = !{!102}
+
+!100 = distinct !DICompileUnit(language: DW_LANG_Rust, file: !101, producer: "symex", isOptimized: false, runtimeVersion: 0, emissionKind: FullDebug)
+!101 = !DIFile(filename: "instructions.rs", directory: "")
+!102 = !{i32 2, !"Debug Info Version", i32 3}
+!103 = distinct !DISubprogram(name: "test_entry_slice", scope: !101, file: !101, line: 1, type: !104, scopeLine: 1, spFlags: DISPFlagDefinition, unit: !100)
+!104 = !DISubroutineType(types: !105)
+!105 = !{!106}
+!106 = !DIBasicType(name: "i32", size: 32, encoding: DW_ATE_signed)
+!107 = !DILocalVariable(name: "s", arg: 1, scope: !103, file: !101, line: 1, type: !109)
+!108 = !DILocation(line: 1, column: 1, scope: !103)
+!109 = !DICompositeType(tag: DW_TAG_structure_type, name: "&[i32]", file: !101, size: 128, align: 64, elements: !{})
+!110 = distinct !DISubprogram(name: "test_heap_use_after_free", scope: !101, file: !101, line: 10, type: !104, scopeLine: 10, spFlags: DISPFlagDefinition, unit: !100)
+!111 = !DILocation(line: 11, column: 5, scope: !110)
+!112 = !DILocation(line: 13, column: 5, scope: !110)
+!113 = !DILocation(line: 14, column: 5, scope: !110)
+!114 = distinct !DISubprogram(name: "test_heap_double_free", scope: !101, file: !101, line: 20, type: !104, scopeLine: 20, spFlags: DISPFlagDefinition, unit: !100)
+!115 = !DILocation(line: 21, column: 5, scope: !114)
+!116 = !DILocation(line: 23, column: 5, scope: !114)
+!117 = distinct !DISubprogram(name: "test_heap_mismatched_dealloc", scope: !101, file: !101, line: 30, type: !104, scopeLine: 30, spFlags: DISPFlagDefinition, unit: !100)
+!118 = !DILocation(line: 31, column: 5, scope: !117)
+!119 = !DILocation(line: 32, column: 5, scope: !117)
+!120 = distinct !DISubprogram(name: "test_heap_out_of_bounds", scope: !101, file: !101, line: 40, type: !104, scopeLine: 40, spFlags: DISPFlagDefinition, unit: !100)
+!121 = !DILocation(line: 41, column: 5, scope: !120)
+!122 = !DILocation(line: 43, column: 5, scope: !120)
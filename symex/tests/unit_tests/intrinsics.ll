@@ -49,6 +49,39 @@ define dso_local [4 x i16] @test_memcpy() #0 {
     ;   -> 0x6543fe671234abcd
 }
 
+define dso_local [4 x i16] @test_memcpy_symbolic_len() #0 {
+    %1 = alloca [4 x i16], align 4
+    %2 = alloca [4 x i16], align 4
+
+    ; [0xcd, 0xab, 0x34, 0x12, 0x67, 0x56, 0xbe, 0xbe]
+    store [4 x i16] [i16 u0xabcd, i16 u0x1234, i16 u0x5667, i16 u0xbebe], [4 x i16]* %1
+
+    ; [0x06, 0x00, 0x07, 0x00, 0xcb, 0xfe, 0x43, 0x65]
+    store [4 x i16] [i16 6, i16 7, i16 u0xfecb, i16 u0x6543], [4 x i16]* %2
+
+    ; setup symbolic len, but constrain to [3, 4].
+    %len = call i32 @symbolic_range(i32 3, i32 4)
+
+    %src = bitcast [4 x i16]* %1 to i8*
+    %dst = bitcast [4 x i16]* %2 to i8*
+    ; copy over 3 or 4 elements.
+    call void @llvm.memcpy.p0i8.p0i8.i32(i8* %dst, i8* %src, i32 %len, i1 0)
+
+    ; overwrite last two i16 in dest with len.
+    %el_addr = getelementptr inbounds [4 x i16], [4 x i16]* %2, i64 0, i32 2
+    %el_addr_i32 = bitcast i16* %el_addr to i32*
+    store i32 %len, i32* %el_addr_i32
+
+    %ret = load [4 x i16], [4 x i16]* %2
+    ret [4 x i16] %ret
+    ; for len := 3
+    ; expect [0xcd, 0xab, 0x34, 0x00, 0x03, 0x00, 0x00, 0x00]
+    ;   -> 0x000000030034abcd
+    ; for len := 4
+    ; expect [0xcd, 0xab, 0x34, 0x12, 0x04, 0x00, 0x00, 0x00]
+    ;   -> 0x000000041234abcd
+}
+
 ; memmove
 declare void @llvm.memmove.p0i8.p0i8.i32(i8* %dst, i8* %src, i32 %len, i1 %isvolatile)
 
@@ -144,6 +177,56 @@ define dso_local [8 x i8] @test_memset() #0 {
     ret [8 x i8] %3 ; expect 0xababababcbcbcbcb
 }
 
+define dso_local [8 x i8] @test_memset_symbolic_len() #0 {
+    %1 = alloca [8 x i8], align 4
+    %2 = bitcast [8 x i8]* %1 to i8*
+    call void @llvm.memset.p0i8.i32(i8* %2, i8 u0xab, i32 8, i1 0)
+
+    ; setup symbolic len, but constrain to [3, 4].
+    %len = call i32 @symbolic_range(i32 3, i32 4)
+    call void @llvm.memset.p0i8.i32(i8* %2, i8 u0xcb, i32 %len, i1 0)
+
+    ; overwrite the last four bytes with len.
+    %el_addr = getelementptr inbounds [8 x i8], [8 x i8]* %1, i64 0, i32 4
+    %el_addr_i32 = bitcast i8* %el_addr to i32*
+    store i32 %len, i32* %el_addr_i32
+
+    %3 = load [8 x i8], [8 x i8]* %1
+    ret [8 x i8] %3
+    ; for len := 3
+    ; expect 0x00000003abcbcbcb
+    ; for len := 4
+    ; expect 0x00000004cbcbcbcb
+}
+
+define dso_local void @test_memset_symbolic_len_short() #0 {
+    %1 = alloca [2 x i8], align 4
+    %2 = bitcast [2 x i8]* %1 to i8*
+
+    ; setup symbolic len, but constrain to [1, 4], past the end of the buffer.
+    %len = call i32 @symbolic_range(i32 1, i32 4)
+    call void @llvm.memset.p0i8.i32(i8* %2, i8 u0xcb, i32 %len, i1 0)
+    ret void
+    ; expect success for len := 1, 2
+    ; expect out of bounds for len := 3, 4
+}
+
+define dso_local void @test_memcpy_symbolic_len_short() #0 {
+    %1 = alloca [2 x i8], align 4
+    %2 = alloca [8 x i8], align 4
+    store [2 x i8] [i8 u0xcd, i8 u0xab], [2 x i8]* %1
+
+    ; setup symbolic len, but constrain to [1, 4], past the end of the source.
+    %len = call i32 @symbolic_range(i32 1, i32 4)
+
+    %src = bitcast [2 x i8]* %1 to i8*
+    %dst = bitcast [8 x i8]* %2 to i8*
+    call void @llvm.memcpy.p0i8.p0i8.i32(i8* %dst, i8* %src, i32 %len, i1 0)
+    ret void
+    ; expect success for len := 1, 2
+    ; expect out of bounds for len := 3, 4
+}
+
 ; umax
 declare i32 @llvm.umax.i32(i32, i32)
 declare <2 x i32> @llvm.umax.v2i32(<2 x i32>, <2 x i32>)
@@ -164,6 +247,56 @@ define dso_local <2 x i32> @test_umax_vec() #0 {
     ;   -> 0x000043210000bcef
 }
 
+; umin, smax, smin
+declare i32 @llvm.umin.i32(i32, i32)
+declare <2 x i8> @llvm.smax.v2i8(<2 x i8>, <2 x i8>)
+declare i8 @llvm.smin.i8(i8, i8)
+
+define dso_local i32 @test_umin() #0 {
+    %1 = call i32 @llvm.umin.i32(i32 u0xabcd, i32 u0xbcef)
+    ret i32 %1 ; expect 0xabcd
+}
+
+define dso_local <2 x i8> @test_smax_vec() #0 {
+    %1 = call <2 x i8> @llvm.smax.v2i8(<2 x i8> <i8 -5, i8 3>, <2 x i8> <i8 2, i8 -1>)
+    ret <2 x i8> %1 ; expect <2, 3> -> 0x0302
+}
+
+define dso_local i8 @test_smin() #0 {
+    %1 = call i8 @llvm.smin.i8(i8 -5, i8 2)
+    ret i8 %1 ; expect -5 -> 0xfb
+}
+
+
+; --------------------------------------------------------------------------------------------------
+; Vector reduction intrinsics
+; --------------------------------------------------------------------------------------------------
+
+declare i32 @llvm.vector.reduce.add.v4i32(<4 x i32>)
+declare i8 @llvm.vector.reduce.xor.v4i8(<4 x i8>)
+declare i16 @llvm.vector.reduce.umax.v3i16(<3 x i16>)
+declare i16 @llvm.vector.reduce.smin.v3i16(<3 x i16>)
+
+define dso_local i32 @test_vector_reduce_add() #0 {
+    %1 = call i32 @llvm.vector.reduce.add.v4i32(<4 x i32> <i32 1, i32 2, i32 3, i32 4>)
+    ret i32 %1 ; expect 10
+}
+
+define dso_local i8 @test_vector_reduce_xor() #0 {
+    %1 = call i8 @llvm.vector.reduce.xor.v4i8(<4 x i8> <i8 1, i8 2, i8 4, i8 3>)
+    ret i8 %1 ; expect 4
+}
+
+define dso_local i16 @test_vector_reduce_umax() #0 {
+    %1 = call i16 @llvm.vector.reduce.umax.v3i16(<3 x i16> <i16 3, i16 -1, i16 7>)
+    ret i16 %1 ; expect 0xffff
+}
+
+define dso_local i16 @test_vector_reduce_smin() #0 {
+    %1 = call i16 @llvm.vector.reduce.smin.v3i16(<3 x i16> <i16 3, i16 -1, i16 7>)
+    ret i16 %1 ; expect -1 -> 0xffff
+}
+
 
 ; --------------------------------------------------------------------------------------------------
 ; Arithmetic with overflow intrinsics
@@ -344,6 +477,38 @@ define dso_local i32 @test_assume() #0 {
     ret i32 %val ; expect 5
 }
 
+declare i8* @llvm.stacksave()
+declare void @llvm.stackrestore(i8* %ptr)
+
+; Allocations made before the stack is saved are still valid after it is restored.
+define dso_local i32 @test_stackrestore() #0 {
+    %a = alloca i32
+    store i32 2, i32* %a
+    %sp = call i8* @llvm.stacksave()
+    %b = alloca i32
+    store i32 1, i32* %b
+    call void @llvm.stackrestore(i8* %sp)
+    %1 = load i32, i32* %a
+    ret i32 %1 ; expect 2
+}
+
+; Allocations made after the stack is saved are freed when it is restored.
+define dso_local i32 @test_stackrestore_freed() #0 {
+    %sp = call i8* @llvm.stacksave()
+    %a = alloca i32
+    store i32 1, i32* %a
+    call void @llvm.stackrestore(i8* %sp)
+    %1 = load i32, i32* %a
+    ret i32 %1 ; expect use after free
+}
+
+declare i64 @llvm.vscale.i64()
+
+define dso_local i64 @test_vscale() #0 {
+    %1 = call i64 @llvm.vscale.i64()
+    ret i64 %1 ; expect 1
+}
+
 
 declare void @assume(i32) #1
 
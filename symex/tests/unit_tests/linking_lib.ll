@@ -0,0 +1,22 @@
+; source_filename = ""
+target datalayout = "e-m:e-p270:32:32-p271:32:32-p272:64:64-i64:64-f80:128-n8:16:32:64-S128"
+target triple = "x86_64-pc-linux-gnu"
+
+; Linked together with linking_main.ll.
+
+@lib_counter = global i32 7
+
+define i32 @clash() {
+    ret i32 2
+}
+
+define internal i32 @helper() {
+    ret i32 10
+}
+
+define i32 @lib_add(i32 %a, i32 %b) {
+    %1 = add i32 %a, %b
+    %2 = call i32 @helper()
+    %3 = add i32 %1, %2
+    ret i32 %3
+}
@@ -0,0 +1,39 @@
+; source_filename = ""
+target datalayout = "e-m:e-p270:32:32-p271:32:32-p272:64:64-i64:64-f80:128-n8:16:32:64-S128"
+target triple = "x86_64-pc-linux-gnu"
+
+; Linked together with linking_lib.ll.
+
+@lib_counter = external global i32
+
+declare i32 @lib_add(i32, i32)
+
+; Defined in both modules, the definition in this module should be used.
+define i32 @clash() {
+    ret i32 1
+}
+
+; Internal in both modules, the linker should rename one of them.
+define internal i32 @helper() {
+    ret i32 100
+}
+
+define i32 @test_link_call() {
+    %1 = call i32 @lib_add(i32 2, i32 3)
+    ret i32 %1 ; expect 2 + 3 + 10 = 15
+}
+
+define i32 @test_link_global() {
+    %1 = load i32, ptr @lib_counter
+    ret i32 %1 ; expect 7
+}
+
+define i32 @test_link_clash() {
+    %1 = call i32 @clash()
+    ret i32 %1 ; expect 1
+}
+
+define i32 @test_link_internal() {
+    %1 = call i32 @helper()
+    ret i32 %1 ; expect 100
+}
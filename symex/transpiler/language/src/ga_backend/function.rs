@@ -124,7 +124,7 @@ impl Compile for Register {
                 .unwrap()
                 .to_string(),
         );
-        Ok(quote!(Operand::Register(#name.to_owned())))
+        Ok(quote!(Operand::Register(const { REGISTERS.named(#name) })))
     }
 }
 
@@ -143,7 +143,7 @@ impl Compile for Flag {
                 .unwrap()
                 .to_string(),
         );
-        Ok(quote!(Operand::Flag(#name.to_owned())))
+        Ok(quote!(Operand::Flag(const { REGISTERS.named(#name) })))
     }
 }
 
@@ -9,13 +9,18 @@ use syn::parse_macro_input;
 #[proc_macro]
 /// Extends or creates a vector of [`general_assembly`] operations.
 ///
+/// Registers and flags named in the pseudo code are resolved at compile time
+/// in the `REGISTERS` set that is in scope where the macro is used.
+///
 /// Usage:
 /// ```
-/// use general_assembly::{operation::Operation,operand::Operand,condition::Condition};
+/// use general_assembly::{operation::Operation,operand::Operand,condition::Condition,register::RegisterSet};
 /// use transpiler::pseudo;
 ///
-/// let a = Operand::Register("a".to_owned());
-/// let b = Operand::Register("b".to_owned());
+/// const REGISTERS: RegisterSet = RegisterSet::new(&["R0", "R1"]);
+///
+/// let a = Operand::Register(REGISTERS.named("R0"));
+/// let b = Operand::Register(REGISTERS.named("R1"));
 /// let c = Operand::Local("c".to_owned());
 /// let cond = false;
 /// let ret = pseudo!([
@@ -1,7 +1,10 @@
 //! Defines a simple field extraction example example
 use general_assembly::{condition::Condition, operand::Operand, operand::DataWord, operation::Operation};
+use general_assembly::register::RegisterSet;
 use transpiler::pseudo;
 
+const REGISTERS: RegisterSet = RegisterSet::new(&["R0", "R1"]);
+
 pub trait LocalInto<T> {
     fn local_into(self) -> T;
 }
@@ -13,8 +16,8 @@ impl LocalInto<Operand>  for u32 {
 }
 
 fn main() {
-    let a = Operand::Register("a".to_owned());
-    let b = Operand::Register("b".to_owned());
+    let a = Operand::Register(REGISTERS.named("R0"));
+    let b = Operand::Register(REGISTERS.named("R1"));
     let c = Operand::Local("c".to_owned());
     let regs = [a,b];
     let cond = false;
@@ -1,10 +1,17 @@
 //! Defines a simple if statement example
-use general_assembly::{condition::Condition, operand::Operand, operation::Operation};
+use general_assembly::{
+    condition::Condition,
+    operand::Operand,
+    operation::Operation,
+    register::RegisterSet,
+};
 use transpiler::pseudo;
 
+const REGISTERS: RegisterSet = RegisterSet::new(&["R0", "R1"]);
+
 fn main() {
-    let a = Operand::Register("a".to_owned());
-    let b = Operand::Register("b".to_owned());
+    let a = Operand::Register(REGISTERS.named("R0"));
+    let b = Operand::Register(REGISTERS.named("R1"));
     let c = Operand::Local("c".to_owned());
     let cond = false;
     let _ret = pseudo!([
@@ -1,4 +1,4 @@
-use symex::run_elf::run_elf;
+use symex::run_elf::{run_elf, ElfConfig, Entry};
 
 fn main() {
     println!("Simple WCET analasis");
@@ -6,7 +6,12 @@ fn main() {
     let path_to_elf_file = "target/thumbv6m-none-eabi/release/examples/rtic_simple_resourse";
     let function_name = "IO_IRQ_BANK0";
 
-    let results = run_elf(path_to_elf_file, function_name, false).unwrap();
+    let results = run_elf(
+        path_to_elf_file,
+        Entry::Function(function_name),
+        ElfConfig::default(),
+    )
+    .unwrap();
 
     let mut max = 0;
     let paths = results.len();
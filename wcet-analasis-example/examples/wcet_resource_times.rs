@@ -1,6 +1,7 @@
 use symex::{
     general_assembly::{
         arch::arm::v6::ArmV6M, project::MemoryHookAddress, state::GAState, Result, RunConfig,
+        DEFAULT_MAX_BRANCH_TARGETS,
     },
     run_elf::{run_elf, run_elf_configured},
     smt::DExpr,
@@ -108,6 +109,10 @@ fn main() {
         ],
         memory_read_hooks: vec![],
         show_path_results: false,
+        target: None,
+        merge_paths: false,
+        max_branch_targets: DEFAULT_MAX_BRANCH_TARGETS,
+        trace: None,
     };
 
     // run the symbolic execution